@@ -21,10 +21,13 @@ use agent_client_protocol::{self as acp};
 use anyhow::{Context as _, Result, anyhow};
 use editor::Bias;
 use futures::{FutureExt, channel::oneshot, future::BoxFuture};
-use gpui::{AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Task, WeakEntity};
+use gpui::{
+    AppContext, AsyncApp, Context, Entity, EventEmitter, Image, SharedString, Task, WeakEntity,
+};
 use itertools::Itertools;
 use language::{Anchor, Buffer, BufferSnapshot, LanguageRegistry, Point, ToPoint, text_diff};
 use markdown::Markdown;
+use midi::MidiPattern;
 use project::{AgentLocation, Project, git_store::GitStoreCheckpoint};
 use std::collections::HashMap;
 use std::error::Error;
@@ -300,6 +303,9 @@ impl ToolCall {
             ToolCallContent::Diff(diff) => Some(diff),
             ToolCallContent::ContentBlock(_) => None,
             ToolCallContent::Terminal(_) => None,
+            ToolCallContent::PianoRoll(_) => None,
+            ToolCallContent::Subagent(_) => None,
+            ToolCallContent::Image(_) => None,
         })
     }
 
@@ -308,6 +314,42 @@ impl ToolCall {
             ToolCallContent::Terminal(terminal) => Some(terminal),
             ToolCallContent::ContentBlock(_) => None,
             ToolCallContent::Diff(_) => None,
+            ToolCallContent::PianoRoll(_) => None,
+            ToolCallContent::Subagent(_) => None,
+            ToolCallContent::Image(_) => None,
+        })
+    }
+
+    pub fn piano_rolls(&self) -> impl Iterator<Item = &Entity<MidiPattern>> {
+        self.content.iter().filter_map(|content| match content {
+            ToolCallContent::PianoRoll(pattern) => Some(pattern),
+            ToolCallContent::ContentBlock(_) => None,
+            ToolCallContent::Diff(_) => None,
+            ToolCallContent::Terminal(_) => None,
+            ToolCallContent::Subagent(_) => None,
+            ToolCallContent::Image(_) => None,
+        })
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = &Entity<Image>> {
+        self.content.iter().filter_map(|content| match content {
+            ToolCallContent::Image(image) => Some(image),
+            ToolCallContent::ContentBlock(_) => None,
+            ToolCallContent::Diff(_) => None,
+            ToolCallContent::Terminal(_) => None,
+            ToolCallContent::PianoRoll(_) => None,
+            ToolCallContent::Subagent(_) => None,
+        })
+    }
+
+    pub fn subagents(&self) -> impl Iterator<Item = &Entity<AcpThread>> {
+        self.content.iter().filter_map(|content| match content {
+            ToolCallContent::Subagent(thread) => Some(thread),
+            ToolCallContent::ContentBlock(_) => None,
+            ToolCallContent::Diff(_) => None,
+            ToolCallContent::Terminal(_) => None,
+            ToolCallContent::PianoRoll(_) => None,
+            ToolCallContent::Image(_) => None,
         })
     }
 
@@ -512,7 +554,8 @@ impl ContentBlock {
                 ..
             }) => Self::resource_link_md(&uri),
             acp::ContentBlock::Image(image) => Self::image_md(&image),
-            acp::ContentBlock::Audio(_) | acp::ContentBlock::Resource(_) => String::new(),
+            acp::ContentBlock::Audio(audio) => Self::audio_md(&audio),
+            acp::ContentBlock::Resource(_) => String::new(),
         }
     }
 
@@ -528,6 +571,10 @@ impl ContentBlock {
         "`Image`".into()
     }
 
+    fn audio_md(_audio: &acp::AudioContent) -> String {
+        "`Audio`".into()
+    }
+
     pub fn to_markdown<'a>(&'a self, cx: &'a App) -> &'a str {
         match self {
             ContentBlock::Empty => "",
@@ -557,6 +604,12 @@ pub enum ToolCallContent {
     ContentBlock(ContentBlock),
     Diff(Entity<Diff>),
     Terminal(Entity<Terminal>),
+    /// A MIDI pattern generated by a tool, previewed as a piano roll.
+    PianoRoll(Entity<MidiPattern>),
+    /// A child thread that a tool delegated a task to.
+    Subagent(Entity<AcpThread>),
+    /// An image generated by a tool.
+    Image(Entity<Image>),
 }
 
 impl ToolCallContent {
@@ -618,6 +671,17 @@ impl ToolCallContent {
             Self::ContentBlock(content) => content.to_markdown(cx).to_string(),
             Self::Diff(diff) => diff.read(cx).to_markdown(cx),
             Self::Terminal(terminal) => terminal.read(cx).to_markdown(cx),
+            Self::PianoRoll(pattern) => {
+                let pattern = pattern.read(cx);
+                format!(
+                    "MIDI pattern with {} notes over {} bars at {} BPM\n\n",
+                    pattern.notes.len(),
+                    pattern.bars,
+                    pattern.bpm
+                )
+            }
+            Self::Subagent(thread) => thread.read(cx).to_markdown(cx),
+            Self::Image(image) => format!("Image ({} bytes)\n\n", image.read(cx).bytes.len()),
         }
     }
 }
@@ -627,6 +691,9 @@ pub enum ToolCallUpdate {
     UpdateFields(acp::ToolCallUpdate),
     UpdateDiff(ToolCallUpdateDiff),
     UpdateTerminal(ToolCallUpdateTerminal),
+    UpdatePianoRoll(ToolCallUpdatePianoRoll),
+    UpdateSubagent(ToolCallUpdateSubagent),
+    UpdateImage(ToolCallUpdateImage),
 }
 
 impl ToolCallUpdate {
@@ -635,6 +702,9 @@ impl ToolCallUpdate {
             Self::UpdateFields(update) => &update.id,
             Self::UpdateDiff(diff) => &diff.id,
             Self::UpdateTerminal(terminal) => &terminal.id,
+            Self::UpdatePianoRoll(piano_roll) => &piano_roll.id,
+            Self::UpdateSubagent(subagent) => &subagent.id,
+            Self::UpdateImage(image) => &image.id,
         }
     }
 }
@@ -669,6 +739,42 @@ pub struct ToolCallUpdateTerminal {
     pub terminal: Entity<Terminal>,
 }
 
+impl From<ToolCallUpdatePianoRoll> for ToolCallUpdate {
+    fn from(piano_roll: ToolCallUpdatePianoRoll) -> Self {
+        Self::UpdatePianoRoll(piano_roll)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ToolCallUpdatePianoRoll {
+    pub id: acp::ToolCallId,
+    pub pattern: Entity<MidiPattern>,
+}
+
+impl From<ToolCallUpdateSubagent> for ToolCallUpdate {
+    fn from(subagent: ToolCallUpdateSubagent) -> Self {
+        Self::UpdateSubagent(subagent)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ToolCallUpdateSubagent {
+    pub id: acp::ToolCallId,
+    pub thread: Entity<AcpThread>,
+}
+
+impl From<ToolCallUpdateImage> for ToolCallUpdate {
+    fn from(image: ToolCallUpdateImage) -> Self {
+        Self::UpdateImage(image)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ToolCallUpdateImage {
+    pub id: acp::ToolCallId,
+    pub image: Entity<Image>,
+}
+
 #[derive(Debug, Default)]
 pub struct Plan {
     pub entries: Vec<PlanEntry>,
@@ -1284,6 +1390,19 @@ impl AcpThread {
                 call.content
                     .push(ToolCallContent::Terminal(update.terminal));
             }
+            ToolCallUpdate::UpdatePianoRoll(update) => {
+                call.content.clear();
+                call.content
+                    .push(ToolCallContent::PianoRoll(update.pattern));
+            }
+            ToolCallUpdate::UpdateSubagent(update) => {
+                call.content.clear();
+                call.content.push(ToolCallContent::Subagent(update.thread));
+            }
+            ToolCallUpdate::UpdateImage(update) => {
+                call.content.clear();
+                call.content.push(ToolCallContent::Image(update.image));
+            }
         }
 
         cx.emit(AcpThreadEvent::EntryUpdated(ix));
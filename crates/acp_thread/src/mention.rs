@@ -330,6 +330,18 @@ pub fn selection_name(path: Option<&Path>, line_range: &RangeInclusive<u32>) ->
     )
 }
 
+/// The `_meta` of an attached audio clip's content block, carrying an automated
+/// analysis of the clip. The native agent keeps the analysis in the thread in
+/// place of the clip once the model has been sent it.
+pub fn audio_analysis_meta(analysis: &str) -> serde_json::Value {
+    serde_json::json!({ "analysis": analysis })
+}
+
+/// The analysis attached to an audio clip with [`audio_analysis_meta`].
+pub fn audio_analysis(audio: &acp::AudioContent) -> Option<&str> {
+    audio.meta.as_ref()?.get("analysis")?.as_str()
+}
+
 #[cfg(test)]
 mod tests {
     use util::{path, uri};
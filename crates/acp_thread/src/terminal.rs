@@ -14,6 +14,10 @@ pub struct Terminal {
     started_at: Instant,
     output: Option<TerminalOutput>,
     output_byte_limit: Option<usize>,
+    /// How many bytes of output scrolled out of the terminal's history, and
+    /// the output as of the last time that was checked.
+    trimmed_output_len: usize,
+    last_retained_output: String,
     _output_task: Shared<Task<acp::TerminalExitStatus>>,
 }
 
@@ -51,6 +55,8 @@ impl Terminal {
             started_at: Instant::now(),
             output: None,
             output_byte_limit,
+            trimmed_output_len: 0,
+            last_retained_output: String::new(),
             _output_task: cx
                 .spawn(async move |this, cx| {
                     let exit_status = command_task.await;
@@ -96,6 +102,12 @@ impl Terminal {
         });
     }
 
+    pub fn write_input(&mut self, input: String, cx: &mut App) {
+        self.terminal.update(cx, |terminal, _cx| {
+            terminal.input(input.into_bytes());
+        });
+    }
+
     pub fn current_output(&self, cx: &App) -> acp::TerminalOutputResponse {
         if let Some(output) = self.output.as_ref() {
             let exit_status = output.exit_status.map(portable_pty::ExitStatus::from);
@@ -122,6 +134,20 @@ impl Terminal {
         }
     }
 
+    /// Returns the output that's still in the terminal's history, along with
+    /// the position it starts at in all the output the command wrote. That
+    /// position only grows, so callers can keep track of what they already
+    /// read after older output scrolled out of the history.
+    pub fn retained_output(&mut self, cx: &App) -> (String, usize) {
+        let terminal = self.terminal.read(cx);
+        let content = terminal.get_content();
+        if terminal.history_is_full() {
+            self.trimmed_output_len += trimmed_len(&self.last_retained_output, &content);
+        }
+        self.last_retained_output = content.clone();
+        (content, self.trimmed_output_len)
+    }
+
     fn truncated_output(&self, cx: &App) -> (String, usize) {
         let terminal = self.terminal.read(cx);
         let mut content = terminal.get_content();
@@ -170,3 +196,43 @@ impl Terminal {
         )
     }
 }
+
+/// How many bytes at the start of `previous` scrolled out of the terminal's
+/// history by the time its output was `current`.
+///
+/// The terminal drops whole lines from the top, so this looks for the line of
+/// `previous` that `current` starts with. The last line isn't compared, since
+/// it may have been rewritten since, as progress bars do.
+fn trimmed_len(previous: &str, current: &str) -> usize {
+    let last_line_start = previous
+        .trim_end_matches('\n')
+        .rfind('\n')
+        .map_or(0, |ix| ix + 1);
+    std::iter::once(0)
+        .chain(previous.match_indices('\n').map(|(ix, _)| ix + 1))
+        .take_while(|&line_start| line_start < last_line_start)
+        .find(|&line_start| {
+            previous[line_start..last_line_start]
+                .split_inclusive('\n')
+                .zip(current.split_inclusive('\n'))
+                .take(3)
+                .all(|(previous_line, current_line)| previous_line == current_line)
+        })
+        .unwrap_or(previous.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trimmed_len() {
+        assert_eq!(trimmed_len("", "a\nb\n"), 0);
+        assert_eq!(trimmed_len("a\nb\n", "a\nb\nc\n"), 0);
+        assert_eq!(trimmed_len("a\nb\nc\nd\n", "b\nc\nd\ne\n"), 2);
+        assert_eq!(trimmed_len("a\nb\nc\nd\n", "c\nd\nx\n"), 4);
+        assert_eq!(trimmed_len("a\nb\n50%", "b\n90%\n"), 2);
+        // None of the previous output is left.
+        assert_eq!(trimmed_len("a\nb\n", "x\ny\n"), 4);
+    }
+}
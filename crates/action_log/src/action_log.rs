@@ -5,7 +5,7 @@ use collections::BTreeMap;
 use futures::{FutureExt, StreamExt, channel::mpsc};
 use gpui::{App, AppContext, AsyncApp, Context, Entity, Subscription, Task, WeakEntity};
 use language::{Anchor, Buffer, BufferEvent, DiskState, Point, ToPoint};
-use project::{Project, ProjectItem, lsp_store::OpenLspBufferHandle};
+use project::{Project, ProjectItem, ProjectPath, lsp_store::OpenLspBufferHandle};
 use std::{cmp, ops::Range, sync::Arc};
 use text::{Edit, Patch, Rope};
 use util::{RangeExt, ResultExt as _};
@@ -14,6 +14,9 @@ use util::{RangeExt, ResultExt as _};
 pub struct ActionLog {
     /// Buffers that we want to notify the model about when they change.
     tracked_buffers: BTreeMap<Entity<Buffer>, TrackedBuffer>,
+    /// Files that tools wrote directly to disk instead of through a buffer,
+    /// such as binary files, along with what they contained before.
+    tracked_files: BTreeMap<ProjectPath, TrackedFile>,
     /// The project this action log is associated with
     project: Entity<Project>,
 }
@@ -23,6 +26,7 @@ impl ActionLog {
     pub fn new(project: Entity<Project>) -> Self {
         Self {
             tracked_buffers: BTreeMap::default(),
+            tracked_files: BTreeMap::default(),
             project,
         }
     }
@@ -530,6 +534,25 @@ impl ActionLog {
         self.track_buffer_internal(buffer, false, cx);
     }
 
+    /// Track a buffer as read by agent at an earlier `version`, so that the
+    /// edits made since then are reviewed as the agent's. This is for edits
+    /// applied before the agent could track the buffer, like a language
+    /// server's rename.
+    pub fn buffer_read_at_version(
+        &mut self,
+        buffer: Entity<Buffer>,
+        version: &clock::Global,
+        cx: &mut Context<Self>,
+    ) {
+        if self.tracked_buffers.contains_key(&buffer) {
+            return;
+        }
+        let base = buffer.read(cx).rope_for_version(version);
+        let tracked_buffer = self.track_buffer_internal(buffer, false, cx);
+        tracked_buffer.diff_base = base.clone();
+        tracked_buffer.last_seen_base = base;
+    }
+
     /// Mark a buffer as created by agent, so we can refresh it in the context
     pub fn buffer_created(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
         self.track_buffer_internal(buffer, true, cx);
@@ -761,7 +784,63 @@ impl ActionLog {
         }
     }
 
+    /// Marks a file that was written directly to disk as changed by the
+    /// agent. `previous_content` is what the file contained before, or `None`
+    /// if the file was created.
+    pub fn file_written(
+        &mut self,
+        project_path: ProjectPath,
+        previous_content: Option<Vec<u8>>,
+        cx: &mut Context<Self>,
+    ) {
+        // Rejecting should restore the file to how it was before the agent
+        // first touched it, so later writes keep the original content.
+        self.tracked_files
+            .entry(project_path)
+            .or_insert(TrackedFile { previous_content });
+        cx.notify();
+    }
+
+    pub fn keep_file(&mut self, project_path: &ProjectPath, cx: &mut Context<Self>) {
+        if self.tracked_files.remove(project_path).is_some() {
+            cx.notify();
+        }
+    }
+
+    /// Restores a file written directly to disk to its previous content,
+    /// deleting it if the agent created it.
+    pub fn reject_file(
+        &mut self,
+        project_path: ProjectPath,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(tracked_file) = self.tracked_files.remove(&project_path) else {
+            return Task::ready(Ok(()));
+        };
+        cx.notify();
+
+        let project = self.project.read(cx);
+        match tracked_file.previous_content {
+            Some(previous_content) => {
+                let fs = project.fs().clone();
+                let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+                    return Task::ready(Err(anyhow::anyhow!(
+                        "worktree for {project_path:?} no longer exists"
+                    )));
+                };
+                cx.background_spawn(async move { fs.write(&abs_path, &previous_content).await })
+            }
+            None => self
+                .project
+                .update(cx, |project, cx| {
+                    project.delete_file(project_path, false, cx)
+                })
+                .unwrap_or(Task::ready(Ok(()))),
+        }
+    }
+
     pub fn keep_all_edits(&mut self, cx: &mut Context<Self>) {
+        self.tracked_files.clear();
         self.tracked_buffers
             .retain(|_buffer, tracked_buffer| match tracked_buffer.status {
                 TrackedBufferStatus::Deleted => false,
@@ -779,18 +858,21 @@ impl ActionLog {
     }
 
     pub fn reject_all_edits(&mut self, cx: &mut Context<Self>) -> Task<()> {
-        let futures = self.changed_buffers(cx).into_keys().map(|buffer| {
-            let reject = self.reject_edits_in_ranges(buffer, vec![Anchor::MIN..Anchor::MAX], cx);
-
-            async move {
-                reject.await.log_err();
-            }
-        });
+        let mut rejects = self
+            .changed_buffers(cx)
+            .into_keys()
+            .map(|buffer| self.reject_edits_in_ranges(buffer, vec![Anchor::MIN..Anchor::MAX], cx))
+            .collect::<Vec<_>>();
+        for project_path in self.changed_files().collect::<Vec<_>>() {
+            rejects.push(self.reject_file(project_path, cx));
+        }
 
-        let task = futures::future::join_all(futures);
+        let task = futures::future::join_all(rejects);
 
         cx.spawn(async move |_, _| {
-            task.await;
+            for result in task.await {
+                result.log_err();
+            }
         })
     }
 
@@ -803,6 +885,11 @@ impl ActionLog {
             .collect()
     }
 
+    /// Returns the files written directly to disk that haven't been reviewed by the user.
+    pub fn changed_files(&self) -> impl Iterator<Item = ProjectPath> + '_ {
+        self.tracked_files.keys().cloned()
+    }
+
     /// Iterate over buffers changed since last read or edited by the model
     pub fn stale_buffers<'a>(&'a self, cx: &'a App) -> impl Iterator<Item = &'a Entity<Buffer>> {
         self.tracked_buffers
@@ -946,6 +1033,10 @@ enum TrackedBufferStatus {
     Deleted,
 }
 
+struct TrackedFile {
+    previous_content: Option<Vec<u8>>,
+}
+
 struct TrackedBuffer {
     buffer: Entity<Buffer>,
     diff_base: Rope,
@@ -1088,6 +1179,67 @@ mod tests {
         assert_eq!(unreviewed_hunks(&action_log, cx), vec![]);
     }
 
+    #[gpui::test(iterations = 10)]
+    async fn test_buffer_read_at_version(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({"file": "abc\ndef\nghi"}))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let file_path = project
+            .read_with(cx, |project, cx| project.find_project_path("dir/file", cx))
+            .unwrap();
+        let buffer = project
+            .update(cx, |project, cx| project.open_buffer(file_path, cx))
+            .await
+            .unwrap();
+
+        // The buffer is edited before the action log starts tracking it.
+        let version = buffer.read_with(cx, |buffer, _| buffer.version());
+        buffer.update(cx, |buffer, cx| {
+            buffer
+                .edit([(Point::new(1, 0)..Point::new(1, 3), "DEF")], None, cx)
+                .unwrap()
+        });
+        cx.update(|cx| {
+            action_log.update(cx, |log, cx| {
+                log.buffer_read_at_version(buffer.clone(), &version, cx);
+                log.buffer_edited(buffer.clone(), cx);
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            unreviewed_hunks(&action_log, cx),
+            vec![(
+                buffer.clone(),
+                vec![HunkStatus {
+                    range: Point::new(1, 0)..Point::new(2, 0),
+                    diff_status: DiffHunkStatusKind::Modified,
+                    old_text: "def\n".into(),
+                }],
+            )]
+        );
+
+        action_log
+            .update(cx, |log, cx| {
+                log.reject_edits_in_ranges(
+                    buffer.clone(),
+                    vec![Point::new(1, 0)..Point::new(1, 0)],
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "abc\ndef\nghi"
+        );
+        assert_eq!(unreviewed_hunks(&action_log, cx), vec![]);
+    }
+
     #[gpui::test(iterations = 10)]
     async fn test_deletions(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2003,6 +2155,59 @@ mod tests {
         assert_eq!(unreviewed_hunks(&action_log, cx), vec![]);
     }
 
+    #[gpui::test]
+    async fn test_reject_written_files(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({"existing.mid": "old"}))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let (existing_path, new_path) = project.read_with(cx, |project, cx| {
+            (
+                project.find_project_path("dir/existing.mid", cx).unwrap(),
+                project.find_project_path("dir/new.mid", cx).unwrap(),
+            )
+        });
+
+        fs.write(path!("/dir/existing.mid").as_ref(), b"MThd1")
+            .await
+            .unwrap();
+        fs.write(path!("/dir/new.mid").as_ref(), b"MThd2")
+            .await
+            .unwrap();
+        action_log.update(cx, |log, cx| {
+            log.file_written(existing_path.clone(), Some(b"old".to_vec()), cx);
+            log.file_written(new_path.clone(), None, cx);
+        });
+        // Writing a file again keeps the content it had before the first write.
+        action_log.update(cx, |log, cx| {
+            log.file_written(existing_path.clone(), Some(b"MThd1".to_vec()), cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            action_log.read_with(cx, |log, _| log.changed_files().collect::<Vec<_>>()),
+            vec![existing_path.clone(), new_path.clone()]
+        );
+
+        action_log
+            .update(cx, |log, cx| log.reject_all_edits(cx))
+            .await;
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load_bytes(path!("/dir/existing.mid").as_ref())
+                .await
+                .unwrap(),
+            b"old"
+        );
+        assert!(!fs.is_file(path!("/dir/new.mid").as_ref()).await);
+        assert_eq!(
+            action_log.read_with(cx, |log, _| log.changed_files().count()),
+            0
+        );
+    }
+
     #[gpui::test]
     async fn test_reject_created_file_with_user_edits(cx: &mut TestAppContext) {
         init_test(cx);
@@ -419,16 +419,21 @@ impl ActivityIndicator {
             });
         }
 
-        let current_job = self
+        let active_repository = self
             .project
             .read(cx)
             .active_repository(cx)
-            .map(|r| r.read(cx))
-            .and_then(Repository::current_job);
-        // Show any long-running git command
+            .map(|r| r.read(cx));
+        let current_job = active_repository.and_then(Repository::current_job);
+        // Show any long-running git command, along with the progress of any
+        // Git LFS transfer it's making.
         if let Some(job_info) = current_job
             && Instant::now() - job_info.start >= GIT_OPERATION_DELAY
         {
+            let message = match active_repository.and_then(Repository::lfs_transfer) {
+                Some(lfs_transfer) => lfs_transfer.to_string(),
+                None => job_info.message.into(),
+            };
             return Some(Content {
                 icon: Some(
                     Icon::new(IconName::ArrowCircle)
@@ -436,7 +441,7 @@ impl ActivityIndicator {
                         .with_rotate_animation(2)
                         .into_any_element(),
                 ),
-                message: job_info.message.into(),
+                message,
                 on_click: None,
                 tooltip_message: None,
             });
@@ -45,8 +45,14 @@ impl AgentProfile {
                 .map(|profile| profile.enable_all_context_servers)
                 .unwrap_or_default(),
             context_servers: base_profile
-                .map(|profile| profile.context_servers)
+                .as_ref()
+                .map(|profile| profile.context_servers.clone())
+                .unwrap_or_default(),
+            hooks: base_profile
+                .as_ref()
+                .map(|profile| profile.hooks.clone())
                 .unwrap_or_default(),
+            voice: base_profile.and_then(|profile| profile.voice),
         };
 
         update_settings_file(fs, cx, {
@@ -251,6 +257,8 @@ mod tests {
                     tools: IndexMap::default(),
                     enable_all_context_servers: false,
                     context_servers: IndexMap::from_iter([("mcp".into(), context_server_preset())]),
+                    voice: None,
+                    hooks: Vec::new(),
                 },
             );
             AgentSettings::override_global(agent_settings, cx);
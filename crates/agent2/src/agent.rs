@@ -6,6 +6,7 @@ use crate::{HistoryStore, TerminalHandle, ThreadEnvironment, TitleUpdated, Token
 use acp_thread::{AcpThread, AgentModelSelector};
 use action_log::ActionLog;
 use agent_client_protocol as acp;
+use agent_settings::{AgentProfileId, AgentSettings};
 use anyhow::{Context as _, Result, anyhow};
 use collections::{HashSet, IndexMap};
 use fs::Fs;
@@ -20,7 +21,7 @@ use project::{Project, ProjectItem, ProjectPath, Worktree};
 use prompt_store::{
     ProjectContext, PromptId, PromptStore, RulesFileContext, UserRulesContext, WorktreeContext,
 };
-use settings::{LanguageModelSelection, update_settings_file};
+use settings::{LanguageModelSelection, Settings as _, update_settings_file};
 use std::any::Any;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -305,10 +306,12 @@ impl NativeAgent {
         let registry = LanguageModelRegistry::read_global(cx);
         let summarization_model = registry.thread_summary_model().map(|c| c.model);
 
+        let agent = cx.weak_entity();
         thread_handle.update(cx, |thread, cx| {
             thread.set_summarization_model(summarization_model, cx);
             thread.add_default_tools(
                 Rc::new(AcpThreadEnvironment {
+                    agent,
                     acp_thread: acp_thread.downgrade(),
                 }) as _,
                 cx,
@@ -664,7 +667,8 @@ impl NativeAgent {
     }
 
     fn save_thread(&mut self, thread: Entity<Thread>, cx: &mut Context<Self>) {
-        if thread.read(cx).is_empty() {
+        // Subagents only live as long as the tool call that spawned them.
+        if thread.read(cx).is_empty() || thread.read(cx).is_subagent() {
             return;
         }
 
@@ -699,6 +703,52 @@ impl NativeAgentConnection {
             .map(|session| session.thread.clone())
     }
 
+    /// Switches a session's thread to the given profile and model, failing if
+    /// either of them doesn't exist.
+    pub fn configure_thread(
+        &self,
+        session_id: &acp::SessionId,
+        profile: Option<AgentProfileId>,
+        model: Option<&LanguageModelSelection>,
+        cx: &mut App,
+    ) -> Result<()> {
+        let thread = self
+            .thread(session_id, cx)
+            .with_context(|| format!("Session not found: {session_id}"))?;
+        if let Some(profile) = &profile
+            && !AgentSettings::get_global(cx).profiles.contains_key(profile)
+        {
+            return Err(anyhow!("There's no agent profile named `{profile}`"));
+        }
+        let model = match model {
+            Some(selection) => Some(
+                LanguageModelRegistry::read_global(cx)
+                    .available_models(cx)
+                    .find(|model| {
+                        model.provider_id().0.as_ref() == selection.provider.0.as_str()
+                            && model.id().0.as_ref() == selection.model.as_str()
+                    })
+                    .with_context(|| {
+                        format!(
+                            "The model `{}/{}` isn't available",
+                            selection.provider.0, selection.model
+                        )
+                    })?,
+            ),
+            None => None,
+        };
+
+        thread.update(cx, |thread, cx| {
+            if let Some(profile) = profile {
+                thread.set_profile(profile);
+            }
+            if let Some(model) = model {
+                thread.set_model(model, cx);
+            }
+        });
+        Ok(())
+    }
+
     fn run_turn(
         &self,
         session_id: acp::SessionId,
@@ -805,6 +855,9 @@ impl NativeAgentConnection {
                                     thread.update_tool_call(update, cx)
                                 })??;
                             }
+                            ThreadEvent::Plan(plan) => {
+                                acp_thread.update(cx, |thread, cx| thread.update_plan(plan, cx))?;
+                            }
                             ThreadEvent::Retry(status) => {
                                 acp_thread.update(cx, |thread, cx| {
                                     thread.update_retry_status(status, cx)
@@ -1138,6 +1191,7 @@ impl acp_thread::AgentSessionSetTitle for NativeAgentSessionSetTitle {
 }
 
 pub struct AcpThreadEnvironment {
+    agent: WeakEntity<NativeAgent>,
     acp_thread: WeakEntity<AcpThread>,
 }
 
@@ -1174,6 +1228,11 @@ impl ThreadEnvironment for AcpThreadEnvironment {
             Ok(Rc::new(handle) as _)
         })
     }
+
+    fn create_subagent(&self, thread: Entity<Thread>, cx: &mut App) -> Result<Entity<AcpThread>> {
+        self.agent
+            .update(cx, |agent, cx| agent.register_session(thread, cx))
+    }
 }
 
 pub struct AcpTerminalHandle {
@@ -1195,6 +1254,20 @@ impl TerminalHandle for AcpTerminalHandle {
         self.terminal
             .read_with(cx, |term, cx| term.current_output(cx))
     }
+
+    fn retained_output(&self, cx: &mut AsyncApp) -> Result<(String, usize)> {
+        self.terminal
+            .update(cx, |term, cx| term.retained_output(cx))
+    }
+
+    fn write_input(&self, input: String, cx: &mut AsyncApp) -> Result<()> {
+        self.terminal
+            .update(cx, |term, cx| term.write_input(input, cx))
+    }
+
+    fn kill(&self, cx: &mut AsyncApp) -> Result<()> {
+        self.terminal.update(cx, |term, cx| term.kill(cx))
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,10 @@
 mod agent;
 mod db;
+mod headless;
 mod history_store;
+mod hooks;
 mod native_agent_server;
+mod scheduler;
 mod templates;
 mod thread;
 mod tool_schema;
@@ -12,8 +15,10 @@ mod tests;
 
 pub use agent::*;
 pub use db::*;
+pub use headless::{HeadlessEvent, HeadlessRunOptions, run_headless};
 pub use history_store::*;
 pub use native_agent_server::NativeAgentServer;
+pub use scheduler::{AgentScheduler, AgentSchedulerEvent};
 pub use templates::*;
 pub use thread::*;
 pub use tools::*;
@@ -51,6 +51,8 @@ pub struct DbThread {
     pub completion_mode: Option<CompletionMode>,
     #[serde(default)]
     pub profile: Option<AgentProfileId>,
+    #[serde(default)]
+    pub plan: Vec<acp::PlanEntry>,
 }
 
 impl DbThread {
@@ -198,6 +200,7 @@ impl DbThread {
             model: thread.model,
             completion_mode: thread.completion_mode,
             profile: thread.profile,
+            plan: Vec::new(),
         })
     }
 }
@@ -0,0 +1,485 @@
+use crate::{HistoryStore, NativeAgent, NativeAgentConnection, Templates};
+use acp_thread::{
+    AcpThread, AcpThreadEvent, AgentConnection as _, AgentThreadEntry, AssistantMessageChunk,
+    ToolCallStatus,
+};
+use agent_client_protocol as acp;
+use agent_settings::AgentProfileId;
+use anyhow::{Result, anyhow};
+use fs::Fs;
+use gpui::{App, AppContext as _, AsyncApp, Entity};
+use itertools::Itertools as _;
+use project::Project;
+use prompt_store::{PromptBuilder, PromptStore};
+use serde::Serialize;
+use settings::LanguageModelSelection;
+use std::{cell::RefCell, path::Path, rc::Rc, sync::Arc};
+use util::ResultExt as _;
+
+/// What to run in a headless agent thread.
+pub struct HeadlessRunOptions {
+    pub prompt: String,
+    pub profile: Option<AgentProfileId>,
+    pub model: Option<LanguageModelSelection>,
+}
+
+/// Something that happened in a headless agent thread, reported as a line of
+/// JSON by the `agent` CLI command.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HeadlessEvent {
+    Started {
+        session_id: acp::SessionId,
+        profile: AgentProfileId,
+        model: Option<String>,
+    },
+    Message {
+        text: String,
+    },
+    Thought {
+        text: String,
+    },
+    ToolCall {
+        id: acp::ToolCallId,
+        title: String,
+        kind: acp::ToolKind,
+        input: Option<serde_json::Value>,
+    },
+    ToolCallFinished {
+        id: acp::ToolCallId,
+        status: &'static str,
+        output: String,
+    },
+    /// A tool call needed permission that the settings don't grant, so it
+    /// was rejected.
+    PermissionDenied {
+        id: acp::ToolCallId,
+        title: String,
+    },
+    Finished {
+        title: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Runs a prompt in a new native agent thread without any UI, reporting what
+/// happens through `emit`.
+///
+/// Tool calls that would ask the user for permission are rejected, so only the
+/// tools that `agent.always_allow_tool_actions` or the tool permission settings
+/// allow can run. The thread is saved to the thread history like any other.
+pub async fn run_headless(
+    project: Entity<Project>,
+    cwd: &Path,
+    options: HeadlessRunOptions,
+    fs: Arc<dyn Fs>,
+    emit: impl Fn(HeadlessEvent) + 'static,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let prompt_builder = Arc::new(PromptBuilder::new(None)?);
+    let context_store = cx
+        .update(|cx| {
+            assistant_context::ContextStore::new(
+                project.clone(),
+                prompt_builder,
+                Arc::default(),
+                cx,
+            )
+        })?
+        .await?;
+    let history = cx.new(|cx| HistoryStore::new(context_store, cx))?;
+    let prompt_store = cx.update(|cx| PromptStore::global(cx))?.await.log_err();
+    let agent = NativeAgent::new(
+        project.clone(),
+        history,
+        Templates::new(),
+        prompt_store,
+        fs,
+        cx,
+    )
+    .await?;
+    run_headless_with_agent(agent, project, cwd, options, emit, cx).await
+}
+
+async fn run_headless_with_agent(
+    agent: Entity<NativeAgent>,
+    project: Entity<Project>,
+    cwd: &Path,
+    options: HeadlessRunOptions,
+    emit: impl Fn(HeadlessEvent) + 'static,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let connection = Rc::new(NativeAgentConnection(agent));
+    let thread = cx
+        .update(|cx| connection.clone().new_thread(project, cwd, cx))?
+        .await?;
+    let started = cx.update(|cx| -> Result<_> {
+        let session_id = thread.read(cx).session_id().clone();
+        connection.configure_thread(&session_id, options.profile, options.model.as_ref(), cx)?;
+        let native_thread = connection
+            .thread(&session_id, cx)
+            .ok_or_else(|| anyhow!("Session not found: {session_id}"))?;
+        let native_thread = native_thread.read(cx);
+        Ok(HeadlessEvent::Started {
+            session_id,
+            profile: native_thread.profile().clone(),
+            model: native_thread
+                .model()
+                .map(|model| format!("{}/{}", model.provider_id().0, model.id().0)),
+        })
+    })??;
+    emit(started);
+
+    let reporter = Rc::new(RefCell::new(Reporter {
+        reported: Vec::new(),
+        refused: false,
+        emit: Box::new(emit),
+    }));
+    let _subscription = cx.update(|cx| {
+        let reporter = reporter.clone();
+        cx.subscribe(&thread, move |thread, event, cx| {
+            reporter.borrow_mut().handle_event(thread, event, cx);
+        })
+    })?;
+
+    let result = thread
+        .update(cx, |thread, cx| thread.send_raw(&options.prompt, cx))?
+        .await;
+    let title = thread.read_with(cx, |thread, cx| {
+        reporter.borrow_mut().report_entries(thread, true, cx);
+        thread.title()
+    })?;
+    result?;
+
+    let reporter = reporter.borrow();
+    if reporter.refused {
+        return Err(anyhow!("The model refused to respond to the prompt"));
+    }
+    (reporter.emit)(HeadlessEvent::Finished {
+        title: title.to_string(),
+    });
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Reported {
+    Nothing,
+    Started,
+    Done,
+}
+
+struct Reporter {
+    reported: Vec<Reported>,
+    refused: bool,
+    emit: Box<dyn Fn(HeadlessEvent)>,
+}
+
+impl Reporter {
+    fn handle_event(&mut self, thread: Entity<AcpThread>, event: &AcpThreadEvent, cx: &mut App) {
+        match event {
+            AcpThreadEvent::NewEntry | AcpThreadEvent::EntryUpdated(_) => {
+                self.report_entries(thread.read(cx), false, cx);
+            }
+            AcpThreadEvent::EntriesRemoved(range) => {
+                self.reported.truncate(range.start);
+            }
+            AcpThreadEvent::ToolAuthorizationRequired => {
+                self.reject_pending_tool_calls(thread, cx);
+            }
+            AcpThreadEvent::Refusal => {
+                self.refused = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Reports the entries that changed since the last call. Messages are
+    /// reported once another entry follows them, or when `finished` is set,
+    /// so that each of them is printed whole.
+    fn report_entries(&mut self, thread: &AcpThread, finished: bool, cx: &App) {
+        let entries = thread.entries();
+        self.reported.resize(entries.len(), Reported::Nothing);
+        for (ix, entry) in entries.iter().enumerate() {
+            if self.reported[ix] == Reported::Done {
+                continue;
+            }
+            let is_last = ix + 1 == entries.len();
+            match entry {
+                AgentThreadEntry::UserMessage(_) => {
+                    self.reported[ix] = Reported::Done;
+                }
+                AgentThreadEntry::AssistantMessage(message) => {
+                    if !finished && is_last {
+                        continue;
+                    }
+                    for chunk in &message.chunks {
+                        (self.emit)(match chunk {
+                            AssistantMessageChunk::Message { block } => HeadlessEvent::Message {
+                                text: block.to_markdown(cx).to_string(),
+                            },
+                            AssistantMessageChunk::Thought { block } => HeadlessEvent::Thought {
+                                text: block.to_markdown(cx).to_string(),
+                            },
+                        });
+                    }
+                    self.reported[ix] = Reported::Done;
+                }
+                AgentThreadEntry::ToolCall(tool_call) => {
+                    let finished_status = match tool_call.status {
+                        ToolCallStatus::Pending | ToolCallStatus::WaitingForConfirmation { .. } => {
+                            continue;
+                        }
+                        ToolCallStatus::InProgress => None,
+                        ToolCallStatus::Completed => Some("completed"),
+                        ToolCallStatus::Failed => Some("failed"),
+                        ToolCallStatus::Rejected => Some("rejected"),
+                        ToolCallStatus::Canceled => Some("canceled"),
+                    };
+                    if self.reported[ix] == Reported::Nothing {
+                        (self.emit)(HeadlessEvent::ToolCall {
+                            id: tool_call.id.clone(),
+                            title: tool_call.label.read(cx).source().to_string(),
+                            kind: tool_call.kind,
+                            input: tool_call.raw_input.clone(),
+                        });
+                        self.reported[ix] = Reported::Started;
+                    }
+                    if let Some(status) = finished_status {
+                        (self.emit)(HeadlessEvent::ToolCallFinished {
+                            id: tool_call.id.clone(),
+                            status,
+                            output: tool_call
+                                .content
+                                .iter()
+                                .map(|content| content.to_markdown(cx))
+                                .join("\n\n"),
+                        });
+                        self.reported[ix] = Reported::Done;
+                    }
+                }
+            }
+        }
+    }
+
+    fn reject_pending_tool_calls(&mut self, thread: Entity<AcpThread>, cx: &mut App) {
+        for (id, title) in reject_pending_tool_calls(&thread, cx) {
+            (self.emit)(HeadlessEvent::PermissionDenied { id, title });
+        }
+    }
+}
+
+/// Rejects the tool calls of `thread` that are waiting for the user's
+/// permission, for threads that run without anyone to ask. Returns the id and
+/// title of each of them.
+pub(crate) fn reject_pending_tool_calls(
+    thread: &Entity<AcpThread>,
+    cx: &mut App,
+) -> Vec<(acp::ToolCallId, String)> {
+    let pending = thread
+        .read(cx)
+        .entries()
+        .iter()
+        .filter_map(|entry| match entry {
+            AgentThreadEntry::ToolCall(tool_call) => match &tool_call.status {
+                ToolCallStatus::WaitingForConfirmation { options, .. } => Some((
+                    tool_call.id.clone(),
+                    tool_call.label.read(cx).source().to_string(),
+                    options
+                        .iter()
+                        .find(|option| matches!(option.kind, acp::PermissionOptionKind::RejectOnce))
+                        .cloned(),
+                )),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut rejected = Vec::new();
+    for (id, title, reject) in pending {
+        match reject {
+            Some(option) => thread.update(cx, |thread, cx| {
+                thread.authorize_tool_call(id.clone(), option.id, option.kind, cx)
+            }),
+            None => {
+                // Without a way to reject just this call, stop the turn
+                // rather than waiting for an answer that never comes.
+                thread.update(cx, |thread, cx| thread.cancel(cx)).detach();
+            }
+        }
+        rejected.push((id, title));
+    }
+    rejected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use language_model::{
+        LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelToolUse, StopReason,
+    };
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_headless_events(cx: &mut TestAppContext) {
+        let (agent, project, events) = init_test(cx).await;
+        let run = spawn_run(agent.clone(), project, events.clone(), cx);
+        cx.run_until_parked();
+
+        // The fetch tool asks for permission, which is denied without a user
+        // to grant it, and the model then gets to respond to the rejection.
+        let model = thread_model(&agent, &events, cx);
+        let model = model.as_fake();
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: "tool_1".into(),
+                name: "fetch".into(),
+                raw_input: json!({"url": "https://zed.dev"}).to_string(),
+                input: json!({"url": "https://zed.dev"}),
+                is_input_complete: true,
+            },
+        ));
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+        model.send_last_completion_stream_text_chunk("I wasn't allowed to fetch it.");
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(
+            StopReason::EndTurn,
+        ));
+        model.end_last_completion_stream();
+        run.await.unwrap();
+
+        let events = events.borrow();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event["type"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            [
+                "started",
+                "tool_call",
+                "permission_denied",
+                "tool_call_finished",
+                "message",
+                "finished"
+            ]
+        );
+        assert_eq!(events[0]["model"], "fake/fake");
+        assert_eq!(events[1]["id"], "tool_1");
+        assert_eq!(events[2]["id"], "tool_1");
+        assert_eq!(events[2]["title"], "https://zed.dev");
+        assert_eq!(events[3]["status"], "rejected");
+        assert_eq!(events[4]["text"], "I wasn't allowed to fetch it.");
+    }
+
+    #[gpui::test]
+    async fn test_headless_refusal(cx: &mut TestAppContext) {
+        let (agent, project, events) = init_test(cx).await;
+        let run = spawn_run(agent.clone(), project, events.clone(), cx);
+        cx.run_until_parked();
+
+        let model = thread_model(&agent, &events, cx);
+        let model = model.as_fake();
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(
+            StopReason::Refusal,
+        ));
+        model.end_last_completion_stream();
+        assert!(run.await.is_err());
+
+        let events = events.borrow();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event["type"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["started"]
+        );
+    }
+
+    type Events = Rc<RefCell<Vec<serde_json::Value>>>;
+
+    async fn init_test(cx: &mut TestAppContext) -> (Entity<NativeAgent>, Entity<Project>, Events) {
+        env_logger::try_init().ok();
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            Project::init_settings(cx);
+            agent_settings::init(cx);
+            language::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/test"), json!({})).await;
+        let project = Project::test(fs.clone(), [path!("/test").as_ref()], cx).await;
+        let context_store = cx.new(|cx| assistant_context::ContextStore::fake(project.clone(), cx));
+        let history_store = cx.new(|cx| HistoryStore::new(context_store, cx));
+        let agent = NativeAgent::new(
+            project.clone(),
+            history_store,
+            Templates::new(),
+            None,
+            fs,
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        (agent, project, Events::default())
+    }
+
+    fn spawn_run(
+        agent: Entity<NativeAgent>,
+        project: Entity<Project>,
+        events: Events,
+        cx: &mut TestAppContext,
+    ) -> gpui::Task<Result<()>> {
+        cx.spawn(move |mut cx| async move {
+            run_headless_with_agent(
+                agent,
+                project,
+                Path::new(path!("/test")),
+                HeadlessRunOptions {
+                    prompt: "Fetch zed.dev".into(),
+                    profile: None,
+                    model: None,
+                },
+                move |event| {
+                    events
+                        .borrow_mut()
+                        .push(serde_json::to_value(event).unwrap())
+                },
+                &mut cx,
+            )
+            .await
+        })
+    }
+
+    /// Returns the model of the thread that the run reported as started.
+    fn thread_model(
+        agent: &Entity<NativeAgent>,
+        events: &Events,
+        cx: &mut TestAppContext,
+    ) -> Arc<dyn language_model::LanguageModel> {
+        let session_id = acp::SessionId(
+            events.borrow()[0]["session_id"]
+                .as_str()
+                .unwrap()
+                .to_string()
+                .into(),
+        );
+        cx.update(|cx| {
+            NativeAgentConnection(agent.clone())
+                .thread(&session_id, cx)
+                .unwrap()
+                .read(cx)
+                .model()
+                .unwrap()
+                .clone()
+        })
+    }
+}
@@ -0,0 +1,522 @@
+use agent_settings::{AgentHook, AgentHookCommand};
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use futures::{AsyncWriteExt as _, FutureExt as _, future::Shared};
+use gpui::{BackgroundExecutor, Task};
+use itertools::Itertools as _;
+use language_model::{LanguageModelToolResult, LanguageModelToolResultContent};
+use serde_json::json;
+use settings::AgentHookEvent;
+use std::{path::PathBuf, process::Stdio, sync::Arc};
+use task::SpawnInTerminal;
+use util::{
+    markdown::MarkdownCodeBlock,
+    shell::{ShellKind, get_system_shell},
+};
+
+const MAX_HOOK_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// The hooks of the thread's profile that apply to a single tool call.
+pub struct ToolCallHooks {
+    tool_name: Arc<str>,
+    pre_tool_use: Vec<AgentHook>,
+    post_tool_use: Vec<AgentHook>,
+    cwd: Option<PathBuf>,
+    tasks: HashMap<String, Shared<Task<Option<SpawnInTerminal>>>>,
+}
+
+/// What a hook command printed, and whether it exited successfully.
+pub struct HookRun {
+    command: String,
+    succeeded: bool,
+    output: String,
+}
+
+impl ToolCallHooks {
+    /// Creates the hooks for a call, using `resolve_task` to look up the
+    /// tasks that hooks refer to by label.
+    pub fn new(
+        tool_name: Arc<str>,
+        hooks: impl IntoIterator<Item = AgentHook>,
+        cwd: Option<PathBuf>,
+        mut resolve_task: impl FnMut(&str) -> Task<Option<SpawnInTerminal>>,
+    ) -> Self {
+        let (pre_tool_use, post_tool_use): (Vec<_>, Vec<_>) = hooks
+            .into_iter()
+            .partition(|hook| hook.event == AgentHookEvent::PreToolUse);
+        let mut tasks = HashMap::default();
+        for hook in pre_tool_use.iter().chain(&post_tool_use) {
+            if let AgentHookCommand::Task(label) = &hook.command {
+                tasks
+                    .entry(label.clone())
+                    .or_insert_with(|| resolve_task(label).shared());
+            }
+        }
+        Self {
+            tool_name,
+            pre_tool_use,
+            post_tool_use,
+            cwd,
+            tasks,
+        }
+    }
+
+    pub fn empty(tool_name: Arc<str>) -> Self {
+        Self::new(tool_name, [], None, |_| Task::ready(None))
+    }
+
+    pub fn has_pre_tool_use(&self) -> bool {
+        !self.pre_tool_use.is_empty()
+    }
+
+    pub fn has_post_tool_use(&self) -> bool {
+        !self.post_tool_use.is_empty()
+    }
+
+    /// Runs the pre-tool hooks in order, stopping at the first one that fails.
+    /// Returns an error describing why the call was blocked if any of them did.
+    pub async fn run_pre_tool_use(
+        &self,
+        input: &serde_json::Value,
+        executor: &BackgroundExecutor,
+    ) -> Result<Vec<HookRun>> {
+        let payload = json!({
+            "event": "pre_tool_use",
+            "tool_name": self.tool_name,
+            "input": input,
+        });
+        let mut runs = Vec::new();
+        for hook in &self.pre_tool_use {
+            let run = self.run_hook(hook, &payload, executor).await;
+            if !run.succeeded {
+                let mut reason =
+                    format!("This tool call was blocked by the `{}` hook.", run.command);
+                if !run.output.is_empty() {
+                    reason.push_str(&format!("\n\n{}", run.output));
+                }
+                return Err(anyhow!(reason));
+            }
+            runs.push(run);
+        }
+        Ok(runs)
+    }
+
+    /// Runs every post-tool hook in order with the result of the call.
+    pub async fn run_post_tool_use(
+        &self,
+        input: &serde_json::Value,
+        output: &LanguageModelToolResultContent,
+        is_error: bool,
+        executor: &BackgroundExecutor,
+    ) -> Vec<HookRun> {
+        let output = match output {
+            LanguageModelToolResultContent::Text(text) => Some(text.as_ref()),
+            LanguageModelToolResultContent::Image(_) => None,
+        };
+        let payload = json!({
+            "event": "post_tool_use",
+            "tool_name": self.tool_name,
+            "input": input,
+            "output": output,
+            "is_error": is_error,
+        });
+        let mut runs = Vec::new();
+        for hook in &self.post_tool_use {
+            runs.push(self.run_hook(hook, &payload, executor).await);
+        }
+        runs
+    }
+
+    async fn run_hook(
+        &self,
+        hook: &AgentHook,
+        payload: &serde_json::Value,
+        executor: &BackgroundExecutor,
+    ) -> HookRun {
+        let task = match &hook.command {
+            AgentHookCommand::Shell(_) => None,
+            AgentHookCommand::Task(label) => self.tasks.get(label).cloned(),
+        };
+        let result = executor
+            .spawn({
+                let hook = hook.clone();
+                let tool_name = self.tool_name.clone();
+                let cwd = self.cwd.clone();
+                let payload = payload.to_string();
+                async move {
+                    let command = match &hook.command {
+                        AgentHookCommand::Shell(command) => HookCommand {
+                            command: command.clone(),
+                            env: HashMap::default(),
+                            cwd: None,
+                        },
+                        AgentHookCommand::Task(label) => {
+                            let task = match task {
+                                Some(task) => task.await,
+                                None => None,
+                            };
+                            HookCommand::for_task(
+                                task.with_context(|| format!("no task labeled `{label}`"))?,
+                            )
+                        }
+                    };
+                    run_command(&hook, command, &tool_name, cwd, payload).await
+                }
+            })
+            .fuse();
+        let timeout = executor.timer(hook.timeout).fuse();
+        futures::pin_mut!(result, timeout);
+        let result = futures::select_biased! {
+            result = result => result,
+            _ = timeout => Err(anyhow!("timed out after {} seconds", hook.timeout.as_secs())),
+        };
+
+        let command = hook.command.label();
+        match result {
+            Ok((succeeded, output)) => HookRun {
+                command,
+                succeeded,
+                output,
+            },
+            Err(error) => {
+                log::error!("Failed to run agent hook `{command}`: {error:#}");
+                HookRun {
+                    command,
+                    succeeded: false,
+                    output: format!("The hook failed to run: {error:#}"),
+                }
+            }
+        }
+    }
+}
+
+/// The shell command line a hook runs, with what a task adds to it.
+struct HookCommand {
+    command: String,
+    env: HashMap<String, String>,
+    cwd: Option<PathBuf>,
+}
+
+impl HookCommand {
+    fn for_task(task: SpawnInTerminal) -> Result<Self> {
+        let shell_kind = ShellKind::new(&get_system_shell());
+        let command = task
+            .command
+            .with_context(|| format!("the `{}` task has no command", task.label))?;
+        let args = task
+            .args
+            .iter()
+            .map(|arg| shell_kind.try_quote(arg).map(|arg| arg.into_owned()))
+            .collect::<Option<Vec<_>>>()
+            .with_context(|| format!("quoting the arguments of the `{}` task", task.label))?;
+        Ok(Self {
+            command: std::iter::once(command).chain(args).join(" "),
+            env: task.env,
+            cwd: task.cwd,
+        })
+    }
+}
+
+async fn run_command(
+    hook: &AgentHook,
+    hook_command: HookCommand,
+    tool_name: &str,
+    cwd: Option<PathBuf>,
+    payload: String,
+) -> Result<(bool, String)> {
+    let shell = get_system_shell();
+    let mut command = util::command::new_smol_command(&shell);
+    command
+        .args(ShellKind::new(&shell).args_for_shell(false, hook_command.command.clone()))
+        .envs(&hook_command.env)
+        .envs(&hook.env)
+        .env(
+            "JAMU_HOOK_EVENT",
+            match hook.event {
+                AgentHookEvent::PreToolUse => "pre_tool_use",
+                AgentHookEvent::PostToolUse => "post_tool_use",
+            },
+        )
+        .env("JAMU_TOOL_NAME", tool_name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = hook_command.cwd {
+        command.current_dir(cwd);
+    } else if let Some(cwd) = cwd {
+        command.current_dir(match &hook.cwd {
+            Some(relative) => cwd.join(relative),
+            None => cwd,
+        });
+    }
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("spawning `{}`", hook_command.command))?;
+    let mut stdin = child.stdin.take().context("hook has no stdin")?;
+    let write_payload = async move {
+        // Hooks that don't read their input may exit before it's written.
+        stdin.write_all(payload.as_bytes()).await.ok();
+        stdin.close().await.ok();
+    };
+    let ((), output) = futures::join!(write_payload, child.output());
+    let output = output?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.trim().is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&stderr);
+    }
+    let text = util::truncate_to_byte_limit(text.trim(), MAX_HOOK_OUTPUT_BYTES).to_string();
+    Ok((output.status.success(), text))
+}
+
+/// Adds what the hooks printed to the tool's result, so the model sees it
+/// alongside the output of the tool.
+pub fn append_hook_output(content: &mut LanguageModelToolResultContent, runs: &[HookRun]) {
+    let runs = runs
+        .iter()
+        .filter(|run| !run.output.is_empty())
+        .collect::<Vec<_>>();
+    if runs.is_empty() {
+        return;
+    }
+    let LanguageModelToolResultContent::Text(text) = content else {
+        log::warn!("Dropping agent hook output for a tool that returned an image");
+        return;
+    };
+
+    let mut text = text.to_string();
+    for run in runs {
+        text.push_str(&format!(
+            "\n\nThe `{}` hook {}:\n{}",
+            run.command,
+            if run.succeeded {
+                "printed"
+            } else {
+                "failed with"
+            },
+            MarkdownCodeBlock {
+                tag: "",
+                text: &run.output,
+            }
+        ));
+    }
+    *content = LanguageModelToolResultContent::Text(text.into());
+}
+
+/// Reports a call whose post-tool hooks failed as failed, and adds what the
+/// hooks printed to its result.
+pub fn apply_post_tool_use(result: &mut LanguageModelToolResult, runs: &[HookRun]) {
+    if runs.iter().any(|run| !run.succeeded) {
+        result.is_error = true;
+    }
+    append_hook_output(&mut result.content, runs);
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use std::time::Duration;
+
+    #[gpui::test]
+    async fn test_pre_tool_use_veto(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let hooks = ToolCallHooks::new(
+            "terminal".into(),
+            [
+                hook(AgentHookEvent::PreToolUse, "cat"),
+                hook(
+                    AgentHookEvent::PreToolUse,
+                    "echo 'not on this drive'; exit 1",
+                ),
+                hook(AgentHookEvent::PreToolUse, "echo unreachable"),
+            ],
+            None,
+            |_| Task::ready(None),
+        );
+        let input = json!({"command": "rm -rf /samples"});
+
+        let error = hooks
+            .run_pre_tool_use(&input, &cx.executor())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "This tool call was blocked by the `echo 'not on this drive'; exit 1` hook.\n\n\
+             not on this drive"
+        );
+
+        // Hooks that pass get the call on stdin.
+        let hooks = ToolCallHooks::new(
+            "terminal".into(),
+            [hook(AgentHookEvent::PreToolUse, "cat")],
+            None,
+            |_| Task::ready(None),
+        );
+        let runs = hooks
+            .run_pre_tool_use(&input, &cx.executor())
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].succeeded);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&runs[0].output).unwrap(),
+            json!({
+                "event": "pre_tool_use",
+                "tool_name": "terminal",
+                "input": input,
+            })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_post_tool_use(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let hooks = ToolCallHooks::new(
+            "edit_file".into(),
+            [
+                hook(AgentHookEvent::PostToolUse, "echo formatted"),
+                hook(AgentHookEvent::PostToolUse, "echo \"$JAMU_TOOL_NAME\" >&2"),
+            ],
+            None,
+            |_| Task::ready(None),
+        );
+        let mut result = tool_result("Edited main.rs");
+        let runs = hooks
+            .run_post_tool_use(&json!({}), &result.content, false, &cx.executor())
+            .await;
+        apply_post_tool_use(&mut result, &runs);
+        assert!(!result.is_error);
+        assert_eq!(
+            result.content,
+            LanguageModelToolResultContent::Text(
+                "Edited main.rs\n\n\
+                 The `echo formatted` hook printed:\n```\nformatted\n```\n\n\n\
+                 The `echo \"$JAMU_TOOL_NAME\" >&2` hook printed:\n```\nedit_file\n```\n"
+                    .into()
+            )
+        );
+
+        // A failing post-tool hook reports the call as failed.
+        let hooks = ToolCallHooks::new(
+            "edit_file".into(),
+            [
+                hook(AgentHookEvent::PostToolUse, "true"),
+                hook(AgentHookEvent::PostToolUse, "echo 'rustfmt failed'; exit 1"),
+            ],
+            None,
+            |_| Task::ready(None),
+        );
+        let mut result = tool_result("Edited main.rs");
+        let runs = hooks
+            .run_post_tool_use(&json!({}), &result.content, false, &cx.executor())
+            .await;
+        apply_post_tool_use(&mut result, &runs);
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            LanguageModelToolResultContent::Text(
+                "Edited main.rs\n\n\
+                 The `echo 'rustfmt failed'; exit 1` hook failed with:\n```\nrustfmt failed\n```\n"
+                    .into()
+            )
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hook_timeout(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let hooks = ToolCallHooks::new(
+            "terminal".into(),
+            [AgentHook {
+                timeout: Duration::from_secs(1),
+                ..hook(AgentHookEvent::PreToolUse, "sleep 30")
+            }],
+            None,
+            |_| Task::ready(None),
+        );
+        let executor = cx.executor();
+        let run = cx
+            .executor()
+            .spawn(async move { hooks.run_pre_tool_use(&json!({}), &executor).await });
+        cx.run_until_parked();
+        cx.executor().advance_clock(Duration::from_secs(2));
+
+        let error = run.await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "This tool call was blocked by the `sleep 30` hook.\n\n\
+             The hook failed to run: timed out after 1 seconds"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_task_hooks(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let hooks = ToolCallHooks::new(
+            "edit_file".into(),
+            [
+                AgentHook {
+                    command: AgentHookCommand::Task("greet".into()),
+                    ..hook(AgentHookEvent::PostToolUse, "")
+                },
+                AgentHook {
+                    command: AgentHookCommand::Task("missing".into()),
+                    ..hook(AgentHookEvent::PostToolUse, "")
+                },
+            ],
+            None,
+            |label| {
+                Task::ready((label == "greet").then(|| SpawnInTerminal {
+                    label: "greet".into(),
+                    command: Some("echo".into()),
+                    args: vec!["hello from $GREETER".into()],
+                    env: HashMap::from_iter([("GREETER".into(), "a task".into())]),
+                    ..Default::default()
+                }))
+            },
+        );
+        let runs = hooks
+            .run_post_tool_use(&json!({}), &tool_result("").content, false, &cx.executor())
+            .await;
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].command, "task: greet");
+        assert!(runs[0].succeeded);
+        // Arguments are quoted, so the shell doesn't expand them.
+        assert_eq!(runs[0].output, "hello from $GREETER");
+        assert_eq!(runs[1].command, "task: missing");
+        assert!(!runs[1].succeeded);
+        assert_eq!(
+            runs[1].output,
+            "The hook failed to run: no task labeled `missing`"
+        );
+    }
+
+    fn hook(event: AgentHookEvent, command: &str) -> AgentHook {
+        AgentHook {
+            event,
+            tools: Vec::new(),
+            command: AgentHookCommand::Shell(command.into()),
+            env: Default::default(),
+            cwd: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    fn tool_result(text: &str) -> LanguageModelToolResult {
+        LanguageModelToolResult {
+            tool_use_id: "tool_1".into(),
+            tool_name: "edit_file".into(),
+            is_error: false,
+            content: LanguageModelToolResultContent::Text(text.into()),
+            output: None,
+        }
+    }
+}
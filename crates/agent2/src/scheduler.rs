@@ -0,0 +1,679 @@
+use crate::{
+    HistoryStore, NativeAgent, NativeAgentConnection, Templates,
+    headless::reject_pending_tool_calls,
+};
+use acp_thread::{AcpThread, AcpThreadEvent, AgentConnection as _};
+use agent_client_protocol as acp;
+use agent_settings::{AgentProfileId, AgentSettings};
+use anyhow::{Context as _, Result, anyhow};
+use chrono::{DateTime, Datelike as _, Local, Timelike as _};
+use collections::HashMap;
+use fs::Fs;
+use gpui::{
+    AsyncApp, Context, Entity, EventEmitter, Global, SharedString, Subscription, Task, WeakEntity,
+};
+use project::Project;
+use prompt_store::PromptStore;
+use settings::{ScheduledAgentRun, Settings as _, SettingsStore};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use util::ResultExt as _;
+
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Starts the scheduled runs from the `agent.scheduled_runs` setting that
+/// belong to a project, each in a new native agent thread.
+pub struct AgentScheduler {
+    project: Entity<Project>,
+    history: Entity<HistoryStore>,
+    fs: Arc<dyn Fs>,
+    agent: Option<Entity<NativeAgent>>,
+    runs: Vec<(ScheduledAgentRun, CronSchedule)>,
+    running: HashMap<String, Task<()>>,
+    /// The thread of the latest run of each job. Keeping it alive until the
+    /// next run makes sure it's fully saved to the thread history.
+    finished: HashMap<String, Entity<AcpThread>>,
+    _tick: Task<()>,
+    _settings_subscription: Subscription,
+}
+
+pub enum AgentSchedulerEvent {
+    RunFinished {
+        name: SharedString,
+        session_id: acp::SessionId,
+        title: SharedString,
+        error: Option<SharedString>,
+    },
+}
+
+impl EventEmitter<AgentSchedulerEvent> for AgentScheduler {}
+
+/// The runs that were started in the current minute, so that a job isn't run
+/// once per window when several windows have its project open.
+#[derive(Default)]
+struct StartedScheduledRuns(HashMap<(PathBuf, String), i64>);
+
+impl Global for StartedScheduledRuns {}
+
+impl AgentScheduler {
+    pub fn new(
+        project: Entity<Project>,
+        history: Entity<HistoryStore>,
+        fs: Arc<dyn Fs>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            project,
+            history,
+            fs,
+            agent: None,
+            runs: Vec::new(),
+            running: HashMap::default(),
+            finished: HashMap::default(),
+            _tick: cx.spawn(async move |this, cx| {
+                loop {
+                    cx.background_executor().timer(TICK_INTERVAL).await;
+                    let Some(due) = this
+                        .update(cx, |this, cx| this.due_runs(Local::now(), cx))
+                        .ok()
+                    else {
+                        break;
+                    };
+                    if due.is_empty() {
+                        continue;
+                    }
+                    let agent = match Self::agent(&this, cx).await {
+                        Ok(agent) => agent,
+                        Err(error) => {
+                            log::error!("Failed to start scheduled agent runs: {error:#}");
+                            continue;
+                        }
+                    };
+                    this.update(cx, |this, cx| {
+                        for (run, cwd) in due {
+                            this.start_run(run, cwd, agent.clone(), cx);
+                        }
+                    })
+                    .ok();
+                }
+            }),
+            _settings_subscription: cx.observe_global::<SettingsStore>(|this, cx| {
+                this.reload_runs(cx);
+            }),
+        };
+        this.reload_runs(cx);
+        this
+    }
+
+    fn reload_runs(&mut self, cx: &mut Context<Self>) {
+        let runs = &AgentSettings::get_global(cx).scheduled_runs;
+        if self.runs.iter().map(|(run, _)| run).eq(runs.iter()) {
+            return;
+        }
+        self.runs = runs
+            .iter()
+            .filter_map(|run| match run.schedule.parse::<CronSchedule>() {
+                Ok(schedule) => Some((run.clone(), schedule)),
+                Err(error) => {
+                    log::error!(
+                        "Invalid schedule {:?} for scheduled agent run {:?}: {error:#}",
+                        run.schedule,
+                        run.name
+                    );
+                    None
+                }
+            })
+            .collect();
+    }
+
+    /// Returns the enabled runs of this project that are due at `now` and
+    /// haven't been started in any window yet, along with their working
+    /// directory.
+    fn due_runs(
+        &mut self,
+        now: DateTime<Local>,
+        cx: &mut Context<Self>,
+    ) -> Vec<(ScheduledAgentRun, PathBuf)> {
+        let project = self.project.read(cx);
+        if !project.is_local() {
+            return Vec::new();
+        }
+        let worktree_paths = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+            .collect::<Vec<_>>();
+
+        let minute = now.timestamp() / 60;
+        let mut due = Vec::new();
+        for (run, schedule) in &self.runs {
+            if !run.enabled.unwrap_or(true)
+                || !schedule.matches(&now)
+                || self.running.contains_key(&run.name)
+            {
+                continue;
+            }
+            let project_path = expand_home_dir(&run.project);
+            if !worktree_paths.iter().any(|path| **path == *project_path) {
+                continue;
+            }
+
+            let started = cx.default_global::<StartedScheduledRuns>();
+            let key = (project_path.clone(), run.name.clone());
+            if started.0.get(&key) == Some(&minute) {
+                continue;
+            }
+            started.0.insert(key, minute);
+            due.push((run.clone(), project_path));
+        }
+        due
+    }
+
+    async fn agent(this: &WeakEntity<Self>, cx: &mut AsyncApp) -> Result<Entity<NativeAgent>> {
+        let (agent, project, history, fs, prompt_store) = this.update(cx, |this, cx| {
+            (
+                this.agent.clone(),
+                this.project.clone(),
+                this.history.clone(),
+                this.fs.clone(),
+                PromptStore::global(cx),
+            )
+        })?;
+        if let Some(agent) = agent {
+            return Ok(agent);
+        }
+
+        let prompt_store = prompt_store.await.log_err();
+        let agent =
+            NativeAgent::new(project, history, Templates::new(), prompt_store, fs, cx).await?;
+        this.update(cx, |this, _| this.agent = Some(agent.clone()))?;
+        Ok(agent)
+    }
+
+    fn start_run(
+        &mut self,
+        run: ScheduledAgentRun,
+        cwd: PathBuf,
+        agent: Entity<NativeAgent>,
+        cx: &mut Context<Self>,
+    ) {
+        log::info!("Starting scheduled agent run {:?}", run.name);
+        let name = run.name.clone();
+        let project = self.project.clone();
+        let task = cx.spawn(async move |this, cx| {
+            let connection = Rc::new(NativeAgentConnection(agent));
+            let thread = match cx.update(|cx| connection.clone().new_thread(project, &cwd, cx)) {
+                Ok(thread) => thread.await,
+                Err(error) => Err(error),
+            };
+            let thread = match thread {
+                Ok(thread) => thread,
+                Err(error) => {
+                    log::error!(
+                        "Failed to start scheduled agent run {:?}: {error:#}",
+                        run.name
+                    );
+                    this.update(cx, |this, _| this.running.remove(&run.name))
+                        .ok();
+                    return;
+                }
+            };
+
+            // Nobody is around to answer permission prompts, so reject them
+            // rather than leaving the run waiting forever.
+            let _subscription = cx.update(|cx| {
+                let name = run.name.clone();
+                cx.subscribe(&thread, move |thread, event, cx| {
+                    if let AcpThreadEvent::ToolAuthorizationRequired = event {
+                        for (_, title) in reject_pending_tool_calls(&thread, cx) {
+                            log::warn!(
+                                "Scheduled agent run {name:?} was denied permission to: {title}"
+                            );
+                        }
+                    }
+                })
+            });
+            let result = Self::send(&connection, &thread, &run, cx).await;
+            this.update(cx, |this, cx| {
+                this.running.remove(&run.name);
+                let (session_id, title) = {
+                    let thread = thread.read(cx);
+                    (thread.session_id().clone(), thread.title())
+                };
+                this.finished.insert(run.name.clone(), thread);
+                cx.emit(AgentSchedulerEvent::RunFinished {
+                    name: run.name.into(),
+                    session_id,
+                    title,
+                    error: result.err().map(|error| format!("{error:#}").into()),
+                });
+            })
+            .ok();
+        });
+        self.running.insert(name, task);
+    }
+
+    async fn send(
+        connection: &NativeAgentConnection,
+        thread: &Entity<AcpThread>,
+        run: &ScheduledAgentRun,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        cx.update(|cx| {
+            let session_id = thread.read(cx).session_id().clone();
+            connection.configure_thread(
+                &session_id,
+                run.profile.clone().map(AgentProfileId),
+                run.model.as_ref(),
+                cx,
+            )
+        })??;
+
+        thread
+            .update(cx, |thread, cx| thread.send_raw(&run.prompt, cx))?
+            .await
+    }
+}
+
+fn expand_home_dir(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(relative) => util::paths::home_dir().join(relative),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// A cron schedule with five fields: minute, hour, day of month, month and
+/// day of week.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Whether the schedule fires during the minute of `time`.
+    ///
+    /// Like cron, when both the day of month and the day of week are
+    /// restricted, a day that matches either of them matches.
+    pub fn matches(&self, time: &DateTime<Local>) -> bool {
+        let day_of_month = self.days_of_month & (1 << time.day()) != 0;
+        let day_of_week = self.days_of_week & (1 << time.weekday().num_days_from_sunday()) != 0;
+        let day = match (self.days_of_month_restricted, self.days_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        };
+        day && self.minutes & (1 << time.minute()) != 0
+            && self.hours & (1 << time.hour()) != 0
+            && self.months & (1 << time.month()) != 0
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(schedule: &str) -> Result<Self> {
+        let schedule = match schedule.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            schedule => schedule,
+        };
+        let fields = schedule.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(anyhow!(
+                "expected 5 fields (minute, hour, day of month, month and day of week), found {}",
+                fields.len()
+            ));
+        };
+
+        let mut days_of_week_mask = parse_field(days_of_week, 0, 7).context("day of week")?;
+        // Both 0 and 7 mean Sunday.
+        if days_of_week_mask & (1 << 7) != 0 {
+            days_of_week_mask = (days_of_week_mask | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minutes, 0, 59).context("minute")?,
+            hours: parse_field(hours, 0, 23).context("hour")?,
+            days_of_month: parse_field(days_of_month, 1, 31).context("day of month")?,
+            months: parse_field(months, 1, 12).context("month")?,
+            days_of_week: days_of_week_mask,
+            days_of_month_restricted: !days_of_month.starts_with('*'),
+            days_of_week_restricted: !days_of_week.starts_with('*'),
+        })
+    }
+}
+
+/// Parses a comma-separated list of `*`, `n` and `a-b` items, each optionally
+/// followed by a `/step`, into a bit mask of the values it contains.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut mask = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .with_context(|| format!("invalid step `{step}`"))?;
+                (range, Some(step))
+            }
+            None => (item, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max)?, parse_value(end, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            // Like cron, `n/step` means every step starting at n.
+            (value, if step.is_some() { max } else { value })
+        };
+        if start > end {
+            return Err(anyhow!("invalid range `{range}`"));
+        }
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|value| (min..=max).contains(value))
+        .with_context(|| format!("`{value}` isn't a number between {min} and {max}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use acp_thread::{AgentThreadEntry, ToolCallStatus};
+    use chrono::TimeZone as _;
+    use fs::FakeFs;
+    use gpui::{TestAppContext, UpdateGlobal as _};
+    use language_model::{
+        LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelToolUse, StopReason,
+    };
+    use serde_json::json;
+    use std::cell::RefCell;
+    use util::path;
+
+    fn time(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        // 2025-09-01 is a Monday.
+        Local
+            .with_ymd_and_hms(2025, 9, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cron_schedule() {
+        let schedule = "0 9 * * 1-5".parse::<CronSchedule>().unwrap();
+        assert!(schedule.matches(&time(1, 9, 0)));
+        assert!(!schedule.matches(&time(1, 9, 1)));
+        assert!(!schedule.matches(&time(1, 10, 0)));
+        assert!(schedule.matches(&time(5, 9, 0)));
+        assert!(!schedule.matches(&time(6, 9, 0)));
+        assert!(!schedule.matches(&time(7, 9, 0)));
+
+        let schedule = "*/15 8-18/2 * * *".parse::<CronSchedule>().unwrap();
+        assert!(schedule.matches(&time(3, 8, 45)));
+        assert!(schedule.matches(&time(3, 18, 0)));
+        assert!(!schedule.matches(&time(3, 9, 0)));
+        assert!(!schedule.matches(&time(3, 8, 10)));
+
+        let schedule = "30 12 * * 7".parse::<CronSchedule>().unwrap();
+        assert!(schedule.matches(&time(7, 12, 30)));
+        assert_eq!(schedule, "30 12 * * 0".parse::<CronSchedule>().unwrap());
+
+        // When both days are restricted, either one matching is enough.
+        let schedule = "0 0 1,15 * 1".parse::<CronSchedule>().unwrap();
+        assert!(schedule.matches(&time(1, 0, 0)));
+        assert!(schedule.matches(&time(8, 0, 0)));
+        assert!(schedule.matches(&time(15, 0, 0)));
+        assert!(!schedule.matches(&time(16, 0, 0)));
+
+        let schedule = "@daily".parse::<CronSchedule>().unwrap();
+        assert!(schedule.matches(&time(20, 0, 0)));
+        assert!(!schedule.matches(&time(20, 1, 0)));
+
+        assert!("* * * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("5-1 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert!("0 0 0 * *".parse::<CronSchedule>().is_err());
+    }
+
+    #[gpui::test]
+    async fn test_due_runs(cx: &mut TestAppContext) {
+        init_test(cx);
+        set_scheduled_runs(
+            cx,
+            vec![
+                scheduled_run("standup", "0 9 * * 1-5", path!("/project")),
+                ScheduledAgentRun {
+                    enabled: Some(false),
+                    ..scheduled_run("disabled", "0 9 * * *", path!("/project"))
+                },
+                scheduled_run("elsewhere", "0 9 * * *", path!("/elsewhere")),
+                scheduled_run("invalid", "0 25 * * *", path!("/project")),
+            ],
+        );
+        let (scheduler, _) = new_scheduler(cx).await;
+
+        let due_names = |now, cx: &mut TestAppContext| {
+            scheduler.update(cx, |scheduler, cx| {
+                scheduler
+                    .due_runs(now, cx)
+                    .into_iter()
+                    .map(|(run, cwd)| (run.name, cwd))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            due_names(time(1, 9, 0), cx),
+            [("standup".to_string(), PathBuf::from(path!("/project")))]
+        );
+        // A run only starts once per minute, however often it's checked.
+        assert_eq!(due_names(time(1, 9, 0), cx), []);
+        assert_eq!(due_names(time(1, 9, 1), cx), []);
+        assert_eq!(due_names(time(6, 9, 0), cx), []);
+        assert_eq!(due_names(time(2, 9, 0), cx).len(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_due_runs_across_windows(cx: &mut TestAppContext) {
+        init_test(cx);
+        set_scheduled_runs(
+            cx,
+            vec![scheduled_run("standup", "0 9 * * *", path!("/project"))],
+        );
+        // Each window has its own project, and so its own scheduler.
+        let (first, _) = new_scheduler(cx).await;
+        let (second, _) = new_scheduler(cx).await;
+
+        let due_count = |scheduler: &Entity<AgentScheduler>, now, cx: &mut TestAppContext| {
+            scheduler.update(cx, |scheduler, cx| scheduler.due_runs(now, cx).len())
+        };
+        assert_eq!(due_count(&first, time(1, 9, 0), cx), 1);
+        assert_eq!(due_count(&second, time(1, 9, 0), cx), 0);
+        assert_eq!(due_count(&second, time(2, 9, 0), cx), 1);
+        assert_eq!(due_count(&first, time(2, 9, 0), cx), 0);
+    }
+
+    #[gpui::test]
+    async fn test_start_run(cx: &mut TestAppContext) {
+        init_test(cx);
+        let run = scheduled_run("standup", "0 9 * * *", path!("/project"));
+        set_scheduled_runs(cx, vec![run.clone()]);
+        let (scheduler, agent) = new_scheduler(cx).await;
+        let finished = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let finished = finished.clone();
+            cx.subscribe(&scheduler, move |_, event, _| {
+                let AgentSchedulerEvent::RunFinished { name, error, .. } = event;
+                finished.borrow_mut().push((name.clone(), error.clone()));
+            })
+            .detach();
+        });
+
+        scheduler.update(cx, |scheduler, cx| {
+            scheduler.start_run(run, PathBuf::from(path!("/project")), agent.clone(), cx)
+        });
+        cx.run_until_parked();
+        scheduler.read_with(cx, |scheduler, _| {
+            assert!(scheduler.running.contains_key("standup"));
+        });
+        // A run that is still going isn't started again.
+        assert_eq!(
+            scheduler.update(cx, |scheduler, cx| scheduler
+                .due_runs(time(1, 9, 0), cx)
+                .len()),
+            0
+        );
+
+        let model = agent
+            .read_with(cx, |agent, _| {
+                agent
+                    .models()
+                    .model_from_id(&acp::ModelId("fake/fake".into()))
+            })
+            .unwrap();
+        let model = model.as_fake();
+        model.send_last_completion_stream_text_chunk("Nothing new today.");
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(
+            StopReason::EndTurn,
+        ));
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        assert_eq!(
+            finished.borrow().as_slice(),
+            [(SharedString::from("standup"), None)]
+        );
+        scheduler.read_with(cx, |scheduler, cx| {
+            assert!(scheduler.running.is_empty());
+            let thread = scheduler.finished["standup"].read(cx);
+            assert_eq!(
+                thread.to_markdown(cx),
+                "## User\n\nSummarize yesterday's commits\n\n## Assistant\n\nNothing new today.\n\n"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_start_run_with_tool_authorization(cx: &mut TestAppContext) {
+        init_test(cx);
+        let run = scheduled_run("standup", "0 9 * * *", path!("/project"));
+        set_scheduled_runs(cx, vec![run.clone()]);
+        let (scheduler, agent) = new_scheduler(cx).await;
+        scheduler.update(cx, |scheduler, cx| {
+            scheduler.start_run(run, PathBuf::from(path!("/project")), agent.clone(), cx)
+        });
+        cx.run_until_parked();
+
+        // The fetch tool asks for permission, which nobody is around to grant,
+        // so it's rejected and the run goes on.
+        let model = agent
+            .read_with(cx, |agent, _| {
+                agent
+                    .models()
+                    .model_from_id(&acp::ModelId("fake/fake".into()))
+            })
+            .unwrap();
+        let model = model.as_fake();
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: "tool_1".into(),
+                name: "fetch".into(),
+                raw_input: json!({"url": "https://zed.dev"}).to_string(),
+                input: json!({"url": "https://zed.dev"}),
+                is_input_complete: true,
+            },
+        ));
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+        model.send_last_completion_stream_text_chunk("I wasn't allowed to fetch it.");
+        model.send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(
+            StopReason::EndTurn,
+        ));
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        scheduler.read_with(cx, |scheduler, cx| {
+            assert!(scheduler.running.is_empty());
+            let thread = scheduler.finished["standup"].read(cx);
+            let Some(AgentThreadEntry::ToolCall(tool_call)) = thread.entries().get(1) else {
+                panic!("expected a tool call");
+            };
+            assert!(matches!(tool_call.status, ToolCallStatus::Rejected));
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            Project::init_settings(cx);
+            agent_settings::init(cx);
+            language::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+    }
+
+    fn set_scheduled_runs(cx: &mut TestAppContext, runs: Vec<ScheduledAgentRun>) {
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.agent.get_or_insert_default().scheduled_runs = runs;
+                });
+            });
+        });
+    }
+
+    fn scheduled_run(name: &str, schedule: &str, project: &str) -> ScheduledAgentRun {
+        ScheduledAgentRun {
+            name: name.into(),
+            schedule: schedule.into(),
+            prompt: "Summarize yesterday's commits".into(),
+            project: project.into(),
+            profile: None,
+            model: None,
+            enabled: None,
+        }
+    }
+
+    async fn new_scheduler(
+        cx: &mut TestAppContext,
+    ) -> (Entity<AgentScheduler>, Entity<NativeAgent>) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({"README.md": "# Project"}))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let context_store = cx.new(|cx| assistant_context::ContextStore::fake(project.clone(), cx));
+        let history = cx.new(|cx| HistoryStore::new(context_store, cx));
+        let agent = NativeAgent::new(
+            project.clone(),
+            history.clone(),
+            Templates::new(),
+            None,
+            fs.clone(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        let scheduler = cx.new(|cx| AgentScheduler::new(project, history, fs, cx));
+        (scheduler, agent)
+    }
+}
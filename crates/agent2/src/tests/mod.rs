@@ -1,5 +1,9 @@
 use super::*;
-use acp_thread::{AgentConnection, AgentModelGroupName, AgentModelList, UserMessageId};
+use acp_thread::{
+    AcpThread, AgentConnection, AgentModelGroupName, AgentModelList, AgentThreadEntry,
+    ToolCallStatus, UserMessageId,
+};
+use action_log::ActionLog;
 use agent_client_protocol::{self as acp};
 use agent_settings::AgentProfileId;
 use anyhow::Result;
@@ -294,6 +298,67 @@ async fn test_prompt_caching(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_audio_sent_once(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let audio = UserMessageContent::from(acp::ContentBlock::Audio(acp::AudioContent {
+        data: "UklGRg==".into(),
+        mime_type: "audio/wav".into(),
+        meta: Some(acp_thread::audio_analysis_meta("A dog barking")),
+        annotations: None,
+    }));
+    thread.update(cx, |thread, _| thread.add_tool(EchoTool));
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(
+                UserMessageId::new(),
+                [UserMessageContent::Text("Listen to this".into()), audio],
+                cx,
+            )
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    // The fake model can't listen to audio, so it gets the clip's analysis.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages[1].content,
+        vec![
+            "Listen to this".into(),
+            "The user attached an audio file, but the current model can't listen to it. \
+            This is an automated analysis of it:\n\nA dog barking"
+                .into(),
+        ]
+    );
+
+    let tool_use = LanguageModelToolUse {
+        id: "tool_1".into(),
+        name: EchoTool::name().into(),
+        raw_input: json!({"text": "test"}).to_string(),
+        input: json!({"text": "test"}),
+        is_input_complete: true,
+    };
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(tool_use));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // Once sent, the clip is replaced with its analysis.
+    let analysis = "The user attached an audio file earlier in the conversation. \
+        This is an automated analysis of it:\n\nA dog barking";
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages[1].content,
+        vec!["Listen to this".into(), analysis.into()]
+    );
+    thread.read_with(cx, |thread, _| {
+        let markdown = thread.to_markdown();
+        assert!(markdown.contains("A dog barking"));
+        assert!(!markdown.contains("<audio />"));
+    });
+}
+
 #[gpui::test]
 #[cfg_attr(not(feature = "e2e"), ignore)]
 async fn test_basic_tool_calls(cx: &mut TestAppContext) {
@@ -1262,6 +1327,82 @@ async fn test_mcp_tool_truncation(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_restrict_tools(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        context_server_store,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "profiles": {
+                    "test": {
+                        "name": "Test Profile",
+                        "enable_all_context_servers": true,
+                        "tools": {
+                            EchoTool::name(): true,
+                            DelayTool::name(): true,
+                        }
+                    },
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+    thread.update(cx, |thread, _| {
+        thread.set_profile(AgentProfileId("test".into()));
+        thread.add_tool(EchoTool);
+        thread.add_tool(DelayTool);
+    });
+    let _mcp_tool_calls = setup_context_server(
+        "test_server",
+        ["lookup", "search"]
+            .into_iter()
+            .map(|name| context_server::types::Tool {
+                name: name.into(),
+                description: None,
+                input_schema: serde_json::to_value(
+                    EchoTool.input_schema(LanguageModelToolSchemaFormat::JsonSchema),
+                )
+                .unwrap(),
+                output_schema: None,
+                annotations: None,
+            })
+            .collect(),
+        &context_server_store,
+        cx,
+    );
+    cx.run_until_parked();
+
+    // Context server tools outside the allowed list are hidden as well.
+    thread
+        .update(cx, |thread, cx| {
+            thread.restrict_tools(
+                [EchoTool::name().into(), "lookup".into()]
+                    .into_iter()
+                    .collect(),
+            );
+            thread.send(UserMessageId::new(), ["Hey"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        tool_names_for_completion(&completion),
+        vec!["echo", "lookup"]
+    );
+}
+
 #[gpui::test]
 #[cfg_attr(not(feature = "e2e"), ignore)]
 async fn test_cancellation(cx: &mut TestAppContext) {
@@ -1944,6 +2085,383 @@ async fn test_agent_connection(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_subagent_tool(cx: &mut TestAppContext) {
+    let (acp_thread, thread) = setup_native_thread(cx).await;
+    let model = thread.read_with(cx, |thread, _| thread.model().unwrap().clone());
+    let fake_model = model.as_fake();
+
+    let request = acp_thread.update(cx, |thread, cx| thread.send(vec!["Fix the bug".into()], cx));
+    cx.run_until_parked();
+    let input = json!({
+        "title": "Find the bug",
+        "prompt": "Find the function that crashes on empty input",
+        "tools": ["grep", "read_file"],
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "1".into(),
+            name: SubagentTool::name().into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The subagent starts from the task prompt alone, with only the tools it
+    // was given.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion
+            .tools
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect::<Vec<_>>(),
+        ["grep", "read_file"]
+    );
+    assert_eq!(
+        completion.messages.last().unwrap().string_contents(),
+        "Find the function that crashes on empty input"
+    );
+    let subagent = acp_thread.read_with(cx, |thread, _| {
+        let Some(AgentThreadEntry::ToolCall(tool_call)) = thread.entries().last() else {
+            panic!("expected a tool call");
+        };
+        tool_call.subagents().next().unwrap().clone()
+    });
+
+    fake_model.send_last_completion_stream_text_chunk("It's `parse` in src/main.rs");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 20,
+            ..Default::default()
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // Only the subagent's answer is sent back to the parent.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let Some(MessageContent::ToolResult(tool_result)) =
+        completion.messages.last().unwrap().content.first()
+    else {
+        panic!("expected a tool result");
+    };
+    assert_eq!(tool_result.content, "It's `parse` in src/main.rs".into());
+    subagent.read_with(cx, |subagent, cx| {
+        assert!(
+            subagent
+                .to_markdown(cx)
+                .contains("It's `parse` in src/main.rs")
+        )
+    });
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.cumulative_token_usage().total_tokens(), 120);
+    });
+
+    fake_model.send_last_completion_stream_text_chunk("Fixed it.");
+    fake_model.end_last_completion_stream();
+    request.await.unwrap();
+}
+
+#[gpui::test]
+async fn test_subagent_token_usage_when_cancelled(cx: &mut TestAppContext) {
+    let (acp_thread, thread) = setup_native_thread(cx).await;
+    let model = thread.read_with(cx, |thread, _| thread.model().unwrap().clone());
+    let fake_model = model.as_fake();
+
+    let request = acp_thread.update(cx, |thread, cx| thread.send(vec!["Fix the bug".into()], cx));
+    cx.run_until_parked();
+    let input = json!({
+        "title": "Find the bug",
+        "prompt": "Find the function that crashes on empty input",
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "1".into(),
+            name: SubagentTool::name().into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_text_chunk("Looking at");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 20,
+            ..Default::default()
+        },
+    ));
+    cx.run_until_parked();
+
+    // The tokens the subagent spent before the turn was cancelled still count
+    // towards the parent.
+    acp_thread.update(cx, |thread, cx| thread.cancel(cx)).await;
+    cx.run_until_parked();
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.cumulative_token_usage().total_tokens(), 120);
+    });
+    request.await.unwrap();
+}
+
+#[gpui::test]
+async fn test_subagent_authorizations(cx: &mut TestAppContext) {
+    let (acp_thread, thread) = setup_native_thread(cx).await;
+    let model = thread.read_with(cx, |thread, _| thread.model().unwrap().clone());
+    let fake_model = model.as_fake();
+
+    let request = acp_thread.update(cx, |thread, cx| {
+        thread.send(vec!["Read the docs".into()], cx)
+    });
+    cx.run_until_parked();
+    let input = json!({
+        "title": "Read the docs",
+        "prompt": "Summarize the docs at a.com and b.com",
+        "tools": ["fetch"],
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "1".into(),
+            name: SubagentTool::name().into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The subagent asks for both fetches at once, but the tool call that
+    // spawned it asks the user about them one at a time.
+    for (id, url) in [("2", "https://a.com"), ("3", "https://b.com")] {
+        let input = json!({ "url": url });
+        fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: id.into(),
+                name: FetchTool::name().into(),
+                raw_input: input.to_string(),
+                input,
+                is_input_complete: true,
+            },
+        ));
+    }
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    for url in ["https://a.com", "https://b.com"] {
+        let (id, title, option) = acp_thread.read_with(cx, |thread, cx| {
+            let Some(AgentThreadEntry::ToolCall(tool_call)) = thread.entries().last() else {
+                panic!("expected a tool call");
+            };
+            let ToolCallStatus::WaitingForConfirmation { options, .. } = &tool_call.status else {
+                panic!("expected the tool call to wait for authorization");
+            };
+            let option = options
+                .iter()
+                .find(|option| matches!(option.kind, acp::PermissionOptionKind::AllowOnce))
+                .unwrap()
+                .clone();
+            (
+                tool_call.id.clone(),
+                tool_call.label.read(cx).source().to_string(),
+                option,
+            )
+        });
+        assert_eq!(title, format!("Read the docs wants to: {url}"));
+        acp_thread.update(cx, |thread, cx| {
+            thread.authorize_tool_call(id, option.id, option.kind, cx)
+        });
+        cx.run_until_parked();
+    }
+
+    // Both fetches ran once they were allowed.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let tool_results = completion
+        .messages
+        .last()
+        .unwrap()
+        .content
+        .iter()
+        .filter(|content| matches!(content, MessageContent::ToolResult(_)))
+        .count();
+    assert_eq!(tool_results, 2);
+
+    fake_model.send_last_completion_stream_text_chunk("Neither page exists.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("The docs are missing.");
+    fake_model.end_last_completion_stream();
+    request.await.unwrap();
+}
+
+/// Creates a thread of the native agent with the fake model, along with the
+/// ACP thread that shows it.
+async fn setup_native_thread(cx: &mut TestAppContext) -> (Entity<AcpThread>, Entity<Thread>) {
+    cx.update(settings::init);
+    cx.update(|cx| {
+        gpui_tokio::init(cx);
+        client::init_settings(cx);
+
+        let http_client = FakeHttpClient::with_404_response();
+        let clock = Arc::new(clock::FakeSystemClock::new());
+        let client = Client::new(clock, http_client, cx);
+        let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+        language_model::init(client.clone(), cx);
+        language_models::init(user_store, client.clone(), cx);
+        Project::init_settings(cx);
+        LanguageModelRegistry::test(cx);
+        agent_settings::init(cx);
+    });
+
+    let fake_fs = cx.update(|cx| fs::FakeFs::new(cx.background_executor().clone()));
+    fake_fs.insert_tree(path!("/test"), json!({})).await;
+    let project = Project::test(fake_fs.clone(), [Path::new("/test")], cx).await;
+    let context_store = cx.new(|cx| assistant_context::ContextStore::fake(project.clone(), cx));
+    let history_store = cx.new(|cx| HistoryStore::new(context_store, cx));
+    let agent = NativeAgent::new(
+        project.clone(),
+        history_store,
+        Templates::new(),
+        None,
+        fake_fs.clone(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    let connection = Rc::new(NativeAgentConnection(agent.clone()));
+    let acp_thread = cx
+        .update(|cx| {
+            connection
+                .clone()
+                .new_thread(project, Path::new("/test"), cx)
+        })
+        .await
+        .unwrap();
+    let session_id = acp_thread.read_with(cx, |thread, _| thread.session_id().clone());
+    let thread = cx.update(|cx| connection.thread(&session_id, cx)).unwrap();
+    (acp_thread, thread)
+}
+
+#[gpui::test]
+async fn test_update_plan(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        project_context,
+        context_server_store,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    thread.update(cx, |thread, cx| {
+        thread.add_tool(UpdatePlanTool::new(cx.weak_entity()))
+    });
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Write a song"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let input = json!({
+        "entries": [
+            { "step": "Write the verse", "status": "in_progress" },
+            { "step": "Write the chorus", "status": "pending", "priority": "high" },
+        ]
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "1".into(),
+            name: UpdatePlanTool::name().into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let expected_plan = [
+        (
+            "Write the verse".to_string(),
+            acp::PlanEntryStatus::InProgress,
+            acp::PlanEntryPriority::Medium,
+        ),
+        (
+            "Write the chorus".to_string(),
+            acp::PlanEntryStatus::Pending,
+            acp::PlanEntryPriority::High,
+        ),
+    ];
+    let plan = loop {
+        if let ThreadEvent::Plan(plan) = events.next().await.unwrap().unwrap() {
+            break plan;
+        }
+    };
+    assert_eq!(plan_summary(&plan.entries), expected_plan);
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(plan_summary(thread.plan()), expected_plan)
+    });
+
+    fake_model.send_last_completion_stream_text_chunk("Starting on the verse.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The plan is saved with the thread, and shown again when it's reopened.
+    let db_thread = thread.read_with(cx, |thread, cx| thread.to_db(cx)).await;
+    let db_thread: DbThread =
+        serde_json::from_value(serde_json::to_value(&db_thread).unwrap()).unwrap();
+    assert_eq!(plan_summary(&db_thread.plan), expected_plan);
+    let project = thread.read_with(cx, |thread, _| thread.project().clone());
+    let context_server_registry =
+        cx.new(|cx| ContextServerRegistry::new(context_server_store.clone(), cx));
+    let action_log = cx.new(|_| ActionLog::new(project.clone()));
+    let restored = cx.new(|cx| {
+        Thread::from_db(
+            acp::SessionId("restored".into()),
+            db_thread,
+            project,
+            project_context,
+            context_server_registry,
+            action_log,
+            Templates::new(),
+            cx,
+        )
+    });
+    restored.read_with(cx, |thread, _| {
+        assert_eq!(plan_summary(thread.plan()), expected_plan)
+    });
+    let events = restored
+        .update(cx, |thread, cx| thread.replay(cx))
+        .collect::<Vec<_>>()
+        .await;
+    let Some(Ok(ThreadEvent::Plan(plan))) = events.last() else {
+        panic!("expected the plan to be replayed last");
+    };
+    assert_eq!(plan_summary(&plan.entries), expected_plan);
+}
+
+fn plan_summary(
+    entries: &[acp::PlanEntry],
+) -> Vec<(String, acp::PlanEntryStatus, acp::PlanEntryPriority)> {
+    entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.content.clone(),
+                entry.status.clone(),
+                entry.priority.clone(),
+            )
+        })
+        .collect()
+}
+
 #[gpui::test]
 async fn test_tool_updates_to_completion(cx: &mut TestAppContext) {
     let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
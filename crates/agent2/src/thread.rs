@@ -1,10 +1,14 @@
+use crate::hooks::{ToolCallHooks, append_hook_output, apply_post_tool_use};
 use crate::{
-    ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
-    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ReadFileTool, SystemPromptTemplate,
-    Template, Templates, TerminalTool, ThinkingTool, WebSearchTool,
+    BackgroundProcesses, ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel,
+    DbThread, DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool,
+    FindReferencesTool, GenerateImageTool, GenerateMidiTool, GoToDefinitionTool, GrepTool,
+    HoverTool, KillProcessTool, ListDirectoryTool, MovePathTool, NowTool, OpenTool,
+    ProcessInputTool, ProcessOutputTool, ReadFileTool, RenameSymbolTool, SearchSamplesTool,
+    SubagentTool, SystemPromptTemplate, Template, Templates, TerminalTool, ThinkingTool,
+    UpdatePlanTool, WebSearchTool, WorkspaceSymbolsTool,
 };
-use acp_thread::{MentionUri, UserMessageId};
+use acp_thread::{AcpThread, MentionUri, UserMessageId};
 use action_log::ActionLog;
 use agent::thread::{GitState, ProjectSnapshot, WorktreeSnapshot};
 use agent_client_protocol as acp;
@@ -28,15 +32,17 @@ use futures::{
 };
 use git::repository::DiffType;
 use gpui::{
-    App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Task, WeakEntity,
+    App, AppContext, AsyncApp, Context, Entity, EventEmitter, Image, SharedString, Task, WeakEntity,
 };
 use language_model::{
-    LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelExt,
-    LanguageModelImage, LanguageModelProviderId, LanguageModelRegistry, LanguageModelRequest,
-    LanguageModelRequestMessage, LanguageModelRequestTool, LanguageModelToolResult,
-    LanguageModelToolResultContent, LanguageModelToolSchemaFormat, LanguageModelToolUse,
-    LanguageModelToolUseId, Role, SelectedModel, StopReason, TokenUsage, ZED_CLOUD_PROVIDER_ID,
+    LanguageModel, LanguageModelAudio, LanguageModelAudioFormat, LanguageModelCompletionError,
+    LanguageModelCompletionEvent, LanguageModelExt, LanguageModelImage, LanguageModelProviderId,
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    LanguageModelRequestTool, LanguageModelToolResult, LanguageModelToolResultContent,
+    LanguageModelToolSchemaFormat, LanguageModelToolUse, LanguageModelToolUseId, Role,
+    SelectedModel, StopReason, TokenUsage, ZED_CLOUD_PROVIDER_ID,
 };
+use midi::MidiPattern;
 use project::{
     Project,
     git_store::{GitStore, RepositoryState},
@@ -55,6 +61,7 @@ use std::{
     time::{Duration, Instant},
 };
 use std::{fmt::Write, path::PathBuf};
+use task::{TaskContext, VariableName};
 use util::{ResultExt, debug_panic, markdown::MarkdownCodeBlock};
 use uuid::Uuid;
 
@@ -148,6 +155,30 @@ pub enum UserMessageContent {
     Text(String),
     Mention { uri: MentionUri, content: String },
     Image(LanguageModelImage),
+    Audio(UserMessageAudio),
+}
+
+/// An audio clip the user attached, which is only sent to the model once.
+/// After that it's replaced in the thread with its analysis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserMessageAudio {
+    #[serde(flatten)]
+    pub audio: LanguageModelAudio,
+    #[serde(default)]
+    pub analysis: Option<String>,
+}
+
+impl UserMessageAudio {
+    /// What the clip is replaced with once it has been sent.
+    fn to_history_text(&self) -> String {
+        match &self.analysis {
+            Some(analysis) => format!(
+                "The user attached an audio file earlier in the conversation. \
+                This is an automated analysis of it:\n\n{analysis}"
+            ),
+            None => "[The user attached an audio file earlier in the conversation]".to_string(),
+        }
+    }
 }
 
 impl UserMessage {
@@ -163,6 +194,9 @@ impl UserMessage {
                 UserMessageContent::Image(_) => {
                     markdown.push_str("<image />\n");
                 }
+                UserMessageContent::Audio(_) => {
+                    markdown.push_str("<audio />\n");
+                }
                 UserMessageContent::Mention { uri, content } => {
                     if !content.is_empty() {
                         let _ = writeln!(&mut markdown, "{}\n\n{}", uri.as_link(), content);
@@ -212,6 +246,9 @@ impl UserMessage {
                 UserMessageContent::Image(value) => {
                     language_model::MessageContent::Image(value.clone())
                 }
+                UserMessageContent::Audio(value) => {
+                    language_model::MessageContent::Audio(value.audio.clone())
+                }
                 UserMessageContent::Mention { uri, content } => {
                     match uri {
                         MentionUri::File { abs_path } => {
@@ -528,7 +565,13 @@ pub enum AgentMessageContent {
 pub trait TerminalHandle {
     fn id(&self, cx: &AsyncApp) -> Result<acp::TerminalId>;
     fn current_output(&self, cx: &AsyncApp) -> Result<acp::TerminalOutputResponse>;
+    /// The output that's still in the terminal, and the position it starts at
+    /// in all the output the command wrote, which grows as older output
+    /// scrolls out of the terminal's history.
+    fn retained_output(&self, cx: &mut AsyncApp) -> Result<(String, usize)>;
     fn wait_for_exit(&self, cx: &AsyncApp) -> Result<Shared<Task<acp::TerminalExitStatus>>>;
+    fn write_input(&self, input: String, cx: &mut AsyncApp) -> Result<()>;
+    fn kill(&self, cx: &mut AsyncApp) -> Result<()>;
 }
 
 pub trait ThreadEnvironment {
@@ -539,6 +582,10 @@ pub trait ThreadEnvironment {
         output_byte_limit: Option<u64>,
         cx: &mut AsyncApp,
     ) -> Task<Result<Rc<dyn TerminalHandle>>>;
+
+    /// Registers a thread created by the subagent tool as its own session, so
+    /// that it can be prompted and displayed like any other thread.
+    fn create_subagent(&self, thread: Entity<Thread>, cx: &mut App) -> Result<Entity<AcpThread>>;
 }
 
 #[derive(Debug)]
@@ -549,6 +596,7 @@ pub enum ThreadEvent {
     ToolCall(acp::ToolCall),
     ToolCallUpdate(acp_thread::ToolCallUpdate),
     ToolCallAuthorization(ToolCallAuthorization),
+    Plan(acp::Plan),
     Retry(acp_thread::RetryStatus),
     Stop(acp::StopReason),
 }
@@ -586,6 +634,8 @@ pub struct Thread {
     pending_title_generation: Option<Task<()>>,
     summary: Option<SharedString>,
     messages: Vec<Message>,
+    /// The task list the model maintains with the `update_plan` tool.
+    plan: Vec<acp::PlanEntry>,
     user_store: Entity<UserStore>,
     completion_mode: CompletionMode,
     /// Holds the task that handles agent interaction until the end of the turn.
@@ -594,14 +644,20 @@ pub struct Thread {
     running_turn: Option<RunningTurn>,
     pending_message: Option<AgentMessage>,
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
+    /// When set, the only tools this thread may use, including those of
+    /// context servers.
+    tool_allowlist: Option<HashSet<SharedString>>,
     tool_use_limit_reached: bool,
     request_token_usage: HashMap<UserMessageId, language_model::TokenUsage>,
-    #[allow(unused)]
+    /// The tokens spent on every request in this thread, including the
+    /// requests of its subagents.
     cumulative_token_usage: TokenUsage,
     #[allow(unused)]
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     context_server_registry: Entity<ContextServerRegistry>,
     profile_id: AgentProfileId,
+    /// Whether this thread was spawned by another thread's subagent tool.
+    is_subagent: bool,
     project_context: Entity<ProjectContext>,
     templates: Arc<Templates>,
     model: Option<Arc<dyn LanguageModel>>,
@@ -615,10 +671,11 @@ pub struct Thread {
 impl Thread {
     fn prompt_capabilities(model: Option<&dyn LanguageModel>) -> acp::PromptCapabilities {
         let image = model.map_or(true, |model| model.supports_images());
+        let audio = model.map_or(false, |model| model.supports_audio());
         acp::PromptCapabilities {
             meta: None,
             image,
-            audio: false,
+            audio,
             embedded_context: true,
         }
     }
@@ -643,11 +700,13 @@ impl Thread {
             pending_title_generation: None,
             summary: None,
             messages: Vec::new(),
+            plan: Vec::new(),
             user_store: project.read(cx).user_store(),
             completion_mode: AgentSettings::get_global(cx).preferred_completion_mode,
             running_turn: None,
             pending_message: None,
             tools: BTreeMap::default(),
+            tool_allowlist: None,
             tool_use_limit_reached: false,
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
@@ -659,6 +718,7 @@ impl Thread {
             },
             context_server_registry,
             profile_id,
+            is_subagent: false,
             project_context,
             templates,
             model,
@@ -705,6 +765,9 @@ impl Thread {
                 Message::Resume => {}
             }
         }
+        if !self.plan.is_empty() {
+            stream.send_plan(self.plan.clone());
+        }
         rx
     }
 
@@ -823,17 +886,20 @@ impl Thread {
             pending_title_generation: None,
             summary: db_thread.detailed_summary,
             messages: db_thread.messages,
+            plan: db_thread.plan,
             user_store: project.read(cx).user_store(),
             completion_mode: db_thread.completion_mode.unwrap_or_default(),
             running_turn: None,
             pending_message: None,
             tools: BTreeMap::default(),
+            tool_allowlist: None,
             tool_use_limit_reached: false,
             request_token_usage: db_thread.request_token_usage.clone(),
             cumulative_token_usage: db_thread.cumulative_token_usage,
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
             context_server_registry,
             profile_id,
+            is_subagent: false,
             project_context,
             templates,
             model,
@@ -862,6 +928,7 @@ impl Thread {
             }),
             completion_mode: Some(self.completion_mode),
             profile: Some(self.profile_id.clone()),
+            plan: self.plan.clone(),
         };
 
         cx.background_spawn(async move {
@@ -987,6 +1054,40 @@ impl Thread {
         self.messages.is_empty() && self.title.is_none()
     }
 
+    pub fn is_subagent(&self) -> bool {
+        self.is_subagent
+    }
+
+    /// Creates a thread that works on a task delegated by this one, sharing
+    /// its project, context and action log so that its edits can be reviewed
+    /// alongside this thread's.
+    pub fn new_subagent(
+        &self,
+        profile_id: AgentProfileId,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) -> Entity<Thread> {
+        let project = self.project.clone();
+        let project_context = self.project_context.clone();
+        let context_server_registry = self.context_server_registry.clone();
+        let templates = self.templates.clone();
+        let action_log = self.action_log.clone();
+        cx.new(|cx| {
+            let mut thread = Thread::new(
+                project,
+                project_context,
+                context_server_registry,
+                templates,
+                Some(model),
+                cx,
+            );
+            thread.profile_id = profile_id;
+            thread.is_subagent = true;
+            thread.action_log = action_log;
+            thread
+        })
+    }
+
     pub fn model(&self) -> Option<&Arc<dyn LanguageModel>> {
         self.model.as_ref()
     }
@@ -1045,6 +1146,7 @@ impl Thread {
         cx: &mut Context<Self>,
     ) {
         let language_registry = self.project.read(cx).languages().clone();
+        let background_processes = BackgroundProcesses::default();
         self.add_tool(CopyPathTool::new(self.project.clone()));
         self.add_tool(CreateDirectoryTool::new(self.project.clone()));
         self.add_tool(DeletePathTool::new(
@@ -1059,18 +1161,45 @@ impl Thread {
         ));
         self.add_tool(FetchTool::new(self.project.read(cx).client().http_client()));
         self.add_tool(FindPathTool::new(self.project.clone()));
+        self.add_tool(FindReferencesTool::new(self.project.clone()));
+        self.add_tool(GenerateImageTool::new(
+            self.project.clone(),
+            self.action_log.clone(),
+            self.project.read(cx).client().http_client(),
+        ));
+        self.add_tool(GenerateMidiTool::new(
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
+        self.add_tool(GoToDefinitionTool::new(self.project.clone()));
         self.add_tool(GrepTool::new(self.project.clone()));
+        self.add_tool(HoverTool::new(self.project.clone()));
+        self.add_tool(KillProcessTool::new(background_processes.clone()));
         self.add_tool(ListDirectoryTool::new(self.project.clone()));
         self.add_tool(MovePathTool::new(self.project.clone()));
         self.add_tool(NowTool);
         self.add_tool(OpenTool::new(self.project.clone()));
+        self.add_tool(ProcessInputTool::new(background_processes.clone()));
+        self.add_tool(ProcessOutputTool::new(background_processes.clone()));
         self.add_tool(ReadFileTool::new(
             self.project.clone(),
             self.action_log.clone(),
         ));
-        self.add_tool(TerminalTool::new(self.project.clone(), environment));
+        self.add_tool(RenameSymbolTool::new(
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
+        self.add_tool(SearchSamplesTool::new(self.project.clone()));
+        self.add_tool(SubagentTool::new(cx.weak_entity(), environment.clone()));
+        self.add_tool(TerminalTool::new(
+            self.project.clone(),
+            environment,
+            background_processes,
+        ));
         self.add_tool(ThinkingTool);
+        self.add_tool(UpdatePlanTool::new(cx.weak_entity()));
         self.add_tool(WebSearchTool);
+        self.add_tool(WorkspaceSymbolsTool::new(self.project.clone()));
     }
 
     pub fn add_tool<T: AgentTool>(&mut self, tool: T) {
@@ -1081,6 +1210,20 @@ impl Thread {
         self.tools.remove(name).is_some()
     }
 
+    /// Limits the tools of this thread, built-in or from context servers, to
+    /// the given names.
+    pub fn restrict_tools(&mut self, names: HashSet<SharedString>) {
+        self.tools.retain(|name, _| names.contains(name));
+        self.tool_allowlist = Some(names);
+    }
+
+    pub fn has_context_server_tool(&self, name: &str, cx: &App) -> bool {
+        self.context_server_registry
+            .read(cx)
+            .servers()
+            .any(|(_, tools)| tools.contains_key(name))
+    }
+
     pub fn profile(&self) -> &AgentProfileId {
         &self.profile_id
     }
@@ -1089,6 +1232,15 @@ impl Thread {
         self.profile_id = profile_id;
     }
 
+    pub fn plan(&self) -> &[acp::PlanEntry] {
+        &self.plan
+    }
+
+    pub fn set_plan(&mut self, entries: Vec<acp::PlanEntry>, cx: &mut Context<Self>) {
+        self.plan = entries;
+        cx.notify();
+    }
+
     pub fn cancel(&mut self, cx: &mut Context<Self>) {
         if let Some(running_turn) = self.running_turn.take() {
             running_turn.cancel();
@@ -1096,6 +1248,15 @@ impl Thread {
         self.flush_pending_message(cx);
     }
 
+    pub fn cumulative_token_usage(&self) -> TokenUsage {
+        self.cumulative_token_usage
+    }
+
+    pub fn add_subagent_token_usage(&mut self, usage: TokenUsage, cx: &mut Context<Self>) {
+        self.cumulative_token_usage = self.cumulative_token_usage + usage;
+        cx.notify();
+    }
+
     fn update_token_usage(&mut self, update: language_model::TokenUsage, cx: &mut Context<Self>) {
         let Some(last_user_message) = self.last_user_message() else {
             return;
@@ -1259,10 +1420,15 @@ impl Thread {
                 Err(err) => (stream::empty().boxed(), Some(err)),
             };
             let mut tool_results = FuturesUnordered::new();
+            // Usage updates report the total for the request so far.
+            let mut request_usage = None;
             while let Some(event) = events.next().await {
                 log::trace!("Received completion event: {:?}", event);
                 match event {
                     Ok(event) => {
+                        if let LanguageModelCompletionEvent::UsageUpdate(usage) = &event {
+                            request_usage = Some(*usage);
+                        }
                         tool_results.extend(this.update(cx, |this, cx| {
                             this.handle_completion_event(event, event_stream, cx)
                         })??);
@@ -1274,6 +1440,15 @@ impl Thread {
                 }
             }
 
+            if let Some(usage) = request_usage {
+                this.update(cx, |this, _cx| {
+                    this.cumulative_token_usage = this.cumulative_token_usage + usage;
+                })?;
+            }
+            if error.is_none() {
+                this.update(cx, |this, _| this.replace_sent_audio())?;
+            }
+
             let end_turn = tool_results.is_empty();
             while let Some(tool_result) = tool_results.next().await {
                 log::debug!("Tool finished {:?}", tool_result);
@@ -1584,7 +1759,23 @@ impl Thread {
             ..Default::default()
         });
         let supports_images = self.model().is_some_and(|model| model.supports_images());
-        let tool_result = tool.run(tool_use.input, tool_event_stream, cx);
+        let hooks = Arc::new(self.tool_call_hooks(&tool_use.name, &tool, cx));
+        let executor = cx.background_executor().clone();
+        let tool_result = if hooks.has_pre_tool_use() {
+            let hooks = hooks.clone();
+            let executor = executor.clone();
+            let input = tool_use.input.clone();
+            cx.spawn(async move |_, cx| {
+                let runs = hooks.run_pre_tool_use(&input, &executor).await?;
+                let mut output = cx
+                    .update(|cx| tool.run(input, tool_event_stream, cx))?
+                    .await?;
+                append_hook_output(&mut output.llm_output, &runs);
+                Ok(output)
+            })
+        } else {
+            tool.run(tool_use.input.clone(), tool_event_stream, cx)
+        };
         log::debug!("Running tool {}", tool_use.name);
         Some(cx.foreground_executor().spawn(async move {
             let tool_result = tool_result.await.and_then(|output| {
@@ -1598,7 +1789,7 @@ impl Thread {
                 Ok(output)
             });
 
-            match tool_result {
+            let mut result = match tool_result {
                 Ok(output) => LanguageModelToolResult {
                     tool_use_id: tool_use.id,
                     tool_name: tool_use.name,
@@ -1613,10 +1804,92 @@ impl Thread {
                     content: LanguageModelToolResultContent::Text(Arc::from(error.to_string())),
                     output: Some(error.to_string().into()),
                 },
+            };
+            if hooks.has_post_tool_use() {
+                let runs = hooks
+                    .run_post_tool_use(&tool_use.input, &result.content, result.is_error, &executor)
+                    .await;
+                apply_post_tool_use(&mut result, &runs);
             }
+            result
         }))
     }
 
+    /// Collects the hooks of the current profile that apply to a call of the
+    /// given tool. Hooks only run for local projects, since their commands
+    /// run on this machine.
+    fn tool_call_hooks(
+        &self,
+        tool_name: &Arc<str>,
+        tool: &Arc<dyn AnyAgentTool>,
+        cx: &App,
+    ) -> ToolCallHooks {
+        let project = self.project.read(cx);
+        let Some(profile) = AgentSettings::get_global(cx).profiles.get(&self.profile_id) else {
+            return ToolCallHooks::empty(tool_name.clone());
+        };
+        if profile.hooks.is_empty() || !project.is_local() {
+            return ToolCallHooks::empty(tool_name.clone());
+        }
+
+        let context_server = if self.tools.contains_key(tool_name.as_ref()) {
+            None
+        } else {
+            self.context_server_registry
+                .read(cx)
+                .servers()
+                .find_map(|(server_id, tools)| {
+                    tools
+                        .values()
+                        .any(|server_tool| Arc::ptr_eq(server_tool, tool))
+                        .then(|| server_id.0.clone())
+                })
+        };
+        let matching_name = match &context_server {
+            Some(_) => tool.name(),
+            None => SharedString::from(tool_name.clone()),
+        };
+        let hooks = profile
+            .hooks
+            .iter()
+            .filter(|hook| hook.matches(&matching_name, context_server.as_deref()))
+            .cloned()
+            .collect::<Vec<_>>();
+        let worktree = project
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx));
+        let worktree_id = worktree.map(|worktree| worktree.id());
+        let cwd = worktree.map(|worktree| worktree.abs_path().to_path_buf());
+        let task_context = TaskContext {
+            cwd: cwd.clone(),
+            task_variables: cwd
+                .iter()
+                .map(|cwd| {
+                    (
+                        VariableName::WorktreeRoot,
+                        cwd.to_string_lossy().into_owned(),
+                    )
+                })
+                .collect(),
+            project_env: Default::default(),
+        };
+        let inventory = project.task_store().read(cx).task_inventory().cloned();
+        ToolCallHooks::new(tool_name.clone(), hooks, cwd, |label| {
+            let Some(inventory) = &inventory else {
+                return Task::ready(None);
+            };
+            let template = inventory
+                .read(cx)
+                .task_template_by_label(None, worktree_id, label, cx);
+            let task_context = task_context.clone();
+            cx.background_spawn(async move {
+                let task = template.await?.resolve_task("agent_hook", &task_context)?;
+                Some(task.resolved)
+            })
+        })
+    }
+
     fn handle_tool_use_json_parse_error_event(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -1669,6 +1942,7 @@ impl Thread {
         for message in &self.messages {
             request.messages.extend(message.to_request());
         }
+        self.replace_unsupported_audio(&mut request.messages, &model);
 
         request.messages.push(LanguageModelRequestMessage {
             role: Role::User,
@@ -1727,6 +2001,7 @@ impl Thread {
         for message in &self.messages {
             request.messages.extend(message.to_request());
         }
+        self.replace_unsupported_audio(&mut request.messages, &model);
 
         request.messages.push(LanguageModelRequestMessage {
             role: Role::User,
@@ -1855,7 +2130,8 @@ impl Thread {
         log::debug!("Completion intent: {:?}", completion_intent);
         log::debug!("Completion mode: {:?}", self.completion_mode);
 
-        let messages = self.build_request_messages(cx);
+        let mut messages = self.build_request_messages(cx);
+        self.replace_unsupported_audio(&mut messages, model);
         log::debug!("Request will include {} messages", messages.len());
         log::debug!("Request includes {} tools", tools.len());
 
@@ -1896,7 +2172,7 @@ impl Thread {
             .tools
             .iter()
             .filter_map(|(tool_name, tool)| {
-                if tool.supported_provider(&model.provider_id())
+                if tool.supported_provider(&model.provider_id(), cx)
                     && profile.is_tool_enabled(tool_name)
                 {
                     Some((truncate(tool_name), tool.clone()))
@@ -1911,7 +2187,12 @@ impl Thread {
         let mut duplicate_tool_names = HashSet::default();
         for (server_id, server_tools) in self.context_server_registry.read(cx).servers() {
             for (tool_name, tool) in server_tools {
-                if profile.is_context_server_tool_enabled(&server_id.0, &tool_name) {
+                if profile.is_context_server_tool_enabled(&server_id.0, &tool_name)
+                    && self
+                        .tool_allowlist
+                        .as_ref()
+                        .is_none_or(|allowlist| allowlist.contains(tool_name))
+                {
                     let tool_name = truncate(tool_name);
                     if !seen_tools.insert(tool_name.clone()) {
                         duplicate_tool_names.insert(tool_name.clone());
@@ -1948,6 +2229,65 @@ impl Thread {
         self.running_turn.as_ref()?.tools.get(name).cloned()
     }
 
+    /// Replaces the audio clips the model has been sent with their analysis,
+    /// so that they aren't sent again with every request.
+    fn replace_sent_audio(&mut self) {
+        for message in &mut self.messages {
+            let Message::User(message) = message else {
+                continue;
+            };
+            for content in &mut message.content {
+                if let UserMessageContent::Audio(audio) = content {
+                    *content = UserMessageContent::Text(audio.to_history_text());
+                }
+            }
+        }
+    }
+
+    /// Replaces audio with its analysis when the model can't listen to it,
+    /// which happens after switching to a model without audio support in the
+    /// middle of a thread.
+    fn replace_unsupported_audio(
+        &self,
+        messages: &mut [LanguageModelRequestMessage],
+        model: &Arc<dyn LanguageModel>,
+    ) {
+        if model.supports_audio() {
+            return;
+        }
+        let analyses = self
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::User(message) => Some(&message.content),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|content| match content {
+                UserMessageContent::Audio(audio) => {
+                    Some((&audio.audio.source, audio.analysis.as_deref()))
+                }
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+        for content in messages.iter_mut().flat_map(|message| &mut message.content) {
+            let language_model::MessageContent::Audio(audio) = content else {
+                continue;
+            };
+            let text = match analyses.get(&audio.source).copied().flatten() {
+                Some(analysis) => format!(
+                    "The user attached an audio file, but the current model can't listen to \
+                    it. This is an automated analysis of it:\n\n{analysis}"
+                ),
+                None => {
+                    "[Audio attachment omitted: the current model does not support audio input]"
+                        .to_string()
+                }
+            };
+            *content = language_model::MessageContent::Text(text);
+        }
+    }
+
     fn build_request_messages(&self, cx: &App) -> Vec<LanguageModelRequestMessage> {
         log::trace!(
             "Building request messages from {} thread messages",
@@ -2172,7 +2512,7 @@ where
 
     /// Some tools rely on a provider for the underlying billing or other reasons.
     /// Allow the tool to check if they are compatible, or should be filtered out.
-    fn supported_provider(&self, _provider: &LanguageModelProviderId) -> bool {
+    fn supported_provider(&self, _provider: &LanguageModelProviderId, _cx: &App) -> bool {
         true
     }
 
@@ -2213,7 +2553,7 @@ pub trait AnyAgentTool {
     fn kind(&self) -> acp::ToolKind;
     fn initial_title(&self, input: serde_json::Value, _cx: &mut App) -> SharedString;
     fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value>;
-    fn supported_provider(&self, _provider: &LanguageModelProviderId) -> bool {
+    fn supported_provider(&self, _provider: &LanguageModelProviderId, _cx: &App) -> bool {
         true
     }
     fn run(
@@ -2258,8 +2598,8 @@ where
         Ok(json)
     }
 
-    fn supported_provider(&self, provider: &LanguageModelProviderId) -> bool {
-        self.0.supported_provider(provider)
+    fn supported_provider(&self, provider: &LanguageModelProviderId, cx: &App) -> bool {
+        self.0.supported_provider(provider, cx)
     }
 
     fn run(
@@ -2369,6 +2709,15 @@ impl ThreadEventStream {
             .ok();
     }
 
+    fn send_plan(&self, entries: Vec<acp::PlanEntry>) {
+        self.0
+            .unbounded_send(Ok(ThreadEvent::Plan(acp::Plan {
+                entries,
+                meta: None,
+            })))
+            .ok();
+    }
+
     fn send_retry(&self, status: acp_thread::RetryStatus) {
         self.0.unbounded_send(Ok(ThreadEvent::Retry(status))).ok();
     }
@@ -2435,6 +2784,49 @@ impl ToolCallEventStream {
             .ok();
     }
 
+    pub fn update_piano_roll(&self, pattern: Entity<MidiPattern>) {
+        self.stream
+            .0
+            .unbounded_send(Ok(ThreadEvent::ToolCallUpdate(
+                acp_thread::ToolCallUpdatePianoRoll {
+                    id: acp::ToolCallId(self.tool_use_id.to_string().into()),
+                    pattern,
+                }
+                .into(),
+            )))
+            .ok();
+    }
+
+    pub fn update_image(&self, image: Entity<Image>) {
+        self.stream
+            .0
+            .unbounded_send(Ok(ThreadEvent::ToolCallUpdate(
+                acp_thread::ToolCallUpdateImage {
+                    id: acp::ToolCallId(self.tool_use_id.to_string().into()),
+                    image,
+                }
+                .into(),
+            )))
+            .ok();
+    }
+
+    pub fn update_subagent(&self, thread: Entity<AcpThread>) {
+        self.stream
+            .0
+            .unbounded_send(Ok(ThreadEvent::ToolCallUpdate(
+                acp_thread::ToolCallUpdateSubagent {
+                    id: acp::ToolCallId(self.tool_use_id.to_string().into()),
+                    thread,
+                }
+                .into(),
+            )))
+            .ok();
+    }
+
+    pub fn update_plan(&self, entries: Vec<acp::PlanEntry>) {
+        self.stream.send_plan(entries);
+    }
+
     pub fn authorize(&self, title: impl Into<String>, cx: &mut App) -> Task<Result<()>> {
         if agent_settings::AgentSettings::get_global(cx).always_allow_tool_actions {
             return Task::ready(Ok(()));
@@ -2537,6 +2929,30 @@ impl ToolCallEventStreamReceiver {
         }
     }
 
+    pub async fn expect_piano_roll(&mut self) -> Entity<MidiPattern> {
+        let event = self.0.next().await;
+        if let Some(Ok(ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdatePianoRoll(
+            update,
+        )))) = event
+        {
+            update.pattern
+        } else {
+            panic!("Expected piano roll but got: {:?}", event);
+        }
+    }
+
+    pub async fn expect_image(&mut self) -> Entity<Image> {
+        let event = self.0.next().await;
+        if let Some(Ok(ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateImage(
+            update,
+        )))) = event
+        {
+            update.image
+        } else {
+            panic!("Expected image but got: {:?}", event);
+        }
+    }
+
     pub async fn expect_terminal(&mut self) -> Entity<acp_thread::Terminal> {
         let event = self.0.next().await;
         if let Some(Ok(ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateTerminal(
@@ -2577,9 +2993,12 @@ impl From<acp::ContentBlock> for UserMessageContent {
         match value {
             acp::ContentBlock::Text(text_content) => Self::Text(text_content.text),
             acp::ContentBlock::Image(image_content) => Self::Image(convert_image(image_content)),
-            acp::ContentBlock::Audio(_) => {
-                // TODO
-                Self::Text("[audio]".to_string())
+            acp::ContentBlock::Audio(audio_content) => {
+                let analysis = acp_thread::audio_analysis(&audio_content).map(str::to_string);
+                match convert_audio(audio_content) {
+                    Some(audio) => Self::Audio(UserMessageAudio { audio, analysis }),
+                    None => Self::Text("[audio]".to_string()),
+                }
             }
             acp::ContentBlock::ResourceLink(resource_link) => {
                 match MentionUri::parse(&resource_link.uri) {
@@ -2636,6 +3055,15 @@ impl From<UserMessageContent> for acp::ContentBlock {
                 annotations: None,
                 uri: None,
             }),
+            UserMessageContent::Audio(audio) => acp::ContentBlock::Audio(acp::AudioContent {
+                data: audio.audio.source.to_string(),
+                mime_type: audio.audio.format.mime_type().to_string(),
+                meta: audio
+                    .analysis
+                    .as_deref()
+                    .map(acp_thread::audio_analysis_meta),
+                annotations: None,
+            }),
             UserMessageContent::Mention { uri, content } => {
                 acp::ContentBlock::Resource(acp::EmbeddedResource {
                     meta: None,
@@ -2654,6 +3082,17 @@ impl From<UserMessageContent> for acp::ContentBlock {
     }
 }
 
+fn convert_audio(audio_content: acp::AudioContent) -> Option<LanguageModelAudio> {
+    let Some(format) = LanguageModelAudioFormat::from_mime_type(&audio_content.mime_type) else {
+        log::error!("unsupported audio MIME type: {:?}", audio_content.mime_type);
+        return None;
+    };
+    Some(LanguageModelAudio {
+        source: audio_content.data.into(),
+        format,
+    })
+}
+
 fn convert_image(image_content: acp::ImageContent) -> LanguageModelImage {
     LanguageModelImage {
         source: image_content.data.into(),
@@ -1,3 +1,4 @@
+mod code_navigation;
 mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
@@ -6,15 +7,29 @@ mod diagnostics_tool;
 mod edit_file_tool;
 mod fetch_tool;
 mod find_path_tool;
+mod find_references_tool;
+mod generate_image_tool;
+mod generate_midi_tool;
+mod go_to_definition_tool;
 mod grep_tool;
+mod hover_tool;
+mod kill_process_tool;
 mod list_directory_tool;
 mod move_path_tool;
 mod now_tool;
 mod open_tool;
+mod process_input_tool;
+mod process_output_tool;
+mod project_file;
 mod read_file_tool;
+mod rename_symbol_tool;
+mod search_samples_tool;
+mod subagent_tool;
 mod terminal_tool;
 mod thinking_tool;
+mod update_plan_tool;
 mod web_search_tool;
+mod workspace_symbols_tool;
 
 /// A list of all built in tool names, for use in deduplicating MCP tool names
 pub fn default_tool_names() -> impl Iterator<Item = &'static str> {
@@ -26,15 +41,28 @@ pub fn default_tool_names() -> impl Iterator<Item = &'static str> {
         EditFileTool::name(),
         FetchTool::name(),
         FindPathTool::name(),
+        FindReferencesTool::name(),
+        GenerateImageTool::name(),
+        GenerateMidiTool::name(),
+        GoToDefinitionTool::name(),
         GrepTool::name(),
+        HoverTool::name(),
+        KillProcessTool::name(),
         ListDirectoryTool::name(),
         MovePathTool::name(),
         NowTool::name(),
         OpenTool::name(),
+        ProcessInputTool::name(),
+        ProcessOutputTool::name(),
         ReadFileTool::name(),
+        RenameSymbolTool::name(),
+        SearchSamplesTool::name(),
+        SubagentTool::name(),
         TerminalTool::name(),
         ThinkingTool::name(),
+        UpdatePlanTool::name(),
         WebSearchTool::name(),
+        WorkspaceSymbolsTool::name(),
     ]
     .into_iter()
 }
@@ -47,14 +75,27 @@ pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
 pub use fetch_tool::*;
 pub use find_path_tool::*;
+pub use find_references_tool::*;
+pub use generate_image_tool::*;
+pub use generate_midi_tool::*;
+pub use go_to_definition_tool::*;
 pub use grep_tool::*;
+pub use hover_tool::*;
+pub use kill_process_tool::*;
 pub use list_directory_tool::*;
 pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
+pub use process_input_tool::*;
+pub use process_output_tool::*;
 pub use read_file_tool::*;
+pub use rename_symbol_tool::*;
+pub use search_samples_tool::*;
+pub use subagent_tool::*;
 pub use terminal_tool::*;
 pub use thinking_tool::*;
+pub use update_plan_tool::*;
 pub use web_search_tool::*;
+pub use workspace_symbols_tool::*;
 
 use crate::AgentTool;
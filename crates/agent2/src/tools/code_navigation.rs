@@ -0,0 +1,185 @@
+//! Helpers shared by the tools that query the project's language servers.
+
+use agent_client_protocol as acp;
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{App, AsyncApp, Entity, Task};
+use language::{Buffer, BufferSnapshot, OffsetRangeExt, Point};
+use project::{Location, Project, lsp_store::OpenLspBufferHandle};
+use std::fmt::Write;
+
+/// Opens the buffer at `path`, registers it with its language servers and
+/// finds where `symbol` appears on the given 1-based `line`.
+///
+/// The returned handle keeps the buffer registered, so it must be held until
+/// the language server has answered.
+pub fn open_symbol(
+    project: &Entity<Project>,
+    path: &str,
+    line: u32,
+    symbol: &str,
+    cx: &mut App,
+) -> Task<Result<(Entity<Buffer>, Point, OpenLspBufferHandle)>> {
+    let Some(project_path) = project.read(cx).find_project_path(path, cx) else {
+        return Task::ready(Err(anyhow!("Could not find path {path} in project")));
+    };
+    let buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+    let project = project.clone();
+    let symbol = symbol.to_string();
+    cx.spawn(async move |cx| {
+        let buffer = buffer.await?;
+        let handle = project.update(cx, |project, cx| {
+            project.register_buffer_with_language_servers(&buffer, cx)
+        })?;
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+        let position = find_symbol(&snapshot, line, &symbol)?;
+        Ok((buffer, position, handle))
+    })
+}
+
+fn find_symbol(snapshot: &BufferSnapshot, line: u32, symbol: &str) -> Result<Point> {
+    let row = line.checked_sub(1).context("Line numbers start at 1")?;
+    if row > snapshot.max_point().row {
+        return Err(anyhow!(
+            "Line {line} is past the end of the file, which has {} lines",
+            snapshot.max_point().row + 1
+        ));
+    }
+    let text = snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect::<String>();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let column = text
+        .match_indices(symbol)
+        .map(|(ix, _)| ix)
+        .find(|&ix| {
+            let before = text[..ix].chars().next_back();
+            let after = text[ix + symbol.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+        .with_context(|| format!("`{symbol}` doesn't appear on line {line}"))?;
+    Ok(Point::new(row, column as u32))
+}
+
+/// Describes each location as its path and line, followed by the text of that line.
+pub fn describe_locations(
+    locations: &[Location],
+    cx: &mut AsyncApp,
+) -> Result<(String, Vec<acp::ToolCallLocation>)> {
+    cx.update(|cx| {
+        let mut output = String::new();
+        let mut tool_call_locations = Vec::new();
+        for location in locations {
+            let buffer = location.buffer.read(cx);
+            let snapshot = buffer.snapshot();
+            let range = location.range.to_point(&snapshot);
+            let path = buffer
+                .file()
+                .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+                .unwrap_or_else(|| "untitled".to_string());
+            let line = snapshot
+                .text_for_range(
+                    Point::new(range.start.row, 0)
+                        ..Point::new(range.start.row, snapshot.line_len(range.start.row)),
+                )
+                .collect::<String>();
+            writeln!(
+                output,
+                "{path}:{}:{}: {}",
+                range.start.row + 1,
+                range.start.column + 1,
+                line.trim()
+            )?;
+            if let Some(abs_path) = buffer.file().and_then(|file| file.as_local()) {
+                tool_call_locations.push(acp::ToolCallLocation {
+                    path: abs_path.abs_path(cx),
+                    line: Some(range.start.row),
+                    meta: None,
+                });
+            }
+        }
+        Ok((output, tool_call_locations))
+    })?
+}
+
+/// Sets up a project at `/root` with a fake Rust language server, and
+/// starts the server by opening `root/src/main.rs`.
+#[cfg(test)]
+pub(crate) async fn init_rust_project(
+    files: serde_json::Value,
+    capabilities: lsp::ServerCapabilities,
+    cx: &mut gpui::TestAppContext,
+) -> (
+    Entity<Project>,
+    lsp::FakeLanguageServer,
+    OpenLspBufferHandle,
+) {
+    use futures::StreamExt as _;
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use settings::SettingsStore;
+    use std::sync::Arc;
+    use util::path;
+
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        language::init(cx);
+        agent_settings::AgentSettings::register(cx);
+        Project::init_settings(cx);
+    });
+
+    let fs = project::FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/root"), files).await;
+    let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    )));
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities,
+            ..Default::default()
+        },
+    );
+
+    let (_, handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/root/src/main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    (project, fake_language_server, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+
+    #[gpui::test]
+    fn test_find_symbol(cx: &mut TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("let tempo = 120;\nset_tempo(tempo);\n", cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        assert_eq!(
+            find_symbol(&snapshot, 2, "tempo").unwrap(),
+            Point::new(1, 10)
+        );
+        assert_eq!(
+            find_symbol(&snapshot, 2, "set_tempo").unwrap(),
+            Point::new(1, 0)
+        );
+        assert!(find_symbol(&snapshot, 1, "bpm").is_err());
+        assert!(find_symbol(&snapshot, 0, "tempo").is_err());
+        assert!(find_symbol(&snapshot, 10, "tempo").is_err());
+    }
+}
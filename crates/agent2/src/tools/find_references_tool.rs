@@ -0,0 +1,200 @@
+use super::code_navigation::{describe_locations, open_symbol};
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+const RESULTS_PER_PAGE: usize = 50;
+
+/// Asks the language server for every place a symbol is referenced in the project.
+///
+/// - Prefer this over `grep` when you need the uses of one particular function, type or variable, rather than every piece of text with the same name.
+/// - Point at the symbol by giving a file and line where it's defined or used, along with its name as it appears on that line.
+/// - Results are paginated with 50 references per page. Use the `offset` parameter to request subsequent pages.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindReferencesToolInput {
+    /// The relative path of a file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The name of the symbol, exactly as it's written on that line.
+    pub symbol: String,
+    /// Optional starting position for paginated results (0-based).
+    #[serde(default)]
+    pub offset: usize,
+}
+
+pub struct FindReferencesTool {
+    project: Entity<Project>,
+}
+
+impl FindReferencesTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindReferencesTool {
+    type Input = FindReferencesToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "find_references"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Find references to {}", MarkdownInlineCode(&input.symbol)).into(),
+            Err(_) => "Find references".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let symbol = open_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let (buffer, position, _handle) = symbol.await?;
+            let references = project
+                .update(cx, |project, cx| project.references(&buffer, position, cx))?
+                .await?
+                .unwrap_or_default();
+            if references.is_empty() {
+                return Ok(format!("No references to `{}` found.", input.symbol));
+            }
+
+            let total = references.len();
+            let page = references
+                .into_iter()
+                .skip(input.offset)
+                .take(RESULTS_PER_PAGE)
+                .collect::<Vec<_>>();
+            if page.is_empty() {
+                return Ok(format!(
+                    "There are only {total} references to `{}`.",
+                    input.symbol
+                ));
+            }
+
+            let (listing, locations) = describe_locations(&page, cx)?;
+            event_stream.update_fields(acp::ToolCallUpdateFields {
+                locations: Some(locations),
+                ..Default::default()
+            });
+
+            let end = input.offset + page.len();
+            if end < total {
+                Ok(format!(
+                    "Showing references {}-{end} of {total} (use offset={end} for the next page):\n{listing}",
+                    input.offset + 1,
+                ))
+            } else if input.offset > 0 {
+                Ok(format!(
+                    "Showing references {}-{end} of {total}:\n{listing}",
+                    input.offset + 1,
+                ))
+            } else {
+                Ok(format!(
+                    "Found {total} references to `{}`:\n{listing}",
+                    input.symbol
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::init_rust_project;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_find_references(cx: &mut TestAppContext) {
+        let (project, fake_language_server, _handle) = init_rust_project(
+            json!({
+                "src": {
+                    "main.rs": "mod tempo;\nfn main() {\n    tempo::set_bpm(120);\n}\n",
+                    "tempo.rs": "pub fn set_bpm(bpm: u32) {}\n",
+                }
+            }),
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        fake_language_server.set_request_handler::<lsp::request::References, _, _>(
+            |params, _| async move {
+                assert_eq!(
+                    params.text_document_position.position,
+                    lsp::Position::new(2, 11)
+                );
+                Ok(Some(vec![
+                    lsp::Location::new(
+                        lsp::Uri::from_file_path(path!("/root/src/tempo.rs")).unwrap(),
+                        lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 14)),
+                    ),
+                    lsp::Location::new(
+                        lsp::Uri::from_file_path(path!("/root/src/main.rs")).unwrap(),
+                        lsp::Range::new(lsp::Position::new(2, 11), lsp::Position::new(2, 18)),
+                    ),
+                ]))
+            },
+        );
+        let tool = Arc::new(FindReferencesTool::new(project));
+        let input = |offset| FindReferencesToolInput {
+            path: "root/src/main.rs".into(),
+            line: 3,
+            symbol: "set_bpm".into(),
+            offset,
+        };
+
+        let (event_stream, mut event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| tool.clone().run(input(0), event_stream, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "Found 2 references to `set_bpm`:\n\
+                {}:1:8: pub fn set_bpm(bpm: u32) {{}}\n\
+                {}:3:12: tempo::set_bpm(120);\n",
+                path!("root/src/tempo.rs"),
+                path!("root/src/main.rs"),
+            )
+        );
+        let update = event_rx.expect_update_fields().await;
+        assert_eq!(update.locations.map(|locations| locations.len()), Some(2));
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| tool.run(input(2), event_stream, cx))
+            .await
+            .unwrap();
+        assert_eq!(output, "There are only 2 references to `set_bpm`.");
+    }
+}
@@ -0,0 +1,438 @@
+use super::project_file::ProjectFile;
+use crate::{AgentTool, ToolCallEventStream};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::{Context as _, Result, anyhow};
+use base64::Engine as _;
+use credentials_provider::load_api_key;
+use futures::AsyncReadExt as _;
+use gpui::{App, AppContext as _, Entity, Image, ImageFormat, SharedString, Task};
+use http_client::{AsyncBody, HttpClient, Method};
+use language_model::LanguageModelToolResultContent;
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use std::{path::Path, sync::Arc};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Generates an image from a description and writes it into the project as a PNG file.
+///
+/// - Use this for cover art, visualizer frames, textures and other artwork the user asks for.
+/// - The image model doesn't see the conversation, so describe the subject, style, composition and colors in the prompt.
+/// - The user reviews the file like any other edit, and can reject it to restore the previous file.
+/// - Existing files at the path are overwritten.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateImageToolInput {
+    /// The path of the image to write, which must end in `.png` and start with one of the project's root directories.
+    ///
+    /// <example>
+    /// If the project has a root directory called "album", you can write cover art to "album/artwork/cover.png".
+    /// </example>
+    pub path: String,
+    /// A detailed description of the image to generate.
+    pub prompt: String,
+    /// The size of the image as `WIDTHxHEIGHT`, such as "1024x1024" or "1536x1024". Leave this out to use the size from the user's settings.
+    #[serde(default)]
+    pub size: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateImageToolOutput {
+    path: String,
+    revised_prompt: Option<String>,
+}
+
+impl From<GenerateImageToolOutput> for LanguageModelToolResultContent {
+    fn from(output: GenerateImageToolOutput) -> Self {
+        match output.revised_prompt {
+            Some(revised_prompt) => format!(
+                "Wrote the image to {}. The endpoint revised the prompt to: {revised_prompt}",
+                output.path
+            )
+            .into(),
+            None => format!("Wrote the image to {}", output.path).into(),
+        }
+    }
+}
+
+pub struct GenerateImageTool {
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+    http_client: Arc<dyn HttpClient>,
+    api_key: Option<Arc<str>>,
+}
+
+impl GenerateImageTool {
+    pub fn new(
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Self {
+        Self {
+            project,
+            action_log,
+            http_client,
+            api_key: None,
+        }
+    }
+
+    /// Uses `api_key` instead of the key that `agent.image_generation` points to.
+    #[cfg(test)]
+    fn with_api_key(mut self, api_key: impl Into<Arc<str>>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+}
+
+impl AgentTool for GenerateImageTool {
+    type Input = GenerateImageToolInput;
+    type Output = GenerateImageToolOutput;
+
+    fn name() -> &'static str {
+        "generate_image"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Generate Image “`{}`”", input.path).into()
+        } else {
+            "Generate Image".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<GenerateImageToolOutput>> {
+        let Some(settings) = AgentSettings::get_global(cx).image_generation.clone() else {
+            return Task::ready(Err(anyhow!(
+                "Image generation is not configured. Ask the user to set `agent.image_generation` in their settings."
+            )));
+        };
+
+        let is_png = Path::new(&input.path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if !is_png {
+            return Task::ready(Err(anyhow!("{} must end in .png", input.path)));
+        }
+        let file = match ProjectFile::resolve(&self.project, &input.path, "Images", cx) {
+            Ok(file) => file,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let action_log = self.action_log.clone();
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        cx.spawn(async move |cx| {
+            let api_key = match api_key {
+                Some(api_key) => Some(api_key),
+                None => {
+                    load_api_key(&settings.api_url, settings.api_key_env_var.as_deref(), cx).await?
+                }
+            };
+            let request = ImageGenerationRequest {
+                model: &settings.model,
+                prompt: &input.prompt,
+                n: 1,
+                size: input.size.as_deref().or(settings.size.as_deref()),
+            };
+            let generated =
+                generate_image(http_client, &settings.api_url, api_key, &request).await?;
+            anyhow::ensure!(
+                generated.bytes.starts_with(PNG_SIGNATURE),
+                "The image generation endpoint didn't return a PNG image"
+            );
+
+            let image = cx.new(|_| Image::from_bytes(ImageFormat::Png, generated.bytes.clone()))?;
+            event_stream.update_image(image);
+
+            file.write(&generated.bytes, &action_log, cx).await?;
+
+            Ok(GenerateImageToolOutput {
+                path: input.path,
+                revised_prompt: generated.revised_prompt,
+            })
+        })
+    }
+
+    fn replay(
+        &self,
+        _input: Self::Input,
+        output: Self::Output,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Result<()> {
+        let project = self.project.read(cx);
+        let abs_path = project
+            .find_project_path(&output.path, cx)
+            .and_then(|project_path| project.absolute_path(&project_path, cx))
+            .with_context(|| format!("{} isn't in this project", output.path))?;
+        let fs = project.fs().clone();
+        // The image may have been rejected or deleted since, in which case
+        // the card is shown without it.
+        cx.spawn(async move |cx| {
+            if let Ok(bytes) = fs.load_bytes(&abs_path).await
+                && let Ok(image) = cx.new(|_| Image::from_bytes(ImageFormat::Png, bytes))
+            {
+                event_stream.update_image(image);
+            }
+        })
+        .detach();
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct ImageGenerationRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    n: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct ImageGenerationResponse {
+    data: Vec<GeneratedImageData>,
+}
+
+/// Endpoints return the image either inline as base64 or as a URL to
+/// download it from, depending on the model and `response_format`.
+#[derive(Deserialize)]
+struct GeneratedImageData {
+    b64_json: Option<String>,
+    url: Option<String>,
+    revised_prompt: Option<String>,
+}
+
+struct GeneratedImage {
+    bytes: Vec<u8>,
+    revised_prompt: Option<String>,
+}
+
+async fn generate_image(
+    http_client: Arc<dyn HttpClient>,
+    api_url: &str,
+    api_key: Option<Arc<str>>,
+    request: &ImageGenerationRequest<'_>,
+) -> Result<GeneratedImage> {
+    let uri = format!("{}/images/generations", api_url.trim_end_matches('/'));
+    let mut http_request = http_client::Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        http_request = http_request.header("Authorization", format!("Bearer {}", api_key.trim()));
+    }
+    let http_request = http_request.body(AsyncBody::from(serde_json::to_string(request)?))?;
+    let body = send(&http_client, http_request, "image generation").await?;
+
+    let response: ImageGenerationResponse =
+        serde_json::from_slice(&body).context("invalid image generation response")?;
+    let data = response
+        .data
+        .into_iter()
+        .next()
+        .context("the image generation endpoint returned no images")?;
+    let bytes = match (data.b64_json, data.url) {
+        (Some(b64_json), _) => base64::prelude::BASE64_STANDARD
+            .decode(b64_json.trim())
+            .context("invalid base64 image data")?,
+        (None, Some(url)) => {
+            let request = http_client::Request::builder()
+                .method(Method::GET)
+                .uri(url)
+                .body(AsyncBody::empty())?;
+            send(&http_client, request, "image download").await?
+        }
+        (None, None) => anyhow::bail!("the image generation endpoint returned no image data"),
+    };
+
+    Ok(GeneratedImage {
+        bytes,
+        revised_prompt: data.revised_prompt,
+    })
+}
+
+async fn send(
+    http_client: &Arc<dyn HttpClient>,
+    request: http_client::Request<AsyncBody>,
+    description: &str,
+) -> Result<Vec<u8>> {
+    let mut response = http_client
+        .send(request)
+        .await
+        .with_context(|| format!("failed to send {description} request"))?;
+    let mut body = Vec::new();
+    response.body_mut().read_to_end(&mut body).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "{description} request failed.\nStatus: {:?}\nBody: {}",
+        response.status(),
+        String::from_utf8_lossy(&body),
+    );
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::Fs as _;
+    use gpui::{TestAppContext, UpdateGlobal as _, http_client::FakeHttpClient};
+    use http_client::Response;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::{ImageGenerationSettingsContent, SettingsStore};
+    use util::path;
+
+    const FAKE_PNG: &[u8] = b"\x89PNG\r\n\x1a\nfake image data";
+
+    #[gpui::test]
+    async fn test_generate_image_and_reject(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({"album": {}})).await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        // Stands in for a local OpenAI-compatible server.
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(
+                request.uri().to_string(),
+                "http://localhost:8080/v1/images/generations"
+            );
+            assert_eq!(request.headers()["Authorization"], "Bearer test-key");
+            let mut body = String::new();
+            request.into_body().read_to_string(&mut body).await?;
+            let body: serde_json::Value = serde_json::from_str(&body)?;
+            assert_eq!(
+                body,
+                json!({
+                    "model": "stub-model",
+                    "prompt": "a neon cassette tape",
+                    "n": 1,
+                    "size": "512x512",
+                })
+            );
+
+            let response = json!({
+                "data": [{
+                    "b64_json": base64::prelude::BASE64_STANDARD.encode(FAKE_PNG),
+                    "revised_prompt": "a glowing neon cassette tape",
+                }]
+            });
+            Ok(Response::builder()
+                .status(200)
+                .body(response.to_string().into())
+                .unwrap())
+        });
+        let tool = Arc::new(
+            GenerateImageTool::new(project.clone(), action_log.clone(), http_client)
+                .with_api_key("test-key"),
+        );
+
+        let input = GenerateImageToolInput {
+            path: "root/album/cover.png".into(),
+            prompt: "a neon cassette tape".into(),
+            size: None,
+        };
+        let (event_stream, mut event_rx) = ToolCallEventStream::test();
+        let task = cx.update(|cx| tool.clone().run(input, event_stream, cx));
+        let image = event_rx.expect_image().await;
+        let output = task.await.unwrap();
+        assert_eq!(
+            image.read_with(cx, |image, _| image.bytes.clone()),
+            FAKE_PNG
+        );
+        assert_eq!(
+            output.revised_prompt.as_deref(),
+            Some("a glowing neon cassette tape")
+        );
+        assert_eq!(
+            fs.load_bytes(path!("/root/album/cover.png").as_ref())
+                .await
+                .unwrap(),
+            FAKE_PNG
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            action_log.read_with(cx, |log, _| log.changed_files().count()),
+            1
+        );
+
+        action_log
+            .update(cx, |log, cx| log.reject_all_edits(cx))
+            .await;
+        cx.run_until_parked();
+        assert!(!fs.is_file(path!("/root/album/cover.png").as_ref()).await);
+    }
+
+    #[gpui::test]
+    async fn test_generate_image_validation(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({})).await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let http_client = FakeHttpClient::create(|_| async move {
+            Ok(Response::builder()
+                .status(200)
+                .body(
+                    json!({"data": [{"b64_json": "bm90IGEgcG5n"}]})
+                        .to_string()
+                        .into(),
+                )
+                .unwrap())
+        });
+        let tool = Arc::new(
+            GenerateImageTool::new(project, action_log, http_client).with_api_key("test-key"),
+        );
+
+        for path in ["root/cover.jpg", "elsewhere/cover.png", "root/cover.png"] {
+            let input = GenerateImageToolInput {
+                path: path.into(),
+                prompt: "a cassette tape".into(),
+                size: None,
+            };
+            let result = cx
+                .update(|cx| tool.clone().run(input, ToolCallEventStream::test().0, cx))
+                .await;
+            assert!(result.is_err(), "{path}");
+        }
+        assert!(!fs.is_file(path!("/root/cover.png").as_ref()).await);
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            agent_settings::init(cx);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.agent.get_or_insert_default().image_generation =
+                        Some(ImageGenerationSettingsContent {
+                            api_url: "http://localhost:8080/v1/".into(),
+                            model: "stub-model".into(),
+                            size: Some("512x512".into()),
+                            api_key_env_var: None,
+                        });
+                });
+            });
+        });
+    }
+}
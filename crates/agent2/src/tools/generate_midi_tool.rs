@@ -0,0 +1,303 @@
+use super::project_file::ProjectFile;
+use crate::{AgentTool, ToolCallEventStream};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, AppContext, Entity, SharedString, Task};
+use language_model::LanguageModelToolResultContent;
+use midi::{
+    Key, MidiPattern, PatternSpec, PatternStyle, note_name, parse_progression, parse_rhythm,
+};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::Arc};
+
+/// Writes a MIDI pattern built from a key, a chord progression and a rhythm into a Standard MIDI File in the project.
+///
+/// - Use this to sketch chord, bass or arpeggio parts that can be dragged into a DAW. It works without any connection to a DAW.
+/// - The user reviews the file like any other edit, and can reject it to restore the previous file.
+/// - Existing files at the path are overwritten.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateMidiToolInput {
+    /// The path of the MIDI file to write, which must end in `.mid` or `.midi` and start with one of the project's root directories.
+    ///
+    /// <example>
+    /// If the project has a root directory called "song", you can write a bass line to "song/midi/bass.mid".
+    /// </example>
+    pub path: String,
+    /// The key of the pattern, such as "C", "F#m", "Bb minor" or "D dorian".
+    pub key: String,
+    /// The chords, separated by spaces, as roman numerals relative to the key ("i VI III VII", "ii7 V7 Imaj7", "bVII") or as chord names ("Am F C G"). The progression repeats until the pattern ends.
+    pub progression: String,
+    /// A rhythm grid in which every character is one step: `x` is a hit, `X` an accented hit, `-` holds the previous hit and `.` is a rest. Spaces and `|` can be used to group steps. The grid repeats until the pattern ends.
+    ///
+    /// <example>
+    /// With the default of 4 steps per beat, "x..x ..x. x... x-x." is a syncopated one-bar rhythm.
+    /// </example>
+    pub rhythm: String,
+    /// The length of the pattern in bars.
+    pub bars: u32,
+    /// The tempo in beats per minute. Defaults to 120.
+    #[serde(default)]
+    pub bpm: Option<f32>,
+    /// What to play on each hit: all tones of the chord, its root as a bass line, or its tones one at a time.
+    #[serde(default)]
+    pub style: PatternStyle,
+    /// The number of beats in a bar. Defaults to 4.
+    #[serde(default)]
+    pub beats_per_bar: Option<u32>,
+    /// How many beats each chord of the progression lasts. Defaults to one bar.
+    #[serde(default)]
+    pub beats_per_chord: Option<u32>,
+    /// How many rhythm steps make up a beat, such as 2 for eighth notes or 3 for triplets. Defaults to 4, for sixteenth notes.
+    #[serde(default)]
+    pub steps_per_beat: Option<u32>,
+    /// The octave of the chord roots, where octave 4 starts at middle C. Defaults to 3 for chords, 2 for bass and 4 for arpeggios.
+    #[serde(default)]
+    pub octave: Option<i8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateMidiToolOutput {
+    path: String,
+    key: String,
+    pattern: MidiPattern,
+}
+
+impl From<GenerateMidiToolOutput> for LanguageModelToolResultContent {
+    fn from(output: GenerateMidiToolOutput) -> Self {
+        let pattern = &output.pattern;
+        let mut llm_output = format!(
+            "Wrote {} notes in {} over {} bars at {} BPM to {}",
+            pattern.notes.len(),
+            output.key,
+            pattern.bars,
+            pattern.bpm,
+            output.path
+        );
+        if let Some(range) = pattern.pitch_range() {
+            llm_output.push_str(&format!(
+                ", ranging from {} to {}",
+                note_name(*range.start()),
+                note_name(*range.end())
+            ));
+        }
+        llm_output.into()
+    }
+}
+
+pub struct GenerateMidiTool {
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl GenerateMidiTool {
+    pub fn new(project: Entity<Project>, action_log: Entity<ActionLog>) -> Self {
+        Self {
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for GenerateMidiTool {
+    type Input = GenerateMidiToolInput;
+    type Output = GenerateMidiToolOutput;
+
+    fn name() -> &'static str {
+        "generate_midi"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Generate MIDI “`{}`”", input.path).into()
+        } else {
+            "Generate MIDI".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<GenerateMidiToolOutput>> {
+        let pattern = match generate_pattern(&input) {
+            Ok(pattern) => pattern,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let is_midi_file = Path::new(&input.path)
+            .extension()
+            .is_some_and(|extension| extension == "mid" || extension == "midi");
+        if !is_midi_file {
+            return Task::ready(Err(anyhow!("{} must end in .mid or .midi", input.path)));
+        }
+        let file = match ProjectFile::resolve(&self.project, &input.path, "MIDI files", cx) {
+            Ok(file) => file,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        // Show the piano roll before writing, so a pattern that fails to
+        // write can still be inspected.
+        event_stream.update_piano_roll(cx.new(|_| pattern.clone()));
+
+        let action_log = self.action_log.clone();
+        let smf = pattern.to_smf();
+        cx.spawn(async move |cx| {
+            file.write(&smf, &action_log, cx).await?;
+            Ok(GenerateMidiToolOutput {
+                path: input.path,
+                key: input.key,
+                pattern,
+            })
+        })
+    }
+
+    fn replay(
+        &self,
+        _input: Self::Input,
+        output: Self::Output,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Result<()> {
+        event_stream.update_piano_roll(cx.new(|_| output.pattern));
+        Ok(())
+    }
+}
+
+fn generate_pattern(input: &GenerateMidiToolInput) -> Result<MidiPattern> {
+    let key = Key::parse(&input.key)?;
+    let beats_per_bar = input.beats_per_bar.unwrap_or(4);
+    PatternSpec {
+        progression: parse_progression(&input.progression, &key)?,
+        rhythm: parse_rhythm(&input.rhythm)?,
+        steps_per_beat: input.steps_per_beat.unwrap_or(4),
+        beats_per_bar,
+        beats_per_chord: input.beats_per_chord.unwrap_or(beats_per_bar),
+        bars: input.bars,
+        bpm: input.bpm.unwrap_or(120.),
+        style: input.style,
+        octave: input.octave,
+    }
+    .generate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::Fs as _;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_generate_midi_and_reject(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({"song": {}})).await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(GenerateMidiTool::new(project.clone(), action_log.clone()));
+
+        let input = GenerateMidiToolInput {
+            path: "root/song/parts/bass.mid".into(),
+            key: "A minor".into(),
+            progression: "i VI III VII".into(),
+            rhythm: "x--. x... x.x. ....".into(),
+            bars: 4,
+            bpm: Some(90.),
+            style: PatternStyle::Bass,
+            beats_per_bar: None,
+            beats_per_chord: None,
+            steps_per_beat: None,
+            octave: None,
+        };
+        let (event_stream, mut event_rx) = ToolCallEventStream::test();
+        let task = cx.update(|cx| tool.clone().run(input, event_stream, cx));
+        let pattern = event_rx.expect_piano_roll().await;
+        let output = task.await.unwrap();
+        assert_eq!(
+            pattern.read_with(cx, |pattern, _| pattern.clone()),
+            output.pattern
+        );
+        assert_eq!(output.pattern.notes.len(), 16);
+        assert_eq!(output.pattern.notes[0].pitch, 45);
+
+        let written = fs
+            .load_bytes(path!("/root/song/parts/bass.mid").as_ref())
+            .await
+            .unwrap();
+        assert_eq!(written, output.pattern.to_smf());
+        cx.run_until_parked();
+        assert_eq!(
+            action_log.read_with(cx, |log, _| log.changed_files().count()),
+            1
+        );
+
+        action_log
+            .update(cx, |log, cx| log.reject_all_edits(cx))
+            .await;
+        cx.run_until_parked();
+        assert!(
+            !fs.is_file(path!("/root/song/parts/bass.mid").as_ref())
+                .await
+        );
+    }
+
+    #[gpui::test]
+    async fn test_generate_midi_validation(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({})).await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(GenerateMidiTool::new(project, action_log));
+
+        for (path, key, progression) in [
+            ("root/chords.txt", "C", "I IV V"),
+            ("elsewhere/chords.mid", "C", "I IV V"),
+            ("root/chords.mid", "C bebop", "I IV V"),
+            ("root/chords.mid", "C", "I IV Q"),
+        ] {
+            let input = GenerateMidiToolInput {
+                path: path.into(),
+                key: key.into(),
+                progression: progression.into(),
+                rhythm: "x...".into(),
+                bars: 1,
+                bpm: None,
+                style: PatternStyle::Chords,
+                beats_per_bar: None,
+                beats_per_chord: None,
+                steps_per_beat: None,
+                octave: None,
+            };
+            let result = cx
+                .update(|cx| tool.clone().run(input, ToolCallEventStream::test().0, cx))
+                .await;
+            assert!(result.is_err(), "{path} {key} {progression}");
+        }
+        assert!(!fs.is_file(path!("/root/chords.mid").as_ref()).await);
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
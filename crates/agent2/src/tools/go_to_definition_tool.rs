@@ -0,0 +1,124 @@
+use super::code_navigation::{describe_locations, open_symbol};
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Asks the language server where a symbol is defined.
+///
+/// - Prefer this over `grep` to find the definition of a function, type or variable used in code, since it resolves imports, shadowing and methods with the same name.
+/// - Point at the symbol by giving the file and line where it's used, along with its name as it appears on that line.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GoToDefinitionToolInput {
+    /// The relative path of the file the symbol is used in.
+    ///
+    /// This path should never be absolute, and the first component of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The name of the symbol, exactly as it's written on that line.
+    pub symbol: String,
+    /// What to look for. Defaults to the definition.
+    #[serde(default)]
+    pub kind: DefinitionKind,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    #[default]
+    Definition,
+    Declaration,
+    TypeDefinition,
+    Implementation,
+}
+
+pub struct GoToDefinitionTool {
+    project: Entity<Project>,
+}
+
+impl GoToDefinitionTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GoToDefinitionTool {
+    type Input = GoToDefinitionToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "go_to_definition"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => {
+                let what = match input.kind {
+                    DefinitionKind::Definition => "definition",
+                    DefinitionKind::Declaration => "declaration",
+                    DefinitionKind::TypeDefinition => "type definition",
+                    DefinitionKind::Implementation => "implementations",
+                };
+                format!("Go to {what} of {}", MarkdownInlineCode(&input.symbol)).into()
+            }
+            Err(_) => "Go to definition".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let symbol = open_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let (buffer, position, _handle) = symbol.await?;
+            let links = project
+                .update(cx, |project, cx| match input.kind {
+                    DefinitionKind::Definition => project.definitions(&buffer, position, cx),
+                    DefinitionKind::Declaration => project.declarations(&buffer, position, cx),
+                    DefinitionKind::TypeDefinition => {
+                        project.type_definitions(&buffer, position, cx)
+                    }
+                    DefinitionKind::Implementation => {
+                        project.implementations(&buffer, position, cx)
+                    }
+                })?
+                .await?
+                .unwrap_or_default();
+            if links.is_empty() {
+                return Ok(format!(
+                    "The language server found nothing for `{}`.",
+                    input.symbol
+                ));
+            }
+
+            let targets = links
+                .into_iter()
+                .map(|link| link.target)
+                .collect::<Vec<_>>();
+            let (output, locations) = describe_locations(&targets, cx)?;
+            event_stream.update_fields(acp::ToolCallUpdateFields {
+                locations: Some(locations),
+                ..Default::default()
+            });
+            Ok(output)
+        })
+    }
+}
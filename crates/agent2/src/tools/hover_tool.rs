@@ -0,0 +1,99 @@
+use super::code_navigation::open_symbol;
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::{HoverBlockKind, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::{MarkdownCodeBlock, MarkdownInlineCode};
+
+/// Asks the language server for the type and documentation of a symbol, as shown when hovering over it in the editor.
+///
+/// - Use this to learn the signature of a function, the type of a variable or the documentation of an item without opening the file it's defined in.
+/// - Point at the symbol by giving the file and line where it appears, along with its name as it's written on that line.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HoverToolInput {
+    /// The relative path of a file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The name of the symbol, exactly as it's written on that line.
+    pub symbol: String,
+}
+
+pub struct HoverTool {
+    project: Entity<Project>,
+}
+
+impl HoverTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for HoverTool {
+    type Input = HoverToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "hover"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Get info for {}", MarkdownInlineCode(&input.symbol)).into(),
+            Err(_) => "Get symbol info".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let symbol = open_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let (buffer, position, _handle) = symbol.await?;
+            let hovers = project
+                .update(cx, |project, cx| project.hover(&buffer, position, cx))?
+                .await
+                .unwrap_or_default();
+
+            let blocks = hovers
+                .iter()
+                .filter(|hover| !hover.is_empty())
+                .flat_map(|hover| &hover.contents)
+                .map(|block| match &block.kind {
+                    HoverBlockKind::Code { language } => MarkdownCodeBlock {
+                        tag: language,
+                        text: &block.text,
+                    }
+                    .to_string(),
+                    HoverBlockKind::PlainText | HoverBlockKind::Markdown => block.text.clone(),
+                })
+                .collect::<Vec<_>>();
+            if blocks.is_empty() {
+                Ok(format!(
+                    "The language server has no information about `{}`.",
+                    input.symbol
+                ))
+            } else {
+                Ok(blocks.join("\n\n"))
+            }
+        })
+    }
+}
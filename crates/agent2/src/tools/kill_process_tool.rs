@@ -0,0 +1,80 @@
+use crate::{AgentTool, BackgroundProcesses, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, SharedString, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Stops a process started in the background with the `terminal` tool.
+///
+/// Any output that wasn't read with `process_output` yet is returned.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct KillProcessToolInput {
+    /// The id of the process, as returned by the `terminal` tool.
+    pub id: usize,
+}
+
+pub struct KillProcessTool {
+    background_processes: BackgroundProcesses,
+}
+
+impl KillProcessTool {
+    pub fn new(background_processes: BackgroundProcesses) -> Self {
+        Self {
+            background_processes,
+        }
+    }
+}
+
+impl AgentTool for KillProcessTool {
+    type Input = KillProcessToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "kill_process"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Kill process {}", input.id).into(),
+            Err(_) => "Kill process".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        cx.spawn(async move |cx| {
+            let terminal = self.background_processes.terminal(input.id)?;
+            terminal.kill(cx)?;
+
+            let output = self.background_processes.read_new_output(input.id, cx)?;
+            self.background_processes.remove(input.id);
+            if output.output.trim().is_empty() {
+                Ok(format!(
+                    "Killed process {} (\"{}\").",
+                    input.id, output.command
+                ))
+            } else {
+                Ok(format!(
+                    "Killed process {} (\"{}\"). Its last output was:\n\n```\n{}\n```",
+                    input.id,
+                    output.command,
+                    output.output.trim_end()
+                ))
+            }
+        })
+    }
+}
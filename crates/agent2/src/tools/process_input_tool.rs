@@ -0,0 +1,80 @@
+use crate::{AgentTool, BackgroundProcesses, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, SharedString, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Sends input to a process started in the background with the `terminal` tool, as if it was typed into its terminal.
+///
+/// - Use this to answer prompts, or to send commands to a REPL or dev server that reads from stdin.
+/// - A newline is not added automatically. End the input with `\n` to submit a line.
+/// - Use `process_output` afterwards to see how the process responded.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProcessInputToolInput {
+    /// The id of the process, as returned by the `terminal` tool.
+    pub id: usize,
+    /// The text to send to the process.
+    pub input: String,
+}
+
+pub struct ProcessInputTool {
+    background_processes: BackgroundProcesses,
+}
+
+impl ProcessInputTool {
+    pub fn new(background_processes: BackgroundProcesses) -> Self {
+        Self {
+            background_processes,
+        }
+    }
+}
+
+impl AgentTool for ProcessInputTool {
+    type Input = ProcessInputToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "process_input"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Send {} to process {}",
+                MarkdownInlineCode(input.input.trim_end()),
+                input.id
+            )
+            .into(),
+            Err(_) => "Send input to process".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let terminal = match self.background_processes.terminal(input.id) {
+            Ok(terminal) => terminal,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let authorize = event_stream.authorize(self.initial_title(Ok(input.clone()), cx), cx);
+        cx.spawn(async move |cx| {
+            authorize.await?;
+            terminal.write_input(input.input, cx)?;
+            Ok(format!("Sent the input to process {}.", input.id))
+        })
+    }
+}
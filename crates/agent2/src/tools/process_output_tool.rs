@@ -0,0 +1,116 @@
+use crate::{AgentTool, BackgroundProcesses, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, SharedString, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+/// Reads the output that a process started in the background with the `terminal` tool has produced since it was last read.
+///
+/// - Use `timeout` to wait for a process that's expected to finish or print something soon, such as a build started by a watcher, instead of polling repeatedly.
+/// - Output is returned in chunks of at most 16KB. If more is waiting, call this again to read the rest.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProcessOutputToolInput {
+    /// The id of the process, as returned by the `terminal` tool.
+    pub id: usize,
+    /// How many seconds to wait for the process to exit before returning the output so far. Defaults to 0, which returns immediately.
+    #[serde(default)]
+    pub timeout: u64,
+}
+
+pub struct ProcessOutputTool {
+    background_processes: BackgroundProcesses,
+}
+
+impl ProcessOutputTool {
+    pub fn new(background_processes: BackgroundProcesses) -> Self {
+        Self {
+            background_processes,
+        }
+    }
+}
+
+impl AgentTool for ProcessOutputTool {
+    type Input = ProcessOutputToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "process_output"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Read output of process {}", input.id).into(),
+            Err(_) => "Read process output".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        cx.spawn(async move |cx| {
+            let terminal = self.background_processes.terminal(input.id)?;
+            if input.timeout > 0 {
+                let exit = terminal.wait_for_exit(cx)?;
+                let timeout = cx
+                    .background_executor()
+                    .timer(Duration::from_secs(input.timeout));
+                futures::select_biased! {
+                    _ = exit.fuse() => {}
+                    _ = timeout.fuse() => {}
+                }
+            }
+
+            let output = self.background_processes.read_new_output(input.id, cx)?;
+            let mut content = String::new();
+            if output.skipped_bytes > 0 {
+                content.push_str(&format!(
+                    "{} bytes of output were discarded before they could be read.\n\n",
+                    output.skipped_bytes
+                ));
+            }
+            if output.output.trim().is_empty() {
+                content.push_str("No new output.");
+            } else {
+                content.push_str(&format!("```\n{}\n```", output.output.trim_end()));
+            }
+            if output.remaining_bytes > 0 {
+                content.push_str(&format!(
+                    "\n\n{} more bytes of output are waiting to be read.",
+                    output.remaining_bytes
+                ));
+            }
+
+            match output.exit_status {
+                Some(status) => {
+                    if output.remaining_bytes == 0 {
+                        self.background_processes.remove(input.id);
+                    }
+                    let status = match status.exit_code {
+                        Some(code) => format!("exited with code {code}"),
+                        None => "was terminated".to_string(),
+                    };
+                    content.push_str(&format!(
+                        "\n\nProcess {} (\"{}\") {status}.",
+                        input.id, output.command
+                    ));
+                }
+                None => content.push_str(&format!("\n\nProcess {} is still running.", input.id)),
+            }
+            Ok(content)
+        })
+    }
+}
@@ -0,0 +1,66 @@
+//! Helpers shared by the tools that write whole files into the project.
+
+use action_log::ActionLog;
+use anyhow::{Context as _, Result, anyhow};
+use fs::Fs;
+use gpui::{App, AsyncApp, Entity};
+use project::{Project, ProjectPath};
+use std::{path::PathBuf, sync::Arc};
+
+/// A file in a local project that a tool writes directly to disk, rather than
+/// by editing a buffer.
+pub struct ProjectFile {
+    path: String,
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    fs: Arc<dyn Fs>,
+}
+
+impl ProjectFile {
+    /// Resolves `path`, which starts with one of the project's root
+    /// directories. `kind` names what the tool writes, such as "MIDI files",
+    /// for the error shown in remote projects.
+    pub fn resolve(project: &Entity<Project>, path: &str, kind: &str, cx: &App) -> Result<Self> {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return Err(anyhow!("{kind} can only be written in local projects"));
+        }
+        let (project_path, abs_path) = project
+            .find_project_path(path, cx)
+            .and_then(|project_path| {
+                let abs_path = project.absolute_path(&project_path, cx)?;
+                Some((project_path, abs_path))
+            })
+            .with_context(|| {
+                format!("Couldn't write {path} because that path isn't in this project.")
+            })?;
+        Ok(Self {
+            path: path.to_string(),
+            project_path,
+            abs_path,
+            fs: project.fs().clone(),
+        })
+    }
+
+    /// Writes `content` to the file, and reports the write to the action log
+    /// so the user can review it and reject it to restore the previous file.
+    pub async fn write(
+        self,
+        content: &[u8],
+        action_log: &Entity<ActionLog>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let previous_content = if self.fs.is_file(&self.abs_path).await {
+            Some(self.fs.load_bytes(&self.abs_path).await?)
+        } else {
+            None
+        };
+        self.fs
+            .write(&self.abs_path, content)
+            .await
+            .with_context(|| format!("Writing {}", self.path))?;
+        action_log.update(cx, |action_log, cx| {
+            action_log.file_written(self.project_path, previous_content, cx)
+        })
+    }
+}
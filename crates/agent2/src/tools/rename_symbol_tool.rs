@@ -0,0 +1,367 @@
+use super::code_navigation::open_symbol;
+use crate::{AgentTool, ToolCallEventStream};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use collections::HashSet;
+use gpui::{App, Entity, SharedString, Task};
+use language::Buffer;
+use project::{PrepareRenameResponse, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Renames a symbol everywhere it's used, using the language server's rename refactoring.
+///
+/// - Prefer this over `edit_file` for renaming a function, type, field or variable, since it updates every reference across the project, including ones a text search would miss or mistake.
+/// - Point at the symbol by giving a file and line where it's defined or used, along with its current name as it's written on that line.
+/// - The edited files are saved, and the user can review the changes like any other edit.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The relative path of a file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The current name of the symbol, exactly as it's written on that line.
+    pub symbol: String,
+    /// The new name for the symbol.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool {
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl RenameSymbolTool {
+    pub fn new(project: Entity<Project>, action_log: Entity<ActionLog>) -> Self {
+        Self {
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for RenameSymbolTool {
+    type Input = RenameSymbolToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "rename_symbol"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            )
+            .into(),
+            Err(_) => "Rename symbol".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let symbol = open_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        cx.spawn(async move |cx| {
+            let (buffer, position, _handle) = symbol.await?;
+            let prepared = project
+                .update(cx, |project, cx| {
+                    project.prepare_rename(buffer.clone(), position, cx)
+                })?
+                .await?;
+            if let PrepareRenameResponse::InvalidPosition = prepared {
+                return Err(anyhow!("`{}` can't be renamed", input.symbol));
+            }
+
+            let transaction = project
+                .update(cx, |project, cx| {
+                    project.perform_rename(buffer.clone(), position, input.new_name.clone(), cx)
+                })?
+                .await?;
+            if transaction.0.is_empty() {
+                return Err(anyhow!(
+                    "The language server didn't change anything when renaming `{}`",
+                    input.symbol
+                ));
+            }
+
+            // The language server may also rename the symbol in files outside
+            // the project, such as dependencies, so those edits need
+            // confirmation before they're kept.
+            let edits_outside_project = project.read_with(cx, |project, cx| {
+                transaction
+                    .0
+                    .keys()
+                    .any(|buffer| !is_in_project(project, buffer, cx))
+            })?;
+            if edits_outside_project {
+                let title = format!(
+                    "Rename {} to {} (outside the project)",
+                    MarkdownInlineCode(&input.symbol),
+                    MarkdownInlineCode(&input.new_name)
+                );
+                let authorize = cx.update(|cx| event_stream.authorize(title, cx))?;
+                if let Err(error) = authorize.await {
+                    cx.update(|cx| {
+                        for (buffer, transaction) in &transaction.0 {
+                            buffer.update(cx, |buffer, cx| {
+                                buffer.undo_transaction(transaction.id, cx)
+                            });
+                        }
+                    })?;
+                    return Err(error);
+                }
+            }
+
+            // The edits were applied before the action log could track these
+            // buffers, so track them from before the rename for review.
+            action_log.update(cx, |log, cx| {
+                for (buffer, transaction) in &transaction.0 {
+                    log.buffer_read_at_version(buffer.clone(), &transaction.start, cx);
+                    log.buffer_edited(buffer.clone(), cx);
+                }
+            })?;
+
+            let buffers = transaction.0.into_keys().collect::<HashSet<_>>();
+            project
+                .update(cx, |project, cx| project.save_buffers(buffers.clone(), cx))?
+                .await?;
+
+            let (mut paths, locations) = cx.update(|cx| {
+                let mut paths = Vec::new();
+                let mut locations = Vec::new();
+                for buffer in &buffers {
+                    let Some(file) = buffer.read(cx).file() else {
+                        continue;
+                    };
+                    paths.push(file.full_path(cx).to_string_lossy().into_owned());
+                    if let Some(file) = file.as_local() {
+                        locations.push(acp::ToolCallLocation {
+                            path: file.abs_path(cx),
+                            line: None,
+                            meta: None,
+                        });
+                    }
+                }
+                (paths, locations)
+            })?;
+            event_stream.update_fields(acp::ToolCallUpdateFields {
+                locations: Some(locations),
+                ..Default::default()
+            });
+
+            paths.sort();
+            let mut output = format!(
+                "Renamed `{}` to `{}` in {} files:\n",
+                input.symbol,
+                input.new_name,
+                paths.len()
+            );
+            for path in paths {
+                writeln!(output, "- {path}")?;
+            }
+            Ok(output)
+        })
+    }
+}
+
+fn is_in_project(project: &Project, buffer: &Entity<Buffer>, cx: &App) -> bool {
+    buffer.read(cx).file().is_some_and(|file| {
+        project
+            .worktree_for_id(file.worktree_id(cx), cx)
+            .is_some_and(|worktree| worktree.read(cx).is_visible())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::init_rust_project;
+    use fs::Fs as _;
+    use gpui::{AppContext as _, TestAppContext};
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+
+    const MAIN_RS: &str = "mod tempo;\nfn main() {\n    tempo::set_bpm(120);\n}\n";
+    const TEMPO_RS: &str = "pub fn set_bpm(bpm: u32) {}\n";
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<Project>,
+        lsp::FakeLanguageServer,
+        project::lsp_store::OpenLspBufferHandle,
+    ) {
+        init_rust_project(
+            json!({
+                "src": {
+                    "main.rs": MAIN_RS,
+                    "tempo.rs": TEMPO_RS,
+                }
+            }),
+            lsp::ServerCapabilities {
+                rename_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await
+    }
+
+    /// Renames `set_bpm` on line 3 of main.rs and at the start of `other_path`.
+    fn handle_rename(fake_language_server: &lsp::FakeLanguageServer, other_path: &'static str) {
+        fake_language_server.set_request_handler::<lsp::request::Rename, _, _>(
+            move |params, _| async move {
+                assert_eq!(
+                    params.text_document_position.position,
+                    lsp::Position::new(2, 11)
+                );
+                Ok(Some(lsp::WorkspaceEdit {
+                    changes: Some(
+                        [
+                            (
+                                lsp::Uri::from_file_path(path!("/root/src/main.rs")).unwrap(),
+                                vec![lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(2, 11),
+                                        lsp::Position::new(2, 18),
+                                    ),
+                                    params.new_name.clone(),
+                                )],
+                            ),
+                            (
+                                lsp::Uri::from_file_path(other_path).unwrap(),
+                                vec![lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(0, 7),
+                                        lsp::Position::new(0, 14),
+                                    ),
+                                    params.new_name,
+                                )],
+                            ),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                }))
+            },
+        );
+    }
+
+    fn rename_input() -> RenameSymbolToolInput {
+        RenameSymbolToolInput {
+            path: "root/src/main.rs".into(),
+            line: 3,
+            symbol: "set_bpm".into(),
+            new_name: "set_tempo".into(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol(cx: &mut TestAppContext) {
+        let (project, fake_language_server, _handle) = init_test(cx).await;
+        handle_rename(&fake_language_server, path!("/root/src/tempo.rs"));
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(RenameSymbolTool::new(project.clone(), action_log.clone()));
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| tool.run(rename_input(), event_stream, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "Renamed `set_bpm` to `set_tempo` in 2 files:\n- {}\n- {}\n",
+                path!("root/src/main.rs"),
+                path!("root/src/tempo.rs")
+            )
+        );
+        cx.run_until_parked();
+
+        let fs = project.read_with(cx, |project, _| project.fs().clone());
+        assert_eq!(
+            fs.load(Path::new(path!("/root/src/tempo.rs")))
+                .await
+                .unwrap(),
+            "pub fn set_tempo(bpm: u32) {}\n"
+        );
+
+        // Both files are up for review, including the one the agent never
+        // read, and rejecting the edits restores them.
+        let changed_buffers = action_log.read_with(cx, |log, cx| log.changed_buffers(cx));
+        assert_eq!(changed_buffers.len(), 2);
+        action_log
+            .update(cx, |log, cx| log.reject_all_edits(cx))
+            .await;
+        cx.run_until_parked();
+        let mut texts = changed_buffers
+            .keys()
+            .map(|buffer| buffer.read_with(cx, |buffer, _| buffer.text()))
+            .collect::<Vec<_>>();
+        texts.sort();
+        assert_eq!(texts, [MAIN_RS, TEMPO_RS]);
+        assert!(action_log.read_with(cx, |log, cx| log.changed_buffers(cx).is_empty()));
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_outside_project(cx: &mut TestAppContext) {
+        let (project, fake_language_server, _handle) = init_test(cx).await;
+        let fs = project.read_with(cx, |project, _| project.fs().clone());
+        fs.write(Path::new(path!("/deps/tempo.rs")), TEMPO_RS.as_bytes())
+            .await
+            .unwrap();
+        handle_rename(&fake_language_server, path!("/deps/tempo.rs"));
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(RenameSymbolTool::new(project.clone(), action_log.clone()));
+
+        let (event_stream, mut event_rx) = ToolCallEventStream::test();
+        let task = cx.update(|cx| tool.run(rename_input(), event_stream, cx));
+        let authorization = event_rx.expect_authorization().await;
+        assert_eq!(
+            authorization.tool_call.fields.title,
+            Some("Rename `set_bpm` to `set_tempo` (outside the project)".into())
+        );
+        authorization
+            .response
+            .send(acp::PermissionOptionId("deny".into()))
+            .unwrap();
+        assert!(task.await.is_err());
+        cx.run_until_parked();
+
+        // The rename is undone in every file, and nothing is saved or left
+        // to review.
+        for path in [path!("/root/src/main.rs"), path!("/deps/tempo.rs")] {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            let text = buffer.read_with(cx, |buffer, _| buffer.text());
+            assert_eq!(text, fs.load(Path::new(path)).await.unwrap());
+            assert!(!text.contains("set_tempo"));
+        }
+        assert!(action_log.read_with(cx, |log, cx| log.changed_buffers(cx).is_empty()));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, Entity, SharedString, Task};
+use language_model::LanguageModelToolResultContent;
+use project::Project;
+use sample_library::{SampleIndex, SampleQuery, parse_key};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::{sync::Arc, time::Duration};
+
+/// Searches the audio files in the project, such as sample libraries, for sounds matching a description.
+///
+/// - Describe the sound in a few words, for example "punchy kick" or "warm analog pad". The words are matched against file names, folder names and tags embedded in the files.
+/// - Set `bpm` and `key` to only find loops that fit the project. Samples without tempo or key information are left out when these are set.
+/// - Returns the paths of the best matches along with their length, tempo and key, when known.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchSamplesToolInput {
+    /// A short description of the sound, such as "dusty vinyl snare".
+    pub description: String,
+    /// Only return samples at this tempo, or at half or double of it.
+    #[serde(default)]
+    pub bpm: Option<f32>,
+    /// Only return samples in this key, such as "F#m" or "Bb".
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Only return samples that are at most this many seconds long, for example to find one-shots rather than loops.
+    #[serde(default)]
+    pub max_duration_seconds: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchSamplesToolOutput {
+    samples: Vec<FoundSample>,
+    still_indexing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FoundSample {
+    path: String,
+    details: String,
+}
+
+impl From<SearchSamplesToolOutput> for LanguageModelToolResultContent {
+    fn from(output: SearchSamplesToolOutput) -> Self {
+        let mut llm_output = if output.samples.is_empty() {
+            "No matching samples found".to_string()
+        } else {
+            let mut llm_output = format!("Found {} samples:", output.samples.len());
+            for sample in output.samples {
+                write!(&mut llm_output, "\n- {}", sample.path).unwrap();
+                if !sample.details.is_empty() {
+                    write!(&mut llm_output, " ({})", sample.details).unwrap();
+                }
+            }
+            llm_output
+        };
+        if output.still_indexing {
+            llm_output.push_str(
+                "\n\nThe project's samples are still being indexed, so searching again later may find more.",
+            );
+        }
+        llm_output.into()
+    }
+}
+
+const MAX_RESULTS: usize = 20;
+
+pub struct SearchSamplesTool {
+    project: Entity<Project>,
+}
+
+impl SearchSamplesTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for SearchSamplesTool {
+    type Input = SearchSamplesToolInput;
+    type Output = SearchSamplesToolOutput;
+
+    fn name() -> &'static str {
+        "search_samples"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        let mut title = "Search samples".to_string();
+        if let Ok(input) = input {
+            title.push_str(&format!(" for “{}”", input.description));
+        }
+        title.into()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<SearchSamplesToolOutput>> {
+        let key = match input.key.as_deref().map(str::trim) {
+            Some(key) if !key.is_empty() => match parse_key(key) {
+                Some(key) => Some(key),
+                None => return Task::ready(Err(anyhow!("Invalid key: {key}"))),
+            },
+            _ => None,
+        };
+        let query = SampleQuery {
+            // Short words like "a" or "in" would match almost any path.
+            terms: input
+                .description
+                .split_whitespace()
+                .filter(|word| word.chars().count() > 2)
+                .map(str::to_string)
+                .collect(),
+            bpm: input.bpm,
+            key,
+            max_duration: input
+                .max_duration_seconds
+                .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok()),
+        };
+
+        let index = SampleIndex::for_project(&self.project, cx);
+        let still_indexing = index.read(cx).is_indexing();
+        let search = index.read(cx).search(query, MAX_RESULTS, cx);
+        cx.background_spawn(async move {
+            let matches = search.await;
+
+            event_stream.update_fields(acp::ToolCallUpdateFields {
+                title: Some(match matches.len() {
+                    0 => "No matching samples".into(),
+                    1 => "1 sample".into(),
+                    count => format!("{count} samples"),
+                }),
+                content: Some(
+                    matches
+                        .iter()
+                        .map(|sample_match| acp::ToolCallContent::Content {
+                            content: acp::ContentBlock::ResourceLink(acp::ResourceLink {
+                                uri: format!("file://{}", sample_match.sample.abs_path.display()),
+                                name: sample_match.sample.display_path.to_string(),
+                                annotations: None,
+                                description: None,
+                                mime_type: None,
+                                size: None,
+                                title: None,
+                                meta: None,
+                            }),
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            });
+
+            Ok(SearchSamplesToolOutput {
+                samples: matches
+                    .into_iter()
+                    .map(|sample_match| FoundSample {
+                        details: sample_match.sample.details(),
+                        path: sample_match.sample.display_path.to_string(),
+                    })
+                    .collect(),
+                still_indexing,
+            })
+        })
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{
+    AgentMessageContent, AgentTool, Message, Thread, ThreadEnvironment, ToolCallEventStream,
+    default_tool_names,
+};
+use acp_thread::{AcpThread, AcpThreadEvent, ToolCallStatus};
+use agent_client_protocol as acp;
+use agent_settings::{AgentProfileId, AgentSettings};
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashSet;
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{App, AppContext, Entity, SharedString, Subscription, Task, WeakEntity};
+use language_model::{
+    LanguageModelRegistry, LanguageModelToolResultContent, SelectedModel, TokenUsage,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+/// Delegates a focused task to a subagent: a separate thread with its own context, which works on the task until it's done and then reports back with a single answer.
+///
+/// - Use this for self-contained tasks that would otherwise fill the conversation with exploration output, such as researching how part of the project works or tracking down where something is defined.
+/// - The subagent doesn't see this conversation. The prompt has to include everything it needs to know, and say what the answer should contain.
+/// - Only the subagent's final answer is returned. Ask it to include any paths, names or details you'll need afterwards.
+/// - Restrict the subagent's tools to what the task needs. A subagent that only investigates shouldn't be able to edit files.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubagentToolInput {
+    /// A short description of the task, shown to the user while the subagent works.
+    ///
+    /// <example>Find where MIDI clock sync is handled</example>
+    pub title: String,
+    /// The complete instructions for the subagent.
+    pub prompt: String,
+    /// The id of the agent profile the subagent runs with, which decides the tools it may use. Defaults to the profile of this thread.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// The model the subagent uses, as `provider/model`. Defaults to the model of this thread.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// The names of the tools the subagent may use, built-in or from context servers, in addition to the restrictions of its profile. Defaults to every tool of the profile.
+    ///
+    /// <example>["read_file", "grep", "find_path"]</example>
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubagentToolOutput {
+    answer: String,
+    token_usage: TokenUsage,
+}
+
+impl From<SubagentToolOutput> for LanguageModelToolResultContent {
+    fn from(output: SubagentToolOutput) -> Self {
+        output.answer.into()
+    }
+}
+
+pub struct SubagentTool {
+    thread: WeakEntity<Thread>,
+    environment: Rc<dyn ThreadEnvironment>,
+}
+
+impl SubagentTool {
+    pub fn new(thread: WeakEntity<Thread>, environment: Rc<dyn ThreadEnvironment>) -> Self {
+        Self {
+            thread,
+            environment,
+        }
+    }
+}
+
+impl AgentTool for SubagentTool {
+    type Input = SubagentToolInput;
+    type Output = SubagentToolOutput;
+
+    fn name() -> &'static str {
+        "subagent"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Other
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => title(&input.title),
+            Err(_) => "Subagent".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<SubagentToolOutput>> {
+        let profile_id = input
+            .profile
+            .as_deref()
+            .map(|profile| AgentProfileId(profile.into()));
+        if let Some(profile_id) = &profile_id
+            && !AgentSettings::get_global(cx)
+                .profiles
+                .contains_key(profile_id)
+        {
+            return Task::ready(Err(anyhow!("There is no profile called {profile_id}")));
+        }
+        if let Some(tools) = &input.tools {
+            let known_tools = default_tool_names().collect::<HashSet<_>>();
+            let Some(thread) = self.thread.upgrade() else {
+                return Task::ready(Err(anyhow!("The thread was closed")));
+            };
+            let thread = thread.read(cx);
+            if let Some(unknown) = tools.iter().find(|tool| {
+                !known_tools.contains(tool.as_str()) && !thread.has_context_server_tool(tool, cx)
+            }) {
+                return Task::ready(Err(anyhow!("There is no tool called {unknown}")));
+            }
+        }
+        let model = match &input.model {
+            Some(model) => {
+                let selected_model = match model.parse::<SelectedModel>() {
+                    Ok(selected_model) => selected_model,
+                    Err(error) => return Task::ready(Err(anyhow!(error))),
+                };
+                let configured_model = LanguageModelRegistry::global(cx)
+                    .update(cx, |registry, cx| {
+                        registry.select_model(&selected_model, cx)
+                    });
+                match configured_model {
+                    Some(configured_model) => Some(configured_model.model),
+                    None => return Task::ready(Err(anyhow!("There is no model called {model}"))),
+                }
+            }
+            None => None,
+        };
+
+        let thread = self.thread.clone();
+        let environment = self.environment.clone();
+        cx.spawn(async move |cx| {
+            let child = thread.update(cx, |thread, cx| {
+                let profile_id = profile_id.unwrap_or_else(|| thread.profile().clone());
+                let model = model
+                    .or_else(|| thread.model().cloned())
+                    .context("No language model configured")?;
+                anyhow::Ok(thread.new_subagent(profile_id, model, cx))
+            })??;
+            let acp_thread = cx.update(|cx| environment.create_subagent(child.clone(), cx))??;
+            child.update(cx, |child, _| {
+                // Subagents can't delegate any further.
+                child.remove_tool(Self::name());
+                if let Some(tools) = &input.tools {
+                    child.restrict_tools(tools.iter().cloned().map(SharedString::from).collect());
+                }
+            })?;
+            event_stream.update_subagent(acp_thread.clone());
+
+            let _authorizations = cx.update(|cx| {
+                forward_authorizations(&acp_thread, event_stream.clone(), title(&input.title), cx)
+            })?;
+            // However the subagent ends, whether it answered, failed or was
+            // dropped with the parent's turn, stop it and count the tokens it
+            // spent towards the parent.
+            let _finish_on_drop = util::defer({
+                let acp_thread = acp_thread.downgrade();
+                let child = child.clone();
+                let thread = thread.clone();
+                let mut cx = cx.clone();
+                move || {
+                    let executor = cx.foreground_executor().clone();
+                    executor
+                        .spawn(async move {
+                            if let Ok(task) =
+                                acp_thread.update(&mut cx, |thread, cx| thread.cancel(cx))
+                            {
+                                task.await;
+                            }
+                            let Ok(token_usage) =
+                                child.read_with(&cx, |child, _| child.cumulative_token_usage())
+                            else {
+                                return;
+                            };
+                            thread
+                                .update(&mut cx, |thread, cx| {
+                                    thread.add_subagent_token_usage(token_usage, cx)
+                                })
+                                .ok();
+                        })
+                        .detach();
+                }
+            });
+
+            acp_thread
+                .update(cx, |thread, cx| {
+                    thread.send(
+                        vec![acp::ContentBlock::Text(acp::TextContent {
+                            text: input.prompt,
+                            annotations: None,
+                            meta: None,
+                        })],
+                        cx,
+                    )
+                })?
+                .await?;
+
+            let (answer, token_usage) = child.read_with(cx, |child, _| {
+                let answer = match child.last_message() {
+                    Some(Message::Agent(message)) => message
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            AgentMessageContent::Text(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect::<String>(),
+                    _ => String::new(),
+                };
+                (answer, child.cumulative_token_usage())
+            })?;
+            if answer.trim().is_empty() {
+                return Err(anyhow!("The subagent stopped without answering"));
+            }
+
+            Ok(SubagentToolOutput {
+                answer,
+                token_usage,
+            })
+        })
+    }
+
+    fn replay(
+        &self,
+        _input: Self::Input,
+        output: Self::Output,
+        event_stream: ToolCallEventStream,
+        _cx: &mut App,
+    ) -> Result<()> {
+        event_stream.update_fields(acp::ToolCallUpdateFields {
+            content: Some(vec![output.answer.into()]),
+            ..Default::default()
+        });
+        Ok(())
+    }
+}
+
+fn title(task: &str) -> SharedString {
+    format!("Subagent: {task}").into()
+}
+
+/// Asks the user to authorize the subagent's tool calls from the tool call
+/// that spawned it, since the subagent's own thread isn't where the user is
+/// looking. The tool call can only wait for one authorization at a time, so
+/// the subagent's requests are forwarded one after another.
+fn forward_authorizations(
+    acp_thread: &Entity<AcpThread>,
+    event_stream: ToolCallEventStream,
+    title: SharedString,
+    cx: &mut App,
+) -> Subscription {
+    let (queue_tx, mut queue_rx) = mpsc::unbounded::<acp::ToolCallId>();
+    let queued = Rc::new(RefCell::new(HashSet::default()));
+    cx.spawn({
+        let acp_thread = acp_thread.downgrade();
+        let queued = queued.clone();
+        async move |cx| {
+            while let Some(id) = queue_rx.next().await {
+                let request = acp_thread.update(cx, |thread, cx| {
+                    let (_, tool_call) = thread.tool_call(&id)?;
+                    let ToolCallStatus::WaitingForConfirmation { options, .. } = &tool_call.status
+                    else {
+                        return None;
+                    };
+                    let allow = options
+                        .iter()
+                        .find(|option| matches!(option.kind, acp::PermissionOptionKind::AllowOnce))
+                        .cloned();
+                    let reject = options
+                        .iter()
+                        .find(|option| matches!(option.kind, acp::PermissionOptionKind::RejectOnce))
+                        .cloned();
+                    let label = tool_call.label.read(cx).source().to_string();
+                    let authorization =
+                        event_stream.authorize(format!("{title} wants to: {label}"), cx);
+                    Some((authorization, allow, reject))
+                });
+                let Ok(Some((authorization, allow, reject))) = request else {
+                    queued.borrow_mut().remove(&id);
+                    continue;
+                };
+                let option = if authorization.await.is_ok() {
+                    allow
+                } else {
+                    reject
+                };
+                event_stream.update_fields(acp::ToolCallUpdateFields {
+                    title: Some(title.to_string()),
+                    status: Some(acp::ToolCallStatus::InProgress),
+                    ..Default::default()
+                });
+                queued.borrow_mut().remove(&id);
+                if let Some(option) = option {
+                    acp_thread
+                        .update(cx, |thread, cx| {
+                            thread.authorize_tool_call(id, option.id, option.kind, cx)
+                        })
+                        .ok();
+                }
+            }
+        }
+    })
+    .detach();
+
+    cx.subscribe(acp_thread, move |acp_thread, event, cx| {
+        if !matches!(event, AcpThreadEvent::ToolAuthorizationRequired) {
+            return;
+        }
+        for entry in acp_thread.read(cx).entries() {
+            if let acp_thread::AgentThreadEntry::ToolCall(tool_call) = entry
+                && matches!(
+                    tool_call.status,
+                    ToolCallStatus::WaitingForConfirmation { .. }
+                )
+                && queued.borrow_mut().insert(tool_call.id.clone())
+            {
+                queue_tx.unbounded_send(tool_call.id.clone()).ok();
+            }
+        }
+    })
+}
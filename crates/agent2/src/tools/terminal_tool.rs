@@ -1,19 +1,22 @@
 use agent_client_protocol as acp;
-use anyhow::Result;
-use gpui::{App, Entity, SharedString, Task};
+use anyhow::{Context as _, Result};
+use collections::BTreeMap;
+use gpui::{App, AsyncApp, Entity, SharedString, Task};
 use project::Project;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{
+    cell::RefCell,
+    mem,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
 };
-use util::markdown::MarkdownInlineCode;
+use util::{ResultExt as _, markdown::MarkdownInlineCode};
 
-use crate::{AgentTool, ThreadEnvironment, ToolCallEventStream};
+use crate::{AgentTool, TerminalHandle, ThreadEnvironment, ToolCallEventStream};
 
-const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
+pub(crate) const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
 
 /// Executes a shell one-liner and returns the combined output.
 ///
@@ -23,7 +26,7 @@ const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
 ///
 /// Make sure you use the `cd` parameter to navigate to one of the root directories of the project. NEVER do it as part of the `command` itself, otherwise it will error.
 ///
-/// Do not use this tool for commands that run indefinitely, such as servers (like `npm run start`, `npm run dev`, `python -m http.server`, etc) or file watchers that don't terminate on their own.
+/// Commands that run indefinitely, such as servers (like `npm run start`, `npm run dev`, `python -m http.server`, etc) or file watchers that don't terminate on their own, must be started with `background` set. The tool then returns a process id right away, which can be passed to `process_output`, `process_input` and `kill_process`. Kill background processes as soon as they're no longer needed.
 ///
 /// Remember that each invocation of this tool will spawn a new shell process, so you can't rely on any state from previous invocations.
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
@@ -32,22 +35,163 @@ pub struct TerminalToolInput {
     command: String,
     /// Working directory for the command. This must be one of the root directories of the project.
     cd: String,
+    /// Whether to keep the command running in the background instead of waiting for it to exit.
+    #[serde(default)]
+    background: bool,
 }
 
 pub struct TerminalTool {
     project: Entity<Project>,
     environment: Rc<dyn ThreadEnvironment>,
+    background_processes: BackgroundProcesses,
 }
 
 impl TerminalTool {
-    pub fn new(project: Entity<Project>, environment: Rc<dyn ThreadEnvironment>) -> Self {
+    pub fn new(
+        project: Entity<Project>,
+        environment: Rc<dyn ThreadEnvironment>,
+        background_processes: BackgroundProcesses,
+    ) -> Self {
         Self {
             project,
             environment,
+            background_processes,
         }
     }
 }
 
+/// The processes the terminal tool started in the background for one thread.
+///
+/// Dropping the last clone kills any process that is still running, so they
+/// don't outlive the thread.
+#[derive(Clone, Default)]
+pub struct BackgroundProcesses(Rc<RefCell<BackgroundProcessesState>>);
+
+#[derive(Default)]
+struct BackgroundProcessesState {
+    next_id: usize,
+    processes: BTreeMap<usize, BackgroundProcess>,
+    /// The context the processes were started in, to kill them on drop.
+    cx: Option<AsyncApp>,
+}
+
+impl Drop for BackgroundProcessesState {
+    fn drop(&mut self) {
+        let Some(mut cx) = self.cx.take() else {
+            return;
+        };
+        let terminals = mem::take(&mut self.processes)
+            .into_values()
+            .map(|process| process.terminal)
+            .collect::<Vec<_>>();
+        if terminals.is_empty() {
+            return;
+        }
+        // The thread may be dropped while it's being updated, so kill the
+        // processes once that's over.
+        cx.foreground_executor()
+            .clone()
+            .spawn(async move {
+                for terminal in terminals {
+                    let is_running = terminal
+                        .current_output(&cx)
+                        .is_ok_and(|output| output.exit_status.is_none());
+                    if is_running {
+                        terminal.kill(&mut cx).log_err();
+                    }
+                }
+            })
+            .detach();
+    }
+}
+
+struct BackgroundProcess {
+    command: String,
+    terminal: Rc<dyn TerminalHandle>,
+    read_offset: usize,
+}
+
+/// Output that a background process produced since it was last read.
+pub struct BackgroundProcessOutput {
+    pub command: String,
+    pub output: String,
+    pub skipped_bytes: usize,
+    pub remaining_bytes: usize,
+    pub exit_status: Option<acp::TerminalExitStatus>,
+}
+
+impl BackgroundProcesses {
+    fn insert(&self, command: String, terminal: Rc<dyn TerminalHandle>, cx: &AsyncApp) -> usize {
+        let mut state = self.0.borrow_mut();
+        state.cx.get_or_insert_with(|| cx.clone());
+        state.next_id += 1;
+        let id = state.next_id;
+        state.processes.insert(
+            id,
+            BackgroundProcess {
+                command,
+                terminal,
+                read_offset: 0,
+            },
+        );
+        id
+    }
+
+    pub fn terminal(&self, id: usize) -> Result<Rc<dyn TerminalHandle>> {
+        self.0
+            .borrow()
+            .processes
+            .get(&id)
+            .map(|process| process.terminal.clone())
+            .with_context(|| format!("There is no background process with id {id}"))
+    }
+
+    pub fn remove(&self, id: usize) -> Option<Rc<dyn TerminalHandle>> {
+        self.0
+            .borrow_mut()
+            .processes
+            .remove(&id)
+            .map(|process| process.terminal)
+    }
+
+    /// Returns the output the process produced since the last call, up to
+    /// `COMMAND_OUTPUT_LIMIT` bytes at a time.
+    pub fn read_new_output(&self, id: usize, cx: &mut AsyncApp) -> Result<BackgroundProcessOutput> {
+        let mut state = self.0.borrow_mut();
+        let process = state
+            .processes
+            .get_mut(&id)
+            .with_context(|| format!("There is no background process with id {id}"))?;
+        let exit_status = process.terminal.current_output(cx)?.exit_status;
+        let (content, content_offset) = process.terminal.retained_output(cx)?;
+
+        // The terminal only keeps a limited scrollback, so output that wasn't
+        // read in time may already be gone.
+        let skipped_bytes = content_offset.saturating_sub(process.read_offset);
+        let mut start = process
+            .read_offset
+            .saturating_sub(content_offset)
+            .min(content.len());
+        while !content.is_char_boundary(start) {
+            start -= 1;
+        }
+        let unread = &content[start..];
+        let mut end = unread.len().min(COMMAND_OUTPUT_LIMIT as usize);
+        while !unread.is_char_boundary(end) {
+            end -= 1;
+        }
+        process.read_offset = content_offset + start + end;
+
+        Ok(BackgroundProcessOutput {
+            command: process.command.clone(),
+            output: unread[..end].to_string(),
+            skipped_bytes,
+            remaining_bytes: unread.len() - end,
+            exit_status,
+        })
+    }
+}
+
 impl AgentTool for TerminalTool {
     type Input = TerminalToolInput;
     type Output = String;
@@ -106,7 +250,9 @@ impl AgentTool for TerminalTool {
                 .create_terminal(
                     input.command.clone(),
                     working_dir,
-                    Some(COMMAND_OUTPUT_LIMIT),
+                    // Background processes are read incrementally, so they
+                    // need all of their output.
+                    (!input.background).then_some(COMMAND_OUTPUT_LIMIT),
                     cx,
                 )
                 .await?;
@@ -117,6 +263,16 @@ impl AgentTool for TerminalTool {
                 ..Default::default()
             });
 
+            if input.background {
+                let id = self
+                    .background_processes
+                    .insert(input.command.clone(), terminal, cx);
+                return Ok(format!(
+                    "Started \"{}\" in the background as process {id}. Use `process_output` to read its output.",
+                    input.command
+                ));
+            }
+
             let exit_status = terminal.wait_for_exit(cx)?.await;
             let output = terminal.current_output(cx)?;
 
@@ -211,3 +367,313 @@ fn working_dir(
         anyhow::bail!("`cd` directory {cd:?} was not in any of the project's worktrees.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KillProcessTool, KillProcessToolInput, ProcessInputTool, ProcessInputToolInput};
+    use fs::FakeFs;
+    use futures::FutureExt as _;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use std::cell::Cell;
+    use util::path;
+
+    #[derive(Default)]
+    struct FakeTerminal {
+        output: RefCell<String>,
+        trimmed_output_len: Cell<usize>,
+        input: RefCell<String>,
+        killed: Cell<bool>,
+    }
+
+    impl FakeTerminal {
+        /// Drops the first `len` bytes of output, like the terminal does when
+        /// they scroll out of its history.
+        fn trim(&self, len: usize) {
+            self.output.borrow_mut().drain(..len);
+            self.trimmed_output_len
+                .set(self.trimmed_output_len.get() + len);
+        }
+    }
+
+    impl TerminalHandle for FakeTerminal {
+        fn id(&self, _cx: &AsyncApp) -> Result<acp::TerminalId> {
+            Ok(acp::TerminalId("fake".into()))
+        }
+
+        fn current_output(&self, _cx: &AsyncApp) -> Result<acp::TerminalOutputResponse> {
+            Ok(acp::TerminalOutputResponse {
+                output: self.output.borrow().clone(),
+                truncated: false,
+                exit_status: self.killed.get().then_some(acp::TerminalExitStatus {
+                    exit_code: None,
+                    signal: Some("SIGKILL".into()),
+                    meta: None,
+                }),
+                meta: None,
+            })
+        }
+
+        fn retained_output(&self, _cx: &mut AsyncApp) -> Result<(String, usize)> {
+            Ok((self.output.borrow().clone(), self.trimmed_output_len.get()))
+        }
+
+        fn wait_for_exit(
+            &self,
+            _cx: &AsyncApp,
+        ) -> Result<futures::future::Shared<Task<acp::TerminalExitStatus>>> {
+            Ok(Task::ready(acp::TerminalExitStatus {
+                exit_code: Some(0),
+                signal: None,
+                meta: None,
+            })
+            .shared())
+        }
+
+        fn write_input(&self, input: String, _cx: &mut AsyncApp) -> Result<()> {
+            self.input.borrow_mut().push_str(&input);
+            self.output.borrow_mut().push_str(&input);
+            Ok(())
+        }
+
+        fn kill(&self, _cx: &mut AsyncApp) -> Result<()> {
+            self.killed.set(true);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeThreadEnvironment {
+        terminals: RefCell<Vec<(String, Option<u64>, Rc<FakeTerminal>)>>,
+    }
+
+    impl ThreadEnvironment for FakeThreadEnvironment {
+        fn create_terminal(
+            &self,
+            command: String,
+            _cwd: Option<PathBuf>,
+            output_byte_limit: Option<u64>,
+            _cx: &mut AsyncApp,
+        ) -> Task<Result<Rc<dyn TerminalHandle>>> {
+            let terminal = Rc::new(FakeTerminal::default());
+            self.terminals
+                .borrow_mut()
+                .push((command, output_byte_limit, terminal.clone()));
+            Task::ready(Ok(terminal as _))
+        }
+
+        fn create_subagent(
+            &self,
+            _thread: Entity<crate::Thread>,
+            _cx: &mut App,
+        ) -> Result<Entity<acp_thread::AcpThread>> {
+            unimplemented!()
+        }
+    }
+
+    #[gpui::test]
+    fn test_background_process_output_is_read_incrementally(cx: &mut TestAppContext) {
+        let mut cx = cx.to_async();
+        let terminal = Rc::new(FakeTerminal::default());
+        terminal.output.borrow_mut().push_str("Compiling...\n");
+        let processes = BackgroundProcesses::default();
+        let id = processes.insert("cargo watch".into(), terminal.clone(), &cx);
+
+        let output = processes.read_new_output(id, &mut cx).unwrap();
+        assert_eq!(output.command, "cargo watch");
+        assert_eq!(output.output, "Compiling...\n");
+        assert_eq!(processes.read_new_output(id, &mut cx).unwrap().output, "");
+
+        terminal.output.borrow_mut().push_str("Finished\n");
+        assert_eq!(
+            processes.read_new_output(id, &mut cx).unwrap().output,
+            "Finished\n"
+        );
+
+        // Output that was already read scrolling out of the terminal doesn't
+        // move the position of the output that wasn't.
+        terminal.trim("Compiling...\n".len());
+        terminal.output.borrow_mut().push_str("Done\n");
+        let output = processes.read_new_output(id, &mut cx).unwrap();
+        assert_eq!(output.output, "Done\n");
+        assert_eq!(output.skipped_bytes, 0);
+
+        // Output that scrolled out of the terminal before it was read is
+        // reported as skipped.
+        terminal.output.borrow_mut().push_str("Warning\nError\n");
+        terminal.trim("Finished\nDone\nWarning\n".len());
+        let output = processes.read_new_output(id, &mut cx).unwrap();
+        assert_eq!(output.output, "Error\n");
+        assert_eq!(output.skipped_bytes, "Warning\n".len());
+
+        terminal
+            .output
+            .borrow_mut()
+            .push_str(&"x".repeat(COMMAND_OUTPUT_LIMIT as usize + 10));
+        let output = processes.read_new_output(id, &mut cx).unwrap();
+        assert_eq!(output.output.len(), COMMAND_OUTPUT_LIMIT as usize);
+        assert_eq!(output.remaining_bytes, 10);
+        assert_eq!(
+            processes.read_new_output(id, &mut cx).unwrap().output,
+            "x".repeat(10)
+        );
+
+        assert!(processes.remove(id).is_some());
+        assert!(processes.read_new_output(id, &mut cx).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_background_process_tools(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({})).await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+        let environment = Rc::new(FakeThreadEnvironment::default());
+        let processes = BackgroundProcesses::default();
+        let terminal_tool = Arc::new(TerminalTool::new(
+            project,
+            environment.clone(),
+            processes.clone(),
+        ));
+        let input_tool = Arc::new(ProcessInputTool::new(processes.clone()));
+        let kill_tool = Arc::new(KillProcessTool::new(processes.clone()));
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| {
+                terminal_tool.clone().run(
+                    TerminalToolInput {
+                        command: "python3 -i".into(),
+                        cd: "project".into(),
+                        background: true,
+                    },
+                    event_stream,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            "Started \"python3 -i\" in the background as process 1. Use `process_output` to read its output."
+        );
+        // Background processes get all of their output, instead of being
+        // limited like commands the tool waits for.
+        let terminal = {
+            let terminals = environment.terminals.borrow();
+            let (command, output_byte_limit, terminal) = &terminals[0];
+            assert_eq!(command, "python3 -i");
+            assert_eq!(*output_byte_limit, None);
+            terminal.clone()
+        };
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| {
+                input_tool.clone().run(
+                    ProcessInputToolInput {
+                        id: 1,
+                        input: "print(6 * 7)\n".into(),
+                    },
+                    event_stream,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(result, "Sent the input to process 1.");
+        assert_eq!(*terminal.input.borrow(), "print(6 * 7)\n");
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| {
+                input_tool.clone().run(
+                    ProcessInputToolInput {
+                        id: 2,
+                        input: "exit()\n".into(),
+                    },
+                    event_stream,
+                    cx,
+                )
+            })
+            .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "There is no background process with id 2"
+        );
+
+        terminal.output.borrow_mut().push_str("42\n");
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| {
+                kill_tool
+                    .clone()
+                    .run(KillProcessToolInput { id: 1 }, event_stream, cx)
+            })
+            .await
+            .unwrap();
+        assert!(terminal.killed.get());
+        assert_eq!(
+            result,
+            "Killed process 1 (\"python3 -i\"). Its last output was:\n\n```\nprint(6 * 7)\n42\n```"
+        );
+        assert!(processes.terminal(1).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_background_processes_are_killed_with_their_thread(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({})).await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+        let environment = Rc::new(FakeThreadEnvironment::default());
+        // The processes are only held by the tools of their thread.
+        let processes = BackgroundProcesses::default();
+        let terminal_tool = Arc::new(TerminalTool::new(
+            project,
+            environment.clone(),
+            processes.clone(),
+        ));
+        let kill_tool = Arc::new(KillProcessTool::new(processes.clone()));
+        drop(processes);
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        cx.update(|cx| {
+            terminal_tool.clone().run(
+                TerminalToolInput {
+                    command: "npm run dev".into(),
+                    cd: "project".into(),
+                    background: true,
+                },
+                event_stream,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        let terminal = environment.terminals.borrow()[0].2.clone();
+        cx.run_until_parked();
+        assert!(!terminal.killed.get());
+
+        drop(terminal_tool);
+        drop(kill_tool);
+        cx.run_until_parked();
+        assert!(terminal.killed.get());
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            agent_settings::AgentSettings::register(cx);
+            Project::init_settings(cx);
+
+            let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+            settings.always_allow_tool_actions = true;
+            agent_settings::AgentSettings::override_global(settings, cx);
+        });
+    }
+}
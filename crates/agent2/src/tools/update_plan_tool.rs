@@ -0,0 +1,176 @@
+use crate::{AgentTool, Thread, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, SharedString, Task, WeakEntity};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Creates or updates the plan for the current task: a short list of steps, each marked as pending, in progress or completed, which the user sees next to the conversation.
+///
+/// - Use this for work that takes several steps, such as "arrange an 8-bar intro, then mix the drums", before starting on it, and again each time a step starts or finishes.
+/// - Always send the complete list of steps. Steps that are left out are removed from the plan.
+/// - Keep exactly one step in progress while working, and mark steps as completed as soon as they are done.
+/// - Don't use this for simple requests that can be handled in one or two steps.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdatePlanToolInput {
+    /// Every step of the plan, in the order they should be carried out.
+    pub entries: Vec<PlanItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlanItem {
+    /// A short description of the step, such as "Write the bass line for the verse".
+    pub step: String,
+    pub status: PlanItemStatus,
+    #[serde(default)]
+    pub priority: PlanItemPriority,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanItemStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+/// How important the step is to the overall task.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanItemPriority {
+    High,
+    #[default]
+    Medium,
+    Low,
+}
+
+impl From<PlanItem> for acp::PlanEntry {
+    fn from(item: PlanItem) -> Self {
+        acp::PlanEntry {
+            content: item.step,
+            priority: match item.priority {
+                PlanItemPriority::High => acp::PlanEntryPriority::High,
+                PlanItemPriority::Medium => acp::PlanEntryPriority::Medium,
+                PlanItemPriority::Low => acp::PlanEntryPriority::Low,
+            },
+            status: match item.status {
+                PlanItemStatus::Pending => acp::PlanEntryStatus::Pending,
+                PlanItemStatus::InProgress => acp::PlanEntryStatus::InProgress,
+                PlanItemStatus::Completed => acp::PlanEntryStatus::Completed,
+            },
+            meta: None,
+        }
+    }
+}
+
+pub struct UpdatePlanTool {
+    thread: WeakEntity<Thread>,
+}
+
+impl UpdatePlanTool {
+    pub fn new(thread: WeakEntity<Thread>) -> Self {
+        Self { thread }
+    }
+}
+
+impl AgentTool for UpdatePlanTool {
+    type Input = UpdatePlanToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "update_plan"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Think
+    }
+
+    fn initial_title(
+        &self,
+        _input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        "Update plan".into()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let entries = input
+            .entries
+            .into_iter()
+            .map(acp::PlanEntry::from)
+            .collect::<Vec<_>>();
+        let completed = entries
+            .iter()
+            .filter(|entry| matches!(entry.status, acp::PlanEntryStatus::Completed))
+            .count();
+        let output = format!(
+            "Updated the plan: {completed} of {} steps completed.",
+            entries.len()
+        );
+
+        if let Err(error) = self
+            .thread
+            .update(cx, |thread, cx| thread.set_plan(entries.clone(), cx))
+        {
+            return Task::ready(Err(error));
+        }
+        event_stream.update_plan(entries);
+        Task::ready(Ok(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_plan_item_conversion() {
+        let input: UpdatePlanToolInput = serde_json::from_value(json!({
+            "entries": [
+                { "step": "Arrange an 8-bar intro", "status": "completed", "priority": "high" },
+                { "step": "Mix the drums", "status": "in_progress" },
+                { "step": "Bounce a preview", "status": "pending", "priority": "low" },
+            ]
+        }))
+        .unwrap();
+        let entries = input
+            .entries
+            .into_iter()
+            .map(acp::PlanEntry::from)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (
+                    entry.content.as_str(),
+                    entry.status.clone(),
+                    entry.priority.clone()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Arrange an 8-bar intro",
+                    acp::PlanEntryStatus::Completed,
+                    acp::PlanEntryPriority::High
+                ),
+                (
+                    "Mix the drums",
+                    acp::PlanEntryStatus::InProgress,
+                    acp::PlanEntryPriority::Medium
+                ),
+                (
+                    "Bounce a preview",
+                    acp::PlanEntryStatus::Pending,
+                    acp::PlanEntryPriority::Low
+                ),
+            ]
+        );
+    }
+}
@@ -11,7 +11,7 @@ use language_model::{
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ui::prelude::*;
-use web_search::WebSearchRegistry;
+use web_search::{WebSearchRegistry, ZED_WEB_SEARCH_PROVIDER_ID};
 
 /// Search the web for information using your query.
 /// Use this when you need real-time information, facts, or data that might not be in your training.
@@ -56,9 +56,15 @@ impl AgentTool for WebSearchTool {
         "Searching the Web".into()
     }
 
-    /// We currently only support Zed Cloud as a provider.
-    fn supported_provider(&self, provider: &LanguageModelProviderId) -> bool {
-        provider == &ZED_CLOUD_PROVIDER_ID
+    /// Zed's search provider can only be used by Zed-hosted models, while the
+    /// ones configured in the `web_search` settings work with any model.
+    fn supported_provider(&self, provider: &LanguageModelProviderId, cx: &App) -> bool {
+        let Some(active_provider) =
+            WebSearchRegistry::try_read_global(cx).and_then(|registry| registry.active_provider())
+        else {
+            return false;
+        };
+        active_provider.id().0 != ZED_WEB_SEARCH_PROVIDER_ID || provider == &ZED_CLOUD_PROVIDER_ID
     }
 
     fn run(
@@ -0,0 +1,127 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::{Project, lsp_store::SymbolLocation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const RESULTS_PER_PAGE: usize = 50;
+
+/// Searches the symbols (functions, types, constants and so on) that the project's language servers know about.
+///
+/// - Use this to find where something is declared when you know roughly what it's called but not which file it's in.
+/// - Matching is fuzzy, and done by each language server, so results for the same query can differ between languages.
+/// - Language servers only start once a file in their language has been opened. If nothing is found, open a relevant file with `read_file` or use `grep` instead.
+/// - Results are paginated with 50 symbols per page. Use the `offset` parameter to request subsequent pages.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbols to find.
+    ///
+    /// <example>TransportClock</example>
+    pub query: String,
+    /// Optional starting position for paginated results (0-based).
+    #[serde(default)]
+    pub offset: usize,
+}
+
+pub struct WorkspaceSymbolsTool {
+    project: Entity<Project>,
+}
+
+impl WorkspaceSymbolsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for WorkspaceSymbolsTool {
+    type Input = WorkspaceSymbolsToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "workspace_symbols"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Search symbols for {}", MarkdownInlineCode(&input.query)).into(),
+            Err(_) => "Search symbols".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(&input.query, cx));
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!("No symbols matching `{}` found.", input.query));
+            }
+
+            project.read_with(cx, |project, cx| {
+                let path_style = project.path_style(cx);
+                let total = symbols.len();
+                let mut output = String::new();
+                for symbol in symbols.iter().skip(input.offset).take(RESULTS_PER_PAGE) {
+                    let path = match &symbol.path {
+                        SymbolLocation::InProject(project_path) => {
+                            match project.worktree_for_id(project_path.worktree_id, cx) {
+                                Some(worktree) => worktree
+                                    .read(cx)
+                                    .root_name()
+                                    .join(&project_path.path)
+                                    .display(path_style)
+                                    .into_owned(),
+                                None => project_path.path.display(path_style).into_owned(),
+                            }
+                        }
+                        SymbolLocation::OutsideProject { abs_path, .. } => {
+                            abs_path.to_string_lossy().into_owned()
+                        }
+                    };
+                    writeln!(
+                        output,
+                        "{:?} {} in {path}:{}",
+                        symbol.kind,
+                        symbol.name,
+                        symbol.range.start.0.row + 1
+                    )?;
+                }
+
+                if output.is_empty() {
+                    return Ok(format!(
+                        "There are only {total} symbols matching `{}`.",
+                        input.query
+                    ));
+                }
+                let end = (input.offset + RESULTS_PER_PAGE).min(total);
+                if end < total {
+                    Ok(format!(
+                        "Showing symbols {}-{end} of {total} (use offset={end} for the next page):\n{output}",
+                        input.offset + 1
+                    ))
+                } else {
+                    Ok(output)
+                }
+            })?
+        })
+    }
+}
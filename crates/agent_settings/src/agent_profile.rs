@@ -1,13 +1,13 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::{Result, bail};
-use collections::IndexMap;
+use collections::{HashMap, IndexMap};
 use convert_case::{Case, Casing as _};
 use fs::Fs;
 use gpui::{App, SharedString};
 use settings::{
-    AgentProfileContent, ContextServerPresetContent, Settings as _, SettingsContent,
-    update_settings_file,
+    AgentHookContent, AgentHookEvent, AgentProfileContent, ContextServerPresetContent,
+    Settings as _, SettingsContent, update_settings_file,
 };
 use util::ResultExt as _;
 
@@ -64,8 +64,14 @@ impl AgentProfile {
                 .map(|profile| profile.enable_all_context_servers)
                 .unwrap_or_default(),
             context_servers: base_profile
-                .map(|profile| profile.context_servers)
+                .as_ref()
+                .map(|profile| profile.context_servers.clone())
+                .unwrap_or_default(),
+            hooks: base_profile
+                .as_ref()
+                .map(|profile| profile.hooks.clone())
                 .unwrap_or_default(),
+            voice: base_profile.and_then(|profile| profile.voice),
         };
 
         update_settings_file(fs, cx, {
@@ -96,6 +102,10 @@ pub struct AgentProfileSettings {
     pub tools: IndexMap<Arc<str>, bool>,
     pub enable_all_context_servers: bool,
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
+    /// The voice used to read agent responses aloud.
+    pub voice: Option<SharedString>,
+    /// Commands that run before or after tool calls.
+    pub hooks: Vec<AgentHook>,
 }
 
 impl AgentProfileSettings {
@@ -144,6 +154,8 @@ impl AgentProfileSettings {
                         )
                     })
                     .collect(),
+                voice: self.voice.as_ref().map(|voice| voice.to_string()),
+                hooks: self.hooks.iter().cloned().map(Into::into).collect(),
             },
         );
 
@@ -162,6 +174,8 @@ impl From<AgentProfileContent> for AgentProfileSettings {
                 .into_iter()
                 .map(|(server_id, preset)| (server_id, preset.into()))
                 .collect(),
+            voice: content.voice.map(Into::into),
+            hooks: content.hooks.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -178,3 +192,130 @@ impl From<settings::ContextServerPresetContent> for ContextServerPreset {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentHook {
+    pub event: AgentHookEvent,
+    pub tools: Vec<String>,
+    pub command: AgentHookCommand,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<String>,
+    pub timeout: Duration,
+}
+
+/// What an [`AgentHook`] runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AgentHookCommand {
+    /// A command run through the system shell.
+    Shell(String),
+    /// The label of a task from `tasks.json`.
+    Task(String),
+}
+
+impl AgentHookCommand {
+    /// A short description of the command, for telling the agent which hook ran.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Shell(command) => command.clone(),
+            Self::Task(label) => format!("task: {label}"),
+        }
+    }
+}
+
+impl AgentHook {
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Whether the hook runs for the given tool. Tools from context servers
+    /// are identified by their server as well as their name.
+    pub fn matches(&self, tool_name: &str, context_server_id: Option<&str>) -> bool {
+        if self.tools.is_empty() {
+            return true;
+        }
+        self.tools.iter().any(|pattern| {
+            if pattern == "*" {
+                return true;
+            }
+            match (context_server_id, pattern.split_once('/')) {
+                (Some(server_id), Some((pattern_server, pattern_tool))) => {
+                    pattern_server == server_id
+                        && (pattern_tool == "*" || pattern_tool == tool_name)
+                }
+                (None, None) => pattern == tool_name,
+                _ => false,
+            }
+        })
+    }
+}
+
+impl From<AgentHookContent> for AgentHook {
+    fn from(content: AgentHookContent) -> Self {
+        Self {
+            event: content.event,
+            tools: content.tools,
+            command: match content.task {
+                Some(label) => AgentHookCommand::Task(label),
+                None => AgentHookCommand::Shell(content.command.unwrap_or_default()),
+            },
+            env: content.env,
+            cwd: content.cwd,
+            timeout: content
+                .timeout
+                .map(Duration::from_secs)
+                .unwrap_or(Self::DEFAULT_TIMEOUT),
+        }
+    }
+}
+
+impl From<AgentHook> for AgentHookContent {
+    fn from(hook: AgentHook) -> Self {
+        Self {
+            event: hook.event,
+            tools: hook.tools,
+            command: match &hook.command {
+                AgentHookCommand::Shell(command) => Some(command.clone()),
+                AgentHookCommand::Task(_) => None,
+            },
+            task: match hook.command {
+                AgentHookCommand::Shell(_) => None,
+                AgentHookCommand::Task(label) => Some(label),
+            },
+            env: hook.env,
+            cwd: hook.cwd,
+            timeout: (hook.timeout != AgentHook::DEFAULT_TIMEOUT).then(|| hook.timeout.as_secs()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_matches() {
+        let hook = |tools: &[&str]| {
+            AgentHook::from(AgentHookContent {
+                event: AgentHookEvent::PreToolUse,
+                tools: tools.iter().map(|tool| tool.to_string()).collect(),
+                command: Some("true".into()),
+                task: None,
+                env: HashMap::default(),
+                cwd: None,
+                timeout: None,
+            })
+        };
+
+        assert!(hook(&[]).matches("terminal", None));
+        assert!(hook(&[]).matches("query", Some("db")));
+        assert!(hook(&["*"]).matches("query", Some("db")));
+
+        assert!(hook(&["terminal"]).matches("terminal", None));
+        assert!(!hook(&["terminal"]).matches("edit_file", None));
+        assert!(!hook(&["terminal"]).matches("terminal", Some("shell")));
+
+        assert!(hook(&["db/*"]).matches("query", Some("db")));
+        assert!(hook(&["db/query"]).matches("query", Some("db")));
+        assert!(!hook(&["db/query"]).matches("migrate", Some("db")));
+        assert!(!hook(&["db/*"]).matches("query", Some("logs")));
+        assert!(!hook(&["db/*"]).matches("query", None));
+    }
+}
@@ -9,8 +9,8 @@ use project::DisableAiSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{
-    DefaultAgentView, DockPosition, LanguageModelParameters, LanguageModelSelection,
-    NotifyWhenAgentWaiting, Settings, SettingsContent,
+    DefaultAgentView, DockPosition, ImageGenerationSettingsContent, LanguageModelParameters,
+    LanguageModelSelection, NotifyWhenAgentWaiting, ScheduledAgentRun, Settings, SettingsContent,
 };
 
 pub use crate::agent_profile::*;
@@ -51,6 +51,8 @@ pub struct AgentSettings {
     pub expand_terminal_card: bool,
     pub use_modifier_to_send: bool,
     pub message_editor_min_lines: usize,
+    pub scheduled_runs: Vec<ScheduledAgentRun>,
+    pub image_generation: Option<ImageGenerationSettingsContent>,
 }
 
 impl AgentSettings {
@@ -184,6 +186,8 @@ impl Settings for AgentSettings {
             expand_terminal_card: agent.expand_terminal_card.unwrap(),
             use_modifier_to_send: agent.use_modifier_to_send.unwrap(),
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
+            scheduled_runs: agent.scheduled_runs,
+            image_generation: agent.image_generation,
         }
     }
 
@@ -1,9 +1,12 @@
 mod completion_provider;
 mod entry_view_state;
+mod image_preview;
 mod message_editor;
 mod mode_selector;
 mod model_selector;
 mod model_selector_popover;
+mod piano_roll;
+mod subagent_view;
 mod thread_history;
 mod thread_view;
 
@@ -6,10 +6,11 @@ use agent2::HistoryStore;
 use collections::HashMap;
 use editor::{Editor, EditorMode, MinimapVisibility};
 use gpui::{
-    AnyEntity, App, AppContext as _, Entity, EntityId, EventEmitter, FocusHandle, Focusable,
+    AnyEntity, App, AppContext as _, Entity, EntityId, EventEmitter, FocusHandle, Focusable, Image,
     ScrollHandle, SharedString, TextStyleRefinement, WeakEntity, Window,
 };
 use language::language_settings::SoftWrap;
+use midi::MidiPattern;
 use project::Project;
 use prompt_store::PromptStore;
 use settings::Settings as _;
@@ -18,7 +19,12 @@ use theme::ThemeSettings;
 use ui::{Context, TextSize};
 use workspace::Workspace;
 
-use crate::acp::message_editor::{MessageEditor, MessageEditorEvent};
+use crate::acp::{
+    image_preview::ImagePreview,
+    message_editor::{MessageEditor, MessageEditorEvent},
+    piano_roll::PianoRoll,
+    subagent_view::SubagentView,
+};
 
 pub struct EntryViewState {
     workspace: WeakEntity<Workspace>,
@@ -118,6 +124,9 @@ impl EntryViewState {
                 let id = tool_call.id.clone();
                 let terminals = tool_call.terminals().cloned().collect::<Vec<_>>();
                 let diffs = tool_call.diffs().cloned().collect::<Vec<_>>();
+                let piano_rolls = tool_call.piano_rolls().cloned().collect::<Vec<_>>();
+                let subagents = tool_call.subagents().cloned().collect::<Vec<_>>();
+                let images = tool_call.images().cloned().collect::<Vec<_>>();
 
                 let views = if let Some(Entry::Content(views)) = self.entries.get_mut(index) {
                     views
@@ -170,6 +179,41 @@ impl EntryViewState {
                         element
                     });
                 }
+
+                for pattern in piano_rolls {
+                    views.entry(pattern.entity_id()).or_insert_with(|| {
+                        let element = cx.new(|_| PianoRoll::new(pattern.clone())).into_any();
+                        cx.emit(EntryViewEvent {
+                            entry_index: index,
+                            view_event: ViewEvent::NewPianoRoll(id.clone()),
+                        });
+                        element
+                    });
+                }
+
+                for image in images {
+                    views.entry(image.entity_id()).or_insert_with(|| {
+                        let element = cx.new(|cx| ImagePreview::new(image.clone(), cx)).into_any();
+                        cx.emit(EntryViewEvent {
+                            entry_index: index,
+                            view_event: ViewEvent::NewImage(id.clone()),
+                        });
+                        element
+                    });
+                }
+
+                for thread in subagents {
+                    views.entry(thread.entity_id()).or_insert_with(|| {
+                        let element = cx
+                            .new(|cx| SubagentView::new(thread.clone(), cx))
+                            .into_any();
+                        cx.emit(EntryViewEvent {
+                            entry_index: index,
+                            view_event: ViewEvent::NewSubagent(id.clone()),
+                        });
+                        element
+                    });
+                }
             }
             AgentThreadEntry::AssistantMessage(message) => {
                 let entry = if let Some(Entry::AssistantMessage(entry)) =
@@ -234,6 +278,9 @@ pub struct EntryViewEvent {
 pub enum ViewEvent {
     NewDiff(ToolCallId),
     NewTerminal(ToolCallId),
+    NewPianoRoll(ToolCallId),
+    NewImage(ToolCallId),
+    NewSubagent(ToolCallId),
     TerminalMovedToBackground(ToolCallId),
     MessageEditorEvent(Entity<MessageEditor>, MessageEditorEvent),
 }
@@ -296,6 +343,27 @@ impl Entry {
             .map(|entity| entity.downcast::<TerminalView>().unwrap())
     }
 
+    pub fn piano_roll(&self, pattern: &Entity<MidiPattern>) -> Option<Entity<PianoRoll>> {
+        self.content_map()?
+            .get(&pattern.entity_id())
+            .cloned()
+            .map(|entity| entity.downcast::<PianoRoll>().unwrap())
+    }
+
+    pub fn image_preview(&self, image: &Entity<Image>) -> Option<Entity<ImagePreview>> {
+        self.content_map()?
+            .get(&image.entity_id())
+            .cloned()
+            .map(|entity| entity.downcast::<ImagePreview>().unwrap())
+    }
+
+    pub fn subagent(&self, thread: &Entity<AcpThread>) -> Option<Entity<SubagentView>> {
+        self.content_map()?
+            .get(&thread.entity_id())
+            .cloned()
+            .map(|entity| entity.downcast::<SubagentView>().unwrap())
+    }
+
     pub fn scroll_handle_for_assistant_message_chunk(
         &self,
         chunk_ix: usize,
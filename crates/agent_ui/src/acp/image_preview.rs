@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use gpui::{Image, ObjectFit, img, px};
+use ui::prelude::*;
+
+const MAX_HEIGHT: f32 = 400.;
+
+/// Previews an image generated by a tool, scaled down to fit the card.
+pub struct ImagePreview {
+    image: Arc<Image>,
+}
+
+impl ImagePreview {
+    pub fn new(image: Entity<Image>, cx: &App) -> Self {
+        Self {
+            image: Arc::new(image.read(cx).clone()),
+        }
+    }
+}
+
+impl Render for ImagePreview {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex().p_2().w_full().justify_center().child(
+            img(self.image.clone())
+                .object_fit(ObjectFit::ScaleDown)
+                .max_w_full()
+                .max_h(px(MAX_HEIGHT)),
+        )
+    }
+}
@@ -9,6 +9,8 @@ use agent2::HistoryStore;
 use anyhow::{Result, anyhow};
 use assistant_slash_commands::codeblock_fence_for_path;
 use assistant_tool::outline;
+use audio::AudioClip;
+use audio_metadata::AudioInfo;
 use collections::{HashMap, HashSet};
 use editor::{
     Addon, Anchor, AnchorRangeExt, ContextMenuOptions, ContextMenuPlacement, Editor, EditorElement,
@@ -27,7 +29,7 @@ use gpui::{
     Subscription, Task, TextStyle, WeakEntity, pulsating_between,
 };
 use language::{Buffer, Language, language_settings::InlayHintKind};
-use language_model::LanguageModelImage;
+use language_model::{LanguageModelAudio, LanguageModelImage};
 use postage::stream::Stream as _;
 use project::{
     CompletionIntent, InlayHint, InlayHintLabel, Project, ProjectItem, ProjectPath, Worktree,
@@ -35,10 +37,12 @@ use project::{
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
 use settings::Settings;
+use speech::{Dictation, DictationEvent, SpeechSettings};
 use std::{
     cell::RefCell,
     ffi::OsStr,
     fmt::Write,
+    io::Read as _,
     ops::{Range, RangeInclusive},
     path::{Path, PathBuf},
     rc::Rc,
@@ -49,7 +53,10 @@ use text::OffsetRangeExt;
 use theme::ThemeSettings;
 use ui::{ButtonLike, TintColor, Toggleable, prelude::*};
 use util::{ResultExt, debug_panic, rel_path::RelPath};
-use workspace::{Workspace, notifications::NotifyResultExt as _};
+use workspace::{
+    Toast, Workspace,
+    notifications::{NotificationId, NotifyResultExt as _},
+};
 use zed_actions::agent::Chat;
 
 pub struct MessageEditor {
@@ -62,8 +69,17 @@ pub struct MessageEditor {
     prompt_capabilities: Rc<RefCell<acp::PromptCapabilities>>,
     available_commands: Rc<RefCell<Vec<acp::AvailableCommand>>>,
     agent_name: SharedString,
+    dictation: Option<ActiveDictation>,
     _subscriptions: Vec<Subscription>,
     _parse_slash_command_task: Task<()>,
+    _start_dictation_task: Task<()>,
+}
+
+struct ActiveDictation {
+    dictation: Entity<Dictation>,
+    /// The text inserted by the latest transcript, which the next transcript replaces.
+    transcript_range: Range<Anchor>,
+    _subscription: Subscription,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -78,6 +94,11 @@ impl EventEmitter<MessageEditorEvent> for MessageEditor {}
 
 const COMMAND_HINT_INLAY_ID: u32 = 0;
 
+/// Models accept about 20MB of inline audio per request. Attachments are converted
+/// to 16kHz mono, so this keeps each of them well below that. Longer ones are
+/// only attached as their analysis.
+const MAX_AUDIO_ATTACHMENT_DURATION: Duration = Duration::from_secs(5 * 60);
+
 impl MessageEditor {
     pub fn new(
         workspace: WeakEntity<Workspace>,
@@ -178,8 +199,10 @@ impl MessageEditor {
             prompt_capabilities,
             available_commands,
             agent_name,
+            dictation: None,
             _subscriptions: subscriptions,
             _parse_slash_command_task: Task::ready(()),
+            _start_dictation_task: Task::ready(()),
         }
     }
 
@@ -405,6 +428,49 @@ impl MessageEditor {
         })
     }
 
+    fn confirm_mention_for_audio(
+        &mut self,
+        abs_path: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let project = self.project.read(cx);
+        if !project.is_local() {
+            return Task::ready(Err(anyhow!(
+                "Audio attachments are only supported in local projects"
+            )));
+        }
+        let fs = project.fs().clone();
+        cx.background_spawn(async move {
+            // Formats with a header tell their length up front, so that long
+            // recordings are only analyzed rather than also converted for the
+            // model.
+            let mut header = Vec::new();
+            fs.open_sync(&abs_path)
+                .await?
+                .take(AudioInfo::HEADER_LEN as u64)
+                .read_to_end(&mut header)?;
+            let header_duration = AudioInfo::parse(&header).map(|info| info.duration);
+
+            let clip = AudioClip::decode(fs.load_bytes(&abs_path).await?)?;
+            let analysis = clip.analyze().to_markdown();
+            if header_duration.unwrap_or_else(|| clip.duration()) > MAX_AUDIO_ATTACHMENT_DURATION {
+                return Ok(Mention::Text {
+                    content: format!(
+                        "The user attached an audio file that is longer than {} minutes, which \
+                        is too long to send to the model. This is an automated analysis of \
+                        it:\n\n{analysis}",
+                        MAX_AUDIO_ATTACHMENT_DURATION.as_secs() / 60
+                    ),
+                    tracked_buffers: Vec::new(),
+                });
+            }
+            Ok(Mention::Audio(MentionAudio {
+                audio: LanguageModelAudio::from_wav(&clip.to_model_wav()),
+                analysis,
+            }))
+        })
+    }
+
     fn confirm_mention_for_file(
         &mut self,
         abs_path: PathBuf,
@@ -422,6 +488,10 @@ impl MessageEditor {
             .and_then(OsStr::to_str)
             .unwrap_or_default();
 
+        if AudioClip::is_supported_extension(extension) {
+            return self.confirm_mention_for_audio(abs_path, cx);
+        }
+
         if Img::extensions().contains(&extension) && !extension.contains("svg") {
             if !self.prompt_capabilities.borrow().image {
                 return Task::ready(Err(anyhow!("This model does not support images yet")));
@@ -771,6 +841,16 @@ impl MessageEditor {
                                     meta: None,
                                 })
                             }
+                            Mention::Audio(mention_audio) => {
+                                acp::ContentBlock::Audio(acp::AudioContent {
+                                    annotations: None,
+                                    data: mention_audio.audio.source.to_string(),
+                                    mime_type: mention_audio.audio.format.mime_type().into(),
+                                    meta: Some(acp_thread::audio_analysis_meta(
+                                        &mention_audio.analysis,
+                                    )),
+                                })
+                            }
                             Mention::UriOnly => {
                                 acp::ContentBlock::ResourceLink(acp::ResourceLink {
                                     name: uri.name(),
@@ -834,6 +914,112 @@ impl MessageEditor {
         cx.emit(MessageEditorEvent::Cancel)
     }
 
+    pub fn is_dictating(&self) -> bool {
+        self.dictation.is_some()
+    }
+
+    /// Starts recording the microphone, or stops an ongoing recording.
+    pub fn toggle_dictation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.dictation.is_some() {
+            self.stop_dictation(cx);
+        } else {
+            self.start_dictation(window, cx);
+        }
+    }
+
+    /// Starts recording the microphone, unless it's already recording. Transcripts
+    /// are inserted at the cursor, with each partial transcript replacing the previous one.
+    pub fn start_dictation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.dictation.is_some() {
+            return;
+        }
+
+        let provider = speech::transcription_provider(cx.http_client(), cx);
+        let interval = SpeechSettings::get_global(cx).partial_transcript_interval;
+        self._start_dictation_task = cx.spawn_in(window, async move |this, cx| {
+            let Some(provider) = provider.await.notify_async_err(cx) else {
+                return;
+            };
+            this.update_in(cx, |this, window, cx| {
+                this.dictate_with(provider, interval, window, cx)
+            })
+            .ok();
+        });
+    }
+
+    /// Stops recording the microphone, including a recording that is still
+    /// starting, and inserts the final transcript.
+    pub fn stop_dictation(&mut self, cx: &mut Context<Self>) {
+        self._start_dictation_task = Task::ready(());
+        if let Some(active) = &self.dictation {
+            active
+                .dictation
+                .update(cx, |dictation, cx| dictation.stop(cx));
+        }
+    }
+
+    fn dictate_with(
+        &mut self,
+        provider: Arc<dyn speech::TranscriptionProvider>,
+        interval: Duration,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let transcript_range = self.editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let cursor = editor.selections.newest::<usize>(cx).head();
+            snapshot.anchor_before(cursor)..snapshot.anchor_after(cursor)
+        });
+        let dictation = cx.new(|cx| Dictation::new(provider, interval, cx));
+        let subscription = cx.subscribe_in(&dictation, window, Self::handle_dictation_event);
+        self.dictation = Some(ActiveDictation {
+            dictation,
+            transcript_range,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn handle_dictation_event(
+        &mut self,
+        _: &Entity<Dictation>,
+        event: &DictationEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active) = &self.dictation else {
+            return;
+        };
+        match event {
+            DictationEvent::Transcript { text, is_final } => {
+                let range = active.transcript_range.clone();
+                self.editor.update(cx, |editor, cx| {
+                    editor.edit([(range, text.as_str())], cx);
+                });
+                if *is_final {
+                    self.dictation = None;
+                    window.focus(&self.editor.focus_handle(cx));
+                }
+            }
+            DictationEvent::Error(error) => {
+                self.dictation = None;
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        struct DictationError;
+                        workspace.show_toast(
+                            Toast::new(
+                                NotificationId::unique::<DictationError>(),
+                                format!("Dictation failed: {error}"),
+                            ),
+                            cx,
+                        );
+                    })
+                    .ok();
+            }
+        }
+        cx.notify();
+    }
+
     fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
         if !self.prompt_capabilities.borrow().image {
             return;
@@ -1498,6 +1684,7 @@ pub enum Mention {
         tracked_buffers: Vec<Entity<Buffer>>,
     },
     Image(MentionImage),
+    Audio(MentionAudio),
     UriOnly,
 }
 
@@ -1507,6 +1694,14 @@ pub struct MentionImage {
     pub format: ImageFormat,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MentionAudio {
+    pub audio: LanguageModelAudio,
+    /// An automated analysis of the audio, which is sent in its place when the
+    /// agent doesn't support audio input.
+    pub analysis: String,
+}
+
 #[derive(Default)]
 pub struct MentionSet {
     mentions: HashMap<CreaseId, (MentionUri, Shared<Task<Result<Mention, String>>>)>,
@@ -1530,6 +1725,7 @@ impl MentionSet {
             return Task::ready(Ok(mentions));
         }
 
+        let supports_audio = prompt_capabilities.audio;
         let mentions = self.mentions.clone();
         cx.spawn(async move |cx| {
             let mut contents = HashMap::default();
@@ -1542,6 +1738,17 @@ impl MentionSet {
                 } else {
                     task.await.map_err(|e| anyhow!("{e}"))?
                 };
+                let content = match content {
+                    Mention::Audio(audio) if !supports_audio => Mention::Text {
+                        content: format!(
+                            "The user attached an audio file, but the current model can't \
+                            listen to it. This is an automated analysis of it:\n\n{}",
+                            audio.analysis
+                        ),
+                        tracked_buffers: Vec::new(),
+                    },
+                    content => content,
+                };
 
                 contents.insert(crease_id, (mention_uri, content));
             }
@@ -0,0 +1,120 @@
+use gpui::{Bounds, Entity, Pixels, canvas, fill, point, px, size};
+use midi::{MidiPattern, note_name};
+use ui::prelude::*;
+
+/// The height of a single pitch, so that short and wide ranges are equally
+/// easy to read.
+const ROW_HEIGHT: f32 = 5.;
+const MIN_HEIGHT: f32 = 60.;
+
+/// Previews a MIDI pattern generated by a tool, with time running from left
+/// to right and pitch from bottom to top.
+pub struct PianoRoll {
+    pattern: Entity<MidiPattern>,
+}
+
+impl PianoRoll {
+    pub fn new(pattern: Entity<MidiPattern>) -> Self {
+        Self { pattern }
+    }
+}
+
+impl Render for PianoRoll {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let pattern = self.pattern.read(cx);
+        let Some(pitch_range) = pattern.pitch_range() else {
+            return div()
+                .p_2()
+                .child(
+                    Label::new("The pattern is empty")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element();
+        };
+        // Leave an empty row above and below the notes.
+        let lowest = pitch_range.start().saturating_sub(1);
+        let highest = pitch_range.end().saturating_add(1).min(127);
+        let row_count = (highest - lowest + 1) as f32;
+
+        let colors = cx.theme().colors();
+        let bar_color = colors.border;
+        let beat_color = colors.border_variant;
+        let note_color = colors.text_accent;
+        let details = format!(
+            "{} notes · {} bars · {} BPM",
+            pattern.notes.len(),
+            pattern.bars,
+            pattern.bpm
+        );
+
+        let pattern = self.pattern.clone();
+        let paint = move |bounds: Bounds<Pixels>, _, window: &mut Window, cx: &mut App| {
+            let pattern = pattern.read(cx);
+            let left: f32 = bounds.origin.x.into();
+            let top: f32 = bounds.origin.y.into();
+            let width: f32 = bounds.size.width.into();
+            let height: f32 = bounds.size.height.into();
+            let ticks_per_pixel = pattern.len_in_ticks() as f32 / width;
+            let row_height = height / row_count;
+            let beat_width = pattern.ticks_per_beat as f32 / ticks_per_pixel;
+
+            for beat in 0..pattern.bars * pattern.beats_per_bar {
+                let x = left + beat as f32 * beat_width;
+                let color = if beat.is_multiple_of(pattern.beats_per_bar) {
+                    bar_color
+                } else {
+                    beat_color
+                };
+                window.paint_quad(fill(
+                    Bounds::new(point(px(x), px(top)), size(px(1.), px(height))),
+                    color,
+                ));
+            }
+
+            for note in &pattern.notes {
+                let x = left + note.start as f32 / ticks_per_pixel;
+                let y = top + (highest - note.pitch) as f32 * row_height;
+                let note_width = (note.duration as f32 / ticks_per_pixel - 1.).max(1.);
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(px(x), px(y)),
+                        size(px(note_width), px((row_height - 1.).max(1.))),
+                    ),
+                    note_color.opacity(0.4 + 0.6 * note.velocity as f32 / 127.),
+                ));
+            }
+        };
+
+        v_flex()
+            .p_2()
+            .gap_1()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        Label::new(format!(
+                            "{}–{}",
+                            note_name(*pitch_range.start()),
+                            note_name(*pitch_range.end())
+                        ))
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted)
+                        .buffer_font(cx),
+                    )
+                    .child(
+                        Label::new(details)
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                canvas(|_, _, _| (), paint)
+                    .w_full()
+                    .h(px((row_count * ROW_HEIGHT).max(MIN_HEIGHT)))
+                    .rounded_sm()
+                    .bg(colors.editor_background),
+            )
+            .into_any_element()
+    }
+}
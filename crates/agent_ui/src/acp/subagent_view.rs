@@ -0,0 +1,136 @@
+use acp_thread::{
+    AcpThread, AcpThreadEvent, AgentThreadEntry, AssistantMessageChunk, ThreadStatus,
+    ToolCallStatus,
+};
+use agent_client_protocol as acp;
+use gpui::{Entity, Subscription};
+use markdown::MarkdownElement;
+use ui::{CommonAnimationExt as _, prelude::*};
+
+use crate::acp::thread_view::default_markdown_style;
+
+/// Shows the progress of a thread that a tool delegated a task to: one row
+/// per tool call, followed by the subagent's latest message.
+pub struct SubagentView {
+    thread: Entity<AcpThread>,
+    _subscription: Subscription,
+}
+
+impl SubagentView {
+    pub fn new(thread: Entity<AcpThread>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.subscribe(&thread, |_, _, _: &AcpThreadEvent, cx| cx.notify());
+        Self {
+            thread,
+            _subscription: subscription,
+        }
+    }
+}
+
+impl Render for SubagentView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let thread = self.thread.read(cx);
+        let is_generating = thread.status() == ThreadStatus::Generating;
+
+        let steps = thread
+            .entries()
+            .iter()
+            .filter_map(|entry| match entry {
+                AgentThreadEntry::ToolCall(tool_call) => Some(tool_call),
+                AgentThreadEntry::UserMessage(_) | AgentThreadEntry::AssistantMessage(_) => None,
+            })
+            .enumerate()
+            .map(|(ix, tool_call)| {
+                let status_icon = match &tool_call.status {
+                    ToolCallStatus::Pending | ToolCallStatus::InProgress => {
+                        Icon::new(IconName::ArrowCircle)
+                            .size(IconSize::XSmall)
+                            .color(Color::Muted)
+                            .with_rotate_animation(2)
+                            .into_any_element()
+                    }
+                    ToolCallStatus::WaitingForConfirmation { .. } => {
+                        Label::new("Waiting for approval")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Warning)
+                            .into_any_element()
+                    }
+                    ToolCallStatus::Completed => Icon::new(IconName::Check)
+                        .size(IconSize::XSmall)
+                        .color(Color::Success)
+                        .into_any_element(),
+                    ToolCallStatus::Failed
+                    | ToolCallStatus::Rejected
+                    | ToolCallStatus::Canceled => Icon::new(IconName::Close)
+                        .size(IconSize::XSmall)
+                        .color(Color::Error)
+                        .into_any_element(),
+                };
+
+                h_flex()
+                    .id(("subagent-step", ix))
+                    .gap_1p5()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_1p5()
+                            .min_w_0()
+                            .child(
+                                Icon::new(tool_icon(tool_call.kind))
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                Label::new(tool_call.label.read(cx).source().to_string())
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted)
+                                    .truncate(),
+                            ),
+                    )
+                    .child(status_icon)
+            })
+            .collect::<Vec<_>>();
+
+        let latest_message = thread.entries().iter().rev().find_map(|entry| match entry {
+            AgentThreadEntry::AssistantMessage(message) => {
+                message.chunks.iter().rev().find_map(|chunk| match chunk {
+                    AssistantMessageChunk::Message { block } => block.markdown().cloned(),
+                    AssistantMessageChunk::Thought { .. } => None,
+                })
+            }
+            AgentThreadEntry::UserMessage(_) | AgentThreadEntry::ToolCall(_) => None,
+        });
+
+        v_flex()
+            .p_2()
+            .gap_1()
+            .children(steps)
+            .when(is_generating && latest_message.is_none(), |this| {
+                this.child(
+                    Label::new("Starting…")
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                )
+            })
+            .children(latest_message.map(|markdown| {
+                div().pt_1().text_xs().child(MarkdownElement::new(
+                    markdown,
+                    default_markdown_style(false, false, window, cx),
+                ))
+            }))
+    }
+}
+
+fn tool_icon(kind: acp::ToolKind) -> IconName {
+    match kind {
+        acp::ToolKind::Read => IconName::ToolSearch,
+        acp::ToolKind::Edit => IconName::ToolPencil,
+        acp::ToolKind::Delete => IconName::ToolDeleteFile,
+        acp::ToolKind::Move => IconName::ArrowRightLeft,
+        acp::ToolKind::Search => IconName::ToolSearch,
+        acp::ToolKind::Execute => IconName::ToolTerminal,
+        acp::ToolKind::Think => IconName::ToolThink,
+        acp::ToolKind::Fetch => IconName::ToolWeb,
+        acp::ToolKind::SwitchMode => IconName::ArrowRightLeft,
+        acp::ToolKind::Other => IconName::ToolHammer,
+    }
+}
@@ -11,11 +11,10 @@ use agent_settings::{AgentProfileId, AgentSettings, CompletionMode};
 use agent2::{DbThreadMetadata, HistoryEntry, HistoryEntryId, HistoryStore, NativeAgentServer};
 use anyhow::{Result, anyhow, bail};
 use arrayvec::ArrayVec;
-use audio::{Audio, Sound};
 use buffer_diff::BufferDiff;
 use client::zed_urls;
 use cloud_llm_client::PlanV1;
-use collections::{HashMap, HashSet};
+use collections::HashSet;
 use editor::scroll::Autoscroll;
 use editor::{Editor, EditorEvent, EditorMode, MultiBuffer, PathKey, SelectionEffects};
 use file_icons::FileIcons;
@@ -23,19 +22,21 @@ use fs::Fs;
 use futures::FutureExt as _;
 use gpui::{
     Action, Animation, AnimationExt, AnyView, App, BorderStyle, ClickEvent, ClipboardItem,
-    CursorStyle, EdgesRefinement, ElementId, Empty, Entity, FocusHandle, Focusable, Hsla, Length,
-    ListOffset, ListState, PlatformDisplay, SharedString, StyleRefinement, Subscription, Task,
-    TextStyle, TextStyleRefinement, UnderlineStyle, WeakEntity, Window, WindowHandle, div,
-    ease_in_out, linear_color_stop, linear_gradient, list, point, pulsating_between,
+    CursorStyle, EdgesRefinement, ElementId, Empty, Entity, FocusHandle, Focusable, Hsla, Image,
+    Length, ListOffset, ListState, SharedString, StyleRefinement, Subscription, Task, TextStyle,
+    TextStyleRefinement, UnderlineStyle, WeakEntity, Window, div, ease_in_out, linear_color_stop,
+    linear_gradient, list, point, pulsating_between,
 };
 use language::Buffer;
 
 use language_model::LanguageModelRegistry;
 use markdown::{HeadingLevelStyles, Markdown, MarkdownElement, MarkdownStyle};
-use project::{Project, ProjectEntryId};
+use midi::MidiPattern;
+use project::{Project, ProjectEntryId, ProjectPath};
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
-use settings::{NotifyWhenAgentWaiting, Settings as _, SettingsStore};
+use settings::{Settings as _, SettingsStore};
+use speech::{ReadAloud, ReadAloudEvent, SpeechSettings};
 use std::cell::RefCell;
 use std::path::Path;
 use std::sync::Arc;
@@ -49,7 +50,7 @@ use ui::{
     PopoverMenuHandle, SpinnerLabel, TintColor, Tooltip, WithScrollbar, prelude::*,
 };
 use util::{ResultExt, size::format_file_size, time::duration_alt_display};
-use workspace::{CollaboratorId, Workspace};
+use workspace::{CollaboratorId, Toast, Workspace, notifications::NotificationId};
 use zed_actions::agent::{Chat, ToggleModelSelector};
 use zed_actions::assistant::OpenRulesLibrary;
 
@@ -62,13 +63,13 @@ use crate::agent_diff::AgentDiff;
 use crate::profile_selector::{ProfileProvider, ProfileSelector};
 
 use crate::ui::{
-    AgentNotification, AgentNotificationEvent, BurnModeTooltip, UnavailableEditingTooltip,
-    UsageCallout,
+    AgentNotification, AgentNotifications, BurnModeTooltip, UnavailableEditingTooltip, UsageCallout,
 };
 use crate::{
     AgentDiffPane, AgentPanel, AllowAlways, AllowOnce, ContinueThread, ContinueWithBurnMode,
-    CycleModeSelector, ExpandMessageEditor, Follow, KeepAll, OpenAgentDiff, OpenHistory, RejectAll,
-    RejectOnce, ToggleBurnMode, ToggleProfileSelector,
+    CycleModeSelector, ExpandMessageEditor, Follow, KeepAll, OpenAgentDiff, OpenHistory,
+    PushToTalk, RejectAll, RejectOnce, StopReadingAloud, ToggleBurnMode, ToggleDictation,
+    ToggleProfileSelector,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -271,8 +272,7 @@ pub struct AcpThreadView {
     focus_handle: FocusHandle,
     model_selector: Option<Entity<AcpModelSelectorPopover>>,
     profile_selector: Option<Entity<ProfileSelector>>,
-    notifications: Vec<WindowHandle<AgentNotification>>,
-    notification_subscriptions: HashMap<WindowHandle<AgentNotification>, Vec<Subscription>>,
+    notifications: AgentNotifications,
     thread_retry_status: Option<RetryStatus>,
     thread_error: Option<ThreadError>,
     thread_feedback: ThreadFeedbackState,
@@ -290,6 +290,11 @@ pub struct AcpThreadView {
     is_loading_contents: bool,
     new_server_version_available: Option<SharedString>,
     resume_thread_metadata: Option<DbThreadMetadata>,
+    read_aloud: Option<(Entity<ReadAloud>, Subscription)>,
+    /// Whether dictation was started by [`PushToTalk`], and so stops once its
+    /// keys are released.
+    push_to_talk: bool,
+    _read_aloud_task: Task<()>,
     _cancel_task: Option<Task<()>>,
     _subscriptions: [Subscription; 5],
 }
@@ -412,8 +417,7 @@ impl AcpThreadView {
             model_selector: None,
             profile_selector: None,
 
-            notifications: Vec::new(),
-            notification_subscriptions: HashMap::default(),
+            notifications: AgentNotifications::default(),
             list_state: list_state,
             thread_retry_status: None,
             thread_error: None,
@@ -431,6 +435,9 @@ impl AcpThreadView {
             history_store,
             hovered_recent_history_item: None,
             is_loading_contents: false,
+            read_aloud: None,
+            push_to_talk: false,
+            _read_aloud_task: Task::ready(()),
             _subscriptions: subscriptions,
             _cancel_task: None,
             focus_handle: cx.focus_handle(),
@@ -800,10 +807,7 @@ impl AcpThreadView {
         // If we're in a LoadError state OR have a thread_error set (which can happen
         // when agent.connect() fails during loading), retry loading the thread.
         // This handles the case where a thread is restored before authentication completes.
-        let should_retry =
-            matches!(&self.thread_state, ThreadState::LoadError(_)) || self.thread_error.is_some();
-
-        if should_retry {
+        if self.has_error() {
             self.thread_error = None;
             self.reset(window, cx);
         }
@@ -854,6 +858,10 @@ impl AcpThreadView {
         }
     }
 
+    pub fn has_error(&self) -> bool {
+        matches!(&self.thread_state, ThreadState::LoadError(_)) || self.thread_error.is_some()
+    }
+
     pub fn cancel_generation(&mut self, cx: &mut Context<Self>) {
         self.thread_error.take();
         self.thread_retry_status.take();
@@ -958,6 +966,14 @@ impl AcpThreadView {
                     self.expanded_tool_calls.insert(tool_call_id.clone());
                 }
             }
+            ViewEvent::NewPianoRoll(tool_call_id) | ViewEvent::NewImage(tool_call_id) => {
+                if AgentSettings::get_global(cx).expand_edit_card {
+                    self.expanded_tool_calls.insert(tool_call_id.clone());
+                }
+            }
+            ViewEvent::NewSubagent(tool_call_id) => {
+                self.expanded_tool_calls.insert(tool_call_id.clone());
+            }
             ViewEvent::NewTerminal(tool_call_id) => {
                 if AgentSettings::get_global(cx).expand_terminal_card {
                     self.expanded_tool_calls.insert(tool_call_id.clone());
@@ -1022,6 +1038,8 @@ impl AcpThreadView {
     fn send(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(thread) = self.thread() else { return };
 
+        self.stop_reading_aloud(cx);
+
         if self.is_loading_contents {
             return;
         }
@@ -1359,6 +1377,7 @@ impl AcpThreadView {
             }
             AcpThreadEvent::Stopped => {
                 self.thread_retry_status.take();
+                self.read_last_response_aloud(thread, cx);
                 let used_tools = thread.read(cx).used_tools_since_last_user_message();
                 self.notify_with_sound(
                     if used_tools {
@@ -2118,7 +2137,9 @@ impl AcpThreadView {
         let is_edit =
             matches!(tool_call.kind, acp::ToolKind::Edit) || tool_call.diffs().next().is_some();
 
-        let use_card_layout = needs_confirmation || is_edit || is_terminal_tool;
+        let is_subagent = tool_call.subagents().next().is_some();
+
+        let use_card_layout = needs_confirmation || is_edit || is_terminal_tool || is_subagent;
 
         let is_collapsible = !tool_call.content.is_empty() && !needs_confirmation;
 
@@ -2435,6 +2456,9 @@ impl AcpThreadView {
             ToolCallContent::Terminal(terminal) => {
                 self.render_terminal_tool_call(entry_ix, terminal, tool_call, window, cx)
             }
+            ToolCallContent::PianoRoll(pattern) => self.render_piano_roll(entry_ix, pattern, cx),
+            ToolCallContent::Subagent(thread) => self.render_subagent(entry_ix, thread, cx),
+            ToolCallContent::Image(image) => self.render_image_preview(entry_ix, image, cx),
         }
     }
 
@@ -2703,6 +2727,60 @@ impl AcpThreadView {
             .into_any()
     }
 
+    fn render_piano_roll(
+        &self,
+        entry_ix: usize,
+        pattern: &Entity<MidiPattern>,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        v_flex()
+            .border_t_1()
+            .border_color(self.tool_card_border_color(cx))
+            .children(
+                self.entry_view_state
+                    .read(cx)
+                    .entry(entry_ix)
+                    .and_then(|entry| entry.piano_roll(pattern)),
+            )
+            .into_any()
+    }
+
+    fn render_image_preview(
+        &self,
+        entry_ix: usize,
+        image: &Entity<Image>,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        v_flex()
+            .border_t_1()
+            .border_color(self.tool_card_border_color(cx))
+            .children(
+                self.entry_view_state
+                    .read(cx)
+                    .entry(entry_ix)
+                    .and_then(|entry| entry.image_preview(image)),
+            )
+            .into_any()
+    }
+
+    fn render_subagent(
+        &self,
+        entry_ix: usize,
+        thread: &Entity<AcpThread>,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        v_flex()
+            .border_t_1()
+            .border_color(self.tool_card_border_color(cx))
+            .children(
+                self.entry_view_state
+                    .read(cx)
+                    .entry(entry_ix)
+                    .and_then(|entry| entry.subagent(thread)),
+            )
+            .into_any()
+    }
+
     fn render_terminal_tool_call(
         &self,
         entry_ix: usize,
@@ -3404,9 +3482,11 @@ impl AcpThreadView {
         let thread = thread_entity.read(cx);
         let action_log = thread.action_log();
         let changed_buffers = action_log.read(cx).changed_buffers(cx);
+        let changed_files = action_log.read(cx).changed_files().collect::<Vec<_>>();
+        let has_changes = !changed_buffers.is_empty() || !changed_files.is_empty();
         let plan = thread.plan();
 
-        if changed_buffers.is_empty() && plan.is_empty() {
+        if !has_changes && plan.is_empty() {
             return None;
         }
 
@@ -3437,12 +3517,12 @@ impl AcpThreadView {
                         parent.child(self.render_plan_entries(plan, window, cx))
                     })
             })
-            .when(!plan.is_empty() && !changed_buffers.is_empty(), |this| {
+            .when(!plan.is_empty() && has_changes, |this| {
                 this.child(Divider::horizontal().color(DividerColor::Border))
             })
-            .when(!changed_buffers.is_empty(), |this| {
+            .when(has_changes, |this| {
                 this.child(self.render_edits_summary(
-                    &changed_buffers,
+                    changed_buffers.len() + changed_files.len(),
                     self.edits_expanded,
                     pending_edits,
                     window,
@@ -3452,6 +3532,7 @@ impl AcpThreadView {
                     parent.child(self.render_edited_files(
                         action_log,
                         &changed_buffers,
+                        &changed_files,
                         pending_edits,
                         cx,
                     ))
@@ -3602,7 +3683,7 @@ impl AcpThreadView {
 
     fn render_edits_summary(
         &self,
-        changed_buffers: &BTreeMap<Entity<Buffer>, Entity<BufferDiff>>,
+        changed_file_count: usize,
         expanded: bool,
         pending_edits: bool,
         window: &mut Window,
@@ -3629,8 +3710,8 @@ impl AcpThreadView {
                             this.child(
                                 Label::new(format!(
                                     "Editing {} {}…",
-                                    changed_buffers.len(),
-                                    if changed_buffers.len() == 1 {
+                                    changed_file_count,
+                                    if changed_file_count == 1 {
                                         "file"
                                     } else {
                                         "files"
@@ -3656,8 +3737,8 @@ impl AcpThreadView {
                             .child(
                                 Label::new(format!(
                                     "{} {}",
-                                    changed_buffers.len(),
-                                    if changed_buffers.len() == 1 {
+                                    changed_file_count,
+                                    if changed_file_count == 1 {
                                         "file"
                                     } else {
                                         "files"
@@ -3738,12 +3819,108 @@ impl AcpThreadView {
         &self,
         action_log: &Entity<ActionLog>,
         changed_buffers: &BTreeMap<Entity<Buffer>, Entity<BufferDiff>>,
+        changed_files: &[ProjectPath],
         pending_edits: bool,
         cx: &Context<Self>,
     ) -> Div {
         let editor_bg_color = cx.theme().colors().editor_background;
+        let row_count = changed_buffers.len() + changed_files.len();
+
+        let file_rows = changed_files.iter().enumerate().map(|(ix, project_path)| {
+            // Files written directly to disk, such as generated MIDI files,
+            // have no buffer to review, so they can only be kept or rejected.
+            let index = changed_buffers.len() + ix;
+            let path = &project_path.path;
+            let path_style = self.project.read(cx).path_style(cx);
+            let separator = path_style.separator();
+
+            let file_path = path.parent().and_then(|parent| {
+                if parent.is_empty() {
+                    None
+                } else {
+                    Some(
+                        Label::new(format!("{}{separator}", parent.display(path_style)))
+                            .color(Color::Muted)
+                            .size(LabelSize::XSmall)
+                            .buffer_font(cx),
+                    )
+                }
+            });
+
+            let file_name = path.file_name().map(|name| {
+                Label::new(name.to_string())
+                    .size(LabelSize::XSmall)
+                    .buffer_font(cx)
+            });
+
+            let file_icon = FileIcons::get_icon(path.as_std_path(), cx)
+                .map(Icon::from_path)
+                .map(|icon| icon.color(Color::Muted).size(IconSize::Small))
+                .unwrap_or_else(|| {
+                    Icon::new(IconName::File)
+                        .color(Color::Muted)
+                        .size(IconSize::Small)
+                });
 
-        v_flex().children(changed_buffers.iter().enumerate().flat_map(
+            h_flex()
+                .group("edited-code")
+                .id(("file-container", index))
+                .py_1()
+                .pl_2()
+                .pr_1()
+                .gap_2()
+                .justify_between()
+                .bg(editor_bg_color)
+                .when(index < row_count - 1, |parent| {
+                    parent.border_color(cx.theme().colors().border).border_b_1()
+                })
+                .child(
+                    h_flex()
+                        .id(("file-name", index))
+                        .gap_1p5()
+                        .w_full()
+                        .overflow_x_scroll()
+                        .child(file_icon)
+                        .child(h_flex().gap_0p5().children(file_name).children(file_path)),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .visible_on_hover("edited-code")
+                        .child(
+                            Button::new("reject-file", "Reject")
+                                .label_size(LabelSize::Small)
+                                .disabled(pending_edits)
+                                .on_click({
+                                    let project_path = project_path.clone();
+                                    let action_log = action_log.clone();
+                                    move |_, _, cx| {
+                                        action_log.update(cx, |action_log, cx| {
+                                            action_log
+                                                .reject_file(project_path.clone(), cx)
+                                                .detach_and_log_err(cx);
+                                        })
+                                    }
+                                }),
+                        )
+                        .child(
+                            Button::new("keep-file", "Keep")
+                                .label_size(LabelSize::Small)
+                                .disabled(pending_edits)
+                                .on_click({
+                                    let project_path = project_path.clone();
+                                    let action_log = action_log.clone();
+                                    move |_, _, cx| {
+                                        action_log.update(cx, |action_log, cx| {
+                                            action_log.keep_file(&project_path, cx);
+                                        })
+                                    }
+                                }),
+                        ),
+                )
+        });
+
+        let buffer_rows = v_flex().children(changed_buffers.iter().enumerate().flat_map(
             |(index, (buffer, _diff))| {
                 let file = buffer.read(cx).file()?;
                 let path = file.path();
@@ -3793,7 +3970,7 @@ impl AcpThreadView {
                     .gap_2()
                     .justify_between()
                     .bg(editor_bg_color)
-                    .when(index < changed_buffers.len() - 1, |parent| {
+                    .when(index < row_count - 1, |parent| {
                         parent.border_color(cx.theme().colors().border).border_b_1()
                     })
                     .child(
@@ -3880,7 +4057,9 @@ impl AcpThreadView {
 
                 Some(element)
             },
-        ))
+        ));
+
+        buffer_rows.children(file_rows)
     }
 
     fn render_message_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) -> AnyElement {
@@ -3993,6 +4172,8 @@ impl AcpThreadView {
                                     .children(self.mode_selector().cloned())
                             })
                             .children(self.model_selector.clone())
+                            .children(self.render_dictation_button(cx))
+                            .children(self.render_stop_reading_aloud_button(cx))
                             .child(self.render_send_button(cx)),
                     ),
             )
@@ -4099,6 +4280,37 @@ impl AcpThreadView {
         });
     }
 
+    fn toggle_dictation(
+        &mut self,
+        _: &ToggleDictation,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.message_editor.update(cx, |message_editor, cx| {
+            message_editor.toggle_dictation(window, cx)
+        });
+    }
+
+    fn push_to_talk(&mut self, _: &PushToTalk, window: &mut Window, cx: &mut Context<Self>) {
+        // Holding the keys repeats the action.
+        if self.push_to_talk {
+            return;
+        }
+        self.push_to_talk = true;
+        self.message_editor.update(cx, |message_editor, cx| {
+            message_editor.start_dictation(window, cx)
+        });
+    }
+
+    fn release_push_to_talk(&mut self, cx: &mut Context<Self>) {
+        if !self.push_to_talk {
+            return;
+        }
+        self.push_to_talk = false;
+        self.message_editor
+            .update(cx, |message_editor, cx| message_editor.stop_dictation(cx));
+    }
+
     fn keep_all(&mut self, _: &KeepAll, _window: &mut Window, cx: &mut Context<Self>) {
         let Some(thread) = self.thread() else {
             return;
@@ -4188,6 +4400,56 @@ impl AcpThreadView {
         )
     }
 
+    fn render_dictation_button(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        SpeechSettings::get_global(cx).transcription.as_ref()?;
+
+        let is_dictating = self.message_editor.read(cx).is_dictating();
+        let tooltip = if is_dictating {
+            "Stop Dictation"
+        } else {
+            "Dictate"
+        };
+
+        Some(
+            IconButton::new("toggle-dictation", IconName::Mic)
+                .icon_color(if is_dictating {
+                    Color::Error
+                } else {
+                    Color::Muted
+                })
+                .toggle_state(is_dictating)
+                .tooltip(move |window, cx| {
+                    Tooltip::with_meta(
+                        tooltip,
+                        Some(&PushToTalk),
+                        "Hold the keys to talk",
+                        window,
+                        cx,
+                    )
+                })
+                .on_click(cx.listener(|this, _, window, cx| {
+                    this.message_editor.update(cx, |message_editor, cx| {
+                        message_editor.toggle_dictation(window, cx)
+                    });
+                }))
+                .into_any_element(),
+        )
+    }
+
+    fn render_stop_reading_aloud_button(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        self.read_aloud.as_ref()?;
+
+        Some(
+            IconButton::new("stop-reading-aloud", IconName::AudioOff)
+                .icon_color(Color::Muted)
+                .tooltip(move |window, cx| {
+                    Tooltip::for_action("Stop Reading Aloud", &StopReadingAloud, window, cx)
+                })
+                .on_click(cx.listener(|this, _, _, cx| this.stop_reading_aloud(cx)))
+                .into_any_element(),
+        )
+    }
+
     fn render_send_button(&self, cx: &mut Context<Self>) -> AnyElement {
         let is_editor_empty = self.message_editor.read(cx).is_empty(cx);
         let is_generating = self
@@ -4565,67 +4827,98 @@ impl AcpThreadView {
         }
     }
 
-    fn notify_with_sound(
-        &mut self,
-        caption: impl Into<SharedString>,
-        icon: IconName,
-        window: &mut Window,
-        cx: &mut Context<Self>,
-    ) {
-        self.play_notification_sound(window, cx);
-        self.show_notification(caption, icon, window, cx);
-    }
-
-    fn play_notification_sound(&self, window: &Window, cx: &mut App) {
-        let settings = AgentSettings::get_global(cx);
-        if settings.play_sound_when_agent_done && !window.is_window_active() {
-            Audio::play_sound(Sound::AgentDone, cx);
+    /// Reads the final assistant message of the turn aloud, when enabled in [`SpeechSettings`].
+    fn read_last_response_aloud(&mut self, thread: &Entity<AcpThread>, cx: &mut Context<Self>) {
+        if !SpeechSettings::get_global(cx).read_agent_responses_aloud {
+            return;
         }
-    }
-
-    fn show_notification(
-        &mut self,
-        caption: impl Into<SharedString>,
-        icon: IconName,
-        window: &mut Window,
-        cx: &mut Context<Self>,
-    ) {
-        if window.is_window_active() || !self.notifications.is_empty() {
+        let Some(AgentThreadEntry::AssistantMessage(message)) = thread.read(cx).entries().last()
+        else {
+            return;
+        };
+        let markdown = message
+            .chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                AssistantMessageChunk::Message { block } => Some(block.to_markdown(cx)),
+                AssistantMessageChunk::Thought { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let text = speech::speakable_text(&markdown);
+        if text.is_empty() {
             return;
         }
 
-        // TODO: Change this once we have title summarization for external agents.
-        let title = self.agent.name();
-
-        match AgentSettings::get_global(cx).notify_when_agent_waiting {
-            NotifyWhenAgentWaiting::PrimaryScreen => {
-                if let Some(primary) = cx.primary_display() {
-                    self.pop_up(icon, caption.into(), title, window, primary, cx);
-                }
-            }
-            NotifyWhenAgentWaiting::AllScreens => {
-                let caption = caption.into();
-                for screen in cx.displays() {
-                    self.pop_up(icon, caption.clone(), title.clone(), window, screen, cx);
+        let voice = self.as_native_thread(cx).and_then(|thread| {
+            let profile_id = thread.read(cx).profile();
+            AgentSettings::get_global(cx)
+                .profiles
+                .get(profile_id)?
+                .voice
+                .clone()
+        });
+        let provider = speech::text_to_speech_provider(cx.http_client(), cx);
+        self.stop_reading_aloud(cx);
+        self._read_aloud_task = cx.spawn(async move |this, cx| {
+            let provider = match provider.await {
+                Ok(provider) => provider,
+                Err(error) => {
+                    this.update(cx, |this, cx| {
+                        this.show_read_aloud_error(&error.to_string(), cx)
+                    })
+                    .ok();
+                    return;
                 }
-            }
-            NotifyWhenAgentWaiting::Never => {
-                // Don't show anything
-            }
+            };
+            this.update(cx, |this, cx| {
+                let read_aloud = cx.new(|cx| ReadAloud::new(provider, text, voice, cx));
+                let subscription = cx.subscribe(&read_aloud, |this, _, event, cx| {
+                    if let ReadAloudEvent::Error(error) = event {
+                        this.show_read_aloud_error(error, cx);
+                    }
+                    this.read_aloud = None;
+                    cx.notify();
+                });
+                this.read_aloud = Some((read_aloud, subscription));
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn stop_reading_aloud(&mut self, cx: &mut Context<Self>) {
+        self._read_aloud_task = Task::ready(());
+        if let Some((read_aloud, _)) = self.read_aloud.take() {
+            read_aloud.update(cx, |read_aloud, cx| read_aloud.stop(cx));
+            cx.notify();
         }
     }
 
-    fn pop_up(
+    fn show_read_aloud_error(&self, error: &str, cx: &mut Context<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                struct ReadAloudError;
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<ReadAloudError>(),
+                        format!("Reading aloud failed: {error}"),
+                    ),
+                    cx,
+                );
+            })
+            .ok();
+    }
+
+    fn notify_with_sound(
         &mut self,
+        caption: impl Into<SharedString>,
         icon: IconName,
-        caption: SharedString,
-        title: SharedString,
         window: &mut Window,
-        screen: Rc<dyn PlatformDisplay>,
         cx: &mut Context<Self>,
     ) {
-        let options = AgentNotification::window_options(screen, cx);
-
+        // TODO: Change this once we have title summarization for external agents.
+        let title = self.agent.name();
         let project_name = self.workspace.upgrade().and_then(|workspace| {
             workspace
                 .read(cx)
@@ -4635,82 +4928,21 @@ impl AcpThreadView {
                 .next()
                 .map(|worktree| worktree.read(cx).root_name_str().to_string())
         });
-
-        if let Some(screen_window) = cx
-            .open_window(options, |_, cx| {
-                cx.new(|_| {
-                    AgentNotification::new(title.clone(), caption.clone(), icon, project_name)
-                })
-            })
-            .log_err()
-            && let Some(pop_up) = screen_window.entity(cx).log_err()
-        {
-            self.notification_subscriptions
-                .entry(screen_window)
-                .or_insert_with(Vec::new)
-                .push(cx.subscribe_in(&pop_up, window, {
-                    |this, _, event, window, cx| match event {
-                        AgentNotificationEvent::Accepted => {
-                            let handle = window.window_handle();
-                            cx.activate(true);
-
-                            let workspace_handle = this.workspace.clone();
-
-                            // If there are multiple Zed windows, activate the correct one.
-                            cx.defer(move |cx| {
-                                handle
-                                    .update(cx, |_view, window, _cx| {
-                                        window.activate_window();
-
-                                        if let Some(workspace) = workspace_handle.upgrade() {
-                                            workspace.update(_cx, |workspace, cx| {
-                                                workspace.focus_panel::<AgentPanel>(window, cx);
-                                            });
-                                        }
-                                    })
-                                    .log_err();
-                            });
-
-                            this.dismiss_notifications(cx);
-                        }
-                        AgentNotificationEvent::Dismissed => {
-                            this.dismiss_notifications(cx);
-                        }
-                    }
-                }));
-
-            self.notifications.push(screen_window);
-
-            // If the user manually refocuses the original window, dismiss the popup.
-            self.notification_subscriptions
-                .entry(screen_window)
-                .or_insert_with(Vec::new)
-                .push({
-                    let pop_up_weak = pop_up.downgrade();
-
-                    cx.observe_window_activation(window, move |_, window, cx| {
-                        if window.is_window_active()
-                            && let Some(pop_up) = pop_up_weak.upgrade()
-                        {
-                            pop_up.update(cx, |_, cx| {
-                                cx.emit(AgentNotificationEvent::Dismissed);
-                            });
-                        }
-                    })
-                });
-        }
-    }
-
-    fn dismiss_notifications(&mut self, cx: &mut Context<Self>) {
-        for window in self.notifications.drain(..) {
-            window
-                .update(cx, |_, window, _| {
-                    window.remove_window();
-                })
-                .ok();
-
-            self.notification_subscriptions.remove(&window);
-        }
+        let workspace = self.workspace.clone();
+        AgentNotifications::notify_with_sound(
+            self,
+            |this| &mut this.notifications,
+            AgentNotification::new(title, caption, icon, project_name),
+            move |window, cx| {
+                if let Some(workspace) = workspace.upgrade() {
+                    workspace.update(cx, |workspace, cx| {
+                        workspace.focus_panel::<AgentPanel>(window, cx);
+                    });
+                }
+            },
+            window,
+            cx,
+        );
     }
 
     fn render_thread_controls(
@@ -5460,6 +5692,11 @@ impl Render for AcpThreadView {
             .key_context("AcpThread")
             .on_action(cx.listener(Self::open_agent_diff))
             .on_action(cx.listener(Self::toggle_burn_mode))
+            .on_action(cx.listener(Self::toggle_dictation))
+            .on_action(cx.listener(Self::push_to_talk))
+            .capture_key_up(cx.listener(|this, _, _, cx| this.release_push_to_talk(cx)))
+            .on_modifiers_changed(cx.listener(|this, _, _, cx| this.release_push_to_talk(cx)))
+            .on_action(cx.listener(|this, _: &StopReadingAloud, _, cx| this.stop_reading_aloud(cx)))
             .on_action(cx.listener(Self::keep_all))
             .on_action(cx.listener(Self::reject_all))
             .on_action(cx.listener(Self::allow_always))
@@ -5551,7 +5788,7 @@ impl Render for AcpThreadView {
     }
 }
 
-fn default_markdown_style(
+pub(super) fn default_markdown_style(
     buffer_font: bool,
     muted_text: bool,
     window: &Window,
@@ -5718,6 +5955,7 @@ pub(crate) mod tests {
     use acp_thread::StubAgentConnection;
     use agent_client_protocol::SessionId;
     use assistant_context::ContextStore;
+    use collections::HashMap;
     use editor::EditorSettings;
     use fs::FakeFs;
     use gpui::{EventEmitter, SemanticVersion, TestAppContext, VisualTestContext};
@@ -6127,6 +6365,7 @@ pub(crate) mod tests {
             language::init(cx);
             Project::init_settings(cx);
             AgentSettings::register(cx);
+            speech::init(cx);
             workspace::init_settings(cx);
             theme::init(theme::LoadThemes::JustBase, cx);
             release_channel::init(SemanticVersion::default(), cx);
@@ -71,6 +71,7 @@ impl AddLlmProviderInput {
 struct ModelCapabilityToggles {
     pub supports_tools: ToggleState,
     pub supports_images: ToggleState,
+    pub supports_audio: ToggleState,
     pub supports_parallel_tool_calls: ToggleState,
     pub supports_prompt_cache_key: ToggleState,
 }
@@ -110,6 +111,7 @@ impl ModelInput {
         let ModelCapabilities {
             tools,
             images,
+            audio,
             parallel_tool_calls,
             prompt_cache_key,
         } = ModelCapabilities::default();
@@ -121,6 +123,7 @@ impl ModelInput {
             capabilities: ModelCapabilityToggles {
                 supports_tools: tools.into(),
                 supports_images: images.into(),
+                supports_audio: audio.into(),
                 supports_parallel_tool_calls: parallel_tool_calls.into(),
                 supports_prompt_cache_key: prompt_cache_key.into(),
             },
@@ -158,6 +161,7 @@ impl ModelInput {
             capabilities: ModelCapabilities {
                 tools: self.capabilities.supports_tools.selected(),
                 images: self.capabilities.supports_images.selected(),
+                audio: self.capabilities.supports_audio.selected(),
                 parallel_tool_calls: self.capabilities.supports_parallel_tool_calls.selected(),
                 prompt_cache_key: self.capabilities.supports_prompt_cache_key.selected(),
             },
@@ -371,6 +375,14 @@ impl AddLlmProviderModal {
                                 cx.notify();
                             })),
                     )
+                    .child(
+                        Checkbox::new(("supports-audio", ix), model.capabilities.supports_audio)
+                            .label("Supports audio")
+                            .on_click(cx.listener(move |this, checked, _window, cx| {
+                                this.input.models[ix].capabilities.supports_audio = *checked;
+                                cx.notify();
+                            })),
+                    )
                     .child(
                         Checkbox::new(
                             ("supports-parallel-tool-calls", ix),
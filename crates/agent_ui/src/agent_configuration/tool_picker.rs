@@ -294,6 +294,8 @@ impl PickerDelegate for ToolPickerDelegate {
                                 )
                             })
                             .collect(),
+                        voice: default_profile.voice.map(|voice| voice.to_string()),
+                        hooks: default_profile.hooks.into_iter().map(Into::into).collect(),
                     });
 
                 if let Some(server_id) = server_id {
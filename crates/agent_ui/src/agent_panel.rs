@@ -1,10 +1,11 @@
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 
-use acp_thread::AcpThread;
-use agent2::{DbThreadMetadata, HistoryEntry};
+use acp_thread::{AcpThread, ThreadStatus};
+use agent_client_protocol as acp;
+use agent2::{AgentScheduler, AgentSchedulerEvent, DbThreadMetadata, HistoryEntry};
 use db::kvp::{Dismissable, KEY_VALUE_STORE};
 use project::agent_server_store::{
     AgentServerCommand, AllAgentServersSettings, CLAUDE_CODE_NAME, CODEX_NAME, GEMINI_NAME,
@@ -17,11 +18,14 @@ use zed_actions::OpenBrowser;
 use zed_actions::agent::{OpenClaudeCodeOnboardingModal, ReauthenticateAgent};
 
 use crate::acp::{AcpThreadHistory, ThreadHistoryEvent};
-use crate::ui::{AcpOnboardingModal, ClaudeCodeOnboardingModal};
+use crate::ui::{
+    AcpOnboardingModal, AgentNotification, AgentNotifications, ClaudeCodeOnboardingModal,
+};
 use crate::{
-    AddContextServer, DeleteRecentlyOpenThread, Follow, InlineAssistant, NewTextThread, NewThread,
-    OpenActiveThreadAsMarkdown, OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell,
-    ToggleNavigationMenu, ToggleNewThreadMenu, ToggleOptionsMenu,
+    AddContextServer, DeleteRecentlyOpenThread, DiscardWorktreeThread, Follow, InlineAssistant,
+    MergeWorktreeThread, NewTextThread, NewThread, NewWorktreeThread, OpenActiveThreadAsMarkdown,
+    OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell, ReviewWorktreeThread, ToggleNavigationMenu,
+    ToggleNewThreadMenu, ToggleOptionsMenu,
     acp::AcpThreadView,
     agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
     slash_command::SlashCommandCompletionProvider,
@@ -44,16 +48,22 @@ use assistant_slash_command::SlashCommandWorkingSet;
 use assistant_tool::ToolWorkingSet;
 use client::{UserStore, zed_urls};
 use cloud_llm_client::{Plan, PlanV1, PlanV2, UsageLimit};
+use collections::{HashMap, HashSet};
 use editor::{Anchor, AnchorRangeExt as _, Editor, EditorEvent, MultiBuffer};
 use fs::Fs;
+use git::repository::CommitOptions;
+use git_ui::project_diff::ProjectDiff;
 use gpui::{
-    Action, AnyElement, App, AsyncWindowContext, Corner, DismissEvent, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, Focusable, KeyContext, Pixels, SharedString, Subscription, Task, UpdateGlobal,
-    WeakEntity, prelude::*,
+    Action, AnyElement, App, AsyncWindowContext, Corner, DismissEvent, Entity, EntityId,
+    EventEmitter, ExternalPaths, FocusHandle, Focusable, Global, KeyContext, Pixels, PromptLevel,
+    SharedString, Subscription, Task, UpdateGlobal, WeakEntity, prelude::*,
 };
 use language::LanguageRegistry;
 use language_model::{ConfigurationError, LanguageModelId, LanguageModelProviderId, LanguageModelRegistry, SelectedModel};
-use project::{Project, ProjectPath, Worktree};
+use project::{
+    Project, ProjectPath, Repository, Worktree,
+    git_store::{GitStoreEvent, RepositoryId},
+};
 use prompt_store::{PromptBuilder, PromptStore, UserPromptId};
 use rules_library::{RulesLibrary, open_rules_library};
 use search::{BufferSearchBar, buffer_search};
@@ -61,13 +71,14 @@ use settings::{Settings, SettingsStore, update_settings_file};
 use theme::ThemeSettings;
 use ui::utils::WithRemSize;
 use ui::{
-    Callout, ContextMenu, ContextMenuEntry, KeyBinding, PopoverMenu, PopoverMenuHandle,
+    Callout, ContextMenu, ContextMenuEntry, Indicator, KeyBinding, PopoverMenu, PopoverMenuHandle,
     ProgressBar, Tab, Tooltip, prelude::*,
 };
 use util::ResultExt as _;
 use workspace::{
-    CollaboratorId, DraggedSelection, DraggedTab, ToggleZoom, ToolbarItemView, Workspace,
+    CollaboratorId, DraggedSelection, DraggedTab, Toast, ToggleZoom, ToolbarItemView, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::{DetachAndPromptErr as _, NotificationId},
 };
 use zed_actions::{
     DecreaseBufferFontSize, IncreaseBufferFontSize, ResetBufferFontSize,
@@ -109,6 +120,35 @@ pub fn init(cx: &mut App) {
                         panel.update(cx, |panel, cx| panel.open_history(window, cx));
                     }
                 })
+                .register_action(|workspace, action: &NewWorktreeThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        workspace.focus_panel::<AgentPanel>(window, cx);
+                        panel.update(cx, |panel, cx| {
+                            panel.new_worktree_thread(action, window, cx)
+                        });
+                    }
+                })
+                .register_action(|workspace, action: &ReviewWorktreeThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.review_worktree_thread(action, window, cx)
+                        });
+                    }
+                })
+                .register_action(|workspace, action: &MergeWorktreeThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.merge_worktree_thread(action, window, cx)
+                        });
+                    }
+                })
+                .register_action(|workspace, action: &DiscardWorktreeThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.discard_worktree_thread(action, window, cx)
+                        });
+                    }
+                })
                 .register_action(|workspace, _: &OpenSettings, window, cx| {
                     if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
                         workspace.focus_panel::<AgentPanel>(window, cx);
@@ -202,6 +242,111 @@ enum ActiveView {
     Configuration,
 }
 
+/// A thread that keeps running while another view is active in the panel.
+struct BackgroundThread {
+    thread_view: Entity<AcpThreadView>,
+    _subscription: Subscription,
+}
+
+/// The git worktree a thread was started in. Its branch is merged into
+/// `repository` once the thread's work is accepted, or deleted along with the
+/// worktree when it's discarded.
+#[derive(Clone)]
+struct ThreadWorktree {
+    repository: Entity<Repository>,
+    project: Entity<Project>,
+    path: PathBuf,
+    branch: String,
+}
+
+/// The worktrees of the threads open in any window, which aren't pruned as
+/// orphans.
+#[derive(Default)]
+struct OpenThreadWorktrees(HashSet<PathBuf>);
+
+impl Global for OpenThreadWorktrees {}
+
+/// The directory that threads' worktrees are created in.
+fn thread_worktrees_dir() -> PathBuf {
+    paths::data_dir().join("worktrees")
+}
+
+/// Picks the name of a new thread's worktree and branch from the time it was
+/// started, numbering threads started within the same second.
+fn thread_worktree_id(timestamp: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => timestamp.to_string(),
+            n => format!("{timestamp}-{n}"),
+        })
+        .find(|id| !is_taken(id))
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Removes a thread's worktree and then deletes its branch, discarding any
+/// changes on them.
+fn delete_thread_worktree(
+    repository: Entity<Repository>,
+    path: PathBuf,
+    branch: String,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    cx.spawn(async move |cx| {
+        repository
+            .update(cx, |repository, _| repository.remove_worktree(path, true))?
+            .await??;
+        repository
+            .update(cx, |repository, _| repository.delete_branch(branch, true))?
+            .await??;
+        Ok(())
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ThreadSwitcherStatus {
+    Generating,
+    WaitingForConfirmation,
+    Idle,
+    Error,
+}
+
+impl ThreadSwitcherStatus {
+    fn for_thread_view(thread_view: &AcpThreadView, cx: &App) -> Self {
+        if thread_view.has_error() {
+            return Self::Error;
+        }
+        let Some(thread) = thread_view.thread() else {
+            return Self::Idle;
+        };
+        let thread = thread.read(cx);
+        if thread.first_tool_awaiting_confirmation().is_some() {
+            Self::WaitingForConfirmation
+        } else if thread.status() == ThreadStatus::Generating {
+            Self::Generating
+        } else {
+            Self::Idle
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Generating => "Generating",
+            Self::WaitingForConfirmation => "Waiting for Confirmation",
+            Self::Idle => "Idle",
+            Self::Error => "Error",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Generating => Color::Accent,
+            Self::WaitingForConfirmation => Color::Warning,
+            Self::Idle => Color::Muted,
+            Self::Error => Color::Error,
+        }
+    }
+}
+
 enum WhichFontSize {
     AgentFont,
     BufferFont,
@@ -427,6 +572,10 @@ pub struct AgentPanel {
     configuration_subscription: Option<Subscription>,
     active_view: ActiveView,
     previous_view: Option<ActiveView>,
+    background_threads: Vec<BackgroundThread>,
+    thread_worktrees: HashMap<EntityId, ThreadWorktree>,
+    /// The repositories whose orphaned thread worktrees were pruned.
+    pruned_repositories: HashSet<RepositoryId>,
     history_store: Entity<HistoryStore>,
     new_thread_menu_handle: PopoverMenuHandle<ContextMenu>,
     agent_panel_menu_handle: PopoverMenuHandle<ContextMenu>,
@@ -438,6 +587,8 @@ pub struct AgentPanel {
     pending_serialization: Option<Task<Result<()>>>,
     onboarding: Entity<AgentPanelOnboarding>,
     selected_agent: AgentType,
+    _scheduler: Entity<AgentScheduler>,
+    scheduled_run_notifications: AgentNotifications,
 }
 
 impl AgentPanel {
@@ -587,6 +738,20 @@ impl AgentPanel {
 
         cx.observe(&history_store, |_, _, cx| cx.notify()).detach();
 
+        let scheduler = cx.new(|cx| {
+            AgentScheduler::new(project.clone(), acp_history_store.clone(), fs.clone(), cx)
+        });
+        cx.subscribe_in(&scheduler, window, Self::handle_scheduler_event)
+            .detach();
+
+        cx.subscribe(project.read(cx).git_store(), |this, _, event, cx| {
+            if let GitStoreEvent::RepositoryAdded(_) | GitStoreEvent::RepositoryUpdated(..) = event
+            {
+                this.prune_orphaned_thread_worktrees(cx);
+            }
+        })
+        .detach();
+
         let panel_type = AgentSettings::get_global(cx).default_view;
         let active_view = match panel_type {
             DefaultView::Thread => ActiveView::native_agent(
@@ -684,6 +849,9 @@ impl AgentPanel {
             configuration_subscription: None,
             inline_assist_context_store,
             previous_view: None,
+            background_threads: Vec::new(),
+            thread_worktrees: HashMap::default(),
+            pruned_repositories: HashSet::default(),
             history_store: history_store.clone(),
             new_thread_menu_handle: PopoverMenuHandle::default(),
             agent_panel_menu_handle: PopoverMenuHandle::default(),
@@ -698,6 +866,8 @@ impl AgentPanel {
             acp_history_store,
             selected_agent: AgentType::default(),
             loading: false,
+            _scheduler: scheduler,
+            scheduled_run_notifications: AgentNotifications::default(),
         }
     }
 
@@ -1270,20 +1440,498 @@ impl AgentPanel {
             ActiveView::History | ActiveView::Configuration => {}
         }
 
+        if let ActiveView::ExternalAgentThread { thread_view } = &new_view {
+            let entity_id = thread_view.entity_id();
+            self.background_threads
+                .retain(|thread| thread.thread_view.entity_id() != entity_id);
+        }
+
         if current_is_special && !new_is_special {
             self.active_view = new_view;
         } else if !current_is_special && new_is_special {
             self.previous_view = Some(std::mem::replace(&mut self.active_view, new_view));
         } else {
-            if !new_is_special {
-                self.previous_view = None;
+            if !new_is_special && let Some(previous_view) = self.previous_view.take() {
+                self.keep_running_in_background(previous_view, cx);
             }
-            self.active_view = new_view;
+            let old_view = std::mem::replace(&mut self.active_view, new_view);
+            self.keep_running_in_background(old_view, cx);
         }
 
         self.focus_handle(cx).focus(window);
     }
 
+    /// Keeps a thread that's being replaced in the panel alive if it's still
+    /// generating or works in its own worktree, so it shows up in the thread
+    /// switcher instead of being dropped.
+    fn keep_running_in_background(&mut self, view: ActiveView, cx: &mut Context<Self>) {
+        let ActiveView::ExternalAgentThread { thread_view } = view else {
+            return;
+        };
+        let is_generating = thread_view
+            .read(cx)
+            .thread()
+            .is_some_and(|thread| thread.read(cx).status() == ThreadStatus::Generating);
+        if !is_generating && !self.thread_worktrees.contains_key(&thread_view.entity_id()) {
+            return;
+        }
+        if self
+            .background_threads
+            .iter()
+            .any(|thread| thread.thread_view == thread_view)
+        {
+            return;
+        }
+
+        let subscription = cx.observe(&thread_view, |_, _, cx| cx.notify());
+        self.background_threads.push(BackgroundThread {
+            thread_view,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn activate_background_thread(
+        &mut self,
+        entity_id: EntityId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self
+            .background_threads
+            .iter()
+            .position(|thread| thread.thread_view.entity_id() == entity_id)
+        else {
+            return;
+        };
+        let thread = self.background_threads.remove(ix);
+        self.set_active_view(
+            ActiveView::ExternalAgentThread {
+                thread_view: thread.thread_view,
+            },
+            window,
+            cx,
+        );
+    }
+
+    fn close_thread(&mut self, entity_id: EntityId, window: &mut Window, cx: &mut Context<Self>) {
+        if self.thread_worktrees.contains_key(&entity_id) {
+            self.confirm_discard_thread_worktree(entity_id, window, cx);
+        } else {
+            self.remove_thread(entity_id, window, cx);
+        }
+    }
+
+    /// Stops and forgets a thread, replacing it with a new one if it's the
+    /// active thread. Doesn't touch its worktree.
+    fn remove_thread(&mut self, entity_id: EntityId, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(worktree) = self.thread_worktrees.remove(&entity_id) {
+            cx.default_global::<OpenThreadWorktrees>()
+                .0
+                .remove(&worktree.path);
+        }
+        self.background_threads
+            .retain(|thread| thread.thread_view.entity_id() != entity_id);
+        if let Some(thread_view) = self.active_thread_view().cloned()
+            && thread_view.entity_id() == entity_id
+        {
+            thread_view.update(cx, |thread_view, cx| thread_view.cancel_generation(cx));
+            self.new_agent_thread(AgentType::NativeAgent, window, cx);
+        }
+        cx.notify();
+    }
+
+    /// Removes the worktrees and branches left behind by threads that were
+    /// neither merged nor discarded before the app closed. Worktrees with
+    /// uncommitted changes and branches with unmerged commits are kept, so no
+    /// work is lost, and can still be reviewed from the git panel.
+    fn prune_orphaned_thread_worktrees(&mut self, cx: &mut Context<Self>) {
+        if !self.project.read(cx).is_local() {
+            return;
+        }
+        let worktrees_dir = thread_worktrees_dir();
+        let open_worktrees = cx.default_global::<OpenThreadWorktrees>().0.clone();
+        let repositories = self
+            .project
+            .read(cx)
+            .repositories(cx)
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for repository in repositories {
+            let snapshot = repository.read(cx);
+            // The main worktree is always listed once the worktrees are loaded.
+            if snapshot.worktrees.is_empty() || !self.pruned_repositories.insert(snapshot.id) {
+                continue;
+            }
+            let orphans = snapshot
+                .worktrees
+                .iter()
+                .filter(|worktree| {
+                    !worktree.is_main
+                        && !worktree.locked
+                        && worktree.path.starts_with(&worktrees_dir)
+                        && worktree
+                            .branch
+                            .as_ref()
+                            .is_some_and(|branch| branch.starts_with("agent/"))
+                        && !open_worktrees.contains(&worktree.path)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if orphans.is_empty() {
+                continue;
+            }
+
+            cx.spawn(async move |_, cx| {
+                if orphans.iter().any(|worktree| worktree.prunable) {
+                    repository
+                        .update(cx, |repository, _| repository.prune_worktrees())?
+                        .await??;
+                }
+                for worktree in orphans {
+                    if !worktree.prunable {
+                        let removed = repository
+                            .update(cx, |repository, _| {
+                                repository.remove_worktree(worktree.path.clone(), false)
+                            })?
+                            .await?;
+                        if let Err(error) = removed {
+                            log::debug!("keeping thread worktree {:?}: {error}", worktree.path);
+                            continue;
+                        }
+                    }
+                    if let Some(branch) = worktree.branch {
+                        let deleted = repository
+                            .update(cx, |repository, _| {
+                                repository.delete_branch(branch.to_string(), false)
+                            })?
+                            .await?;
+                        if let Err(error) = deleted {
+                            log::debug!("keeping thread branch {branch}: {error}");
+                        }
+                    }
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    fn new_worktree_thread(
+        &mut self,
+        _: &NewWorktreeThread,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        if !self.project.read(cx).is_local() {
+            return;
+        }
+        let Some(repository) = self.project.read(cx).active_repository(cx) else {
+            return;
+        };
+
+        let app_state = workspace.read(cx).app_state().clone();
+        let work_directory = repository.read(cx).work_directory_abs_path.clone();
+        let worktrees_dir =
+            thread_worktrees_dir().join(work_directory.file_name().unwrap_or_default());
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let existing_branches = repository
+            .read(cx)
+            .worktrees
+            .iter()
+            .filter_map(|worktree| worktree.branch.clone())
+            .collect::<HashSet<_>>();
+        let open_worktrees = &cx.default_global::<OpenThreadWorktrees>().0;
+        let id = thread_worktree_id(&timestamp, |id| {
+            let path = worktrees_dir.join(id);
+            open_worktrees.contains(&path)
+                || path.exists()
+                || existing_branches.contains(format!("agent/{id}").as_str())
+        });
+        let branch = format!("agent/{id}");
+        let path = worktrees_dir.join(&id);
+        cx.default_global::<OpenThreadWorktrees>()
+            .0
+            .insert(path.clone());
+        let create_worktree = repository.update(cx, |repository, _| {
+            repository.create_worktree(branch.clone(), path.clone(), None)
+        });
+
+        let open_path = path.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = create_worktree.await;
+            if !matches!(result, Ok(Ok(()))) {
+                cx.update(|_, cx| {
+                    cx.default_global::<OpenThreadWorktrees>()
+                        .0
+                        .remove(&open_path)
+                })?;
+            }
+            result??;
+            let project = cx.update(|_, cx| {
+                Project::local(
+                    app_state.client.clone(),
+                    app_state.node_runtime.clone(),
+                    app_state.user_store.clone(),
+                    app_state.languages.clone(),
+                    app_state.fs.clone(),
+                    None,
+                    cx,
+                )
+            })?;
+            project
+                .update(cx, |project, cx| {
+                    project.find_or_create_worktree(&path, true, cx)
+                })?
+                .await?;
+
+            this.update_in(cx, |this, window, cx| {
+                telemetry::event!("Agent Thread Started", agent = "zed-worktree");
+
+                let thread_view = cx.new(|cx| {
+                    crate::acp::AcpThreadView::new(
+                        ExternalAgent::NativeAgent
+                            .server(this.fs.clone(), this.acp_history_store.clone()),
+                        None,
+                        None,
+                        this.workspace.clone(),
+                        project.clone(),
+                        this.acp_history_store.clone(),
+                        this.prompt_store.clone(),
+                        window,
+                        cx,
+                    )
+                });
+                this.thread_worktrees.insert(
+                    thread_view.entity_id(),
+                    ThreadWorktree {
+                        repository,
+                        project,
+                        path,
+                        branch,
+                    },
+                );
+                this.set_active_view(ActiveView::ExternalAgentThread { thread_view }, window, cx);
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to create a worktree for the thread",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
+    fn thread_view(&self, entity_id: EntityId) -> Option<&Entity<AcpThreadView>> {
+        self.active_thread_view()
+            .into_iter()
+            .chain(
+                self.background_threads
+                    .iter()
+                    .map(|thread| &thread.thread_view),
+            )
+            .find(|thread_view| thread_view.entity_id() == entity_id)
+    }
+
+    /// Commits the changes a thread left uncommitted in its worktree, so that
+    /// its branch holds all of them. Returns `None` if the thread has no
+    /// worktree or is still generating.
+    fn commit_thread_worktree(
+        &self,
+        entity_id: EntityId,
+        cx: &mut Context<Self>,
+    ) -> Option<(ThreadWorktree, Task<anyhow::Result<()>>)> {
+        let worktree = self.thread_worktrees.get(&entity_id)?.clone();
+        let worktree_repository = worktree.project.read(cx).active_repository(cx)?;
+        let thread = self.thread_view(entity_id)?.read(cx).thread();
+        if thread.is_some_and(|thread| thread.read(cx).status() == ThreadStatus::Generating) {
+            return None;
+        }
+
+        let message = thread
+            .map(|thread| thread.read(cx).title())
+            .unwrap_or_else(|| "Agent changes".into());
+        let has_changes = worktree_repository
+            .read(cx)
+            .cached_status()
+            .next()
+            .is_some();
+        let task = cx.spawn(async move |_, cx| {
+            if has_changes {
+                worktree_repository
+                    .update(cx, |repository, cx| repository.stage_all(cx))?
+                    .await?;
+                worktree_repository
+                    .update(cx, |repository, cx| {
+                        repository.commit(message, None, CommitOptions::default(), cx)
+                    })?
+                    .await??;
+            }
+            anyhow::Ok(())
+        });
+        Some((worktree, task))
+    }
+
+    /// Opens the changes on the active thread's branch since it diverged from
+    /// the project's HEAD, without merging them.
+    fn review_worktree_thread(
+        &mut self,
+        _: &ReviewWorktreeThread,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entity_id) = self.active_thread_view().map(|view| view.entity_id()) else {
+            return;
+        };
+        let Some((worktree, commit)) = self.commit_thread_worktree(entity_id, cx) else {
+            return;
+        };
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            commit.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                git_ui::branch_review::review_range(
+                    workspace,
+                    worktree.repository,
+                    "HEAD".to_string(),
+                    worktree.branch,
+                    window,
+                    cx,
+                )
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to review the thread's changes",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
+    /// Once the user accepts, squashes the active thread's branch into the
+    /// project's working tree and opens the result in the project diff, then
+    /// removes the thread's worktree and branch.
+    fn merge_worktree_thread(
+        &mut self,
+        _: &MergeWorktreeThread,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entity_id) = self.active_thread_view().map(|view| view.entity_id()) else {
+            return;
+        };
+        let Some(worktree) = self.thread_worktrees.get(&entity_id) else {
+            return;
+        };
+        let answer = window.prompt(
+            PromptLevel::Info,
+            &format!("Merge the changes on {}?", worktree.branch),
+            Some(
+                "They will be squashed into your working tree to be committed, \
+                and the thread's worktree and branch will be deleted.",
+            ),
+            &["Merge", "Review First", "Cancel"],
+            cx,
+        );
+        let workspace = self.workspace.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            match answer.await {
+                Ok(0) => {}
+                Ok(1) => {
+                    return this.update_in(cx, |this, window, cx| {
+                        this.review_worktree_thread(&ReviewWorktreeThread, window, cx)
+                    });
+                }
+                _ => return Ok(()),
+            }
+            let Some((worktree, commit)) =
+                this.update(cx, |this, cx| this.commit_thread_worktree(entity_id, cx))?
+            else {
+                return Ok(());
+            };
+            commit.await?;
+            let ThreadWorktree {
+                repository,
+                path,
+                branch,
+                ..
+            } = worktree;
+            repository
+                .update(cx, |repository, _| repository.merge_squash(branch.clone()))?
+                .await??;
+
+            this.update_in(cx, |this, window, cx| {
+                this.remove_thread(entity_id, window, cx)
+            })?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                ProjectDiff::deploy_at(workspace, None, window, cx)
+            })?;
+
+            cx.update(|_, cx| delete_thread_worktree(repository, path, branch, cx))?
+                .await
+        })
+        .detach_and_prompt_err(
+            "Failed to merge the thread's worktree",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
+    fn discard_worktree_thread(
+        &mut self,
+        _: &DiscardWorktreeThread,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(thread_view) = self.active_thread_view() {
+            self.confirm_discard_thread_worktree(thread_view.entity_id(), window, cx);
+        }
+    }
+
+    fn confirm_discard_thread_worktree(
+        &mut self,
+        entity_id: EntityId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(worktree) = self.thread_worktrees.get(&entity_id) else {
+            return;
+        };
+        let repository = worktree.repository.clone();
+        let path = worktree.path.clone();
+        let branch = worktree.branch.clone();
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            &format!("Discard the changes on {branch}?"),
+            Some("The thread will be stopped and its worktree and branch deleted."),
+            &["Discard", "Cancel"],
+            cx,
+        );
+
+        cx.spawn_in(window, async move |this, cx| {
+            if answer.await != Ok(0) {
+                return Ok(());
+            }
+            this.update_in(cx, |this, window, cx| {
+                this.remove_thread(entity_id, window, cx)
+            })?;
+            cx.update(|_, cx| delete_thread_worktree(repository, path, branch, cx))?
+                .await
+        })
+        .detach_and_prompt_err(
+            "Failed to discard the thread's worktree",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
     fn populate_recently_opened_menu_section(
         mut menu: ContextMenu,
         panel: Entity<Self>,
@@ -1424,6 +2072,95 @@ impl AgentPanel {
             cx,
         );
     }
+
+    fn handle_scheduler_event(
+        &mut self,
+        _: &Entity<AgentScheduler>,
+        event: &AgentSchedulerEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let AgentSchedulerEvent::RunFinished {
+            name,
+            session_id,
+            title,
+            error,
+        } = event;
+        let (caption, icon) = match error {
+            Some(error) => (
+                format!("Scheduled run \"{name}\" failed: {error}"),
+                IconName::Warning,
+            ),
+            None => (
+                format!("Scheduled run \"{name}\" finished"),
+                IconName::ZedAssistant,
+            ),
+        };
+
+        if window.is_window_active() {
+            let panel = cx.entity().downgrade();
+            let session_id = session_id.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    struct ScheduledRunFinished;
+                    workspace.show_toast(
+                        Toast::new(NotificationId::unique::<ScheduledRunFinished>(), caption)
+                            .on_click("Open Thread", move |window, cx| {
+                                Self::open_scheduled_run(&panel, &session_id, window, cx);
+                            }),
+                        cx,
+                    );
+                })
+                .ok();
+            return;
+        }
+
+        let project_name = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).root_name_str().to_string());
+        // Only the latest run is shown, rather than stacking a pop-up per run.
+        self.scheduled_run_notifications.dismiss(cx);
+        let panel = cx.entity().downgrade();
+        let session_id = session_id.clone();
+        AgentNotifications::notify_with_sound(
+            self,
+            |this| &mut this.scheduled_run_notifications,
+            AgentNotification::new(title.clone(), caption, icon, project_name),
+            move |window, cx| Self::open_scheduled_run(&panel, &session_id, window, cx),
+            window,
+            cx,
+        );
+    }
+
+    fn open_scheduled_run(
+        panel: &WeakEntity<Self>,
+        session_id: &acp::SessionId,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some(panel) = panel.upgrade() else {
+            return;
+        };
+        let workspace = panel.read(cx).workspace.clone();
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.focus_panel::<Self>(window, cx);
+            })
+            .ok();
+        panel.update(cx, |panel, cx| {
+            let thread = panel
+                .acp_history_store
+                .read(cx)
+                .thread_from_session_id(session_id)
+                .cloned();
+            if let Some(thread) = thread {
+                panel.load_agent_thread(thread, window, cx);
+            }
+        });
+    }
 }
 
 impl Focusable for AgentPanel {
@@ -1858,6 +2595,10 @@ impl AgentPanel {
                         workspace.project().read(cx).is_via_collab()
                     })
                     .unwrap_or_default();
+                let can_use_worktrees = {
+                    let project = self.project.read(cx);
+                    project.is_local() && project.active_repository(cx).is_some()
+                };
                 let focus_handle = focus_handle.clone();
                 let agent_server_store = agent_server_store.clone();
 
@@ -1954,6 +2695,20 @@ impl AgentPanel {
                                             }
                                         }),
                                 )
+                                .when(can_use_worktrees, |this| {
+                                    this.item(
+                                        ContextMenuEntry::new("New Thread in Worktree")
+                                            .icon(IconName::GitBranch)
+                                            .icon_color(Color::Muted)
+                                            .action(NewWorktreeThread.boxed_clone())
+                                            .handler(|window, cx| {
+                                                window.dispatch_action(
+                                                    NewWorktreeThread.boxed_clone(),
+                                                    cx,
+                                                );
+                                            }),
+                                    )
+                                })
                                 .item(
                                     ContextMenuEntry::new("New Text Thread")
                                         .icon(IconName::TextThread)
@@ -2438,6 +3193,165 @@ impl AgentPanel {
             .child(self.render_drag_target(cx))
     }
 
+    fn render_thread_switcher(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let active_thread = self.active_thread_view()?;
+        let active_worktree = self.thread_worktrees.get(&active_thread.entity_id());
+        if self.background_threads.is_empty() && active_worktree.is_none() {
+            return None;
+        }
+
+        let threads = std::iter::once(active_thread).chain(
+            self.background_threads
+                .iter()
+                .map(|thread| &thread.thread_view),
+        );
+
+        Some(
+            v_flex()
+                .w(px(200.))
+                .h_full()
+                .flex_none()
+                .border_r_1()
+                .border_color(cx.theme().colors().border)
+                .child(
+                    v_flex()
+                        .id("thread-switcher")
+                        .flex_1()
+                        .overflow_y_scroll()
+                        .children(threads.enumerate().map(|(ix, thread_view)| {
+                            self.render_thread_switcher_entry(ix, thread_view, ix == 0, cx)
+                        })),
+                )
+                .when(active_worktree.is_some(), |this| {
+                    this.child(
+                        h_flex()
+                            .p_1()
+                            .gap_1()
+                            .justify_end()
+                            .border_t_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(
+                                Button::new("discard-worktree-thread", "Discard")
+                                    .label_size(LabelSize::Small)
+                                    .tooltip(Tooltip::text("Delete the thread's worktree"))
+                                    .on_click(|_, window, cx| {
+                                        window.dispatch_action(
+                                            DiscardWorktreeThread.boxed_clone(),
+                                            cx,
+                                        )
+                                    }),
+                            )
+                            .child(
+                                Button::new("review-worktree-thread", "Review")
+                                    .label_size(LabelSize::Small)
+                                    .tooltip(Tooltip::text(
+                                        "Compare the thread's branch with the project",
+                                    ))
+                                    .on_click(|_, window, cx| {
+                                        window
+                                            .dispatch_action(ReviewWorktreeThread.boxed_clone(), cx)
+                                    }),
+                            )
+                            .child(
+                                Button::new("merge-worktree-thread", "Merge")
+                                    .label_size(LabelSize::Small)
+                                    .tooltip(Tooltip::text(
+                                        "Merge the thread's changes into the project",
+                                    ))
+                                    .on_click(|_, window, cx| {
+                                        window
+                                            .dispatch_action(MergeWorktreeThread.boxed_clone(), cx)
+                                    }),
+                            ),
+                    )
+                }),
+        )
+    }
+
+    fn render_thread_switcher_entry(
+        &self,
+        ix: usize,
+        thread_view: &Entity<AcpThreadView>,
+        is_active: bool,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let entity_id = thread_view.entity_id();
+        let thread_view = thread_view.read(cx);
+        let title = thread_view
+            .thread()
+            .map(|thread| thread.read(cx).title())
+            .unwrap_or_else(|| thread_view.title(cx));
+        let status = ThreadSwitcherStatus::for_thread_view(thread_view, cx);
+        let worktree = self.thread_worktrees.get(&entity_id);
+        let close_tooltip = if worktree.is_some() {
+            "Discard Worktree"
+        } else {
+            "Close Thread"
+        };
+
+        v_flex()
+            .id(("thread-switcher-entry", ix))
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_0p5()
+            .when(is_active, |this| {
+                this.bg(cx.theme().colors().element_selected)
+            })
+            .when(!is_active, |this| {
+                this.cursor_pointer()
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.activate_background_thread(entity_id, window, cx)
+                    }))
+            })
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .child(Indicator::dot().color(status.color()))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .child(Label::new(title).size(LabelSize::Small).truncate()),
+                    )
+                    .when(!is_active || worktree.is_some(), |this| {
+                        this.child(
+                            IconButton::new(("close-thread", ix), IconName::Close)
+                                .icon_size(IconSize::XSmall)
+                                .icon_color(Color::Muted)
+                                .tooltip(Tooltip::text(close_tooltip))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.close_thread(entity_id, window, cx)
+                                })),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Label::new(status.label())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .when_some(worktree, |this, worktree| {
+                        this.child(
+                            Icon::new(IconName::GitBranch)
+                                .size(IconSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            Label::new(worktree.branch.clone())
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                    }),
+            )
+            .into_any_element()
+    }
+
     fn render_drag_target(&self, cx: &Context<Self>) -> Div {
         let is_local = self.project.read(cx).is_local();
         div()
@@ -2579,9 +3493,17 @@ impl Render for AgentPanel {
             .child(self.render_toolbar(window, cx))
             .children(self.render_onboarding(window, cx))
             .map(|parent| match &self.active_view {
-                ActiveView::ExternalAgentThread { thread_view, .. } => parent
-                    .child(thread_view.clone())
-                    .child(self.render_drag_target(cx)),
+                ActiveView::ExternalAgentThread { thread_view, .. } => {
+                    match self.render_thread_switcher(cx) {
+                        Some(thread_switcher) => parent.child(
+                            h_flex().size_full().min_h_0().child(thread_switcher).child(
+                                div().flex_1().h_full().min_w_0().child(thread_view.clone()),
+                            ),
+                        ),
+                        None => parent.child(thread_view.clone()),
+                    }
+                    .child(self.render_drag_target(cx))
+                }
                 ActiveView::History => parent.child(self.acp_history.clone()),
                 ActiveView::TextThread {
                     context_editor,
@@ -2775,3 +3697,393 @@ struct TrialEndUpsell;
 impl Dismissable for TrialEndUpsell {
     const KEY: &'static str = "dismissed-trial-end-upsell";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    fn init_panel_test(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            AgentSettings::register(cx);
+            workspace::init_settings(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            release_channel::init(gpui::SemanticVersion::default(), cx);
+            speech::init(cx);
+            prompt_store::init(cx);
+            language_model::LanguageModelRegistry::test(cx);
+            editor::init(cx);
+            git_ui::init(cx);
+        });
+    }
+
+    async fn init_repository(
+        cx: &mut TestAppContext,
+    ) -> (Arc<FakeFs>, Entity<Project>, Entity<Repository>) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": "a",
+                "b.txt": "b",
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/project/.git"));
+        fs.set_head_and_index_for_repo(dot_git, &[("a.txt", "a".into()), ("b.txt", "b".into())]);
+        fs.set_branch_name(dot_git, Some("main"));
+
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        cx.run_until_parked();
+        let repository =
+            project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+        (fs, project, repository)
+    }
+
+    fn add_panel(
+        project: Entity<Project>,
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<Workspace>,
+        Entity<AgentPanel>,
+        &mut VisualTestContext,
+    ) {
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let panel = workspace.update_in(cx, |workspace, window, cx| {
+            let thread_store = cx.new(|cx| ThreadStore::fake(project.clone(), cx));
+            let context_store =
+                cx.new(|cx| assistant_context::ContextStore::fake(project.clone(), cx));
+            cx.new(|cx| AgentPanel::new(workspace, thread_store, context_store, None, window, cx))
+        });
+        cx.run_until_parked();
+        (workspace, panel, cx)
+    }
+
+    fn active_thread_worktree(
+        panel: &Entity<AgentPanel>,
+        cx: &VisualTestContext,
+    ) -> ThreadWorktree {
+        panel.read_with(cx, |panel, _| {
+            let thread_view = panel.active_thread_view().unwrap();
+            panel.thread_worktrees[&thread_view.entity_id()].clone()
+        })
+    }
+
+    async fn create_thread_worktree(
+        repository: &Entity<Repository>,
+        path: &Path,
+        branch: &str,
+        cx: &mut TestAppContext,
+    ) -> Result<()> {
+        let result = repository
+            .update(cx, |repository, _| {
+                repository.create_worktree(branch.to_string(), path.to_path_buf(), None)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        result
+    }
+
+    #[test]
+    fn test_thread_worktree_id() {
+        assert_eq!(
+            thread_worktree_id("20260101-120000", |_| false),
+            "20260101-120000"
+        );
+        assert_eq!(
+            thread_worktree_id("20260101-120000", |id| {
+                ["20260101-120000", "20260101-120000-2"].contains(&id)
+            }),
+            "20260101-120000-3"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_create_thread_worktree(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, _, repository) = init_repository(cx).await;
+        let path = Path::new(path!("/worktrees/project/1"));
+
+        create_thread_worktree(&repository, path, "agent/1", cx)
+            .await
+            .unwrap();
+        assert_eq!(fs.load(&path.join("a.txt")).await.unwrap(), "a");
+        assert_eq!(fs.load(&path.join("b.txt")).await.unwrap(), "b");
+        repository.read_with(cx, |repository, _| {
+            let worktrees = repository
+                .worktrees
+                .iter()
+                .map(|worktree| (worktree.path.as_path(), worktree.branch.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                worktrees,
+                [
+                    (Path::new(path!("/project")), Some("main".into())),
+                    (path, Some("agent/1".into())),
+                ]
+            );
+        });
+
+        // Another thread can't take the same branch.
+        let other_path = Path::new(path!("/worktrees/project/2"));
+        assert!(
+            create_thread_worktree(&repository, other_path, "agent/1", cx)
+                .await
+                .is_err()
+        );
+        assert!(!fs.is_dir(other_path).await);
+    }
+
+    #[gpui::test]
+    async fn test_merge_thread_worktree(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, _, repository) = init_repository(cx).await;
+        let path = Path::new(path!("/worktrees/project/1"));
+        create_thread_worktree(&repository, path, "agent/1", cx)
+            .await
+            .unwrap();
+
+        // The thread changes a file, deletes another and adds a third, and
+        // its changes are committed on its branch.
+        fs.set_head_and_index_for_repo(
+            &path.join(".git"),
+            &[("a.txt", "a2".into()), ("c.txt", "c".into())],
+        );
+
+        repository
+            .update(cx, |repository, _| {
+                repository.merge_squash("agent/1".to_string())
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        cx.update(|cx| {
+            delete_thread_worktree(
+                repository.clone(),
+                path.to_path_buf(),
+                "agent/1".to_string(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            "a2"
+        );
+        assert_eq!(
+            fs.load(Path::new(path!("/project/c.txt"))).await.unwrap(),
+            "c"
+        );
+        assert!(!fs.is_file(Path::new(path!("/project/b.txt"))).await);
+        repository.read_with(cx, |repository, _| {
+            let changed_paths = repository
+                .cached_status()
+                .map(|entry| entry.repo_path.as_unix_str().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(changed_paths, ["a.txt", "b.txt", "c.txt"]);
+            assert_eq!(repository.worktrees.len(), 1);
+        });
+        assert!(!fs.is_dir(path).await);
+        let branches = fs
+            .with_git_state(Path::new(path!("/project/.git")), false, |state| {
+                state.branches.clone()
+            })
+            .unwrap();
+        assert!(!branches.contains("agent/1"));
+    }
+
+    #[gpui::test]
+    async fn test_discard_thread_worktree(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, _, repository) = init_repository(cx).await;
+        let path = Path::new(path!("/worktrees/project/1"));
+        create_thread_worktree(&repository, path, "agent/1", cx)
+            .await
+            .unwrap();
+
+        // The thread leaves uncommitted changes behind, which keep its
+        // worktree from being removed without force, as orphans are.
+        fs.write(&path.join("a.txt"), b"a2").await.unwrap();
+        let removed = repository
+            .update(cx, |repository, _| {
+                repository.remove_worktree(path.to_path_buf(), false)
+            })
+            .await
+            .unwrap();
+        assert!(removed.is_err());
+        assert!(fs.is_dir(path).await);
+
+        cx.update(|cx| {
+            delete_thread_worktree(
+                repository.clone(),
+                path.to_path_buf(),
+                "agent/1".to_string(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        cx.run_until_parked();
+
+        assert!(!fs.is_dir(path).await);
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            "a"
+        );
+        repository.read_with(cx, |repository, _| {
+            assert_eq!(repository.cached_status().count(), 0);
+            assert_eq!(repository.worktrees.len(), 1);
+        });
+        let branches = fs
+            .with_git_state(Path::new(path!("/project/.git")), false, |state| {
+                state.branches.clone()
+            })
+            .unwrap();
+        assert!(!branches.contains("agent/1"));
+    }
+    #[gpui::test]
+    async fn test_new_worktree_thread(cx: &mut TestAppContext) {
+        init_panel_test(cx);
+        let (fs, project, repository) = init_repository(cx).await;
+        let (_, panel, cx) = add_panel(project, cx);
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.new_worktree_thread(&NewWorktreeThread, window, cx)
+        });
+        cx.run_until_parked();
+
+        // The thread runs in a project of its own, on a new branch checked
+        // out in a new worktree.
+        let worktree = active_thread_worktree(&panel, cx);
+        assert!(worktree.branch.starts_with("agent/"));
+        assert_eq!(
+            worktree.path,
+            thread_worktrees_dir()
+                .join("project")
+                .join(worktree.branch.trim_start_matches("agent/"))
+        );
+        assert_eq!(fs.load(&worktree.path.join("a.txt")).await.unwrap(), "a");
+        worktree.project.read_with(cx, |project, cx| {
+            let roots = project
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>();
+            assert_eq!(roots, [worktree.path.clone()]);
+        });
+        repository.read_with(cx, |repository, _| {
+            assert!(repository.worktrees.iter().any(|git_worktree| {
+                git_worktree.path == worktree.path
+                    && git_worktree.branch.as_deref() == Some(worktree.branch.as_str())
+            }));
+        });
+
+        // Another thread started in the same second gets a branch of its own.
+        panel.update_in(cx, |panel, window, cx| {
+            panel.new_worktree_thread(&NewWorktreeThread, window, cx)
+        });
+        cx.run_until_parked();
+        let other_worktree = active_thread_worktree(&panel, cx);
+        assert_ne!(other_worktree.branch, worktree.branch);
+        assert_ne!(other_worktree.path, worktree.path);
+        panel.read_with(cx, |panel, _| assert_eq!(panel.thread_worktrees.len(), 2));
+    }
+
+    #[gpui::test]
+    async fn test_merge_worktree_thread(cx: &mut TestAppContext) {
+        init_panel_test(cx);
+        let (fs, project, repository) = init_repository(cx).await;
+        let (workspace, panel, cx) = add_panel(project, cx);
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.new_worktree_thread(&NewWorktreeThread, window, cx)
+        });
+        cx.run_until_parked();
+        let worktree = active_thread_worktree(&panel, cx);
+
+        // The thread changes a file, deletes another and adds a third, and
+        // leaves them uncommitted.
+        fs.write(&worktree.path.join("a.txt"), b"a2").await.unwrap();
+        fs.remove_file(&worktree.path.join("b.txt"), Default::default())
+            .await
+            .unwrap();
+        fs.write(&worktree.path.join("c.txt"), b"c").await.unwrap();
+        cx.run_until_parked();
+
+        // Nothing happens until the merge is confirmed.
+        panel.update_in(cx, |panel, window, cx| {
+            panel.merge_worktree_thread(&MergeWorktreeThread, window, cx)
+        });
+        assert_eq!(
+            cx.pending_prompt().unwrap().0,
+            format!("Merge the changes on {}?", worktree.branch)
+        );
+        cx.simulate_prompt_answer("Cancel");
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            "a"
+        );
+        assert!(fs.is_dir(&worktree.path).await);
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.merge_worktree_thread(&MergeWorktreeThread, window, cx)
+        });
+        cx.simulate_prompt_answer("Merge");
+        cx.run_until_parked();
+
+        // The thread's changes are committed on its branch and squashed into
+        // the project's working tree, which is opened in the project diff.
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            "a2"
+        );
+        assert_eq!(
+            fs.load(Path::new(path!("/project/c.txt"))).await.unwrap(),
+            "c"
+        );
+        assert!(!fs.is_file(Path::new(path!("/project/b.txt"))).await);
+        repository.read_with(cx, |repository, _| {
+            let changed_paths = repository
+                .cached_status()
+                .map(|entry| entry.repo_path.as_unix_str().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(changed_paths, ["a.txt", "b.txt", "c.txt"]);
+            assert_eq!(repository.worktrees.len(), 1);
+        });
+        workspace.read_with(cx, |workspace, cx| {
+            assert!(workspace.active_item_as::<ProjectDiff>(cx).is_some());
+        });
+
+        // The thread, its worktree and its branch are gone.
+        panel.read_with(cx, |panel, _| {
+            assert!(panel.thread_worktrees.is_empty());
+            assert!(panel.active_thread_view().is_some());
+        });
+        assert!(!fs.is_dir(&worktree.path).await);
+        let branches = fs
+            .with_git_state(Path::new(path!("/project/.git")), false, |state| {
+                state.branches.clone()
+            })
+            .unwrap();
+        assert!(!branches.contains(&worktree.branch));
+    }
+}
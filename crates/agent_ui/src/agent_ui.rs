@@ -126,6 +126,21 @@ actions!(
         ContinueWithBurnMode,
         /// Toggles burn mode for faster responses.
         ToggleBurnMode,
+        /// Starts or stops dictating into the message editor.
+        ToggleDictation,
+        /// Dictates into the message editor while the keys bound to it are held down.
+        PushToTalk,
+        /// Stops reading the agent's response aloud.
+        StopReadingAloud,
+        /// Starts a new agent thread in its own git worktree.
+        NewWorktreeThread,
+        /// Opens the changes on the active thread's branch for review, without merging them.
+        ReviewWorktreeThread,
+        /// Squashes the active thread's branch into the project once confirmed, then deletes
+        /// its worktree and branch.
+        MergeWorktreeThread,
+        /// Deletes the active thread's worktree and branch, discarding its changes.
+        DiscardWorktreeThread,
     ]
 );
 
@@ -1,13 +1,19 @@
+use agent_settings::AgentSettings;
+use audio::{Audio, Sound};
+use collections::HashMap;
 use gpui::{
-    App, Context, EventEmitter, IntoElement, PlatformDisplay, Size, Window,
-    WindowBackgroundAppearance, WindowBounds, WindowDecorations, WindowKind, WindowOptions,
-    linear_color_stop, linear_gradient, point,
+    App, Context, EventEmitter, IntoElement, PlatformDisplay, Size, Subscription, Window,
+    WindowBackgroundAppearance, WindowBounds, WindowDecorations, WindowHandle, WindowKind,
+    WindowOptions, linear_color_stop, linear_gradient, point,
 };
 use release_channel::ReleaseChannel;
+use settings::{NotifyWhenAgentWaiting, Settings as _};
 use std::rc::Rc;
 use theme;
 use ui::{Render, prelude::*};
+use util::ResultExt as _;
 
+#[derive(Clone)]
 pub struct AgentNotification {
     title: SharedString,
     caption: SharedString,
@@ -75,6 +81,104 @@ pub enum AgentNotificationEvent {
 
 impl EventEmitter<AgentNotificationEvent> for AgentNotification {}
 
+/// The notifications a view pops up while its window is inactive, such as
+/// when the agent finishes a turn or waits for confirmation.
+#[derive(Default)]
+pub struct AgentNotifications {
+    windows: Vec<WindowHandle<AgentNotification>>,
+    subscriptions: HashMap<WindowHandle<AgentNotification>, Vec<Subscription>>,
+}
+
+impl AgentNotifications {
+    /// Plays the sound for `agent.play_sound_when_agent_done` and pops up
+    /// `notification` on the screens picked by `agent.notify_when_agent_waiting`,
+    /// unless `window` is active or a notification is already showing.
+    ///
+    /// `notifications` returns the view's notifications, and `on_accept` runs
+    /// in `window` after it's activated when the user clicks one of them.
+    pub fn notify_with_sound<V: 'static>(
+        this: &mut V,
+        notifications: fn(&mut V) -> &mut Self,
+        notification: AgentNotification,
+        on_accept: impl Fn(&mut Window, &mut App) + 'static,
+        window: &mut Window,
+        cx: &mut Context<V>,
+    ) {
+        if window.is_window_active() {
+            return;
+        }
+        let settings = AgentSettings::get_global(cx);
+        if settings.play_sound_when_agent_done {
+            Audio::play_sound(Sound::AgentDone, cx);
+        }
+        if !notifications(this).windows.is_empty() {
+            return;
+        }
+
+        let screens = match AgentSettings::get_global(cx).notify_when_agent_waiting {
+            NotifyWhenAgentWaiting::PrimaryScreen => cx.primary_display().into_iter().collect(),
+            NotifyWhenAgentWaiting::AllScreens => cx.displays(),
+            NotifyWhenAgentWaiting::Never => Vec::new(),
+        };
+        let on_accept = Rc::new(on_accept);
+        for screen in screens {
+            let options = AgentNotification::window_options(screen, cx);
+            let Some(pop_up_window) = cx
+                .open_window(options, |_, cx| cx.new(|_| notification.clone()))
+                .log_err()
+            else {
+                continue;
+            };
+            let Some(pop_up) = pop_up_window.entity(cx).log_err() else {
+                continue;
+            };
+
+            let on_accept = on_accept.clone();
+            let subscriptions = vec![
+                cx.subscribe_in(&pop_up, window, move |this, _, event, window, cx| {
+                    if let AgentNotificationEvent::Accepted = event {
+                        let handle = window.window_handle();
+                        let on_accept = on_accept.clone();
+                        cx.activate(true);
+                        // If there are multiple Zed windows, activate the correct one.
+                        cx.defer(move |cx| {
+                            handle
+                                .update(cx, |_, window, cx| {
+                                    window.activate_window();
+                                    on_accept(window, cx);
+                                })
+                                .log_err();
+                        });
+                    }
+                    notifications(this).dismiss(cx);
+                }),
+                // If the user manually refocuses the original window, dismiss the pop-up.
+                cx.observe_window_activation(window, move |this, window, cx| {
+                    if window.is_window_active() {
+                        notifications(this).dismiss(cx);
+                    }
+                }),
+            ];
+            let notifications = notifications(this);
+            notifications.windows.push(pop_up_window);
+            notifications
+                .subscriptions
+                .insert(pop_up_window, subscriptions);
+        }
+    }
+
+    pub fn dismiss(&mut self, cx: &mut App) {
+        for window in self.windows.drain(..) {
+            window
+                .update(cx, |_, window, _| {
+                    window.remove_window();
+                })
+                .ok();
+        }
+        self.subscriptions.clear();
+    }
+}
+
 impl Render for AgentNotification {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let ui_font = theme::setup_ui_font(window, cx);
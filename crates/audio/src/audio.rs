@@ -23,11 +23,15 @@ use settings::Settings;
 use std::{io::Cursor, num::NonZero, path::PathBuf, sync::atomic::Ordering, time::Duration};
 use util::ResultExt;
 
+mod audio_clip;
 mod audio_settings;
 mod replays;
 mod rodio_ext;
+mod speech_playback;
+pub use audio_clip::{AUDIO_CLIP_EXTENSIONS, AudioAnalysis, AudioClip, ClipPlayback, encode_wav};
 pub use audio_settings::AudioSettings;
 pub use rodio_ext::RodioExt;
+pub use speech_playback::SpeechPlayback;
 
 use crate::audio_settings::LIVE_SETTINGS;
 
@@ -82,6 +86,9 @@ impl Sound {
 pub struct Audio {
     output_handle: Option<OutputStream>,
     output_mixer: Option<Mixer>,
+    /// A stream of its own for clips, which plays them at the device's
+    /// parameters rather than those of calls.
+    clip_output_handle: Option<OutputStream>,
     #[cfg(not(any(all(target_os = "windows", target_env = "gnu"), target_os = "freebsd")))]
     pub echo_canceller: Arc<Mutex<apm::AudioProcessingModule>>,
     source_cache: HashMap<Sound, Buffered<Decoder<Cursor<Vec<u8>>>>>,
@@ -93,6 +100,7 @@ impl Default for Audio {
         Self {
             output_handle: Default::default(),
             output_mixer: Default::default(),
+            clip_output_handle: Default::default(),
             #[cfg(not(any(
                 all(target_os = "windows", target_env = "gnu"),
                 target_os = "freebsd"
@@ -157,6 +165,21 @@ impl Audio {
             .expect("we only get here if opening the outputstream succeeded"))
     }
 
+    fn ensure_clip_output_exists(&mut self) -> Result<&Mixer> {
+        if self.clip_output_handle.is_none() {
+            let output_handle = OutputStreamBuilder::open_default_stream()
+                .context("Could not open default output stream")?;
+            info!("Clip output stream: {:?}", output_handle);
+            self.clip_output_handle = Some(output_handle);
+        }
+
+        Ok(self
+            .clip_output_handle
+            .as_ref()
+            .expect("we only get here if opening the outputstream succeeded")
+            .mixer())
+    }
+
     pub fn save_replays(
         &self,
         executor: BackgroundExecutor,
@@ -267,6 +290,33 @@ impl Audio {
         });
     }
 
+    /// Starts playing synthesized speech. Utterances are appended to the
+    /// returned [`SpeechPlayback`] as they become available.
+    pub fn play_speech(cx: &mut App) -> Result<SpeechPlayback> {
+        cx.update_default_global(|this: &mut Self, _cx| {
+            let output_mixer = this
+                .ensure_output_exists()
+                .context("Could not get output mixer")?;
+            let (playback, source) = SpeechPlayback::new();
+            output_mixer.add(source);
+            Ok(playback)
+        })
+    }
+
+    /// Plays a decoded clip once, at its own sample rate and channel count
+    /// rather than through the call mixer. Dropping the returned handle stops
+    /// it.
+    pub fn play_clip(clip: AudioClip, cx: &mut App) -> Result<ClipPlayback> {
+        cx.update_default_global(|this: &mut Self, _cx| {
+            let output_mixer = this
+                .ensure_clip_output_exists()
+                .context("Could not get clip output mixer")?;
+            let (playback, source) = clip.into_playback();
+            output_mixer.add(source);
+            Ok(playback)
+        })
+    }
+
     pub fn end_call(cx: &mut App) {
         cx.update_default_global(|this: &mut Self, _cx| {
             this.output_handle.take();
@@ -317,4 +367,12 @@ impl VoipParts {
             replays,
         })
     }
+
+    /// Parts for a microphone that is transcribed locally rather than sent
+    /// to a call, so it always produces [`SAMPLE_RATE`] mono audio.
+    pub fn for_dictation(cx: &AsyncApp) -> anyhow::Result<Self> {
+        let mut parts = Self::new(cx)?;
+        parts.legacy_audio_compatible = false;
+        Ok(parts)
+    }
 }
@@ -0,0 +1,368 @@
+use std::{
+    fmt::Write as _,
+    io::Cursor,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use rodio::{ChannelCount, Decoder, Sample, SampleRate, Source, buffer::SamplesBuffer};
+
+use crate::{CHANNEL_COUNT, RodioExt as _, SAMPLE_RATE};
+
+/// The file extensions [`AudioClip::decode`] understands.
+pub const AUDIO_CLIP_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "ogg"];
+
+/// Frames where every channel is quieter than this are considered silent.
+const SILENCE_THRESHOLD_DBFS: f32 = -60.0;
+/// Samples at or above this amplitude are counted as clipped.
+const CLIPPING_THRESHOLD: f32 = 0.999;
+const LOW_BAND_CUTOFF_HZ: f32 = 250.0;
+const HIGH_BAND_CUTOFF_HZ: f32 = 4000.0;
+
+/// A decoded audio file, such as a bounce or a stem attached to an agent message.
+pub struct AudioClip {
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    /// Interleaved samples of all channels.
+    samples: Vec<Sample>,
+}
+
+impl AudioClip {
+    /// Decodes a WAV, MP3, FLAC or Ogg Vorbis file.
+    pub fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let decoder = Decoder::new(Cursor::new(bytes)).context("unsupported audio format")?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        let samples = decoder.collect::<Vec<_>>();
+        anyhow::ensure!(!samples.is_empty(), "audio file contains no samples");
+        Ok(Self {
+            channels,
+            sample_rate,
+            samples,
+        })
+    }
+
+    pub fn is_supported_extension(extension: &str) -> bool {
+        AUDIO_CLIP_EXTENSIONS
+            .iter()
+            .any(|supported| supported.eq_ignore_ascii_case(extension))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.frames_to_duration(self.frame_count())
+    }
+
+    /// Downmixes and resamples the clip to a 16kHz mono WAV file. Models
+    /// downsample audio input to about that resolution anyway, and it keeps
+    /// attachments of long bounces small.
+    pub fn to_model_wav(&self) -> Vec<u8> {
+        let samples = SamplesBuffer::new(self.channels, self.sample_rate, self.samples.clone())
+            .constant_params(CHANNEL_COUNT, SAMPLE_RATE)
+            .collect::<Vec<_>>();
+        encode_wav(&samples, SAMPLE_RATE.get())
+    }
+
+    pub fn analyze(&self) -> AudioAnalysis {
+        let channels = self.channels.get() as usize;
+        let frames = self.samples.chunks_exact(channels);
+
+        let peak = self
+            .samples
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+        let mean_square = self
+            .samples
+            .iter()
+            .map(|sample| sample * sample)
+            .sum::<f32>()
+            / self.samples.len() as f32;
+        let clipped_samples = self
+            .samples
+            .iter()
+            .filter(|sample| sample.abs() >= CLIPPING_THRESHOLD)
+            .count();
+
+        let silence_threshold = dbfs_to_amplitude(SILENCE_THRESHOLD_DBFS);
+        let is_audible = |frame: &[Sample]| frame.iter().any(|s| s.abs() > silence_threshold);
+        let leading_silent_frames = frames
+            .clone()
+            .take_while(|frame| !is_audible(*frame))
+            .count();
+        let trailing_silent_frames = if leading_silent_frames == self.frame_count() {
+            0
+        } else {
+            frames
+                .clone()
+                .rev()
+                .take_while(|frame| !is_audible(*frame))
+                .count()
+        };
+
+        let stereo_correlation = (channels == 2)
+            .then(|| {
+                let (mut left_right, mut left_squared, mut right_squared) = (0.0, 0.0, 0.0);
+                for frame in frames.clone() {
+                    left_right += frame[0] * frame[1];
+                    left_squared += frame[0] * frame[0];
+                    right_squared += frame[1] * frame[1];
+                }
+                let norm = f32::sqrt(left_squared * right_squared);
+                (norm > 0.0).then(|| left_right / norm)
+            })
+            .flatten();
+
+        let sample_rate = self.sample_rate.get() as f32;
+        let mut low_pass = OnePoleLowPass::new(LOW_BAND_CUTOFF_HZ, sample_rate);
+        let mut high_pass = OnePoleLowPass::new(HIGH_BAND_CUTOFF_HZ, sample_rate);
+        let mut band_energy = [0.0_f32; 3];
+        for frame in frames {
+            let mono = frame.iter().sum::<f32>() / channels as f32;
+            let low = low_pass.process(mono);
+            let below_high = high_pass.process(mono);
+            band_energy[0] += low * low;
+            band_energy[1] += (below_high - low) * (below_high - low);
+            band_energy[2] += (mono - below_high) * (mono - below_high);
+        }
+        let total_energy = band_energy.iter().sum::<f32>();
+        let spectral_balance = if total_energy > 0.0 {
+            band_energy.map(|energy| energy / total_energy)
+        } else {
+            [0.0; 3]
+        };
+
+        AudioAnalysis {
+            duration: self.duration(),
+            channels: self.channels.get(),
+            sample_rate: self.sample_rate.get(),
+            peak_dbfs: amplitude_to_dbfs(peak),
+            rms_dbfs: amplitude_to_dbfs(mean_square.sqrt()),
+            clipped_samples,
+            leading_silence: self.frames_to_duration(leading_silent_frames),
+            trailing_silence: self.frames_to_duration(trailing_silent_frames),
+            stereo_correlation,
+            spectral_balance,
+        }
+    }
+
+    /// Returns a source playing the clip at its own parameters, along with
+    /// the handle that stops it.
+    pub(crate) fn into_playback(self) -> (ClipPlayback, impl Source + Send + 'static) {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let source = SamplesBuffer::new(self.channels, self.sample_rate, self.samples)
+            .stoppable()
+            .periodic_access(Duration::from_millis(10), {
+                let stopped = stopped.clone();
+                move |source| {
+                    if stopped.load(Ordering::Relaxed) {
+                        source.stop();
+                    }
+                }
+            });
+        (ClipPlayback { stopped }, source)
+    }
+
+    fn frame_count(&self) -> usize {
+        self.samples.len() / self.channels.get() as usize
+    }
+
+    fn frames_to_duration(&self, frames: usize) -> Duration {
+        Duration::from_secs_f64(frames as f64 / self.sample_rate.get() as f64)
+    }
+}
+
+/// An [`AudioClip`] playing through the output device, for example a sample
+/// being auditioned. Playback ends when the handle is stopped or dropped.
+pub struct ClipPlayback {
+    stopped: Arc<AtomicBool>,
+}
+
+impl ClipPlayback {
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ClipPlayback {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Levels and balance of an [`AudioClip`], used in place of the audio itself
+/// for models that do not support audio input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioAnalysis {
+    pub duration: Duration,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    pub clipped_samples: usize,
+    pub leading_silence: Duration,
+    pub trailing_silence: Duration,
+    /// The correlation between the left and right channel of stereo clips,
+    /// from -1 (out of phase) to 1 (mono).
+    pub stereo_correlation: Option<f32>,
+    /// The share of energy below 250Hz, between 250Hz and 4kHz, and above 4kHz.
+    pub spectral_balance: [f32; 3],
+}
+
+impl AudioAnalysis {
+    pub fn to_markdown(&self) -> String {
+        let seconds = self.duration.as_secs_f32();
+        let mut markdown = String::new();
+        writeln!(
+            markdown,
+            "- Duration: {}:{:05.2}",
+            (seconds / 60.0) as u32,
+            seconds % 60.0
+        )
+        .ok();
+        writeln!(
+            markdown,
+            "- Format: {} channel(s), {} Hz",
+            self.channels, self.sample_rate
+        )
+        .ok();
+        writeln!(markdown, "- Peak: {:.1} dBFS", self.peak_dbfs).ok();
+        writeln!(
+            markdown,
+            "- RMS level: {:.1} dBFS (crest factor {:.1} dB)",
+            self.rms_dbfs,
+            self.peak_dbfs - self.rms_dbfs
+        )
+        .ok();
+        writeln!(markdown, "- Clipped samples: {}", self.clipped_samples).ok();
+        writeln!(
+            markdown,
+            "- Silence: {:.2} s at the start, {:.2} s at the end",
+            self.leading_silence.as_secs_f32(),
+            self.trailing_silence.as_secs_f32()
+        )
+        .ok();
+        if let Some(correlation) = self.stereo_correlation {
+            writeln!(markdown, "- Stereo correlation: {correlation:.2}").ok();
+        }
+        let [low, mid, high] = self.spectral_balance.map(|share| share * 100.0);
+        writeln!(
+            markdown,
+            "- Spectral balance: {low:.0}% below 250 Hz, {mid:.0}% between 250 Hz and 4 kHz, {high:.0}% above 4 kHz"
+        )
+        .ok();
+        markdown
+    }
+}
+
+struct OnePoleLowPass {
+    coefficient: f32,
+    state: f32,
+}
+
+impl OnePoleLowPass {
+    fn new(cutoff_hz: f32, sample_rate: f32) -> Self {
+        Self {
+            coefficient: 1.0 - (-2.0 * std::f32::consts::PI * cutoff_hz / sample_rate).exp(),
+            state: 0.0,
+        }
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        self.state += self.coefficient * (sample - self.state);
+        self.state
+    }
+}
+
+fn amplitude_to_dbfs(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(1e-10).log10()
+}
+
+fn dbfs_to_amplitude(dbfs: f32) -> f32 {
+    10.0_f32.powf(dbfs / 20.0)
+}
+
+/// Encodes mono samples as a 16-bit PCM WAV file.
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    const CHANNELS: u16 = 1;
+
+    let data_len = (samples.len() * 2) as u32;
+    let byte_rate = sample_rate * u32::from(CHANNELS) * u32::from(BITS_PER_SAMPLE) / 8;
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&CHANNELS.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use rodio::nz;
+
+    use super::*;
+
+    #[test]
+    fn test_encode_wav() {
+        let wav = encode_wav(&[0.0, 1.0, -1.0, 2.0], 16000);
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 16000);
+        assert_eq!(u32::from_le_bytes(wav[28..32].try_into().unwrap()), 32000);
+        assert_eq!(&wav[36..40], b"data");
+        let samples = wav[44..]
+            .chunks(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(samples, [0, i16::MAX, -i16::MAX, i16::MAX]);
+    }
+
+    #[test]
+    fn test_analyze() {
+        // A quarter second of silence followed by a second of a 1kHz tone at half
+        // scale, identical in both channels.
+        let sample_rate = 48000;
+        let mut samples = vec![0.0; sample_rate / 4 * 2];
+        for ix in 0..sample_rate {
+            let phase = 2.0 * std::f32::consts::PI * 1000.0 * ix as f32 / sample_rate as f32;
+            let sample = 0.5 * phase.sin();
+            samples.extend([sample, sample]);
+        }
+        let clip = AudioClip {
+            channels: nz!(2),
+            sample_rate: nz!(48000),
+            samples,
+        };
+
+        let analysis = clip.analyze();
+        assert_eq!(analysis.duration, Duration::from_millis(1250));
+        assert!((analysis.peak_dbfs - -6.02).abs() < 0.1);
+        assert!((analysis.rms_dbfs - -10.0).abs() < 0.1);
+        assert_eq!(analysis.clipped_samples, 0);
+        assert_eq!(analysis.leading_silence, Duration::from_millis(250));
+        assert!(analysis.trailing_silence < Duration::from_millis(1));
+        assert!((analysis.stereo_correlation.unwrap() - 1.0).abs() < 0.001);
+        let [low, mid, high] = analysis.spectral_balance;
+        assert!(mid > low && mid > high);
+    }
+}
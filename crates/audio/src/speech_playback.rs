@@ -0,0 +1,115 @@
+use std::{
+    io::Cursor,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use crossbeam::channel::{Receiver, Sender, TryRecvError};
+use rodio::{ChannelCount, Decoder, Sample, SampleRate, Source};
+
+use crate::{CHANNEL_COUNT, RodioExt as _, SAMPLE_RATE};
+
+/// Synthesized speech that starts playing as soon as the first utterance is
+/// appended. Playback ends when the handle is stopped or dropped.
+pub struct SpeechPlayback {
+    utterances: Sender<Vec<Sample>>,
+    queued_samples: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl SpeechPlayback {
+    pub(crate) fn new() -> (Self, impl Source + Send + 'static) {
+        let (utterances_tx, utterances_rx) = crossbeam::channel::unbounded();
+        let queued_samples = Arc::new(AtomicUsize::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let source = SpeechSource {
+            utterances: utterances_rx,
+            current: Vec::new().into_iter(),
+            queued_samples: queued_samples.clone(),
+            stopped: stopped.clone(),
+        };
+        let this = Self {
+            utterances: utterances_tx,
+            queued_samples,
+            stopped,
+        };
+        (this, source)
+    }
+
+    /// Decodes a WAV utterance and queues it behind the utterances appended before.
+    pub fn append_wav(&self, wav: Vec<u8>) -> Result<()> {
+        let samples = Decoder::new(Cursor::new(wav))?
+            .constant_params(CHANNEL_COUNT, SAMPLE_RATE)
+            .collect::<Vec<_>>();
+        self.queued_samples
+            .fetch_add(samples.len(), Ordering::Relaxed);
+        self.utterances.send(samples).ok();
+        Ok(())
+    }
+
+    /// Whether everything appended so far has been played.
+    pub fn is_idle(&self) -> bool {
+        self.queued_samples.load(Ordering::Relaxed) == 0
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SpeechPlayback {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Plays queued utterances back to back, and silence while waiting for the next one.
+struct SpeechSource {
+    utterances: Receiver<Vec<Sample>>,
+    current: std::vec::IntoIter<Sample>,
+    queued_samples: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Iterator for SpeechSource {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+        loop {
+            if let Some(sample) = self.current.next() {
+                self.queued_samples.fetch_sub(1, Ordering::Relaxed);
+                return Some(sample);
+            }
+            match self.utterances.try_recv() {
+                Ok(utterance) => self.current = utterance.into_iter(),
+                Err(TryRecvError::Empty) => return Some(0.0),
+                Err(TryRecvError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+impl Source for SpeechSource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        CHANNEL_COUNT
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
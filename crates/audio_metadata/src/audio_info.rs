@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+/// The uncompressed and lossless formats whose headers we understand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Aiff,
+    Flac,
+}
+
+/// The format and length of an audio file, read from its header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioInfo {
+    pub format: AudioFormat,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    pub duration: Duration,
+}
+
+impl AudioInfo {
+    /// How many bytes of a file to read to find its audio header.
+    pub const HEADER_LEN: usize = 64 * 1024;
+
+    /// Reads the format and duration of a WAV, AIFF or FLAC file from the
+    /// start of its contents.
+    pub fn parse(header: &[u8]) -> Option<Self> {
+        match header.get(0..4)? {
+            b"RIFF" => parse_wav(header),
+            b"FORM" => parse_aiff(header),
+            b"fLaC" => parse_flac(header),
+            _ => None,
+        }
+    }
+}
+
+fn parse_wav(header: &[u8]) -> Option<AudioInfo> {
+    if header.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut offset = 12;
+    let mut format = None;
+    while let Some(chunk) = header.get(offset..offset + 8) {
+        let id = &chunk[0..4];
+        let len = u32::from_le_bytes(chunk[4..8].try_into().ok()?) as usize;
+        let body = offset + 8;
+        match id {
+            b"fmt " => {
+                let fmt = header.get(body..body + 16)?;
+                let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+                let sample_rate = u32::from_le_bytes(fmt[4..8].try_into().ok()?);
+                let byte_rate = u32::from_le_bytes(fmt[8..12].try_into().ok()?);
+                let bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
+                format = Some((channels, sample_rate, byte_rate, bits_per_sample));
+            }
+            b"data" => {
+                let (channels, sample_rate, byte_rate, bits_per_sample) = format?;
+                if byte_rate == 0 {
+                    return None;
+                }
+                return Some(AudioInfo {
+                    format: AudioFormat::Wav,
+                    channels,
+                    sample_rate,
+                    bits_per_sample,
+                    duration: Duration::try_from_secs_f64(len as f64 / byte_rate as f64).ok()?,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        offset = body + len + (len & 1);
+    }
+    None
+}
+
+fn parse_aiff(header: &[u8]) -> Option<AudioInfo> {
+    if !matches!(header.get(8..12)?, b"AIFF" | b"AIFC") {
+        return None;
+    }
+    let mut offset = 12;
+    while let Some(chunk) = header.get(offset..offset + 8) {
+        let len = u32::from_be_bytes(chunk[4..8].try_into().ok()?) as usize;
+        let body = offset + 8;
+        if &chunk[0..4] == b"COMM" {
+            let comm = header.get(body..body + 18)?;
+            let channels = u16::from_be_bytes([comm[0], comm[1]]);
+            let frames = u32::from_be_bytes(comm[2..6].try_into().ok()?);
+            let bits_per_sample = u16::from_be_bytes([comm[6], comm[7]]);
+            let sample_rate = parse_extended_float(comm[8..18].try_into().ok()?);
+            // The sample rate comes straight from the file, so it may be NaN,
+            // infinite or too small to divide by.
+            if !(sample_rate.is_finite() && sample_rate >= 1.0) {
+                return None;
+            }
+            return Some(AudioInfo {
+                format: AudioFormat::Aiff,
+                channels,
+                sample_rate: sample_rate.round() as u32,
+                bits_per_sample,
+                duration: Duration::try_from_secs_f64(frames as f64 / sample_rate).ok()?,
+            });
+        }
+        offset = body + len + (len & 1);
+    }
+    None
+}
+
+/// Decodes the 80-bit IEEE 754 extended precision float AIFF uses for its
+/// sample rate.
+fn parse_extended_float(bytes: [u8; 10]) -> f64 {
+    let exponent = (u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7fff) as i32;
+    let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+    if exponent == 0 && mantissa == 0 {
+        return 0.0;
+    }
+    let value = mantissa as f64 * 2f64.powi(exponent - 16383 - 63);
+    if bytes[0] & 0x80 != 0 { -value } else { value }
+}
+
+fn parse_flac(header: &[u8]) -> Option<AudioInfo> {
+    // STREAMINFO is always the first metadata block.
+    let block_header = header.get(4..8)?;
+    if block_header[0] & 0x7f != 0 {
+        return None;
+    }
+    let info = header.get(8..8 + 34)?;
+    let sample_rate = (info[10] as u32) << 12 | (info[11] as u32) << 4 | (info[12] as u32) >> 4;
+    let channels = ((info[12] >> 1) & 0x7) as u16 + 1;
+    let bits_per_sample = (((info[12] & 0x1) << 4) | (info[13] >> 4)) as u16 + 1;
+    let total_samples =
+        ((info[13] & 0xf) as u64) << 32 | u32::from_be_bytes(info[14..18].try_into().ok()?) as u64;
+    if sample_rate == 0 {
+        return None;
+    }
+    Some(AudioInfo {
+        format: AudioFormat::Flac,
+        channels,
+        sample_rate,
+        bits_per_sample,
+        duration: Duration::try_from_secs_f64(total_samples as f64 / sample_rate as f64).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_audio() {
+        // 1 second of 16-bit stereo audio at 44.1kHz, with a LIST chunk
+        // before the data.
+        let mut wav = Vec::new();
+        wav.extend(b"RIFF\0\0\0\0WAVE");
+        wav.extend(b"fmt \x10\0\0\0\x01\0\x02\0");
+        wav.extend(44100u32.to_le_bytes());
+        wav.extend((44100u32 * 4).to_le_bytes());
+        wav.extend(b"\x04\0\x10\0");
+        wav.extend(b"LIST\x03\0\0\0abc\0");
+        wav.extend(b"data");
+        wav.extend((44100u32 * 4).to_le_bytes());
+        let info = AudioInfo::parse(&wav).unwrap();
+        assert_eq!(
+            info,
+            AudioInfo {
+                format: AudioFormat::Wav,
+                channels: 2,
+                sample_rate: 44100,
+                bits_per_sample: 16,
+                duration: Duration::from_secs(1),
+            }
+        );
+
+        // 2 seconds of 24-bit mono audio at 48kHz.
+        let mut aiff = Vec::new();
+        aiff.extend(b"FORM\0\0\0\0AIFF");
+        aiff.extend(b"COMM\0\0\0\x12\0\x01");
+        aiff.extend(96000u32.to_be_bytes());
+        aiff.extend(b"\0\x18");
+        aiff.extend(b"\x40\x0e\xbb\x80\0\0\0\0\0\0");
+        let info = AudioInfo::parse(&aiff).unwrap();
+        assert_eq!(
+            (info.format, info.channels, info.sample_rate, info.duration),
+            (AudioFormat::Aiff, 1, 48000, Duration::from_secs(2))
+        );
+
+        // Corrupt sample rates that are infinite or too small to divide by.
+        for sample_rate in [b"\x7f\xff\x80\0\0\0\0\0\0\0", b"\0\0\0\0\0\0\0\0\0\x01"] {
+            let mut aiff = aiff.clone();
+            aiff[28..38].copy_from_slice(sample_rate);
+            assert_eq!(AudioInfo::parse(&aiff), None);
+        }
+
+        // 3 seconds of 16-bit stereo audio at 44.1kHz.
+        let mut flac = Vec::new();
+        flac.extend(b"fLaC\0\0\0\x22");
+        flac.extend([0; 10]);
+        let samples = 3 * 44100u64;
+        flac.extend([
+            (44100u32 >> 12) as u8,
+            (44100u32 >> 4) as u8,
+            ((44100u32 & 0xf) << 4) as u8 | (1 << 1),
+            (15 << 4) | (samples >> 32) as u8,
+        ]);
+        flac.extend((samples as u32).to_be_bytes());
+        flac.extend([0; 16]);
+        let info = AudioInfo::parse(&flac).unwrap();
+        assert_eq!(
+            (
+                info.format,
+                info.channels,
+                info.sample_rate,
+                info.bits_per_sample,
+                info.duration
+            ),
+            (AudioFormat::Flac, 2, 44100, 16, Duration::from_secs(3))
+        );
+
+        assert_eq!(AudioInfo::parse(b"<?xml version"), None);
+    }
+}
@@ -0,0 +1,8 @@
+//! Reads the format, length, tempo and key of audio files from the start of
+//! their contents, without decoding them.
+
+mod audio_info;
+mod sample_metadata;
+
+pub use audio_info::{AudioFormat, AudioInfo};
+pub use sample_metadata::{METADATA_HEADER_LEN, SampleMetadata, bpm_matches, parse_key};
@@ -0,0 +1,562 @@
+use std::{path::Path, sync::LazyLock, time::Duration};
+
+use regex::Regex;
+
+use crate::AudioInfo;
+
+/// How much of a file is read when looking for metadata. The chunks we
+/// understand almost always precede the audio data.
+pub const METADATA_HEADER_LEN: u64 = 1024 * 1024;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+static BPM_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(\d{2,3}(?:\.\d+)?)\s*bpm").unwrap());
+static KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Ga-g])(#|♯|b|♭|sharp|flat)?(m|min|minor|maj|major)?$").unwrap()
+});
+
+/// What we know about a sample without decoding it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SampleMetadata {
+    pub duration: Option<Duration>,
+    pub bpm: Option<f32>,
+    /// The musical key in a normalized form, such as "F#m" or "C".
+    pub key: Option<String>,
+    /// Lowercase words describing the sample, taken from its file name and
+    /// embedded title, genre and keyword tags.
+    pub tags: Vec<String>,
+}
+
+impl SampleMetadata {
+    /// Reads metadata from the file name alone, for example for samples in
+    /// remote projects.
+    pub fn from_path(path: &Path) -> Self {
+        let mut metadata = Self::default();
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            metadata.merge_text(stem);
+        }
+        metadata
+    }
+
+    /// Reads metadata from the file name and the beginning of the file.
+    /// `file_len` is used to estimate the length of MP3 files without a
+    /// length tag.
+    pub fn read(path: &Path, header: &[u8], file_len: u64) -> Self {
+        let mut embedded = Self::default();
+        if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WAVE".as_slice()) {
+            embedded.read_riff(header);
+        } else if header.starts_with(b"ID3") || is_mpeg_frame(header) {
+            let audio_start = embedded.read_id3(header);
+            if embedded.duration.is_none() {
+                embedded.duration = estimate_mp3_duration(header, audio_start, file_len);
+            }
+        } else if header.starts_with(b"fLaC") {
+            embedded.read_flac(header);
+        }
+        if let Some(info) = AudioInfo::parse(header) {
+            embedded.duration = Some(info.duration);
+        }
+
+        // Tags set by the author take precedence over guesses from the name.
+        let mut metadata = Self::from_path(path);
+        metadata.duration = embedded.duration.or(metadata.duration);
+        metadata.bpm = embedded.bpm.or(metadata.bpm);
+        metadata.key = embedded.key.or(metadata.key);
+        for tag in embedded.tags {
+            if !metadata.tags.contains(&tag) {
+                metadata.tags.push(tag);
+            }
+        }
+        metadata
+    }
+
+    /// Extracts the tempo, key and descriptive words from free text, such as
+    /// a file name or a title tag.
+    pub fn merge_text(&mut self, text: &str) {
+        for word in self.take_tempo_and_key(text) {
+            let word = word.to_lowercase();
+            if word.len() > 1
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !self.tags.contains(&word)
+            {
+                self.tags.push(word);
+            }
+        }
+    }
+
+    /// Sets the tempo and key mentioned in `text` unless they are already
+    /// known, and returns the remaining words.
+    pub fn take_tempo_and_key(&mut self, text: &str) -> Vec<String> {
+        if self.bpm.is_none() {
+            self.bpm = BPM_REGEX
+                .captures(text)
+                .and_then(|captures| captures[1].parse().ok())
+                .filter(|bpm| is_plausible_bpm(*bpm));
+        }
+        let text = BPM_REGEX.replace(text, " ");
+
+        let mut remaining_words = Vec::new();
+        let words = split_words(&text);
+        let mut words = words.iter().peekable();
+        while let Some(word) = words.next() {
+            let mode = words
+                .peek()
+                .filter(|next| matches!(next.to_lowercase().as_str(), "major" | "minor"));
+            if let Some(key) = mode.and_then(|mode| parse_key(&format!("{word}{mode}"))) {
+                self.key.get_or_insert(key);
+                words.next();
+            } else if let Some(key) = parse_key(word) {
+                self.key.get_or_insert(key);
+            } else {
+                remaining_words.push(word.to_string());
+            }
+        }
+        remaining_words
+    }
+
+    fn read_riff(&mut self, bytes: &[u8]) {
+        for (id, chunk) in riff_chunks(bytes, 12) {
+            match id {
+                b"acid" => {
+                    const ROOT_NOTE_SET: u32 = 0x02;
+                    let flags = read_u32_le(chunk, 0).unwrap_or(0);
+                    if flags & ROOT_NOTE_SET != 0
+                        && let Some(root_note) = read_u16_le(chunk, 4)
+                    {
+                        self.key = Some(NOTE_NAMES[root_note as usize % 12].to_string());
+                    }
+                    self.bpm = read_f32_le(chunk, 20).filter(|bpm| is_plausible_bpm(*bpm));
+                }
+                b"LIST" if chunk.starts_with(b"INFO") => {
+                    for (id, value) in riff_chunks(chunk, 4) {
+                        if matches!(id, b"INAM" | b"IGNR" | b"IKEY") {
+                            self.merge_text(&latin1_to_string(value));
+                        }
+                    }
+                }
+                b"id3 " | b"ID3 " => {
+                    self.read_id3(chunk);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads an ID3v2.3 or ID3v2.4 tag and returns the offset of the data
+    /// following it.
+    fn read_id3(&mut self, bytes: &[u8]) -> usize {
+        const EXTENDED_HEADER: u8 = 0x40;
+
+        if !bytes.starts_with(b"ID3") || bytes.len() < 10 {
+            return 0;
+        }
+        let version = bytes[3];
+        let flags = bytes[5];
+        let tag_end = (10 + read_synchsafe(&bytes[6..10]) as usize).min(bytes.len());
+        if !matches!(version, 3 | 4) {
+            return tag_end;
+        }
+
+        let mut offset = 10;
+        if flags & EXTENDED_HEADER != 0 {
+            let Some(extended_len) = bytes.get(10..14) else {
+                return tag_end;
+            };
+            offset += match version {
+                3 => 4 + u32::from_be_bytes(extended_len.try_into().unwrap()) as usize,
+                _ => read_synchsafe(extended_len) as usize,
+            };
+        }
+
+        while offset + 10 <= tag_end {
+            let id = &bytes[offset..offset + 4];
+            if id[0] == 0 {
+                break;
+            }
+            let size_bytes = &bytes[offset + 4..offset + 8];
+            let size = match version {
+                3 => u32::from_be_bytes(size_bytes.try_into().unwrap()),
+                _ => read_synchsafe(size_bytes),
+            } as usize;
+            let frame_start = offset + 10;
+            let frame_end = frame_start.saturating_add(size).min(tag_end);
+            let frame = &bytes[frame_start..frame_end];
+            offset = frame_start.saturating_add(size);
+
+            let Some(text) = decode_id3_text(frame) else {
+                continue;
+            };
+            match id {
+                b"TBPM" => {
+                    self.bpm = text
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|bpm| is_plausible_bpm(*bpm));
+                }
+                b"TKEY" => self.key = parse_key(text.trim()),
+                b"TLEN" => {
+                    self.duration = text.trim().parse().ok().map(Duration::from_millis);
+                }
+                b"TIT2" | b"TCON" => self.merge_text(&text),
+                _ => {}
+            }
+        }
+        tag_end
+    }
+
+    fn read_flac(&mut self, bytes: &[u8]) {
+        const VORBIS_COMMENT: u8 = 4;
+        const LAST_BLOCK: u8 = 0x80;
+
+        let mut offset = 4;
+        while let Some(header) = bytes.get(offset..offset + 4) {
+            let block_type = header[0] & !LAST_BLOCK;
+            let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+            let block_start = offset + 4;
+            let block = &bytes[block_start..block_start.saturating_add(len).min(bytes.len())];
+            if block_type == VORBIS_COMMENT {
+                self.read_vorbis_comments(block);
+            }
+            if header[0] & LAST_BLOCK != 0 {
+                break;
+            }
+            offset = block_start.saturating_add(len);
+        }
+    }
+
+    fn read_vorbis_comments(&mut self, block: &[u8]) {
+        let Some(vendor_len) = read_u32_le(block, 0) else {
+            return;
+        };
+        let mut offset = 4 + vendor_len as usize;
+        let Some(count) = read_u32_le(block, offset) else {
+            return;
+        };
+        offset += 4;
+        for _ in 0..count {
+            let Some(len) = read_u32_le(block, offset) else {
+                return;
+            };
+            let start = offset + 4;
+            let Some(comment) = block.get(start..start + len as usize) else {
+                return;
+            };
+            offset = start + len as usize;
+
+            let comment = String::from_utf8_lossy(comment);
+            let Some((field, value)) = comment.split_once('=') else {
+                continue;
+            };
+            match field.to_uppercase().as_str() {
+                "BPM" | "TEMPO" => {
+                    self.bpm = value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|bpm| is_plausible_bpm(*bpm));
+                }
+                "KEY" | "INITIALKEY" => self.key = parse_key(value.trim()),
+                "TITLE" | "GENRE" => self.merge_text(value),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parses a musical key such as "Am", "C#", "Bb minor" or "F sharp major",
+/// returning it with sharps and a trailing "m" for minor keys.
+pub fn parse_key(text: &str) -> Option<String> {
+    let text = text.replace(' ', "");
+    let captures = KEY_REGEX.captures(&text)?;
+    let note = captures[1].chars().next()?;
+    let accidental = captures.get(2).map(|m| m.as_str());
+    let mode = captures.get(3).map(|m| m.as_str().to_lowercase());
+    // A lowercase letter on its own is more likely a word than a key.
+    if note.is_ascii_lowercase() && accidental.is_none() && mode.is_none() {
+        return None;
+    }
+
+    let mut semitone: i32 = match note.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        _ => 11,
+    };
+    match accidental {
+        Some("#" | "♯" | "sharp") => semitone += 1,
+        Some("b" | "♭" | "flat") => semitone -= 1,
+        _ => {}
+    }
+    let mut key = NOTE_NAMES[semitone.rem_euclid(12) as usize].to_string();
+    if matches!(mode.as_deref(), Some("m" | "min" | "minor")) {
+        key.push('m');
+    }
+    Some(key)
+}
+
+/// Whether a sample at `bpm` can be used in a project at `target_bpm`,
+/// including at half or double time.
+pub fn bpm_matches(bpm: f32, target_bpm: f32) -> bool {
+    let tolerance = (target_bpm * 0.02).max(1.5);
+    [target_bpm, target_bpm / 2.0, target_bpm * 2.0]
+        .iter()
+        .any(|target| (bpm - target).abs() <= tolerance)
+}
+
+fn is_plausible_bpm(bpm: f32) -> bool {
+    (20.0..=400.0).contains(&bpm)
+}
+
+fn split_words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '#' || c == '♯' || c == '♭'))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Iterates over the ids and contents of the chunks of a RIFF file, starting
+/// at `offset`. Chunks cut off by the end of `bytes` are truncated.
+fn riff_chunks(bytes: &[u8], mut offset: usize) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        let id = bytes.get(offset..offset + 4)?;
+        let len = read_u32_le(bytes, offset + 4)?;
+        let start = offset + 8;
+        let end = start.saturating_add(len as usize);
+        let chunk = &bytes[start.min(bytes.len())..end.min(bytes.len())];
+        // Chunks are padded to an even length.
+        offset = end.saturating_add(len as usize % 2);
+        Some((id, chunk))
+    })
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_f32_le(bytes: &[u8], offset: usize) -> Option<f32> {
+    read_u32_le(bytes, offset).map(f32::from_bits)
+}
+
+fn read_synchsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, byte| (value << 7) | (byte & 0x7F) as u32)
+}
+
+fn latin1_to_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|byte| **byte != 0)
+        .map(|byte| *byte as char)
+        .collect()
+}
+
+fn decode_id3_text(frame: &[u8]) -> Option<String> {
+    let (encoding, text) = frame.split_first()?;
+    let text = match encoding {
+        0 => latin1_to_string(text),
+        1 | 2 => {
+            let (big_endian, text) = match text {
+                [0xFE, 0xFF, rest @ ..] => (true, rest),
+                [0xFF, 0xFE, rest @ ..] => (false, rest),
+                _ => (*encoding == 2, text),
+            };
+            let units = text
+                .chunks_exact(2)
+                .map(|unit| {
+                    if big_endian {
+                        u16::from_be_bytes([unit[0], unit[1]])
+                    } else {
+                        u16::from_le_bytes([unit[0], unit[1]])
+                    }
+                })
+                .take_while(|unit| *unit != 0)
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        3 => String::from_utf8_lossy(text.split(|byte| *byte == 0).next()?).into_owned(),
+        _ => return None,
+    };
+    Some(text)
+}
+
+fn is_mpeg_frame(bytes: &[u8]) -> bool {
+    matches!(bytes, [0xFF, second, ..] if second & 0xE0 == 0xE0)
+}
+
+/// Estimates the length of an MP3 file from the bitrate of its first frame,
+/// which is exact for constant bitrate files.
+fn estimate_mp3_duration(bytes: &[u8], audio_start: usize, file_len: u64) -> Option<Duration> {
+    const MPEG1_BITRATES: [u32; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const MPEG2_BITRATES: [u32; 15] =
+        [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+    const LAYER_3: u8 = 0b01;
+    const MPEG_1: u8 = 0b11;
+
+    let frame_start = audio_start
+        + bytes
+            .get(audio_start..)?
+            .windows(2)
+            .position(is_mpeg_frame)?;
+    let header = bytes.get(frame_start..frame_start + 4)?;
+    let version = (header[1] >> 3) & 0b11;
+    let layer = (header[1] >> 1) & 0b11;
+    if layer != LAYER_3 {
+        return None;
+    }
+    let bitrates = if version == MPEG_1 {
+        &MPEG1_BITRATES
+    } else {
+        &MPEG2_BITRATES
+    };
+    let kilobits_per_second = *bitrates.get((header[2] >> 4) as usize)?;
+    if kilobits_per_second == 0 {
+        return None;
+    }
+    let audio_len = file_len.checked_sub(frame_start as u64)?;
+    Some(Duration::from_secs_f64(
+        audio_len as f64 * 8.0 / (kilobits_per_second as f64 * 1000.0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_from_file_name() {
+        let metadata = SampleMetadata::from_path(Path::new("Drums/Kick_Punchy_01.wav"));
+        assert_eq!(metadata.bpm, None);
+        assert_eq!(metadata.key, None);
+        assert_eq!(metadata.tags, ["kick", "punchy"]);
+
+        let metadata = SampleMetadata::from_path(Path::new("DL_Pad Warm 120bpm Am.wav"));
+        assert_eq!(metadata.bpm, Some(120.0));
+        assert_eq!(metadata.key.as_deref(), Some("Am"));
+        assert_eq!(metadata.tags, ["dl", "pad", "warm"]);
+
+        let metadata = SampleMetadata::from_path(Path::new("bass loop - Bb minor - 92 BPM.flac"));
+        assert_eq!(metadata.bpm, Some(92.0));
+        assert_eq!(metadata.key.as_deref(), Some("A#m"));
+        assert_eq!(metadata.tags, ["bass", "loop"]);
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("C").as_deref(), Some("C"));
+        assert_eq!(parse_key("F#m").as_deref(), Some("F#m"));
+        assert_eq!(parse_key("Ebmaj").as_deref(), Some("D#"));
+        assert_eq!(parse_key("Cb").as_deref(), Some("B"));
+        assert_eq!(parse_key("G sharp minor").as_deref(), Some("G#m"));
+        assert_eq!(parse_key("am").as_deref(), Some("Am"));
+        assert_eq!(parse_key("a"), None);
+        assert_eq!(parse_key("bass"), None);
+    }
+
+    #[test]
+    fn test_read_wav_chunks() {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&2u16.to_le_bytes());
+        fmt.extend_from_slice(&44_100u32.to_le_bytes());
+        fmt.extend_from_slice(&(44_100u32 * 4).to_le_bytes());
+        fmt.extend_from_slice(&4u16.to_le_bytes());
+        fmt.extend_from_slice(&16u16.to_le_bytes());
+
+        let mut acid = Vec::new();
+        acid.extend_from_slice(&0x02u32.to_le_bytes());
+        acid.extend_from_slice(&62u16.to_le_bytes());
+        acid.extend_from_slice(&[0; 14]);
+        acid.extend_from_slice(&128.0f32.to_le_bytes());
+
+        let mut info = b"INFO".to_vec();
+        info.extend_from_slice(b"IGNR");
+        info.extend_from_slice(&6u32.to_le_bytes());
+        info.extend_from_slice(b"House\0");
+
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        for (id, chunk) in [(b"fmt ", &fmt), (b"acid", &acid), (b"LIST", &info)] {
+            wav.extend_from_slice(id);
+            wav.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            wav.extend_from_slice(chunk);
+        }
+        // A two second data chunk, cut off by the end of the header.
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(44_100u32 * 4 * 2).to_le_bytes());
+        wav.extend_from_slice(&[0; 64]);
+
+        let metadata = SampleMetadata::read(Path::new("Chord Stab.wav"), &wav, 0);
+        assert_eq!(metadata.duration, Some(Duration::from_secs(2)));
+        assert_eq!(metadata.bpm, Some(128.0));
+        assert_eq!(metadata.key.as_deref(), Some("D"));
+        assert_eq!(metadata.tags, ["chord", "stab", "house"]);
+    }
+
+    #[test]
+    fn test_read_id3_tag() {
+        fn text_frame(id: &[u8; 4], text: &str) -> Vec<u8> {
+            let mut frame = id.to_vec();
+            frame.extend_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+            frame.extend_from_slice(&[0, 0, 3]);
+            frame.extend_from_slice(text.as_bytes());
+            frame
+        }
+
+        let frames = [
+            text_frame(b"TBPM", "95"),
+            text_frame(b"TKEY", "Ebm"),
+            text_frame(b"TLEN", "4500"),
+            text_frame(b"TCON", "Hip-Hop"),
+        ]
+        .concat();
+        let mut mp3 = b"ID3\x03\0\0".to_vec();
+        let len = frames.len() as u32;
+        mp3.extend_from_slice(&[
+            (len >> 21) as u8 & 0x7F,
+            (len >> 14) as u8 & 0x7F,
+            (len >> 7) as u8 & 0x7F,
+            len as u8 & 0x7F,
+        ]);
+        mp3.extend_from_slice(&frames);
+
+        let metadata = SampleMetadata::read(Path::new("vox chop 90bpm.mp3"), &mp3, 100_000);
+        assert_eq!(metadata.bpm, Some(95.0));
+        assert_eq!(metadata.key.as_deref(), Some("D#m"));
+        assert_eq!(metadata.duration, Some(Duration::from_millis(4500)));
+        assert_eq!(metadata.tags, ["vox", "chop", "hip", "hop"]);
+    }
+
+    #[test]
+    fn test_estimate_mp3_duration() {
+        // An MPEG-1 layer III frame header at 128kbps.
+        let mut mp3 = vec![0xFF, 0xFB, 0x90, 0x64];
+        mp3.extend_from_slice(&[0; 32]);
+        let metadata = SampleMetadata::read(Path::new("riser.mp3"), &mp3, 160_000);
+        assert_eq!(metadata.duration, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_bpm_matches() {
+        assert!(bpm_matches(120.0, 120.0));
+        assert!(bpm_matches(121.0, 120.0));
+        assert!(bpm_matches(60.0, 120.0));
+        assert!(bpm_matches(174.0, 87.0));
+        assert!(!bpm_matches(128.0, 120.0));
+    }
+}
@@ -20,6 +20,15 @@ pub enum CliRequest {
         env: Option<HashMap<String, String>>,
         user_data_dir: Option<String>,
     },
+    /// Runs a prompt with the native agent in a project without opening it in
+    /// a window, reporting each event as a line of JSON on stdout.
+    RunAgent {
+        path: String,
+        prompt: String,
+        profile: Option<String>,
+        model: Option<String>,
+        env: Option<HashMap<String, String>>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
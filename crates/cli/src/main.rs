@@ -8,7 +8,7 @@
 )]
 
 use anyhow::{Context as _, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use cli::{CliRequest, CliResponse, IpcHandshake, ipc::IpcOneShotServer};
 use parking_lot::Mutex;
 use std::{
@@ -54,7 +54,9 @@ Examples:
     `zed path-to-your-project`
           Open your project in Zed
     `zed -n path-to-file `
-          Open file/folder in a new window",
+          Open file/folder in a new window
+    `zed agent \"fix the failing tests\" --profile write`
+          Run a prompt with the agent in the current directory",
     after_help = "To read from stdin, append '-', e.g. 'ps axf | zed -'"
 )]
 struct Args {
@@ -77,6 +79,7 @@ struct Args {
     /// The paths to open in Zed (space-separated).
     ///
     /// Use `path:line:column` syntax to open a file at the given line and column.
+    /// Use `./agent` to open a path named `agent`.
     paths_with_position: Vec<String>,
     /// Print Zed's version and the app path.
     #[arg(short, long)]
@@ -121,6 +124,35 @@ struct Args {
     /// by having Zed act like netcat communicating over a Unix socket.
     #[arg(long, hide = true)]
     askpass: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run a prompt with the agent in a project, without opening it in a window.
+    ///
+    /// Each event of the run is printed to stdout as a line of JSON. Tool calls that
+    /// would ask for permission are rejected, so only the tools allowed by
+    /// `agent.always_allow_tool_actions` or the tool permission settings can run.
+    ///
+    /// Exits with status 0 if the agent finished its turn, and 1 otherwise.
+    Agent {
+        /// The prompt to run. Use `-` to read it from stdin.
+        prompt: String,
+        /// The project directory to run in. Defaults to the current directory.
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+        /// The agent profile to use, such as `write` or `ask`.
+        /// Defaults to `agent.default_profile`.
+        #[arg(long)]
+        profile: Option<String>,
+        /// The model to use, such as `anthropic/claude-sonnet-4-latest`.
+        /// Defaults to `agent.default_model`.
+        #[arg(long, value_name = "PROVIDER/MODEL")]
+        model: Option<String>,
+    },
 }
 
 fn parse_path_with_position(argument_str: &str) -> anyhow::Result<String> {
@@ -307,6 +339,35 @@ fn main() -> Result<()> {
         }
     };
 
+    let agent_request = match args.command {
+        Some(Command::Agent {
+            prompt,
+            project,
+            profile,
+            model,
+        }) => {
+            let prompt = if prompt == "-" {
+                io::read_to_string(io::stdin()).context("reading the prompt from stdin")?
+            } else {
+                prompt
+            };
+            let project = match project {
+                Some(project) => project,
+                None => env::current_dir().context("retrieving current directory")?,
+            };
+            let path = fs::canonicalize(&project)
+                .with_context(|| format!("opening project {}", project.display()))?;
+            Some(CliRequest::RunAgent {
+                path: path.to_string_lossy().into_owned(),
+                prompt,
+                profile,
+                model,
+                env: env.clone(),
+            })
+        }
+        None => None,
+    };
+
     let exit_status = Arc::new(Mutex::new(None));
     let mut paths = vec![];
     let mut urls = vec![];
@@ -365,7 +426,7 @@ fn main() -> Result<()> {
                 #[cfg(not(target_os = "windows"))]
                 let wsl = None;
 
-                tx.send(CliRequest::Open {
+                tx.send(agent_request.unwrap_or(CliRequest::Open {
                     paths,
                     urls,
                     diff_paths,
@@ -374,7 +435,7 @@ fn main() -> Result<()> {
                     open_new_workspace,
                     env,
                     user_data_dir: user_data_dir_for_thread,
-                })?;
+                }))?;
 
                 while let Ok(response) = rx.recv() {
                     match response {
@@ -795,6 +795,10 @@ impl Database {
                             is_last_update: true,
                             merge_message: db_repository.merge_message,
                             stash_entries: Vec::new(),
+                            rebasing: false,
+                            lfs_paths: Vec::new(),
+                            submodules: Vec::new(),
+                            worktrees: Vec::new(),
                         });
                     }
                 }
@@ -455,8 +455,35 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::GetRemotes>)
             .add_request_handler(forward_read_only_project_request::<proto::GitShow>)
             .add_request_handler(forward_read_only_project_request::<proto::LoadCommitDiff>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitLog>)
             .add_request_handler(forward_read_only_project_request::<proto::GitReset>)
             .add_request_handler(forward_read_only_project_request::<proto::GitCheckoutFiles>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRebase>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRebaseCommand>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitCherryPick>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRevert>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitGetTags>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitCreateTag>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitDeleteTag>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectStart>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectMark>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectReset>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitGetBisectState>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitSubmoduleCommand>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitGetSubmoduleRange>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitDiffRefs>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitLoadStagedDiff>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitMergeBase>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitLoadMergeStages>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitCreateWorktree>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRemoveWorktree>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitPruneWorktrees>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitDeleteBranch>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitMergeSquash>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitLfsLocks>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitLfsLockPath>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitLfsUnlockPath>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitLfsMetadata>)
             .add_request_handler(forward_mutating_project_request::<proto::SetIndexText>)
             .add_request_handler(forward_mutating_project_request::<proto::ToggleBreakpoint>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BreakpointsForFile>)
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::{Arc, LazyLock};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use futures::FutureExt as _;
 use gpui::{App, AsyncApp};
 use release_channel::ReleaseChannel;
@@ -83,6 +83,28 @@ impl dyn CredentialsProvider {
     }
 }
 
+/// Loads an API key from `env_var` when it's set, and otherwise from the
+/// credentials stored for `api_url`, the same way as the `openai_compatible`
+/// language model provider.
+pub async fn load_api_key(
+    api_url: &str,
+    env_var: Option<&str>,
+    cx: &AsyncApp,
+) -> Result<Option<Arc<str>>> {
+    if let Some(env_var) = env_var {
+        let key = std::env::var(env_var)
+            .with_context(|| format!("environment variable {env_var} is not set"))?;
+        return Ok(Some(key.into()));
+    }
+
+    let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx))?;
+    let Some((_, key)) = credentials_provider.read_credentials(api_url, cx).await? else {
+        return Ok(None);
+    };
+    let key = String::from_utf8(key).context("invalid API key in keychain")?;
+    Ok(Some(key.into()))
+}
+
 /// A credentials provider that stores credentials in the system keychain.
 struct KeychainCredentialsProvider;
 
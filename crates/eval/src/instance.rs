@@ -938,6 +938,9 @@ impl RequestMarkdown {
                     MessageContent::Image(_) => {
                         messages.push_str("[IMAGE DATA]\n\n");
                     }
+                    MessageContent::Audio(_) => {
+                        messages.push_str("[AUDIO DATA]\n\n");
+                    }
                     MessageContent::Thinking { text, signature } => {
                         messages.push_str("**Thinking**:\n\n");
                         if let Some(sig) = signature {
@@ -1,22 +1,30 @@
-use crate::{FakeFs, FakeFsEntry, Fs};
+use crate::{FakeFs, FakeFsEntry, Fs, RemoveOptions};
 use anyhow::{Context as _, Result, bail};
 use collections::{HashMap, HashSet};
 use futures::future::{self, BoxFuture, join_all};
 use git::{
     Oid,
     blame::Blame,
+    lfs::{LfsDiffMetadata, LfsLock},
     repository::{
-        AskPassDelegate, Branch, CommitDetails, CommitOptions, FetchOptions, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RepoPath, ResetMode,
+        AskPassDelegate, BisectMark, BisectState, Branch, CommitDetails, CommitOptions,
+        CommitSummary, FetchOptions, GitRepository, GitRepositoryCheckpoint, LogOptions,
+        MergeStages, PushOptions, RebaseCommand, RebaseTodoEntry, Remote, RepoPath, ResetMode, Tag,
     },
     status::{FileStatus, GitStatus, StatusCode, TrackedStatus, UnmergedStatus},
+    submodule::{Submodule, SubmoduleCommand, SubmoduleRange},
+    worktree::GitWorktree,
 };
 use gpui::{AsyncApp, BackgroundExecutor, SharedString, Task};
 use ignore::gitignore::GitignoreBuilder;
 use parking_lot::Mutex;
 use rope::Rope;
 use smol::future::FutureExt as _;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use util::{paths::PathStyle, rel_path::RelPath};
 
 #[derive(Clone)]
@@ -40,6 +48,12 @@ pub struct FakeGitRepositoryState {
     pub branches: HashSet<String>,
     pub simulated_index_write_error_message: Option<String>,
     pub refs: HashMap<String, String>,
+    /// The linked worktrees, each of which is a fake repository of its own
+    /// at `<path>/.git`.
+    pub worktrees: Vec<GitWorktree>,
+    /// The message and contents of each commit that was made, oldest first.
+    pub commits: Vec<(String, HashMap<RepoPath, String>)>,
+    pub merge_stages: HashMap<RepoPath, MergeStages>,
 }
 
 impl FakeGitRepositoryState {
@@ -54,11 +68,21 @@ impl FakeGitRepositoryState {
             branches: Default::default(),
             simulated_index_write_error_message: Default::default(),
             refs: HashMap::from_iter([("HEAD".into(), "abc".into())]),
+            worktrees: Default::default(),
+            commits: Default::default(),
+            merge_stages: Default::default(),
         }
     }
 }
 
 impl FakeGitRepository {
+    /// Where git keeps the administrative files of a linked worktree.
+    fn worktree_admin_dir(&self, path: &Path) -> PathBuf {
+        self.common_dir_path
+            .join("worktrees")
+            .join(path.file_name().unwrap_or_default())
+    }
+
     fn with_state_async<F, T>(&self, write: bool, f: F) -> BoxFuture<'static, Result<T>>
     where
         F: 'static + Send + FnOnce(&mut FakeGitRepositoryState) -> Result<T>,
@@ -93,6 +117,12 @@ impl GitRepository for FakeGitRepository {
         .boxed()
     }
 
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<'_, Result<MergeStages>> {
+        self.with_state_async(false, move |state| {
+            Ok(state.merge_stages.get(&path).cloned().unwrap_or_default())
+        })
+    }
+
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
         async {
             self.with_state_async(false, move |state| {
@@ -116,6 +146,45 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn diff_refs(
+        &self,
+        _base: String,
+        _head: String,
+        _cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<git::repository::CommitDiff>> {
+        unimplemented!()
+    }
+
+    fn load_staged_diff(
+        &self,
+        _cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<git::repository::CommitDiff>> {
+        self.with_state_async(false, |state| {
+            let paths = state
+                .head_contents
+                .keys()
+                .chain(state.index_contents.keys())
+                .collect::<BTreeSet<_>>();
+            let files = paths
+                .into_iter()
+                .filter_map(|path| {
+                    let old_text = state.head_contents.get(path).cloned();
+                    let new_text = state.index_contents.get(path).cloned();
+                    (old_text != new_text).then(|| git::repository::CommitFile {
+                        path: path.clone(),
+                        old_text,
+                        new_text,
+                    })
+                })
+                .collect();
+            Ok(git::repository::CommitDiff { files })
+        })
+    }
+
+    fn merge_base(&self, _first: String, _second: String) -> BoxFuture<'_, Result<Option<String>>> {
+        unimplemented!()
+    }
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -187,6 +256,10 @@ impl GitRepository for FakeGitRepository {
         async move { None }.boxed()
     }
 
+    fn rebase_in_progress(&self) -> BoxFuture<'_, bool> {
+        async move { false }.boxed()
+    }
+
     fn status(&self, path_prefixes: &[RepoPath]) -> Task<Result<GitStatus>> {
         let workdir_path = self.dot_git_path.parent().unwrap();
 
@@ -314,6 +387,7 @@ impl GitRepository for FakeGitRepository {
             entries.sort_by(|a, b| a.0.cmp(&b.0));
             anyhow::Ok(GitStatus {
                 entries: entries.into(),
+                lfs_paths: Arc::new([]),
             })
         });
         Task::ready(match result {
@@ -369,6 +443,346 @@ impl GitRepository for FakeGitRepository {
         })
     }
 
+    fn delete_branch(&self, name: String, _force: bool) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            if !state.branches.remove(&name) {
+                bail!("no such branch: {name}");
+            }
+            Ok(())
+        })
+    }
+
+    fn merge_squash(
+        &self,
+        branch: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        // The fake has no history, so a branch can only be merged from the
+        // worktree it's checked out in, and its changes are the difference
+        // between that worktree's HEAD and ours.
+        async move {
+            let worktree_path = self
+                .with_state_async(false, move |state| {
+                    state
+                        .worktrees
+                        .iter()
+                        .find(|worktree| worktree.branch.as_deref() == Some(branch.as_str()))
+                        .map(|worktree| worktree.path.clone())
+                        .with_context(|| format!("{branch} is not checked out in a worktree"))
+                })
+                .await?;
+            let branch_contents =
+                self.fs
+                    .with_git_state(&worktree_path.join(".git"), false, |state| {
+                        state.head_contents.clone()
+                    })?;
+            let head_contents = self
+                .with_state_async(false, |state| Ok(state.head_contents.clone()))
+                .await?;
+
+            let workdir_path = self.dot_git_path.parent().unwrap();
+            for (path, content) in &branch_contents {
+                if head_contents.get(path) != Some(content) {
+                    self.fs
+                        .write(&workdir_path.join(path.as_std_path()), content.as_bytes())
+                        .await?;
+                }
+            }
+            for path in head_contents.keys() {
+                if !branch_contents.contains_key(path) {
+                    self.fs
+                        .remove_file(&workdir_path.join(path.as_std_path()), Default::default())
+                        .await?;
+                }
+            }
+            self.with_state_async(true, move |state| {
+                state.index_contents = branch_contents;
+                Ok(())
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn create_worktree(
+        &self,
+        branch: String,
+        path: PathBuf,
+        _base: Option<String>,
+    ) -> BoxFuture<'_, Result<()>> {
+        async move {
+            if self.fs.is_dir(&path).await {
+                bail!("'{}' already exists", path.display());
+            }
+            let head_contents = self
+                .with_state_async(true, {
+                    let branch = branch.clone();
+                    let path = path.clone();
+                    move |state| {
+                        if !state.branches.insert(branch.clone()) {
+                            bail!("a branch named '{branch}' already exists");
+                        }
+                        state.worktrees.push(GitWorktree {
+                            path,
+                            sha: state.refs.get("HEAD").cloned().map(Into::into),
+                            branch: Some(branch.into()),
+                            is_main: false,
+                            locked: false,
+                            prunable: false,
+                        });
+                        Ok(state.head_contents.clone())
+                    }
+                })
+                .await?;
+
+            for (repo_path, content) in &head_contents {
+                self.fs
+                    .write(&path.join(repo_path.as_std_path()), content.as_bytes())
+                    .await?;
+            }
+            let admin_dir = self.worktree_admin_dir(&path);
+            self.fs.create_dir(&admin_dir).await?;
+            self.fs
+                .write(
+                    &admin_dir.join("HEAD"),
+                    format!("ref: refs/heads/{branch}\n").as_bytes(),
+                )
+                .await?;
+            let dot_git_path = path.join(".git");
+            self.fs.create_dir(&dot_git_path).await?;
+            self.fs.with_git_state(&dot_git_path, true, |state| {
+                state.index_contents = head_contents.clone();
+                state.head_contents = head_contents;
+                state.branches.insert(branch.clone());
+                state.current_branch_name = Some(branch);
+            })
+        }
+        .boxed()
+    }
+
+    fn remove_worktree(&self, path: PathBuf, force: bool) -> BoxFuture<'_, Result<()>> {
+        async move {
+            let dot_git_path = path.join(".git");
+            if !force {
+                let mut working_contents = HashMap::default();
+                for file in self.fs.files() {
+                    let Ok(repo_path) = file.strip_prefix(&path) else {
+                        continue;
+                    };
+                    if repo_path.starts_with(".git") {
+                        continue;
+                    }
+                    let repo_path = RelPath::new(repo_path, PathStyle::local())?;
+                    working_contents.insert(RepoPath::from(repo_path), self.fs.load(&file).await?);
+                }
+                let is_clean = self.fs.with_git_state(&dot_git_path, false, |state| {
+                    state.index_contents == state.head_contents
+                        && state.index_contents == working_contents
+                })?;
+                if !is_clean {
+                    bail!(
+                        "'{}' contains modified or untracked files, use --force to delete it",
+                        path.display()
+                    );
+                }
+            }
+
+            self.with_state_async(true, {
+                let path = path.clone();
+                move |state| {
+                    let ix = state
+                        .worktrees
+                        .iter()
+                        .position(|worktree| worktree.path == path)
+                        .with_context(|| format!("'{}' is not a working tree", path.display()))?;
+                    state.worktrees.remove(ix);
+                    Ok(())
+                }
+            })
+            .await?;
+            for dir in [self.worktree_admin_dir(&path), path] {
+                self.fs
+                    .remove_dir(
+                        &dir,
+                        RemoveOptions {
+                            recursive: true,
+                            ignore_if_not_exists: true,
+                        },
+                    )
+                    .await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>> {
+        let workdir_path = self.dot_git_path.parent().unwrap().to_path_buf();
+        async move {
+            let mut worktrees = self
+                .with_state_async(false, move |state| {
+                    let main = GitWorktree {
+                        path: workdir_path,
+                        sha: state.refs.get("HEAD").cloned().map(Into::into),
+                        branch: state.current_branch_name.clone().map(Into::into),
+                        is_main: true,
+                        locked: false,
+                        prunable: false,
+                    };
+                    Ok(std::iter::once(main)
+                        .chain(state.worktrees.iter().cloned())
+                        .collect::<Vec<_>>())
+                })
+                .await?;
+            for worktree in worktrees.iter_mut().skip(1) {
+                worktree.prunable = !self.fs.is_dir(&worktree.path).await;
+            }
+            Ok(worktrees)
+        }
+        .boxed()
+    }
+
+    fn prune_worktrees(&self) -> BoxFuture<'_, Result<()>> {
+        async move {
+            let mut missing = Vec::new();
+            for worktree in self.worktrees().await?.into_iter().skip(1) {
+                if worktree.prunable {
+                    self.fs
+                        .remove_dir(
+                            &self.worktree_admin_dir(&worktree.path),
+                            RemoveOptions {
+                                recursive: true,
+                                ignore_if_not_exists: true,
+                            },
+                        )
+                        .await?;
+                    missing.push(worktree.path);
+                }
+            }
+            self.with_state_async(true, move |state| {
+                state
+                    .worktrees
+                    .retain(|worktree| !missing.contains(&worktree.path));
+                Ok(())
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn rebase_interactive(
+        &self,
+        _base: String,
+        _todo: Vec<RebaseTodoEntry>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn rebase_command(
+        &self,
+        _command: RebaseCommand,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn cherry_pick(
+        &self,
+        _commit: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn revert(
+        &self,
+        _commit: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn tags(&self) -> BoxFuture<'_, Result<Vec<Tag>>> {
+        async { Ok(Vec::new()) }.boxed()
+    }
+
+    fn create_tag(
+        &self,
+        _name: String,
+        _target: Option<String>,
+        _message: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn delete_tag(&self, _name: String) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn bisect_start(
+        &self,
+        _bad: String,
+        _good: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn bisect_mark(
+        &self,
+        _mark: BisectMark,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn bisect_reset(&self, _env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn bisect_state(&self) -> BoxFuture<'_, Result<Option<BisectState>>> {
+        async { Ok(None) }.boxed()
+    }
+
+    fn submodules(&self) -> BoxFuture<'_, Result<Vec<Submodule>>> {
+        async { Ok(Vec::new()) }.boxed()
+    }
+
+    fn submodule_command(
+        &self,
+        _command: SubmoduleCommand,
+        _paths: Vec<RepoPath>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn submodule_range(&self, _path: RepoPath) -> BoxFuture<'_, Result<SubmoduleRange>> {
+        unimplemented!()
+    }
+
+    fn lfs_locks(&self) -> BoxFuture<'_, Result<Vec<LfsLock>>> {
+        async { Ok(Vec::new()) }.boxed()
+    }
+
+    fn lfs_lock(&self, _path: RepoPath) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn lfs_unlock(&self, _path: RepoPath, _force: bool) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn lfs_metadata(&self, _path: RepoPath) -> BoxFuture<'_, Result<LfsDiffMetadata>> {
+        unimplemented!()
+    }
+
+    fn log(&self, _options: LogOptions) -> BoxFuture<'_, Result<Vec<CommitSummary>>> {
+        unimplemented!()
+    }
+
     fn blame(&self, path: RepoPath, _content: Rope) -> BoxFuture<'_, Result<git::blame::Blame>> {
         self.with_state_async(false, move |state| {
             state
@@ -461,12 +875,18 @@ impl GitRepository for FakeGitRepository {
 
     fn commit(
         &self,
-        _message: gpui::SharedString,
+        message: gpui::SharedString,
         _name_and_email: Option<(gpui::SharedString, gpui::SharedString)>,
         _options: CommitOptions,
         _env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        unimplemented!()
+        self.with_state_async(true, move |state| {
+            state.head_contents = state.index_contents.clone();
+            state
+                .commits
+                .push((message.to_string(), state.index_contents.clone()));
+            Ok(())
+        })
     }
 
     fn push(
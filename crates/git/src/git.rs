@@ -1,10 +1,13 @@
 pub mod blame;
 pub mod commit;
 mod hosting_provider;
+pub mod lfs;
 mod remote;
 pub mod repository;
 pub mod stash;
 pub mod status;
+pub mod submodule;
+pub mod worktree;
 
 pub use crate::hosting_provider::*;
 pub use crate::remote::*;
@@ -88,6 +91,9 @@ actions!(
         ExpandCommitEditor,
         /// Generates a commit message using AI.
         GenerateCommitMessage,
+        /// Uses AI to propose splitting the staged changes into several
+        /// commits, to be reviewed before committing them.
+        SplitIntoCommits,
         /// Initializes a new git repository.
         Init,
         /// Opens all modified files in the editor.
@@ -96,6 +102,49 @@ actions!(
         Clone,
         /// Adds a file to .gitignore.
         AddToGitignore,
+        /// Locks the selected Git LFS file so that others can't push changes to it.
+        LockFile,
+        /// Releases your Git LFS lock on the selected file.
+        UnlockFile,
+        /// Opens the commit history of the repository.
+        ViewHistory,
+        /// Opens the commit history of the current file.
+        ViewFileHistory,
+        /// Opens the commit history of the selected lines in the current file.
+        ViewLineHistory,
+        /// Opens an editor to reorder, squash, reword or drop recent commits.
+        InteractiveRebase,
+        /// Continues the rebase in progress after resolving conflicts.
+        RebaseContinue,
+        /// Skips the commit the rebase in progress stopped at.
+        RebaseSkip,
+        /// Aborts the rebase in progress, restoring the branch as it was.
+        RebaseAbort,
+        /// Applies the changes of the open commit on top of the current branch.
+        CherryPick,
+        /// Creates a commit that undoes the changes of the open commit.
+        RevertCommit,
+        /// Creates a tag pointing at the open commit.
+        CreateTag,
+        /// Opens the bisect assistant, to find the commit that introduced a regression.
+        Bisect,
+        /// Compares two revisions, such as the current branch and its upstream,
+        /// to review their differences like a pull request.
+        ReviewBranch,
+        /// Opens the conflicted file in a three-way merge editor, showing the
+        /// base, our and their versions alongside the result.
+        OpenMergeEditor,
+        /// Copies the URLs of the submodules into the repository's config.
+        SubmoduleInit,
+        /// Clones missing submodules and checks out the commits recorded for them.
+        SubmoduleUpdate,
+        /// Updates the submodules' remote URLs after they changed in `.gitmodules`.
+        SubmoduleSync,
+        /// Creates a linked worktree with a new branch, in a directory chosen
+        /// from a prompt.
+        AddWorktree,
+        /// Cleans up the worktrees whose directories were deleted.
+        PruneWorktrees,
     ]
 );
 
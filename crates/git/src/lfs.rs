@@ -0,0 +1,432 @@
+use crate::repository::RepoPath;
+use anyhow::{Context as _, Result};
+use gpui::SharedString;
+use serde::Deserialize;
+use std::fmt::{self, Write as _};
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+use util::ResultExt as _;
+use util::size::format_file_size;
+
+pub use audio_metadata::{AudioFormat, AudioInfo};
+
+/// The first line of every Git LFS pointer file.
+pub const LFS_POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+
+/// Pointer files are tiny, so anything larger than this is real content.
+pub const MAX_LFS_POINTER_SIZE: u64 = 1024;
+
+/// The contents of a Git LFS pointer file, which is what Git stores in place
+/// of an LFS-tracked file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LfsPointer {
+    /// The SHA-256 of the object, in hex.
+    pub oid: String,
+    pub size: u64,
+}
+
+impl LfsPointer {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != LFS_POINTER_VERSION {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "oid" => oid = Some(value.strip_prefix("sha256:")?.to_string()),
+                "size" => size = Some(value.parse().ok()?),
+                _ => {}
+            }
+        }
+        let oid =
+            oid.filter(|oid| oid.len() == 64 && oid.bytes().all(|b| b.is_ascii_hexdigit()))?;
+        Some(Self { oid, size: size? })
+    }
+
+    /// Where Git LFS keeps the object once it has been downloaded, given the
+    /// repository's common `.git` directory.
+    pub fn object_path(&self, common_dir: &Path) -> PathBuf {
+        common_dir
+            .join(crate::LFS_DIR)
+            .join("objects")
+            .join(&self.oid[0..2])
+            .join(&self.oid[2..4])
+            .join(&self.oid)
+    }
+}
+
+/// Parses the output of `git check-attr -z filter`, returning the paths
+/// whose `filter` attribute is `lfs`.
+pub fn parse_lfs_check_attr(output: &str) -> Vec<RepoPath> {
+    let mut fields = output.split('\0');
+    let mut paths = Vec::new();
+    while let (Some(path), Some(_attribute), Some(value)) =
+        (fields.next(), fields.next(), fields.next())
+    {
+        if value == "lfs"
+            && let Some(path) = RepoPath::new(path).log_err()
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort_unstable();
+    paths
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LfsLock {
+    pub id: SharedString,
+    pub path: RepoPath,
+    pub owner: SharedString,
+    /// Whether the lock is held by the current user.
+    pub ours: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LfsLocksJson {
+    Verified {
+        #[serde(default)]
+        ours: Vec<LfsLockJson>,
+        #[serde(default)]
+        theirs: Vec<LfsLockJson>,
+    },
+    // Servers that don't support verifying locks only list them.
+    Unverified(Vec<LfsLockJson>),
+}
+
+#[derive(Deserialize)]
+struct LfsLockJson {
+    id: String,
+    path: String,
+    #[serde(default)]
+    owner: Option<LfsLockOwnerJson>,
+}
+
+#[derive(Deserialize)]
+struct LfsLockOwnerJson {
+    name: String,
+}
+
+/// Parses the output of `git lfs locks --json`, with or without `--verify`.
+pub fn parse_lfs_locks(output: &str) -> Result<Vec<LfsLock>> {
+    let locks: LfsLocksJson =
+        serde_json::from_str(output).context("invalid `git lfs locks` output")?;
+    let locks = match locks {
+        LfsLocksJson::Verified { ours, theirs } => [(ours, true), (theirs, false)],
+        LfsLocksJson::Unverified(locks) => [(locks, false), (Vec::new(), false)],
+    };
+    let mut result = Vec::new();
+    for (locks, ours) in locks {
+        for lock in locks {
+            result.push(LfsLock {
+                id: lock.id.into(),
+                path: RepoPath::new(&lock.path)?,
+                owner: lock
+                    .owner
+                    .map(|owner| owner.name)
+                    .unwrap_or_default()
+                    .into(),
+                ours,
+            });
+        }
+    }
+    result.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    Ok(result)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LfsTransferDirection {
+    Download,
+    Upload,
+    Checkout,
+}
+
+/// One line of the progress that Git LFS writes to the file named by
+/// `GIT_LFS_PROGRESS`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LfsTransferProgress {
+    pub direction: LfsTransferDirection,
+    /// The 1-based index of the file being transferred.
+    pub current: usize,
+    pub total: usize,
+    pub bytes_transferred: u64,
+    pub bytes_total: u64,
+    pub name: String,
+}
+
+impl LfsTransferProgress {
+    /// Parses a line like `download 1/3 1024/4096 stems/drums.wav`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.splitn(4, ' ');
+        let direction = match parts.next()? {
+            "download" => LfsTransferDirection::Download,
+            "upload" => LfsTransferDirection::Upload,
+            "checkout" => LfsTransferDirection::Checkout,
+            _ => return None,
+        };
+        let (current, total) = parts.next()?.split_once('/')?;
+        let (bytes_transferred, bytes_total) = parts.next()?.split_once('/')?;
+        Some(Self {
+            direction,
+            current: current.parse().ok()?,
+            total: total.parse().ok()?,
+            bytes_transferred: bytes_transferred.parse().ok()?,
+            bytes_total: bytes_total.parse().ok()?,
+            name: parts.next()?.to_string(),
+        })
+    }
+
+    /// Returns the most recent progress in the contents of a progress file.
+    pub fn last_in(contents: &str) -> Option<Self> {
+        contents.lines().rev().find_map(Self::parse)
+    }
+}
+
+impl fmt::Display for LfsTransferProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = match self.direction {
+            LfsTransferDirection::Download => "Downloading",
+            LfsTransferDirection::Upload => "Uploading",
+            LfsTransferDirection::Checkout => "Checking out",
+        };
+        write!(
+            f,
+            "{verb} LFS objects ({}/{}): {} {} / {}",
+            self.current,
+            self.total,
+            self.name,
+            format_file_size(self.bytes_transferred, true),
+            format_file_size(self.bytes_total, true),
+        )
+    }
+}
+
+fn read_header(path: &Path) -> Result<Vec<u8>> {
+    let mut header = Vec::new();
+    std::fs::File::open(path)?
+        .take(AudioInfo::HEADER_LEN as u64)
+        .read_to_end(&mut header)?;
+    Ok(header)
+}
+
+/// What is known about one side of an LFS-tracked file without reading all
+/// of its contents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LfsFileMetadata {
+    /// The LFS object id, when the file is known by its pointer.
+    pub oid: Option<String>,
+    pub size: u64,
+    pub audio: Option<AudioInfo>,
+}
+
+impl LfsFileMetadata {
+    fn from_pointer(pointer: LfsPointer, common_dir: &Path) -> Self {
+        let audio = read_header(&pointer.object_path(common_dir))
+            .ok()
+            .and_then(|header| AudioInfo::parse(&header));
+        Self {
+            oid: Some(pointer.oid),
+            size: pointer.size,
+            audio,
+        }
+    }
+
+    /// Describes a file from its contents in a Git tree, which is normally an
+    /// LFS pointer. The audio details are only known if the object has been
+    /// downloaded.
+    pub fn from_blob(content: &[u8], common_dir: &Path) -> Self {
+        if let Some(pointer) = str::from_utf8(content).ok().and_then(LfsPointer::parse) {
+            return Self::from_pointer(pointer, common_dir);
+        }
+        Self {
+            oid: None,
+            size: content.len() as u64,
+            audio: AudioInfo::parse(content),
+        }
+    }
+
+    /// Describes a file in the working tree, which is still a pointer if Git
+    /// LFS hasn't checked it out.
+    pub fn from_file(abs_path: &Path, common_dir: &Path) -> Result<Self> {
+        let size = std::fs::metadata(abs_path)?.len();
+        let header = read_header(abs_path)?;
+        if size <= MAX_LFS_POINTER_SIZE
+            && let Some(pointer) = str::from_utf8(&header).ok().and_then(LfsPointer::parse)
+        {
+            return Ok(Self::from_pointer(pointer, common_dir));
+        }
+        Ok(Self {
+            oid: None,
+            size,
+            audio: AudioInfo::parse(&header),
+        })
+    }
+
+    /// Renders the metadata as lines of text, so that the two sides of a
+    /// change can be compared with a regular text diff.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if let Some(oid) = &self.oid {
+            writeln!(text, "LFS object: {oid}").ok();
+        }
+        writeln!(
+            text,
+            "Size: {} ({} bytes)",
+            format_file_size(self.size, true),
+            self.size
+        )
+        .ok();
+        if let Some(audio) = &self.audio {
+            let format = match audio.format {
+                AudioFormat::Wav => "WAV",
+                AudioFormat::Aiff => "AIFF",
+                AudioFormat::Flac => "FLAC",
+            };
+            let millis = audio.duration.as_millis();
+            writeln!(text, "Format: {format}").ok();
+            writeln!(
+                text,
+                "Duration: {}:{:02}.{:03}",
+                millis / 60_000,
+                millis / 1000 % 60,
+                millis % 1000
+            )
+            .ok();
+            writeln!(text, "Sample rate: {} Hz", audio.sample_rate).ok();
+            writeln!(text, "Bit depth: {}", audio.bits_per_sample).ok();
+            writeln!(text, "Channels: {}", audio.channels).ok();
+        }
+        text
+    }
+}
+
+/// The metadata of an LFS-tracked file in HEAD and in the working tree,
+/// either of which is missing when the file was added or deleted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LfsDiffMetadata {
+    pub head: Option<LfsFileMetadata>,
+    pub worktree: Option<LfsFileMetadata>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::repo_path;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    const OID: &str = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+
+    #[test]
+    fn test_parse_pointer() {
+        let pointer = LfsPointer::parse(&format!(
+            "{LFS_POINTER_VERSION}\noid sha256:{OID}\nsize 12345\n"
+        ))
+        .unwrap();
+        assert_eq!(
+            pointer,
+            LfsPointer {
+                oid: OID.into(),
+                size: 12345
+            }
+        );
+        assert_eq!(
+            pointer.object_path(Path::new("/repo/.git")),
+            Path::new("/repo/.git/lfs/objects/4d/7a").join(OID)
+        );
+
+        assert_eq!(LfsPointer::parse("just some text\n"), None);
+        assert_eq!(
+            LfsPointer::parse(&format!("{LFS_POINTER_VERSION}\noid sha256:abc\nsize 1\n")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_check_attr() {
+        let output = "b.wav\0filter\0lfs\0a.txt\0filter\0unspecified\0a.als\0filter\0lfs\0";
+        assert_eq!(
+            parse_lfs_check_attr(output),
+            [repo_path("a.als"), repo_path("b.wav")]
+        );
+    }
+
+    #[test]
+    fn test_parse_locks() {
+        let locks = parse_lfs_locks(
+            r#"{
+                "ours": [{"id": "2", "path": "stems/bass.wav", "owner": {"name": "me"}, "locked_at": "2024-01-01T00:00:00Z"}],
+                "theirs": [{"id": "1", "path": "drums.als", "owner": {"name": "sam"}, "locked_at": "2024-01-01T00:00:00Z"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            locks
+                .iter()
+                .map(|lock| (lock.path.as_unix_str(), lock.owner.as_ref(), lock.ours))
+                .collect::<Vec<_>>(),
+            [("drums.als", "sam", false), ("stems/bass.wav", "me", true)]
+        );
+
+        let locks = parse_lfs_locks(
+            r#"[{"id": "1", "path": "drums.als", "owner": {"name": "sam"}, "locked_at": "2024-01-01T00:00:00Z"}]"#,
+        )
+        .unwrap();
+        assert_eq!(locks.len(), 1);
+        assert!(!locks[0].ours);
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        let contents =
+            "download 1/2 512/1024 a.wav\ndownload 2/2 1048576/4194304 stems/my drums.wav\n";
+        let progress = LfsTransferProgress::last_in(contents).unwrap();
+        assert_eq!(
+            progress,
+            LfsTransferProgress {
+                direction: LfsTransferDirection::Download,
+                current: 2,
+                total: 2,
+                bytes_transferred: 1048576,
+                bytes_total: 4194304,
+                name: "stems/my drums.wav".into(),
+            }
+        );
+        assert_eq!(
+            progress.to_string(),
+            "Downloading LFS objects (2/2): stems/my drums.wav 1.0MB / 4.2MB"
+        );
+        assert_eq!(LfsTransferProgress::last_in(""), None);
+    }
+
+    #[test]
+    fn test_metadata_text() {
+        let metadata = LfsFileMetadata {
+            oid: Some(OID.into()),
+            size: 176444,
+            audio: Some(AudioInfo {
+                format: AudioFormat::Wav,
+                channels: 2,
+                sample_rate: 44100,
+                bits_per_sample: 16,
+                duration: Duration::from_millis(61_250),
+            }),
+        };
+        assert_eq!(
+            metadata.to_text(),
+            format!(
+                "LFS object: {OID}\n\
+                 Size: 176.4KB (176444 bytes)\n\
+                 Format: WAV\n\
+                 Duration: 1:01.250\n\
+                 Sample rate: 44100 Hz\n\
+                 Bit depth: 16\n\
+                 Channels: 2\n"
+            )
+        );
+    }
+}
@@ -1,6 +1,14 @@
 use crate::commit::parse_git_diff_name_status;
+use crate::lfs::{
+    LfsDiffMetadata, LfsFileMetadata, LfsLock, LfsPointer, parse_lfs_check_attr, parse_lfs_locks,
+};
 use crate::stash::GitStash;
 use crate::status::{GitStatus, StatusCode};
+use crate::submodule::{
+    Submodule, SubmoduleCommand, SubmoduleRange, SubmoduleStatus, parse_dirty_submodules,
+    parse_submodule_status,
+};
+use crate::worktree::{GitWorktree, parse_worktree_list};
 use crate::{Oid, SHORT_SHA_LENGTH};
 use anyhow::{Context as _, Result, anyhow, bail};
 use collections::HashMap;
@@ -16,10 +24,12 @@ use serde::Deserialize;
 use smol::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use std::borrow::Cow;
 use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
 use std::process::{ExitStatus, Stdio};
 use std::{
     cmp::Ordering,
     future,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -28,7 +38,7 @@ use thiserror::Error;
 use util::command::new_smol_command;
 use util::paths::PathStyle;
 use util::rel_path::RelPath;
-use util::{ResultExt, paths};
+use util::{ResultExt, maybe, paths};
 use uuid::Uuid;
 
 pub use askpass::{AskPassDelegate, AskPassResult, AskPassSession};
@@ -176,12 +186,40 @@ pub struct CommitFile {
     pub new_text: Option<String>,
 }
 
+/// The versions of a conflicted file that are being merged, as recorded in
+/// the index. A version is `None` if the file doesn't exist on that side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeStages {
+    /// The version in the common ancestor.
+    pub base: Option<String>,
+    /// The version on the current branch.
+    pub ours: Option<String>,
+    /// The version being merged in.
+    pub theirs: Option<String>,
+}
+
 impl CommitDetails {
     pub fn short_sha(&self) -> SharedString {
         self.sha[..SHORT_SHA_LENGTH].to_string().into()
     }
 }
 
+/// Which commits to list in [`GitRepository::log`], newest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// The revision whose history is listed, or HEAD when unset.
+    pub branch: Option<String>,
+    /// Only lists commits whose author matches this pattern.
+    pub author: Option<String>,
+    /// Only lists commits that changed this path, following renames of a file.
+    pub path: Option<RepoPath>,
+    /// Only lists commits that changed these lines of `path`, numbered from 1.
+    pub line_range: Option<RangeInclusive<u32>>,
+    /// How many matching commits to skip, to page through long histories.
+    pub skip: usize,
+    pub limit: usize,
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Remote {
     pub name: SharedString,
@@ -196,6 +234,136 @@ pub enum ResetMode {
     Mixed,
 }
 
+/// What an interactive rebase does with a commit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RebaseAction {
+    #[default]
+    Pick,
+    /// Keeps the commit, replacing its message.
+    Reword,
+    /// Melds the commit into the previous one, joining their messages.
+    Squash,
+    /// Melds the commit into the previous one, discarding its message.
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [Self; 5] = [
+        Self::Pick,
+        Self::Reword,
+        Self::Squash,
+        Self::Fixup,
+        Self::Drop,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pick => "Pick",
+            Self::Reword => "Reword",
+            Self::Squash => "Squash",
+            Self::Fixup => "Fixup",
+            Self::Drop => "Drop",
+        }
+    }
+
+    /// Whether the commit is melded into the one before it.
+    pub fn melds(&self) -> bool {
+        matches!(self, Self::Squash | Self::Fixup)
+    }
+}
+
+/// One line of an interactive rebase's todo list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub action: RebaseAction,
+    pub sha: SharedString,
+    /// The new message of a commit that is reworded.
+    pub message: Option<String>,
+}
+
+/// How to proceed with a rebase that stopped, usually because of conflicts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseCommand {
+    Continue,
+    Skip,
+    Abort,
+}
+
+impl RebaseCommand {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            Self::Continue => "--continue",
+            Self::Skip => "--skip",
+            Self::Abort => "--abort",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tag {
+    pub name: SharedString,
+    /// The SHA of the tagged commit.
+    pub sha: SharedString,
+    /// The subject of the tagged commit.
+    pub subject: SharedString,
+    /// The message of an annotated tag, or `None` for a lightweight one.
+    pub message: Option<SharedString>,
+    /// When an annotated tag was created, or when the commit of a lightweight
+    /// one was made.
+    pub timestamp: i64,
+}
+
+impl Tag {
+    pub fn is_annotated(&self) -> bool {
+        self.message.is_some()
+    }
+}
+
+/// How the commit being tested during a bisect is marked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BisectMark {
+    /// The commit doesn't have the regression.
+    Good,
+    /// The commit has the regression.
+    Bad,
+    /// The commit can't be tested, for example because it doesn't build.
+    Skip,
+}
+
+impl BisectMark {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            Self::Good => "good",
+            Self::Bad => "bad",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+/// A `git bisect` in progress.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BisectState {
+    /// The newest commit known to have the regression.
+    pub bad: Option<SharedString>,
+    /// The commits known not to have the regression.
+    pub good: Vec<SharedString>,
+    /// The commits marked as untestable.
+    pub skipped: Vec<SharedString>,
+    /// The commits that may have introduced the regression, newest first.
+    pub remaining: Vec<CommitSummary>,
+}
+
+impl BisectState {
+    /// The commit that introduced the regression, once it's the only one left.
+    pub fn culprit(&self) -> Option<&CommitSummary> {
+        match self.remaining.as_slice() {
+            [culprit] => Some(culprit),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum FetchOptions {
     All,
@@ -351,6 +519,12 @@ pub trait GitRepository: Send + Sync {
     /// Also returns `None` for symlinks.
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>>;
 
+    /// Returns the base, ours and theirs versions of a conflicted file.
+    ///
+    /// Fails if any of them isn't valid UTF-8, rather than treating that
+    /// version as missing.
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<'_, Result<MergeStages>>;
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -378,6 +552,9 @@ pub trait GitRepository: Send + Sync {
 
     fn merge_message(&self) -> BoxFuture<'_, Option<String>>;
 
+    /// Whether an interactive rebase or `git am` session is in progress.
+    fn rebase_in_progress(&self) -> BoxFuture<'_, bool>;
+
     fn status(&self, path_prefixes: &[RepoPath]) -> Task<Result<GitStatus>>;
 
     fn stash_entries(&self) -> BoxFuture<'_, Result<GitStash>>;
@@ -387,6 +564,137 @@ pub trait GitRepository: Send + Sync {
     fn change_branch(&self, name: String) -> BoxFuture<'_, Result<()>>;
     fn create_branch(&self, name: String) -> BoxFuture<'_, Result<()>>;
     fn rename_branch(&self, branch: String, new_name: String) -> BoxFuture<'_, Result<()>>;
+    fn delete_branch(&self, name: String, force: bool) -> BoxFuture<'_, Result<()>>;
+
+    /// Applies the changes of `branch` to the working tree and index as a
+    /// single uncommitted change, like `git merge --squash`.
+    fn merge_squash(
+        &self,
+        branch: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Creates a linked worktree at `path`, checking out a new branch named
+    /// `branch` that starts at `base`, or at HEAD if no base is given.
+    fn create_worktree(
+        &self,
+        branch: String,
+        path: PathBuf,
+        base: Option<String>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Removes the linked worktree at `path`. Unless `force` is set, this
+    /// fails if the worktree has uncommitted changes.
+    fn remove_worktree(&self, path: PathBuf, force: bool) -> BoxFuture<'_, Result<()>>;
+
+    /// Lists the repository's worktrees, starting with the main one.
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>>;
+
+    /// Removes the administrative files of linked worktrees whose
+    /// directories no longer exist.
+    fn prune_worktrees(&self) -> BoxFuture<'_, Result<()>>;
+
+    /// Rewrites the commits after `base` by running `git rebase -i` with the
+    /// given todo list, oldest commit first. If the rebase stops because of
+    /// conflicts, this returns an error and the rebase stays in progress.
+    fn rebase_interactive(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Continues, skips the current commit of, or aborts a rebase in progress.
+    fn rebase_command(
+        &self,
+        command: RebaseCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Applies the changes introduced by `commit` on top of HEAD as a new
+    /// commit. If they conflict, this returns an error and the cherry-pick
+    /// stays in progress until the resolved changes are committed.
+    fn cherry_pick(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Creates a commit that undoes the changes introduced by `commit`.
+    /// Conflicts are handled as they are by `cherry_pick`.
+    fn revert(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Lists the repository's tags, most recently created first.
+    fn tags(&self) -> BoxFuture<'_, Result<Vec<Tag>>>;
+
+    /// Creates a tag pointing at `target`, or at HEAD if no target is given.
+    /// The tag is annotated if it has a message, and lightweight otherwise.
+    fn create_tag(
+        &self,
+        name: String,
+        target: Option<String>,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    fn delete_tag(&self, name: String) -> BoxFuture<'_, Result<()>>;
+
+    /// Starts bisecting the commits between `good` and `bad`, checking out the
+    /// one halfway between them.
+    fn bisect_start(
+        &self,
+        bad: String,
+        good: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Marks the checked out commit, after which the next one to test is
+    /// checked out.
+    fn bisect_mark(
+        &self,
+        mark: BisectMark,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Ends the bisect, checking out the branch it was started from.
+    fn bisect_reset(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>>;
+
+    /// Returns the bisect in progress, if there is one.
+    fn bisect_state(&self) -> BoxFuture<'_, Result<Option<BisectState>>>;
+
+    /// Lists the repository's submodules, sorted by path.
+    fn submodules(&self) -> BoxFuture<'_, Result<Vec<Submodule>>>;
+
+    /// Runs a `git submodule` subcommand on the given submodules, or on all
+    /// of them if no paths are given.
+    fn submodule_command(
+        &self,
+        command: SubmoduleCommand,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Compares the commit checked out in a submodule with the one recorded
+    /// for it in HEAD.
+    fn submodule_range(&self, path: RepoPath) -> BoxFuture<'_, Result<SubmoduleRange>>;
+
+    /// Lists the Git LFS locks held on the current remote.
+    fn lfs_locks(&self) -> BoxFuture<'_, Result<Vec<LfsLock>>>;
+
+    /// Locks an LFS-tracked file on the current remote, so that only the
+    /// current user can push changes to it.
+    fn lfs_lock(&self, path: RepoPath) -> BoxFuture<'_, Result<()>>;
+
+    /// Releases a lock on an LFS-tracked file. Unless `force` is set, this
+    /// fails for locks held by other users.
+    fn lfs_unlock(&self, path: RepoPath, force: bool) -> BoxFuture<'_, Result<()>>;
+
+    /// Describes an LFS-tracked file as it is in HEAD and in the working tree.
+    fn lfs_metadata(&self, path: RepoPath) -> BoxFuture<'_, Result<LfsDiffMetadata>>;
 
     fn reset(
         &self,
@@ -405,8 +713,28 @@ pub trait GitRepository: Send + Sync {
     fn show(&self, commit: String) -> BoxFuture<'_, Result<CommitDetails>>;
 
     fn load_commit(&self, commit: String, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>>;
+
+    /// Loads the files that differ between two revisions, with their
+    /// contents in each.
+    fn diff_refs(
+        &self,
+        base: String,
+        head: String,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<CommitDiff>>;
+
+    /// Loads the files whose staged version differs from HEAD, with their
+    /// contents in HEAD and in the index.
+    fn load_staged_diff(&self, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>>;
+
+    /// Returns the best common ancestor of two revisions, or `None` if their
+    /// histories are unrelated.
+    fn merge_base(&self, first: String, second: String) -> BoxFuture<'_, Result<Option<String>>>;
+
     fn blame(&self, path: RepoPath, content: Rope) -> BoxFuture<'_, Result<crate::blame::Blame>>;
 
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<CommitSummary>>>;
+
     /// Returns the absolute path to the repository. For worktrees, this will be the path to the
     /// worktree's gitdir within the main repository (typically `.git/worktrees/<name>`).
     fn path(&self) -> PathBuf;
@@ -681,6 +1009,57 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<CommitSummary>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let mut command = new_smol_command(git_binary_path);
+                command.current_dir(&working_directory).args([
+                    "--no-optional-locks",
+                    "log",
+                    "--format=%H%x00%s%x00%at%x00%an%x00%P",
+                    &format!("--skip={}", options.skip),
+                    &format!("--max-count={}", options.limit),
+                ]);
+                if let Some(author) = &options.author {
+                    command.arg(format!("--author={author}"));
+                }
+                match (&options.path, &options.line_range) {
+                    (Some(path), Some(line_range)) => {
+                        // Line history can't be combined with a pathspec, and
+                        // `-s` drops the patches it would otherwise print.
+                        command.arg("-s").arg(format!(
+                            "-L{},{}:{}",
+                            line_range.start(),
+                            line_range.end(),
+                            path.as_unix_str()
+                        ));
+                    }
+                    (Some(_), None) => {
+                        command.arg("--follow");
+                    }
+                    (None, _) => {}
+                }
+                let branch = options.branch.as_deref().unwrap_or("HEAD");
+                validate_revision(branch)?;
+                command.arg("--end-of-options").arg(branch);
+                if let (Some(path), None) = (&options.path, &options.line_range) {
+                    command.arg("--").arg(path.as_unix_str());
+                }
+
+                let output = command.output().await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to list commits:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                parse_log_output(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
     fn load_commit(&self, commit: String, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>> {
         let Some(working_directory) = self.repository.lock().workdir().map(ToOwned::to_owned)
         else {
@@ -711,96 +1090,144 @@ impl GitRepository for RealGitRepository {
             let parent_sha = lines.next().unwrap().trim().trim_end_matches('\0');
             let changes = parse_git_diff_name_status(lines.next().unwrap_or(""));
 
-            let mut cat_file_process = util::command::new_smol_command(&git_binary_path)
+            let files = load_changed_files(
+                &git_binary_path,
+                &working_directory,
+                parent_sha,
+                &commit,
+                changes,
+            )
+            .await?;
+            Ok(CommitDiff { files })
+        })
+        .boxed()
+    }
+
+    fn diff_refs(
+        &self,
+        base: String,
+        head: String,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<CommitDiff>> {
+        let Some(working_directory) = self.repository.lock().workdir().map(ToOwned::to_owned)
+        else {
+            return future::ready(Err(anyhow!("no working directory"))).boxed();
+        };
+        let git_binary_path = self.any_git_binary_path.clone();
+        cx.background_spawn(async move {
+            validate_revision(&base)?;
+            validate_revision(&head)?;
+            let diff_output = util::command::new_smol_command(&git_binary_path)
                 .current_dir(&working_directory)
-                .args(["--no-optional-locks", "cat-file", "--batch=%(objectsize)"])
-                .stdin(Stdio::piped())
+                .args([
+                    "--no-optional-locks",
+                    "diff",
+                    "-z",
+                    "--no-renames",
+                    "--name-status",
+                    "--end-of-options",
+                ])
+                .arg(&base)
+                .arg(&head)
+                .arg("--")
+                .stdin(Stdio::null())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
-                .spawn()
-                .context("starting git cat-file process")?;
-
-            let mut files = Vec::<CommitFile>::new();
-            let mut stdin = BufWriter::with_capacity(512, cat_file_process.stdin.take().unwrap());
-            let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
-            let mut info_line = String::new();
-            let mut newline = [b'\0'];
-            for (path, status_code) in changes {
-                // git-show outputs `/`-delimited paths even on Windows.
-                let Some(rel_path) = RelPath::unix(path).log_err() else {
-                    continue;
-                };
-
-                match status_code {
-                    StatusCode::Modified => {
-                        stdin.write_all(commit.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                        stdin.write_all(parent_sha.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                    }
-                    StatusCode::Added => {
-                        stdin.write_all(commit.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                    }
-                    StatusCode::Deleted => {
-                        stdin.write_all(parent_sha.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                    }
-                    _ => continue,
-                }
-                stdin.flush().await?;
-
-                info_line.clear();
-                stdout.read_line(&mut info_line).await?;
+                .output()
+                .await
+                .context("starting git diff process")?;
+            anyhow::ensure!(
+                diff_output.status.success(),
+                "Failed to compare {base} with {head}:\n{}",
+                String::from_utf8_lossy(&diff_output.stderr)
+            );
 
-                let len = info_line.trim_end().parse().with_context(|| {
-                    format!("invalid object size output from cat-file {info_line}")
-                })?;
-                let mut text = vec![0; len];
-                stdout.read_exact(&mut text).await?;
-                stdout.read_exact(&mut newline).await?;
-                let text = String::from_utf8_lossy(&text).to_string();
-
-                let mut old_text = None;
-                let mut new_text = None;
-                match status_code {
-                    StatusCode::Modified => {
-                        info_line.clear();
-                        stdout.read_line(&mut info_line).await?;
-                        let len = info_line.trim_end().parse().with_context(|| {
-                            format!("invalid object size output from cat-file {}", info_line)
-                        })?;
-                        let mut parent_text = vec![0; len];
-                        stdout.read_exact(&mut parent_text).await?;
-                        stdout.read_exact(&mut newline).await?;
-                        old_text = Some(String::from_utf8_lossy(&parent_text).to_string());
-                        new_text = Some(text);
-                    }
-                    StatusCode::Added => new_text = Some(text),
-                    StatusCode::Deleted => old_text = Some(text),
-                    _ => continue,
-                }
+            let diff_stdout = String::from_utf8_lossy(&diff_output.stdout);
+            let changes = parse_git_diff_name_status(&diff_stdout);
+            let files =
+                load_changed_files(&git_binary_path, &working_directory, &base, &head, changes)
+                    .await?;
+            Ok(CommitDiff { files })
+        })
+        .boxed()
+    }
 
-                files.push(CommitFile {
-                    path: rel_path.into(),
-                    old_text,
-                    new_text,
-                })
-            }
+    fn load_staged_diff(&self, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>> {
+        let Some(working_directory) = self.repository.lock().workdir().map(ToOwned::to_owned)
+        else {
+            return future::ready(Err(anyhow!("no working directory"))).boxed();
+        };
+        let git_binary_path = self.any_git_binary_path.clone();
+        cx.background_spawn(async move {
+            let diff_output = util::command::new_smol_command(&git_binary_path)
+                .current_dir(&working_directory)
+                .args([
+                    "--no-optional-locks",
+                    "diff",
+                    "--cached",
+                    "-z",
+                    "--no-renames",
+                    "--name-status",
+                    "--",
+                ])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .output()
+                .await
+                .context("starting git diff process")?;
+            anyhow::ensure!(
+                diff_output.status.success(),
+                "Failed to list the staged changes:\n{}",
+                String::from_utf8_lossy(&diff_output.stderr)
+            );
 
+            let diff_stdout = String::from_utf8_lossy(&diff_output.stdout);
+            let changes = parse_git_diff_name_status(&diff_stdout);
+            // An empty revision makes cat-file read `:<path>`, the staged
+            // version of the file.
+            let files =
+                load_changed_files(&git_binary_path, &working_directory, "HEAD", "", changes)
+                    .await?;
             Ok(CommitDiff { files })
         })
         .boxed()
     }
 
+    fn merge_base(&self, first: String, second: String) -> BoxFuture<'_, Result<Option<String>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+
+        self.executor
+            .spawn(async move {
+                validate_revision(&first)?;
+                validate_revision(&second)?;
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "merge-base",
+                        "--end-of-options",
+                        &first,
+                        &second,
+                    ])
+                    .output()
+                    .await?;
+                // git-merge-base exits with 1 when there is no common ancestor.
+                match output.status.code() {
+                    Some(0) => Ok(Some(
+                        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+                    )),
+                    Some(1) if output.stderr.is_empty() => Ok(None),
+                    _ => Err(anyhow!(
+                        "Failed to find the merge base of {first} and {second}:\n{}",
+                        String::from_utf8_lossy(&output.stderr)
+                    )),
+                }
+            })
+            .boxed()
+    }
+
     fn reset(
         &self,
         commit: String,
@@ -892,6 +1319,32 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<'_, Result<MergeStages>> {
+        let repo = self.repository.clone();
+        self.executor
+            .spawn(async move {
+                let repo = repo.lock();
+                let mut index = repo.index()?;
+                index.read(false)?;
+                let text_for_stage = |stage: i32| -> Result<Option<String>> {
+                    let Some(entry) = index.get_path(path.as_std_path(), stage) else {
+                        return Ok(None);
+                    };
+                    let content = repo.find_blob(entry.id)?.content().to_owned();
+                    let text = String::from_utf8(content).with_context(|| {
+                        format!("stage {stage} of {} is not valid UTF-8", path.as_unix_str())
+                    })?;
+                    Ok(Some(text))
+                };
+                Ok(MergeStages {
+                    base: text_for_stage(1)?,
+                    ours: text_for_stage(2)?,
+                    theirs: text_for_stage(3)?,
+                })
+            })
+            .boxed()
+    }
+
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
         let repo = self.repository.clone();
         self.executor
@@ -1036,6 +1489,15 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn rebase_in_progress(&self) -> BoxFuture<'_, bool> {
+        let path = self.path();
+        self.executor
+            .spawn(async move {
+                path.join("rebase-merge").exists() || path.join("rebase-apply").exists()
+            })
+            .boxed()
+    }
+
     fn status(&self, path_prefixes: &[RepoPath]) -> Task<Result<GitStatus>> {
         let git_binary_path = self.any_git_binary_path.clone();
         let working_directory = match self.working_directory() {
@@ -1046,13 +1508,23 @@ impl GitRepository for RealGitRepository {
         log::debug!("Checking for git status in {path_prefixes:?}");
         self.executor.spawn(async move {
             let output = new_smol_command(&git_binary_path)
-                .current_dir(working_directory)
+                .current_dir(&working_directory)
                 .args(args)
                 .output()
                 .await?;
             if output.status.success() {
                 let stdout = String::from_utf8_lossy(&output.stdout);
-                stdout.parse()
+                let mut status: GitStatus = stdout.parse()?;
+                if !status.entries.is_empty() {
+                    let paths = status.entries.iter().map(|(path, _)| path);
+                    status.lfs_paths =
+                        lfs_tracked_paths(&git_binary_path, &working_directory, paths)
+                            .await
+                            .log_err()
+                            .unwrap_or_default()
+                            .into();
+                }
+                Ok(status)
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
                 anyhow::bail!("git status failed: {stderr}");
@@ -1214,1069 +1686,2970 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
-    fn blame(&self, path: RepoPath, content: Rope) -> BoxFuture<'_, Result<crate::blame::Blame>> {
-        let working_directory = self.working_directory();
+    fn delete_branch(&self, name: String, force: bool) -> BoxFuture<'_, Result<()>> {
         let git_binary_path = self.any_git_binary_path.clone();
-
-        let remote_url = self
-            .remote_url("upstream")
-            .or_else(|| self.remote_url("origin"));
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
 
         self.executor
             .spawn(async move {
-                crate::blame::Blame::for_path(
-                    &git_binary_path,
-                    &working_directory?,
-                    &path,
-                    &content,
-                    remote_url,
-                )
-                .await
+                validate_revision(&name)?;
+                let flag = if force { "-D" } else { "-d" };
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&["branch", flag, "--end-of-options", &name])
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn diff(&self, diff: DiffType) -> BoxFuture<'_, Result<String>> {
-        let working_directory = self.working_directory();
+    fn merge_squash(
+        &self,
+        branch: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let args = match diff {
-                    DiffType::HeadToIndex => Some("--staged"),
-                    DiffType::HeadToWorktree => None,
-                };
-
-                let output = new_smol_command(&git_binary_path)
-                    .current_dir(&working_directory?)
-                    .args(["diff"])
-                    .args(args)
-                    .output()
+                validate_revision(&branch)?;
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&["merge", "--squash", "--end-of-options", &branch])
                     .await?;
-
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to run git diff:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn stage_paths(
+    fn create_worktree(
         &self,
-        paths: Vec<RepoPath>,
-        env: Arc<HashMap<String, String>>,
+        branch: String,
+        path: PathBuf,
+        base: Option<String>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                if !paths.is_empty() {
-                    let output = new_smol_command(&git_binary_path)
-                        .current_dir(&working_directory?)
-                        .envs(env.iter())
-                        .args(["update-index", "--add", "--remove", "--"])
-                        .args(paths.iter().map(|p| p.as_unix_str()))
-                        .output()
-                        .await?;
-                    anyhow::ensure!(
-                        output.status.success(),
-                        "Failed to stage paths:\n{}",
-                        String::from_utf8_lossy(&output.stderr),
-                    );
+                validate_revision(&branch)?;
+                if let Some(base) = &base {
+                    validate_revision(base)?;
                 }
-                Ok(())
+                let mut args = vec![
+                    OsString::from("worktree"),
+                    "add".into(),
+                    "-b".into(),
+                    branch.into(),
+                    "--end-of-options".into(),
+                    path.into_os_string(),
+                ];
+                if let Some(base) = base {
+                    args.push(base.into());
+                }
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&args)
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn unstage_paths(
-        &self,
-        paths: Vec<RepoPath>,
-        env: Arc<HashMap<String, String>>,
-    ) -> BoxFuture<'_, Result<()>> {
+    fn remove_worktree(&self, path: PathBuf, force: bool) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
         let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let mut args = vec![OsString::from("worktree"), "remove".into()];
+                if force {
+                    args.push("--force".into());
+                }
+                args.push(path.into_os_string());
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&args)
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>> {
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
 
         self.executor
             .spawn(async move {
-                if !paths.is_empty() {
-                    let output = new_smol_command(&git_binary_path)
-                        .current_dir(&working_directory?)
-                        .envs(env.iter())
-                        .args(["reset", "--quiet", "--"])
-                        .args(paths.iter().map(|p| p.as_std_path()))
-                        .output()
-                        .await?;
+                let output = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&["worktree", "list", "--porcelain"])
+                    .await?;
+                parse_worktree_list(&output)
+            })
+            .boxed()
+    }
 
-                    anyhow::ensure!(
-                        output.status.success(),
-                        "Failed to unstage:\n{}",
-                        String::from_utf8_lossy(&output.stderr),
-                    );
-                }
-                Ok(())
+    fn prune_worktrees(&self) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&["worktree", "prune"])
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn stash_paths(
+    fn rebase_interactive(
         &self,
-        paths: Vec<RepoPath>,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let repository_dir = self.path();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let mut cmd = new_smol_command(&git_binary_path);
-                cmd.current_dir(&working_directory?)
-                    .envs(env.iter())
-                    .args(["stash", "push", "--quiet"])
-                    .arg("--include-untracked");
-
-                cmd.args(paths.iter().map(|p| p.as_unix_str()));
-
-                let output = cmd.output().await?;
-
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to stash:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
+                validate_revision(&base)?;
+                let todo_path = write_rebase_todo(&repository_dir, &git_binary_path, &todo).await?;
+                let mut envs = rebase_envs(&env);
+                envs.insert(
+                    "GIT_SEQUENCE_EDITOR".into(),
+                    format!("cp {}", shell_quote(&todo_path)),
                 );
-                Ok(())
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(envs)
+                    .run(&["rebase", "--interactive", "--end-of-options", &base])
+                    .await?;
+                remove_rebase_messages(&repository_dir).await;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn stash_pop(
+    fn rebase_command(
         &self,
-        index: Option<usize>,
+        command: RebaseCommand,
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let repository_dir = self.path();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let mut cmd = new_smol_command(git_binary_path);
-                let mut args = vec!["stash".to_string(), "pop".to_string()];
-                if let Some(index) = index {
-                    args.push(format!("stash@{{{}}}", index));
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(rebase_envs(&env))
+                    .run(&["rebase", command.as_arg()])
+                    .await?;
+                if smol::fs::metadata(repository_dir.join("rebase-merge"))
+                    .await
+                    .is_err()
+                {
+                    remove_rebase_messages(&repository_dir).await;
                 }
-                cmd.current_dir(&working_directory?)
-                    .envs(env.iter())
-                    .args(args);
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
 
-                let output = cmd.output().await?;
+    fn cherry_pick(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
 
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to stash pop:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                Ok(())
+        self.executor
+            .spawn(async move {
+                validate_revision(&commit)?;
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&["cherry-pick", "--end-of-options", &commit])
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn stash_apply(
+    fn revert(
         &self,
-        index: Option<usize>,
+        commit: String,
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let mut cmd = new_smol_command(git_binary_path);
-                let mut args = vec!["stash".to_string(), "apply".to_string()];
-                if let Some(index) = index {
-                    args.push(format!("stash@{{{}}}", index));
-                }
-                cmd.current_dir(&working_directory?)
-                    .envs(env.iter())
-                    .args(args);
+                validate_revision(&commit)?;
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&["revert", "--no-edit", "--end-of-options", &commit])
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
 
-                let output = cmd.output().await?;
+    fn tags(&self) -> BoxFuture<'_, Result<Vec<Tag>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
 
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to apply stash:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                Ok(())
+        self.executor
+            .spawn(async move {
+                let fields = [
+                    "%(refname:strip=2)",
+                    "%(objecttype)",
+                    "%(objectname)",
+                    "%(*objectname)",
+                    "%(*objecttype)",
+                    "%(creatordate:unix)",
+                    "%(contents:subject)",
+                    "%(*contents:subject)",
+                ]
+                .join("%00");
+                let output = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&[
+                        "for-each-ref",
+                        "refs/tags",
+                        "--sort=-creatordate",
+                        "--format",
+                        &fields,
+                    ])
+                    .await?;
+                parse_tag_input(&output)
             })
             .boxed()
     }
 
-    fn stash_drop(
+    fn create_tag(
         &self,
-        index: Option<usize>,
+        name: String,
+        target: Option<String>,
+        message: Option<String>,
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let mut cmd = new_smol_command(git_binary_path);
-                let mut args = vec!["stash".to_string(), "drop".to_string()];
-                if let Some(index) = index {
-                    args.push(format!("stash@{{{}}}", index));
+                let git = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env));
+                validate_tag_name(&git, &name).await?;
+                if let Some(target) = &target {
+                    validate_revision(target)?;
                 }
-                cmd.current_dir(&working_directory?)
-                    .envs(env.iter())
-                    .args(args);
+                let mut args = vec!["tag".to_string()];
+                if let Some(message) = message {
+                    args.extend(["--annotate".to_string(), "--message".to_string(), message]);
+                }
+                args.push("--end-of-options".to_string());
+                args.push(name);
+                args.extend(target);
+                git.run(&args).await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
 
-                let output = cmd.output().await?;
+    fn delete_tag(&self, name: String) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
 
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to stash drop:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                Ok(())
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor);
+                validate_tag_name(&git, &name).await?;
+                git.run(&["tag", "--delete", "--end-of-options", &name])
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn commit(
+    fn bisect_start(
         &self,
-        message: SharedString,
-        name_and_email: Option<(SharedString, SharedString)>,
-        options: CommitOptions,
+        bad: String,
+        good: String,
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let mut cmd = new_smol_command(git_binary_path);
-                cmd.current_dir(&working_directory?)
-                    .envs(env.iter())
-                    .args(["commit", "--quiet", "-m"])
-                    .arg(&message.to_string())
-                    .arg("--cleanup=strip");
-
-                if options.amend {
-                    cmd.arg("--amend");
-                }
-
-                if options.signoff {
-                    cmd.arg("--signoff");
-                }
-
-                if let Some((name, email)) = name_and_email {
-                    cmd.arg("--author").arg(&format!("{name} <{email}>"));
-                }
-
-                let output = cmd.output().await?;
-
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to commit:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                Ok(())
+                validate_revision(&bad)?;
+                validate_revision(&good)?;
+                // git-bisect takes the arguments before `--` as revisions and
+                // those after it as paths, and has no `--end-of-options`.
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&["bisect", "start", &bad, &good, "--"])
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn push(
+    fn bisect_mark(
         &self,
-        branch_name: String,
-        remote_name: String,
-        options: Option<PushOptions>,
-        ask_pass: AskPassDelegate,
+        mark: BisectMark,
         env: Arc<HashMap<String, String>>,
-        cx: AsyncApp,
-    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
         let working_directory = self.working_directory();
-        let executor = cx.background_executor().clone();
-        let git_binary_path = self.system_git_binary_path.clone();
-        async move {
-            let git_binary_path = git_binary_path.context("git not found on $PATH, can't push")?;
-            let working_directory = working_directory?;
-            let mut command = new_smol_command(git_binary_path);
-            command
-                .envs(env.iter())
-                .current_dir(&working_directory)
-                .args(["push"])
-                .args(options.map(|option| match option {
-                    PushOptions::SetUpstream => "--set-upstream",
-                    PushOptions::Force => "--force-with-lease",
-                }))
-                .arg(remote_name)
-                .arg(format!("{}:{}", branch_name, branch_name))
-                .stdin(smol::process::Stdio::null())
-                .stdout(smol::process::Stdio::piped())
-                .stderr(smol::process::Stdio::piped());
+        let executor = self.executor.clone();
 
-            run_git_command(env, ask_pass, command, &executor).await
-        }
-        .boxed()
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&["bisect", mark.as_arg()])
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
     }
 
-    fn pull(
-        &self,
-        branch_name: String,
-        remote_name: String,
-        ask_pass: AskPassDelegate,
-        env: Arc<HashMap<String, String>>,
-        cx: AsyncApp,
-    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+    fn bisect_reset(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
         let working_directory = self.working_directory();
-        let executor = cx.background_executor().clone();
-        let git_binary_path = self.system_git_binary_path.clone();
-        async move {
-            let git_binary_path = git_binary_path.context("git not found on $PATH, can't pull")?;
-            let mut command = new_smol_command(git_binary_path);
-            command
-                .envs(env.iter())
-                .current_dir(&working_directory?)
-                .args(["pull"])
-                .arg(remote_name)
-                .arg(branch_name)
-                .stdout(smol::process::Stdio::piped())
-                .stderr(smol::process::Stdio::piped());
+        let executor = self.executor.clone();
 
-            run_git_command(env, ask_pass, command, &executor).await
-        }
-        .boxed()
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&["bisect", "reset"])
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
     }
 
-    fn fetch(
-        &self,
-        fetch_options: FetchOptions,
-        ask_pass: AskPassDelegate,
-        env: Arc<HashMap<String, String>>,
-        cx: AsyncApp,
-    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+    fn bisect_state(&self) -> BoxFuture<'_, Result<Option<BisectState>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
         let working_directory = self.working_directory();
-        let remote_name = format!("{}", fetch_options);
-        let git_binary_path = self.system_git_binary_path.clone();
-        let executor = cx.background_executor().clone();
-        async move {
-            let git_binary_path = git_binary_path.context("git not found on $PATH, can't fetch")?;
-            let mut command = new_smol_command(git_binary_path);
-            command
-                .envs(env.iter())
-                .current_dir(&working_directory?)
-                .args(["fetch", &remote_name])
-                .stdout(smol::process::Stdio::piped())
-                .stderr(smol::process::Stdio::piped());
+        let repository_dir = self.path();
+        let executor = self.executor.clone();
 
-            run_git_command(env, ask_pass, command, &executor).await
-        }
-        .boxed()
+        self.executor
+            .spawn(async move {
+                if smol::fs::metadata(repository_dir.join("BISECT_START"))
+                    .await
+                    .is_err()
+                {
+                    return Ok(None);
+                }
+                let git = GitBinary::new(git_binary_path, working_directory?, executor);
+                let refs = git
+                    .run(&[
+                        "for-each-ref",
+                        "refs/bisect",
+                        "--format=%(refname:strip=2)%00%(objectname)",
+                    ])
+                    .await?;
+                let mut state = parse_bisect_refs(&refs);
+                if let Some(bad) = &state.bad
+                    && !state.good.is_empty()
+                {
+                    let mut args = vec![
+                        "--no-optional-locks".to_string(),
+                        "log".to_string(),
+                        "--format=%H%x00%s%x00%at%x00%an%x00%P".to_string(),
+                        bad.to_string(),
+                        "--not".to_string(),
+                    ];
+                    args.extend(state.good.iter().map(|sha| sha.to_string()));
+                    let output = git.run(&args).await?;
+                    state.remaining = parse_log_output(&output)?;
+                }
+                Ok(Some(state))
+            })
+            .boxed()
     }
 
-    fn get_remotes(&self, branch_name: Option<String>) -> BoxFuture<'_, Result<Vec<Remote>>> {
-        let working_directory = self.working_directory();
+    fn submodules(&self) -> BoxFuture<'_, Result<Vec<Submodule>>> {
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
                 let working_directory = working_directory?;
-                if let Some(branch_name) = branch_name {
-                    let output = new_smol_command(&git_binary_path)
-                        .current_dir(&working_directory)
-                        .args(["config", "--get"])
-                        .arg(format!("branch.{}.remote", branch_name))
-                        .output()
-                        .await?;
-
-                    if output.status.success() {
-                        let remote_name = String::from_utf8_lossy(&output.stdout);
-
-                        return Ok(vec![Remote {
-                            name: remote_name.trim().to_string().into(),
-                        }]);
-                    }
+                if smol::fs::metadata(working_directory.join(".gitmodules"))
+                    .await
+                    .is_err()
+                {
+                    return Ok(Vec::new());
+                }
+                let git = GitBinary::new(git_binary_path, working_directory, executor);
+                let output = git.run(&["submodule", "status"]).await?;
+                let mut submodules = parse_submodule_status(&output)?;
+                if submodules
+                    .iter()
+                    .all(|submodule| submodule.status == SubmoduleStatus::Uninitialized)
+                {
+                    return Ok(submodules);
                 }
 
-                let output = new_smol_command(&git_binary_path)
-                    .current_dir(&working_directory)
-                    .args(["remote"])
-                    .output()
-                    .await?;
-
-                anyhow::ensure!(
-                    output.status.success(),
-                    "Failed to get remotes:\n{}",
-                    String::from_utf8_lossy(&output.stderr)
+                let mut args = vec![
+                    "--no-optional-locks".to_string(),
+                    "status".to_string(),
+                    "--porcelain=v2".to_string(),
+                    "-z".to_string(),
+                    "--ignore-submodules=none".to_string(),
+                    "--".to_string(),
+                ];
+                args.extend(
+                    submodules
+                        .iter()
+                        .map(|submodule| submodule.path.as_unix_str().to_string()),
                 );
-                let remote_names = String::from_utf8_lossy(&output.stdout)
-                    .split('\n')
-                    .filter(|name| !name.is_empty())
-                    .map(|name| Remote {
-                        name: name.trim().to_string().into(),
-                    })
-                    .collect();
-                Ok(remote_names)
+                let output = git.run(&args).await?;
+                let dirty_paths = parse_dirty_submodules(&output);
+                for submodule in &mut submodules {
+                    submodule.dirty = dirty_paths.contains(&submodule.path);
+                }
+                Ok(submodules)
             })
             .boxed()
     }
 
-    fn check_for_pushed_commit(&self) -> BoxFuture<'_, Result<Vec<SharedString>>> {
+    fn submodule_command(
+        &self,
+        command: SubmoduleCommand,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
         let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let mut args = vec!["submodule".to_string()];
+                args.extend(command.as_args().iter().map(|arg| arg.to_string()));
+                args.push("--".to_string());
+                args.extend(paths.iter().map(|path| path.as_unix_str().to_string()));
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(HashMap::clone(&env))
+                    .run(&args)
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn submodule_range(&self, path: RepoPath) -> BoxFuture<'_, Result<SubmoduleRange>> {
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
                 let working_directory = working_directory?;
-                let git_cmd = async |args: &[&str]| -> Result<String> {
-                    let output = new_smol_command(&git_binary_path)
-                        .current_dir(&working_directory)
-                        .args(args)
-                        .output()
-                        .await?;
-                    anyhow::ensure!(
-                        output.status.success(),
-                        String::from_utf8_lossy(&output.stderr).to_string()
-                    );
-                    Ok(String::from_utf8(output.stdout)?)
-                };
-
-                let head = git_cmd(&["rev-parse", "HEAD"])
+                let git = GitBinary::new(
+                    git_binary_path.clone(),
+                    working_directory.clone(),
+                    executor.clone(),
+                );
+                let old = git
+                    .run(&[
+                        "rev-parse",
+                        "--verify",
+                        "--quiet",
+                        &format!("HEAD:{}", path.as_unix_str()),
+                    ])
                     .await
-                    .context("Failed to get HEAD")?
-                    .trim()
-                    .to_owned();
-
-                let mut remote_branches = vec![];
-                let mut add_if_matching = async |remote_head: &str| {
-                    if let Ok(merge_base) = git_cmd(&["merge-base", &head, remote_head]).await
-                        && merge_base.trim() == head
-                        && let Some(s) = remote_head.strip_prefix("refs/remotes/")
-                    {
-                        remote_branches.push(s.to_owned().into());
-                    }
-                };
+                    .ok()
+                    .map(SharedString::from);
 
-                // check the main branch of each remote
-                let remotes = git_cmd(&["remote"])
+                let submodule_directory = working_directory.join(path.as_std_path());
+                if smol::fs::metadata(submodule_directory.join(".git"))
                     .await
-                    .context("Failed to get remotes")?;
-                for remote in remotes.lines() {
-                    if let Ok(remote_head) =
-                        git_cmd(&["symbolic-ref", &format!("refs/remotes/{remote}/HEAD")]).await
-                    {
-                        add_if_matching(remote_head.trim()).await;
-                    }
+                    .is_err()
+                {
+                    return Ok(SubmoduleRange {
+                        old,
+                        ..Default::default()
+                    });
                 }
+                let submodule_git = GitBinary::new(git_binary_path, submodule_directory, executor);
+                let new = submodule_git
+                    .run(&["rev-parse", "HEAD"])
+                    .await
+                    .ok()
+                    .map(SharedString::from);
 
-                // ... and the remote branch that the checked-out one is tracking
-                if let Ok(remote_head) =
-                    git_cmd(&["rev-parse", "--symbolic-full-name", "@{u}"]).await
+                let mut range = SubmoduleRange {
+                    old,
+                    new,
+                    ..Default::default()
+                };
+                if let (Some(old), Some(new)) = (&range.old, &range.new)
+                    && old != new
                 {
-                    add_if_matching(remote_head.trim()).await;
+                    let log = async |range: String| {
+                        let output = submodule_git
+                            .run(&[
+                                "--no-optional-locks",
+                                "log",
+                                "--format=%H%x00%s%x00%at%x00%an%x00%P",
+                                &range,
+                            ])
+                            .await?;
+                        parse_log_output(&output)
+                    };
+                    // The old commit may not have been fetched into the
+                    // submodule, in which case only the range is known.
+                    range.added = log(format!("{old}..{new}")).await.unwrap_or_default();
+                    range.removed = log(format!("{new}..{old}")).await.unwrap_or_default();
                 }
-
-                Ok(remote_branches)
+                Ok(range)
             })
             .boxed()
     }
 
-    fn checkpoint(&self) -> BoxFuture<'static, Result<GitRepositoryCheckpoint>> {
-        let working_directory = self.working_directory();
+    fn lfs_locks(&self) -> BoxFuture<'_, Result<Vec<LfsLock>>> {
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
         let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
-                let working_directory = working_directory?;
-                let mut git = GitBinary::new(git_binary_path, working_directory.clone(), executor)
-                    .envs(checkpoint_author_envs());
-                git.with_temp_index(async |git| {
-                    let head_sha = git.run(&["rev-parse", "HEAD"]).await.ok();
-                    let mut excludes = exclude_files(git).await?;
-
-                    git.run(&["add", "--all"]).await?;
-                    let tree = git.run(&["write-tree"]).await?;
-                    let checkpoint_sha = if let Some(head_sha) = head_sha.as_deref() {
-                        git.run(&["commit-tree", &tree, "-p", head_sha, "-m", "Checkpoint"])
-                            .await?
-                    } else {
-                        git.run(&["commit-tree", &tree, "-m", "Checkpoint"]).await?
-                    };
+                let git = GitBinary::new(git_binary_path, working_directory?, executor);
+                let output = match git.run(&["lfs", "locks", "--verify", "--json"]).await {
+                    Ok(output) => output,
+                    Err(error) => {
+                        log::debug!("failed to verify LFS locks, listing them instead: {error:?}");
+                        git.run(&["lfs", "locks", "--json"]).await?
+                    }
+                };
+                parse_lfs_locks(&output)
+            })
+            .boxed()
+    }
 
-                    excludes.restore_original().await?;
+    fn lfs_lock(&self, path: RepoPath) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
 
-                    Ok(GitRepositoryCheckpoint {
-                        commit_sha: checkpoint_sha.parse()?,
-                    })
-                })
-                .await
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&["lfs", "lock", "--", path.as_unix_str()])
+                    .await?;
+                anyhow::Ok(())
             })
             .boxed()
     }
 
-    fn restore_checkpoint(&self, checkpoint: GitRepositoryCheckpoint) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
+    fn lfs_unlock(&self, path: RepoPath, force: bool) -> BoxFuture<'_, Result<()>> {
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let mut args = vec!["lfs", "unlock"];
+                if force {
+                    args.push("--force");
+                }
+                args.extend(["--", path.as_unix_str()]);
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&args)
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
 
+    fn lfs_metadata(&self, path: RepoPath) -> BoxFuture<'_, Result<LfsDiffMetadata>> {
+        let repo = self.repository.clone();
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
         let executor = self.executor.clone();
+
         self.executor
             .spawn(async move {
                 let working_directory = working_directory?;
+                let (head_content, common_dir) = {
+                    let repo = repo.lock();
+                    let head_content = maybe!({
+                        let head = repo.head().ok()?.peel_to_tree().ok()?;
+                        let entry = head.get_path(path.as_std_path()).ok()?;
+                        Some(repo.find_blob(entry.id()).ok()?.content().to_owned())
+                    });
+                    (head_content, repo.commondir().to_path_buf())
+                };
+                let head =
+                    head_content.map(|content| LfsFileMetadata::from_blob(&content, &common_dir));
+
+                let abs_path = working_directory.join(path.as_std_path());
+                let worktree = if smol::fs::metadata(&abs_path).await.is_ok() {
+                    let mut metadata = LfsFileMetadata::from_file(&abs_path, &common_dir)?;
+                    // Git LFS computes the object id of a checked out file
+                    // the same way it would when staging it.
+                    if metadata.oid.is_none() {
+                        metadata.oid = GitBinary::new(git_binary_path, working_directory, executor)
+                            .run(&["lfs", "pointer", "--file", path.as_unix_str()])
+                            .await
+                            .ok()
+                            .and_then(|pointer| LfsPointer::parse(&pointer))
+                            .map(|pointer| pointer.oid);
+                    }
+                    Some(metadata)
+                } else {
+                    None
+                };
 
-                let git = GitBinary::new(git_binary_path, working_directory, executor);
-                git.run(&[
-                    "restore",
-                    "--source",
-                    &checkpoint.commit_sha.to_string(),
-                    "--worktree",
-                    ".",
-                ])
-                .await?;
+                Ok(LfsDiffMetadata { head, worktree })
+            })
+            .boxed()
+    }
 
-                // TODO: We don't track binary and large files anymore,
-                //       so the following call would delete them.
-                //       Implement an alternative way to track files added by agent.
-                //
-                // git.with_temp_index(async move |git| {
-                //     git.run(&["read-tree", &checkpoint.commit_sha.to_string()])
-                //         .await?;
-                //     git.run(&["clean", "-d", "--force"]).await
-                // })
-                // .await?;
+    fn blame(&self, path: RepoPath, content: Rope) -> BoxFuture<'_, Result<crate::blame::Blame>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
 
-                Ok(())
+        let remote_url = self
+            .remote_url("upstream")
+            .or_else(|| self.remote_url("origin"));
+
+        self.executor
+            .spawn(async move {
+                crate::blame::Blame::for_path(
+                    &git_binary_path,
+                    &working_directory?,
+                    &path,
+                    &content,
+                    remote_url,
+                )
+                .await
             })
             .boxed()
     }
 
-    fn compare_checkpoints(
-        &self,
-        left: GitRepositoryCheckpoint,
-        right: GitRepositoryCheckpoint,
-    ) -> BoxFuture<'_, Result<bool>> {
+    fn diff(&self, diff: DiffType) -> BoxFuture<'_, Result<String>> {
         let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
-
-        let executor = self.executor.clone();
         self.executor
             .spawn(async move {
-                let working_directory = working_directory?;
-                let git = GitBinary::new(git_binary_path, working_directory, executor);
-                let result = git
-                    .run(&[
-                        "diff-tree",
-                        "--quiet",
-                        &left.commit_sha.to_string(),
-                        &right.commit_sha.to_string(),
-                    ])
-                    .await;
-                match result {
-                    Ok(_) => Ok(true),
-                    Err(error) => {
-                        if let Some(GitBinaryCommandError { status, .. }) =
-                            error.downcast_ref::<GitBinaryCommandError>()
-                            && status.code() == Some(1)
-                        {
-                            return Ok(false);
-                        }
+                let args = match diff {
+                    DiffType::HeadToIndex => Some("--staged"),
+                    DiffType::HeadToWorktree => None,
+                };
 
-                        Err(error)
-                    }
-                }
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args(["diff"])
+                    .args(args)
+                    .output()
+                    .await?;
+
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to run git diff:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
             })
             .boxed()
     }
 
-    fn diff_checkpoints(
+    fn stage_paths(
         &self,
-        base_checkpoint: GitRepositoryCheckpoint,
-        target_checkpoint: GitRepositoryCheckpoint,
-    ) -> BoxFuture<'_, Result<String>> {
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
         let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
-
-        let executor = self.executor.clone();
         self.executor
             .spawn(async move {
-                let working_directory = working_directory?;
-                let git = GitBinary::new(git_binary_path, working_directory, executor);
-                git.run(&[
-                    "diff",
-                    "--find-renames",
-                    "--patch",
-                    &base_checkpoint.commit_sha.to_string(),
-                    &target_checkpoint.commit_sha.to_string(),
-                ])
-                .await
+                if !paths.is_empty() {
+                    let output = new_smol_command(&git_binary_path)
+                        .current_dir(&working_directory?)
+                        .envs(env.iter())
+                        .args(["update-index", "--add", "--remove", "--"])
+                        .args(paths.iter().map(|p| p.as_unix_str()))
+                        .output()
+                        .await?;
+                    anyhow::ensure!(
+                        output.status.success(),
+                        "Failed to stage paths:\n{}",
+                        String::from_utf8_lossy(&output.stderr),
+                    );
+                }
+                Ok(())
             })
             .boxed()
     }
 
-    fn default_branch(&self) -> BoxFuture<'_, Result<Option<SharedString>>> {
+    fn unstage_paths(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
         let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
 
-        let executor = self.executor.clone();
         self.executor
             .spawn(async move {
-                let working_directory = working_directory?;
-                let git = GitBinary::new(git_binary_path, working_directory, executor);
+                if !paths.is_empty() {
+                    let output = new_smol_command(&git_binary_path)
+                        .current_dir(&working_directory?)
+                        .envs(env.iter())
+                        .args(["reset", "--quiet", "--"])
+                        .args(paths.iter().map(|p| p.as_std_path()))
+                        .output()
+                        .await?;
 
-                if let Ok(output) = git
-                    .run(&["symbolic-ref", "refs/remotes/upstream/HEAD"])
-                    .await
-                {
-                    let output = output
-                        .strip_prefix("refs/remotes/upstream/")
-                        .map(|s| SharedString::from(s.to_owned()));
-                    return Ok(output);
+                    anyhow::ensure!(
+                        output.status.success(),
+                        "Failed to unstage:\n{}",
+                        String::from_utf8_lossy(&output.stderr),
+                    );
                 }
-
-                let output = git
-                    .run(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-                    .await?;
-
-                Ok(output
-                    .strip_prefix("refs/remotes/origin/")
-                    .map(|s| SharedString::from(s.to_owned())))
+                Ok(())
             })
             .boxed()
     }
-}
 
-fn git_status_args(path_prefixes: &[RepoPath]) -> Vec<OsString> {
-    let mut args = vec![
-        OsString::from("--no-optional-locks"),
-        OsString::from("status"),
-        OsString::from("--porcelain=v1"),
-        OsString::from("--untracked-files=all"),
-        OsString::from("--no-renames"),
-        OsString::from("-z"),
-    ];
-    args.extend(path_prefixes.iter().map(|path_prefix| {
-        if path_prefix.is_empty() {
-            Path::new(".").into()
-        } else {
-            path_prefix.as_std_path().into()
-        }
-    }));
-    args
-}
+    fn stash_paths(
+        &self,
+        paths: Vec<RepoPath>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let mut cmd = new_smol_command(&git_binary_path);
+                cmd.current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(["stash", "push", "--quiet"])
+                    .arg("--include-untracked");
 
-/// Temporarily git-ignore commonly ignored files and files over 2MB
-async fn exclude_files(git: &GitBinary) -> Result<GitExcludeOverride> {
-    const MAX_SIZE: u64 = 2 * 1024 * 1024; // 2 MB
-    let mut excludes = git.with_exclude_overrides().await?;
-    excludes
-        .add_excludes(include_str!("./checkpoint.gitignore"))
-        .await?;
+                cmd.args(paths.iter().map(|p| p.as_unix_str()));
 
-    let working_directory = git.working_directory.clone();
-    let untracked_files = git.list_untracked_files().await?;
-    let excluded_paths = untracked_files.into_iter().map(|path| {
-        let working_directory = working_directory.clone();
-        smol::spawn(async move {
-            let full_path = working_directory.join(path.clone());
-            match smol::fs::metadata(&full_path).await {
-                Ok(metadata) if metadata.is_file() && metadata.len() >= MAX_SIZE => {
-                    Some(PathBuf::from("/").join(path.clone()))
+                let output = cmd.output().await?;
+
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to stash:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn stash_pop(
+        &self,
+        index: Option<usize>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let mut cmd = new_smol_command(git_binary_path);
+                let mut args = vec!["stash".to_string(), "pop".to_string()];
+                if let Some(index) = index {
+                    args.push(format!("stash@{{{}}}", index));
                 }
-                _ => None,
-            }
-        })
-    });
+                cmd.current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(args);
 
-    let excluded_paths = futures::future::join_all(excluded_paths).await;
-    let excluded_paths = excluded_paths.into_iter().flatten().collect::<Vec<_>>();
+                let output = cmd.output().await?;
 
-    if !excluded_paths.is_empty() {
-        let exclude_patterns = excluded_paths
-            .into_iter()
-            .map(|path| path.to_string_lossy().into_owned())
-            .collect::<Vec<_>>()
-            .join("\n");
-        excludes.add_excludes(&exclude_patterns).await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to stash pop:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
     }
 
-    Ok(excludes)
-}
+    fn stash_apply(
+        &self,
+        index: Option<usize>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let mut cmd = new_smol_command(git_binary_path);
+                let mut args = vec!["stash".to_string(), "apply".to_string()];
+                if let Some(index) = index {
+                    args.push(format!("stash@{{{}}}", index));
+                }
+                cmd.current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(args);
 
-struct GitBinary {
-    git_binary_path: PathBuf,
-    working_directory: PathBuf,
-    executor: BackgroundExecutor,
-    index_file_path: Option<PathBuf>,
-    envs: HashMap<String, String>,
-}
+                let output = cmd.output().await?;
 
-impl GitBinary {
-    fn new(
-        git_binary_path: PathBuf,
-        working_directory: PathBuf,
-        executor: BackgroundExecutor,
-    ) -> Self {
-        Self {
-            git_binary_path,
-            working_directory,
-            executor,
-            index_file_path: None,
-            envs: HashMap::default(),
-        }
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to apply stash:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
     }
 
-    async fn list_untracked_files(&self) -> Result<Vec<PathBuf>> {
-        let status_output = self
-            .run(&["status", "--porcelain=v1", "--untracked-files=all", "-z"])
-            .await?;
+    fn stash_drop(
+        &self,
+        index: Option<usize>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let mut cmd = new_smol_command(git_binary_path);
+                let mut args = vec!["stash".to_string(), "drop".to_string()];
+                if let Some(index) = index {
+                    args.push(format!("stash@{{{}}}", index));
+                }
+                cmd.current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(args);
 
-        let paths = status_output
-            .split('\0')
-            .filter(|entry| entry.len() >= 3 && entry.starts_with("?? "))
-            .map(|entry| PathBuf::from(&entry[3..]))
-            .collect::<Vec<_>>();
-        Ok(paths)
-    }
+                let output = cmd.output().await?;
 
-    fn envs(mut self, envs: HashMap<String, String>) -> Self {
-        self.envs = envs;
-        self
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to stash drop:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
     }
 
-    pub async fn with_temp_index<R>(
-        &mut self,
-        f: impl AsyncFnOnce(&Self) -> Result<R>,
-    ) -> Result<R> {
-        let index_file_path = self.path_for_index_id(Uuid::new_v4());
+    fn commit(
+        &self,
+        message: SharedString,
+        name_and_email: Option<(SharedString, SharedString)>,
+        options: CommitOptions,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let mut cmd = new_smol_command(git_binary_path);
+                cmd.current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(["commit", "--quiet", "-m"])
+                    .arg(&message.to_string())
+                    .arg("--cleanup=strip");
 
-        let delete_temp_index = util::defer({
-            let index_file_path = index_file_path.clone();
-            let executor = self.executor.clone();
-            move || {
-                executor
-                    .spawn(async move {
-                        smol::fs::remove_file(index_file_path).await.log_err();
-                    })
-                    .detach();
-            }
-        });
+                if options.amend {
+                    cmd.arg("--amend");
+                }
 
-        // Copy the default index file so that Git doesn't have to rebuild the
-        // whole index from scratch. This might fail if this is an empty repository.
-        smol::fs::copy(
-            self.working_directory.join(".git").join("index"),
-            &index_file_path,
-        )
-        .await
-        .ok();
+                if options.signoff {
+                    cmd.arg("--signoff");
+                }
 
-        self.index_file_path = Some(index_file_path.clone());
-        let result = f(self).await;
-        self.index_file_path = None;
-        let result = result?;
+                if let Some((name, email)) = name_and_email {
+                    cmd.arg("--author").arg(&format!("{name} <{email}>"));
+                }
 
-        smol::fs::remove_file(index_file_path).await.ok();
-        delete_temp_index.abort();
+                let output = cmd.output().await?;
 
-        Ok(result)
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to commit:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
     }
 
-    pub async fn with_exclude_overrides(&self) -> Result<GitExcludeOverride> {
-        let path = self
-            .working_directory
-            .join(".git")
-            .join("info")
-            .join("exclude");
-
-        GitExcludeOverride::new(path).await
-    }
+    fn push(
+        &self,
+        branch_name: String,
+        remote_name: String,
+        options: Option<PushOptions>,
+        ask_pass: AskPassDelegate,
+        env: Arc<HashMap<String, String>>,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+        let working_directory = self.working_directory();
+        let executor = cx.background_executor().clone();
+        let git_binary_path = self.system_git_binary_path.clone();
+        async move {
+            let git_binary_path = git_binary_path.context("git not found on $PATH, can't push")?;
+            let working_directory = working_directory?;
+            let mut command = new_smol_command(git_binary_path);
+            command
+                .envs(env.iter())
+                .current_dir(&working_directory)
+                .args(["push"])
+                .args(options.map(|option| match option {
+                    PushOptions::SetUpstream => "--set-upstream",
+                    PushOptions::Force => "--force-with-lease",
+                }))
+                .arg(remote_name)
+                .arg(format!("{}:{}", branch_name, branch_name))
+                .stdin(smol::process::Stdio::null())
+                .stdout(smol::process::Stdio::piped())
+                .stderr(smol::process::Stdio::piped());
 
-    fn path_for_index_id(&self, id: Uuid) -> PathBuf {
-        self.working_directory
-            .join(".git")
-            .join(format!("index-{}.tmp", id))
+            run_git_command(env, ask_pass, command, &executor).await
+        }
+        .boxed()
     }
 
-    pub async fn run<S>(&self, args: impl IntoIterator<Item = S>) -> Result<String>
-    where
-        S: AsRef<OsStr>,
-    {
-        let mut stdout = self.run_raw(args).await?;
-        if stdout.chars().last() == Some('\n') {
-            stdout.pop();
+    fn pull(
+        &self,
+        branch_name: String,
+        remote_name: String,
+        ask_pass: AskPassDelegate,
+        env: Arc<HashMap<String, String>>,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+        let working_directory = self.working_directory();
+        let executor = cx.background_executor().clone();
+        let git_binary_path = self.system_git_binary_path.clone();
+        async move {
+            let git_binary_path = git_binary_path.context("git not found on $PATH, can't pull")?;
+            let mut command = new_smol_command(git_binary_path);
+            command
+                .envs(env.iter())
+                .current_dir(&working_directory?)
+                .args(["pull"])
+                .arg(remote_name)
+                .arg(branch_name)
+                .stdout(smol::process::Stdio::piped())
+                .stderr(smol::process::Stdio::piped());
+
+            run_git_command(env, ask_pass, command, &executor).await
         }
-        Ok(stdout)
+        .boxed()
     }
 
-    /// Returns the result of the command without trimming the trailing newline.
-    pub async fn run_raw<S>(&self, args: impl IntoIterator<Item = S>) -> Result<String>
-    where
-        S: AsRef<OsStr>,
-    {
-        let mut command = self.build_command(args);
-        let output = command.output().await?;
-        anyhow::ensure!(
-            output.status.success(),
-            GitBinaryCommandError {
-                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                status: output.status,
-            }
-        );
-        Ok(String::from_utf8(output.stdout)?)
-    }
+    fn fetch(
+        &self,
+        fetch_options: FetchOptions,
+        ask_pass: AskPassDelegate,
+        env: Arc<HashMap<String, String>>,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+        let working_directory = self.working_directory();
+        let remote_name = format!("{}", fetch_options);
+        let git_binary_path = self.system_git_binary_path.clone();
+        let lfs_dir = self.repository.lock().commondir().join(crate::LFS_DIR);
+        let executor = cx.background_executor().clone();
+        async move {
+            let git_binary_path = git_binary_path.context("git not found on $PATH, can't fetch")?;
+            let working_directory = working_directory?;
+            let mut command = new_smol_command(&git_binary_path);
+            command
+                .envs(env.iter())
+                .current_dir(&working_directory)
+                .args(["fetch", &remote_name])
+                .stdout(smol::process::Stdio::piped())
+                .stderr(smol::process::Stdio::piped());
 
-    fn build_command<S>(&self, args: impl IntoIterator<Item = S>) -> smol::process::Command
-    where
-        S: AsRef<OsStr>,
-    {
-        let mut command = new_smol_command(&self.git_binary_path);
-        command.current_dir(&self.working_directory);
-        command.args(args);
-        if let Some(index_file_path) = self.index_file_path.as_ref() {
-            command.env("GIT_INDEX_FILE", index_file_path);
+            let mut output = run_git_command(env.clone(), ask_pass, command, &executor).await?;
+
+            // Download the LFS objects of the fetched branch ahead of time. The
+            // credentials have just been used by `git fetch`, so this never
+            // needs to prompt for them.
+            if smol::fs::metadata(&lfs_dir).await.is_ok() {
+                let mut command = new_smol_command(&git_binary_path);
+                command
+                    .envs(env.iter())
+                    .env("GIT_TERMINAL_PROMPT", "0")
+                    .current_dir(&working_directory)
+                    .args(["lfs", "fetch"]);
+                if let FetchOptions::Remote(remote) = &fetch_options {
+                    command.arg(remote.name.as_ref());
+                }
+                let lfs_output = command.output().await?;
+                anyhow::ensure!(
+                    lfs_output.status.success(),
+                    "git lfs fetch failed: {}",
+                    String::from_utf8_lossy(&lfs_output.stderr)
+                );
+                output
+                    .stderr
+                    .push_str(&String::from_utf8_lossy(&lfs_output.stderr));
+            }
+            Ok(output)
         }
-        command.envs(&self.envs);
-        command
+        .boxed()
     }
-}
 
-#[derive(Error, Debug)]
-#[error("Git command failed:\n{stdout}{stderr}\n")]
-struct GitBinaryCommandError {
-    stdout: String,
-    stderr: String,
-    status: ExitStatus,
-}
+    fn get_remotes(&self, branch_name: Option<String>) -> BoxFuture<'_, Result<Vec<Remote>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                if let Some(branch_name) = branch_name {
+                    let output = new_smol_command(&git_binary_path)
+                        .current_dir(&working_directory)
+                        .args(["config", "--get"])
+                        .arg(format!("branch.{}.remote", branch_name))
+                        .output()
+                        .await?;
 
-async fn run_git_command(
-    env: Arc<HashMap<String, String>>,
-    ask_pass: AskPassDelegate,
-    mut command: smol::process::Command,
-    executor: &BackgroundExecutor,
-) -> Result<RemoteCommandOutput> {
-    if env.contains_key("GIT_ASKPASS") {
-        let git_process = command.spawn()?;
-        let output = git_process.output().await?;
-        anyhow::ensure!(
-            output.status.success(),
-            "{}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-        Ok(RemoteCommandOutput {
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        })
-    } else {
-        let ask_pass = AskPassSession::new(executor, ask_pass).await?;
-        command
-            .env("GIT_ASKPASS", ask_pass.script_path())
-            .env("SSH_ASKPASS", ask_pass.script_path())
-            .env("SSH_ASKPASS_REQUIRE", "force");
-        let git_process = command.spawn()?;
+                    if output.status.success() {
+                        let remote_name = String::from_utf8_lossy(&output.stdout);
 
-        run_askpass_command(ask_pass, git_process).await
+                        return Ok(vec![Remote {
+                            name: remote_name.trim().to_string().into(),
+                        }]);
+                    }
+                }
+
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .args(["remote"])
+                    .output()
+                    .await?;
+
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to get remotes:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                let remote_names = String::from_utf8_lossy(&output.stdout)
+                    .split('\n')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| Remote {
+                        name: name.trim().to_string().into(),
+                    })
+                    .collect();
+                Ok(remote_names)
+            })
+            .boxed()
     }
-}
 
-async fn run_askpass_command(
-    mut ask_pass: AskPassSession,
-    git_process: smol::process::Child,
-) -> anyhow::Result<RemoteCommandOutput> {
-    select_biased! {
-        result = ask_pass.run().fuse() => {
-            match result {
-                AskPassResult::CancelledByUser => {
-                    Err(anyhow!(REMOTE_CANCELLED_BY_USER))?
+    fn check_for_pushed_commit(&self) -> BoxFuture<'_, Result<Vec<SharedString>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let git_cmd = async |args: &[&str]| -> Result<String> {
+                    let output = new_smol_command(&git_binary_path)
+                        .current_dir(&working_directory)
+                        .args(args)
+                        .output()
+                        .await?;
+                    anyhow::ensure!(
+                        output.status.success(),
+                        String::from_utf8_lossy(&output.stderr).to_string()
+                    );
+                    Ok(String::from_utf8(output.stdout)?)
+                };
+
+                let head = git_cmd(&["rev-parse", "HEAD"])
+                    .await
+                    .context("Failed to get HEAD")?
+                    .trim()
+                    .to_owned();
+
+                let mut remote_branches = vec![];
+                let mut add_if_matching = async |remote_head: &str| {
+                    if let Ok(merge_base) = git_cmd(&["merge-base", &head, remote_head]).await
+                        && merge_base.trim() == head
+                        && let Some(s) = remote_head.strip_prefix("refs/remotes/")
+                    {
+                        remote_branches.push(s.to_owned().into());
+                    }
+                };
+
+                // check the main branch of each remote
+                let remotes = git_cmd(&["remote"])
+                    .await
+                    .context("Failed to get remotes")?;
+                for remote in remotes.lines() {
+                    if let Ok(remote_head) =
+                        git_cmd(&["symbolic-ref", &format!("refs/remotes/{remote}/HEAD")]).await
+                    {
+                        add_if_matching(remote_head.trim()).await;
+                    }
                 }
-                AskPassResult::Timedout => {
-                    Err(anyhow!("Connecting to host timed out"))?
+
+                // ... and the remote branch that the checked-out one is tracking
+                if let Ok(remote_head) =
+                    git_cmd(&["rev-parse", "--symbolic-full-name", "@{u}"]).await
+                {
+                    add_if_matching(remote_head.trim()).await;
                 }
-            }
-        }
-        output = git_process.output().fuse() => {
-            let output = output?;
-            anyhow::ensure!(
-                output.status.success(),
-                "{}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-            Ok(RemoteCommandOutput {
-                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+
+                Ok(remote_branches)
+            })
+            .boxed()
+    }
+
+    fn checkpoint(&self) -> BoxFuture<'static, Result<GitRepositoryCheckpoint>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let mut git = GitBinary::new(git_binary_path, working_directory.clone(), executor)
+                    .envs(checkpoint_author_envs());
+                git.with_temp_index(async |git| {
+                    let head_sha = git.run(&["rev-parse", "HEAD"]).await.ok();
+                    let mut excludes = exclude_files(git).await?;
+
+                    git.run(&["add", "--all"]).await?;
+                    let tree = git.run(&["write-tree"]).await?;
+                    let checkpoint_sha = if let Some(head_sha) = head_sha.as_deref() {
+                        git.run(&["commit-tree", &tree, "-p", head_sha, "-m", "Checkpoint"])
+                            .await?
+                    } else {
+                        git.run(&["commit-tree", &tree, "-m", "Checkpoint"]).await?
+                    };
+
+                    excludes.restore_original().await?;
+
+                    Ok(GitRepositoryCheckpoint {
+                        commit_sha: checkpoint_sha.parse()?,
+                    })
+                })
+                .await
+            })
+            .boxed()
+    }
+
+    fn restore_checkpoint(&self, checkpoint: GitRepositoryCheckpoint) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+
+                let git = GitBinary::new(git_binary_path, working_directory, executor);
+                git.run(&[
+                    "restore",
+                    "--source",
+                    &checkpoint.commit_sha.to_string(),
+                    "--worktree",
+                    ".",
+                ])
+                .await?;
+
+                // TODO: We don't track binary and large files anymore,
+                //       so the following call would delete them.
+                //       Implement an alternative way to track files added by agent.
+                //
+                // git.with_temp_index(async move |git| {
+                //     git.run(&["read-tree", &checkpoint.commit_sha.to_string()])
+                //         .await?;
+                //     git.run(&["clean", "-d", "--force"]).await
+                // })
+                // .await?;
+
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn compare_checkpoints(
+        &self,
+        left: GitRepositoryCheckpoint,
+        right: GitRepositoryCheckpoint,
+    ) -> BoxFuture<'_, Result<bool>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let git = GitBinary::new(git_binary_path, working_directory, executor);
+                let result = git
+                    .run(&[
+                        "diff-tree",
+                        "--quiet",
+                        &left.commit_sha.to_string(),
+                        &right.commit_sha.to_string(),
+                    ])
+                    .await;
+                match result {
+                    Ok(_) => Ok(true),
+                    Err(error) => {
+                        if let Some(GitBinaryCommandError { status, .. }) =
+                            error.downcast_ref::<GitBinaryCommandError>()
+                            && status.code() == Some(1)
+                        {
+                            return Ok(false);
+                        }
+
+                        Err(error)
+                    }
+                }
+            })
+            .boxed()
+    }
+
+    fn diff_checkpoints(
+        &self,
+        base_checkpoint: GitRepositoryCheckpoint,
+        target_checkpoint: GitRepositoryCheckpoint,
+    ) -> BoxFuture<'_, Result<String>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let git = GitBinary::new(git_binary_path, working_directory, executor);
+                git.run(&[
+                    "diff",
+                    "--find-renames",
+                    "--patch",
+                    &base_checkpoint.commit_sha.to_string(),
+                    &target_checkpoint.commit_sha.to_string(),
+                ])
+                .await
+            })
+            .boxed()
+    }
+
+    fn default_branch(&self) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let git = GitBinary::new(git_binary_path, working_directory, executor);
+
+                if let Ok(output) = git
+                    .run(&["symbolic-ref", "refs/remotes/upstream/HEAD"])
+                    .await
+                {
+                    let output = output
+                        .strip_prefix("refs/remotes/upstream/")
+                        .map(|s| SharedString::from(s.to_owned()));
+                    return Ok(output);
+                }
+
+                let output = git
+                    .run(&["symbolic-ref", "refs/remotes/origin/HEAD"])
+                    .await?;
+
+                Ok(output
+                    .strip_prefix("refs/remotes/origin/")
+                    .map(|s| SharedString::from(s.to_owned())))
+            })
+            .boxed()
+    }
+}
+
+/// Rejects revisions that git would take for an option or that contain whitespace.
+fn validate_revision(revision: &str) -> Result<()> {
+    anyhow::ensure!(
+        !revision.is_empty()
+            && !revision.starts_with('-')
+            && !revision.contains(|c: char| c.is_whitespace() || c.is_control()),
+        "invalid revision {revision:?}"
+    );
+    Ok(())
+}
+
+/// Rejects tag names that git would take for an option or that aren't valid refs.
+async fn validate_tag_name(git: &GitBinary, name: &str) -> Result<()> {
+    validate_revision(name)?;
+    git.run(&["check-ref-format", &format!("refs/tags/{name}")])
+        .await
+        .with_context(|| format!("invalid tag name {name:?}"))?;
+    Ok(())
+}
+
+/// Reads the old and new contents of the files changed between two
+/// revisions, given as the output of `git diff --name-status`.
+async fn load_changed_files(
+    git_binary_path: &Path,
+    working_directory: &Path,
+    old_rev: &str,
+    new_rev: &str,
+    changes: impl Iterator<Item = (&str, StatusCode)>,
+) -> Result<Vec<CommitFile>> {
+    let mut cat_file_process = util::command::new_smol_command(git_binary_path)
+        .current_dir(working_directory)
+        .args(["--no-optional-locks", "cat-file", "--batch=%(objectsize)"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("starting git cat-file process")?;
+
+    let mut files = Vec::<CommitFile>::new();
+    let mut stdin = BufWriter::with_capacity(512, cat_file_process.stdin.take().unwrap());
+    let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
+    let mut info_line = String::new();
+    let mut newline = [b'\0'];
+    for (path, status_code) in changes {
+        // git-show and git-diff output `/`-delimited paths even on Windows.
+        let Some(rel_path) = RelPath::unix(path).log_err() else {
+            continue;
+        };
+
+        match status_code {
+            StatusCode::Modified => {
+                stdin.write_all(new_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+                stdin.write_all(old_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            StatusCode::Added => {
+                stdin.write_all(new_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            StatusCode::Deleted => {
+                stdin.write_all(old_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            _ => continue,
+        }
+        stdin.flush().await?;
+
+        info_line.clear();
+        stdout.read_line(&mut info_line).await?;
+
+        let len = info_line
+            .trim_end()
+            .parse()
+            .with_context(|| format!("invalid object size output from cat-file {info_line}"))?;
+        let mut text = vec![0; len];
+        stdout.read_exact(&mut text).await?;
+        stdout.read_exact(&mut newline).await?;
+        let text = String::from_utf8_lossy(&text).to_string();
+
+        let mut old_text = None;
+        let mut new_text = None;
+        match status_code {
+            StatusCode::Modified => {
+                info_line.clear();
+                stdout.read_line(&mut info_line).await?;
+                let len = info_line.trim_end().parse().with_context(|| {
+                    format!("invalid object size output from cat-file {}", info_line)
+                })?;
+                let mut parent_text = vec![0; len];
+                stdout.read_exact(&mut parent_text).await?;
+                stdout.read_exact(&mut newline).await?;
+                old_text = Some(String::from_utf8_lossy(&parent_text).to_string());
+                new_text = Some(text);
+            }
+            StatusCode::Added => new_text = Some(text),
+            StatusCode::Deleted => old_text = Some(text),
+            _ => continue,
+        }
+
+        files.push(CommitFile {
+            path: rel_path.into(),
+            old_text,
+            new_text,
+        })
+    }
+
+    Ok(files)
+}
+
+/// Returns which of `paths` have their `filter` attribute set to `lfs`.
+async fn lfs_tracked_paths(
+    git_binary_path: &Path,
+    working_directory: &Path,
+    paths: impl Iterator<Item = &RepoPath>,
+) -> Result<Vec<RepoPath>> {
+    let mut input = Vec::new();
+    for path in paths {
+        input.extend_from_slice(path.as_unix_str().as_bytes());
+        input.push(b'\0');
+    }
+    let mut child = new_smol_command(git_binary_path)
+        .current_dir(working_directory)
+        .args(["check-attr", "-z", "--stdin", "filter"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("starting git check-attr process")?;
+    let mut stdin = child.stdin.take().context("no stdin for git check-attr")?;
+    // Write the paths while reading the output, so that neither pipe fills up.
+    let (write_result, output) = futures::join!(
+        async move {
+            stdin.write_all(&input).await?;
+            stdin.close().await
+        },
+        child.output()
+    );
+    write_result?;
+    let output = output?;
+    anyhow::ensure!(
+        output.status.success(),
+        "git check-attr failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(parse_lfs_check_attr(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn git_status_args(path_prefixes: &[RepoPath]) -> Vec<OsString> {
+    let mut args = vec![
+        OsString::from("--no-optional-locks"),
+        OsString::from("status"),
+        OsString::from("--porcelain=v1"),
+        OsString::from("--untracked-files=all"),
+        OsString::from("--no-renames"),
+        OsString::from("-z"),
+    ];
+    args.extend(path_prefixes.iter().map(|path_prefix| {
+        if path_prefix.is_empty() {
+            Path::new(".").into()
+        } else {
+            path_prefix.as_std_path().into()
+        }
+    }));
+    args
+}
+
+/// Temporarily git-ignore commonly ignored files and files over 2MB
+async fn exclude_files(git: &GitBinary) -> Result<GitExcludeOverride> {
+    const MAX_SIZE: u64 = 2 * 1024 * 1024; // 2 MB
+    let mut excludes = git.with_exclude_overrides().await?;
+    excludes
+        .add_excludes(include_str!("./checkpoint.gitignore"))
+        .await?;
+
+    let working_directory = git.working_directory.clone();
+    let untracked_files = git.list_untracked_files().await?;
+    let excluded_paths = untracked_files.into_iter().map(|path| {
+        let working_directory = working_directory.clone();
+        smol::spawn(async move {
+            let full_path = working_directory.join(path.clone());
+            match smol::fs::metadata(&full_path).await {
+                Ok(metadata) if metadata.is_file() && metadata.len() >= MAX_SIZE => {
+                    Some(PathBuf::from("/").join(path.clone()))
+                }
+                _ => None,
+            }
+        })
+    });
+
+    let excluded_paths = futures::future::join_all(excluded_paths).await;
+    let excluded_paths = excluded_paths.into_iter().flatten().collect::<Vec<_>>();
+
+    if !excluded_paths.is_empty() {
+        let exclude_patterns = excluded_paths
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("\n");
+        excludes.add_excludes(&exclude_patterns).await?;
+    }
+
+    Ok(excludes)
+}
+
+struct GitBinary {
+    git_binary_path: PathBuf,
+    working_directory: PathBuf,
+    executor: BackgroundExecutor,
+    index_file_path: Option<PathBuf>,
+    envs: HashMap<String, String>,
+}
+
+impl GitBinary {
+    fn new(
+        git_binary_path: PathBuf,
+        working_directory: PathBuf,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        Self {
+            git_binary_path,
+            working_directory,
+            executor,
+            index_file_path: None,
+            envs: HashMap::default(),
+        }
+    }
+
+    async fn list_untracked_files(&self) -> Result<Vec<PathBuf>> {
+        let status_output = self
+            .run(&["status", "--porcelain=v1", "--untracked-files=all", "-z"])
+            .await?;
+
+        let paths = status_output
+            .split('\0')
+            .filter(|entry| entry.len() >= 3 && entry.starts_with("?? "))
+            .map(|entry| PathBuf::from(&entry[3..]))
+            .collect::<Vec<_>>();
+        Ok(paths)
+    }
+
+    fn envs(mut self, envs: HashMap<String, String>) -> Self {
+        self.envs = envs;
+        self
+    }
+
+    pub async fn with_temp_index<R>(
+        &mut self,
+        f: impl AsyncFnOnce(&Self) -> Result<R>,
+    ) -> Result<R> {
+        let index_file_path = self.path_for_index_id(Uuid::new_v4());
+
+        let delete_temp_index = util::defer({
+            let index_file_path = index_file_path.clone();
+            let executor = self.executor.clone();
+            move || {
+                executor
+                    .spawn(async move {
+                        smol::fs::remove_file(index_file_path).await.log_err();
+                    })
+                    .detach();
+            }
+        });
+
+        // Copy the default index file so that Git doesn't have to rebuild the
+        // whole index from scratch. This might fail if this is an empty repository.
+        smol::fs::copy(
+            self.working_directory.join(".git").join("index"),
+            &index_file_path,
+        )
+        .await
+        .ok();
+
+        self.index_file_path = Some(index_file_path.clone());
+        let result = f(self).await;
+        self.index_file_path = None;
+        let result = result?;
+
+        smol::fs::remove_file(index_file_path).await.ok();
+        delete_temp_index.abort();
+
+        Ok(result)
+    }
+
+    pub async fn with_exclude_overrides(&self) -> Result<GitExcludeOverride> {
+        let path = self
+            .working_directory
+            .join(".git")
+            .join("info")
+            .join("exclude");
+
+        GitExcludeOverride::new(path).await
+    }
+
+    fn path_for_index_id(&self, id: Uuid) -> PathBuf {
+        self.working_directory
+            .join(".git")
+            .join(format!("index-{}.tmp", id))
+    }
+
+    pub async fn run<S>(&self, args: impl IntoIterator<Item = S>) -> Result<String>
+    where
+        S: AsRef<OsStr>,
+    {
+        let mut stdout = self.run_raw(args).await?;
+        if stdout.chars().last() == Some('\n') {
+            stdout.pop();
+        }
+        Ok(stdout)
+    }
+
+    /// Returns the result of the command without trimming the trailing newline.
+    pub async fn run_raw<S>(&self, args: impl IntoIterator<Item = S>) -> Result<String>
+    where
+        S: AsRef<OsStr>,
+    {
+        let mut command = self.build_command(args);
+        let output = command.output().await?;
+        anyhow::ensure!(
+            output.status.success(),
+            GitBinaryCommandError {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                status: output.status,
+            }
+        );
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    fn build_command<S>(&self, args: impl IntoIterator<Item = S>) -> smol::process::Command
+    where
+        S: AsRef<OsStr>,
+    {
+        let mut command = new_smol_command(&self.git_binary_path);
+        command.current_dir(&self.working_directory);
+        command.args(args);
+        if let Some(index_file_path) = self.index_file_path.as_ref() {
+            command.env("GIT_INDEX_FILE", index_file_path);
+        }
+        command.envs(&self.envs);
+        command
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Git command failed:\n{stdout}{stderr}\n")]
+struct GitBinaryCommandError {
+    stdout: String,
+    stderr: String,
+    status: ExitStatus,
+}
+
+async fn run_git_command(
+    env: Arc<HashMap<String, String>>,
+    ask_pass: AskPassDelegate,
+    mut command: smol::process::Command,
+    executor: &BackgroundExecutor,
+) -> Result<RemoteCommandOutput> {
+    if env.contains_key("GIT_ASKPASS") {
+        let git_process = command.spawn()?;
+        let output = git_process.output().await?;
+        anyhow::ensure!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(RemoteCommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    } else {
+        let ask_pass = AskPassSession::new(executor, ask_pass).await?;
+        command
+            .env("GIT_ASKPASS", ask_pass.script_path())
+            .env("SSH_ASKPASS", ask_pass.script_path())
+            .env("SSH_ASKPASS_REQUIRE", "force");
+        let git_process = command.spawn()?;
+
+        run_askpass_command(ask_pass, git_process).await
+    }
+}
+
+async fn run_askpass_command(
+    mut ask_pass: AskPassSession,
+    git_process: smol::process::Child,
+) -> anyhow::Result<RemoteCommandOutput> {
+    select_biased! {
+        result = ask_pass.run().fuse() => {
+            match result {
+                AskPassResult::CancelledByUser => {
+                    Err(anyhow!(REMOTE_CANCELLED_BY_USER))?
+                }
+                AskPassResult::Timedout => {
+                    Err(anyhow!("Connecting to host timed out"))?
+                }
+            }
+        }
+        output = git_process.output().fuse() => {
+            let output = output?;
+            anyhow::ensure!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Ok(RemoteCommandOutput {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            })
+        }
+    }
+}
+
+#[derive(Clone, Debug, Ord, Hash, PartialOrd, Eq, PartialEq)]
+pub struct RepoPath(pub Arc<RelPath>);
+
+impl RepoPath {
+    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> Result<Self> {
+        let rel_path = RelPath::unix(s.as_ref())?;
+        Ok(rel_path.into())
+    }
+
+    pub fn from_proto(proto: &str) -> Result<Self> {
+        let rel_path = RelPath::from_proto(proto)?;
+        Ok(rel_path.into())
+    }
+
+    pub fn from_std_path(path: &Path, path_style: PathStyle) -> Result<Self> {
+        let rel_path = RelPath::new(path, path_style)?;
+        Ok(Self(rel_path.as_ref().into()))
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+pub fn repo_path<S: AsRef<str> + ?Sized>(s: &S) -> RepoPath {
+    RepoPath(RelPath::unix(s.as_ref()).unwrap().into())
+}
+
+impl From<&RelPath> for RepoPath {
+    fn from(value: &RelPath) -> Self {
+        RepoPath(value.into())
+    }
+}
+
+impl<'a> From<Cow<'a, RelPath>> for RepoPath {
+    fn from(value: Cow<'a, RelPath>) -> Self {
+        value.as_ref().into()
+    }
+}
+
+impl From<Arc<RelPath>> for RepoPath {
+    fn from(value: Arc<RelPath>) -> Self {
+        RepoPath(value)
+    }
+}
+
+impl Default for RepoPath {
+    fn default() -> Self {
+        RepoPath(RelPath::empty().into())
+    }
+}
+
+impl std::ops::Deref for RepoPath {
+    type Target = RelPath;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+// impl AsRef<Path> for RepoPath {
+//     fn as_ref(&self) -> &Path {
+//         RelPath::as_ref(&self.0)
+//     }
+// }
+
+#[derive(Debug)]
+pub struct RepoPathDescendants<'a>(pub &'a RepoPath);
+
+impl MapSeekTarget<RepoPath> for RepoPathDescendants<'_> {
+    fn cmp_cursor(&self, key: &RepoPath) -> Ordering {
+        if key.starts_with(self.0) {
+            Ordering::Greater
+        } else {
+            self.0.cmp(key)
+        }
+    }
+}
+
+fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
+    let mut branches = Vec::new();
+    for line in input.split('\n') {
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\x00');
+        let is_current_branch = fields.next().context("no HEAD")? == "*";
+        let head_sha: SharedString = fields.next().context("no objectname")?.to_string().into();
+        let parent_sha: SharedString = fields.next().context("no parent")?.to_string().into();
+        let ref_name = fields.next().context("no refname")?.to_string().into();
+        let upstream_name = fields.next().context("no upstream")?.to_string();
+        let upstream_tracking = parse_upstream_track(fields.next().context("no upstream:track")?)?;
+        let commiterdate = fields.next().context("no committerdate")?.parse::<i64>()?;
+        let author_name = fields.next().context("no authorname")?.to_string().into();
+        let subject: SharedString = fields
+            .next()
+            .context("no contents:subject")?
+            .to_string()
+            .into();
+
+        branches.push(Branch {
+            is_head: is_current_branch,
+            ref_name,
+            most_recent_commit: Some(CommitSummary {
+                sha: head_sha,
+                subject,
+                commit_timestamp: commiterdate,
+                author_name: author_name,
+                has_parent: !parent_sha.is_empty(),
+            }),
+            upstream: if upstream_name.is_empty() {
+                None
+            } else {
+                Some(Upstream {
+                    ref_name: upstream_name.into(),
+                    tracking: upstream_tracking,
+                })
+            },
+        })
+    }
+
+    Ok(branches)
+}
+
+fn parse_tag_input(input: &str) -> Result<Vec<Tag>> {
+    let mut tags = Vec::new();
+    for line in input.split('\n') {
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\x00');
+        let name = fields.next().context("no refname")?;
+        let object_type = fields.next().context("no objecttype")?;
+        let object_sha = fields.next().context("no objectname")?;
+        let target_sha = fields.next().context("no *objectname")?;
+        let target_type = fields.next().context("no *objecttype")?;
+        let timestamp = fields.next().context("no creatordate")?.parse::<i64>()?;
+        let subject = fields.next().context("no contents:subject")?;
+        let target_subject = fields.next().context("no *contents:subject")?;
+
+        let tag = match object_type {
+            "commit" => Tag {
+                name: name.to_string().into(),
+                sha: object_sha.to_string().into(),
+                subject: subject.to_string().into(),
+                message: None,
+                timestamp,
+            },
+            // Tags of trees, blobs or other tags aren't listed.
+            "tag" if target_type == "commit" => Tag {
+                name: name.to_string().into(),
+                sha: target_sha.to_string().into(),
+                subject: target_subject.to_string().into(),
+                message: Some(subject.to_string().into()),
+                timestamp,
+            },
+            _ => continue,
+        };
+        tags.push(tag);
+    }
+
+    Ok(tags)
+}
+
+fn parse_upstream_track(upstream_track: &str) -> Result<UpstreamTracking> {
+    if upstream_track.is_empty() {
+        return Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
+            ahead: 0,
+            behind: 0,
+        }));
+    }
+
+    let upstream_track = upstream_track.strip_prefix("[").context("missing [")?;
+    let upstream_track = upstream_track.strip_suffix("]").context("missing [")?;
+    let mut ahead: u32 = 0;
+    let mut behind: u32 = 0;
+    for component in upstream_track.split(", ") {
+        if component == "gone" {
+            return Ok(UpstreamTracking::Gone);
+        }
+        if let Some(ahead_num) = component.strip_prefix("ahead ") {
+            ahead = ahead_num.parse::<u32>()?;
+        }
+        if let Some(behind_num) = component.strip_prefix("behind ") {
+            behind = behind_num.parse::<u32>()?;
+        }
+    }
+    Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
+        ahead,
+        behind,
+    }))
+}
+
+/// Reads the refs that `git bisect` keeps under `refs/bisect`: `bad`, and
+/// `good-<sha>` and `skip-<sha>` for every commit marked good or skipped.
+fn parse_bisect_refs(input: &str) -> BisectState {
+    let mut state = BisectState::default();
+    for line in input.lines() {
+        let Some((name, sha)) = line.split_once('\0') else {
+            continue;
+        };
+        let sha = SharedString::from(sha.to_string());
+        if name == "bad" {
+            state.bad = Some(sha);
+        } else if name.starts_with("good-") {
+            state.good.push(sha);
+        } else if name.starts_with("skip-") {
+            state.skipped.push(sha);
+        }
+    }
+    state
+}
+
+fn parse_log_output(output: &str) -> Result<Vec<CommitSummary>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields = line.split('\0').collect::<Vec<_>>();
+            let [sha, subject, commit_timestamp, author_name, parents] = fields[..] else {
+                bail!("unexpected git-log output: {line:?}");
+            };
+            Ok(CommitSummary {
+                sha: sha.to_string().into(),
+                subject: subject.to_string().into(),
+                commit_timestamp: commit_timestamp.parse()?,
+                author_name: author_name.to_string().into(),
+                has_parent: !parents.is_empty(),
+            })
+        })
+        .collect()
+}
+
+/// The directory in the repository's git directory that holds the todo list
+/// of an interactive rebase, along with the messages of reworded commits.
+const REBASE_TODO_DIR: &str = "zed-rebase";
+
+/// Writes the todo list that is handed to `git rebase -i`. Reworded commits
+/// are picked and then amended with their new message, which is kept in a file
+/// until the rebase finishes, since it may stop on conflicts before that.
+async fn write_rebase_todo(
+    repository_dir: &Path,
+    git_binary_path: &Path,
+    todo: &[RebaseTodoEntry],
+) -> Result<PathBuf> {
+    // Each line of the todo list is a command, so anything but an object id
+    // could run arbitrary commands.
+    for entry in todo {
+        anyhow::ensure!(
+            (4..=64).contains(&entry.sha.len()) && entry.sha.chars().all(|c| c.is_ascii_hexdigit()),
+            "invalid commit sha {:?}",
+            entry.sha
+        );
+    }
+    if let Some(first) = todo.iter().find(|entry| entry.action != RebaseAction::Drop) {
+        anyhow::ensure!(
+            !first.action.melds(),
+            "the first commit can't be melded into a previous one"
+        );
+    }
+
+    let rebase_dir = repository_dir.join(REBASE_TODO_DIR);
+    smol::fs::remove_dir_all(&rebase_dir).await.ok();
+    smol::fs::create_dir_all(&rebase_dir).await?;
+    let mut script = String::new();
+    for (ix, entry) in todo.iter().enumerate() {
+        let command = match entry.action {
+            RebaseAction::Pick | RebaseAction::Reword => "pick",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        };
+        writeln!(script, "{command} {}", entry.sha)?;
+        if entry.action == RebaseAction::Reword {
+            let message = entry
+                .message
+                .as_deref()
+                .with_context(|| format!("no new message for {}", entry.sha))?;
+            let message_path = rebase_dir.join(format!("message-{ix}"));
+            smol::fs::write(&message_path, message).await?;
+            writeln!(
+                script,
+                "exec {} commit --amend --only --allow-empty --quiet --file {}",
+                shell_quote(git_binary_path),
+                shell_quote(&message_path)
+            )?;
+        }
+    }
+    let todo_path = rebase_dir.join("git-rebase-todo");
+    smol::fs::write(&todo_path, script).await?;
+    Ok(todo_path)
+}
+
+async fn remove_rebase_messages(repository_dir: &Path) {
+    smol::fs::remove_dir_all(repository_dir.join(REBASE_TODO_DIR))
+        .await
+        .ok();
+}
+
+/// Keeps git from waiting on an editor for the messages of squashed commits
+/// and of commits whose conflicts were resolved, keeping the messages it
+/// proposes.
+fn rebase_envs(env: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut envs = env.clone();
+    envs.insert("GIT_EDITOR".into(), "true".into());
+    envs
+}
+
+/// Quotes a path for the shell that git runs editors and `exec` lines in.
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
+fn checkpoint_author_envs() -> HashMap<String, String> {
+    HashMap::from_iter([
+        ("GIT_AUTHOR_NAME".to_string(), "Zed".to_string()),
+        ("GIT_AUTHOR_EMAIL".to_string(), "hi@zed.dev".to_string()),
+        ("GIT_COMMITTER_NAME".to_string(), "Zed".to_string()),
+        ("GIT_COMMITTER_EMAIL".to_string(), "hi@zed.dev".to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_checkpoint_basic(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let file_path = repo_dir.path().join("file");
+        smol::fs::write(&file_path, "initial").await.unwrap();
+
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        repo.stage_paths(vec![repo_path("file")], Arc::new(HashMap::default()))
+            .await
+            .unwrap();
+        repo.commit(
+            "Initial commit".into(),
+            None,
+            CommitOptions::default(),
+            Arc::new(checkpoint_author_envs()),
+        )
+        .await
+        .unwrap();
+
+        smol::fs::write(&file_path, "modified before checkpoint")
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("new_file_before_checkpoint"), "1")
+            .await
+            .unwrap();
+        let checkpoint = repo.checkpoint().await.unwrap();
+
+        // Ensure the user can't see any branches after creating a checkpoint.
+        assert_eq!(repo.branches().await.unwrap().len(), 1);
+
+        smol::fs::write(&file_path, "modified after checkpoint")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![repo_path("file")], Arc::new(HashMap::default()))
+            .await
+            .unwrap();
+        repo.commit(
+            "Commit after checkpoint".into(),
+            None,
+            CommitOptions::default(),
+            Arc::new(checkpoint_author_envs()),
+        )
+        .await
+        .unwrap();
+
+        smol::fs::remove_file(repo_dir.path().join("new_file_before_checkpoint"))
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("new_file_after_checkpoint"), "2")
+            .await
+            .unwrap();
+
+        // Ensure checkpoint stays alive even after a Git GC.
+        repo.gc().await.unwrap();
+        repo.restore_checkpoint(checkpoint.clone()).await.unwrap();
+
+        assert_eq!(
+            smol::fs::read_to_string(&file_path).await.unwrap(),
+            "modified before checkpoint"
+        );
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("new_file_before_checkpoint"))
+                .await
+                .unwrap(),
+            "1"
+        );
+        // See TODO above
+        // assert_eq!(
+        //     smol::fs::read_to_string(repo_dir.path().join("new_file_after_checkpoint"))
+        //         .await
+        //         .ok(),
+        //     None
+        // );
+    }
+
+    #[gpui::test]
+    async fn test_checkpoint_empty_repo(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+
+        smol::fs::write(repo_dir.path().join("foo"), "foo")
+            .await
+            .unwrap();
+        let checkpoint_sha = repo.checkpoint().await.unwrap();
+
+        // Ensure the user can't see any branches after creating a checkpoint.
+        assert_eq!(repo.branches().await.unwrap().len(), 1);
+
+        smol::fs::write(repo_dir.path().join("foo"), "bar")
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("baz"), "qux")
+            .await
+            .unwrap();
+        repo.restore_checkpoint(checkpoint_sha).await.unwrap();
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("foo"))
+                .await
+                .unwrap(),
+            "foo"
+        );
+        // See TODOs above
+        // assert_eq!(
+        //     smol::fs::read_to_string(repo_dir.path().join("baz"))
+        //         .await
+        //         .ok(),
+        //     None
+        // );
+    }
+
+    #[gpui::test]
+    async fn test_compare_checkpoints(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+
+        smol::fs::write(repo_dir.path().join("file1"), "content1")
+            .await
+            .unwrap();
+        let checkpoint1 = repo.checkpoint().await.unwrap();
+
+        smol::fs::write(repo_dir.path().join("file2"), "content2")
+            .await
+            .unwrap();
+        let checkpoint2 = repo.checkpoint().await.unwrap();
+
+        assert!(
+            !repo
+                .compare_checkpoints(checkpoint1, checkpoint2.clone())
+                .await
+                .unwrap()
+        );
+
+        let checkpoint3 = repo.checkpoint().await.unwrap();
+        assert!(
+            repo.compare_checkpoints(checkpoint2, checkpoint3)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_checkpoint_exclude_binary_files(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        let text_path = repo_dir.path().join("main.rs");
+        let bin_path = repo_dir.path().join("binary.o");
+
+        git2::Repository::init(repo_dir.path()).unwrap();
+
+        smol::fs::write(&text_path, "fn main() {}").await.unwrap();
+
+        smol::fs::write(&bin_path, "some binary file here")
+            .await
+            .unwrap();
+
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+
+        // initial commit
+        repo.stage_paths(vec![repo_path("main.rs")], Arc::new(HashMap::default()))
+            .await
+            .unwrap();
+        repo.commit(
+            "Initial commit".into(),
+            None,
+            CommitOptions::default(),
+            Arc::new(checkpoint_author_envs()),
+        )
+        .await
+        .unwrap();
+
+        let checkpoint = repo.checkpoint().await.unwrap();
+
+        smol::fs::write(&text_path, "fn main() { println!(\"Modified\"); }")
+            .await
+            .unwrap();
+        smol::fs::write(&bin_path, "Modified binary file")
+            .await
+            .unwrap();
+
+        repo.restore_checkpoint(checkpoint).await.unwrap();
+
+        // Text files should be restored to checkpoint state,
+        // but binaries should not (they aren't tracked)
+        assert_eq!(
+            smol::fs::read_to_string(&text_path).await.unwrap(),
+            "fn main() {}"
+        );
+
+        assert_eq!(
+            smol::fs::read_to_string(&bin_path).await.unwrap(),
+            "Modified binary file"
+        );
+    }
+
+    #[test]
+    fn test_branches_parsing() {
+        // suppress "help: octal escapes are not supported, `\0` is always null"
+        #[allow(clippy::octal_escapes)]
+        let input = "*\0060964da10574cd9bf06463a53bf6e0769c5c45e\0\0refs/heads/zed-patches\0refs/remotes/origin/zed-patches\0\01733187470\0John Doe\0generated protobuf\n";
+        assert_eq!(
+            parse_branch_input(input).unwrap(),
+            vec![Branch {
+                is_head: true,
+                ref_name: "refs/heads/zed-patches".into(),
+                upstream: Some(Upstream {
+                    ref_name: "refs/remotes/origin/zed-patches".into(),
+                    tracking: UpstreamTracking::Tracked(UpstreamTrackingStatus {
+                        ahead: 0,
+                        behind: 0
+                    })
+                }),
+                most_recent_commit: Some(CommitSummary {
+                    sha: "060964da10574cd9bf06463a53bf6e0769c5c45e".into(),
+                    subject: "generated protobuf".into(),
+                    commit_timestamp: 1733187470,
+                    author_name: SharedString::new("John Doe"),
+                    has_parent: false,
+                })
+            }]
+        )
+    }
+
+    #[gpui::test]
+    async fn test_log(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+
+        let commits = [
+            ("a.txt", "one\ntwo\nthree\n", "Add a", "Ada"),
+            ("b.txt", "b\n", "Add b", "Grace"),
+            ("a.txt", "one\ntwo\nTHREE\n", "Edit the end of a", "Ada"),
+            ("a.txt", "ONE\ntwo\nTHREE\n", "Edit the start of a", "Grace"),
+        ];
+        for (path, content, message, author) in commits {
+            smol::fs::write(repo_dir.path().join(path), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![repo_path(path)], Arc::new(HashMap::default()))
+                .await
+                .unwrap();
+            let mut env = checkpoint_author_envs();
+            env.insert("GIT_AUTHOR_NAME".into(), author.into());
+            repo.commit(
+                message.into(),
+                None,
+                CommitOptions::default(),
+                Arc::new(env),
+            )
+            .await
+            .unwrap();
+        }
+
+        let subjects = async |options: LogOptions| {
+            repo.log(LogOptions {
+                limit: 10,
+                ..options
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|commit| commit.subject.to_string())
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            subjects(LogOptions::default()).await,
+            ["Edit the start of a", "Edit the end of a", "Add b", "Add a"]
+        );
+
+        let page = repo
+            .log(LogOptions {
+                skip: 2,
+                limit: 1,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].subject.as_ref(), "Add b");
+        assert_eq!(page[0].author_name.as_ref(), "Grace");
+        assert!(page[0].has_parent);
+
+        assert_eq!(
+            subjects(LogOptions {
+                author: Some("Ada".into()),
+                ..Default::default()
+            })
+            .await,
+            ["Edit the end of a", "Add a"]
+        );
+        assert_eq!(
+            subjects(LogOptions {
+                path: Some(repo_path("b.txt")),
+                ..Default::default()
+            })
+            .await,
+            ["Add b"]
+        );
+        assert_eq!(
+            subjects(LogOptions {
+                path: Some(repo_path("a.txt")),
+                line_range: Some(3..=3),
+                ..Default::default()
+            })
+            .await,
+            ["Edit the end of a", "Add a"]
+        );
+
+        let initial_commit = repo.log(LogOptions {
+            skip: 3,
+            limit: 1,
+            ..Default::default()
+        });
+        assert!(!initial_commit.await.unwrap()[0].has_parent);
+
+        assert!(
+            repo.log(LogOptions {
+                branch: Some("--output=out".into()),
+                limit: 1,
+                ..Default::default()
+            })
+            .await
+            .is_err()
+        );
+        assert!(!repo_dir.path().join("out").exists());
+    }
+
+    #[gpui::test]
+    async fn test_delete_branch_and_merge_squash(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        smol::fs::write(repo_dir.path().join("a.txt"), "a\n")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![repo_path("a.txt")], env.clone())
+            .await
+            .unwrap();
+        repo.commit("Add a".into(), None, CommitOptions::default(), env.clone())
+            .await
+            .unwrap();
+        repo.create_branch("feature".into()).await.unwrap();
+
+        assert!(repo.delete_branch("--all".into(), true).await.is_err());
+        assert!(
+            repo.merge_squash("--no-verify".into(), env.clone())
+                .await
+                .is_err()
+        );
+        repo.delete_branch("feature".into(), true).await.unwrap();
+        assert!(
+            repo.branches()
+                .await
+                .unwrap()
+                .iter()
+                .all(|branch| branch.name() != "feature")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rebase_interactive(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        let commit = async |path: &str, content: &str, message: &str| {
+            smol::fs::write(repo_dir.path().join(path), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![repo_path(path)], env.clone())
+                .await
+                .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+            repo.head_sha().await.unwrap()
+        };
+        let subjects = async || {
+            repo.log(LogOptions {
+                limit: 10,
+                ..Default::default()
             })
-        }
-    }
-}
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|commit| commit.subject.to_string())
+            .collect::<Vec<_>>()
+        };
+        let entry = |action, sha: &str| RebaseTodoEntry {
+            action,
+            sha: sha.to_string().into(),
+            message: None,
+        };
 
-#[derive(Clone, Debug, Ord, Hash, PartialOrd, Eq, PartialEq)]
-pub struct RepoPath(pub Arc<RelPath>);
+        let base = commit("a.txt", "one\n", "Add a").await;
+        let add_b = commit("b.txt", "b\n", "Add b").await;
+        let edit_a = commit("a.txt", "two\n", "Edit a").await;
+        let add_c = commit("c.txt", "c\n", "Add c").await;
+
+        // Reorder, reword and fixup commits.
+        repo.rebase_interactive(
+            base.clone(),
+            vec![
+                entry(RebaseAction::Pick, &add_c),
+                RebaseTodoEntry {
+                    message: Some("Add b and edit a\n\nWith a body".into()),
+                    ..entry(RebaseAction::Reword, &add_b)
+                },
+                entry(RebaseAction::Fixup, &edit_a),
+            ],
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(subjects().await, ["Add b and edit a", "Add c", "Add a"]);
+        let head = repo.show("HEAD".into()).await.unwrap();
+        assert_eq!(head.message.trim_end(), "Add b and edit a\n\nWith a body");
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("a.txt"))
+                .await
+                .unwrap(),
+            "two\n"
+        );
+        assert!(!repo_dir.path().join(".git").join(REBASE_TODO_DIR).exists());
+
+        let error = repo
+            .rebase_interactive(
+                base.clone(),
+                vec![entry(RebaseAction::Squash, &head.sha)],
+                env.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the first commit can't be melded into a previous one"
+        );
 
-impl RepoPath {
-    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> Result<Self> {
-        let rel_path = RelPath::unix(s.as_ref())?;
-        Ok(rel_path.into())
-    }
+        let error = repo
+            .rebase_interactive(
+                base.clone(),
+                vec![entry(
+                    RebaseAction::Pick,
+                    &format!("{}\nexec touch out", head.sha),
+                )],
+                env.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with("invalid commit sha"));
+        repo.rebase_interactive(
+            "--exec=touch out".into(),
+            vec![entry(RebaseAction::Pick, &head.sha)],
+            env.clone(),
+        )
+        .await
+        .unwrap_err();
+        assert!(!repo_dir.path().join("out").exists());
 
-    pub fn from_proto(proto: &str) -> Result<Self> {
-        let rel_path = RelPath::from_proto(proto)?;
-        Ok(rel_path.into())
-    }
+        // Dropping a commit that a later one builds on stops on a conflict.
+        let edit_a_again = commit("a.txt", "three\n", "Edit a again").await;
+        let [Some(add_c), Some(edit_a)] = &repo
+            .revparse_batch(vec!["HEAD~2".into(), "HEAD~1".into()])
+            .await
+            .unwrap()[..]
+        else {
+            panic!("missing commits");
+        };
+        let rebase_with_conflict = async || {
+            repo.rebase_interactive(
+                base.clone(),
+                vec![
+                    entry(RebaseAction::Pick, add_c),
+                    entry(RebaseAction::Drop, edit_a),
+                    entry(RebaseAction::Pick, &edit_a_again),
+                ],
+                env.clone(),
+            )
+            .await
+        };
+        rebase_with_conflict().await.unwrap_err();
+        let status = repo.status(&[]).await.unwrap();
+        assert_eq!(
+            status
+                .entries
+                .iter()
+                .filter(|(_, status)| status.is_conflicted())
+                .map(|(path, _)| path.as_unix_str())
+                .collect::<Vec<_>>(),
+            ["a.txt"]
+        );
 
-    pub fn from_std_path(path: &Path, path_style: PathStyle) -> Result<Self> {
-        let rel_path = RelPath::new(path, path_style)?;
-        Ok(Self(rel_path.as_ref().into()))
+        repo.rebase_command(RebaseCommand::Abort, env.clone())
+            .await
+            .unwrap();
+        assert_eq!(repo.head_sha().await.unwrap(), edit_a_again);
+
+        rebase_with_conflict().await.unwrap_err();
+        smol::fs::write(repo_dir.path().join("a.txt"), "three\n")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![repo_path("a.txt")], env.clone())
+            .await
+            .unwrap();
+        repo.rebase_command(RebaseCommand::Continue, env.clone())
+            .await
+            .unwrap();
+        assert_eq!(subjects().await, ["Edit a again", "Add c", "Add a"]);
     }
-}
 
-#[cfg(any(test, feature = "test-support"))]
-pub fn repo_path<S: AsRef<str> + ?Sized>(s: &S) -> RepoPath {
-    RepoPath(RelPath::unix(s.as_ref()).unwrap().into())
-}
+    #[gpui::test]
+    async fn test_lfs_status_and_metadata(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
 
-impl From<&RelPath> for RepoPath {
-    fn from(value: &RelPath) -> Self {
-        RepoPath(value.into())
-    }
-}
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        // 8-bit mono audio at 8kHz.
+        let wav = |seconds: u32| {
+            let mut wav = Vec::new();
+            wav.extend(b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x01\0");
+            wav.extend(8000u32.to_le_bytes());
+            wav.extend(8000u32.to_le_bytes());
+            wav.extend(b"\x01\0\x08\0data");
+            wav.extend((seconds * 8000).to_le_bytes());
+            wav.resize(wav.len() + (seconds * 8000) as usize, 0x80);
+            wav
+        };
 
-impl<'a> From<Cow<'a, RelPath>> for RepoPath {
-    fn from(value: Cow<'a, RelPath>) -> Self {
-        value.as_ref().into()
-    }
-}
+        // Commit a pointer to a downloaded object, without needing Git LFS
+        // to be installed.
+        let oid = "a".repeat(64);
+        let pointer = LfsPointer {
+            oid: oid.clone(),
+            size: 16044,
+        };
+        let object_path = pointer.object_path(&repo_dir.path().join(".git"));
+        smol::fs::create_dir_all(object_path.parent().unwrap())
+            .await
+            .unwrap();
+        smol::fs::write(&object_path, wav(2)).await.unwrap();
+        smol::fs::write(
+            repo_dir.path().join(".gitattributes"),
+            "*.wav filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .await
+        .unwrap();
+        smol::fs::write(
+            repo_dir.path().join("drums.wav"),
+            format!(
+                "{}\noid sha256:{oid}\nsize {}\n",
+                crate::lfs::LFS_POINTER_VERSION,
+                pointer.size
+            ),
+        )
+        .await
+        .unwrap();
+        repo.stage_paths(
+            vec![repo_path(".gitattributes"), repo_path("drums.wav")],
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        repo.commit(
+            "Add drums".into(),
+            None,
+            CommitOptions::default(),
+            env.clone(),
+        )
+        .await
+        .unwrap();
 
-impl From<Arc<RelPath>> for RepoPath {
-    fn from(value: Arc<RelPath>) -> Self {
-        RepoPath(value)
+        smol::fs::write(repo_dir.path().join("drums.wav"), wav(3))
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("notes.txt"), "new\n")
+            .await
+            .unwrap();
+        let status = repo.status(&[RelPath::empty().into()]).await.unwrap();
+        assert_eq!(status.entries.len(), 2);
+        assert_eq!(&*status.lfs_paths, [repo_path("drums.wav")]);
+
+        let metadata = repo.lfs_metadata(repo_path("drums.wav")).await.unwrap();
+        let head = metadata.head.unwrap();
+        assert_eq!(head.oid, Some(oid));
+        assert_eq!(head.size, 16044);
+        assert_eq!(
+            head.audio.map(|audio| audio.duration),
+            Some(std::time::Duration::from_secs(2))
+        );
+        let worktree = metadata.worktree.unwrap();
+        assert_eq!(worktree.size, 24044);
+        assert_eq!(
+            worktree.audio.map(|audio| audio.duration),
+            Some(std::time::Duration::from_secs(3))
+        );
+
+        let metadata = repo.lfs_metadata(repo_path("missing.wav")).await.unwrap();
+        assert_eq!(metadata, LfsDiffMetadata::default());
     }
-}
 
-impl Default for RepoPath {
-    fn default() -> Self {
-        RepoPath(RelPath::empty().into())
+    #[test]
+    fn test_tags_parsing() {
+        // suppress "help: octal escapes are not supported, `\0` is always null"
+        #[allow(clippy::octal_escapes)]
+        let input = "v2\0commit\0060964da10574cd9bf06463a53bf6e0769c5c45e\0\0\01733187470\0generated protobuf\0\n\
+            v1\0tag\0a1b2c3d4e5f60718293a4b5c6d7e8f9012345678\0e3b0c44298fc1c149afbf4c8996fb92427ae41e4\0commit\01733100000\0First release\0Initial commit\n\
+            tree-tag\0tag\0b1b2c3d4e5f60718293a4b5c6d7e8f9012345678\0f3b0c44298fc1c149afbf4c8996fb92427ae41e4\0tree\01733000000\0A tree\0\n";
+        assert_eq!(
+            parse_tag_input(input).unwrap(),
+            vec![
+                Tag {
+                    name: "v2".into(),
+                    sha: "060964da10574cd9bf06463a53bf6e0769c5c45e".into(),
+                    subject: "generated protobuf".into(),
+                    message: None,
+                    timestamp: 1733187470,
+                },
+                Tag {
+                    name: "v1".into(),
+                    sha: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4".into(),
+                    subject: "Initial commit".into(),
+                    message: Some("First release".into()),
+                    timestamp: 1733100000,
+                },
+            ]
+        )
     }
-}
 
-impl std::ops::Deref for RepoPath {
-    type Target = RelPath;
+    #[gpui::test]
+    async fn test_cherry_pick_revert_and_tags(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        let env = Arc::new(checkpoint_author_envs());
 
-// impl AsRef<Path> for RepoPath {
-//     fn as_ref(&self) -> &Path {
-//         RelPath::as_ref(&self.0)
-//     }
-// }
+        let commit = async |path: &str, content: &str, message: &str| {
+            smol::fs::write(repo_dir.path().join(path), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![repo_path(path)], env.clone())
+                .await
+                .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+            repo.head_sha().await.unwrap()
+        };
+        let subjects = async || {
+            repo.log(LogOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|commit| commit.subject.to_string())
+            .collect::<Vec<_>>()
+        };
 
-#[derive(Debug)]
-pub struct RepoPathDescendants<'a>(pub &'a RepoPath);
+        let base = commit("a.txt", "one\n", "Add a").await;
+        repo.create_branch("feature".into()).await.unwrap();
+        let add_b = commit("b.txt", "b\n", "Add b").await;
+        let edit_a = commit("a.txt", "two\n", "Edit a").await;
+        repo.change_branch("feature".into()).await.unwrap();
 
-impl MapSeekTarget<RepoPath> for RepoPathDescendants<'_> {
-    fn cmp_cursor(&self, key: &RepoPath) -> Ordering {
-        if key.starts_with(self.0) {
-            Ordering::Greater
-        } else {
-            self.0.cmp(key)
-        }
-    }
-}
+        repo.cherry_pick(add_b, env.clone()).await.unwrap();
+        assert_eq!(subjects().await, ["Add b", "Add a"]);
+        assert!(repo_dir.path().join("b.txt").exists());
 
-fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
-    let mut branches = Vec::new();
-    for line in input.split('\n') {
-        if line.is_empty() {
-            continue;
-        }
-        let mut fields = line.split('\x00');
-        let is_current_branch = fields.next().context("no HEAD")? == "*";
-        let head_sha: SharedString = fields.next().context("no objectname")?.to_string().into();
-        let parent_sha: SharedString = fields.next().context("no parent")?.to_string().into();
-        let ref_name = fields.next().context("no refname")?.to_string().into();
-        let upstream_name = fields.next().context("no upstream")?.to_string();
-        let upstream_tracking = parse_upstream_track(fields.next().context("no upstream:track")?)?;
-        let commiterdate = fields.next().context("no committerdate")?.parse::<i64>()?;
-        let author_name = fields.next().context("no authorname")?.to_string().into();
-        let subject: SharedString = fields
-            .next()
-            .context("no contents:subject")?
-            .to_string()
-            .into();
+        repo.revert("HEAD".into(), env.clone()).await.unwrap();
+        assert_eq!(subjects().await, ["Revert \"Add b\"", "Add b", "Add a"]);
+        assert!(!repo_dir.path().join("b.txt").exists());
 
-        branches.push(Branch {
-            is_head: is_current_branch,
-            ref_name,
-            most_recent_commit: Some(CommitSummary {
-                sha: head_sha,
-                subject,
-                commit_timestamp: commiterdate,
-                author_name: author_name,
-                has_parent: !parent_sha.is_empty(),
-            }),
-            upstream: if upstream_name.is_empty() {
-                None
-            } else {
-                Some(Upstream {
-                    ref_name: upstream_name.into(),
-                    tracking: upstream_tracking,
-                })
-            },
-        })
-    }
+        // A conflicting cherry-pick stays in progress until it's committed.
+        commit("a.txt", "three\n", "Edit a differently").await;
+        repo.cherry_pick(edit_a.clone(), env.clone())
+            .await
+            .unwrap_err();
+        let status = repo.status(&[]).await.unwrap();
+        assert!(
+            status
+                .entries
+                .iter()
+                .any(|(path, status)| { path.as_unix_str() == "a.txt" && status.is_conflicted() })
+        );
+        assert_eq!(
+            repo.revparse_batch(vec!["CHERRY_PICK_HEAD".into()])
+                .await
+                .unwrap(),
+            [Some(edit_a)]
+        );
+        commit("a.txt", "two\n", "Edit a").await;
+        assert_eq!(
+            repo.revparse_batch(vec!["CHERRY_PICK_HEAD".into()])
+                .await
+                .unwrap(),
+            [None]
+        );
 
-    Ok(branches)
-}
+        repo.create_tag("v2".into(), None, None, env.clone())
+            .await
+            .unwrap();
+        repo.create_tag(
+            "v1".into(),
+            Some(base.clone()),
+            Some("First release".into()),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        let mut tags = repo.tags().await.unwrap();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            tags.iter()
+                .map(|tag| (
+                    tag.name.as_ref(),
+                    tag.sha.as_ref(),
+                    tag.subject.as_ref(),
+                    tag.message.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("v1", base.as_str(), "Add a", Some("First release")),
+                (
+                    "v2",
+                    repo.head_sha().await.unwrap().as_str(),
+                    "Edit a",
+                    None
+                ),
+            ]
+        );
 
-fn parse_upstream_track(upstream_track: &str) -> Result<UpstreamTracking> {
-    if upstream_track.is_empty() {
-        return Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
-            ahead: 0,
-            behind: 0,
-        }));
+        repo.delete_tag("v2".into()).await.unwrap();
+        assert_eq!(
+            repo.tags()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|tag| tag.name)
+                .collect::<Vec<_>>(),
+            ["v1"]
+        );
+
+        assert!(
+            repo.cherry_pick("--abort".into(), env.clone())
+                .await
+                .is_err()
+        );
+        assert!(repo.revert("--quit".into(), env.clone()).await.is_err());
+        assert!(
+            repo.create_tag("-f".into(), Some("v1".into()), None, env.clone())
+                .await
+                .is_err()
+        );
+        assert!(
+            repo.create_tag("v1..v2".into(), None, None, env.clone())
+                .await
+                .is_err()
+        );
+        assert!(repo.delete_tag("--list".into()).await.is_err());
+        assert_eq!(repo.tags().await.unwrap().len(), 1);
     }
 
-    let upstream_track = upstream_track.strip_prefix("[").context("missing [")?;
-    let upstream_track = upstream_track.strip_suffix("]").context("missing [")?;
-    let mut ahead: u32 = 0;
-    let mut behind: u32 = 0;
-    for component in upstream_track.split(", ") {
-        if component == "gone" {
-            return Ok(UpstreamTracking::Gone);
-        }
-        if let Some(ahead_num) = component.strip_prefix("ahead ") {
-            ahead = ahead_num.parse::<u32>()?;
-        }
-        if let Some(behind_num) = component.strip_prefix("behind ") {
-            behind = behind_num.parse::<u32>()?;
+    #[gpui::test]
+    async fn test_bisect(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        let mut shas = Vec::new();
+        for ix in 0..8 {
+            smol::fs::write(repo_dir.path().join("level.txt"), ix.to_string())
+                .await
+                .unwrap();
+            repo.stage_paths(vec![repo_path("level.txt")], env.clone())
+                .await
+                .unwrap();
+            repo.commit(
+                format!("Set level to {ix}").into(),
+                None,
+                CommitOptions::default(),
+                env.clone(),
+            )
+            .await
+            .unwrap();
+            shas.push(repo.head_sha().await.unwrap());
         }
-    }
-    Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
-        ahead,
-        behind,
-    }))
-}
+        let culprit = &shas[5];
 
-fn checkpoint_author_envs() -> HashMap<String, String> {
-    HashMap::from_iter([
-        ("GIT_AUTHOR_NAME".to_string(), "Zed".to_string()),
-        ("GIT_AUTHOR_EMAIL".to_string(), "hi@zed.dev".to_string()),
-        ("GIT_COMMITTER_NAME".to_string(), "Zed".to_string()),
-        ("GIT_COMMITTER_EMAIL".to_string(), "hi@zed.dev".to_string()),
-    ])
-}
+        assert!(
+            repo.bisect_start("--no-checkout".into(), shas[0].clone(), env.clone())
+                .await
+                .is_err()
+        );
+        assert_eq!(repo.bisect_state().await.unwrap(), None);
+        repo.bisect_start(shas[7].clone(), shas[0].clone(), env.clone())
+            .await
+            .unwrap();
+        let state = repo.bisect_state().await.unwrap().unwrap();
+        assert_eq!(state.bad.as_deref(), Some(shas[7].as_str()));
+        assert_eq!(state.good, [SharedString::from(shas[0].clone())]);
+        assert_eq!(state.remaining.len(), 7);
+        assert_eq!(state.culprit(), None);
+
+        // The regression is in every commit from the culprit onwards.
+        let mut state = state;
+        while state.culprit().is_none() {
+            let head = repo.head_sha().await.unwrap();
+            let ix = shas.iter().position(|sha| *sha == head).unwrap();
+            let mark = if ix >= 5 {
+                BisectMark::Bad
+            } else {
+                BisectMark::Good
+            };
+            repo.bisect_mark(mark, env.clone()).await.unwrap();
+            state = repo.bisect_state().await.unwrap().unwrap();
+        }
+        assert_eq!(state.culprit().unwrap().sha.as_ref(), culprit.as_str());
+        assert_eq!(state.culprit().unwrap().subject.as_ref(), "Set level to 5");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use gpui::TestAppContext;
+        repo.bisect_reset(env.clone()).await.unwrap();
+        assert_eq!(repo.bisect_state().await.unwrap(), None);
+        assert_eq!(repo.head_sha().await.unwrap(), shas[7]);
+    }
 
     #[gpui::test]
-    async fn test_checkpoint_basic(cx: &mut TestAppContext) {
+    async fn test_diff_refs(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
 
         let repo_dir = tempfile::tempdir().unwrap();
-
         git2::Repository::init(repo_dir.path()).unwrap();
-        let file_path = repo_dir.path().join("file");
-        smol::fs::write(&file_path, "initial").await.unwrap();
-
         let repo = RealGitRepository::new(
             &repo_dir.path().join(".git"),
             None,
@@ -2284,76 +4657,102 @@ mod tests {
             cx.executor(),
         )
         .unwrap();
-        repo.stage_paths(vec![repo_path("file")], Arc::new(HashMap::default()))
+        let env = Arc::new(checkpoint_author_envs());
+
+        smol::fs::write(repo_dir.path().join("a.txt"), "one\n")
+            .await
+            .unwrap();
+        smol::fs::write(repo_dir.path().join("b.txt"), "two\n")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![repo_path("a.txt"), repo_path("b.txt")], env.clone())
             .await
             .unwrap();
         repo.commit(
-            "Initial commit".into(),
+            "Initial".into(),
             None,
             CommitOptions::default(),
-            Arc::new(checkpoint_author_envs()),
+            env.clone(),
         )
         .await
         .unwrap();
+        let base = repo.head_sha().await.unwrap();
 
-        smol::fs::write(&file_path, "modified before checkpoint")
+        repo.create_branch("feature".into()).await.unwrap();
+        repo.change_branch("feature".into()).await.unwrap();
+        smol::fs::write(repo_dir.path().join("a.txt"), "one\nmore\n")
             .await
             .unwrap();
-        smol::fs::write(repo_dir.path().join("new_file_before_checkpoint"), "1")
-            .await
-            .unwrap();
-        let checkpoint = repo.checkpoint().await.unwrap();
-
-        // Ensure the user can't see any branches after creating a checkpoint.
-        assert_eq!(repo.branches().await.unwrap().len(), 1);
-
-        smol::fs::write(&file_path, "modified after checkpoint")
+        smol::fs::remove_file(repo_dir.path().join("b.txt"))
             .await
             .unwrap();
-        repo.stage_paths(vec![repo_path("file")], Arc::new(HashMap::default()))
+        smol::fs::write(repo_dir.path().join("c.txt"), "three\n")
             .await
             .unwrap();
+        repo.stage_paths(
+            vec![repo_path("a.txt"), repo_path("b.txt"), repo_path("c.txt")],
+            env.clone(),
+        )
+        .await
+        .unwrap();
         repo.commit(
-            "Commit after checkpoint".into(),
+            "Feature".into(),
             None,
             CommitOptions::default(),
-            Arc::new(checkpoint_author_envs()),
+            env.clone(),
         )
         .await
         .unwrap();
+        let head = repo.head_sha().await.unwrap();
 
-        smol::fs::remove_file(repo_dir.path().join("new_file_before_checkpoint"))
-            .await
-            .unwrap();
-        smol::fs::write(repo_dir.path().join("new_file_after_checkpoint"), "2")
-            .await
-            .unwrap();
-
-        // Ensure checkpoint stays alive even after a Git GC.
-        repo.gc().await.unwrap();
-        repo.restore_checkpoint(checkpoint.clone()).await.unwrap();
-
-        assert_eq!(
-            smol::fs::read_to_string(&file_path).await.unwrap(),
-            "modified before checkpoint"
-        );
         assert_eq!(
-            smol::fs::read_to_string(repo_dir.path().join("new_file_before_checkpoint"))
+            repo.merge_base("feature".into(), base.clone())
                 .await
                 .unwrap(),
-            "1"
+            Some(base.clone())
+        );
+
+        assert!(
+            repo.merge_base("--output=out".into(), base.clone())
+                .await
+                .is_err()
+        );
+        assert!(
+            repo.diff_refs("--output=out".into(), head.clone(), cx.to_async())
+                .await
+                .is_err()
+        );
+        assert!(
+            repo.diff_refs(base.clone(), "HEAD\nHEAD".into(), cx.to_async())
+                .await
+                .is_err()
+        );
+        assert!(!repo_dir.path().join("out").exists());
+
+        let diff = repo.diff_refs(base, head, cx.to_async()).await.unwrap();
+        let files = diff
+            .files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.as_unix_str(),
+                    file.old_text.as_deref(),
+                    file.new_text.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            [
+                ("a.txt", Some("one\n"), Some("one\nmore\n")),
+                ("b.txt", Some("two\n"), None),
+                ("c.txt", None, Some("three\n")),
+            ]
         );
-        // See TODO above
-        // assert_eq!(
-        //     smol::fs::read_to_string(repo_dir.path().join("new_file_after_checkpoint"))
-        //         .await
-        //         .ok(),
-        //     None
-        // );
     }
 
     #[gpui::test]
-    async fn test_checkpoint_empty_repo(cx: &mut TestAppContext) {
+    async fn test_load_merge_stages(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
 
         let repo_dir = tempfile::tempdir().unwrap();
@@ -2365,92 +4764,80 @@ mod tests {
             cx.executor(),
         )
         .unwrap();
+        let env = Arc::new(checkpoint_author_envs());
 
-        smol::fs::write(repo_dir.path().join("foo"), "foo")
-            .await
-            .unwrap();
-        let checkpoint_sha = repo.checkpoint().await.unwrap();
-
-        // Ensure the user can't see any branches after creating a checkpoint.
-        assert_eq!(repo.branches().await.unwrap().len(), 1);
-
-        smol::fs::write(repo_dir.path().join("foo"), "bar")
+        let commit_file = async |content: &str, message: &str| {
+            smol::fs::write(repo_dir.path().join("file.txt"), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![repo_path("file.txt")], env.clone())
+                .await
+                .unwrap();
+            repo.commit(
+                message.to_string().into(),
+                None,
+                CommitOptions::default(),
+                env.clone(),
+            )
             .await
             .unwrap();
-        smol::fs::write(repo_dir.path().join("baz"), "qux")
-            .await
+        };
+        commit_file("one\n", "Base").await;
+        repo.create_branch("ours".into()).await.unwrap();
+        repo.create_branch("theirs".into()).await.unwrap();
+        repo.change_branch("theirs".into()).await.unwrap();
+        commit_file("three\n", "Theirs").await;
+        repo.change_branch("ours".into()).await.unwrap();
+        commit_file("two\n", "Ours").await;
+
+        let output = std::process::Command::new("git")
+            .current_dir(repo_dir.path())
+            .envs(env.iter())
+            .args(["merge", "theirs"])
+            .output()
             .unwrap();
-        repo.restore_checkpoint(checkpoint_sha).await.unwrap();
+        assert!(!output.status.success());
+
         assert_eq!(
-            smol::fs::read_to_string(repo_dir.path().join("foo"))
-                .await
-                .unwrap(),
-            "foo"
+            repo.load_merge_stages(repo_path("file.txt")).await.unwrap(),
+            MergeStages {
+                base: Some("one\n".into()),
+                ours: Some("two\n".into()),
+                theirs: Some("three\n".into()),
+            }
         );
-        // See TODOs above
-        // assert_eq!(
-        //     smol::fs::read_to_string(repo_dir.path().join("baz"))
-        //         .await
-        //         .ok(),
-        //     None
-        // );
     }
 
     #[gpui::test]
-    async fn test_compare_checkpoints(cx: &mut TestAppContext) {
+    async fn test_submodules(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
 
-        let repo_dir = tempfile::tempdir().unwrap();
-        git2::Repository::init(repo_dir.path()).unwrap();
-        let repo = RealGitRepository::new(
-            &repo_dir.path().join(".git"),
+        let env = Arc::new(checkpoint_author_envs());
+        let sdk_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(sdk_dir.path()).unwrap();
+        let sdk = RealGitRepository::new(
+            &sdk_dir.path().join(".git"),
             None,
             Some("git".into()),
             cx.executor(),
         )
         .unwrap();
-
-        smol::fs::write(repo_dir.path().join("file1"), "content1")
-            .await
-            .unwrap();
-        let checkpoint1 = repo.checkpoint().await.unwrap();
-
-        smol::fs::write(repo_dir.path().join("file2"), "content2")
-            .await
-            .unwrap();
-        let checkpoint2 = repo.checkpoint().await.unwrap();
-
-        assert!(
-            !repo
-                .compare_checkpoints(checkpoint1, checkpoint2.clone())
+        let mut sdk_shas = Vec::new();
+        for (content, message) in [("1\n", "Add the SDK"), ("2\n", "Bump the SDK")] {
+            smol::fs::write(sdk_dir.path().join("version.txt"), content)
                 .await
-                .unwrap()
-        );
-
-        let checkpoint3 = repo.checkpoint().await.unwrap();
-        assert!(
-            repo.compare_checkpoints(checkpoint2, checkpoint3)
+                .unwrap();
+            sdk.stage_paths(vec![repo_path("version.txt")], env.clone())
                 .await
-                .unwrap()
-        );
-    }
-
-    #[gpui::test]
-    async fn test_checkpoint_exclude_binary_files(cx: &mut TestAppContext) {
-        cx.executor().allow_parking();
+                .unwrap();
+            sdk.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+            sdk_shas.push(sdk.head_sha().await.unwrap());
+        }
 
         let repo_dir = tempfile::tempdir().unwrap();
-        let text_path = repo_dir.path().join("main.rs");
-        let bin_path = repo_dir.path().join("binary.o");
-
         git2::Repository::init(repo_dir.path()).unwrap();
-
-        smol::fs::write(&text_path, "fn main() {}").await.unwrap();
-
-        smol::fs::write(&bin_path, "some binary file here")
-            .await
-            .unwrap();
-
         let repo = RealGitRepository::new(
             &repo_dir.path().join(".git"),
             None,
@@ -2458,70 +4845,83 @@ mod tests {
             cx.executor(),
         )
         .unwrap();
+        assert_eq!(repo.submodules().await.unwrap(), []);
 
-        // initial commit
-        repo.stage_paths(vec![repo_path("main.rs")], Arc::new(HashMap::default()))
-            .await
-            .unwrap();
+        let git = |dir: &Path, args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .current_dir(dir)
+                .envs(env.iter())
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        };
+        git(
+            repo_dir.path(),
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                sdk_dir.path().to_str().unwrap(),
+                "sdk",
+            ],
+        );
         repo.commit(
-            "Initial commit".into(),
+            "Add the SDK".into(),
             None,
             CommitOptions::default(),
-            Arc::new(checkpoint_author_envs()),
+            env.clone(),
         )
         .await
         .unwrap();
 
-        let checkpoint = repo.checkpoint().await.unwrap();
-
-        smol::fs::write(&text_path, "fn main() { println!(\"Modified\"); }")
-            .await
-            .unwrap();
-        smol::fs::write(&bin_path, "Modified binary file")
-            .await
-            .unwrap();
-
-        repo.restore_checkpoint(checkpoint).await.unwrap();
-
-        // Text files should be restored to checkpoint state,
-        // but binaries should not (they aren't tracked)
+        let submodules = repo.submodules().await.unwrap();
         assert_eq!(
-            smol::fs::read_to_string(&text_path).await.unwrap(),
-            "fn main() {}"
+            submodules,
+            [Submodule {
+                path: repo_path("sdk"),
+                sha: sdk_shas[1].clone().into(),
+                status: SubmoduleStatus::UpToDate,
+                dirty: false,
+            }]
         );
 
+        let sdk_checkout = repo_dir.path().join("sdk");
+        git(&sdk_checkout, &["checkout", "--quiet", &sdk_shas[0]]);
+        let submodules = repo.submodules().await.unwrap();
+        assert_eq!(submodules[0].status, SubmoduleStatus::OutOfDate);
+        assert_eq!(submodules[0].sha.as_ref(), sdk_shas[0].as_str());
+        let range = repo.submodule_range(repo_path("sdk")).await.unwrap();
+        assert_eq!(range.old.as_deref(), Some(sdk_shas[1].as_str()));
+        assert_eq!(range.new.as_deref(), Some(sdk_shas[0].as_str()));
+        assert_eq!(range.added, []);
         assert_eq!(
-            smol::fs::read_to_string(&bin_path).await.unwrap(),
-            "Modified binary file"
+            range
+                .removed
+                .iter()
+                .map(|commit| commit.subject.as_ref())
+                .collect::<Vec<_>>(),
+            ["Bump the SDK"]
         );
-    }
 
-    #[test]
-    fn test_branches_parsing() {
-        // suppress "help: octal escapes are not supported, `\0` is always null"
-        #[allow(clippy::octal_escapes)]
-        let input = "*\0060964da10574cd9bf06463a53bf6e0769c5c45e\0\0refs/heads/zed-patches\0refs/remotes/origin/zed-patches\0\01733187470\0John Doe\0generated protobuf\n";
-        assert_eq!(
-            parse_branch_input(input).unwrap(),
-            vec![Branch {
-                is_head: true,
-                ref_name: "refs/heads/zed-patches".into(),
-                upstream: Some(Upstream {
-                    ref_name: "refs/remotes/origin/zed-patches".into(),
-                    tracking: UpstreamTracking::Tracked(UpstreamTrackingStatus {
-                        ahead: 0,
-                        behind: 0
-                    })
-                }),
-                most_recent_commit: Some(CommitSummary {
-                    sha: "060964da10574cd9bf06463a53bf6e0769c5c45e".into(),
-                    subject: "generated protobuf".into(),
-                    commit_timestamp: 1733187470,
-                    author_name: SharedString::new("John Doe"),
-                    has_parent: false,
-                })
-            }]
-        )
+        repo.submodule_command(SubmoduleCommand::Update, Vec::new(), env.clone())
+            .await
+            .unwrap();
+        let submodules = repo.submodules().await.unwrap();
+        assert_eq!(submodules[0].status, SubmoduleStatus::UpToDate);
+        assert!(!submodules[0].dirty);
+
+        smol::fs::write(sdk_checkout.join("version.txt"), "3\n")
+            .await
+            .unwrap();
+        let submodules = repo.submodules().await.unwrap();
+        assert_eq!(submodules[0].status, SubmoduleStatus::UpToDate);
+        assert!(submodules[0].dirty);
     }
 
     impl RealGitRepository {
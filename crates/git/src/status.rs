@@ -424,6 +424,8 @@ impl std::ops::Sub for GitSummary {
 #[derive(Clone, Debug)]
 pub struct GitStatus {
     pub entries: Arc<[(RepoPath, FileStatus)]>,
+    /// The paths among `entries` that are tracked by Git LFS, in sorted order.
+    pub lfs_paths: Arc<[RepoPath]>,
 }
 
 impl FromStr for GitStatus {
@@ -475,6 +477,7 @@ impl FromStr for GitStatus {
         });
         Ok(Self {
             entries: entries.into(),
+            lfs_paths: Arc::new([]),
         })
     }
 }
@@ -483,6 +486,7 @@ impl Default for GitStatus {
     fn default() -> Self {
         Self {
             entries: Arc::new([]),
+            lfs_paths: Arc::new([]),
         }
     }
 }
@@ -0,0 +1,263 @@
+use crate::SHORT_SHA_LENGTH;
+use crate::repository::{CommitSummary, RepoPath};
+use anyhow::{Context as _, Result};
+use gpui::SharedString;
+use std::fmt::Write as _;
+
+/// The state of a submodule's checkout, as reported by `git submodule status`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubmoduleStatus {
+    /// The submodule hasn't been cloned into the working tree.
+    Uninitialized,
+    /// The checked out commit is the one recorded in the index.
+    UpToDate,
+    /// The checked out commit differs from the one recorded in the index.
+    OutOfDate,
+    /// The submodule's commit has merge conflicts.
+    Conflicted,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Submodule {
+    pub path: RepoPath,
+    /// The commit the submodule is checked out at, or the commit recorded in
+    /// the index if it isn't initialized.
+    pub sha: SharedString,
+    pub status: SubmoduleStatus,
+    /// Whether the submodule's working tree has changes of its own.
+    pub dirty: bool,
+}
+
+impl Submodule {
+    /// Whether `git submodule update` would change the checkout.
+    pub fn needs_update(&self) -> bool {
+        matches!(
+            self.status,
+            SubmoduleStatus::Uninitialized | SubmoduleStatus::OutOfDate
+        )
+    }
+}
+
+/// The `git submodule` subcommands that can be run on some or all submodules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmoduleCommand {
+    /// Copies the submodules' URLs from `.gitmodules` into the repository's config.
+    Init,
+    /// Clones missing submodules and checks out the commits recorded for them.
+    Update,
+    /// Updates the submodules' remote URLs after they changed in `.gitmodules`.
+    Sync,
+}
+
+impl SubmoduleCommand {
+    pub fn as_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Init => &["init"],
+            Self::Update => &["update", "--init"],
+            Self::Sync => &["sync"],
+        }
+    }
+}
+
+/// How a submodule's checked out commit differs from the one recorded in HEAD.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubmoduleRange {
+    /// The commit recorded in HEAD, or `None` if the submodule was added.
+    pub old: Option<SharedString>,
+    /// The checked out commit, or `None` if the submodule isn't initialized.
+    pub new: Option<SharedString>,
+    /// The commits in `new` that aren't in `old`, newest first.
+    pub added: Vec<CommitSummary>,
+    /// The commits in `old` that aren't in `new`, for when the submodule was
+    /// moved back or to another branch.
+    pub removed: Vec<CommitSummary>,
+}
+
+impl SubmoduleRange {
+    /// Renders the commit recorded in HEAD, as the old side of a text diff.
+    pub fn old_text(&self) -> Option<String> {
+        self.old
+            .as_ref()
+            .map(|sha| format!("Submodule commit {sha}\n"))
+    }
+
+    /// Renders the checked out commit and the commits between it and the one
+    /// in HEAD, as the new side of a text diff.
+    pub fn new_text(&self) -> String {
+        let mut text = String::new();
+        match &self.new {
+            Some(sha) => writeln!(text, "Submodule commit {sha}").ok(),
+            None => writeln!(text, "Submodule not checked out").ok(),
+        };
+        if let (Some(old), Some(new)) = (&self.old, &self.new)
+            && (!self.added.is_empty() || !self.removed.is_empty())
+        {
+            writeln!(text, "Commits {}..{}:", short_sha(old), short_sha(new)).ok();
+            for commit in &self.added {
+                writeln!(text, "  > {} {}", short_sha(&commit.sha), commit.subject).ok();
+            }
+            for commit in &self.removed {
+                writeln!(text, "  < {} {}", short_sha(&commit.sha), commit.subject).ok();
+            }
+        }
+        text
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..SHORT_SHA_LENGTH).unwrap_or(sha)
+}
+
+/// Parses the output of `git submodule status`, whose lines are a status
+/// character, a commit, the path and, for initialized submodules, a
+/// description of the commit in parentheses.
+pub fn parse_submodule_status(output: &str) -> Result<Vec<Submodule>> {
+    let mut submodules = output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut chars = line.chars();
+            let status = match chars.next() {
+                Some('-') => SubmoduleStatus::Uninitialized,
+                Some('+') => SubmoduleStatus::OutOfDate,
+                Some('U') => SubmoduleStatus::Conflicted,
+                _ => SubmoduleStatus::UpToDate,
+            };
+            let (sha, rest) = chars
+                .as_str()
+                .split_once(' ')
+                .with_context(|| format!("unexpected git-submodule output: {line:?}"))?;
+            let path = match rest.rsplit_once(" (") {
+                Some((path, _)) if rest.ends_with(')') => path,
+                _ => rest,
+            };
+            Ok(Submodule {
+                path: RepoPath::new(path)?,
+                sha: sha.to_string().into(),
+                status,
+                dirty: false,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    submodules.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(submodules)
+}
+
+/// Parses the output of `git status --porcelain=v2 -z`, returning the
+/// submodules whose working trees have modified or untracked files.
+pub fn parse_dirty_submodules(output: &str) -> Vec<RepoPath> {
+    let mut records = output.split('\0');
+    let mut paths = Vec::new();
+    while let Some(record) = records.next() {
+        let field_count = match record.as_bytes().first() {
+            Some(b'1') => 9,
+            Some(b'2') => 10,
+            Some(b'u') => 11,
+            _ => continue,
+        };
+        let fields = record.splitn(field_count, ' ').collect::<Vec<_>>();
+        if record.starts_with('2') {
+            // Renames are followed by the original path.
+            records.next();
+        }
+        let (Some(submodule), Some(path)) = (fields.get(2), fields.last()) else {
+            continue;
+        };
+        let submodule = submodule.as_bytes();
+        if submodule.first() == Some(&b'S')
+            && (submodule.get(2) == Some(&b'M') || submodule.get(3) == Some(&b'U'))
+            && let Ok(path) = RepoPath::new(path)
+        {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::repo_path;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_submodule_status() {
+        let output = "\
+ 0123456789abcdef0123456789abcdef01234567 sdk/audio (v1.2.0)
++89abcdef0123456789abcdef0123456789abcdef plugins/reverb sdk (heads/main)
+-fedcba9876543210fedcba9876543210fedcba98 vendor/old
+";
+        assert_eq!(
+            parse_submodule_status(output).unwrap(),
+            vec![
+                Submodule {
+                    path: repo_path("plugins/reverb sdk"),
+                    sha: "89abcdef0123456789abcdef0123456789abcdef".into(),
+                    status: SubmoduleStatus::OutOfDate,
+                    dirty: false,
+                },
+                Submodule {
+                    path: repo_path("sdk/audio"),
+                    sha: "0123456789abcdef0123456789abcdef01234567".into(),
+                    status: SubmoduleStatus::UpToDate,
+                    dirty: false,
+                },
+                Submodule {
+                    path: repo_path("vendor/old"),
+                    sha: "fedcba9876543210fedcba9876543210fedcba98".into(),
+                    status: SubmoduleStatus::Uninitialized,
+                    dirty: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dirty_submodules() {
+        let output = [
+            "1 .M S.M. 160000 160000 160000 aaaa aaaa sdk/audio",
+            "1 .M N... 100644 100644 100644 bbbb bbbb README.md",
+            "1 M. SC.. 160000 160000 160000 cccc dddd sdk/moved",
+            "2 R. N... 100644 100644 100644 eeee eeee R100 new name.txt",
+            "old name.txt",
+            "1 .M S..U 160000 160000 160000 ffff ffff plugins/reverb sdk",
+            "",
+        ]
+        .join("\0");
+        assert_eq!(
+            parse_dirty_submodules(&output),
+            vec![repo_path("sdk/audio"), repo_path("plugins/reverb sdk")]
+        );
+    }
+
+    #[test]
+    fn test_submodule_range_text() {
+        let commit = |sha: &str, subject: &str| CommitSummary {
+            sha: sha.to_string().into(),
+            subject: subject.to_string().into(),
+            commit_timestamp: 0,
+            author_name: "Author".into(),
+            has_parent: true,
+        };
+        let range = SubmoduleRange {
+            old: Some("1111111111111111111111111111111111111111".into()),
+            new: Some("3333333333333333333333333333333333333333".into()),
+            added: vec![
+                commit("3333333333333333333333333333333333333333", "Add a filter"),
+                commit("2222222222222222222222222222222222222222", "Fix the build"),
+            ],
+            removed: Vec::new(),
+        };
+        assert_eq!(
+            range.old_text().unwrap(),
+            "Submodule commit 1111111111111111111111111111111111111111\n"
+        );
+        assert_eq!(
+            range.new_text(),
+            "Submodule commit 3333333333333333333333333333333333333333\n\
+             Commits 1111111..3333333:\n  \
+             > 3333333 Add a filter\n  \
+             > 2222222 Fix the build\n"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{Context as _, Result};
+use gpui::SharedString;
+use std::path::PathBuf;
+
+/// A working tree of a repository, as listed by `git worktree list`. Every
+/// repository has a main worktree, and may have linked ones that share its
+/// history and branches.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GitWorktree {
+    pub path: PathBuf,
+    /// The commit checked out in the worktree, or `None` for a bare repository.
+    pub sha: Option<SharedString>,
+    /// The branch checked out in the worktree, or `None` if its HEAD is
+    /// detached.
+    pub branch: Option<SharedString>,
+    /// Whether this is the worktree the repository was created with, which
+    /// can't be removed.
+    pub is_main: bool,
+    /// Whether the worktree is locked against being pruned or removed.
+    pub locked: bool,
+    /// Whether the worktree's directory is missing, so that
+    /// `git worktree prune` would remove it.
+    pub prunable: bool,
+}
+
+impl GitWorktree {
+    /// A short name for the worktree, the name of its directory.
+    pub fn name(&self) -> SharedString {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.to_string_lossy().into_owned())
+            .into()
+    }
+}
+
+/// Parses the output of `git worktree list --porcelain`, whose records are
+/// separated by blank lines and start with the worktree's path. The main
+/// worktree is always listed first.
+pub fn parse_worktree_list(output: &str) -> Result<Vec<GitWorktree>> {
+    let mut worktrees = Vec::new();
+    for record in output.split("\n\n") {
+        let mut lines = record.lines().filter(|line| !line.is_empty()).peekable();
+        if lines.peek().is_none() {
+            continue;
+        }
+        let path = lines
+            .next()
+            .and_then(|line| line.strip_prefix("worktree "))
+            .with_context(|| format!("unexpected git-worktree output: {record:?}"))?;
+        let mut worktree = GitWorktree {
+            path: PathBuf::from(path),
+            sha: None,
+            branch: None,
+            is_main: worktrees.is_empty(),
+            locked: false,
+            prunable: false,
+        };
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "HEAD" => worktree.sha = Some(value.to_string().into()),
+                "branch" => {
+                    let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                    worktree.branch = Some(branch.to_string().into());
+                }
+                "locked" => worktree.locked = true,
+                "prunable" => worktree.prunable = true,
+                _ => {}
+            }
+        }
+        worktrees.push(worktree);
+    }
+    Ok(worktrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "\
+worktree /code/app
+HEAD 0123456789abcdef0123456789abcdef01234567
+branch refs/heads/main
+
+worktree /code/app-feature
+HEAD 89abcdef0123456789abcdef0123456789abcdef
+branch refs/heads/feature/search
+locked on a removable drive
+
+worktree /tmp/app-review
+HEAD fedcba9876543210fedcba9876543210fedcba98
+detached
+prunable gitdir file points to non-existent location
+
+";
+        assert_eq!(
+            parse_worktree_list(output).unwrap(),
+            vec![
+                GitWorktree {
+                    path: PathBuf::from("/code/app"),
+                    sha: Some("0123456789abcdef0123456789abcdef01234567".into()),
+                    branch: Some("main".into()),
+                    is_main: true,
+                    locked: false,
+                    prunable: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/code/app-feature"),
+                    sha: Some("89abcdef0123456789abcdef0123456789abcdef".into()),
+                    branch: Some("feature/search".into()),
+                    is_main: false,
+                    locked: true,
+                    prunable: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/tmp/app-review"),
+                    sha: Some("fedcba9876543210fedcba9876543210fedcba98".into()),
+                    branch: None,
+                    is_main: false,
+                    locked: false,
+                    prunable: true,
+                },
+            ]
+        );
+        assert_eq!(parse_worktree_list("").unwrap(), Vec::new());
+    }
+}
@@ -0,0 +1,889 @@
+use anyhow::{Context as _, Result};
+use git::{
+    SHORT_SHA_LENGTH,
+    repository::{BisectMark, BisectState, CommitSummary, LogOptions},
+};
+use gpui::{
+    App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task,
+    WeakEntity, actions,
+};
+use project::{TaskSourceKind, git_store::Repository};
+use task::{TaskContext, TaskId, TaskTemplate};
+use ui::{ContextMenu, ListItem, ListItemSpacing, PopoverMenu, Tooltip, prelude::*};
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+use crate::commit_view::CommitView;
+
+actions!(
+    bisect_view,
+    [
+        /// Starts bisecting between the chosen good and bad commits.
+        StartBisect,
+        /// Marks the checked out commit as not having the regression.
+        MarkGood,
+        /// Marks the checked out commit as having the regression.
+        MarkBad,
+        /// Skips the checked out commit, for when it can't be tested.
+        MarkSkip,
+        /// Runs the chosen task on each commit to mark it, until the first bad
+        /// commit is found.
+        RunTask,
+        /// Ends the bisect, checking out the branch it was started from.
+        ResetBisect,
+    ]
+);
+
+/// How many of the most recent commits can be chosen to bisect between.
+const MAX_COMMITS: usize = 200;
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+fn open(
+    workspace: &mut Workspace,
+    _: &git::Bisect,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    if let Some(existing) = workspace.item_of_type::<BisectView>(cx) {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+    let workspace_handle = workspace.weak_handle();
+    let view = cx.new(|cx| BisectView::new(repository, workspace_handle, window, cx));
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+/// Finds the commit that introduced a regression with `git bisect`. The range
+/// is picked from the most recent commits, after which every commit that is
+/// checked out is marked by hand, or by running a task and reading its exit
+/// status, until the first bad commit is found and opened.
+pub struct BisectView {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    /// The bisect in progress, if any.
+    state: Option<BisectState>,
+    /// The most recent commits, newest first, to choose the range from.
+    commits: Vec<CommitSummary>,
+    bad_index: usize,
+    good_index: Option<usize>,
+    selected_index: Option<usize>,
+    tasks: Vec<(TaskSourceKind, TaskTemplate)>,
+    selected_task: Option<usize>,
+    /// The task testing each commit, while it runs.
+    running_task: Option<TaskId>,
+    load_task: Option<Task<()>>,
+    pending_task: Option<Task<()>>,
+    error: Option<SharedString>,
+    scroll_handle: ScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl BisectView {
+    fn new(
+        repository: Entity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let commits = repository.update(cx, |repository, _| {
+            repository.log(LogOptions {
+                limit: MAX_COMMITS,
+                ..Default::default()
+            })
+        });
+        let state = repository.update(cx, |repository, _| repository.bisect_state());
+        let load_task = cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let commits = commits.await??;
+                let state = state.await??;
+                anyhow::Ok((commits, state))
+            }
+            .await;
+            this.update_in(cx, |this, window, cx| {
+                this.load_task = None;
+                match result {
+                    Ok((commits, state)) => {
+                        this.commits = commits;
+                        this.set_state(state, window, cx);
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        let mut this = Self {
+            repository,
+            workspace,
+            state: None,
+            commits: Vec::new(),
+            bad_index: 0,
+            good_index: None,
+            selected_index: None,
+            tasks: Vec::new(),
+            selected_task: None,
+            running_task: None,
+            load_task: Some(load_task),
+            pending_task: None,
+            error: None,
+            scroll_handle: ScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        };
+        this.load_tasks(cx);
+        this
+    }
+
+    fn load_tasks(&mut self, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().read(cx);
+        let worktree = project
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).id());
+        let Some(inventory) = project.task_store().read(cx).task_inventory().cloned() else {
+            return;
+        };
+        let tasks = inventory.read(cx).list_tasks(None, None, worktree, cx);
+        cx.spawn(async move |this, cx| {
+            let tasks = tasks.await;
+            this.update(cx, |this, cx| {
+                this.tasks = tasks;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn set_state(
+        &mut self,
+        state: Option<BisectState>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let had_culprit = self
+            .state
+            .as_ref()
+            .is_some_and(|state| state.culprit().is_some());
+        if !had_culprit && let Some(culprit) = state.as_ref().and_then(|state| state.culprit()) {
+            CommitView::open(
+                culprit.clone(),
+                self.repository.downgrade(),
+                self.workspace.clone(),
+                window,
+                cx,
+            );
+        }
+        self.state = state;
+        self.selected_index = None;
+        cx.notify();
+    }
+
+    fn refresh_state(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let state = self
+            .repository
+            .update(cx, |repository, _| repository.bisect_state());
+        cx.spawn_in(window, async move |this, cx| {
+            let state = state.await??;
+            this.update_in(cx, |this, window, cx| this.set_state(state, window, cx))
+        })
+    }
+
+    /// Runs a git operation followed by a refresh of the bisect, showing the
+    /// error if either fails.
+    fn run_operation(
+        &mut self,
+        operation: impl AsyncFnOnce(&mut AsyncWindowContext) -> Result<()> + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.error = None;
+        self.pending_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                operation(cx).await?;
+                this.update_in(cx, |this, window, cx| this.refresh_state(window, cx))?
+                    .await
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.pending_task = None;
+                this.running_task = None;
+                if let Err(error) = result {
+                    this.error = Some(error.to_string().into());
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn start_bisect(&mut self, _: &StartBisect, window: &mut Window, cx: &mut Context<Self>) {
+        if self.state.is_some() || self.pending_task.is_some() {
+            return;
+        }
+        let (Some(bad), Some(good)) = (
+            self.commits.get(self.bad_index),
+            self.good_index.and_then(|ix| self.commits.get(ix)),
+        ) else {
+            self.error = Some("Choose a good commit that is older than the bad one".into());
+            cx.notify();
+            return;
+        };
+        let started = self.repository.update(cx, |repository, _| {
+            repository.bisect_start(bad.sha.to_string(), good.sha.to_string())
+        });
+        self.run_operation(async move |_| started.await?, window, cx);
+    }
+
+    fn mark(&mut self, mark: BisectMark, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_mark() {
+            return;
+        }
+        let marked = self
+            .repository
+            .update(cx, |repository, _| repository.bisect_mark(mark));
+        self.run_operation(async move |_| marked.await?, window, cx);
+    }
+
+    fn mark_good(&mut self, _: &MarkGood, window: &mut Window, cx: &mut Context<Self>) {
+        self.mark(BisectMark::Good, window, cx);
+    }
+
+    fn mark_bad(&mut self, _: &MarkBad, window: &mut Window, cx: &mut Context<Self>) {
+        self.mark(BisectMark::Bad, window, cx);
+    }
+
+    fn mark_skip(&mut self, _: &MarkSkip, window: &mut Window, cx: &mut Context<Self>) {
+        self.mark(BisectMark::Skip, window, cx);
+    }
+
+    fn reset_bisect(&mut self, _: &ResetBisect, window: &mut Window, cx: &mut Context<Self>) {
+        if self.state.is_none() {
+            return;
+        }
+        // Resetting stops a task that is running, rather than waiting for it.
+        self.kill_running_task(cx);
+        let reset = self
+            .repository
+            .update(cx, |repository, _| repository.bisect_reset());
+        self.run_operation(async move |_| reset.await?, window, cx);
+    }
+
+    fn run_task(&mut self, _: &RunTask, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_mark() {
+            return;
+        }
+        let Some((kind, template)) = self.selected_task.and_then(|ix| self.tasks.get(ix)) else {
+            self.error = Some("Choose a task to test the commits with".into());
+            cx.notify();
+            return;
+        };
+        let task_context = TaskContext {
+            cwd: Some(
+                self.repository
+                    .read(cx)
+                    .work_directory_abs_path
+                    .to_path_buf(),
+            ),
+            ..Default::default()
+        };
+        let Some(resolved) = template.resolve_task(&kind.to_id_base(), &task_context) else {
+            self.error = Some(format!("The task \"{}\" can't be run", template.label).into());
+            cx.notify();
+            return;
+        };
+
+        let task_id = resolved.id.clone();
+        let workspace = self.workspace.clone();
+        let repository = self.repository.clone();
+        let this = cx.entity().downgrade();
+        self.run_operation(
+            async move |cx| {
+                loop {
+                    let status = workspace
+                        .update_in(cx, |workspace, window, cx| {
+                            workspace.spawn_in_terminal(resolved.resolved.clone(), window, cx)
+                        })?
+                        .await
+                        .context("The task was cancelled")??;
+                    let mark = mark_for_exit_code(status.code()).with_context(|| {
+                        format!("The task exited with {status}, so the bisect was stopped")
+                    })?;
+                    repository
+                        .update(cx, |repository, _| repository.bisect_mark(mark))?
+                        .await??;
+                    let state = repository
+                        .update(cx, |repository, _| repository.bisect_state())?
+                        .await??;
+                    let finished = state.as_ref().is_none_or(|state| state.culprit().is_some());
+                    this.update_in(cx, |this, window, cx| this.set_state(state, window, cx))?;
+                    if finished {
+                        return Ok(());
+                    }
+                }
+            },
+            window,
+            cx,
+        );
+        self.running_task = Some(task_id);
+    }
+
+    fn stop_task(&mut self, cx: &mut Context<Self>) {
+        self.kill_running_task(cx);
+        self.pending_task = None;
+        cx.notify();
+    }
+
+    /// Kills the process of the running task in its terminal, which keeps
+    /// running when the loop waiting for it is dropped.
+    fn kill_running_task(&mut self, cx: &mut Context<Self>) {
+        let Some(task_id) = self.running_task.take() else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let terminals = workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .local_terminal_handles()
+            .clone();
+        for terminal in terminals {
+            terminal
+                .update(cx, |terminal, _| {
+                    if terminal
+                        .task()
+                        .is_some_and(|task| task.spawned_task.id == task_id)
+                    {
+                        terminal.kill_active_task();
+                    }
+                })
+                .ok();
+        }
+    }
+
+    fn can_mark(&self) -> bool {
+        self.pending_task.is_none()
+            && self
+                .state
+                .as_ref()
+                .is_some_and(|state| state.culprit().is_none())
+    }
+
+    fn row_count(&self) -> usize {
+        match &self.state {
+            Some(state) => state.remaining.len(),
+            None => self.commits.len(),
+        }
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_index = Some(ix);
+        self.scroll_handle.scroll_to_item(ix);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let row_count = self.row_count();
+        if row_count > 0 {
+            let ix = self
+                .selected_index
+                .map_or(0, |ix| (ix + 1).min(row_count - 1));
+            self.select_index(ix, cx);
+        }
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.row_count() > 0 {
+            let ix = self.selected_index.map_or(0, |ix| ix.saturating_sub(1));
+            self.select_index(ix, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.selected_index else {
+            return;
+        };
+        if self.state.is_some() {
+            self.open_commit(ix, window, cx);
+        } else {
+            self.set_good(ix, cx);
+        }
+    }
+
+    fn set_bad(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.bad_index = ix;
+        if self.good_index.is_some_and(|good_index| good_index <= ix) {
+            self.good_index = None;
+        }
+        self.error = None;
+        cx.notify();
+    }
+
+    fn set_good(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if ix <= self.bad_index {
+            self.error = Some("The good commit has to be older than the bad one".into());
+        } else {
+            self.good_index = Some(ix);
+            self.error = None;
+        }
+        cx.notify();
+    }
+
+    fn open_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(commit) = self
+            .state
+            .as_ref()
+            .and_then(|state| state.remaining.get(ix))
+        else {
+            return;
+        };
+        CommitView::open(
+            commit.clone(),
+            self.repository.downgrade(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+
+    fn render_commits(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        self.commits
+            .iter()
+            .enumerate()
+            .map(|(ix, commit)| {
+                let in_range = self
+                    .good_index
+                    .is_some_and(|good_index| (self.bad_index..=good_index).contains(&ix));
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(self.selected_index == Some(ix))
+                    .on_click(cx.listener(move |this, _, _, cx| this.set_good(ix, cx)))
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_3()
+                            .child(
+                                Button::new(("bad", ix), "Bad")
+                                    .label_size(LabelSize::Small)
+                                    .toggle_state(self.bad_index == ix)
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        cx.stop_propagation();
+                                        this.set_bad(ix, cx)
+                                    })),
+                            )
+                            .child(
+                                Button::new(("good", ix), "Good")
+                                    .label_size(LabelSize::Small)
+                                    .toggle_state(self.good_index == Some(ix))
+                                    .disabled(ix <= self.bad_index)
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        cx.stop_propagation();
+                                        this.set_good(ix, cx)
+                                    })),
+                            )
+                            .child(render_sha(commit, cx))
+                            .child(
+                                div().flex_1().min_w_0().child(
+                                    Label::new(commit.subject.clone())
+                                        .truncate()
+                                        .when(!in_range && self.good_index.is_some(), |label| {
+                                            label.color(Color::Muted)
+                                        }),
+                                ),
+                            )
+                            .child(
+                                Label::new(commit.author_name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_remaining(&self, state: &BisectState, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let head = self
+            .repository
+            .read(cx)
+            .head_commit
+            .as_ref()
+            .map(|commit| commit.sha.clone());
+        state
+            .remaining
+            .iter()
+            .enumerate()
+            .map(|(ix, commit)| {
+                let is_head = head.as_ref() == Some(&commit.sha);
+                let is_skipped = state.skipped.contains(&commit.sha);
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(self.selected_index == Some(ix))
+                    .on_click(
+                        cx.listener(move |this, _, window, cx| this.open_commit(ix, window, cx)),
+                    )
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_3()
+                            .child(div().w_4().when(is_head, |this| {
+                                this.child(
+                                    Icon::new(IconName::ArrowRight)
+                                        .size(IconSize::Small)
+                                        .color(Color::Accent),
+                                )
+                            }))
+                            .child(render_sha(commit, cx))
+                            .child(
+                                div().flex_1().min_w_0().child(
+                                    Label::new(commit.subject.clone())
+                                        .truncate()
+                                        .when(is_skipped, |label| label.color(Color::Muted)),
+                                ),
+                            )
+                            .when(is_skipped, |this| {
+                                this.child(
+                                    Label::new("Skipped")
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            })
+                            .child(
+                                Label::new(commit.author_name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border);
+        let Some(state) = &self.state else {
+            return header
+                .child(
+                    Label::new(
+                        "Choose the newest commit with the regression and an older one without it.",
+                    )
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                )
+                .child(
+                    Button::new("start-bisect", "Start Bisect")
+                        .style(ButtonStyle::Filled)
+                        .disabled(self.good_index.is_none() || self.pending_task.is_some())
+                        .tooltip(Tooltip::for_action_title_in(
+                            "git bisect start",
+                            &StartBisect,
+                            &self.focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.start_bisect(&StartBisect, window, cx)
+                        })),
+                )
+                .into_any_element();
+        };
+
+        let summary = if let Some(culprit) = state.culprit() {
+            h_flex()
+                .min_w_0()
+                .gap_1p5()
+                .child(
+                    Label::new("The first bad commit is")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(render_sha(culprit, cx))
+                .child(
+                    Label::new(culprit.subject.clone())
+                        .size(LabelSize::Small)
+                        .truncate(),
+                )
+        } else {
+            let remaining = state.remaining.len();
+            h_flex().min_w_0().child(
+                Label::new(format!(
+                    "{remaining} {} left to test, roughly {} {}",
+                    if remaining == 1 { "commit" } else { "commits" },
+                    steps(remaining),
+                    if steps(remaining) == 1 {
+                        "step"
+                    } else {
+                        "steps"
+                    }
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+        };
+
+        let can_mark = self.can_mark();
+        let mark_button = |id: &'static str, label: &'static str, action: &dyn gpui::Action| {
+            Button::new(id, label)
+                .disabled(!can_mark)
+                .tooltip(Tooltip::for_action_title_in(
+                    label,
+                    action,
+                    &self.focus_handle,
+                ))
+        };
+        header
+            .child(summary)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .when(state.culprit().is_none(), |this| {
+                        this.child(mark_button("mark-good", "Good", &MarkGood).on_click(
+                            cx.listener(|this, _, window, cx| {
+                                this.mark_good(&MarkGood, window, cx)
+                            }),
+                        ))
+                        .child(mark_button("mark-bad", "Bad", &MarkBad).on_click(
+                            cx.listener(|this, _, window, cx| this.mark_bad(&MarkBad, window, cx)),
+                        ))
+                        .child(mark_button("mark-skip", "Skip", &MarkSkip).on_click(
+                            cx.listener(|this, _, window, cx| {
+                                this.mark_skip(&MarkSkip, window, cx)
+                            }),
+                        ))
+                        .child(self.render_task_menu(cx))
+                        .child(if self.running_task.is_some() {
+                            Button::new("stop-task", "Stop")
+                                .on_click(cx.listener(|this, _, _, cx| this.stop_task(cx)))
+                        } else {
+                            Button::new("run-task", "Run Task")
+                                .disabled(!can_mark || self.selected_task.is_none())
+                                .tooltip(Tooltip::for_action_title_in(
+                                    "Mark each commit by the exit status of the task",
+                                    &RunTask,
+                                    &self.focus_handle,
+                                ))
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.run_task(&RunTask, window, cx)
+                                }))
+                        })
+                    })
+                    .child(
+                        Button::new("reset-bisect", "Reset")
+                            .style(ButtonStyle::Filled)
+                            .tooltip(Tooltip::for_action_title_in(
+                                "git bisect reset",
+                                &ResetBisect,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.reset_bisect(&ResetBisect, window, cx)
+                            })),
+                    ),
+            )
+            .into_any_element()
+    }
+
+    fn render_task_menu(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let label: SharedString = self
+            .selected_task
+            .and_then(|ix| self.tasks.get(ix))
+            .map_or("Choose Task".into(), |(_, template)| {
+                template.label.clone().into()
+            });
+        let labels = self
+            .tasks
+            .iter()
+            .map(|(_, template)| SharedString::from(template.label.clone()))
+            .collect::<Vec<_>>();
+        let selected_task = self.selected_task;
+        let this = cx.entity().downgrade();
+        PopoverMenu::new("bisect-task")
+            .trigger(
+                Button::new("bisect-task-trigger", label)
+                    .disabled(self.running_task.is_some())
+                    .icon(IconName::ChevronDown)
+                    .icon_position(IconPosition::End)
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted),
+            )
+            .menu(move |window, cx| {
+                let labels = labels.clone();
+                let this = this.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    if labels.is_empty() {
+                        return menu.label("There are no tasks");
+                    }
+                    for (ix, label) in labels.into_iter().enumerate() {
+                        let this = this.clone();
+                        menu = menu.toggleable_entry(
+                            label,
+                            selected_task == Some(ix),
+                            IconPosition::Start,
+                            None,
+                            move |_, cx| {
+                                this.update(cx, |this, cx| {
+                                    this.selected_task = Some(ix);
+                                    this.error = None;
+                                    cx.notify();
+                                })
+                                .ok();
+                            },
+                        );
+                    }
+                    menu
+                }))
+            })
+    }
+
+    fn render_status(&self) -> Option<impl IntoElement> {
+        let message: SharedString = if let Some(error) = &self.error {
+            error.clone()
+        } else if self.load_task.is_some() {
+            "Loading commits…".into()
+        } else if self.running_task.is_some() {
+            "Running the task on each commit…".into()
+        } else if self.pending_task.is_some() {
+            "Bisecting…".into()
+        } else if self.row_count() == 0 {
+            "There are no commits".into()
+        } else {
+            return None;
+        };
+        Some(
+            h_flex().p_2().justify_center().child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(if self.error.is_some() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            ),
+        )
+    }
+}
+
+/// Maps the exit status of a task to a mark the way `git bisect run` does:
+/// 0 means good, 125 means the commit can't be tested, and any other code
+/// below 128 means bad. Higher codes, such as those of a crash, stop the
+/// bisect.
+fn mark_for_exit_code(code: Option<i32>) -> Option<BisectMark> {
+    match code? {
+        0 => Some(BisectMark::Good),
+        125 => Some(BisectMark::Skip),
+        1..=127 => Some(BisectMark::Bad),
+        _ => None,
+    }
+}
+
+/// How many more commits need to be tested to find the culprit among the
+/// remaining ones.
+fn steps(remaining: usize) -> u32 {
+    remaining.next_power_of_two().trailing_zeros()
+}
+
+fn render_sha(commit: &CommitSummary, cx: &App) -> Label {
+    Label::new(
+        commit
+            .sha
+            .get(..SHORT_SHA_LENGTH)
+            .unwrap_or(&commit.sha)
+            .to_string(),
+    )
+    .buffer_font(cx)
+    .size(LabelSize::Small)
+    .color(Color::Muted)
+}
+
+impl EventEmitter<ItemEvent> for BisectView {}
+
+impl Focusable for BisectView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for BisectView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Bisect".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Bisect Opened")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for BisectView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = match &self.state {
+            Some(state) => self.render_remaining(state, cx),
+            None => self.render_commits(cx),
+        };
+        v_flex()
+            .key_context("GitBisect")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::start_bisect))
+            .on_action(cx.listener(Self::mark_good))
+            .on_action(cx.listener(Self::mark_bad))
+            .on_action(cx.listener(Self::mark_skip))
+            .on_action(cx.listener(Self::run_task))
+            .on_action(cx.listener(Self::reset_bisect))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                v_flex()
+                    .id("git-bisect-commits")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll_handle)
+                    .children(rows),
+            )
+            .children(self.render_status())
+    }
+}
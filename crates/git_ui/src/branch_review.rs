@@ -0,0 +1,987 @@
+use std::{fmt::Write as _, path::PathBuf, sync::Arc};
+
+use anyhow::Context as _;
+use collections::HashSet;
+use editor::{
+    Editor, MultiBuffer, SelectionEffects, multibuffer_context_lines, scroll::Autoscroll,
+};
+use git::{repository::RepoPath, status::StatusCode};
+use gpui::{
+    App, ClipboardItem, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task,
+    WeakEntity, actions,
+};
+use language::{Anchor, BufferId, Capability, OffsetRangeExt as _, Point};
+use multi_buffer::{PathKey, ToPoint as _};
+use project::{Project, git_store::Repository};
+use ui::{Checkbox, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+use crate::{
+    commit_view::{GitBlob, build_buffer, build_buffer_diff},
+    git_status_icon,
+    persistence::{GIT_REVIEW_DB, ReviewComment},
+};
+
+actions!(
+    branch_review,
+    [
+        /// Compares the chosen revisions.
+        Compare,
+        /// Starts a review comment on the line under the cursor.
+        AddComment,
+        /// Opens the review comments as a markdown document.
+        ExportReview,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+fn open(
+    workspace: &mut Workspace,
+    _: &git::ReviewBranch,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    if let Some(existing) = workspace.item_of_type::<BranchReview>(cx) {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+    let branch = repository.read(cx).branch.clone();
+    let base = branch
+        .as_ref()
+        .and_then(|branch| branch.upstream.as_ref())
+        .and_then(|upstream| upstream.stripped_ref_name())
+        .map(ToString::to_string);
+    let head = branch
+        .as_ref()
+        .map_or("HEAD".to_string(), |branch| branch.name().to_string());
+    let project = workspace.project().clone();
+    let workspace_handle = workspace.weak_handle();
+    let view = cx.new(|cx| {
+        BranchReview::new(
+            repository,
+            project,
+            workspace_handle,
+            base,
+            head,
+            window,
+            cx,
+        )
+    });
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+/// Opens a review of the changes on `head` since it diverged from `base`,
+/// reusing the open review if there is one.
+pub fn review_range(
+    workspace: &mut Workspace,
+    repository: Entity<Repository>,
+    base: String,
+    head: String,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if let Some(existing) = workspace.item_of_type::<BranchReview>(cx) {
+        existing.update(cx, |review, cx| {
+            review.repository = repository;
+            review.use_merge_base = true;
+            review
+                .base_editor
+                .update(cx, |editor, cx| editor.set_text(base.as_str(), window, cx));
+            review
+                .head_editor
+                .update(cx, |editor, cx| editor.set_text(head.as_str(), window, cx));
+            review.compare(&Compare, window, cx);
+        });
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+    let project = workspace.project().clone();
+    let workspace_handle = workspace.weak_handle();
+    let view = cx.new(|cx| {
+        BranchReview::new(
+            repository,
+            project,
+            workspace_handle,
+            Some(base),
+            head,
+            window,
+            cx,
+        )
+    });
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+/// A file that differs between the compared revisions.
+struct ReviewFile {
+    path: RepoPath,
+    status: StatusCode,
+    buffer_id: BufferId,
+}
+
+/// Reviews the differences between two revisions the way a pull request
+/// would be reviewed, without a hosting provider. By default, the current
+/// branch is compared with its upstream from their merge base. Files can be
+/// marked as viewed and lines commented on, both of which are stored locally
+/// for the compared range.
+pub struct BranchReview {
+    repository: Entity<Repository>,
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    base_editor: Entity<Editor>,
+    head_editor: Entity<Editor>,
+    comment_editor: Entity<Editor>,
+    /// Whether the diff starts from the merge base of the revisions, like
+    /// `git diff base...head`, rather than from the base itself.
+    use_merge_base: bool,
+    editor: Entity<Editor>,
+    multibuffer: Entity<MultiBuffer>,
+    /// The compared range, such as `origin/main...feature`, which identifies
+    /// the review in the database.
+    review_range: Option<String>,
+    files: Vec<ReviewFile>,
+    viewed: HashSet<RepoPath>,
+    comments: Vec<ReviewComment>,
+    /// The line a comment is being written for.
+    pending_comment: Option<(RepoPath, u32)>,
+    load_task: Option<Task<()>>,
+    error: Option<SharedString>,
+    scroll_handle: ScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl BranchReview {
+    fn new(
+        repository: Entity<Repository>,
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        base: Option<String>,
+        head: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let base_editor = ref_editor("Base revision", base.as_deref(), window, cx);
+        let head_editor = ref_editor("Head revision", Some(&head), window, cx);
+        let comment_editor = ref_editor("Leave a comment", None, window, cx);
+
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
+        let editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), window, cx);
+            editor.disable_inline_diagnostics();
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let mut this = Self {
+            repository,
+            project,
+            workspace,
+            base_editor,
+            head_editor,
+            comment_editor,
+            use_merge_base: true,
+            editor,
+            multibuffer,
+            review_range: None,
+            files: Vec::new(),
+            viewed: HashSet::default(),
+            comments: Vec::new(),
+            pending_comment: None,
+            load_task: None,
+            error: None,
+            scroll_handle: ScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        };
+        if base.is_some() {
+            this.compare(&Compare, window, cx);
+        }
+        this
+    }
+
+    fn repository_path(&self, cx: &App) -> PathBuf {
+        self.repository
+            .read(cx)
+            .work_directory_abs_path
+            .to_path_buf()
+    }
+
+    fn compare(&mut self, _: &Compare, _: &mut Window, cx: &mut Context<Self>) {
+        let text = |editor: &Entity<Editor>| editor.read(cx).text(cx).trim().to_string();
+        let base = text(&self.base_editor);
+        let head = match text(&self.head_editor) {
+            head if head.is_empty() => "HEAD".to_string(),
+            head => head,
+        };
+        if base.is_empty() {
+            self.error = Some("Choose a revision to compare with".into());
+            cx.notify();
+            return;
+        }
+
+        let use_merge_base = self.use_merge_base;
+        let review_range = if use_merge_base {
+            format!("{base}...{head}")
+        } else {
+            format!("{base}..{head}")
+        };
+        let repository = self.repository.clone();
+        let repository_path = self.repository_path(cx);
+        let language_registry = self.project.read(cx).languages().clone();
+        let first_worktree_id = self
+            .project
+            .read(cx)
+            .worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).id());
+
+        self.error = None;
+        self.load_task = Some(cx.spawn(async move |this, cx| {
+            let result = async {
+                let diff_base = if use_merge_base {
+                    repository
+                        .update(cx, |repository, _| {
+                            repository.merge_base(base.clone(), head.clone())
+                        })?
+                        .await??
+                        .with_context(|| format!("{base} and {head} have no common history"))?
+                } else {
+                    base.clone()
+                };
+                let diff = repository
+                    .update(cx, |repository, _| {
+                        repository.diff_refs(diff_base, head.clone())
+                    })?
+                    .await??;
+
+                let mut files = Vec::new();
+                for file in diff.files {
+                    let status = match (&file.old_text, &file.new_text) {
+                        (None, _) => StatusCode::Added,
+                        (_, None) => StatusCode::Deleted,
+                        _ => StatusCode::Modified,
+                    };
+                    let worktree_id = repository
+                        .update(cx, |repository, cx| {
+                            repository
+                                .repo_path_to_project_path(&file.path, cx)
+                                .map(|path| path.worktree_id)
+                                .or(first_worktree_id)
+                        })?
+                        .context("project has no worktrees")?;
+                    let blob = Arc::new(GitBlob {
+                        path: file.path.clone(),
+                        is_deleted: status == StatusCode::Deleted,
+                        worktree_id,
+                    }) as Arc<dyn language::File>;
+                    let buffer = build_buffer(
+                        file.new_text.unwrap_or_default(),
+                        blob,
+                        &language_registry,
+                        cx,
+                    )
+                    .await?;
+                    let buffer_diff =
+                        build_buffer_diff(file.old_text, &buffer, &language_registry, cx).await?;
+                    files.push((file.path, status, buffer, buffer_diff));
+                }
+
+                let viewed = GIT_REVIEW_DB
+                    .viewed_files(repository_path.clone(), review_range.clone())
+                    .log_err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|path| RepoPath::new(&path).log_err())
+                    .collect::<HashSet<_>>();
+                let comments = GIT_REVIEW_DB
+                    .comments(repository_path, review_range.clone())
+                    .log_err()
+                    .unwrap_or_default();
+                anyhow::Ok((files, viewed, comments))
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                this.load_task = None;
+                let (files, viewed, comments) = match result {
+                    Ok(result) => result,
+                    Err(error) => {
+                        this.error = Some(error.to_string().into());
+                        cx.notify();
+                        return;
+                    }
+                };
+                this.review_range = Some(review_range);
+                this.viewed = viewed;
+                this.comments = comments;
+                this.pending_comment = None;
+                this.files.clear();
+                this.multibuffer
+                    .update(cx, |multibuffer, cx| multibuffer.clear(cx));
+                for (path, status, buffer, buffer_diff) in files {
+                    let buffer_id = buffer.read(cx).remote_id();
+                    this.multibuffer.update(cx, |multibuffer, cx| {
+                        let snapshot = buffer.read(cx).snapshot();
+                        let diff_hunk_ranges = buffer_diff
+                            .read(cx)
+                            .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot, cx)
+                            .map(|diff_hunk| diff_hunk.buffer_range.to_point(&snapshot))
+                            .collect::<Vec<_>>();
+                        multibuffer.set_excerpts_for_path(
+                            path_key(&path),
+                            buffer,
+                            diff_hunk_ranges,
+                            multibuffer_context_lines(cx),
+                            cx,
+                        );
+                        multibuffer.add_diff(buffer_diff, cx);
+                    });
+                    if this.viewed.contains(&path) {
+                        this.editor.update(cx, |editor, cx| {
+                            editor.fold_buffer(buffer_id, cx);
+                        });
+                    }
+                    this.files.push(ReviewFile {
+                        path,
+                        status,
+                        buffer_id,
+                    });
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn toggle_merge_base(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.use_merge_base = !self.use_merge_base;
+        self.compare(&Compare, window, cx);
+    }
+
+    fn toggle_viewed(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let (Some(file), Some(review_range)) = (self.files.get(ix), self.review_range.clone())
+        else {
+            return;
+        };
+        let path = file.path.clone();
+        let buffer_id = file.buffer_id;
+        let viewed = !self.viewed.contains(&path);
+        if viewed {
+            self.viewed.insert(path.clone());
+        } else {
+            self.viewed.remove(&path);
+        }
+        // Viewed files are collapsed, like in a pull request.
+        self.editor.update(cx, |editor, cx| {
+            if viewed {
+                editor.fold_buffer(buffer_id, cx);
+            } else {
+                editor.unfold_buffer(buffer_id, cx);
+            }
+        });
+
+        let repository_path = self.repository_path(cx);
+        let file_path = path.as_unix_str().to_string();
+        cx.background_spawn(async move {
+            if viewed {
+                GIT_REVIEW_DB
+                    .mark_file_viewed(repository_path, review_range, file_path)
+                    .await
+            } else {
+                GIT_REVIEW_DB
+                    .unmark_file_viewed(repository_path, review_range, file_path)
+                    .await
+            }
+        })
+        .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    /// Returns the file and head-side row under the cursor, if the cursor is
+    /// on a line that exists in the head revision.
+    fn cursor_line(&self, cx: &App) -> Option<(RepoPath, u32)> {
+        let position = self.editor.read(cx).selections.newest_anchor().head();
+        let snapshot = self.multibuffer.read(cx).snapshot(cx);
+        let point = position.to_point(&snapshot);
+        let (buffer, buffer_point, _) = snapshot.point_to_buffer_point(point)?;
+        let path = buffer.file()?.path().clone();
+        Some((RepoPath::from(path), buffer_point.row))
+    }
+
+    fn add_comment(&mut self, _: &AddComment, window: &mut Window, cx: &mut Context<Self>) {
+        if self.review_range.is_none() {
+            return;
+        }
+        let Some(line) = self.cursor_line(cx) else {
+            self.error = Some("Place the cursor on a line of the head revision to comment".into());
+            cx.notify();
+            return;
+        };
+        self.error = None;
+        self.pending_comment = Some(line);
+        self.comment_editor.update(cx, |editor, cx| {
+            editor.clear(window, cx);
+        });
+        window.focus(&self.comment_editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn submit_comment(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some((path, row)), Some(review_range)) =
+            (self.pending_comment.clone(), self.review_range.clone())
+        else {
+            return;
+        };
+        let body = self.comment_editor.read(cx).text(cx).trim().to_string();
+        if body.is_empty() {
+            return;
+        }
+        self.pending_comment = None;
+        self.comment_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+        window.focus(&self.editor.focus_handle(cx));
+
+        let repository_path = self.repository_path(cx);
+        let file_path = path.as_unix_str().to_string();
+        cx.spawn(async move |this, cx| {
+            let id = GIT_REVIEW_DB
+                .add_comment(repository_path, review_range, file_path, row, body.clone())
+                .await?
+                .context("the comment wasn't saved")?;
+            this.update(cx, |this, cx| {
+                let ix = this
+                    .comments
+                    .partition_point(|comment| (&comment.path, comment.row) <= (&path, row));
+                this.comments.insert(
+                    ix,
+                    ReviewComment {
+                        id,
+                        path,
+                        row,
+                        body,
+                    },
+                );
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn delete_comment(&mut self, id: i64, cx: &mut Context<Self>) {
+        self.comments.retain(|comment| comment.id != id);
+        cx.background_spawn(async move { GIT_REVIEW_DB.delete_comment(id).await })
+            .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.comment_editor.focus_handle(cx).is_focused(window) {
+            self.submit_comment(window, cx);
+        } else {
+            self.compare(&Compare, window, cx);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        if self.pending_comment.take().is_some() {
+            window.focus(&self.editor.focus_handle(cx));
+            cx.notify();
+        }
+    }
+
+    fn scroll_to(
+        &mut self,
+        path: &RepoPath,
+        row: u32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(file) = self.files.iter().find(|file| &file.path == path) else {
+            return;
+        };
+        let multibuffer = self.multibuffer.read(cx);
+        let Some(position) = multibuffer
+            .buffer(file.buffer_id)
+            .and_then(|buffer| multibuffer.buffer_point_to_anchor(&buffer, Point::new(row, 0), cx))
+            .or_else(|| multibuffer.location_for_path(&path_key(&path), cx))
+        else {
+            return;
+        };
+        self.editor.update(cx, |editor, cx| {
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::focused()),
+                window,
+                cx,
+                |selections| selections.select_ranges([position..position]),
+            );
+        });
+        window.focus(&self.editor.focus_handle(cx));
+    }
+
+    fn export_review(&mut self, _: &ExportReview, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(review_range) = self.review_range.clone() else {
+            return;
+        };
+        let line_text = |path: &RepoPath, row: u32| {
+            let file = self.files.iter().find(|file| &file.path == path)?;
+            let buffer = self.multibuffer.read(cx).buffer(file.buffer_id)?;
+            let buffer = buffer.read(cx);
+            (row <= buffer.max_point().row).then(|| {
+                buffer
+                    .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                    .collect::<String>()
+            })
+        };
+        let markdown = review_markdown(
+            &review_range,
+            self.files.len(),
+            self.viewed.len(),
+            &self.comments,
+            line_text,
+        );
+
+        if !self.project.read(cx).is_local() {
+            cx.write_to_clipboard(ClipboardItem::new_string(markdown));
+            self.error = Some("The review was copied to the clipboard".into());
+            cx.notify();
+            return;
+        }
+
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let markdown_language = self
+            .project
+            .read(cx)
+            .languages()
+            .language_for_name("Markdown");
+        let project = self.project.clone();
+        window
+            .spawn(cx, async move |cx| {
+                let markdown_language = markdown_language.await.log_err();
+                workspace.update_in(cx, |workspace, window, cx| {
+                    let buffer = project.update(cx, |project, cx| {
+                        project.create_local_buffer(&markdown, markdown_language, true, cx)
+                    });
+                    let title = format!("Review of {review_range}");
+                    let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                    let editor = cx.new(|cx| {
+                        Editor::for_multibuffer(buffer, Some(project.clone()), window, cx)
+                    });
+                    workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+                })
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn render_ref_input(&self, label: &'static str, editor: &Entity<Editor>, cx: &App) -> Div {
+        h_flex()
+            .flex_1()
+            .gap_1p5()
+            .px_2()
+            .py_1()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .rounded_sm()
+            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+            .child(div().flex_1().child(editor.clone()))
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(self.render_ref_input("Base", &self.base_editor, cx))
+            .child(self.render_ref_input("Head", &self.head_editor, cx))
+            .child(
+                Checkbox::new("review-merge-base", self.use_merge_base.into())
+                    .label("From merge base")
+                    .tooltip(Tooltip::text(
+                        "Only show the changes made on the head revision since it diverged",
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_merge_base(window, cx);
+                    })),
+            )
+            .child(
+                Button::new("review-compare", "Compare")
+                    .style(ButtonStyle::Filled)
+                    .disabled(self.load_task.is_some())
+                    .tooltip(Tooltip::for_action_title_in(
+                        "Compare the revisions",
+                        &Compare,
+                        &self.focus_handle,
+                    ))
+                    .on_click(
+                        cx.listener(|this, _, window, cx| this.compare(&Compare, window, cx)),
+                    ),
+            )
+            .child(
+                Button::new("review-comment", "Comment")
+                    .disabled(self.review_range.is_none())
+                    .tooltip(Tooltip::for_action_title_in(
+                        "Comment on the line under the cursor",
+                        &AddComment,
+                        &self.focus_handle,
+                    ))
+                    .on_click(
+                        cx.listener(|this, _, window, cx| {
+                            this.add_comment(&AddComment, window, cx)
+                        }),
+                    ),
+            )
+            .child(
+                Button::new("review-export", "Export")
+                    .disabled(self.review_range.is_none())
+                    .tooltip(Tooltip::for_action_title_in(
+                        "Open the review as markdown",
+                        &ExportReview,
+                        &self.focus_handle,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.export_review(&ExportReview, window, cx)
+                    })),
+            )
+    }
+
+    fn render_files(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = Label::new(format!(
+            "{} of {} files viewed",
+            self.viewed.len(),
+            self.files.len()
+        ))
+        .size(LabelSize::Small)
+        .color(Color::Muted);
+        v_flex()
+            .p_1()
+            .child(div().px_2().py_1().child(header))
+            .children(self.files.iter().enumerate().map(|(ix, file)| {
+                let path = file.path.clone();
+                let comment_count = self
+                    .comments
+                    .iter()
+                    .filter(|comment| comment.path == file.path)
+                    .count();
+                ListItem::new(("review-file", ix))
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(
+                        Checkbox::new(
+                            ("review-viewed", ix),
+                            self.viewed.contains(&file.path).into(),
+                        )
+                        .tooltip(Tooltip::text("Viewed"))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.toggle_viewed(ix, cx);
+                        })),
+                    )
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_1p5()
+                            .child(git_status_icon(file.status.index()))
+                            .child(
+                                div().flex_1().min_w_0().child(
+                                    Label::new(file.path.as_unix_str().to_string())
+                                        .size(LabelSize::Small)
+                                        .truncate(),
+                                ),
+                            )
+                            .when(comment_count > 0, |this| {
+                                this.child(
+                                    Label::new(comment_count.to_string())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            }),
+                    )
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.scroll_to(&path, 0, window, cx);
+                    }))
+            }))
+    }
+
+    fn render_comments(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .p_1()
+            .gap_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div().px_2().py_1().child(
+                    Label::new("Comments")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .when_some(self.pending_comment.as_ref(), |this, (path, row)| {
+                this.child(
+                    v_flex()
+                        .mx_2()
+                        .gap_1()
+                        .child(
+                            Label::new(format!("{}:{}", path.as_unix_str(), row + 1))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            div()
+                                .px_2()
+                                .py_1()
+                                .border_1()
+                                .border_color(cx.theme().colors().border_variant)
+                                .rounded_sm()
+                                .child(self.comment_editor.clone()),
+                        ),
+                )
+            })
+            .children(self.comments.iter().map(|comment| {
+                let id = comment.id;
+                let path = comment.path.clone();
+                let row = comment.row;
+                ListItem::new(("review-comment", id as usize))
+                    .spacing(ListItemSpacing::Sparse)
+                    .child(
+                        v_flex()
+                            .w_full()
+                            .child(
+                                Label::new(format!("{}:{}", comment.path.as_unix_str(), row + 1))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .truncate(),
+                            )
+                            .child(Label::new(comment.body.clone()).size(LabelSize::Small)),
+                    )
+                    .end_slot(
+                        IconButton::new(("delete-review-comment", id as usize), IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Delete Comment"))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.delete_comment(id, cx);
+                            })),
+                    )
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.scroll_to(&path, row, window, cx);
+                    }))
+            }))
+    }
+
+    fn render_status(&self) -> Option<impl IntoElement> {
+        let message: SharedString = if let Some(error) = &self.error {
+            error.clone()
+        } else if self.load_task.is_some() {
+            "Comparing revisions…".into()
+        } else if self.review_range.is_none() {
+            "Choose the revisions to compare".into()
+        } else if self.files.is_empty() {
+            "The revisions have no differences".into()
+        } else {
+            return None;
+        };
+        Some(
+            h_flex().p_2().justify_center().child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(if self.error.is_some() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            ),
+        )
+    }
+}
+
+/// Renders the comments of a review as markdown, grouped by file and quoting
+/// the line each comment is on.
+fn review_markdown(
+    review_range: &str,
+    file_count: usize,
+    viewed_count: usize,
+    comments: &[ReviewComment],
+    line_text: impl Fn(&RepoPath, u32) -> Option<String>,
+) -> String {
+    let mut markdown = String::new();
+    writeln!(markdown, "# Review of `{review_range}`\n").ok();
+    writeln!(markdown, "Viewed {viewed_count} of {file_count} files.").ok();
+    let mut current_path = None;
+    for comment in comments {
+        if current_path != Some(&comment.path) {
+            writeln!(markdown, "\n## `{}`", comment.path.as_unix_str()).ok();
+            current_path = Some(&comment.path);
+        }
+        write!(markdown, "\n**Line {}**", comment.row + 1).ok();
+        match line_text(&comment.path, comment.row) {
+            Some(line) if !line.trim().is_empty() => {
+                writeln!(markdown, ": `{}`", line.trim()).ok();
+            }
+            _ => markdown.push('\n'),
+        }
+        writeln!(markdown, "\n{}", comment.body).ok();
+    }
+    markdown
+}
+
+fn path_key(path: &RepoPath) -> PathKey {
+    PathKey::with_sort_prefix(0, path.0.clone())
+}
+
+fn ref_editor(
+    placeholder: &str,
+    text: Option<&str>,
+    window: &mut Window,
+    cx: &mut Context<BranchReview>,
+) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::single_line(window, cx);
+        editor.set_placeholder_text(placeholder, window, cx);
+        if let Some(text) = text {
+            editor.set_text(text, window, cx);
+        }
+        editor
+    })
+}
+
+impl EventEmitter<ItemEvent> for BranchReview {}
+
+impl Focusable for BranchReview {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for BranchReview {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        match &self.review_range {
+            Some(review_range) => format!("Review {review_range}").into(),
+            None => "Review".into(),
+        }
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Branch Review Opened")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for BranchReview {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("GitBranchReview")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::compare))
+            .on_action(cx.listener(Self::add_comment))
+            .on_action(cx.listener(Self::export_review))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .children(self.render_status())
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .items_start()
+                    .child(
+                        v_flex()
+                            .id("git-review-files")
+                            .w_64()
+                            .h_full()
+                            .flex_none()
+                            .overflow_y_scroll()
+                            .track_scroll(&self.scroll_handle)
+                            .border_r_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(self.render_files(cx))
+                            .child(self.render_comments(cx)),
+                    )
+                    .child(div().flex_1().h_full().child(self.editor.clone())),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_review_markdown() {
+        let comment = |id, path: &str, row, body: &str| ReviewComment {
+            id,
+            path: RepoPath::new(path).unwrap(),
+            row,
+            body: body.to_string(),
+        };
+        let comments = [
+            comment(2, "src/lib.rs", 3, "Missing docs"),
+            comment(1, "src/lib.rs", 12, "Can this panic?"),
+            comment(3, "src/main.rs", 0, "Why was this added?"),
+        ];
+        let markdown = review_markdown(
+            "origin/main...feature",
+            3,
+            1,
+            &comments,
+            |path, row| match (path.as_unix_str(), row) {
+                ("src/lib.rs", 3) => Some("    pub fn run() {".to_string()),
+                ("src/lib.rs", 12) => Some("        value.unwrap()".to_string()),
+                _ => Some(String::new()),
+            },
+        );
+        assert_eq!(
+            markdown,
+            "# Review of `origin/main...feature`\n\
+             \n\
+             Viewed 1 of 3 files.\n\
+             \n\
+             ## `src/lib.rs`\n\
+             \n\
+             **Line 4**: `pub fn run() {`\n\
+             \n\
+             Missing docs\n\
+             \n\
+             **Line 13**: `value.unwrap()`\n\
+             \n\
+             Can this panic?\n\
+             \n\
+             ## `src/main.rs`\n\
+             \n\
+             **Line 1**\n\
+             \n\
+             Why was this added?\n"
+        );
+    }
+}
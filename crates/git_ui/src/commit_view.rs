@@ -2,8 +2,9 @@ use anyhow::{Context as _, Result};
 use buffer_diff::{BufferDiff, BufferDiffSnapshot};
 use editor::{Editor, EditorEvent, MultiBuffer, SelectionEffects, multibuffer_context_lines};
 use git::repository::{CommitDetails, CommitDiff, CommitSummary, RepoPath};
+use git::{CherryPick, CreateTag, RevertCommit};
 use gpui::{
-    AnyElement, AnyView, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter,
+    Action, AnyElement, AnyView, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter,
     FocusHandle, Focusable, IntoElement, Render, WeakEntity, Window,
 };
 use language::{
@@ -18,24 +19,29 @@ use std::{
     path::PathBuf,
     sync::Arc,
 };
-use ui::{Color, Icon, IconName, Label, LabelCommon as _, SharedString};
+use ui::{Tooltip, prelude::*};
 use util::{ResultExt, paths::PathStyle, rel_path::RelPath, truncate_and_trailoff};
 use workspace::{
-    Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
+    Item, ItemHandle, ItemNavHistory, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView,
+    Workspace,
     item::{BreadcrumbText, ItemEvent, TabContentParams},
     searchable::SearchableItemHandle,
 };
 
+use crate::{git_panel::GitPanel, tag_picker};
+
 pub struct CommitView {
     commit: CommitDetails,
     editor: Entity<Editor>,
     multibuffer: Entity<MultiBuffer>,
+    repository: WeakEntity<Repository>,
+    workspace: WeakEntity<Workspace>,
 }
 
-struct GitBlob {
-    path: RepoPath,
-    worktree_id: WorktreeId,
-    is_deleted: bool,
+pub(crate) struct GitBlob {
+    pub(crate) path: RepoPath,
+    pub(crate) worktree_id: WorktreeId,
+    pub(crate) is_deleted: bool,
 }
 
 struct CommitMetadataFile {
@@ -71,12 +77,14 @@ impl CommitView {
                 workspace
                     .update_in(cx, |workspace, window, cx| {
                         let project = workspace.project();
+                        let weak_workspace = cx.weak_entity();
                         let commit_view = cx.new(|cx| {
                             CommitView::new(
                                 commit_details,
                                 commit_diff,
                                 repo,
                                 project.clone(),
+                                weak_workspace,
                                 window,
                                 cx,
                             )
@@ -106,9 +114,11 @@ impl CommitView {
         commit_diff: CommitDiff,
         repository: Entity<Repository>,
         project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
+        let weak_repository = repository.downgrade();
         let language_registry = project.read(cx).languages().clone();
         let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
         let editor = cx.new(|cx| {
@@ -211,8 +221,54 @@ impl CommitView {
             commit,
             editor,
             multibuffer,
+            repository: weak_repository,
+            workspace,
         }
     }
+
+    fn cherry_pick(&mut self, _: &CherryPick, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.upgrade() else {
+            return;
+        };
+        let sha = self.commit.sha.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                    panel.update(cx, |panel, cx| {
+                        panel.cherry_pick(repository, sha, window, cx);
+                    });
+                }
+            })
+            .ok();
+    }
+
+    fn revert_commit(&mut self, _: &RevertCommit, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.upgrade() else {
+            return;
+        };
+        let sha = self.commit.sha.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                    panel.update(cx, |panel, cx| {
+                        panel.revert_commit(repository, sha, window, cx);
+                    });
+                }
+            })
+            .ok();
+    }
+
+    fn create_tag(&mut self, _: &CreateTag, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.upgrade() else {
+            return;
+        };
+        let sha = self.commit.sha.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                tag_picker::create_tag(workspace, repository, sha, window, cx);
+            })
+            .ok();
+    }
 }
 
 impl language::File for GitBlob {
@@ -295,7 +351,7 @@ impl language::File for CommitMetadataFile {
     }
 }
 
-async fn build_buffer(
+pub(crate) async fn build_buffer(
     mut text: String,
     blob: Arc<dyn File>,
     language_registry: &Arc<language::LanguageRegistry>,
@@ -328,7 +384,7 @@ async fn build_buffer(
     Ok(buffer)
 }
 
-async fn build_buffer_diff(
+pub(crate) async fn build_buffer_diff(
     mut old_text: Option<String>,
     buffer: &Entity<Buffer>,
     language_registry: &Arc<LanguageRegistry>,
@@ -538,13 +594,120 @@ impl Item for CommitView {
                 editor,
                 multibuffer,
                 commit: self.commit.clone(),
+                repository: self.repository.clone(),
+                workspace: self.workspace.clone(),
             }
         }))
     }
 }
 
 impl Render for CommitView {
-    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
-        self.editor.clone()
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .key_context("CommitView")
+            .size_full()
+            .on_action(cx.listener(Self::cherry_pick))
+            .on_action(cx.listener(Self::revert_commit))
+            .on_action(cx.listener(Self::create_tag))
+            .child(self.editor.clone())
+    }
+}
+
+pub struct CommitViewToolbar {
+    commit_view: Option<WeakEntity<CommitView>>,
+}
+
+impl Default for CommitViewToolbar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommitViewToolbar {
+    pub fn new() -> Self {
+        Self { commit_view: None }
+    }
+
+    fn commit_view(&self) -> Option<Entity<CommitView>> {
+        self.commit_view.as_ref()?.upgrade()
+    }
+
+    fn dispatch_action(&self, action: &dyn Action, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(commit_view) = self.commit_view() {
+            commit_view.focus_handle(cx).focus(window);
+        }
+        let action = action.boxed_clone();
+        cx.defer(move |cx| {
+            cx.dispatch_action(action.as_ref());
+        })
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for CommitViewToolbar {}
+
+impl ToolbarItemView for CommitViewToolbar {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> ToolbarItemLocation {
+        self.commit_view = active_pane_item
+            .and_then(|item| item.act_as::<CommitView>(cx))
+            .map(|entity| entity.downgrade());
+        if self.commit_view.is_some() {
+            ToolbarItemLocation::PrimaryRight
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+}
+
+impl Render for CommitViewToolbar {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(commit_view) = self.commit_view() else {
+            return div();
+        };
+        let focus_handle = commit_view.focus_handle(cx);
+
+        div().child(
+            h_group_sm()
+                .my_neg_1()
+                .py_1()
+                .items_center()
+                .child(
+                    Button::new("cherry-pick", "Cherry-Pick")
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Apply this commit on top of the current branch",
+                            &CherryPick,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&CherryPick, window, cx)
+                        })),
+                )
+                .child(
+                    Button::new("revert", "Revert")
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Create a commit that undoes this commit",
+                            &RevertCommit,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&RevertCommit, window, cx)
+                        })),
+                )
+                .child(
+                    Button::new("create-tag", "Tag")
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Create a tag pointing at this commit",
+                            &CreateTag,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&CreateTag, window, cx)
+                        })),
+                ),
+        )
     }
 }
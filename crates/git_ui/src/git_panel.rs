@@ -2,6 +2,7 @@ use crate::askpass_modal::AskPassModal;
 use crate::commit_modal::CommitModal;
 use crate::commit_tooltip::CommitTooltip;
 use crate::commit_view::CommitView;
+use crate::merge_editor::MergeEditor;
 use crate::project_diff::{self, Diff, ProjectDiff};
 use crate::remote_output::{self, RemoteAction, SuccessMessage};
 use crate::{branch_picker, picker_prompt, render_remote_button};
@@ -14,18 +15,22 @@ use askpass::AskPassDelegate;
 use db::kvp::KEY_VALUE_STORE;
 use editor::{Editor, EditorElement, EditorMode, MultiBuffer};
 use futures::StreamExt as _;
+use futures::channel::oneshot;
 use git::blame::ParsedCommitMessage;
+use git::lfs::LfsLock;
 use git::repository::{
     Branch, CommitDetails, CommitOptions, CommitSummary, DiffType, FetchOptions, GitCommitter,
-    PushOptions, Remote, RemoteCommandOutput, ResetMode, Upstream, UpstreamTracking,
+    PushOptions, RebaseCommand, Remote, RemoteCommandOutput, ResetMode, Upstream, UpstreamTracking,
     UpstreamTrackingStatus, get_git_committer,
 };
 use git::stash::GitStash;
 use git::status::StageStatus;
+use git::submodule::{Submodule, SubmoduleCommand, SubmoduleStatus};
+use git::worktree::GitWorktree;
 use git::{Amend, Signoff, ToggleStaged, repository::RepoPath, status::FileStatus};
 use git::{
-    ExpandCommitEditor, RestoreTrackedFiles, StageAll, StashAll, StashApply, StashPop,
-    TrashUntrackedFiles, UnstageAll,
+    ExpandCommitEditor, RestoreTrackedFiles, SHORT_SHA_LENGTH, StageAll, StashAll, StashApply,
+    StashPop, TrashUntrackedFiles, UnstageAll,
 };
 use gpui::{
     Action, AsyncApp, AsyncWindowContext, ClickEvent, Corner, DismissEvent, Entity, EventEmitter,
@@ -46,7 +51,7 @@ use panel::{
     panel_icon_button,
 };
 use project::{
-    Fs, Project, ProjectPath,
+    DirectoryLister, Fs, Project, ProjectPath,
     git_store::{GitStoreEvent, Repository, RepositoryEvent, RepositoryId},
 };
 use serde::{Deserialize, Serialize};
@@ -54,7 +59,12 @@ use settings::{Settings, SettingsStore, StatusStyle};
 use std::future::Future;
 use std::ops::Range;
 use std::path::Path;
-use std::{collections::HashSet, sync::Arc, time::Duration, usize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+    usize,
+};
 use strum::{IntoEnumIterator, VariantNames};
 use time::OffsetDateTime;
 use ui::{
@@ -140,6 +150,11 @@ fn git_panel_context_menu(
                 "Unstage All",
                 UnstageAll.boxed_clone(),
             )
+            .action_disabled_when(
+                !state.has_staged_changes,
+                "Split Into Commits…",
+                git::SplitIntoCommits.boxed_clone(),
+            )
             .separator()
             .action_disabled_when(
                 !(state.has_new_changes || state.has_tracked_changes),
@@ -149,7 +164,15 @@ fn git_panel_context_menu(
             .action_disabled_when(!state.has_stash_items, "Stash Pop", StashPop.boxed_clone())
             .action("View Stash", zed_actions::git::ViewStash.boxed_clone())
             .separator()
+            .action("Add Worktree…", git::AddWorktree.boxed_clone())
+            .action(
+                "Open Worktree…",
+                zed_actions::git::OpenWorktree::default().boxed_clone(),
+            )
+            .separator()
             .action("Open Diff", project_diff::Diff.boxed_clone())
+            .action("View History", git::ViewHistory.boxed_clone())
+            .action("Interactive Rebase", git::InteractiveRebase.boxed_clone())
             .separator()
             .action_disabled_when(
                 !state.has_tracked_changes,
@@ -324,6 +347,8 @@ pub struct GitPanel {
     local_committer_task: Option<Task<()>>,
     bulk_staging: Option<BulkStaging>,
     stash_entries: GitStash,
+    lfs_locks: HashMap<RepoPath, LfsLock>,
+    lfs_locks_repository: Option<RepositoryId>,
     _settings_subscription: Subscription,
 }
 
@@ -421,6 +446,8 @@ impl GitPanel {
                 move |this, _git_store, event, window, cx| match event {
                     GitStoreEvent::ActiveRepositoryChanged(_) => {
                         this.active_repository = this.project.read(cx).active_repository(cx);
+                        this.lfs_locks.clear();
+                        this.lfs_locks_repository = None;
                         this.schedule_update(true, window, cx);
                     }
                     GitStoreEvent::RepositoryUpdated(
@@ -485,6 +512,8 @@ impl GitPanel {
                 entry_count: 0,
                 bulk_staging: None,
                 stash_entries: Default::default(),
+                lfs_locks: HashMap::default(),
+                lfs_locks_repository: None,
                 _settings_subscription,
             };
 
@@ -870,6 +899,101 @@ impl GitPanel {
         });
     }
 
+    fn refresh_lfs_locks(&mut self, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        // Locks only matter for the changed LFS files the panel lists.
+        if repo.read(cx).lfs_paths.is_empty() {
+            self.lfs_locks.clear();
+            return;
+        }
+        self.lfs_locks_repository = Some(repo.read(cx).id);
+        let locks = repo.update(cx, |repo, _| repo.lfs_locks());
+        cx.spawn(async move |this, cx| {
+            let locks = match locks.await? {
+                Ok(locks) => locks,
+                Err(error) => {
+                    log::debug!("failed to load Git LFS locks: {error:?}");
+                    return Ok(());
+                }
+            };
+            this.update(cx, |this, cx| {
+                this.lfs_locks = locks
+                    .into_iter()
+                    .map(|lock| (lock.path.clone(), lock))
+                    .collect();
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn lock_file(&mut self, _: &git::LockFile, _window: &mut Window, cx: &mut Context<Self>) {
+        self.set_file_locked(true, cx);
+    }
+
+    fn unlock_file(&mut self, _: &git::UnlockFile, _window: &mut Window, cx: &mut Context<Self>) {
+        self.set_file_locked(false, cx);
+    }
+
+    fn set_file_locked(&mut self, locked: bool, cx: &mut Context<Self>) {
+        maybe!({
+            let entry = self
+                .entries
+                .get(self.selected_entry?)?
+                .status_entry()?
+                .clone();
+            let repo = self.active_repository.clone()?;
+            if !repo.read(cx).lfs_paths.contains(&entry.repo_path) {
+                return None;
+            }
+            let task = repo.update(cx, |repo, _| {
+                if locked {
+                    repo.lfs_lock(entry.repo_path)
+                } else {
+                    repo.lfs_unlock(entry.repo_path, false)
+                }
+            });
+            cx.spawn(async move |this, cx| {
+                let result = task.await;
+                this.update(cx, |this, cx| {
+                    if let Ok(Err(e)) = result {
+                        let action = if locked { "lfs lock" } else { "lfs unlock" };
+                        this.show_error_toast(action, e, cx);
+                    }
+                    this.refresh_lfs_locks(cx);
+                })
+            })
+            .detach_and_log_err(cx);
+            Some(())
+        });
+    }
+
+    fn open_merge_editor(
+        &mut self,
+        _: &git::OpenMergeEditor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        maybe!({
+            let entry = self
+                .entries
+                .get(self.selected_entry?)?
+                .status_entry()?
+                .clone();
+            if !entry.status.is_conflicted() {
+                return None;
+            }
+            let repo = self.active_repository.clone()?;
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    MergeEditor::open(repo, entry.repo_path, workspace, window, cx);
+                })
+                .ok()
+        });
+    }
+
     fn add_to_gitignore(
         &mut self,
         _: &git::AddToGitignore,
@@ -1360,6 +1484,204 @@ impl GitPanel {
         .detach();
     }
 
+    pub fn rebase_command(&mut self, command: RebaseCommand, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let rebase_task = active_repository
+                .update(cx, |repo, _| repo.rebase_command(command))?
+                .await;
+            this.update(cx, |this, cx| {
+                if let Ok(Err(e)) = rebase_task {
+                    this.show_error_toast("rebase", e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
+    /// Runs `git submodule init`, `update` or `sync` on the given submodules,
+    /// or on all of them if no paths are given.
+    pub fn submodule_command(
+        &mut self,
+        command: SubmoduleCommand,
+        paths: Vec<RepoPath>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let submodule_task = active_repository
+                .update(cx, |repo, _| repo.submodule_command(command, paths))?
+                .await;
+            this.update(cx, |this, cx| {
+                if let Ok(Err(e)) = submodule_task {
+                    let action = format!("submodule {}", command.as_args()[0]);
+                    this.show_error_toast(action, e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
+    /// Prompts for the directory of a new linked worktree, and creates it
+    /// with a new branch named after the directory.
+    pub fn add_worktree(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let workspace = self.workspace.clone();
+        let suggested_name = active_repository
+            .read(cx)
+            .work_directory_abs_path
+            .file_name()
+            .map(|name| format!("{}-worktree", name.to_string_lossy()));
+
+        cx.spawn_in(window, async move |this, cx| {
+            let prompt = workspace.update_in(cx, |workspace, window, cx| {
+                let lister = if workspace.project().read(cx).is_local() {
+                    DirectoryLister::Local(
+                        workspace.project().clone(),
+                        workspace.app_state().fs.clone(),
+                    )
+                } else {
+                    DirectoryLister::Project(workspace.project().clone())
+                };
+                workspace.prompt_for_new_path(lister, suggested_name, window, cx)
+            })?;
+            let Some(path) = prompt.await.ok().flatten().into_iter().flatten().next() else {
+                return Ok(());
+            };
+            let branch = path
+                .file_name()
+                .context("worktree path has no directory name")?
+                .to_string_lossy()
+                .into_owned();
+            let worktree_task = active_repository
+                .update(cx, |repo, _| repo.create_worktree(branch, path, None))?
+                .await;
+            this.update(cx, |this, cx| {
+                if let Ok(Err(e)) = worktree_task {
+                    this.show_error_toast("worktree add", e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn remove_worktree(
+        &mut self,
+        worktree: GitWorktree,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let prompt = window.prompt(
+            PromptLevel::Warning,
+            &format!("Remove the worktree {}?", worktree.name()),
+            Some(&format!(
+                "{} will be deleted. Its branch is kept.",
+                worktree.path.display()
+            )),
+            &["Remove", "Cancel"],
+            cx,
+        );
+
+        cx.spawn(async move |this, cx| {
+            if prompt.await != Ok(0) {
+                return Ok(());
+            }
+            let worktree_task = active_repository
+                .update(cx, |repo, _| repo.remove_worktree(worktree.path, false))?
+                .await;
+            this.update(cx, |this, cx| {
+                if let Ok(Err(e)) = worktree_task {
+                    this.show_error_toast("worktree remove", e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn prune_worktrees(&mut self, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let worktree_task = active_repository
+                .update(cx, |repo, _| repo.prune_worktrees())?
+                .await;
+            this.update(cx, |this, cx| {
+                if let Ok(Err(e)) = worktree_task {
+                    this.show_error_toast("worktree prune", e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn cherry_pick(
+        &mut self,
+        repository: Entity<Repository>,
+        commit: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let task = repository.update(cx, |repo, _| repo.cherry_pick(commit.to_string()));
+        self.apply_commit("cherry-pick", task, window, cx);
+    }
+
+    pub(crate) fn revert_commit(
+        &mut self,
+        repository: Entity<Repository>,
+        commit: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let task = repository.update(cx, |repo, _| repo.revert(commit.to_string()));
+        self.apply_commit("revert", task, window, cx);
+    }
+
+    fn apply_commit(
+        &mut self,
+        operation: &'static str,
+        task: oneshot::Receiver<anyhow::Result<()>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn_in(window, async move |this, cx| {
+            let result = task.await;
+            this.update_in(cx, |this, window, cx| {
+                if let Ok(Err(e)) = result {
+                    // Conflicts are resolved in the project diff, and committing
+                    // the resolution completes the cherry-pick or revert.
+                    if e.to_string().contains("CONFLICT") {
+                        this.workspace
+                            .update(cx, |workspace, cx| {
+                                ProjectDiff::deploy_at(workspace, None, window, cx)
+                            })
+                            .ok();
+                    }
+                    this.show_error_toast(operation, e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
     pub fn stash_apply(&mut self, _: &StashApply, _window: &mut Window, cx: &mut Context<Self>) {
         let Some(active_repository) = self.active_repository.clone() else {
             return;
@@ -2017,7 +2339,10 @@ impl GitPanel {
                         FetchOptions::Remote(remote) => RemoteAction::Fetch(Some(remote)),
                     };
                     match remote_message {
-                        Ok(remote_message) => this.show_remote_output(action, remote_message, cx),
+                        Ok(remote_message) => {
+                            this.show_remote_output(action, remote_message, cx);
+                            this.refresh_lfs_locks(cx);
+                        }
                         Err(e) => {
                             log::error!("Error while fetching {:?}", e);
                             this.show_error_toast(action.name(), e, cx)
@@ -2248,7 +2573,10 @@ impl GitPanel {
 
             let action = RemoteAction::Pull(remote);
             this.update(cx, |this, cx| match remote_message {
-                Ok(remote_message) => this.show_remote_output(action, remote_message, cx),
+                Ok(remote_message) => {
+                    this.show_remote_output(action, remote_message, cx);
+                    this.refresh_lfs_locks(cx);
+                }
                 Err(e) => {
                     log::error!("Error while pulling {:?}", e);
                     this.show_error_toast(action.name(), e, cx)
@@ -2338,6 +2666,64 @@ impl GitPanel {
         .detach_and_log_err(cx);
     }
 
+    pub(crate) fn push_tag(
+        &mut self,
+        tag: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.can_push_and_pull(cx) {
+            return;
+        }
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let remote = self.get_remote(true, window, cx);
+
+        cx.spawn_in(window, async move |this, cx| {
+            let remote = match remote.await {
+                Ok(Some(remote)) => remote,
+                Ok(None) => {
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::error!("Failed to get current remote: {}", e);
+                    this.update(cx, |this, cx| this.show_error_toast("push", e, cx))
+                        .ok();
+                    return Ok(());
+                }
+            };
+
+            let askpass_delegate = this.update_in(cx, |this, window, cx| {
+                this.askpass_delegate(format!("git push {} {}", remote.name, tag), window, cx)
+            })?;
+
+            let push = repo.update(cx, |repo, cx| {
+                repo.push(
+                    format!("refs/tags/{tag}").into(),
+                    remote.name.clone(),
+                    None,
+                    askpass_delegate,
+                    cx,
+                )
+            })?;
+
+            let remote_output = push.await?;
+
+            let action = RemoteAction::Push(tag, remote);
+            this.update(cx, |this, cx| match remote_output {
+                Ok(remote_message) => this.show_remote_output(action, remote_message, cx),
+                Err(e) => {
+                    log::error!("Error while pushing tag {:?}", e);
+                    this.show_error_toast(action.name(), e, cx)
+                }
+            })?;
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn askpass_delegate(
         &self,
         operation: impl Into<SharedString>,
@@ -2661,6 +3047,8 @@ impl GitPanel {
         let repo = repo.read(cx);
 
         self.stash_entries = repo.cached_stash();
+        let needs_lfs_locks =
+            !repo.lfs_paths.is_empty() && self.lfs_locks_repository != Some(repo.id);
 
         for entry in repo.cached_status() {
             let is_conflict = repo.had_conflict_on_last_merge_head_change(&entry.repo_path);
@@ -2804,6 +3192,10 @@ impl GitPanel {
             editor.set_placeholder_text(&placeholder_text, window, cx)
         });
 
+        if needs_lfs_locks {
+            self.refresh_lfs_locks(cx);
+        }
+
         cx.notify();
     }
 
@@ -3580,6 +3972,375 @@ impl GitPanel {
             )
     }
 
+    fn render_rebase_in_progress(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let active_repository = self.active_repository.as_ref()?;
+        if !active_repository.read(cx).merge.rebasing {
+            return None;
+        }
+        let message = if self.has_unstaged_conflicts() {
+            "Rebasing. Resolve and stage the conflicts to continue."
+        } else {
+            "Rebasing."
+        };
+
+        Some(
+            h_flex()
+                .py_1p5()
+                .px_2()
+                .gap_1p5()
+                .justify_between()
+                .border_t_1()
+                .border_color(cx.theme().colors().border.opacity(0.8))
+                .child(
+                    div()
+                        .flex_grow()
+                        .overflow_hidden()
+                        .child(Label::new(message).size(LabelSize::Small).truncate()),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            panel_button("Abort")
+                                .size(ButtonSize::Default)
+                                .tooltip(Tooltip::for_action_title_in(
+                                    "git rebase --abort",
+                                    &git::RebaseAbort,
+                                    &self.focus_handle,
+                                ))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.rebase_command(RebaseCommand::Abort, cx)
+                                })),
+                        )
+                        .child(
+                            panel_button("Skip")
+                                .size(ButtonSize::Default)
+                                .tooltip(Tooltip::for_action_title_in(
+                                    "git rebase --skip",
+                                    &git::RebaseSkip,
+                                    &self.focus_handle,
+                                ))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.rebase_command(RebaseCommand::Skip, cx)
+                                })),
+                        )
+                        .child(
+                            panel_filled_button("Continue")
+                                .size(ButtonSize::Default)
+                                .disabled(self.has_unstaged_conflicts())
+                                .tooltip(Tooltip::for_action_title_in(
+                                    "git rebase --continue",
+                                    &git::RebaseContinue,
+                                    &self.focus_handle,
+                                ))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.rebase_command(RebaseCommand::Continue, cx)
+                                })),
+                        ),
+                ),
+        )
+    }
+
+    fn render_submodules(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let active_repository = self.active_repository.as_ref()?;
+        let submodules = active_repository.read(cx).submodules.clone();
+        if submodules.is_empty() {
+            return None;
+        }
+
+        Some(
+            v_flex()
+                .py_1p5()
+                .px_2()
+                .gap_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border.opacity(0.8))
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .justify_between()
+                        .child(
+                            Label::new("Submodules")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    panel_button("Sync")
+                                        .tooltip(Tooltip::for_action_title_in(
+                                            "git submodule sync",
+                                            &git::SubmoduleSync,
+                                            &self.focus_handle,
+                                        ))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.submodule_command(
+                                                SubmoduleCommand::Sync,
+                                                Vec::new(),
+                                                cx,
+                                            )
+                                        })),
+                                )
+                                .child(
+                                    panel_button("Init")
+                                        .disabled(!submodules.iter().any(|submodule| {
+                                            submodule.status == SubmoduleStatus::Uninitialized
+                                        }))
+                                        .tooltip(Tooltip::for_action_title_in(
+                                            "git submodule init",
+                                            &git::SubmoduleInit,
+                                            &self.focus_handle,
+                                        ))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.submodule_command(
+                                                SubmoduleCommand::Init,
+                                                Vec::new(),
+                                                cx,
+                                            )
+                                        })),
+                                )
+                                .child(
+                                    panel_button("Update")
+                                        .disabled(!submodules.iter().any(Submodule::needs_update))
+                                        .tooltip(Tooltip::for_action_title_in(
+                                            "git submodule update --init",
+                                            &git::SubmoduleUpdate,
+                                            &self.focus_handle,
+                                        ))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.submodule_command(
+                                                SubmoduleCommand::Update,
+                                                Vec::new(),
+                                                cx,
+                                            )
+                                        })),
+                                ),
+                        ),
+                )
+                .children(
+                    submodules
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, submodule)| self.render_submodule(ix, submodule, cx)),
+                ),
+        )
+    }
+
+    fn render_worktrees(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let active_repository = self.active_repository.as_ref()?.read(cx);
+        // Only list them once there's a linked worktree besides the main one.
+        if active_repository.worktrees.len() < 2 {
+            return None;
+        }
+        let worktrees = active_repository.worktrees.clone();
+        let current_path = active_repository
+            .current_worktree()
+            .map(|worktree| worktree.path.clone());
+
+        Some(
+            v_flex()
+                .py_1p5()
+                .px_2()
+                .gap_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border.opacity(0.8))
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .justify_between()
+                        .child(
+                            Label::new("Worktrees")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    panel_button("Prune")
+                                        .disabled(
+                                            !worktrees.iter().any(|worktree| worktree.prunable),
+                                        )
+                                        .tooltip(Tooltip::for_action_title_in(
+                                            "git worktree prune",
+                                            &git::PruneWorktrees,
+                                            &self.focus_handle,
+                                        ))
+                                        .on_click(
+                                            cx.listener(|this, _, _, cx| this.prune_worktrees(cx)),
+                                        ),
+                                )
+                                .child(
+                                    panel_button("Add")
+                                        .tooltip(Tooltip::for_action_title_in(
+                                            "git worktree add",
+                                            &git::AddWorktree,
+                                            &self.focus_handle,
+                                        ))
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.add_worktree(window, cx)
+                                        })),
+                                ),
+                        ),
+                )
+                .children(worktrees.into_iter().enumerate().map(|(ix, worktree)| {
+                    let is_current = current_path.as_ref() == Some(&worktree.path);
+                    self.render_worktree(ix, worktree, is_current, cx)
+                })),
+        )
+    }
+
+    fn render_worktree(
+        &self,
+        ix: usize,
+        worktree: GitWorktree,
+        is_current: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let (status, color) = if worktree.prunable {
+            ("Missing", Color::Warning)
+        } else if worktree.locked {
+            ("Locked", Color::Muted)
+        } else if is_current {
+            ("Current", Color::Accent)
+        } else {
+            ("", Color::Muted)
+        };
+        let branch = worktree
+            .branch
+            .clone()
+            .or_else(|| {
+                worktree.sha.as_ref().map(|sha| {
+                    sha.chars()
+                        .take(SHORT_SHA_LENGTH)
+                        .collect::<String>()
+                        .into()
+                })
+            })
+            .unwrap_or_default();
+        let can_open = !is_current && !worktree.prunable;
+        let can_remove = !is_current && !worktree.is_main && !worktree.locked;
+        h_flex()
+            .gap_1p5()
+            .child(
+                Icon::new(IconName::GitBranchAlt)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_1()
+                    .child(
+                        Label::new(worktree.name())
+                            .size(LabelSize::Small)
+                            .truncate(),
+                    )
+                    .child(
+                        Label::new(branch)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .truncate(),
+                    ),
+            )
+            .when(!status.is_empty(), |this| {
+                this.child(Label::new(status).size(LabelSize::Small).color(color))
+            })
+            .when(can_open, |this| {
+                let path = worktree.path.clone();
+                this.child(
+                    IconButton::new(("open-worktree", ix), IconName::ArrowUpRight)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text(format!(
+                            "Open {} in a New Window",
+                            worktree.path.display()
+                        )))
+                        .on_click(move |_, window, cx| {
+                            window.dispatch_action(
+                                Box::new(zed_actions::git::OpenWorktree {
+                                    path: Some(path.clone()),
+                                }),
+                                cx,
+                            )
+                        }),
+                )
+            })
+            .when(can_remove, |this| {
+                this.child(
+                    IconButton::new(("remove-worktree", ix), IconName::Trash)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("git worktree remove"))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.remove_worktree(worktree.clone(), window, cx)
+                        })),
+                )
+            })
+    }
+
+    fn render_submodule(
+        &self,
+        ix: usize,
+        submodule: &Submodule,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let (status, color) = match submodule.status {
+            SubmoduleStatus::Uninitialized => ("Not initialized", Color::Muted),
+            SubmoduleStatus::OutOfDate => ("Out of date", Color::Warning),
+            SubmoduleStatus::Conflicted => ("Conflicted", Color::VersionControlConflict),
+            SubmoduleStatus::UpToDate if submodule.dirty => {
+                ("Modified", Color::VersionControlModified)
+            }
+            SubmoduleStatus::UpToDate => ("Up to date", Color::Muted),
+        };
+        let path = submodule.path.clone();
+        h_flex()
+            .gap_1p5()
+            .child(
+                Icon::new(IconName::Folder)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                div().flex_1().min_w_0().child(
+                    Label::new(submodule.path.as_unix_str().to_string())
+                        .size(LabelSize::Small)
+                        .truncate(),
+                ),
+            )
+            .when(
+                submodule.dirty && submodule.status != SubmoduleStatus::UpToDate,
+                |this| {
+                    this.child(
+                        Label::new("Modified")
+                            .size(LabelSize::Small)
+                            .color(Color::VersionControlModified),
+                    )
+                },
+            )
+            .child(Label::new(status).size(LabelSize::Small).color(color))
+            .when(submodule.needs_update(), |this| {
+                let label = if submodule.status == SubmoduleStatus::Uninitialized {
+                    "Init"
+                } else {
+                    "Update"
+                };
+                this.child(
+                    Button::new(("submodule-update", ix), label)
+                        .label_size(LabelSize::Small)
+                        .size(ButtonSize::Compact)
+                        .tooltip(Tooltip::text(format!(
+                            "git submodule update --init -- {}",
+                            submodule.path.as_unix_str()
+                        )))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.submodule_command(SubmoduleCommand::Update, vec![path.clone()], cx)
+                        })),
+                )
+            })
+    }
+
     fn render_previous_commit(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
         let active_repository = self.active_repository.as_ref()?;
         let branch = active_repository.read(cx).branch.as_ref()?;
@@ -3887,6 +4648,11 @@ impl GitPanel {
         } else {
             "Restore File"
         };
+        let is_lfs = self
+            .active_repository
+            .as_ref()
+            .is_some_and(|repo| repo.read(cx).lfs_paths.contains(&entry.repo_path));
+        let lfs_lock = self.lfs_locks.get(&entry.repo_path).cloned();
         let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
             let mut context_menu = context_menu
                 .context(self.focus_handle.clone())
@@ -3898,6 +4664,25 @@ impl GitPanel {
                     context_menu.action("Add to .gitignore", git::AddToGitignore.boxed_clone());
             }
 
+            if entry.status.is_conflicted() {
+                context_menu =
+                    context_menu.action("Open Merge Editor", git::OpenMergeEditor.boxed_clone());
+            }
+
+            if is_lfs {
+                context_menu = match lfs_lock {
+                    None => context_menu.action("Lock File", git::LockFile.boxed_clone()),
+                    Some(lock) if lock.ours => {
+                        context_menu.action("Unlock File", git::UnlockFile.boxed_clone())
+                    }
+                    Some(lock) => context_menu.action_disabled_when(
+                        true,
+                        format!("Locked by {}", lock.owner),
+                        git::UnlockFile.boxed_clone(),
+                    ),
+                };
+            }
+
             context_menu
                 .separator()
                 .action("Open Diff", Confirm.boxed_clone())
@@ -3972,6 +4757,14 @@ impl GitPanel {
         let has_conflict = status.is_conflicted();
         let is_modified = status.is_modified();
         let is_deleted = status.is_deleted();
+        let is_lfs = self
+            .active_repository
+            .as_ref()
+            .is_some_and(|repo| repo.read(cx).lfs_paths.contains(&entry.repo_path));
+        let is_submodule = self
+            .active_repository
+            .as_ref()
+            .is_some_and(|repo| repo.read(cx).submodule(&entry.repo_path).is_some());
 
         let label_color = if status_style == StatusStyle::LabelColor {
             if has_conflict {
@@ -3999,6 +4792,8 @@ impl GitPanel {
             ElementId::Name(format!("entry_{}_{}_checkbox_wrapper", display_name, ix).into());
         let checkbox_id: ElementId =
             ElementId::Name(format!("entry_{}_{}_checkbox", display_name, ix).into());
+        let lock_id: ElementId =
+            ElementId::Name(format!("entry_{}_{}_lock", display_name, ix).into());
 
         let entry_staging = self.entry_staging(entry);
         let mut is_staged: ToggleState = self.entry_staging(entry).as_bool().into();
@@ -4153,6 +4948,42 @@ impl GitPanel {
                             .when(status.is_deleted(), |this| this.strikethrough()),
                     ),
             )
+            .when(is_lfs, |this| {
+                this.child(
+                    Label::new("LFS")
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                )
+            })
+            .when(is_submodule, |this| {
+                this.child(
+                    Label::new("Submodule")
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                )
+            })
+            .when_some(self.lfs_locks.get(&entry.repo_path), |this, lock| {
+                let tooltip = if lock.ours {
+                    "Locked by you".to_string()
+                } else {
+                    format!("Locked by {}", lock.owner)
+                };
+                this.child(
+                    div()
+                        .id(lock_id)
+                        .flex_none()
+                        .child(
+                            Icon::new(IconName::LockOutlined)
+                                .size(IconSize::XSmall)
+                                .color(if lock.ours {
+                                    Color::Accent
+                                } else {
+                                    Color::Warning
+                                }),
+                        )
+                        .tooltip(Tooltip::text(tooltip)),
+                )
+            })
             .into_any_element()
     }
 
@@ -4322,6 +5153,9 @@ impl Render for GitPanel {
                     .on_action(cx.listener(Self::restore_tracked_files))
                     .on_action(cx.listener(Self::revert_selected))
                     .on_action(cx.listener(Self::add_to_gitignore))
+                    .on_action(cx.listener(Self::open_merge_editor))
+                    .on_action(cx.listener(Self::lock_file))
+                    .on_action(cx.listener(Self::unlock_file))
                     .on_action(cx.listener(Self::clean_all))
                     .on_action(cx.listener(Self::generate_commit_message_action))
                     .on_action(cx.listener(Self::stash_all))
@@ -4355,6 +5189,9 @@ impl Render for GitPanel {
                             this.child(self.render_empty_state(cx).into_any_element())
                         }
                     })
+                    .children(self.render_rebase_in_progress(cx))
+                    .children(self.render_submodules(cx))
+                    .children(self.render_worktrees(cx))
                     .children(self.render_footer(window, cx))
                     .when(self.amend_pending, |this| {
                         this.child(self.render_pending_amend(cx))
@@ -12,8 +12,9 @@ use ui::{
 mod blame_ui;
 
 use git::{
-    repository::{Branch, Upstream, UpstreamTracking, UpstreamTrackingStatus},
+    repository::{Branch, RebaseCommand, Upstream, UpstreamTracking, UpstreamTrackingStatus},
     status::{FileStatus, StatusCode, UnmergedStatus, UnmergedStatusCode},
+    submodule::SubmoduleCommand,
 };
 use git_panel_settings::GitPanelSettings;
 use gpui::{
@@ -31,20 +32,28 @@ use zed_actions;
 use crate::{git_panel::GitPanel, text_diff_view::TextDiffView};
 
 mod askpass_modal;
+mod bisect_view;
 pub mod branch_picker;
+pub mod branch_review;
 mod commit_modal;
 pub mod commit_tooltip;
-mod commit_view;
+pub mod commit_view;
 mod conflict_view;
 pub mod file_diff_view;
 pub mod git_panel;
 mod git_panel_settings;
+mod history_view;
+mod merge_editor;
 pub mod onboarding;
+mod persistence;
 pub mod picker_prompt;
 pub mod project_diff;
+mod rebase_editor;
 pub(crate) mod remote_output;
 pub mod repository_selector;
+mod split_commits;
 pub mod stash_picker;
+pub mod tag_picker;
 pub mod text_diff_view;
 
 actions!(
@@ -72,6 +81,13 @@ pub fn init(cx: &mut App) {
         repository_selector::register(workspace);
         branch_picker::register(workspace);
         stash_picker::register(workspace);
+        tag_picker::register(workspace);
+        history_view::register(workspace);
+        rebase_editor::register(workspace);
+        bisect_view::register(workspace);
+        branch_review::register(workspace);
+        merge_editor::register(workspace);
+        split_commits::register(workspace);
 
         let project = workspace.project().read(cx);
         if project.is_read_only(cx) {
@@ -151,6 +167,70 @@ pub fn init(cx: &mut App) {
                 panel.stash_apply(action, window, cx);
             });
         });
+        workspace.register_action(|workspace, _: &git::RebaseContinue, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.rebase_command(RebaseCommand::Continue, cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::RebaseSkip, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.rebase_command(RebaseCommand::Skip, cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::RebaseAbort, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.rebase_command(RebaseCommand::Abort, cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::SubmoduleInit, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.submodule_command(SubmoduleCommand::Init, Vec::new(), cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::SubmoduleUpdate, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.submodule_command(SubmoduleCommand::Update, Vec::new(), cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::SubmoduleSync, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.submodule_command(SubmoduleCommand::Sync, Vec::new(), cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::AddWorktree, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.add_worktree(window, cx);
+            });
+        });
+        workspace.register_action(|workspace, _: &git::PruneWorktrees, _, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.prune_worktrees(cx);
+            });
+        });
         workspace.register_action(|workspace, action: &git::StageAll, window, cx| {
             let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
                 return;
@@ -0,0 +1,518 @@
+use std::{
+    ops::{Range, RangeInclusive},
+    time::Duration,
+};
+
+use editor::{Editor, EditorEvent};
+use git::{
+    SHORT_SHA_LENGTH,
+    repository::{CommitSummary, LogOptions, RepoPath},
+};
+use gpui::{
+    App, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy, Subscription, Task,
+    UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use language::Point;
+use project::git_store::Repository;
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+use crate::commit_view::CommitView;
+
+/// How many commits are loaded at a time as the list is scrolled.
+const PAGE_SIZE: usize = 100;
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(view_history);
+    workspace.register_action(view_file_history);
+    workspace.register_action(view_line_history);
+}
+
+fn view_history(
+    workspace: &mut Workspace,
+    _: &git::ViewHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    HistoryView::open(repository, None, None, workspace, window, cx);
+}
+
+fn view_file_history(
+    workspace: &mut Workspace,
+    _: &git::ViewFileHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some((_, repository, path)) = active_file(workspace, cx) else {
+        return;
+    };
+    HistoryView::open(repository, Some(path), None, workspace, window, cx);
+}
+
+fn view_line_history(
+    workspace: &mut Workspace,
+    _: &git::ViewLineHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some((editor, repository, path)) = active_file(workspace, cx) else {
+        return;
+    };
+    let selection = editor.update(cx, |editor, cx| editor.selections.newest::<Point>(cx));
+    let mut end_row = selection.end.row;
+    // A selection of whole lines ends at the start of the following line.
+    if selection.end.column == 0 && end_row > selection.start.row {
+        end_row -= 1;
+    }
+    let line_range = selection.start.row + 1..=end_row + 1;
+    HistoryView::open(
+        repository,
+        Some(path),
+        Some(line_range),
+        workspace,
+        window,
+        cx,
+    );
+}
+
+fn active_file(
+    workspace: &Workspace,
+    cx: &App,
+) -> Option<(Entity<Editor>, Entity<Repository>, RepoPath)> {
+    let editor = workspace.active_item_as::<Editor>(cx)?;
+    let project_path = editor.read(cx).project_path(cx)?;
+    let (repository, path) = workspace
+        .project()
+        .read(cx)
+        .git_store()
+        .read(cx)
+        .repository_and_path_for_project_path(&project_path, cx)?;
+    Some((editor, repository, path))
+}
+
+/// Lists the commits of a repository, a file or a range of lines in a file,
+/// newest first, loading more of them as the list is scrolled.
+pub struct HistoryView {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    title: SharedString,
+    branch_editor: Entity<Editor>,
+    author_editor: Entity<Editor>,
+    path_editor: Entity<Editor>,
+    /// The lines whose history is listed, until the path filter is changed
+    /// from the file they belong to.
+    line_range: Option<(RepoPath, RangeInclusive<u32>)>,
+    commits: Vec<CommitSummary>,
+    has_more: bool,
+    load_task: Option<Task<()>>,
+    error: Option<SharedString>,
+    selected_index: Option<usize>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl HistoryView {
+    pub fn open(
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        line_range: Option<RangeInclusive<u32>>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let workspace_handle = workspace.weak_handle();
+        let view =
+            cx.new(|cx| Self::new(repository, path, line_range, workspace_handle, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        line_range: Option<RangeInclusive<u32>>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let title = match (&path, &line_range) {
+            (Some(path), Some(line_range)) => format!(
+                "History of {}:{}-{}",
+                path.file_name().unwrap_or_default(),
+                line_range.start(),
+                line_range.end()
+            ),
+            (Some(path), None) => {
+                format!("History of {}", path.file_name().unwrap_or_default())
+            }
+            (None, _) => "History".to_string(),
+        };
+
+        let branch_editor = filter_editor("Branch or revision", None, window, cx);
+        let author_editor = filter_editor("Author", None, window, cx);
+        let path_editor = filter_editor(
+            "Path",
+            path.as_ref().map(|path| path.as_unix_str()),
+            window,
+            cx,
+        );
+        let subscriptions = [&branch_editor, &author_editor, &path_editor]
+            .into_iter()
+            .map(|editor| {
+                cx.subscribe(editor, |this, _, event: &EditorEvent, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.schedule_reload(cx);
+                    }
+                })
+            })
+            .collect();
+
+        let mut this = Self {
+            repository,
+            workspace,
+            title: title.into(),
+            branch_editor,
+            author_editor,
+            path_editor,
+            line_range: path.zip(line_range),
+            commits: Vec::new(),
+            has_more: true,
+            load_task: None,
+            error: None,
+            selected_index: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            _subscriptions: subscriptions,
+        };
+        this.load_next_page(cx);
+        this
+    }
+
+    fn schedule_reload(&mut self, cx: &mut Context<Self>) {
+        self.load_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(FILTER_DEBOUNCE).await;
+            this.update(cx, |this, cx| {
+                if let Some((path, _)) = &this.line_range
+                    && this.path_editor.read(cx).text(cx).trim() != path.as_unix_str()
+                {
+                    this.line_range = None;
+                }
+                this.load_task = None;
+                this.commits.clear();
+                this.has_more = true;
+                this.error = None;
+                this.selected_index = None;
+                this.load_next_page(cx);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn log_options(&self, cx: &App) -> anyhow::Result<LogOptions> {
+        let text = |editor: &Entity<Editor>| {
+            let text = editor.read(cx).text(cx);
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        };
+        let path = text(&self.path_editor)
+            .map(|path| RepoPath::new(&path))
+            .transpose()?;
+        Ok(LogOptions {
+            branch: text(&self.branch_editor),
+            author: text(&self.author_editor),
+            path,
+            line_range: self
+                .line_range
+                .as_ref()
+                .map(|(_, line_range)| line_range.clone()),
+            skip: self.commits.len(),
+            limit: PAGE_SIZE,
+        })
+    }
+
+    fn load_next_page(&mut self, cx: &mut Context<Self>) {
+        if self.load_task.is_some() || !self.has_more {
+            return;
+        }
+        let options = match self.log_options(cx) {
+            Ok(options) => options,
+            Err(error) => {
+                self.has_more = false;
+                self.error = Some(error.to_string().into());
+                return;
+            }
+        };
+
+        let commits = self
+            .repository
+            .update(cx, |repository, _| repository.log(options));
+        self.load_task = Some(cx.spawn(async move |this, cx| {
+            let commits = match commits.await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!("The repository was closed")),
+            };
+            this.update(cx, |this, cx| {
+                this.load_task = None;
+                match commits {
+                    Ok(commits) => {
+                        this.has_more = commits.len() == PAGE_SIZE;
+                        this.commits.extend(commits);
+                    }
+                    Err(error) => {
+                        this.has_more = false;
+                        this.error = Some(error.to_string().into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_index = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let ix = self
+            .selected_index
+            .map_or(0, |ix| (ix + 1).min(self.commits.len().saturating_sub(1)));
+        if !self.commits.is_empty() {
+            self.select_index(ix, cx);
+        }
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = self.selected_index.map_or(0, |ix| ix.saturating_sub(1));
+        if !self.commits.is_empty() {
+            self.select_index(ix, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_index {
+            self.open_commit(ix, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(commit) = self.commits.get(ix) else {
+            return;
+        };
+        CommitView::open(
+            commit.clone(),
+            self.repository.downgrade(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+
+    fn render_filter(&self, label: &'static str, editor: &Entity<Editor>, cx: &App) -> Div {
+        h_flex()
+            .flex_1()
+            .gap_1p5()
+            .px_2()
+            .py_1()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .rounded_sm()
+            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+            .child(div().flex_1().child(editor.clone()))
+    }
+
+    fn render_commits(&self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let now = OffsetDateTime::now_utc();
+        range
+            .filter_map(|ix| {
+                let commit = self.commits.get(ix)?;
+                let commit_time =
+                    OffsetDateTime::from_unix_timestamp(commit.commit_timestamp).unwrap_or(now);
+                let short_sha = commit.sha.get(..SHORT_SHA_LENGTH).unwrap_or(&commit.sha);
+                Some(
+                    ListItem::new(ix)
+                        .spacing(ListItemSpacing::Sparse)
+                        .toggle_state(self.selected_index == Some(ix))
+                        .child(
+                            h_flex()
+                                .w_full()
+                                .gap_3()
+                                .child(
+                                    Label::new(short_sha.to_string())
+                                        .buffer_font(cx)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .min_w_0()
+                                        .child(Label::new(commit.subject.clone()).truncate()),
+                                )
+                                .child(
+                                    Label::new(commit.author_name.clone())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    Label::new(format_local_timestamp(
+                                        commit_time,
+                                        now,
+                                        time_format::TimestampFormat::Relative,
+                                    ))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                ),
+                        )
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.select_index(ix, cx);
+                            this.open_commit(ix, window, cx);
+                        }))
+                        .into_any_element(),
+                )
+            })
+            .collect()
+    }
+
+    fn render_status(&self) -> Option<impl IntoElement> {
+        let message: SharedString = if let Some(error) = &self.error {
+            error.clone()
+        } else if self.load_task.is_some() {
+            "Loading commits…".into()
+        } else if self.commits.is_empty() {
+            "No commits match these filters".into()
+        } else {
+            return None;
+        };
+        Some(
+            h_flex().p_2().justify_center().child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(if self.error.is_some() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            ),
+        )
+    }
+}
+
+fn filter_editor(
+    placeholder: &str,
+    text: Option<&str>,
+    window: &mut Window,
+    cx: &mut Context<HistoryView>,
+) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::single_line(window, cx);
+        editor.set_placeholder_text(placeholder, window, cx);
+        if let Some(text) = text {
+            editor.set_text(text, window, cx);
+        }
+        editor
+    })
+}
+
+impl EventEmitter<ItemEvent> for HistoryView {}
+
+impl Focusable for HistoryView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HistoryView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        self.title.clone()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git History Opened")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for HistoryView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_range = self
+            .line_range
+            .as_ref()
+            .map(|(_, line_range)| format!("Lines {}–{}", line_range.start(), line_range.end()));
+        v_flex()
+            .key_context("GitHistory")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.render_filter("Branch", &self.branch_editor, cx))
+                    .child(self.render_filter("Author", &self.author_editor, cx))
+                    .child(self.render_filter("Path", &self.path_editor, cx))
+                    .children(line_range.map(|line_range| {
+                        Label::new(line_range)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    })),
+            )
+            .child(
+                uniform_list(
+                    "git-history",
+                    self.commits.len(),
+                    cx.processor(|this, range: Range<usize>, _window, cx| {
+                        if range.end + PAGE_SIZE / 2 >= this.commits.len() {
+                            this.load_next_page(cx);
+                        }
+                        this.render_commits(range, cx)
+                    }),
+                )
+                .flex_1()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+            .children(self.render_status())
+    }
+}
@@ -0,0 +1,1115 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Context as _;
+use editor::{Editor, MultiBuffer, SelectionEffects, scroll::Autoscroll};
+use git::repository::{MergeStages, RepoPath};
+use gpui::{
+    App, Entity, EventEmitter, FocusHandle, Focusable, PromptLevel, Subscription, Task, actions,
+};
+use language::{Buffer, OffsetRangeExt as _, ToOffset as _};
+use project::{ConflictRegion, ConflictSet, ConflictSetUpdate, Project, git_store::Repository};
+use ui::{Tooltip, prelude::*};
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+use crate::commit_view::{GitBlob, build_buffer, build_buffer_diff};
+
+actions!(
+    merge_editor,
+    [
+        /// Resolves the current conflict with our version of it.
+        AcceptOurs,
+        /// Resolves the current conflict with their version of it.
+        AcceptTheirs,
+        /// Resolves the current conflict with our version followed by theirs.
+        AcceptBoth,
+        /// Resolves the current conflict with the version both sides started from.
+        AcceptBase,
+        /// Moves to the next conflict in the result.
+        NextConflict,
+        /// Moves to the previous conflict in the result.
+        PreviousConflict,
+        /// Saves the result and stages it, marking the conflict as resolved.
+        MarkResolved,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+fn open(
+    workspace: &mut Workspace,
+    _: &git::OpenMergeEditor,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(project_path) = editor.read(cx).project_path(cx) else {
+        return;
+    };
+    let Some((repository, path)) = workspace
+        .project()
+        .read(cx)
+        .git_store()
+        .read(cx)
+        .repository_and_path_for_project_path(&project_path, cx)
+    else {
+        return;
+    };
+    if !repository.read(cx).has_conflict(&path) {
+        return;
+    }
+    MergeEditor::open(repository, path, workspace, window, cx);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Base,
+    Ours,
+    Theirs,
+}
+
+impl Side {
+    fn title(self) -> &'static str {
+        match self {
+            Side::Base => "Base",
+            Side::Ours => "Ours (HEAD)",
+            Side::Theirs => "Theirs",
+        }
+    }
+
+    fn range(self, conflict: &ConflictRegion) -> Option<Range<language::Anchor>> {
+        match self {
+            Side::Base => conflict.base.clone(),
+            Side::Ours => Some(conflict.ours.clone()),
+            Side::Theirs => Some(conflict.theirs.clone()),
+        }
+    }
+}
+
+/// A read-only version of the file from one of the index stages of the merge.
+struct MergePane {
+    side: Side,
+    buffer: Entity<Buffer>,
+    editor: Entity<Editor>,
+}
+
+/// Resolves a conflicted file by comparing the version both sides started
+/// from, our version and their version side by side, above the result being
+/// edited. Our and their versions are highlighted against the base. Each
+/// conflict in the result can be resolved with either side, both or the base,
+/// and the file is staged once it's marked as resolved.
+pub struct MergeEditor {
+    repository: Entity<Repository>,
+    project: Entity<Project>,
+    path: RepoPath,
+    panes: Vec<MergePane>,
+    result_buffer: Option<Entity<Buffer>>,
+    result_editor: Option<Entity<Editor>>,
+    conflict_set: Option<Entity<ConflictSet>>,
+    /// Whether the first conflict has been selected, which waits for the
+    /// conflicts to be parsed after the result is opened.
+    first_conflict_selected: bool,
+    load_task: Option<Task<()>>,
+    pending_task: Option<Task<()>>,
+    error: Option<SharedString>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MergeEditor {
+    pub(crate) fn open(
+        repository: Entity<Repository>,
+        path: RepoPath,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .items_of_type::<MergeEditor>(cx)
+            .find(|item| item.read(cx).path == path);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+        let project = workspace.project().clone();
+        let view = cx.new(|cx| Self::new(repository, project, path, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        project: Entity<Project>,
+        path: RepoPath,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let stages = repository.update(cx, |repository, _| {
+            repository.load_merge_stages(path.clone())
+        });
+        let open_buffer = repository
+            .read(cx)
+            .repo_path_to_project_path(&path, cx)
+            .map(|project_path| {
+                project.update(cx, |project, cx| project.open_buffer(project_path, cx))
+            });
+        let language_registry = project.read(cx).languages().clone();
+        let path_for_blobs = path.clone();
+
+        let load_task = cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let buffer = open_buffer.context("file is not in the project")?.await?;
+                let stages = stages.await??;
+                let worktree_id = buffer
+                    .read_with(cx, |buffer, cx| {
+                        buffer.file().map(|file| file.worktree_id(cx))
+                    })?
+                    .context("file is not in the project")?;
+                let MergeStages { base, ours, theirs } = stages;
+
+                let mut panes = Vec::new();
+                for (side, text) in [
+                    (Side::Base, &base),
+                    (Side::Ours, &ours),
+                    (Side::Theirs, &theirs),
+                ] {
+                    let Some(text) = text.clone() else {
+                        continue;
+                    };
+                    let blob = Arc::new(GitBlob {
+                        path: path_for_blobs.clone(),
+                        worktree_id,
+                        is_deleted: false,
+                    }) as Arc<dyn language::File>;
+                    let pane_buffer = build_buffer(text, blob, &language_registry, cx).await?;
+                    let diff = if side == Side::Base {
+                        None
+                    } else {
+                        Some(
+                            build_buffer_diff(base.clone(), &pane_buffer, &language_registry, cx)
+                                .await?,
+                        )
+                    };
+                    panes.push((side, pane_buffer, diff));
+                }
+                anyhow::Ok((buffer, panes))
+            }
+            .await;
+
+            this.update_in(cx, |this, window, cx| {
+                this.load_task = None;
+                match result {
+                    Ok((buffer, panes)) => this.set_buffers(buffer, panes, window, cx),
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        Self {
+            repository,
+            project,
+            path,
+            panes: Vec::new(),
+            result_buffer: None,
+            result_editor: None,
+            conflict_set: None,
+            first_conflict_selected: false,
+            load_task: Some(load_task),
+            pending_task: None,
+            error: None,
+            focus_handle: cx.focus_handle(),
+            _subscriptions: Vec::new(),
+        }
+    }
+
+    fn set_buffers(
+        &mut self,
+        buffer: Entity<Buffer>,
+        panes: Vec<(
+            Side,
+            Entity<Buffer>,
+            Option<Entity<buffer_diff::BufferDiff>>,
+        )>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.panes = panes
+            .into_iter()
+            .map(|(side, pane_buffer, diff)| {
+                let editor = cx.new(|cx| {
+                    let multibuffer = cx.new(|cx| {
+                        let mut multibuffer = MultiBuffer::singleton(pane_buffer.clone(), cx);
+                        if let Some(diff) = diff {
+                            multibuffer.add_diff(diff, cx);
+                        }
+                        multibuffer
+                    });
+                    let mut editor = Editor::for_multibuffer(
+                        multibuffer,
+                        Some(self.project.clone()),
+                        window,
+                        cx,
+                    );
+                    editor.set_read_only(true);
+                    editor.disable_inline_diagnostics();
+                    editor.set_expand_all_diff_hunks(cx);
+                    editor
+                });
+                MergePane {
+                    side,
+                    buffer: pane_buffer,
+                    editor,
+                }
+            })
+            .collect();
+
+        let conflict_set = self.project.update(cx, |project, cx| {
+            project.git_store().update(cx, |git_store, cx| {
+                git_store.open_conflict_set(buffer.clone(), cx)
+            })
+        });
+        let result_editor =
+            cx.new(|cx| Editor::for_buffer(buffer.clone(), Some(self.project.clone()), window, cx));
+        self._subscriptions = vec![
+            cx.subscribe_in(
+                &conflict_set,
+                window,
+                |this, _, _: &ConflictSetUpdate, window, cx| {
+                    if !this.first_conflict_selected {
+                        this.select_first_conflict(window, cx);
+                    }
+                    cx.notify();
+                },
+            ),
+            cx.observe(&buffer, |_, _, cx| cx.notify()),
+        ];
+        self.result_buffer = Some(buffer);
+        self.result_editor = Some(result_editor);
+        self.conflict_set = Some(conflict_set);
+
+        self.select_first_conflict(window, cx);
+        window.focus(&self.focus_handle(cx));
+    }
+
+    fn select_first_conflict(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let conflicts = self.conflicts(cx);
+        if !conflicts.is_empty() {
+            self.first_conflict_selected = true;
+            self.select_conflict(&conflicts, 0, window, cx);
+        }
+    }
+
+    /// The conflicts left in the result, with their offsets in it.
+    fn conflicts(&self, cx: &App) -> Vec<(ConflictRegion, Range<usize>)> {
+        let (Some(buffer), Some(conflict_set)) = (&self.result_buffer, &self.conflict_set) else {
+            return Vec::new();
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        conflict_set
+            .read(cx)
+            .snapshot()
+            .conflicts
+            .iter()
+            .map(|conflict| (conflict.clone(), conflict.range.to_offset(&snapshot)))
+            .collect()
+    }
+
+    fn cursor_offset(&self, cx: &App) -> Option<usize> {
+        let editor = self.result_editor.as_ref()?.read(cx);
+        let buffer = self.result_buffer.as_ref()?.read(cx);
+        let cursor = editor.selections.newest_anchor().head().text_anchor;
+        Some(cursor.to_offset(&buffer.snapshot()))
+    }
+
+    /// The conflict under the cursor, or else the nearest one after it.
+    fn current_conflict(&self, cx: &App) -> Option<ConflictRegion> {
+        let cursor = self.cursor_offset(cx)?;
+        let mut conflicts = self.conflicts(cx);
+        let ix = conflicts
+            .iter()
+            .position(|(_, range)| range.end >= cursor)
+            .or(conflicts.len().checked_sub(1))?;
+        Some(conflicts.swap_remove(ix).0)
+    }
+
+    fn next_conflict(&mut self, _: &NextConflict, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cursor) = self.cursor_offset(cx) else {
+            return;
+        };
+        let conflicts = self.conflicts(cx);
+        let ix = conflicts
+            .iter()
+            .position(|(_, range)| range.start > cursor)
+            .or((!conflicts.is_empty()).then_some(0));
+        if let Some(ix) = ix {
+            self.select_conflict(&conflicts, ix, window, cx);
+        }
+    }
+
+    fn previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(cursor) = self.cursor_offset(cx) else {
+            return;
+        };
+        let conflicts = self.conflicts(cx);
+        let ix = conflicts
+            .iter()
+            .rposition(|(_, range)| range.start < cursor)
+            .or(conflicts.len().checked_sub(1));
+        if let Some(ix) = ix {
+            self.select_conflict(&conflicts, ix, window, cx);
+        }
+    }
+
+    /// Moves the cursor to the conflict, and scrolls the other versions to
+    /// the text each side of it came from.
+    fn select_conflict(
+        &mut self,
+        conflicts: &[(ConflictRegion, Range<usize>)],
+        ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (Some(buffer), Some(result_editor)) = (&self.result_buffer, &self.result_editor) else {
+            return;
+        };
+        let offset = conflicts[ix].1.start;
+        result_editor.update(cx, |editor, cx| {
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_ranges([offset..offset]),
+            );
+        });
+
+        let snapshot = buffer.read(cx).snapshot();
+        for pane in &self.panes {
+            let pane_snapshot = pane.buffer.read(cx).snapshot();
+            let Some(range) =
+                side_range(pane.side, conflicts, ix, &snapshot, &pane_snapshot.text())
+            else {
+                continue;
+            };
+            let range =
+                pane_snapshot.anchor_after(range.start)..pane_snapshot.anchor_before(range.end);
+            pane.editor.update(cx, |editor, cx| {
+                // The pane's expanded diff hunks are part of its multibuffer,
+                // so the range is selected by anchors in the pane's buffer.
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let Some((&excerpt_id, _, _)) = multibuffer.as_singleton() else {
+                    return;
+                };
+                let Some(range) = multibuffer
+                    .anchor_in_excerpt(excerpt_id, range.start)
+                    .zip(multibuffer.anchor_in_excerpt(excerpt_id, range.end))
+                    .map(|(start, end)| start..end)
+                else {
+                    return;
+                };
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::center()),
+                    window,
+                    cx,
+                    |selections| selections.select_anchor_ranges([range]),
+                );
+            });
+        }
+        cx.notify();
+    }
+
+    fn accept(&mut self, sides: &[Side], window: &mut Window, cx: &mut Context<Self>) {
+        let Some(buffer) = self.result_buffer.clone() else {
+            return;
+        };
+        let Some(conflict) = self.current_conflict(cx) else {
+            return;
+        };
+        let ranges = sides
+            .iter()
+            .filter_map(|side| side.range(&conflict))
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            return;
+        }
+        conflict.resolve(buffer.clone(), &ranges, cx);
+        let offset = conflict.range.start.to_offset(&buffer.read(cx).snapshot());
+        if let Some(editor) = &self.result_editor {
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::center()),
+                    window,
+                    cx,
+                    |selections| selections.select_ranges([offset..offset]),
+                );
+            });
+        }
+        cx.notify();
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Ours], window, cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Theirs], window, cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Ours, Side::Theirs], window, cx);
+    }
+
+    fn accept_base(&mut self, _: &AcceptBase, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Base], window, cx);
+    }
+
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(buffer) = self.result_buffer.clone() else {
+            return;
+        };
+        if self.pending_task.is_some() {
+            return;
+        }
+        let remaining = self.conflicts(cx).len();
+        let confirm = (remaining > 0).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                &format!(
+                    "{} still has {remaining} conflict{}. Mark it as resolved anyway?",
+                    self.file_name(cx),
+                    if remaining == 1 { "" } else { "s" }
+                ),
+                None,
+                &["Mark Resolved", "Cancel"],
+                cx,
+            )
+        });
+        let project = self.project.clone();
+        let repository = self.repository.clone();
+        let path = self.path.clone();
+        self.error = None;
+        self.pending_task = Some(cx.spawn_in(window, async move |this, cx| {
+            if let Some(confirm) = confirm
+                && confirm.await.ok() != Some(0)
+            {
+                this.update(cx, |this, cx| {
+                    this.pending_task = None;
+                    cx.notify();
+                })
+                .ok();
+                return;
+            }
+            let result = async {
+                project
+                    .update(cx, |project, cx| project.save_buffer(buffer, cx))?
+                    .await?;
+                repository
+                    .update(cx, |repository, cx| {
+                        repository.stage_entries(vec![path], cx)
+                    })?
+                    .await
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.pending_task = None;
+                match result {
+                    Ok(()) => cx.emit(ItemEvent::CloseItem),
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn file_name(&self, cx: &App) -> String {
+        let path_style = self.project.read(cx).path_style(cx);
+        self.path
+            .file_name()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| self.path.display(path_style).to_string())
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let conflicts = self.conflicts(cx);
+        let current = self.current_conflict(cx);
+        let has_current = current.is_some();
+        let has_base = current.is_some_and(|conflict| conflict.base.is_some());
+        let summary = match conflicts.len() {
+            _ if self.result_buffer.is_none() => String::new(),
+            0 => "All conflicts are resolved".to_string(),
+            1 => "1 conflict left".to_string(),
+            count => format!("{count} conflicts left"),
+        };
+        let focus_handle = self.focus_handle.clone();
+        let button = |id: &'static str, label: &'static str, action: &dyn gpui::Action| {
+            Button::new(id, label)
+                .label_size(LabelSize::Small)
+                .tooltip(Tooltip::for_action_title_in(label, action, &focus_handle))
+        };
+
+        h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .min_w_0()
+                    .gap_2()
+                    .child(Label::new(self.file_name(cx)).truncate())
+                    .child(
+                        Label::new(summary)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new("previous-conflict", IconName::ArrowUp)
+                            .icon_size(IconSize::Small)
+                            .disabled(conflicts.is_empty())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Previous Conflict",
+                                &PreviousConflict,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.previous_conflict(&PreviousConflict, window, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("next-conflict", IconName::ArrowDown)
+                            .icon_size(IconSize::Small)
+                            .disabled(conflicts.is_empty())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Next Conflict",
+                                &NextConflict,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.next_conflict(&NextConflict, window, cx)
+                            })),
+                    )
+                    .child(
+                        button("accept-ours", "Accept Ours", &AcceptOurs)
+                            .disabled(!has_current)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.accept_ours(&AcceptOurs, window, cx)
+                            })),
+                    )
+                    .child(
+                        button("accept-theirs", "Accept Theirs", &AcceptTheirs)
+                            .disabled(!has_current)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.accept_theirs(&AcceptTheirs, window, cx)
+                            })),
+                    )
+                    .child(
+                        button("accept-both", "Accept Both", &AcceptBoth)
+                            .disabled(!has_current)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.accept_both(&AcceptBoth, window, cx)
+                            })),
+                    )
+                    .child(
+                        button("accept-base", "Accept Base", &AcceptBase)
+                            .disabled(!has_base)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.accept_base(&AcceptBase, window, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("mark-resolved", "Mark Resolved")
+                            .style(ButtonStyle::Filled)
+                            .label_size(LabelSize::Small)
+                            .disabled(self.result_buffer.is_none() || self.pending_task.is_some())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Save and stage the file",
+                                &MarkResolved,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.mark_resolved(&MarkResolved, window, cx)
+                            })),
+                    ),
+            )
+    }
+
+    fn render_pane(
+        &self,
+        title: &'static str,
+        editor: &Entity<Editor>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .min_h_0()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+    }
+
+    fn render_status(&self) -> Option<impl IntoElement> {
+        let message: SharedString = if let Some(error) = &self.error {
+            error.clone()
+        } else if self.load_task.is_some() {
+            "Loading the merge…".into()
+        } else if self.pending_task.is_some() {
+            "Staging the result…".into()
+        } else {
+            return None;
+        };
+        Some(
+            h_flex().p_2().justify_center().child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(if self.error.is_some() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            ),
+        )
+    }
+}
+
+/// Where a conflict's text for one side is in that side's version of the
+/// file. Up to the conflict, the result matches that version once each earlier
+/// conflict is replaced with that side's text, apart from changes git merged in
+/// from the other side, so the occurrence of the text nearest to that offset
+/// is used.
+fn side_range(
+    side: Side,
+    conflicts: &[(ConflictRegion, Range<usize>)],
+    ix: usize,
+    snapshot: &language::BufferSnapshot,
+    side_text: &str,
+) -> Option<Range<usize>> {
+    let (conflict, range) = &conflicts[ix];
+    let text = snapshot
+        .text_for_range(side.range(conflict)?)
+        .collect::<String>();
+    let mut expected_start = range.start as isize;
+    for (earlier, earlier_range) in &conflicts[..ix] {
+        let side_len = side
+            .range(earlier)
+            .map_or(0, |range| range.to_offset(snapshot).len());
+        expected_start += side_len as isize - earlier_range.len() as isize;
+    }
+    let start = if text.is_empty() {
+        let mut start = (expected_start.max(0) as usize).min(side_text.len());
+        while !side_text.is_char_boundary(start) {
+            start -= 1;
+        }
+        start
+    } else {
+        side_text
+            .match_indices(&text)
+            .map(|(start, _)| start)
+            .min_by_key(|start| (*start as isize - expected_start).abs())?
+    };
+    Some(start..start + text.len())
+}
+
+impl EventEmitter<ItemEvent> for MergeEditor {}
+
+impl Focusable for MergeEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        match &self.result_editor {
+            Some(editor) => editor.focus_handle(cx),
+            None => self.focus_handle.clone(),
+        }
+    }
+}
+
+impl Item for MergeEditor {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        format!("Merge {}", self.file_name(cx)).into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Merge Editor Opened")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.result_buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.read(cx).is_dirty())
+    }
+}
+
+impl Render for MergeEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let panes = self
+            .panes
+            .iter()
+            .map(|pane| {
+                self.render_pane(pane.side.title(), &pane.editor, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+        let result = self
+            .result_editor
+            .clone()
+            .map(|editor| self.render_pane("Result", &editor, cx));
+
+        v_flex()
+            .key_context("GitMergeEditor")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::accept_base))
+            .on_action(cx.listener(Self::next_conflict))
+            .on_action(cx.listener(Self::previous_conflict))
+            .on_action(cx.listener(Self::mark_resolved))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .children(self.render_status())
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .children(panes),
+            )
+            .child(v_flex().flex_1().min_h_0().children(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::{
+        repository::repo_path,
+        status::{UnmergedStatus, UnmergedStatusCode},
+    };
+    use gpui::{TestAppContext, VisualTestContext};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{cell::RefCell, path::Path, rc::Rc};
+    use util::path;
+
+    const BASE: &str = "same\n1\nmiddle\nbase\nend\n";
+    const OURS: &str = "same\none\nmiddle\nsame\nend\n";
+    const THEIRS: &str = "same\nONE\nmiddle\nSAME\nend\n";
+    const RESULT: &str = indoc! {"
+        same
+        <<<<<<< HEAD
+        one
+        ||||||| base
+        1
+        =======
+        ONE
+        >>>>>>> theirs
+        middle
+        <<<<<<< HEAD
+        same
+        ||||||| base
+        base
+        =======
+        SAME
+        >>>>>>> theirs
+        end
+    "};
+
+    #[gpui::test]
+    async fn test_select_conflict(cx: &mut TestAppContext) {
+        let (_fs, project) = init_test(cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let view = merge_editor(&project, cx);
+
+        assert_eq!(
+            pane_selections(&view, cx),
+            [("Base", 5..7), ("Ours (HEAD)", 5..9), ("Theirs", 5..9)]
+        );
+
+        // Our version of the second conflict is also at the start of the
+        // file, but the pane scrolls to where it came from.
+        view.update_in(cx, |view, window, cx| {
+            view.next_conflict(&NextConflict, window, cx)
+        });
+        assert_eq!(
+            pane_selections(&view, cx),
+            [
+                ("Base", 14..19),
+                ("Ours (HEAD)", 16..21),
+                ("Theirs", 16..21)
+            ]
+        );
+
+        view.update_in(cx, |view, window, cx| {
+            view.next_conflict(&NextConflict, window, cx)
+        });
+        assert_eq!(
+            pane_selections(&view, cx),
+            [("Base", 5..7), ("Ours (HEAD)", 5..9), ("Theirs", 5..9)]
+        );
+
+        view.update_in(cx, |view, window, cx| {
+            view.previous_conflict(&PreviousConflict, window, cx)
+        });
+        assert_eq!(
+            pane_selections(&view, cx),
+            [
+                ("Base", 14..19),
+                ("Ours (HEAD)", 16..21),
+                ("Theirs", 16..21)
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_accept(cx: &mut TestAppContext) {
+        let (_fs, project) = init_test(cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let view = merge_editor(&project, cx);
+
+        view.update_in(cx, |view, window, cx| {
+            view.accept_base(&AcceptBase, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            result_text(&view, cx),
+            indoc! {"
+                same
+                1
+                middle
+                <<<<<<< HEAD
+                same
+                ||||||| base
+                base
+                =======
+                SAME
+                >>>>>>> theirs
+                end
+            "}
+        );
+
+        // The cursor stays where the resolved conflict was, so the next
+        // conflict is the current one.
+        view.update_in(cx, |view, window, cx| {
+            view.accept_both(&AcceptBoth, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(result_text(&view, cx), "same\n1\nmiddle\nsame\nSAME\nend\n");
+        view.read_with(cx, |view, cx| assert!(view.conflicts(cx).is_empty()));
+
+        view.update_in(cx, |view, window, cx| {
+            view.accept_ours(&AcceptOurs, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(result_text(&view, cx), "same\n1\nmiddle\nsame\nSAME\nend\n");
+    }
+
+    #[gpui::test]
+    async fn test_mark_resolved(cx: &mut TestAppContext) {
+        let (fs, project) = init_test(cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let view = merge_editor(&project, cx);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|_, cx| {
+            let events = events.clone();
+            cx.subscribe(&view, move |_, event: &ItemEvent, _| {
+                events.borrow_mut().push(*event)
+            })
+        });
+
+        view.update_in(cx, |view, window, cx| {
+            view.accept_theirs(&AcceptTheirs, window, cx)
+        });
+        cx.run_until_parked();
+
+        // Marking the file as resolved with conflicts left asks first.
+        view.update_in(cx, |view, window, cx| {
+            view.mark_resolved(&MarkResolved, window, cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer("Cancel");
+        cx.run_until_parked();
+        view.read_with(cx, |view, cx| {
+            assert!(view.pending_task.is_none());
+            assert!(view.is_dirty(cx));
+        });
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            RESULT
+        );
+        assert_eq!(*events.borrow(), []);
+
+        view.update_in(cx, |view, window, cx| {
+            view.mark_resolved(&MarkResolved, window, cx)
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Mark Resolved");
+        cx.run_until_parked();
+
+        let resolved = indoc! {"
+            same
+            ONE
+            middle
+            <<<<<<< HEAD
+            same
+            ||||||| base
+            base
+            =======
+            SAME
+            >>>>>>> theirs
+            end
+        "};
+        view.read_with(cx, |view, cx| {
+            assert_eq!(view.error, None);
+            assert!(!view.is_dirty(cx));
+        });
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            resolved
+        );
+        let index_text = fs
+            .with_git_state(Path::new(path!("/project/.git")), false, |state| {
+                state.index_contents.get(&repo_path("a.txt")).cloned()
+            })
+            .unwrap();
+        assert_eq!(index_text.as_deref(), Some(resolved));
+        assert_eq!(*events.borrow(), [ItemEvent::CloseItem]);
+    }
+
+    fn merge_editor(project: &Entity<Project>, cx: &mut VisualTestContext) -> Entity<MergeEditor> {
+        let repository = project
+            .read_with(cx, |project, cx| project.active_repository(cx))
+            .unwrap();
+        let project = project.clone();
+        let view = cx.new_window_entity(|window, cx| {
+            MergeEditor::new(repository, project, repo_path("a.txt"), window, cx)
+        });
+        cx.run_until_parked();
+        view
+    }
+
+    fn result_text(view: &Entity<MergeEditor>, cx: &mut VisualTestContext) -> String {
+        view.read_with(cx, |view, cx| {
+            view.result_buffer.as_ref().unwrap().read(cx).text()
+        })
+    }
+
+    /// The range selected in each version of the file.
+    fn pane_selections(
+        view: &Entity<MergeEditor>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<(&'static str, Range<usize>)> {
+        view.read_with(cx, |view, cx| {
+            view.panes
+                .iter()
+                .map(|pane| {
+                    let selection = pane.editor.read(cx).selections.newest_anchor().clone();
+                    let buffer = pane.buffer.read(cx).snapshot();
+                    let range = selection.start.text_anchor.to_offset(&buffer)
+                        ..selection.end.text_anchor.to_offset(&buffer);
+                    (pane.side.title(), range)
+                })
+                .collect()
+        })
+    }
+
+    /// Creates a project whose repository is in the middle of a merge that
+    /// left two conflicts in a.txt.
+    async fn init_test(cx: &mut TestAppContext) -> (Arc<FakeFs>, Entity<Project>) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            workspace::init_settings(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": RESULT,
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/project/.git"));
+        fs.set_head_and_index_for_repo(dot_git, &[("a.txt", OURS.into())]);
+        fs.set_unmerged_paths_for_repo(
+            dot_git,
+            &[(
+                repo_path("a.txt"),
+                UnmergedStatus {
+                    first_head: UnmergedStatusCode::Updated,
+                    second_head: UnmergedStatusCode::Updated,
+                },
+            )],
+        );
+        fs.with_git_state(dot_git, true, |state| {
+            state.merge_stages.insert(
+                repo_path("a.txt"),
+                MergeStages {
+                    base: Some(BASE.into()),
+                    ours: Some(OURS.into()),
+                    theirs: Some(THEIRS.into()),
+                },
+            );
+        })
+        .unwrap();
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        cx.run_until_parked();
+        (fs, project)
+    }
+}
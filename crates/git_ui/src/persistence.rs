@@ -0,0 +1,195 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use git::repository::RepoPath;
+
+/// A comment left on a line of a file while reviewing a branch.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReviewComment {
+    pub(crate) id: i64,
+    pub(crate) path: RepoPath,
+    /// The zero-based row the comment is on, in the head revision of the file.
+    pub(crate) row: u32,
+    pub(crate) body: String,
+}
+
+impl Column for ReviewComment {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index): (i64, i32) = Column::column(statement, start_index)?;
+        let (path, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (row, next_index): (u32, i32) = Column::column(statement, next_index)?;
+        let (body, next_index): (String, i32) = Column::column(statement, next_index)?;
+
+        let comment = Self {
+            id,
+            path: RepoPath::new(&path)?,
+            row,
+            body,
+        };
+        Ok((comment, next_index))
+    }
+}
+
+/// Stores the progress of local branch reviews. Reviews are identified by
+/// the repository's working directory and the range being reviewed, such as
+/// `origin/main...feature`, so that they survive new commits on either side.
+pub struct GitReviewDb(ThreadSafeConnection);
+
+impl Domain for GitReviewDb {
+    const NAME: &str = stringify!(GitReviewDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE review_viewed_files(
+            repository_path BLOB NOT NULL,
+            review_range TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            PRIMARY KEY(repository_path, review_range, file_path)
+        ) STRICT;
+
+        CREATE TABLE review_comments(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            repository_path BLOB NOT NULL,
+            review_range TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            body TEXT NOT NULL
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(GIT_REVIEW_DB, GitReviewDb, []);
+
+impl GitReviewDb {
+    query! {
+        pub(crate) fn viewed_files(repository_path: PathBuf, review_range: String) -> Result<Vec<String>> {
+            SELECT file_path
+            FROM review_viewed_files
+            WHERE repository_path = (?) AND review_range = (?)
+            ORDER BY file_path
+        }
+    }
+
+    query! {
+        pub(crate) async fn mark_file_viewed(repository_path: PathBuf, review_range: String, file_path: String) -> Result<()> {
+            INSERT OR IGNORE INTO review_viewed_files(repository_path, review_range, file_path)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub(crate) async fn unmark_file_viewed(repository_path: PathBuf, review_range: String, file_path: String) -> Result<()> {
+            DELETE FROM review_viewed_files
+            WHERE repository_path = (?) AND review_range = (?) AND file_path = (?)
+        }
+    }
+
+    query! {
+        pub(crate) fn comments(repository_path: PathBuf, review_range: String) -> Result<Vec<ReviewComment>> {
+            SELECT id, file_path, line, body
+            FROM review_comments
+            WHERE repository_path = (?) AND review_range = (?)
+            ORDER BY file_path, line, id
+        }
+    }
+
+    query! {
+        pub(crate) async fn add_comment(repository_path: PathBuf, review_range: String, file_path: String, row: u32, body: String) -> Result<Option<i64>> {
+            INSERT INTO review_comments(repository_path, review_range, file_path, line, body)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING id
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_comment(id: i64) -> Result<()> {
+            DELETE FROM review_comments WHERE id = (?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_review_persistence() {
+        let db = GitReviewDb::open_test_db("test_review_persistence").await;
+        let repository_path = PathBuf::from("/code/project");
+        let range = "origin/main...feature".to_string();
+
+        db.mark_file_viewed(repository_path.clone(), range.clone(), "b.rs".into())
+            .await
+            .unwrap();
+        db.mark_file_viewed(repository_path.clone(), range.clone(), "a.rs".into())
+            .await
+            .unwrap();
+        db.mark_file_viewed(repository_path.clone(), "main..other".into(), "c.rs".into())
+            .await
+            .unwrap();
+        db.unmark_file_viewed(repository_path.clone(), range.clone(), "b.rs".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.viewed_files(repository_path.clone(), range.clone())
+                .unwrap(),
+            ["a.rs"]
+        );
+
+        let first = db
+            .add_comment(
+                repository_path.clone(),
+                range.clone(),
+                "src/lib.rs".into(),
+                12,
+                "Can this panic?".into(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let second = db
+            .add_comment(
+                repository_path.clone(),
+                range.clone(),
+                "src/lib.rs".into(),
+                3,
+                "Missing docs".into(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            db.comments(repository_path.clone(), range.clone()).unwrap(),
+            [
+                ReviewComment {
+                    id: second,
+                    path: RepoPath::new("src/lib.rs").unwrap(),
+                    row: 3,
+                    body: "Missing docs".into(),
+                },
+                ReviewComment {
+                    id: first,
+                    path: RepoPath::new("src/lib.rs").unwrap(),
+                    row: 12,
+                    body: "Can this panic?".into(),
+                },
+            ]
+        );
+
+        db.delete_comment(second).await.unwrap();
+        assert_eq!(
+            db.comments(repository_path, range)
+                .unwrap()
+                .into_iter()
+                .map(|comment| comment.id)
+                .collect::<Vec<_>>(),
+            [first]
+        );
+    }
+}
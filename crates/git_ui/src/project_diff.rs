@@ -1,4 +1,5 @@
 use crate::{
+    commit_view::{GitBlob, build_buffer, build_buffer_diff},
     conflict_view::ConflictAddon,
     git_panel::{GitPanel, GitPanelAddon, GitStatusEntry},
     git_panel_settings::GitPanelSettings,
@@ -32,6 +33,7 @@ use project::{
 use settings::{Settings, SettingsStore};
 use std::any::{Any, TypeId};
 use std::ops::Range;
+use std::sync::Arc;
 use theme::ActiveTheme;
 use ui::{KeyBinding, Tooltip, prelude::*, vertical_divider};
 use util::ResultExt as _;
@@ -384,6 +386,71 @@ impl ProjectDiff {
                 let path_key = PathKey::with_sort_prefix(sort_prefix, entry.repo_path.0.clone());
 
                 previous_paths.remove(&path_key);
+
+                // The contents of LFS-tracked files are usually binary, and Git
+                // only stores a pointer to them, so compare what's known about
+                // each version instead.
+                if repo.lfs_paths.contains(&entry.repo_path) {
+                    let metadata = repo.lfs_metadata(entry.repo_path.clone());
+                    let file = Arc::new(GitBlob {
+                        path: entry.repo_path.clone(),
+                        worktree_id: project_path.worktree_id,
+                        is_deleted: entry.status.is_deleted(),
+                    }) as Arc<dyn language::File>;
+                    let language_registry = self.project.read(cx).languages().clone();
+                    result.push(cx.spawn(async move |_, cx| {
+                        let metadata = metadata.await??;
+                        let old_text = metadata.head.map(|metadata| metadata.to_text());
+                        let new_text = metadata
+                            .worktree
+                            .map(|metadata| metadata.to_text())
+                            .unwrap_or_default();
+                        let buffer = build_buffer(new_text, file, &language_registry, cx).await?;
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.set_capability(Capability::ReadOnly, cx)
+                        })?;
+                        let diff =
+                            build_buffer_diff(old_text, &buffer, &language_registry, cx).await?;
+                        Ok(DiffBuffer {
+                            path_key,
+                            buffer,
+                            diff,
+                            file_status: entry.status,
+                        })
+                    }));
+                    continue;
+                }
+
+                // A submodule is stored as the commit it points to, so show
+                // the commits between the recorded and the checked out one.
+                if repo.submodule(&entry.repo_path).is_some() {
+                    let range = repo.submodule_range(entry.repo_path.clone());
+                    let file = Arc::new(GitBlob {
+                        path: entry.repo_path.clone(),
+                        worktree_id: project_path.worktree_id,
+                        is_deleted: entry.status.is_deleted(),
+                    }) as Arc<dyn language::File>;
+                    let language_registry = self.project.read(cx).languages().clone();
+                    result.push(cx.spawn(async move |_, cx| {
+                        let range = range.await??;
+                        let buffer =
+                            build_buffer(range.new_text(), file, &language_registry, cx).await?;
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.set_capability(Capability::ReadOnly, cx)
+                        })?;
+                        let diff =
+                            build_buffer_diff(range.old_text(), &buffer, &language_registry, cx)
+                                .await?;
+                        Ok(DiffBuffer {
+                            path_key,
+                            buffer,
+                            diff,
+                            file_status: entry.status,
+                        })
+                    }));
+                    continue;
+                }
+
                 let load_buffer = self
                     .project
                     .update(cx, |project, cx| project.open_buffer(project_path, cx));
@@ -421,7 +488,7 @@ impl ProjectDiff {
     ) {
         let path_key = diff_buffer.path_key;
         let buffer = diff_buffer.buffer;
-        let diff = diff_buffer.diff;
+        let diff_entity = diff_buffer.diff;
 
         let conflict_addon = self
             .editor
@@ -430,7 +497,7 @@ impl ProjectDiff {
             .expect("project diff editor should have a conflict addon");
 
         let snapshot = buffer.read(cx).snapshot();
-        let diff = diff.read(cx);
+        let diff = diff_entity.read(cx);
         let diff_hunk_ranges = diff
             .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot, cx)
             .map(|diff_hunk| diff_hunk.buffer_range);
@@ -446,6 +513,13 @@ impl ProjectDiff {
 
         let (was_empty, is_excerpt_newly_added) = self.multibuffer.update(cx, |multibuffer, cx| {
             let was_empty = multibuffer.is_empty();
+            // The editor only loads the diffs of project buffers, not those
+            // describing LFS-tracked files.
+            if multibuffer.diff_for(snapshot.remote_id()).is_none()
+                && project::File::from_dyn(snapshot.file()).is_none()
+            {
+                multibuffer.add_diff(diff_entity.clone(), cx);
+            }
             let (_, is_newly_added) = multibuffer.set_excerpts_for_path(
                 path_key.clone(),
                 buffer,
@@ -0,0 +1,640 @@
+use editor::Editor;
+use git::{
+    SHORT_SHA_LENGTH,
+    repository::{CommitSummary, LogOptions, RebaseAction, RebaseTodoEntry},
+};
+use gpui::{
+    App, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task, WeakEntity, actions,
+};
+use project::git_store::Repository;
+use ui::{ContextMenu, ListItem, ListItemSpacing, PopoverMenu, Tooltip, prelude::*};
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+actions!(
+    rebase_editor,
+    [
+        /// Moves the selected commit up, so that it is applied earlier.
+        MoveUp,
+        /// Moves the selected commit down, so that it is applied later.
+        MoveDown,
+        /// Rebases the commits as they are listed.
+        StartRebase,
+    ]
+);
+
+/// How many of the most recent commits can be chosen as the base.
+const MAX_COMMITS: usize = 100;
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+fn open(
+    workspace: &mut Workspace,
+    _: &git::InteractiveRebase,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let view = cx.new(|cx| RebaseEditor::new(repository, window, cx));
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+struct TodoEntry {
+    commit: CommitSummary,
+    action: RebaseAction,
+    /// Holds the new message of a reworded commit.
+    message_editor: Option<Entity<Editor>>,
+}
+
+/// Rewrites the commits after a chosen base commit with `git rebase -i`. The
+/// base is picked from the most recent commits first, after which the commits
+/// that follow it are listed oldest first, to be reordered, squashed, reworded
+/// or dropped.
+pub struct RebaseEditor {
+    repository: Entity<Repository>,
+    /// The most recent commits, newest first.
+    commits: Vec<CommitSummary>,
+    base: Option<CommitSummary>,
+    /// The commits after the base, oldest first.
+    todo: Vec<TodoEntry>,
+    selected_index: Option<usize>,
+    load_task: Option<Task<()>>,
+    rebase_task: Option<Task<()>>,
+    error: Option<SharedString>,
+    scroll_handle: ScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl RebaseEditor {
+    fn new(repository: Entity<Repository>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let commits = repository.update(cx, |repository, _| {
+            repository.log(LogOptions {
+                limit: MAX_COMMITS,
+                ..Default::default()
+            })
+        });
+        let load_task = cx.spawn_in(window, async move |this, cx| {
+            let commits = match commits.await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!("The repository was closed")),
+            };
+            this.update(cx, |this, cx| {
+                this.load_task = None;
+                match commits {
+                    Ok(commits) => this.commits = commits,
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        Self {
+            repository,
+            commits: Vec::new(),
+            base: None,
+            todo: Vec::new(),
+            selected_index: None,
+            load_task: Some(load_task),
+            rebase_task: None,
+            error: None,
+            scroll_handle: ScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        if self.base.is_some() {
+            self.todo.len()
+        } else {
+            self.commits.len()
+        }
+    }
+
+    fn set_base(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(base) = self.commits.get(ix) else {
+            return;
+        };
+        self.base = Some(base.clone());
+        self.todo = self.commits[..ix]
+            .iter()
+            .rev()
+            .map(|commit| TodoEntry {
+                commit: commit.clone(),
+                action: RebaseAction::Pick,
+                message_editor: None,
+            })
+            .collect();
+        self.selected_index = None;
+        self.error = None;
+        cx.notify();
+    }
+
+    fn change_base(&mut self, cx: &mut Context<Self>) {
+        self.base = None;
+        self.todo.clear();
+        self.selected_index = None;
+        self.error = None;
+        cx.notify();
+    }
+
+    fn set_action(
+        &mut self,
+        ix: usize,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self.todo.get_mut(ix) else {
+            return;
+        };
+        entry.action = action;
+        if action == RebaseAction::Reword && entry.message_editor.is_none() {
+            let subject = entry.commit.subject.clone();
+            let editor = cx.new(|cx| {
+                let mut editor = Editor::auto_height(1, 8, window, cx);
+                editor.set_text(subject.as_ref(), window, cx);
+                editor
+            });
+            // Start from the full message rather than just its subject.
+            let details = self.repository.update(cx, |repository, _| {
+                repository.show(entry.commit.sha.to_string())
+            });
+            cx.spawn_in(window, {
+                let editor = editor.downgrade();
+                async move |_, cx| {
+                    let Ok(Ok(details)) = details.await else {
+                        return;
+                    };
+                    editor
+                        .update_in(cx, |editor, window, cx| {
+                            if editor.text(cx) == subject.as_ref() {
+                                editor.set_text(details.message.trim_end(), window, cx);
+                            }
+                        })
+                        .ok();
+                }
+            })
+            .detach();
+            entry.message_editor = Some(editor);
+        }
+        self.error = None;
+        cx.notify();
+    }
+
+    fn move_entry(&mut self, ix: usize, new_ix: usize, cx: &mut Context<Self>) {
+        if ix >= self.todo.len() || new_ix >= self.todo.len() {
+            return;
+        }
+        self.todo.swap(ix, new_ix);
+        self.select_index(new_ix, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_index.filter(|ix| *ix > 0) {
+            self.move_entry(ix, ix - 1, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_index {
+            self.move_entry(ix, ix + 1, cx);
+        }
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_index = Some(ix);
+        self.scroll_handle.scroll_to_item(ix);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let row_count = self.row_count();
+        if row_count > 0 {
+            let ix = self
+                .selected_index
+                .map_or(0, |ix| (ix + 1).min(row_count - 1));
+            self.select_index(ix, cx);
+        }
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.row_count() > 0 {
+            let ix = self.selected_index.map_or(0, |ix| ix.saturating_sub(1));
+            self.select_index(ix, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        if self.base.is_none()
+            && let Some(ix) = self.selected_index
+        {
+            self.set_base(ix, cx);
+        }
+    }
+
+    fn todo_entries(&self, cx: &App) -> anyhow::Result<Vec<RebaseTodoEntry>> {
+        if let Some(first) = self
+            .todo
+            .iter()
+            .find(|entry| entry.action != RebaseAction::Drop)
+        {
+            anyhow::ensure!(
+                !first.action.melds(),
+                "The first commit can't be squashed or fixed up, since there is no commit before it"
+            );
+        }
+        self.todo
+            .iter()
+            .map(|entry| {
+                let message = match &entry.message_editor {
+                    Some(editor) if entry.action == RebaseAction::Reword => {
+                        let message = editor.read(cx).text(cx);
+                        anyhow::ensure!(
+                            !message.trim().is_empty(),
+                            "The new message of {} is empty",
+                            short_sha(&entry.commit)
+                        );
+                        Some(message)
+                    }
+                    _ => None,
+                };
+                Ok(RebaseTodoEntry {
+                    action: entry.action,
+                    sha: entry.commit.sha.clone(),
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    fn start_rebase(&mut self, _: &StartRebase, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(base) = self.base.as_ref() else {
+            return;
+        };
+        if self.rebase_task.is_some() || self.todo.is_empty() {
+            return;
+        }
+        let todo = match self.todo_entries(cx) {
+            Ok(todo) => todo,
+            Err(error) => {
+                self.error = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        let base = base.sha.to_string();
+        let rebase = self.repository.update(cx, |repository, _| {
+            repository.rebase_interactive(base, todo)
+        });
+        self.error = None;
+        self.rebase_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = match rebase.await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!("The repository was closed")),
+            };
+            this.update(cx, |this, cx| {
+                this.rebase_task = None;
+                match result {
+                    Ok(()) => cx.emit(ItemEvent::CloseItem),
+                    // Conflicts are resolved in the editor and the rebase is
+                    // continued from the git panel.
+                    Err(error) => this.error = Some(error.to_string().trim().to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn render_base_commits(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        self.commits
+            .iter()
+            .enumerate()
+            .map(|(ix, commit)| {
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(self.selected_index == Some(ix))
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_3()
+                            .child(render_sha(commit, cx))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_w_0()
+                                    .child(Label::new(commit.subject.clone()).truncate()),
+                            )
+                            .child(
+                                Label::new(commit.author_name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .on_click(cx.listener(move |this, _, _, cx| this.set_base(ix, cx)))
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_todo(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let this = cx.entity().downgrade();
+        let last_ix = self.todo.len().saturating_sub(1);
+        self.todo
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| {
+                let dropped = entry.action == RebaseAction::Drop;
+                v_flex()
+                    .child(
+                        ListItem::new(ix)
+                            .spacing(ListItemSpacing::Sparse)
+                            .toggle_state(self.selected_index == Some(ix))
+                            .on_click(cx.listener(move |this, _, _, cx| this.select_index(ix, cx)))
+                            .child(
+                                h_flex()
+                                    .w_full()
+                                    .gap_3()
+                                    .when(entry.action.melds(), |this| this.pl_4())
+                                    .child(render_action_menu(ix, entry.action, this.clone()))
+                                    .child(render_sha(&entry.commit, cx))
+                                    .child(
+                                        div().flex_1().min_w_0().child(
+                                            Label::new(entry.commit.subject.clone())
+                                                .truncate()
+                                                .when(dropped, |label| {
+                                                    label.strikethrough().color(Color::Muted)
+                                                }),
+                                        ),
+                                    )
+                                    .child(
+                                        IconButton::new(("move-up", ix), IconName::ArrowUp)
+                                            .icon_size(IconSize::Small)
+                                            .disabled(ix == 0)
+                                            .tooltip(Tooltip::text("Move Up"))
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                this.move_entry(ix, ix.saturating_sub(1), cx)
+                                            })),
+                                    )
+                                    .child(
+                                        IconButton::new(("move-down", ix), IconName::ArrowDown)
+                                            .icon_size(IconSize::Small)
+                                            .disabled(ix == last_ix)
+                                            .tooltip(Tooltip::text("Move Down"))
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                this.move_entry(ix, ix + 1, cx)
+                                            })),
+                                    ),
+                            ),
+                    )
+                    .when_some(
+                        entry
+                            .message_editor
+                            .as_ref()
+                            .filter(|_| entry.action == RebaseAction::Reword),
+                        |this, editor| {
+                            this.child(
+                                div()
+                                    .mx_2()
+                                    .mb_1()
+                                    .px_2()
+                                    .py_1()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border_variant)
+                                    .rounded_sm()
+                                    .child(editor.clone()),
+                            )
+                        },
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border);
+        let Some(base) = &self.base else {
+            return header.child(
+                Label::new(
+                    "Choose the commit to rebase onto. The commits after it can be edited next.",
+                )
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            );
+        };
+
+        let is_rebasing = self.rebase_task.is_some() || self.repository.read(cx).merge.rebasing;
+        header
+            .child(
+                h_flex()
+                    .min_w_0()
+                    .gap_1p5()
+                    .child(
+                        Label::new(format!(
+                            "Rebasing {} onto",
+                            if self.todo.len() == 1 {
+                                "1 commit".to_string()
+                            } else {
+                                format!("{} commits", self.todo.len())
+                            }
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(render_sha(base, cx))
+                    .child(
+                        Label::new(base.subject.clone())
+                            .size(LabelSize::Small)
+                            .truncate(),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("change-base", "Change Base")
+                            .disabled(self.rebase_task.is_some())
+                            .on_click(cx.listener(|this, _, _, cx| this.change_base(cx))),
+                    )
+                    .child(
+                        Button::new("start-rebase", "Rebase")
+                            .style(ButtonStyle::Filled)
+                            .disabled(is_rebasing || self.todo.is_empty())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "git rebase --interactive",
+                                &StartRebase,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.start_rebase(&StartRebase, window, cx)
+                            })),
+                    ),
+            )
+    }
+
+    fn render_status(&self) -> Option<impl IntoElement> {
+        let message: SharedString = if let Some(error) = &self.error {
+            error.clone()
+        } else if self.load_task.is_some() {
+            "Loading commits…".into()
+        } else if self.rebase_task.is_some() {
+            "Rebasing…".into()
+        } else if self.row_count() == 0 {
+            if self.base.is_some() {
+                "There are no commits after the base".into()
+            } else {
+                "There are no commits".into()
+            }
+        } else {
+            return None;
+        };
+        Some(
+            h_flex().p_2().justify_center().child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(if self.error.is_some() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            ),
+        )
+    }
+}
+
+fn short_sha(commit: &CommitSummary) -> &str {
+    commit.sha.get(..SHORT_SHA_LENGTH).unwrap_or(&commit.sha)
+}
+
+fn render_sha(commit: &CommitSummary, cx: &App) -> Label {
+    Label::new(short_sha(commit).to_string())
+        .buffer_font(cx)
+        .size(LabelSize::Small)
+        .color(Color::Muted)
+}
+
+fn render_action_menu(
+    ix: usize,
+    action: RebaseAction,
+    editor: WeakEntity<RebaseEditor>,
+) -> impl IntoElement {
+    PopoverMenu::new(("rebase-action", ix))
+        .trigger(
+            Button::new(("rebase-action-trigger", ix), action.label())
+                .label_size(LabelSize::Small)
+                .icon(IconName::ChevronDown)
+                .icon_position(IconPosition::End)
+                .icon_size(IconSize::XSmall)
+                .icon_color(Color::Muted),
+        )
+        .menu(move |window, cx| {
+            let editor = editor.clone();
+            Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                for new_action in RebaseAction::ALL {
+                    let editor = editor.clone();
+                    menu = menu.toggleable_entry(
+                        new_action.label(),
+                        new_action == action,
+                        IconPosition::Start,
+                        None,
+                        move |window, cx| {
+                            editor
+                                .update(cx, |editor, cx| {
+                                    editor.set_action(ix, new_action, window, cx)
+                                })
+                                .ok();
+                        },
+                    );
+                }
+                menu
+            }))
+        })
+}
+
+impl EventEmitter<ItemEvent> for RebaseEditor {}
+
+impl Focusable for RebaseEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for RebaseEditor {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Interactive Rebase".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Interactive Rebase Opened")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for RebaseEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = if self.base.is_some() {
+            self.render_todo(cx)
+        } else {
+            self.render_base_commits(cx)
+        };
+        v_flex()
+            .key_context("GitRebase")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::start_rebase))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                v_flex()
+                    .id("git-rebase-commits")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll_handle)
+                    .children(rows),
+            )
+            .children(self.render_status())
+    }
+}
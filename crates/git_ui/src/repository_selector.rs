@@ -225,14 +225,32 @@ impl PickerDelegate for RepositorySelectorDelegate {
         _window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> Option<Self::ListItem> {
-        let repo_info = self.filtered_repositories.get(ix)?;
-        let display_name = repo_info.read(cx).display_name();
+        let repo_info = self.filtered_repositories.get(ix)?.read(cx);
+        let display_name = repo_info.display_name();
+        let worktree_status = repo_info.current_worktree().and_then(|worktree| {
+            let linked_count = repo_info.worktrees.len().saturating_sub(1);
+            if !worktree.is_main {
+                let main = repo_info.worktrees.first()?;
+                Some(format!("Worktree of {}", main.name()))
+            } else if linked_count == 1 {
+                Some("1 linked worktree".to_string())
+            } else if linked_count > 1 {
+                Some(format!("{linked_count} linked worktrees"))
+            } else {
+                None
+            }
+        });
         Some(
             ListItem::new(ix)
                 .inset(true)
                 .spacing(ListItemSpacing::Sparse)
                 .toggle_state(selected)
-                .child(Label::new(display_name)),
+                .child(Label::new(display_name))
+                .end_slot::<Label>(worktree_status.map(|status| {
+                    Label::new(status)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                })),
         )
     }
 }
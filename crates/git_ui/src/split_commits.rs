@@ -0,0 +1,1236 @@
+use std::{collections::HashSet, fmt::Write as _, ops::Range};
+
+use agent_settings::AgentSettings;
+use anyhow::{Context as _, Result, anyhow};
+use cloud_llm_client::CompletionIntent;
+use editor::Editor;
+use futures::StreamExt as _;
+use git::repository::{CommitDiff, CommitOptions, RepoPath};
+use gpui::{App, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task, actions};
+use language_model::{
+    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+};
+use project::{Project, git_store::Repository};
+use serde::Deserialize;
+use ui::{Tooltip, prelude::*};
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+actions!(
+    split_commits,
+    [
+        /// Makes the proposed commits, in order.
+        CommitAll,
+    ]
+);
+
+/// How much of the staged changes is sent to the model.
+const MAX_PROMPT_LEN: usize = 1_000_000;
+
+/// How many lines of a hunk are shown when hovering it.
+const MAX_PREVIEW_LINES: usize = 20;
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+fn open(
+    workspace: &mut Workspace,
+    _: &git::SplitIntoCommits,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let project = workspace.project().clone();
+    let view = cx.new(|cx| SplitCommits::new(repository, project, window, cx));
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+/// A file with staged changes, as it is in HEAD and in the index.
+struct StagedFile {
+    path: RepoPath,
+    old_text: Option<String>,
+    new_text: Option<String>,
+    /// Binary files are kept whole, since their contents can't be written to
+    /// the index as text.
+    is_binary: bool,
+    hunk_count: usize,
+}
+
+impl StagedFile {
+    /// The version of the file once the given hunks, sorted by position, are
+    /// applied to its version in HEAD.
+    fn text_with_hunks(&self, hunks: &[&Hunk]) -> Option<String> {
+        if hunks.len() == self.hunk_count {
+            self.new_text.clone()
+        } else if hunks.is_empty() {
+            self.old_text.clone()
+        } else {
+            Some(apply_hunks(
+                self.old_text.as_deref().unwrap_or_default(),
+                self.new_text.as_deref().unwrap_or_default(),
+                hunks,
+            ))
+        }
+    }
+}
+
+/// A contiguous range of changed lines in a staged file. Files without
+/// changed lines, such as binary or empty files, have a single hunk with
+/// empty ranges that stands for the whole file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Hunk {
+    file_ix: usize,
+    old_rows: Range<u32>,
+    new_rows: Range<u32>,
+}
+
+struct ProposedCommit {
+    message_editor: Entity<Editor>,
+    /// Indices into the hunks, in the order they appear in the diff.
+    hunks: Vec<usize>,
+}
+
+/// The changes to the index that make up one of the proposed commits.
+struct CommitStep {
+    message: String,
+    /// Files whose staged version is their version in the worktree.
+    stage: Vec<RepoPath>,
+    /// Files whose staged version is their version in HEAD.
+    unstage: Vec<RepoPath>,
+    /// Files whose staged version only includes some of their hunks.
+    index_texts: Vec<(RepoPath, Option<String>)>,
+}
+
+/// Asks the commit message model to split the staged changes into several
+/// commits, listing the proposed commits to be reviewed before they are made.
+/// Hunks can be moved between commits and messages edited, after which the
+/// commits are made one by one, staging only the hunks of each.
+pub struct SplitCommits {
+    repository: Entity<Repository>,
+    project: Entity<Project>,
+    files: Vec<StagedFile>,
+    hunks: Vec<Hunk>,
+    /// A short excerpt of each hunk's diff.
+    previews: Vec<SharedString>,
+    commits: Vec<ProposedCommit>,
+    /// How many of the proposed commits have been made.
+    committed_count: usize,
+    load_task: Option<Task<()>>,
+    commit_task: Option<Task<()>>,
+    error: Option<SharedString>,
+    scroll_handle: ScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl SplitCommits {
+    fn new(
+        repository: Entity<Repository>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            repository,
+            project,
+            files: Vec::new(),
+            hunks: Vec::new(),
+            previews: Vec::new(),
+            commits: Vec::new(),
+            committed_count: 0,
+            load_task: None,
+            commit_task: None,
+            error: None,
+            scroll_handle: ScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        };
+        this.load_task = match this.propose_commits(window, cx) {
+            Ok(task) => Some(task),
+            Err(error) => {
+                this.error = Some(error.to_string().into());
+                None
+            }
+        };
+        this
+    }
+
+    fn propose_commits(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Result<Task<()>> {
+        anyhow::ensure!(
+            AgentSettings::get_global(cx).enabled(cx),
+            "Enable the agent to split changes into commits"
+        );
+        let ConfiguredModel { provider, model } = LanguageModelRegistry::read_global(cx)
+            .commit_message_model()
+            .context("Configure a commit message model to split changes into commits")?;
+        let temperature = AgentSettings::temperature_for_model(&model, cx);
+        let diff = self
+            .repository
+            .update(cx, |repository, _| repository.load_staged_diff());
+
+        telemetry::event!("Git Commits Split");
+
+        Ok(cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let diff = diff.await??;
+                let (files, hunks) = cx.background_spawn(async move { staged_hunks(diff) }).await;
+                anyhow::ensure!(!hunks.is_empty(), "There are no staged changes");
+                let (mut prompt_diff, previews) = describe_hunks(&files, &hunks);
+                if prompt_diff.len() > MAX_PROMPT_LEN {
+                    prompt_diff = prompt_diff.chars().take(MAX_PROMPT_LEN).collect();
+                }
+
+                if let Some(task) = cx.update(|_, cx| {
+                    (!provider.is_authenticated(cx)).then(|| provider.authenticate(cx))
+                })? {
+                    task.await?;
+                }
+
+                const PROMPT: &str = include_str!("split_commits_prompt.txt");
+                let request = LanguageModelRequest {
+                    thread_id: None,
+                    prompt_id: None,
+                    intent: Some(CompletionIntent::GenerateGitCommitMessage),
+                    mode: None,
+                    messages: vec![LanguageModelRequestMessage {
+                        role: Role::User,
+                        content: vec![
+                            format!("{PROMPT}\nHere are the staged hunks:\n{prompt_diff}").into(),
+                        ],
+                        cache: false,
+                    }],
+                    tools: Vec::new(),
+                    tool_choice: None,
+                    stop: Vec::new(),
+                    temperature,
+                    thinking_allowed: false,
+                };
+                let mut response = String::new();
+                let mut stream = model.stream_completion_text(request, cx).await?.stream;
+                while let Some(text) = stream.next().await {
+                    response.push_str(&text?);
+                }
+                let proposal = parse_proposal(&response, hunks.len())?;
+                anyhow::Ok((files, hunks, previews, proposal))
+            }
+            .await;
+
+            this.update_in(cx, |this, window, cx| {
+                this.load_task = None;
+                match result {
+                    Ok((files, hunks, previews, proposal)) => {
+                        this.files = files;
+                        this.hunks = hunks;
+                        this.previews = previews;
+                        this.commits = proposal
+                            .into_iter()
+                            .map(|(message, hunks)| ProposedCommit {
+                                message_editor: message_editor(&message, window, cx),
+                                hunks,
+                            })
+                            .collect();
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }))
+    }
+
+    fn move_commit(&mut self, ix: usize, new_ix: usize, cx: &mut Context<Self>) {
+        if ix >= self.commits.len() || new_ix >= self.commits.len() {
+            return;
+        }
+        self.commits.swap(ix, new_ix);
+        self.scroll_handle.scroll_to_item(new_ix);
+        cx.notify();
+    }
+
+    /// Moves a hunk into the commit at `new_ix`, or into a new last commit
+    /// if that's past the end. Commits left without hunks are removed.
+    fn move_hunk(
+        &mut self,
+        hunk_ix: usize,
+        ix: usize,
+        new_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(commit) = self.commits.get_mut(ix) else {
+            return;
+        };
+        commit.hunks.retain(|hunk| *hunk != hunk_ix);
+        if new_ix == self.commits.len() {
+            self.commits.push(ProposedCommit {
+                message_editor: message_editor("", window, cx),
+                hunks: Vec::new(),
+            });
+        }
+        if let Some(new_commit) = self.commits.get_mut(new_ix) {
+            new_commit.hunks.push(hunk_ix);
+            new_commit.hunks.sort_unstable();
+        }
+        self.commits.retain(|commit| !commit.hunks.is_empty());
+        self.error = None;
+        cx.notify();
+    }
+
+    /// Computes the index changes for each commit. Each commit's changes are
+    /// applied on top of the previous one's, starting from the index as it
+    /// is, so that the last commit leaves everything staged as before.
+    fn plan(&self, stageable: &HashSet<RepoPath>, cx: &App) -> Result<Vec<CommitStep>> {
+        let mut applied = vec![Vec::<&Hunk>::new(); self.files.len()];
+        let mut index_texts = self
+            .files
+            .iter()
+            .map(|file| file.new_text.clone())
+            .collect::<Vec<_>>();
+        let mut steps = Vec::with_capacity(self.commits.len());
+        for (ix, commit) in self.commits.iter().enumerate() {
+            let message = commit.message_editor.read(cx).text(cx);
+            anyhow::ensure!(
+                !message.trim().is_empty(),
+                "The message of commit {} is empty",
+                ix + 1
+            );
+            for hunk in commit.hunks.iter().filter_map(|ix| self.hunks.get(*ix)) {
+                applied[hunk.file_ix].push(hunk);
+            }
+
+            let mut step = CommitStep {
+                message,
+                stage: Vec::new(),
+                unstage: Vec::new(),
+                index_texts: Vec::new(),
+            };
+            for (file_ix, file) in self.files.iter().enumerate() {
+                let hunks = &mut applied[file_ix];
+                hunks.sort_unstable_by_key(|hunk| hunk.old_rows.start);
+                let text = file.text_with_hunks(hunks);
+                if text == index_texts[file_ix] {
+                    continue;
+                }
+                if text == file.new_text && stageable.contains(&file.path) {
+                    step.stage.push(file.path.clone());
+                } else if text == file.old_text {
+                    step.unstage.push(file.path.clone());
+                } else {
+                    anyhow::ensure!(
+                        !file.is_binary,
+                        "{} is binary and has unstaged changes, so it can't be committed separately",
+                        file.path.as_unix_str()
+                    );
+                    step.index_texts.push((file.path.clone(), text.clone()));
+                }
+                index_texts[file_ix] = text;
+            }
+            steps.push(step);
+        }
+        Ok(steps)
+    }
+
+    /// Files that can be staged from the worktree, because their staged
+    /// version is the one on disk.
+    fn stageable_paths(&self, cx: &App) -> HashSet<RepoPath> {
+        let repository = self.repository.read(cx);
+        let project = self.project.read(cx);
+        self.files
+            .iter()
+            .filter(|file| {
+                let fully_staged = repository
+                    .status_for_path(&file.path)
+                    .is_some_and(|entry| !entry.status.staging().has_unstaged());
+                let has_unsaved_edits = repository
+                    .repo_path_to_project_path(&file.path, cx)
+                    .and_then(|project_path| project.get_open_buffer(&project_path, cx))
+                    .is_some_and(|buffer| buffer.read(cx).is_dirty());
+                fully_staged && !has_unsaved_edits
+            })
+            .map(|file| file.path.clone())
+            .collect()
+    }
+
+    fn matches_diff(&self, diff: &CommitDiff) -> bool {
+        self.files.len() == diff.files.len()
+            && self.files.iter().zip(&diff.files).all(|(file, staged)| {
+                file.path == staged.path
+                    && file.old_text == staged.old_text
+                    && file.new_text == staged.new_text
+            })
+    }
+
+    fn commit_all(&mut self, _: &CommitAll, window: &mut Window, cx: &mut Context<Self>) {
+        if self.commit_task.is_some() || self.load_task.is_some() || self.commits.is_empty() {
+            return;
+        }
+        let stageable = self.stageable_paths(cx);
+        let steps = match self.plan(&stageable, cx) {
+            Ok(steps) => steps,
+            Err(error) => {
+                self.error = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        let repository = self.repository.clone();
+        let diff = repository.update(cx, |repository, _| repository.load_staged_diff());
+        self.error = None;
+        self.commit_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let diff = diff.await??;
+                anyhow::ensure!(
+                    this.read_with(cx, |this, _| this.matches_diff(&diff))?,
+                    "The staged changes were modified since the commits were proposed"
+                );
+                for step in steps {
+                    repository
+                        .update(cx, |repository, cx| {
+                            repository.stage_entries(step.stage, cx)
+                        })?
+                        .await?;
+                    repository
+                        .update(cx, |repository, cx| {
+                            repository.unstage_entries(step.unstage, cx)
+                        })?
+                        .await?;
+                    for (path, text) in step.index_texts {
+                        repository
+                            .update(cx, |repository, cx| {
+                                repository.set_index_text(path, text, cx)
+                            })?
+                            .await??;
+                    }
+                    repository
+                        .update(cx, |repository, cx| {
+                            repository.commit(
+                                step.message.into(),
+                                None,
+                                CommitOptions::default(),
+                                cx,
+                            )
+                        })?
+                        .await??;
+                    this.update(cx, |this, cx| {
+                        this.committed_count += 1;
+                        cx.notify();
+                    })?;
+                }
+                anyhow::Ok(())
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                this.commit_task = None;
+                match result {
+                    Ok(()) => cx.emit(ItemEvent::CloseItem),
+                    Err(error) if this.committed_count > 0 => {
+                        this.error = Some(
+                            format!(
+                                "Stopped after {} of {} commits: {error}",
+                                this.committed_count,
+                                this.commits.len()
+                            )
+                            .into(),
+                        )
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn render_hunk(
+        &self,
+        hunk_ix: usize,
+        ix: usize,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let hunk = self.hunks.get(hunk_ix)?;
+        let file = self.files.get(hunk.file_ix)?;
+        let preview = self.previews.get(hunk_ix).cloned().unwrap_or_default();
+        let rows = if hunk.new_rows.is_empty() {
+            &hunk.old_rows
+        } else {
+            &hunk.new_rows
+        };
+        let location = if file.hunk_count == 1 && rows.is_empty() {
+            file.path.as_unix_str().to_string()
+        } else if rows.len() <= 1 {
+            format!("{}:{}", file.path.as_unix_str(), rows.start + 1)
+        } else {
+            format!(
+                "{}:{}–{}",
+                file.path.as_unix_str(),
+                rows.start + 1,
+                rows.end
+            )
+        };
+        let is_committing = self.commit_task.is_some();
+        Some(
+            h_flex()
+                .id(("split-commit-hunk", hunk_ix))
+                .w_full()
+                .gap_2()
+                .pl_4()
+                .tooltip(Tooltip::text(preview))
+                .child(
+                    div().flex_1().min_w_0().child(
+                        Label::new(location)
+                            .size(LabelSize::Small)
+                            .buffer_font(cx)
+                            .truncate(),
+                    ),
+                )
+                .child(
+                    Label::new(format!("+{}", hunk.new_rows.len()))
+                        .size(LabelSize::Small)
+                        .color(Color::Created),
+                )
+                .child(
+                    Label::new(format!("−{}", hunk.old_rows.len()))
+                        .size(LabelSize::Small)
+                        .color(Color::Deleted),
+                )
+                .child(
+                    IconButton::new(("hunk-to-previous", hunk_ix), IconName::ArrowUp)
+                        .icon_size(IconSize::Small)
+                        .disabled(is_committing || ix == 0)
+                        .tooltip(Tooltip::text("Move to the Previous Commit"))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.move_hunk(hunk_ix, ix, ix.saturating_sub(1), window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new(("hunk-to-next", hunk_ix), IconName::ArrowDown)
+                        .icon_size(IconSize::Small)
+                        .disabled(is_committing)
+                        .tooltip(Tooltip::text("Move to the Next Commit"))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.move_hunk(hunk_ix, ix, ix + 1, window, cx)
+                        })),
+                ),
+        )
+    }
+
+    fn render_commits(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let last_ix = self.commits.len().saturating_sub(1);
+        let is_committing = self.commit_task.is_some();
+        self.commits
+            .iter()
+            .enumerate()
+            .map(|(ix, commit)| {
+                let committed = ix < self.committed_count;
+                v_flex()
+                    .p_2()
+                    .gap_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Icon::new(if committed {
+                                    IconName::Check
+                                } else {
+                                    IconName::GitBranch
+                                })
+                                .size(IconSize::Small)
+                                .color(if committed {
+                                    Color::Success
+                                } else {
+                                    Color::Muted
+                                }),
+                            )
+                            .child(
+                                div().flex_1().child(
+                                    Label::new(format!(
+                                        "Commit {} of {}",
+                                        ix + 1,
+                                        self.commits.len()
+                                    ))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                ),
+                            )
+                            .child(
+                                IconButton::new(("move-commit-up", ix), IconName::ArrowUp)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(is_committing || ix == 0)
+                                    .tooltip(Tooltip::text("Move Up"))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.move_commit(ix, ix.saturating_sub(1), cx)
+                                    })),
+                            )
+                            .child(
+                                IconButton::new(("move-commit-down", ix), IconName::ArrowDown)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(is_committing || ix == last_ix)
+                                    .tooltip(Tooltip::text("Move Down"))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.move_commit(ix, ix + 1, cx)
+                                    })),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .rounded_sm()
+                            .child(commit.message_editor.clone()),
+                    )
+                    .children(
+                        commit
+                            .hunks
+                            .iter()
+                            .filter_map(|hunk_ix| self.render_hunk(*hunk_ix, ix, cx)),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let file_count = self.files.len();
+        let summary = format!(
+            "{} {} in {} {}, split into {} {}",
+            self.hunks.len(),
+            if self.hunks.len() == 1 {
+                "hunk"
+            } else {
+                "hunks"
+            },
+            file_count,
+            if file_count == 1 { "file" } else { "files" },
+            self.commits.len(),
+            if self.commits.len() == 1 {
+                "commit"
+            } else {
+                "commits"
+            },
+        );
+        h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Label::new(if self.commits.is_empty() {
+                    "Splitting the staged changes into commits".to_string()
+                } else {
+                    summary
+                })
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(
+                Button::new("commit-all", "Commit All")
+                    .style(ButtonStyle::Filled)
+                    .disabled(
+                        self.commits.is_empty()
+                            || self.load_task.is_some()
+                            || self.commit_task.is_some()
+                            || self.committed_count > 0,
+                    )
+                    .tooltip(Tooltip::for_action_title_in(
+                        "Make the commits in order",
+                        &CommitAll,
+                        &self.focus_handle,
+                    ))
+                    .on_click(
+                        cx.listener(|this, _, window, cx| this.commit_all(&CommitAll, window, cx)),
+                    ),
+            )
+    }
+
+    fn render_status(&self) -> Option<impl IntoElement> {
+        let message: SharedString = if let Some(error) = &self.error {
+            error.clone()
+        } else if self.load_task.is_some() {
+            "Proposing commits…".into()
+        } else if self.commit_task.is_some() {
+            format!(
+                "Committing {} of {}…",
+                self.committed_count + 1,
+                self.commits.len()
+            )
+            .into()
+        } else {
+            return None;
+        };
+        Some(
+            h_flex().p_2().justify_center().child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(if self.error.is_some() {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    }),
+            ),
+        )
+    }
+}
+
+fn message_editor(message: &str, window: &mut Window, cx: &mut App) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::auto_height(1, 8, window, cx);
+        editor.set_placeholder_text("Commit message", window, cx);
+        editor.set_text(message, window, cx);
+        editor
+    })
+}
+
+/// Splits the staged files into hunks.
+fn staged_hunks(diff: CommitDiff) -> (Vec<StagedFile>, Vec<Hunk>) {
+    let mut files = Vec::with_capacity(diff.files.len());
+    let mut hunks = Vec::new();
+    for (file_ix, file) in diff.files.into_iter().enumerate() {
+        let is_binary = [&file.old_text, &file.new_text]
+            .into_iter()
+            .flatten()
+            .any(|text| text.contains('\0'));
+        let mut file_hunks = if is_binary {
+            Vec::new()
+        } else {
+            language::line_diff(
+                file.old_text.as_deref().unwrap_or_default(),
+                file.new_text.as_deref().unwrap_or_default(),
+            )
+            .into_iter()
+            .map(|(old_rows, new_rows)| Hunk {
+                file_ix,
+                old_rows,
+                new_rows,
+            })
+            .collect::<Vec<_>>()
+        };
+        if file_hunks.is_empty() {
+            file_hunks.push(Hunk {
+                file_ix,
+                old_rows: 0..0,
+                new_rows: 0..0,
+            });
+        }
+        files.push(StagedFile {
+            path: file.path,
+            old_text: file.old_text,
+            new_text: file.new_text,
+            is_binary,
+            hunk_count: file_hunks.len(),
+        });
+        hunks.extend(file_hunks);
+    }
+    (files, hunks)
+}
+
+/// Formats the numbered hunks for the prompt, along with a short preview of
+/// each.
+fn describe_hunks(files: &[StagedFile], hunks: &[Hunk]) -> (String, Vec<SharedString>) {
+    let mut description = String::new();
+    let mut previews = Vec::with_capacity(hunks.len());
+    for (file_ix, file) in files.iter().enumerate() {
+        let old_lines = lines(file.old_text.as_deref());
+        let new_lines = lines(file.new_text.as_deref());
+        let change = match (&file.old_text, &file.new_text) {
+            (None, _) => "added",
+            (_, None) => "deleted",
+            _ => "modified",
+        };
+        for (ix, hunk) in hunks.iter().enumerate() {
+            if hunk.file_ix != file_ix {
+                continue;
+            }
+            writeln!(
+                description,
+                "Hunk {ix}: {} ({change})",
+                file.path.as_unix_str()
+            )
+            .ok();
+            let mut diff = String::new();
+            if file.is_binary {
+                diff.push_str("Binary file\n");
+            }
+            for line in &old_lines[hunk.old_rows.start as usize..hunk.old_rows.end as usize] {
+                diff.push('-');
+                diff.push_str(line);
+            }
+            for line in &new_lines[hunk.new_rows.start as usize..hunk.new_rows.end as usize] {
+                diff.push('+');
+                diff.push_str(line);
+            }
+            if !diff.ends_with('\n') {
+                diff.push('\n');
+            }
+            description.push_str(&diff);
+            previews.push(
+                diff.lines()
+                    .take(MAX_PREVIEW_LINES)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .into(),
+            );
+        }
+    }
+    (description, previews)
+}
+
+fn lines(text: Option<&str>) -> Vec<&str> {
+    text.unwrap_or_default().split_inclusive('\n').collect()
+}
+
+/// Replaces the old rows of each hunk, sorted by position, with its new rows.
+fn apply_hunks(old_text: &str, new_text: &str, hunks: &[&Hunk]) -> String {
+    let old_lines = lines(Some(old_text));
+    let new_lines = lines(Some(new_text));
+    let mut text = String::with_capacity(old_text.len().max(new_text.len()));
+    let mut old_row = 0;
+    for hunk in hunks {
+        text.extend(
+            old_lines[old_row..hunk.old_rows.start as usize]
+                .iter()
+                .copied(),
+        );
+        text.extend(
+            new_lines[hunk.new_rows.start as usize..hunk.new_rows.end as usize]
+                .iter()
+                .copied(),
+        );
+        old_row = hunk.old_rows.end as usize;
+    }
+    text.extend(old_lines[old_row..].iter().copied());
+    text
+}
+
+#[derive(Deserialize)]
+struct CommitProposal {
+    message: String,
+    hunks: Vec<usize>,
+}
+
+/// Parses the model's response into commit messages and the hunks of each
+/// commit. Unknown and repeated hunks are ignored, and hunks that the model
+/// left out are added to the last commit, so that every hunk is committed.
+fn parse_proposal(response: &str, hunk_count: usize) -> Result<Vec<(String, Vec<usize>)>> {
+    let json = response
+        .find('[')
+        .zip(response.rfind(']'))
+        .and_then(|(start, end)| response.get(start..=end))
+        .ok_or_else(|| anyhow!("The model didn't propose any commits"))?;
+    let proposals = serde_json::from_str::<Vec<CommitProposal>>(json)
+        .context("The model's proposal couldn't be parsed")?;
+
+    let mut assigned = vec![false; hunk_count];
+    let mut commits = proposals
+        .into_iter()
+        .filter_map(|proposal| {
+            let mut hunks = proposal
+                .hunks
+                .into_iter()
+                .filter(|ix| {
+                    assigned
+                        .get_mut(*ix)
+                        .is_some_and(|assigned| !std::mem::replace(assigned, true))
+                })
+                .collect::<Vec<_>>();
+            hunks.sort_unstable();
+            (!hunks.is_empty()).then(|| (proposal.message.trim().to_string(), hunks))
+        })
+        .collect::<Vec<_>>();
+
+    let unassigned = (0..hunk_count).filter(|ix| !assigned[*ix]);
+    match commits.last_mut() {
+        Some((_, hunks)) => {
+            hunks.extend(unassigned);
+            hunks.sort_unstable();
+        }
+        None => commits.push((String::new(), unassigned.collect())),
+    }
+    Ok(commits)
+}
+
+impl EventEmitter<ItemEvent> for SplitCommits {}
+
+impl Focusable for SplitCommits {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for SplitCommits {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Split Into Commits".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Split Into Commits Opened")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for SplitCommits {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("GitSplitCommits")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::commit_all))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                v_flex()
+                    .id("git-split-commits")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll_handle)
+                    .children(self.render_commits(cx)),
+            )
+            .children(self.render_status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use pretty_assertions::assert_eq;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{collections::BTreeMap, path::Path, sync::Arc};
+    use util::path;
+
+    const OLD_TEXT: &str = "one\ntwo\nthree\nfour\nfive\n";
+    const NEW_TEXT: &str = "one\n2\nthree\nfour\nfive\nsix\n";
+
+    #[test]
+    fn test_apply_hunks() {
+        let old_text = "one\ntwo\nthree\nfour\nfive\n";
+        let new_text = "one\n2\nthree\nfour\nfive\nsix\n";
+        let hunks = language::line_diff(old_text, new_text)
+            .into_iter()
+            .map(|(old_rows, new_rows)| Hunk {
+                file_ix: 0,
+                old_rows,
+                new_rows,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(hunks.len(), 2);
+
+        assert_eq!(apply_hunks(old_text, new_text, &[]), old_text);
+        assert_eq!(
+            apply_hunks(old_text, new_text, &[&hunks[0]]),
+            "one\n2\nthree\nfour\nfive\n"
+        );
+        assert_eq!(
+            apply_hunks(old_text, new_text, &[&hunks[1]]),
+            "one\ntwo\nthree\nfour\nfive\nsix\n"
+        );
+        assert_eq!(
+            apply_hunks(old_text, new_text, &[&hunks[0], &hunks[1]]),
+            new_text
+        );
+    }
+
+    #[test]
+    fn test_parse_proposal() {
+        let response = "```json\n[\
+            {\"message\": \"Add the parser\", \"hunks\": [2, 0, 7]},\
+            {\"message\": \"Use the parser \", \"hunks\": [0, 1]},\
+            {\"message\": \"Nothing\", \"hunks\": [9]}\
+        ]\n```";
+        assert_eq!(
+            parse_proposal(response, 4).unwrap(),
+            vec![
+                ("Add the parser".to_string(), vec![0, 2]),
+                ("Use the parser".to_string(), vec![1, 3]),
+            ]
+        );
+        assert_eq!(
+            parse_proposal("[]", 2).unwrap(),
+            vec![(String::new(), vec![0, 1])]
+        );
+        assert!(parse_proposal("I can't split these changes.", 2).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_plan(cx: &mut TestAppContext) {
+        let (_fs, project) = init_test(cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        // The hunks are in added.rs, deleted.rs, image.png, and the two in
+        // partial.rs.
+        let view = split_commits(
+            &project,
+            &[("Add the new file", &[0, 3]), ("Finish", &[1, 2, 4])],
+            cx,
+        )
+        .await;
+        let all_paths = ["added.rs", "deleted.rs", "image.png", "partial.rs"];
+
+        assert_eq!(
+            plan(&view, &all_paths, cx).unwrap(),
+            [
+                StepSummary {
+                    message: "Add the new file".into(),
+                    stage: vec!["added.rs".into()],
+                    unstage: vec!["deleted.rs".into(), "image.png".into()],
+                    index_texts: vec![(
+                        "partial.rs".into(),
+                        Some("one\n2\nthree\nfour\nfive\n".into())
+                    )],
+                },
+                StepSummary {
+                    message: "Finish".into(),
+                    stage: vec!["deleted.rs".into(), "image.png".into(), "partial.rs".into()],
+                    unstage: vec![],
+                    index_texts: vec![],
+                },
+            ]
+        );
+
+        // Files with unstaged changes get their staged version written to the
+        // index, rather than being staged from the worktree.
+        assert_eq!(
+            plan(&view, &["added.rs", "deleted.rs", "image.png"], cx).unwrap()[1],
+            StepSummary {
+                message: "Finish".into(),
+                stage: vec!["deleted.rs".into(), "image.png".into()],
+                unstage: vec![],
+                index_texts: vec![("partial.rs".into(), Some(NEW_TEXT.into()))],
+            }
+        );
+        assert_eq!(
+            plan(&view, &["added.rs", "deleted.rs", "partial.rs"], cx)
+                .unwrap_err()
+                .to_string(),
+            "image.png is binary and has unstaged changes, so it can't be committed separately"
+        );
+
+        view.update_in(cx, |view, window, cx| {
+            view.commits[1]
+                .message_editor
+                .update(cx, |editor, cx| editor.set_text("  ", window, cx));
+        });
+        assert_eq!(
+            plan(&view, &all_paths, cx).unwrap_err().to_string(),
+            "The message of commit 2 is empty"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_commit_all(cx: &mut TestAppContext) {
+        let (fs, project) = init_test(cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let view = split_commits(
+            &project,
+            &[("Add the new file", &[0, 3]), ("Finish", &[1, 2, 4])],
+            cx,
+        )
+        .await;
+
+        view.update_in(cx, |view, window, cx| {
+            view.commit_all(&CommitAll, window, cx)
+        });
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.error, None);
+            assert_eq!(view.committed_count, 2);
+        });
+        let commits = fs
+            .with_git_state(Path::new(path!("/project/.git")), false, |state| {
+                state.commits.clone()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|(message, contents)| {
+                let contents = contents
+                    .into_iter()
+                    .map(|(path, text)| (path.as_unix_str().to_string(), text))
+                    .collect::<BTreeMap<_, _>>();
+                (message, contents)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            commits,
+            [
+                (
+                    "Add the new file".to_string(),
+                    BTreeMap::from_iter([
+                        ("added.rs".to_string(), "new\n".to_string()),
+                        ("deleted.rs".to_string(), "gone\n".to_string()),
+                        ("image.png".to_string(), "\0old".to_string()),
+                        (
+                            "partial.rs".to_string(),
+                            "one\n2\nthree\nfour\nfive\n".to_string()
+                        ),
+                    ])
+                ),
+                (
+                    "Finish".to_string(),
+                    BTreeMap::from_iter([
+                        ("added.rs".to_string(), "new\n".to_string()),
+                        ("image.png".to_string(), "\0new".to_string()),
+                        ("partial.rs".to_string(), NEW_TEXT.to_string()),
+                    ])
+                ),
+            ]
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct StepSummary {
+        message: String,
+        stage: Vec<String>,
+        unstage: Vec<String>,
+        index_texts: Vec<(String, Option<String>)>,
+    }
+
+    fn plan(
+        view: &Entity<SplitCommits>,
+        stageable: &[&str],
+        cx: &mut VisualTestContext,
+    ) -> Result<Vec<StepSummary>> {
+        view.read_with(cx, |view, cx| {
+            let stageable = view
+                .files
+                .iter()
+                .filter(|file| stageable.contains(&file.path.as_unix_str()))
+                .map(|file| file.path.clone())
+                .collect();
+            let steps = view.plan(&stageable, cx)?;
+            let paths = |paths: Vec<RepoPath>| {
+                paths
+                    .iter()
+                    .map(|path| path.as_unix_str().to_string())
+                    .collect()
+            };
+            Ok(steps
+                .into_iter()
+                .map(|step| StepSummary {
+                    message: step.message,
+                    stage: paths(step.stage),
+                    unstage: paths(step.unstage),
+                    index_texts: step
+                        .index_texts
+                        .into_iter()
+                        .map(|(path, text)| (path.as_unix_str().to_string(), text))
+                        .collect(),
+                })
+                .collect())
+        })
+    }
+
+    /// Opens the view on the repository's staged changes, proposing the
+    /// given commits instead of asking a model.
+    async fn split_commits(
+        project: &Entity<Project>,
+        commits: &[(&str, &[usize])],
+        cx: &mut VisualTestContext,
+    ) -> Entity<SplitCommits> {
+        let repository = project
+            .read_with(cx, |project, cx| project.active_repository(cx))
+            .unwrap();
+        let diff = repository
+            .update(cx, |repository, _| repository.load_staged_diff())
+            .await
+            .unwrap()
+            .unwrap();
+        let (files, hunks) = staged_hunks(diff);
+        let project = project.clone();
+        cx.new_window_entity(|window, cx| {
+            let mut view = SplitCommits::new(repository, project, window, cx);
+            view.files = files;
+            view.hunks = hunks;
+            view.commits = commits
+                .iter()
+                .map(|(message, hunks)| ProposedCommit {
+                    message_editor: message_editor(message, window, cx),
+                    hunks: hunks.to_vec(),
+                })
+                .collect();
+            view
+        })
+    }
+
+    /// Creates a project whose repository has an added, a deleted, a binary
+    /// and a partially changed file staged.
+    async fn init_test(cx: &mut TestAppContext) -> (Arc<FakeFs>, Entity<Project>) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AgentSettings::register(cx);
+            workspace::init_settings(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            language_model::init_settings(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "added.rs": "new\n",
+                "image.png": "\0new",
+                "partial.rs": NEW_TEXT,
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/project/.git"));
+        fs.set_head_for_repo(
+            dot_git,
+            &[
+                ("deleted.rs", "gone\n".into()),
+                ("image.png", "\0old".into()),
+                ("partial.rs", OLD_TEXT.into()),
+            ],
+            "abc",
+        );
+        fs.set_index_for_repo(
+            dot_git,
+            &[
+                ("added.rs", "new\n".into()),
+                ("image.png", "\0new".into()),
+                ("partial.rs", NEW_TEXT.into()),
+            ],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        cx.run_until_parked();
+        (fs, project)
+    }
+}
@@ -0,0 +1,572 @@
+use fuzzy::StringMatchCandidate;
+
+use git::repository::{CommitSummary, Tag};
+use gpui::{
+    Action, AnyElement, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled, Subscription,
+    Task, WeakEntity, Window, actions, rems,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::{HighlightedLabel, KeyBinding, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{ModalView, Workspace};
+
+use crate::{commit_view::CommitView, git_panel::GitPanel, tag_picker};
+
+actions!(
+    tag_picker,
+    [
+        /// Deletes the selected tag.
+        DeleteTag,
+        /// Pushes the selected tag to a remote.
+        PushTag,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+pub fn open(
+    workspace: &mut Workspace,
+    _: &zed_actions::git::ViewTags,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx);
+    let weak_workspace = cx.weak_entity();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        TagList::new(repository, None, weak_workspace, rems(34.), window, cx)
+    })
+}
+
+/// Opens the tag selector to create a tag pointing at `target`.
+pub(crate) fn create_tag(
+    workspace: &mut Workspace,
+    repository: Entity<Repository>,
+    target: SharedString,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let weak_workspace = cx.weak_entity();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        TagList::new(
+            Some(repository),
+            Some(target),
+            weak_workspace,
+            rems(34.),
+            window,
+            cx,
+        )
+    })
+}
+
+pub struct TagList {
+    width: Rems,
+    pub picker: Entity<Picker<TagListDelegate>>,
+    picker_focus_handle: FocusHandle,
+    _subscription: Subscription,
+}
+
+impl TagList {
+    fn new(
+        repository: Option<Entity<Repository>>,
+        target: Option<SharedString>,
+        workspace: WeakEntity<Workspace>,
+        width: Rems,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = TagListDelegate::new(repository, target, workspace, cx);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let picker_focus_handle = picker.focus_handle(cx);
+        picker.update(cx, |picker, cx| {
+            picker.delegate.focus_handle = picker_focus_handle.clone();
+            picker.delegate.reload_tags(window, cx);
+        });
+
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+
+        Self {
+            picker,
+            picker_focus_handle,
+            width,
+            _subscription,
+        }
+    }
+
+    fn handle_delete_tag(&mut self, _: &DeleteTag, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker
+                .delegate
+                .delete_tag_at(picker.delegate.selected_index(), window, cx);
+        });
+        cx.notify();
+    }
+
+    fn handle_push_tag(&mut self, _: &PushTag, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker
+                .delegate
+                .push_tag_at(picker.delegate.selected_index(), window, cx);
+        });
+    }
+}
+
+impl ModalView for TagList {}
+impl EventEmitter<DismissEvent> for TagList {}
+impl Focusable for TagList {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.picker_focus_handle.clone()
+    }
+}
+
+impl Render for TagList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TagList")
+            .w(self.width)
+            .on_action(cx.listener(Self::handle_delete_tag))
+            .on_action(cx.listener(Self::handle_push_tag))
+            .child(self.picker.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TagEntry {
+    tag: Tag,
+    positions: Vec<usize>,
+    is_new: bool,
+}
+
+pub struct TagListDelegate {
+    matches: Vec<TagEntry>,
+    all_tags: Option<Vec<Tag>>,
+    repo: Option<Entity<Repository>>,
+    /// The commit new tags point at, or `None` for HEAD.
+    target: Option<SharedString>,
+    workspace: WeakEntity<Workspace>,
+    selected_index: usize,
+    focus_handle: FocusHandle,
+}
+
+impl TagListDelegate {
+    fn new(
+        repo: Option<Entity<Repository>>,
+        target: Option<SharedString>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<TagList>,
+    ) -> Self {
+        Self {
+            matches: vec![],
+            all_tags: None,
+            repo,
+            target,
+            workspace,
+            selected_index: 0,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn reload_tags(&self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let tags_request = repo.update(cx, |repo, _| repo.tags());
+        cx.spawn_in(window, async move |picker, cx| {
+            let tags = tags_request.await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.all_tags = Some(tags);
+                picker.refresh(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn create_tag(
+        &self,
+        name: SharedString,
+        message: Option<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let target = self.target.as_ref().map(|target| target.to_string());
+        cx.spawn(async move |_, cx| {
+            repo.update(cx, |repo, _| {
+                repo.create_tag(
+                    name.to_string(),
+                    target,
+                    message.map(|message| message.to_string()),
+                )
+            })?
+            .await??;
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to create tag", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn delete_tag_at(&self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(ix).filter(|entry| !entry.is_new) else {
+            return;
+        };
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let name = entry.tag.name.to_string();
+
+        cx.spawn_in(window, async move |picker, cx| {
+            repo.update(cx, |repo, _| repo.delete_tag(name))?.await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.reload_tags(window, cx);
+            })?;
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to delete tag", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn push_tag_at(&self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(ix).filter(|entry| !entry.is_new) else {
+            return;
+        };
+        let name = entry.tag.name.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.push_tag(name, window, cx));
+                }
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn open_commit(&self, tag: &Tag, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.as_ref() else {
+            return;
+        };
+        let commit = CommitSummary {
+            sha: tag.sha.clone(),
+            subject: tag.subject.clone(),
+            commit_timestamp: tag.timestamp,
+            author_name: SharedString::default(),
+            has_parent: true,
+        };
+        CommitView::open(commit, repo.downgrade(), self.workspace.clone(), window, cx);
+        cx.emit(DismissEvent);
+    }
+}
+
+/// Splits a query into the name of a tag to create and, if anything follows
+/// the name, the message that makes it an annotated tag.
+fn parse_new_tag(query: &str) -> Option<(SharedString, Option<SharedString>)> {
+    let query = query.trim();
+    let (name, message) = match query.split_once(char::is_whitespace) {
+        Some((name, message)) => (name, Some(message.trim().to_string().into())),
+        None => (query, None),
+    };
+    (!name.is_empty()).then(|| (name.to_string().into(), message))
+}
+
+impl PickerDelegate for TagListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select a tag, or type a name and message to create one…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(all_tags) = self.all_tags.clone() else {
+            return Task::ready(());
+        };
+        let target = self.target.clone();
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let new_tag = parse_new_tag(&query);
+            let mut matches: Vec<TagEntry> = match &new_tag {
+                None => all_tags
+                    .into_iter()
+                    .map(|tag| TagEntry {
+                        tag,
+                        positions: Vec::new(),
+                        is_new: false,
+                    })
+                    .collect(),
+                Some((name, _)) => {
+                    let candidates = all_tags
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, tag)| StringMatchCandidate::new(ix, &tag.name))
+                        .collect::<Vec<StringMatchCandidate>>();
+                    fuzzy::match_strings(
+                        &candidates,
+                        name,
+                        true,
+                        true,
+                        10000,
+                        &Default::default(),
+                        cx.background_executor().clone(),
+                    )
+                    .await
+                    .into_iter()
+                    .map(|candidate| TagEntry {
+                        tag: all_tags[candidate.candidate_id].clone(),
+                        positions: candidate.positions,
+                        is_new: false,
+                    })
+                    .collect()
+                }
+            };
+
+            picker
+                .update(cx, |picker, _| {
+                    if let Some((name, message)) = new_tag
+                        && !matches.iter().any(|entry| entry.tag.name == name)
+                    {
+                        matches.push(TagEntry {
+                            tag: Tag {
+                                name,
+                                sha: target.unwrap_or_default(),
+                                subject: SharedString::default(),
+                                message,
+                                timestamp: 0,
+                            },
+                            positions: Vec::new(),
+                            is_new: true,
+                        });
+                    }
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            core::cmp::min(delegate.selected_index, delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index()).cloned() else {
+            return;
+        };
+        if entry.is_new {
+            self.create_tag(entry.tag.name, entry.tag.message, window, cx);
+        } else {
+            self.open_commit(&entry.tag, window, cx);
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = self.matches.get(ix)?;
+        let tag = &entry.tag;
+
+        let (tag_label, description) = if entry.is_new {
+            let kind = if tag.is_annotated() {
+                "annotated tag"
+            } else {
+                "tag"
+            };
+            let label = h_flex()
+                .gap_1()
+                .child(
+                    Icon::new(IconName::Plus)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    Label::new(format!("Create {kind} \"{}\"…", tag.name))
+                        .single_line()
+                        .truncate(),
+                )
+                .into_any_element();
+            let target = if tag.sha.is_empty() {
+                self.repo
+                    .as_ref()
+                    .and_then(|repo| repo.read(cx).branch.as_ref())
+                    .map(|branch| branch.name().to_string())
+                    .unwrap_or_else(|| "HEAD".to_string())
+            } else {
+                tag.sha.chars().take(7).collect()
+            };
+            let description = match &tag.message {
+                Some(message) => format!("at {target}: {message}"),
+                None => format!("at {target}"),
+            };
+            (label, description)
+        } else {
+            let label = HighlightedLabel::new(tag.name.clone(), entry.positions.clone())
+                .truncate()
+                .into_any_element();
+            let short_sha = tag.sha.get(0..7).unwrap_or(&*tag.sha);
+            let description = match &tag.message {
+                Some(message) if !message.is_empty() => format!("{short_sha} • {message}"),
+                _ => format!("{short_sha} • {}", tag.subject),
+            };
+            (label, description)
+        };
+
+        let tag_time = (!entry.is_new).then(|| {
+            let time = OffsetDateTime::from_unix_timestamp(tag.timestamp)
+                .unwrap_or_else(|_| OffsetDateTime::now_utc());
+            format_local_timestamp(
+                time,
+                OffsetDateTime::now_utc(),
+                time_format::TimestampFormat::Relative,
+            )
+        });
+
+        Some(
+            ListItem::new(SharedString::from(format!("tag-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .tooltip(Tooltip::text(if entry.is_new {
+                    format!("Create tag \"{}\"", tag.name)
+                } else {
+                    tag.subject.to_string()
+                }))
+                .child(
+                    v_flex()
+                        .w_full()
+                        .overflow_hidden()
+                        .child(
+                            h_flex()
+                                .gap_6()
+                                .justify_between()
+                                .overflow_x_hidden()
+                                .child(tag_label)
+                                .when_some(tag_time, |label, tag_time| {
+                                    label.child(
+                                        Label::new(tag_time)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                }),
+                        )
+                        .child(
+                            Label::new(description)
+                                .size(LabelSize::Small)
+                                .truncate()
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No tags found".into())
+    }
+
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        let entry = self.matches.get(self.selected_index)?;
+        let focus_handle = self.focus_handle.clone();
+
+        let confirm_label = if entry.is_new { "Create" } else { "Open" };
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("confirm-tag", confirm_label)
+                        .key_binding(
+                            KeyBinding::for_action_in(&menu::Confirm, &focus_handle, window, cx)
+                                .map(|kb| kb.size(rems_from_px(12.))),
+                        )
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(menu::Confirm.boxed_clone(), cx)
+                        }),
+                )
+                .when(!entry.is_new, |footer| {
+                    footer
+                        .child(
+                            Button::new("push-tag", "Push")
+                                .key_binding(
+                                    KeyBinding::for_action_in(
+                                        &tag_picker::PushTag,
+                                        &focus_handle,
+                                        window,
+                                        cx,
+                                    )
+                                    .map(|kb| kb.size(rems_from_px(12.))),
+                                )
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(tag_picker::PushTag.boxed_clone(), cx)
+                                }),
+                        )
+                        .child(
+                            Button::new("delete-tag", "Delete")
+                                .key_binding(
+                                    KeyBinding::for_action_in(
+                                        &tag_picker::DeleteTag,
+                                        &focus_handle,
+                                        window,
+                                        cx,
+                                    )
+                                    .map(|kb| kb.size(rems_from_px(12.))),
+                                )
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(tag_picker::DeleteTag.boxed_clone(), cx)
+                                }),
+                        )
+                })
+                .into_any(),
+        )
+    }
+}
@@ -542,6 +542,11 @@ pub trait LanguageModel: Send + Sync {
     /// Whether this model supports images
     fn supports_images(&self) -> bool;
 
+    /// Whether this model supports audio input.
+    fn supports_audio(&self) -> bool {
+        false
+    }
+
     /// Whether this model supports tools.
     fn supports_tools(&self) -> bool;
 
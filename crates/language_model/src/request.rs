@@ -2,7 +2,7 @@ use std::io::{Cursor, Write};
 use std::sync::Arc;
 
 use anyhow::Result;
-use base64::write::EncoderWriter;
+use base64::{Engine as _, write::EncoderWriter};
 use cloud_llm_client::{CompletionIntent, CompletionMode};
 use gpui::{
     App, AppContext as _, DevicePixels, Image, ImageFormat, ObjectFit, SharedString, Size, Task,
@@ -160,6 +160,72 @@ impl LanguageModelImage {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct LanguageModelAudio {
+    /// Base64-encoded audio data.
+    pub source: SharedString,
+    pub format: LanguageModelAudioFormat,
+}
+
+impl LanguageModelAudio {
+    pub fn from_wav(wav: &[u8]) -> Self {
+        Self {
+            source: base64::engine::general_purpose::STANDARD.encode(wav).into(),
+            format: LanguageModelAudioFormat::Wav,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.source.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+}
+
+impl std::fmt::Debug for LanguageModelAudio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LanguageModelAudio")
+            .field("source", &format!("<{} bytes>", self.source.len()))
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+/// The audio formats accepted by every provider that supports audio input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageModelAudioFormat {
+    Wav,
+    Mp3,
+}
+
+impl LanguageModelAudioFormat {
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type {
+            "audio/wav" | "audio/x-wav" | "audio/wave" => Some(Self::Wav),
+            "audio/mpeg" | "audio/mp3" => Some(Self::Mp3),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Wav => "audio/wav",
+            Self::Mp3 => "audio/mpeg",
+        }
+    }
+
+    /// The name of the format in OpenAI's `input_audio` content parts.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Mp3 => "mp3",
+        }
+    }
+}
+
 fn encode_as_base64(data: Arc<Image>, image: image::DynamicImage) -> Result<Vec<u8>> {
     let mut base64_image = Vec::new();
     {
@@ -313,6 +379,7 @@ pub enum MessageContent {
     },
     RedactedThinking(String),
     Image(LanguageModelImage),
+    Audio(LanguageModelAudio),
     ToolUse(LanguageModelToolUse),
     ToolResult(LanguageModelToolResult),
 }
@@ -324,7 +391,9 @@ impl MessageContent {
             MessageContent::Thinking { text, .. } => Some(text.as_str()),
             MessageContent::RedactedThinking(_) => None,
             MessageContent::ToolResult(tool_result) => tool_result.content.to_str(),
-            MessageContent::ToolUse(_) | MessageContent::Image(_) => None,
+            MessageContent::ToolUse(_) | MessageContent::Image(_) | MessageContent::Audio(_) => {
+                None
+            }
         }
     }
 
@@ -335,7 +404,8 @@ impl MessageContent {
             MessageContent::ToolResult(tool_result) => tool_result.content.is_empty(),
             MessageContent::RedactedThinking(_)
             | MessageContent::ToolUse(_)
-            | MessageContent::Image(_) => false,
+            | MessageContent::Image(_)
+            | MessageContent::Audio(_) => false,
         }
     }
 }
@@ -256,6 +256,9 @@ pub fn count_anthropic_tokens(
                     MessageContent::RedactedThinking(_) => {
                         // Thinking blocks are not included in the input token count.
                     }
+                    MessageContent::Audio(_) => {
+                        // Audio is never sent to Anthropic models.
+                    }
                     MessageContent::Image(image) => {
                         tokens_from_images += image.estimate_tokens();
                     }
@@ -491,6 +494,7 @@ pub fn into_anthropic(
                                 None
                             }
                         }
+                        MessageContent::Audio(_) => None,
                         MessageContent::Image(image) => Some(anthropic::RequestContent::Image {
                             source: anthropic::ImageSource {
                                 source_type: "base64".to_string(),
@@ -832,7 +832,7 @@ pub fn get_bedrock_tokens(
                         MessageContent::Text(text) | MessageContent::Thinking { text, .. } => {
                             string_contents.push_str(&text);
                         }
-                        MessageContent::RedactedThinking(_) => {}
+                        MessageContent::RedactedThinking(_) | MessageContent::Audio(_) => {}
                         MessageContent::Image(image) => {
                             tokens_from_images += image.estimate_tokens();
                         }
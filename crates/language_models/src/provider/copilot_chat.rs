@@ -584,7 +584,8 @@ fn into_copilot_chat(
                         MessageContent::ToolUse(_)
                         | MessageContent::RedactedThinking(_)
                         | MessageContent::ToolResult(_)
-                        | MessageContent::Image(_) => None,
+                        | MessageContent::Image(_)
+                        | MessageContent::Audio(_) => None,
                     }) {
                         buffer.push_str(string);
                     }
@@ -313,6 +313,10 @@ impl LanguageModel for GoogleLanguageModel {
         self.model.supports_images()
     }
 
+    fn supports_audio(&self) -> bool {
+        true
+    }
+
     fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
         match choice {
             LanguageModelToolChoice::Auto
@@ -438,6 +442,14 @@ pub fn into_google(
                         },
                     })]
                 }
+                language_model::MessageContent::Audio(audio) => {
+                    vec![Part::InlineDataPart(google_ai::InlineDataPart {
+                        inline_data: google_ai::GenerativeContentBlob {
+                            mime_type: audio.format.mime_type().to_string(),
+                            data: audio.source.to_string(),
+                        },
+                    })]
+                }
                 language_model::MessageContent::ToolUse(tool_use) => {
                     vec![Part::FunctionCallPart(google_ai::FunctionCallPart {
                         function_call: google_ai::FunctionCall {
@@ -278,6 +278,7 @@ impl LmStudioLanguageModel {
                     ),
                     MessageContent::Thinking { .. } => {}
                     MessageContent::RedactedThinking(_) => {}
+                    MessageContent::Audio(_) => {}
                     MessageContent::Image(image) => {
                         add_message_content_part(
                             lmstudio::MessagePart::Image {
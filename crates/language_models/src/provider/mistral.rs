@@ -429,6 +429,7 @@ pub fn into_mistral(
                             }
                         }
                         MessageContent::RedactedThinking(_) => {}
+                        MessageContent::Audio(_) => {}
                         MessageContent::ToolUse(_) => {
                             // Tool use is not supported in User messages for Mistral
                         }
@@ -479,7 +480,7 @@ pub fn into_mistral(
                             }
                         }
                         MessageContent::RedactedThinking(_) => {}
-                        MessageContent::Image(_) => {}
+                        MessageContent::Image(_) | MessageContent::Audio(_) => {}
                         MessageContent::ToolUse(tool_use) => {
                             let tool_call = mistral::ToolCall {
                                 id: tool_use.id.to_string(),
@@ -534,9 +535,10 @@ pub fn into_mistral(
                         }
                         MessageContent::RedactedThinking(_) => {}
                         MessageContent::Image(_)
+                        | MessageContent::Audio(_)
                         | MessageContent::ToolUse(_)
                         | MessageContent::ToolResult(_) => {
-                            // Images and tools are not supported in System messages
+                            // Images, audio and tools are not supported in System messages
                         }
                     }
                 }
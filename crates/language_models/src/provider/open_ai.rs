@@ -282,6 +282,10 @@ impl LanguageModel for OpenAiLanguageModel {
         }
     }
 
+    fn supports_audio(&self) -> bool {
+        self.model.supports_audio_input()
+    }
+
     fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
         match choice {
             LanguageModelToolChoice::Auto => true,
@@ -378,6 +382,18 @@ pub fn into_open_ai(
                         &mut messages,
                     );
                 }
+                MessageContent::Audio(audio) => {
+                    add_message_content_part(
+                        open_ai::MessagePart::InputAudio {
+                            input_audio: open_ai::InputAudio {
+                                data: audio.source.to_string(),
+                                format: audio.format.extension().to_string(),
+                            },
+                        },
+                        message.role,
+                        &mut messages,
+                    );
+                }
                 MessageContent::ToolUse(tool_use) => {
                     let tool_call = open_ai::ToolCall {
                         id: tool_use.id.to_string(),
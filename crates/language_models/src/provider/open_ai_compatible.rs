@@ -44,6 +44,8 @@ struct ModelInfoDetails {
     #[serde(default)]
     supports_vision: bool,
     #[serde(default)]
+    supports_audio_input: bool,
+    #[serde(default)]
     supports_function_calling: bool,
 }
 
@@ -141,6 +143,7 @@ impl State {
                     capabilities: ModelCapabilities {
                         tools: entry.model_info.supports_function_calling,
                         images: entry.model_info.supports_vision,
+                        audio: entry.model_info.supports_audio_input,
                         parallel_tool_calls: false,
                         prompt_cache_key: false,
                     },
@@ -403,6 +406,10 @@ impl LanguageModel for OpenAiCompatibleLanguageModel {
         self.model.capabilities.images
     }
 
+    fn supports_audio(&self) -> bool {
+        self.model.capabilities.audio
+    }
+
     fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
         match choice {
             LanguageModelToolChoice::Auto => self.model.capabilities.tools,
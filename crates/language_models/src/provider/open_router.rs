@@ -401,6 +401,7 @@ pub fn into_open_router(
                 ),
                 MessageContent::Thinking { .. } => {}
                 MessageContent::RedactedThinking(_) => {}
+                MessageContent::Audio(_) => {}
                 MessageContent::Image(image) => {
                     add_message_content_part(
                         open_router::MessagePart::Image {
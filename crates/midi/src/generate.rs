@@ -0,0 +1,278 @@
+use anyhow::{Context as _, Result, anyhow};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{Chord, MidiNote, MidiPattern, TICKS_PER_BEAT};
+
+/// What the pattern plays on each hit of the rhythm grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternStyle {
+    /// Every tone of the current chord at once.
+    #[default]
+    Chords,
+    /// The root of the current chord, two octaves below middle C.
+    Bass,
+    /// One tone of the current chord at a time, rising through two octaves.
+    Arpeggio,
+}
+
+impl PatternStyle {
+    fn default_octave(self) -> i8 {
+        match self {
+            PatternStyle::Chords => 3,
+            PatternStyle::Bass => 2,
+            PatternStyle::Arpeggio => 4,
+        }
+    }
+}
+
+/// A step of a rhythm grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Hit {
+        accent: bool,
+    },
+    /// Keeps the notes of the previous step sounding.
+    Hold,
+    Rest,
+}
+
+/// Parses a rhythm grid such as "x..x..x. X-x.", where `x` is a hit, `X` an
+/// accented hit, `-` holds the previous hit and `.` is a rest. Spaces and bar
+/// lines are ignored.
+pub fn parse_rhythm(text: &str) -> Result<Vec<Step>> {
+    let steps = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '|')
+        .map(|c| match c {
+            'x' => Ok(Step::Hit { accent: false }),
+            'X' => Ok(Step::Hit { accent: true }),
+            '-' => Ok(Step::Hold),
+            '.' => Ok(Step::Rest),
+            _ => Err(anyhow!("unexpected character in rhythm: {c:?}")),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if !steps.iter().any(|step| matches!(step, Step::Hit { .. })) {
+        return Err(anyhow!("the rhythm doesn't contain any hits"));
+    }
+    Ok(steps)
+}
+
+pub struct PatternSpec {
+    pub progression: Vec<Chord>,
+    /// Repeated for the whole length of the pattern.
+    pub rhythm: Vec<Step>,
+    pub steps_per_beat: u32,
+    pub beats_per_bar: u32,
+    pub beats_per_chord: u32,
+    pub bars: u32,
+    pub bpm: f32,
+    pub style: PatternStyle,
+    /// The octave of the chord roots, where octave 4 starts at middle C.
+    pub octave: Option<i8>,
+}
+
+impl PatternSpec {
+    pub fn generate(&self) -> Result<MidiPattern> {
+        if !(1..=16).contains(&self.steps_per_beat)
+            || !(TICKS_PER_BEAT as u32).is_multiple_of(self.steps_per_beat)
+        {
+            return Err(anyhow!(
+                "steps per beat must divide {TICKS_PER_BEAT}, such as 2, 3, 4, 6, 8 or 12"
+            ));
+        }
+        if !(1..=16).contains(&self.beats_per_bar) {
+            return Err(anyhow!("beats per bar must be between 1 and 16"));
+        }
+        if !(1..=256).contains(&self.bars) {
+            return Err(anyhow!("the pattern must be between 1 and 256 bars long"));
+        }
+        if !(1..=256).contains(&self.beats_per_chord) {
+            return Err(anyhow!("beats per chord must be between 1 and 256"));
+        }
+        if !(20.0..=999.0).contains(&self.bpm) {
+            return Err(anyhow!("the tempo must be between 20 and 999 BPM"));
+        }
+        if self.progression.is_empty() || self.rhythm.is_empty() {
+            return Err(anyhow!("the progression and rhythm must not be empty"));
+        }
+        let octave = self.octave.unwrap_or(self.style.default_octave());
+        if !(0..=8).contains(&octave) {
+            return Err(anyhow!("the octave must be between 0 and 8"));
+        }
+
+        let ticks_per_step = TICKS_PER_BEAT as u32 / self.steps_per_beat;
+        let steps_per_chord = self.beats_per_chord * self.steps_per_beat;
+        let total_steps = self.bars * self.beats_per_bar * self.steps_per_beat;
+
+        let mut notes = Vec::<MidiNote>::new();
+        // The notes started by the last hit, which a hold extends.
+        let mut sounding = 0..0;
+        let mut arpeggio_index = 0;
+        for step_index in 0..total_steps {
+            let chord_index = step_index / steps_per_chord;
+            let chord = &self.progression[chord_index as usize % self.progression.len()];
+            if step_index.is_multiple_of(steps_per_chord) {
+                // Notes never ring into the next chord.
+                sounding = notes.len()..notes.len();
+                arpeggio_index = 0;
+            }
+
+            match self.rhythm[step_index as usize % self.rhythm.len()] {
+                Step::Hit { accent } => {
+                    let root = (octave as i32 + 1) * 12 + chord.root as i32;
+                    let pitches = match self.style {
+                        PatternStyle::Chords => chord
+                            .intervals
+                            .iter()
+                            .map(|interval| root + *interval as i32)
+                            .collect(),
+                        PatternStyle::Bass => vec![root],
+                        PatternStyle::Arpeggio => {
+                            let tone_count = chord.intervals.len();
+                            let tone = arpeggio_index % (tone_count * 2);
+                            arpeggio_index += 1;
+                            vec![
+                                root + chord.intervals[tone % tone_count] as i32
+                                    + 12 * (tone / tone_count) as i32,
+                            ]
+                        }
+                    };
+                    let start = notes.len();
+                    for pitch in pitches {
+                        let pitch = u8::try_from(pitch).ok().filter(|pitch| *pitch <= 127);
+                        let pitch = pitch.context("the notes are too high, use a lower octave")?;
+                        notes.push(MidiNote {
+                            start: step_index * ticks_per_step,
+                            duration: ticks_per_step,
+                            pitch,
+                            velocity: if accent { 118 } else { 96 },
+                        });
+                    }
+                    sounding = start..notes.len();
+                }
+                Step::Hold => {
+                    for note in &mut notes[sounding.clone()] {
+                        note.duration += ticks_per_step;
+                    }
+                }
+                Step::Rest => sounding = notes.len()..notes.len(),
+            }
+        }
+
+        Ok(MidiPattern {
+            ticks_per_beat: TICKS_PER_BEAT,
+            beats_per_bar: self.beats_per_bar,
+            bars: self.bars,
+            bpm: self.bpm,
+            notes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, parse_progression};
+
+    fn spec(progression: &str, rhythm: &str, style: PatternStyle) -> PatternSpec {
+        let key = Key::parse("C major").unwrap();
+        PatternSpec {
+            progression: parse_progression(progression, &key).unwrap(),
+            rhythm: parse_rhythm(rhythm).unwrap(),
+            steps_per_beat: 4,
+            beats_per_bar: 4,
+            beats_per_chord: 4,
+            bars: 2,
+            bpm: 120.0,
+            style,
+            octave: None,
+        }
+    }
+
+    fn summarize(pattern: &MidiPattern) -> Vec<(u32, u32, u8)> {
+        pattern
+            .notes
+            .iter()
+            .map(|note| (note.start / 120, note.duration / 120, note.pitch))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_rhythm() {
+        assert_eq!(
+            parse_rhythm("X-. | x").unwrap(),
+            [
+                Step::Hit { accent: true },
+                Step::Hold,
+                Step::Rest,
+                Step::Hit { accent: false },
+            ]
+        );
+        assert!(parse_rhythm("....").is_err());
+        assert!(parse_rhythm("x o x").is_err());
+    }
+
+    #[test]
+    fn test_generate_bass() {
+        let pattern = spec("I V", "x--. x... x.x. ....", PatternStyle::Bass)
+            .generate()
+            .unwrap();
+        assert_eq!(pattern.len_in_ticks(), 2 * 4 * 480);
+        assert_eq!(
+            summarize(&pattern),
+            [
+                (0, 3, 36),
+                (4, 1, 36),
+                (8, 1, 36),
+                (10, 1, 36),
+                (16, 3, 43),
+                (20, 1, 43),
+                (24, 1, 43),
+                (26, 1, 43),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_chords_and_arpeggios() {
+        let mut chords = spec("vi", "X---------------", PatternStyle::Chords);
+        chords.bars = 1;
+        let pattern = chords.generate().unwrap();
+        assert_eq!(summarize(&pattern), [(0, 16, 57), (0, 16, 60), (0, 16, 64)]);
+        assert!(pattern.notes.iter().all(|note| note.velocity == 118));
+
+        let mut arpeggio = spec("I", "xxxx xxxx", PatternStyle::Arpeggio);
+        arpeggio.bars = 1;
+        arpeggio.steps_per_beat = 2;
+        let pitches = arpeggio
+            .generate()
+            .unwrap()
+            .notes
+            .iter()
+            .map(|note| note.pitch)
+            .collect::<Vec<_>>();
+        assert_eq!(pitches, [60, 64, 67, 72, 76, 79, 60, 64]);
+
+        let mut too_high = spec("V", "xxxx", PatternStyle::Arpeggio);
+        too_high.octave = Some(8);
+        assert!(too_high.generate().is_err());
+        too_high.style = PatternStyle::Chords;
+        assert!(too_high.generate().is_ok());
+
+        let mut too_long = spec("I", "x", PatternStyle::Chords);
+        too_long.beats_per_chord = u32::MAX;
+        assert!(too_long.generate().is_err());
+    }
+
+    #[test]
+    fn test_holds_stop_at_chord_changes() {
+        let mut bass = spec("I IV", "x---", PatternStyle::Bass);
+        bass.bars = 1;
+        bass.beats_per_chord = 1;
+        bass.steps_per_beat = 2;
+        let pattern = bass.generate().unwrap();
+        assert_eq!(summarize(&pattern), [(0, 4, 36), (8, 4, 36)]);
+    }
+}
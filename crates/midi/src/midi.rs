@@ -0,0 +1,10 @@
+//! Generates simple note patterns from a key, a chord progression and a
+//! rhythm grid, and writes them as Standard MIDI Files.
+
+mod generate;
+mod pattern;
+mod theory;
+
+pub use generate::{PatternSpec, PatternStyle, Step, parse_rhythm};
+pub use pattern::{MidiNote, MidiPattern, TICKS_PER_BEAT, note_name};
+pub use theory::{Chord, Key, Mode, parse_progression};
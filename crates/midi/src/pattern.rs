@@ -0,0 +1,174 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+/// The resolution of generated patterns, which is divisible by all the
+/// common subdivisions of a beat.
+pub const TICKS_PER_BEAT: u16 = 480;
+
+/// A note, with its position and length in ticks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MidiNote {
+    pub start: u32,
+    pub duration: u32,
+    pub pitch: u8,
+    pub velocity: u8,
+}
+
+/// A single-track pattern that can be written as a Standard MIDI File.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MidiPattern {
+    pub ticks_per_beat: u16,
+    pub beats_per_bar: u32,
+    pub bars: u32,
+    pub bpm: f32,
+    pub notes: Vec<MidiNote>,
+}
+
+impl MidiPattern {
+    pub fn len_in_ticks(&self) -> u32 {
+        self.bars * self.beats_per_bar * self.ticks_per_beat as u32
+    }
+
+    pub fn pitch_range(&self) -> Option<RangeInclusive<u8>> {
+        let lowest = self.notes.iter().map(|note| note.pitch).min()?;
+        let highest = self.notes.iter().map(|note| note.pitch).max()?;
+        Some(lowest..=highest)
+    }
+
+    /// Encodes the pattern as a format 0 Standard MIDI File on channel 1.
+    pub fn to_smf(&self) -> Vec<u8> {
+        let mut events = Vec::with_capacity(self.notes.len() * 2);
+        for note in &self.notes {
+            // Note offs sort before note ons at the same tick, so repeated
+            // notes of the same pitch don't cut each other off.
+            events.push((note.start + note.duration, 0, [0x80, note.pitch, 0]));
+            events.push((note.start, 1, [0x90, note.pitch, note.velocity]));
+        }
+        events.sort_by_key(|(tick, order, _)| (*tick, *order));
+
+        let mut track = Vec::new();
+        let tempo = (60_000_000.0 / self.bpm).round() as u32;
+        write_vlq(&mut track, 0);
+        track.extend_from_slice(&[0xFF, 0x51, 0x03]);
+        track.extend_from_slice(&tempo.to_be_bytes()[1..]);
+        write_vlq(&mut track, 0);
+        // The denominator is a power of two, and 24 MIDI clocks per click and
+        // 8 32nd notes per quarter are the values every sequencer writes.
+        track.extend_from_slice(&[0xFF, 0x58, 0x04, self.beats_per_bar as u8, 2, 24, 8]);
+
+        let mut last_tick = 0;
+        for (tick, _, event) in events {
+            write_vlq(&mut track, tick - last_tick);
+            track.extend_from_slice(&event);
+            last_tick = tick;
+        }
+        write_vlq(&mut track, self.len_in_ticks().saturating_sub(last_tick));
+        track.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+
+        let mut smf = Vec::with_capacity(22 + track.len());
+        smf.extend_from_slice(b"MThd");
+        smf.extend_from_slice(&6u32.to_be_bytes());
+        smf.extend_from_slice(&0u16.to_be_bytes());
+        smf.extend_from_slice(&1u16.to_be_bytes());
+        smf.extend_from_slice(&self.ticks_per_beat.to_be_bytes());
+        smf.extend_from_slice(b"MTrk");
+        smf.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        smf.extend_from_slice(&track);
+        smf
+    }
+}
+
+/// Returns the name of a MIDI pitch, such as "C4" for middle C.
+pub fn note_name(pitch: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+    format!("{}{}", NAMES[pitch as usize % 12], pitch as i32 / 12 - 1)
+}
+
+/// Writes a variable-length quantity, as used for delta times.
+fn write_vlq(out: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = [0; 5];
+    let mut start = bytes.len() - 1;
+    bytes[start] = (value & 0x7F) as u8;
+    value >>= 7;
+    while value > 0 {
+        start -= 1;
+        bytes[start] = (value & 0x7F) as u8 | 0x80;
+        value >>= 7;
+    }
+    out.extend_from_slice(&bytes[start..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_vlq() {
+        for (value, expected) in [
+            (0, vec![0x00]),
+            (0x40, vec![0x40]),
+            (0x7F, vec![0x7F]),
+            (0x80, vec![0x81, 0x00]),
+            (0x2000, vec![0xC0, 0x00]),
+            (0x0FFF_FFFF, vec![0xFF, 0xFF, 0xFF, 0x7F]),
+        ] {
+            let mut out = Vec::new();
+            write_vlq(&mut out, value);
+            assert_eq!(out, expected, "{value:#x}");
+        }
+    }
+
+    #[test]
+    fn test_to_smf() {
+        let pattern = MidiPattern {
+            ticks_per_beat: 96,
+            beats_per_bar: 4,
+            bars: 1,
+            bpm: 120.0,
+            notes: vec![
+                MidiNote {
+                    start: 0,
+                    duration: 96,
+                    pitch: 60,
+                    velocity: 100,
+                },
+                MidiNote {
+                    start: 96,
+                    duration: 96,
+                    pitch: 60,
+                    velocity: 80,
+                },
+            ],
+        };
+        let smf = pattern.to_smf();
+        assert_eq!(
+            &smf[..22],
+            [
+                b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0, 96, //
+                b'M', b'T', b'r', b'k', 0, 0, 0, 36,
+            ]
+        );
+        assert_eq!(
+            &smf[22..],
+            [
+                0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 500000 µs per beat
+                0x00, 0xFF, 0x58, 0x04, 4, 2, 24, 8, //
+                0x00, 0x90, 60, 100, //
+                0x60, 0x80, 60, 0, //
+                0x00, 0x90, 60, 80, //
+                0x60, 0x80, 60, 0, //
+                0x81, 0x40, 0xFF, 0x2F, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_note_name() {
+        assert_eq!(note_name(60), "C4");
+        assert_eq!(note_name(69), "A4");
+        assert_eq!(note_name(0), "C-1");
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+
+use anyhow::{Context as _, Result, anyhow};
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    HarmonicMinor,
+}
+
+impl Mode {
+    /// The semitones of each degree above the tonic.
+    fn intervals(self) -> [u8; 7] {
+        match self {
+            Mode::Major => [0, 2, 4, 5, 7, 9, 11],
+            Mode::Minor => [0, 2, 3, 5, 7, 8, 10],
+            Mode::Dorian => [0, 2, 3, 5, 7, 9, 10],
+            Mode::Phrygian => [0, 1, 3, 5, 7, 8, 10],
+            Mode::Lydian => [0, 2, 4, 6, 7, 9, 11],
+            Mode::Mixolydian => [0, 2, 4, 5, 7, 9, 10],
+            Mode::Locrian => [0, 1, 3, 5, 6, 8, 10],
+            Mode::HarmonicMinor => [0, 2, 3, 5, 7, 8, 11],
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        if text.trim() == "M" {
+            return Some(Mode::Major);
+        }
+        let text = text.trim().to_lowercase().replace(['_', '-'], " ");
+        Some(match text.as_str() {
+            "" | "maj" | "major" | "ionian" => Mode::Major,
+            "m" | "min" | "minor" | "aeolian" | "natural minor" => Mode::Minor,
+            "dorian" => Mode::Dorian,
+            "phrygian" => Mode::Phrygian,
+            "lydian" => Mode::Lydian,
+            "mixolydian" => Mode::Mixolydian,
+            "locrian" => Mode::Locrian,
+            "harmonic minor" => Mode::HarmonicMinor,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Major => "major",
+            Mode::Minor => "minor",
+            Mode::Dorian => "dorian",
+            Mode::Phrygian => "phrygian",
+            Mode::Lydian => "lydian",
+            Mode::Mixolydian => "mixolydian",
+            Mode::Locrian => "locrian",
+            Mode::HarmonicMinor => "harmonic minor",
+        }
+    }
+}
+
+/// A tonic and the mode built on it, such as "A minor" or "D dorian".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    /// The pitch class of the tonic, where 0 is C.
+    pub tonic: u8,
+    pub mode: Mode,
+}
+
+impl Key {
+    /// Parses keys such as "C", "F#m", "Bb minor" or "D dorian".
+    pub fn parse(text: &str) -> Result<Self> {
+        let (tonic, rest) =
+            parse_pitch_class(text.trim()).with_context(|| format!("invalid key: {text}"))?;
+        let mode = Mode::parse(rest).with_context(|| format!("unknown mode in key: {text}"))?;
+        Ok(Self { tonic, mode })
+    }
+
+    /// The pitch class of a zero-based scale degree.
+    pub fn degree(&self, degree: usize) -> u8 {
+        (self.tonic + self.mode.intervals()[degree % 7]) % 12
+    }
+
+    /// The pitch class of a zero-based degree of the major scale on the same
+    /// tonic, which is what accidentals in roman numerals such as "bVII" are
+    /// relative to.
+    fn major_degree(&self, degree: usize) -> u8 {
+        (self.tonic + Mode::Major.intervals()[degree % 7]) % 12
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            NOTE_NAMES[self.tonic as usize],
+            self.mode.name()
+        )
+    }
+}
+
+/// A chord as a root pitch class and the semitones of its tones above the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chord {
+    pub root: u8,
+    pub intervals: Vec<u8>,
+}
+
+/// Parses a chord progression in the given key. Chords are separated by
+/// spaces, dashes, commas or bar lines, and can be written either as roman
+/// numerals relative to the key ("i VI III VII", "ii7 V7 Imaj7", "bVII") or
+/// as chord names ("Am F C G").
+pub fn parse_progression(text: &str, key: &Key) -> Result<Vec<Chord>> {
+    let chords = text
+        .split(|c: char| c.is_whitespace() || matches!(c, '-' | ',' | '|'))
+        .filter(|token| !token.is_empty())
+        .map(|token| parse_chord(token, key).with_context(|| format!("invalid chord: {token}")))
+        .collect::<Result<Vec<_>>>()?;
+    if chords.is_empty() {
+        return Err(anyhow!("the chord progression is empty"));
+    }
+    Ok(chords)
+}
+
+fn parse_chord(token: &str, key: &Key) -> Result<Chord> {
+    if token.starts_with(|c: char| c.is_ascii_uppercase() && c != 'I' && c != 'V') {
+        let (root, suffix) = parse_pitch_class(token).context("unknown chord root")?;
+        let intervals = parse_quality(suffix, false).context("unknown chord quality")?;
+        return Ok(Chord { root, intervals });
+    }
+
+    let (accidental, rest) = match token.chars().next() {
+        Some(c @ ('b' | '♭')) => (Some(11), &token[c.len_utf8()..]),
+        Some(c @ ('#' | '♯')) => (Some(1), &token[c.len_utf8()..]),
+        _ => (None, token),
+    };
+    let numeral_len = rest
+        .find(|c: char| !matches!(c, 'I' | 'V' | 'i' | 'v'))
+        .unwrap_or(rest.len());
+    let (numeral, suffix) = rest.split_at(numeral_len);
+    let minor = if numeral.chars().all(|c| c.is_ascii_lowercase()) {
+        true
+    } else if numeral.chars().all(|c| c.is_ascii_uppercase()) {
+        false
+    } else {
+        return Err(anyhow!("mixed case roman numeral"));
+    };
+    let degree = match numeral.to_ascii_uppercase().as_str() {
+        "I" => 0,
+        "II" => 1,
+        "III" => 2,
+        "IV" => 3,
+        "V" => 4,
+        "VI" => 5,
+        "VII" => 6,
+        _ => return Err(anyhow!("expected a roman numeral or a chord name")),
+    };
+    let intervals = parse_quality(suffix, minor).context("unknown chord quality")?;
+    let root = match accidental {
+        Some(accidental) => (key.major_degree(degree) + accidental) % 12,
+        None => key.degree(degree),
+    };
+    Ok(Chord { root, intervals })
+}
+
+fn parse_quality(suffix: &str, minor: bool) -> Option<Vec<u8>> {
+    let third = if minor { 3 } else { 4 };
+    Some(match suffix {
+        "" => vec![0, third, 7],
+        "m" | "min" => vec![0, 3, 7],
+        "7" => vec![0, third, 7, 10],
+        "m7" | "min7" => vec![0, 3, 7, 10],
+        "maj7" | "M7" | "Δ" | "Δ7" => vec![0, third, 7, 11],
+        "6" => vec![0, third, 7, 9],
+        "9" => vec![0, third, 7, 10, 14],
+        "add9" => vec![0, third, 7, 14],
+        "°" | "o" | "dim" => vec![0, 3, 6],
+        "°7" | "o7" | "dim7" => vec![0, 3, 6, 9],
+        "ø" | "ø7" | "m7b5" => vec![0, 3, 6, 10],
+        "+" | "aug" => vec![0, 4, 8],
+        "sus2" => vec![0, 2, 7],
+        "sus" | "sus4" => vec![0, 5, 7],
+        _ => return None,
+    })
+}
+
+/// Parses a note name such as "C", "f#" or "Bb" at the start of `text`,
+/// returning its pitch class and the rest of the text.
+fn parse_pitch_class(text: &str) -> Option<(u8, &str)> {
+    let mut chars = text.chars();
+    let natural = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    Some(match rest.chars().next() {
+        Some(c @ ('#' | '♯')) => ((natural + 1) % 12, &rest[c.len_utf8()..]),
+        Some(c @ ('b' | '♭')) => ((natural + 11) % 12, &rest[c.len_utf8()..]),
+        _ => (natural, rest),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        let key = Key::parse("C").unwrap();
+        assert_eq!((key.tonic, key.mode), (0, Mode::Major));
+        let key = Key::parse("F#m").unwrap();
+        assert_eq!((key.tonic, key.mode), (6, Mode::Minor));
+        let key = Key::parse("Bb harmonic minor").unwrap();
+        assert_eq!((key.tonic, key.mode), (10, Mode::HarmonicMinor));
+        assert_eq!(key.to_string(), "A# harmonic minor");
+        assert!(Key::parse("H").is_err());
+        assert!(Key::parse("C bebop").is_err());
+    }
+
+    #[test]
+    fn test_parse_progression() {
+        let key = Key::parse("C major").unwrap();
+        let chords = parse_progression("I vi-IV | V7", &key).unwrap();
+        assert_eq!(
+            chords,
+            [
+                Chord {
+                    root: 0,
+                    intervals: vec![0, 4, 7]
+                },
+                Chord {
+                    root: 9,
+                    intervals: vec![0, 3, 7]
+                },
+                Chord {
+                    root: 5,
+                    intervals: vec![0, 4, 7]
+                },
+                Chord {
+                    root: 7,
+                    intervals: vec![0, 4, 7, 10]
+                },
+            ]
+        );
+
+        let key = Key::parse("A minor").unwrap();
+        let chords = parse_progression("i bVII Dm7 E", &key).unwrap();
+        let roots = chords.iter().map(|chord| chord.root).collect::<Vec<_>>();
+        assert_eq!(roots, [9, 7, 2, 4]);
+        assert_eq!(chords[2].intervals, [0, 3, 7, 10]);
+
+        assert!(parse_progression("", &key).is_err());
+        assert!(parse_progression("I Vi", &key).is_err());
+        assert!(parse_progression("I Xmaj", &key).is_err());
+    }
+}
@@ -238,6 +238,31 @@ impl Model {
         }
     }
 
+    /// Returns whether the given model accepts `input_audio` message parts.
+    ///
+    /// Only the audio models, such as `gpt-4o-audio-preview`, take audio input in chat
+    /// completions, and none of them are built in.
+    pub fn supports_audio_input(&self) -> bool {
+        match self {
+            Self::ThreePointFiveTurbo
+            | Self::Four
+            | Self::FourTurbo
+            | Self::FourOmni
+            | Self::FourOmniMini
+            | Self::FourPointOne
+            | Self::FourPointOneMini
+            | Self::FourPointOneNano
+            | Self::Five
+            | Self::FiveMini
+            | Self::FiveNano
+            | Self::O1
+            | Self::O3
+            | Self::O3Mini
+            | Self::O4Mini => false,
+            Self::Custom { name, .. } => name.contains("-audio"),
+        }
+    }
+
     /// Returns whether the given model supports the `prompt_cache_key` parameter.
     ///
     /// If the model does not support the parameter, do not pass it up.
@@ -352,7 +377,9 @@ impl MessageContent {
                     text,
                     cache_control: _,
                 } => *self = MessageContent::Plain(text),
-                MessagePart::Image { .. } => *self = MessageContent::Multipart(vec![part]),
+                MessagePart::Image { .. } | MessagePart::InputAudio { .. } => {
+                    *self = MessageContent::Multipart(vec![part])
+                }
             },
             MessageContent::Multipart(parts) => parts.push(part),
         }
@@ -384,6 +411,8 @@ pub enum MessagePart {
     },
     #[serde(rename = "image_url")]
     Image { image_url: ImageUrl },
+    #[serde(rename = "input_audio")]
+    InputAudio { input_audio: InputAudio },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -399,6 +428,14 @@ pub struct ImageUrl {
     pub detail: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct InputAudio {
+    /// Base64-encoded audio data.
+    pub data: String,
+    /// Either `wav` or `mp3`.
+    pub format: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct ToolCall {
     pub id: String,
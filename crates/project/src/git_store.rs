@@ -12,7 +12,7 @@ use buffer_diff::{BufferDiff, BufferDiffEvent};
 use client::ProjectId;
 use collections::HashMap;
 pub use conflict_set::{ConflictRegion, ConflictSet, ConflictSetSnapshot, ConflictSetUpdate};
-use fs::Fs;
+use fs::{Fs, MTime};
 use futures::{
     FutureExt, StreamExt,
     channel::{mpsc, oneshot},
@@ -22,16 +22,20 @@ use futures::{
 use git::{
     BuildPermalinkParams, GitHostingProviderRegistry, Oid,
     blame::Blame,
+    lfs::{AudioFormat, AudioInfo, LfsDiffMetadata, LfsFileMetadata, LfsLock, LfsTransferProgress},
     parse_git_remote_url,
     repository::{
-        Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, FetchOptions,
-        GitRepository, GitRepositoryCheckpoint, PushOptions, Remote, RemoteCommandOutput, RepoPath,
-        ResetMode, UpstreamTrackingStatus,
+        BisectMark, BisectState, Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions,
+        CommitSummary, DiffType, FetchOptions, GitRepository, GitRepositoryCheckpoint, LogOptions,
+        MergeStages, PushOptions, RebaseAction, RebaseCommand, RebaseTodoEntry, Remote,
+        RemoteCommandOutput, RepoPath, ResetMode, Tag, UpstreamTrackingStatus,
     },
     stash::{GitStash, StashEntry},
     status::{
         FileStatus, GitSummary, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
     },
+    submodule::{Submodule, SubmoduleCommand, SubmoduleRange, SubmoduleStatus},
+    worktree::GitWorktree,
 };
 use gpui::{
     App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Subscription, Task,
@@ -45,7 +49,10 @@ use parking_lot::Mutex;
 use postage::stream::Stream as _;
 use rpc::{
     AnyProtoClient, TypedEnvelope,
-    proto::{self, git_reset, split_repository_update},
+    proto::{
+        self, git_bisect_mark, git_lfs_file_metadata, git_rebase_command, git_reset, git_submodule,
+        git_submodule_command, rebase_todo_entry, split_repository_update,
+    },
 };
 use serde::Deserialize;
 use std::{
@@ -59,7 +66,7 @@ use std::{
         Arc,
         atomic::{self, AtomicU64},
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 use sum_tree::{Edit, SumTree, TreeSet};
 use text::{Bias, BufferId};
@@ -75,6 +82,11 @@ use worktree::{
 };
 use zeroize::Zeroize;
 
+/// The file in the repository's git directory that Git LFS reports the
+/// progress of transfers to during a fetch or pull.
+const LFS_PROGRESS_FILE: &str = "zed-lfs-progress";
+const LFS_PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
 pub struct GitStore {
     state: GitStoreState,
     buffer_store: Entity<BufferStore>,
@@ -238,12 +250,21 @@ pub struct MergeDetails {
     pub conflicted_paths: TreeSet<RepoPath>,
     pub message: Option<SharedString>,
     pub heads: Vec<Option<SharedString>>,
+    /// Whether a rebase is in progress, stopped at a conflict or a commit to
+    /// edit.
+    pub rebasing: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RepositorySnapshot {
     pub id: RepositoryId,
     pub statuses_by_path: SumTree<StatusEntry>,
+    /// The paths with changes that are tracked by Git LFS.
+    pub lfs_paths: TreeSet<RepoPath>,
+    /// The repository's submodules, sorted by path.
+    pub submodules: Vec<Submodule>,
+    /// The repository's worktrees, starting with the main one.
+    pub worktrees: Vec<GitWorktree>,
     pub work_directory_abs_path: Arc<Path>,
     pub path_style: PathStyle,
     pub branch: Option<Branch>,
@@ -253,6 +274,46 @@ pub struct RepositorySnapshot {
     pub remote_origin_url: Option<String>,
     pub remote_upstream_url: Option<String>,
     pub stash_entries: GitStash,
+    /// For local repositories, what the submodules and worktrees were last
+    /// listed from.
+    metadata_mtimes: Option<RepositoryMetadataMtimes>,
+}
+
+/// The modification times of the files that a repository's submodules and
+/// linked worktrees are read from, so that they're only listed again, which
+/// runs git, once those change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct RepositoryMetadataMtimes {
+    gitmodules: Option<MTime>,
+    /// The administrative directory of each linked worktree in
+    /// `.git/worktrees`, along with its modification time and its `HEAD`'s.
+    linked_worktrees: Vec<(PathBuf, Option<MTime>, Option<MTime>)>,
+}
+
+impl RepositoryMetadataMtimes {
+    async fn load(fs: &dyn Fs, work_directory_abs_path: &Path, common_dir_abs_path: &Path) -> Self {
+        let gitmodules = mtime(fs, &work_directory_abs_path.join(".gitmodules")).await;
+        let mut linked_worktrees = Vec::new();
+        if let Ok(mut entries) = fs.read_dir(&common_dir_abs_path.join("worktrees")).await {
+            while let Some(entry) = entries.next().await {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                let dir_mtime = mtime(fs, &entry).await;
+                let head_mtime = mtime(fs, &entry.join("HEAD")).await;
+                linked_worktrees.push((entry, dir_mtime, head_mtime));
+            }
+        }
+        linked_worktrees.sort_by(|a, b| a.0.cmp(&b.0));
+        Self {
+            gitmodules,
+            linked_worktrees,
+        }
+    }
+}
+
+async fn mtime(fs: &dyn Fs, path: &Path) -> Option<MTime> {
+    Some(fs.metadata(path).await.ok()??.mtime)
 }
 
 type JobId = u64;
@@ -276,6 +337,7 @@ pub struct Repository {
     job_id: JobId,
     askpass_delegates: Arc<Mutex<HashMap<u64, AskPassDelegate>>>,
     latest_askpass_id: u64,
+    lfs_transfer: Option<LfsTransferProgress>,
 }
 
 impl std::ops::Deref for Repository {
@@ -291,6 +353,7 @@ pub enum RepositoryState {
     Local {
         backend: Arc<dyn GitRepository>,
         environment: Arc<HashMap<String, String>>,
+        fs: Arc<dyn Fs>,
     },
     Remote {
         project_id: ProjectId,
@@ -421,6 +484,33 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_reset);
         client.add_entity_request_handler(Self::handle_show);
         client.add_entity_request_handler(Self::handle_load_commit_diff);
+        client.add_entity_request_handler(Self::handle_diff_refs);
+        client.add_entity_request_handler(Self::handle_load_staged_diff);
+        client.add_entity_request_handler(Self::handle_merge_base);
+        client.add_entity_request_handler(Self::handle_load_merge_stages);
+        client.add_entity_request_handler(Self::handle_log);
+        client.add_entity_request_handler(Self::handle_rebase);
+        client.add_entity_request_handler(Self::handle_rebase_command);
+        client.add_entity_request_handler(Self::handle_cherry_pick);
+        client.add_entity_request_handler(Self::handle_revert);
+        client.add_entity_request_handler(Self::handle_get_tags);
+        client.add_entity_request_handler(Self::handle_create_tag);
+        client.add_entity_request_handler(Self::handle_delete_tag);
+        client.add_entity_request_handler(Self::handle_bisect_start);
+        client.add_entity_request_handler(Self::handle_bisect_mark);
+        client.add_entity_request_handler(Self::handle_bisect_reset);
+        client.add_entity_request_handler(Self::handle_get_bisect_state);
+        client.add_entity_request_handler(Self::handle_submodule_command);
+        client.add_entity_request_handler(Self::handle_get_submodule_range);
+        client.add_entity_request_handler(Self::handle_create_worktree);
+        client.add_entity_request_handler(Self::handle_remove_worktree);
+        client.add_entity_request_handler(Self::handle_prune_worktrees);
+        client.add_entity_request_handler(Self::handle_delete_branch);
+        client.add_entity_request_handler(Self::handle_merge_squash);
+        client.add_entity_request_handler(Self::handle_lfs_locks);
+        client.add_entity_request_handler(Self::handle_lfs_lock);
+        client.add_entity_request_handler(Self::handle_lfs_unlock);
+        client.add_entity_request_handler(Self::handle_lfs_metadata);
         client.add_entity_request_handler(Self::handle_checkout_files);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
         client.add_entity_request_handler(Self::handle_set_index_text);
@@ -1989,939 +2079,1467 @@ impl GitStore {
         })
     }
 
-    async fn handle_load_commit_diff(
+    async fn handle_log(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::LoadCommitDiff>,
+        envelope: TypedEnvelope<proto::GitLog>,
         mut cx: AsyncApp,
-    ) -> Result<proto::LoadCommitDiffResponse> {
+    ) -> Result<proto::GitLogResponse> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let options = log_options_from_proto(envelope.payload)?;
 
-        let commit_diff = repository_handle
+        let commits = repository_handle
             .update(&mut cx, |repository_handle, _| {
-                repository_handle.load_commit_diff(envelope.payload.commit)
+                repository_handle.log(options)
             })?
             .await??;
-        Ok(proto::LoadCommitDiffResponse {
-            files: commit_diff
-                .files
-                .into_iter()
-                .map(|file| proto::CommitFile {
-                    path: file.path.to_proto(),
-                    old_text: file.old_text,
-                    new_text: file.new_text,
-                })
-                .collect(),
+        Ok(proto::GitLogResponse {
+            commits: commits.iter().map(commit_summary_to_proto).collect(),
         })
     }
 
-    async fn handle_reset(
+    async fn handle_rebase(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GitReset>,
+        envelope: TypedEnvelope<proto::GitRebase>,
         mut cx: AsyncApp,
     ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let todo = envelope
+            .payload
+            .todo
+            .iter()
+            .map(proto_to_rebase_todo_entry)
+            .collect();
 
-        let mode = match envelope.payload.mode() {
-            git_reset::ResetMode::Soft => ResetMode::Soft,
-            git_reset::ResetMode::Mixed => ResetMode::Mixed,
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_interactive(envelope.payload.base, todo)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_rebase_command(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseCommand>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let command = match envelope.payload.command() {
+            git_rebase_command::Command::Continue => RebaseCommand::Continue,
+            git_rebase_command::Command::Skip => RebaseCommand::Skip,
+            git_rebase_command::Command::Abort => RebaseCommand::Abort,
         };
 
         repository_handle
-            .update(&mut cx, |repository_handle, cx| {
-                repository_handle.reset(envelope.payload.commit, mode, cx)
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_command(command)
             })?
             .await??;
         Ok(proto::Ack {})
     }
 
-    async fn handle_checkout_files(
+    async fn handle_cherry_pick(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GitCheckoutFiles>,
+        envelope: TypedEnvelope<proto::GitCherryPick>,
         mut cx: AsyncApp,
     ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
-        let paths = envelope
-            .payload
-            .paths
-            .iter()
-            .map(|s| RepoPath::from_proto(s))
-            .collect::<Result<Vec<_>>>()?;
 
         repository_handle
-            .update(&mut cx, |repository_handle, cx| {
-                repository_handle.checkout_files(&envelope.payload.commit, paths, cx)
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.cherry_pick(envelope.payload.commit)
             })?
             .await??;
         Ok(proto::Ack {})
     }
 
-    async fn handle_open_commit_message_buffer(
+    async fn handle_revert(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::OpenCommitMessageBuffer>,
+        envelope: TypedEnvelope<proto::GitRevert>,
         mut cx: AsyncApp,
-    ) -> Result<proto::OpenBufferResponse> {
+    ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
-        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
-        let buffer = repository
-            .update(&mut cx, |repository, cx| {
-                repository.open_commit_buffer(None, this.read(cx).buffer_store.clone(), cx)
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.revert(envelope.payload.commit)
             })?
-            .await?;
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-        let buffer_id = buffer.read_with(&cx, |buffer, _| buffer.remote_id())?;
-        this.update(&mut cx, |this, cx| {
-            this.buffer_store.update(cx, |buffer_store, cx| {
-                buffer_store
-                    .create_buffer_for_peer(
-                        &buffer,
-                        envelope.original_sender_id.unwrap_or(envelope.sender_id),
-                        cx,
-                    )
-                    .detach_and_log_err(cx);
-            })
-        })?;
+    async fn handle_get_tags(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitGetTags>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitTagsResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        Ok(proto::OpenBufferResponse {
-            buffer_id: buffer_id.to_proto(),
+        let tags = repository_handle
+            .update(&mut cx, |repository_handle, _| repository_handle.tags())?
+            .await??;
+        Ok(proto::GitTagsResponse {
+            tags: tags.iter().map(tag_to_proto).collect(),
         })
     }
 
-    async fn handle_askpass(
+    async fn handle_create_tag(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::AskPassRequest>,
+        envelope: TypedEnvelope<proto::GitCreateTag>,
         mut cx: AsyncApp,
-    ) -> Result<proto::AskPassResponse> {
+    ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
-        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let delegates = cx.update(|cx| repository.read(cx).askpass_delegates.clone())?;
-        let Some(mut askpass) = delegates.lock().remove(&envelope.payload.askpass_id) else {
-            debug_panic!("no askpass found");
-            anyhow::bail!("no askpass found");
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.create_tag(
+                    envelope.payload.name,
+                    envelope.payload.target,
+                    envelope.payload.message,
+                )
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_delete_tag(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitDeleteTag>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.delete_tag(envelope.payload.name)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_bisect_start(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectStart>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_start(envelope.payload.bad, envelope.payload.good)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_bisect_mark(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectMark>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let mark = match envelope.payload.mark() {
+            git_bisect_mark::Mark::Good => BisectMark::Good,
+            git_bisect_mark::Mark::Bad => BisectMark::Bad,
+            git_bisect_mark::Mark::Skip => BisectMark::Skip,
         };
 
-        let response = askpass
-            .ask_password(envelope.payload.prompt)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("askpass cancelled"))?;
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_mark(mark)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-        delegates
-            .lock()
-            .insert(envelope.payload.askpass_id, askpass);
+    async fn handle_bisect_reset(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectReset>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        // In fact, we don't quite know what we're doing here, as we're sending askpass password unencrypted, but..
-        Ok(proto::AskPassResponse {
-            response: response.decrypt(IKnowWhatIAmDoingAndIHaveReadTheDocs)?,
-        })
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_reset()
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_check_for_pushed_commits(
+    async fn handle_get_bisect_state(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::CheckForPushedCommits>,
+        envelope: TypedEnvelope<proto::GitGetBisectState>,
         mut cx: AsyncApp,
-    ) -> Result<proto::CheckForPushedCommitsResponse> {
+    ) -> Result<proto::GitBisectStateResponse> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let branches = repository_handle
+        let state = repository_handle
             .update(&mut cx, |repository_handle, _| {
-                repository_handle.check_for_pushed_commits()
+                repository_handle.bisect_state()
             })?
             .await??;
-        Ok(proto::CheckForPushedCommitsResponse {
-            pushed_to: branches
-                .into_iter()
-                .map(|commit| commit.to_string())
-                .collect(),
+        Ok(proto::GitBisectStateResponse {
+            state: state.as_ref().map(bisect_state_to_proto),
         })
     }
 
-    async fn handle_git_diff(
+    async fn handle_submodule_command(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GitDiff>,
+        envelope: TypedEnvelope<proto::GitSubmoduleCommand>,
         mut cx: AsyncApp,
-    ) -> Result<proto::GitDiffResponse> {
+    ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
-        let diff_type = match envelope.payload.diff_type() {
-            proto::git_diff::DiffType::HeadToIndex => DiffType::HeadToIndex,
-            proto::git_diff::DiffType::HeadToWorktree => DiffType::HeadToWorktree,
+        let command = match envelope.payload.command() {
+            git_submodule_command::Command::Init => SubmoduleCommand::Init,
+            git_submodule_command::Command::Update => SubmoduleCommand::Update,
+            git_submodule_command::Command::Sync => SubmoduleCommand::Sync,
         };
+        let paths = envelope
+            .payload
+            .paths
+            .iter()
+            .map(|path| RepoPath::from_proto(path))
+            .collect::<Result<Vec<_>>>()?;
 
-        let mut diff = repository_handle
-            .update(&mut cx, |repository_handle, cx| {
-                repository_handle.diff(diff_type, cx)
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.submodule_command(command, paths)
             })?
             .await??;
-        const ONE_MB: usize = 1_000_000;
-        if diff.len() > ONE_MB {
-            diff = diff.chars().take(ONE_MB).collect()
-        }
-
-        Ok(proto::GitDiffResponse { diff })
+        Ok(proto::Ack {})
     }
 
-    async fn handle_open_unstaged_diff(
+    async fn handle_get_submodule_range(
         this: Entity<Self>,
-        request: TypedEnvelope<proto::OpenUnstagedDiff>,
+        envelope: TypedEnvelope<proto::GitGetSubmoduleRange>,
         mut cx: AsyncApp,
-    ) -> Result<proto::OpenUnstagedDiffResponse> {
-        let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        let diff = this
-            .update(&mut cx, |this, cx| {
-                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
-                Some(this.open_unstaged_diff(buffer, cx))
+    ) -> Result<proto::GitSubmoduleRangeResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = RepoPath::from_proto(&envelope.payload.path)?;
+
+        let range = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.submodule_range(path)
             })?
-            .context("missing buffer")?
-            .await?;
-        this.update(&mut cx, |this, _| {
-            let shared_diffs = this
-                .shared_diffs
-                .entry(request.original_sender_id.unwrap_or(request.sender_id))
-                .or_default();
-            shared_diffs.entry(buffer_id).or_default().unstaged = Some(diff.clone());
-        })?;
-        let staged_text = diff.read_with(&cx, |diff, _| diff.base_text_string())?;
-        Ok(proto::OpenUnstagedDiffResponse { staged_text })
+            .await??;
+        Ok(proto::GitSubmoduleRangeResponse {
+            old: range.old.map(|sha| sha.to_string()),
+            new: range.new.map(|sha| sha.to_string()),
+            added: range.added.iter().map(commit_summary_to_proto).collect(),
+            removed: range.removed.iter().map(commit_summary_to_proto).collect(),
+        })
     }
 
-    async fn handle_open_uncommitted_diff(
+    async fn handle_create_worktree(
         this: Entity<Self>,
-        request: TypedEnvelope<proto::OpenUncommittedDiff>,
+        envelope: TypedEnvelope<proto::GitCreateWorktree>,
         mut cx: AsyncApp,
-    ) -> Result<proto::OpenUncommittedDiffResponse> {
-        let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        let diff = this
-            .update(&mut cx, |this, cx| {
-                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
-                Some(this.open_uncommitted_diff(buffer, cx))
-            })?
-            .context("missing buffer")?
-            .await?;
-        this.update(&mut cx, |this, _| {
-            let shared_diffs = this
-                .shared_diffs
-                .entry(request.original_sender_id.unwrap_or(request.sender_id))
-                .or_default();
-            shared_diffs.entry(buffer_id).or_default().uncommitted = Some(diff.clone());
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = PathBuf::from(envelope.payload.path);
+        // Guests may only add worktrees next to the repository, rather than
+        // anywhere on the host.
+        let parent_dir = repository_handle.read_with(&cx, |repository_handle, _| {
+            repository_handle
+                .work_directory_abs_path
+                .parent()
+                .map(Path::to_path_buf)
         })?;
-        diff.read_with(&cx, |diff, cx| {
-            use proto::open_uncommitted_diff_response::Mode;
+        let is_next_to_repository = parent_dir.is_some_and(|parent_dir| {
+            path.starts_with(&parent_dir)
+                && path != parent_dir
+                && !path
+                    .components()
+                    .any(|component| component == std::path::Component::ParentDir)
+        });
+        anyhow::ensure!(
+            is_next_to_repository,
+            "worktree path {path:?} is outside the repository's parent directory"
+        );
 
-            let unstaged_diff = diff.secondary_diff();
-            let index_snapshot = unstaged_diff.and_then(|diff| {
-                let diff = diff.read(cx);
-                diff.base_text_exists().then(|| diff.base_text())
-            });
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.create_worktree(
+                    envelope.payload.branch,
+                    path,
+                    envelope.payload.base,
+                )
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-            let mode;
-            let staged_text;
-            let committed_text;
-            if diff.base_text_exists() {
-                let committed_snapshot = diff.base_text();
-                committed_text = Some(committed_snapshot.text());
-                if let Some(index_text) = index_snapshot {
-                    if index_text.remote_id() == committed_snapshot.remote_id() {
-                        mode = Mode::IndexMatchesHead;
-                        staged_text = None;
-                    } else {
-                        mode = Mode::IndexAndHead;
-                        staged_text = Some(index_text.text());
-                    }
-                } else {
-                    mode = Mode::IndexAndHead;
-                    staged_text = None;
-                }
-            } else {
-                mode = Mode::IndexAndHead;
-                committed_text = None;
-                staged_text = index_snapshot.as_ref().map(|buffer| buffer.text());
-            }
+    async fn handle_remove_worktree(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRemoveWorktree>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = PathBuf::from(envelope.payload.path);
 
-            proto::OpenUncommittedDiffResponse {
-                committed_text,
-                staged_text,
-                mode: mode.into(),
-            }
-        })
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.remove_worktree(path, envelope.payload.force)
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_update_diff_bases(
+    async fn handle_prune_worktrees(
         this: Entity<Self>,
-        request: TypedEnvelope<proto::UpdateDiffBases>,
+        envelope: TypedEnvelope<proto::GitPruneWorktrees>,
         mut cx: AsyncApp,
-    ) -> Result<()> {
-        let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        this.update(&mut cx, |this, cx| {
-            if let Some(diff_state) = this.diffs.get_mut(&buffer_id)
-                && let Some(buffer) = this.buffer_store.read(cx).get(buffer_id)
-            {
-                let buffer = buffer.read(cx).text_snapshot();
-                diff_state.update(cx, |diff_state, cx| {
-                    diff_state.handle_base_texts_updated(buffer, request.payload, cx);
-                })
-            }
-        })
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.prune_worktrees()
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_blame_buffer(
+    async fn handle_delete_branch(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::BlameBuffer>,
+        envelope: TypedEnvelope<proto::GitDeleteBranch>,
         mut cx: AsyncApp,
-    ) -> Result<proto::BlameBufferResponse> {
-        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
-        let version = deserialize_version(&envelope.payload.version);
-        let buffer = this.read_with(&cx, |this, cx| {
-            this.buffer_store.read(cx).get_existing(buffer_id)
-        })??;
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(version.clone())
-            })?
-            .await?;
-        let blame = this
-            .update(&mut cx, |this, cx| {
-                this.blame_buffer(&buffer, Some(version), cx)
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.delete_branch(envelope.payload.branch, envelope.payload.force)
             })?
-            .await?;
-        Ok(serialize_blame_buffer_response(blame))
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_get_permalink_to_line(
+    async fn handle_merge_squash(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GetPermalinkToLine>,
+        envelope: TypedEnvelope<proto::GitMergeSquash>,
         mut cx: AsyncApp,
-    ) -> Result<proto::GetPermalinkToLineResponse> {
-        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
-        // let version = deserialize_version(&envelope.payload.version);
-        let selection = {
-            let proto_selection = envelope
-                .payload
-                .selection
-                .context("no selection to get permalink for defined")?;
-            proto_selection.start as u32..proto_selection.end as u32
-        };
-        let buffer = this.read_with(&cx, |this, cx| {
-            this.buffer_store.read(cx).get_existing(buffer_id)
-        })??;
-        let permalink = this
-            .update(&mut cx, |this, cx| {
-                this.get_permalink_to_line(&buffer, selection, cx)
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.merge_squash(envelope.payload.branch)
             })?
-            .await?;
-        Ok(proto::GetPermalinkToLineResponse {
-            permalink: permalink.to_string(),
-        })
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    fn repository_for_request(
-        this: &Entity<Self>,
-        id: RepositoryId,
-        cx: &mut AsyncApp,
-    ) -> Result<Entity<Repository>> {
-        this.read_with(cx, |this, _| {
-            this.repositories
-                .get(&id)
-                .context("missing repository handle")
-                .cloned()
-        })?
-    }
+    async fn handle_lfs_locks(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLfsLocks>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitLfsLocksResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-    pub fn repo_snapshots(&self, cx: &App) -> HashMap<RepositoryId, RepositorySnapshot> {
-        self.repositories
-            .iter()
-            .map(|(id, repo)| (*id, repo.read(cx).snapshot.clone()))
-            .collect()
+        let locks = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.lfs_locks()
+            })?
+            .await??;
+        Ok(proto::GitLfsLocksResponse {
+            locks: locks.iter().map(lfs_lock_to_proto).collect(),
+        })
     }
 
-    fn process_updated_entries(
-        &self,
-        worktree: &Entity<Worktree>,
-        updated_entries: &[(Arc<RelPath>, ProjectEntryId, PathChange)],
-        cx: &mut App,
-    ) -> Task<HashMap<Entity<Repository>, Vec<RepoPath>>> {
-        let path_style = worktree.read(cx).path_style();
-        let mut repo_paths = self
-            .repositories
-            .values()
-            .map(|repo| (repo.read(cx).work_directory_abs_path.clone(), repo.clone()))
-            .collect::<Vec<_>>();
-        let mut entries: Vec<_> = updated_entries
-            .iter()
-            .map(|(path, _, _)| path.clone())
-            .collect();
-        entries.sort();
-        let worktree = worktree.read(cx);
+    async fn handle_lfs_lock(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLfsLockPath>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = RepoPath::from_proto(&envelope.payload.path)?;
 
-        let entries = entries
-            .into_iter()
-            .map(|path| worktree.absolutize(&path))
-            .collect::<Arc<[_]>>();
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.lfs_lock(path)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-        let executor = cx.background_executor().clone();
-        cx.background_executor().spawn(async move {
-            repo_paths.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
-            let mut paths_by_git_repo = HashMap::<_, Vec<_>>::default();
-            let mut tasks = FuturesOrdered::new();
-            for (repo_path, repo) in repo_paths.into_iter().rev() {
-                let entries = entries.clone();
-                let task = executor.spawn(async move {
-                    // Find all repository paths that belong to this repo
-                    let mut ix = entries.partition_point(|path| path < &*repo_path);
-                    if ix == entries.len() {
-                        return None;
-                    };
+    async fn handle_lfs_unlock(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLfsUnlockPath>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = RepoPath::from_proto(&envelope.payload.path)?;
 
-                    let mut paths = Vec::new();
-                    // All paths prefixed by a given repo will constitute a continuous range.
-                    while let Some(path) = entries.get(ix)
-                        && let Some(repo_path) = RepositorySnapshot::abs_path_to_repo_path_inner(
-                            &repo_path, path, path_style,
-                        )
-                    {
-                        paths.push((repo_path, ix));
-                        ix += 1;
-                    }
-                    if paths.is_empty() {
-                        None
-                    } else {
-                        Some((repo, paths))
-                    }
-                });
-                tasks.push_back(task);
-            }
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.lfs_unlock(path, envelope.payload.force)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-            // Now, let's filter out the "duplicate" entries that were processed by multiple distinct repos.
-            let mut path_was_used = vec![false; entries.len()];
-            let tasks = tasks.collect::<Vec<_>>().await;
-            // Process tasks from the back: iterating backwards allows us to see more-specific paths first.
-            // We always want to assign a path to it's innermost repository.
-            for t in tasks {
-                let Some((repo, paths)) = t else {
-                    continue;
-                };
-                let entry = paths_by_git_repo.entry(repo).or_default();
-                for (repo_path, ix) in paths {
-                    if path_was_used[ix] {
-                        continue;
-                    }
-                    path_was_used[ix] = true;
-                    entry.push(repo_path);
-                }
-            }
+    async fn handle_lfs_metadata(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLfsMetadata>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitLfsMetadataResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = RepoPath::from_proto(&envelope.payload.path)?;
 
-            paths_by_git_repo
+        let metadata = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.lfs_metadata(path)
+            })?
+            .await??;
+        Ok(proto::GitLfsMetadataResponse {
+            head: metadata.head.as_ref().map(lfs_file_metadata_to_proto),
+            worktree: metadata.worktree.as_ref().map(lfs_file_metadata_to_proto),
         })
     }
-}
 
-impl BufferGitState {
-    fn new(_git_store: WeakEntity<GitStore>) -> Self {
-        Self {
-            unstaged_diff: Default::default(),
-            uncommitted_diff: Default::default(),
-            recalculate_diff_task: Default::default(),
-            language: Default::default(),
-            language_registry: Default::default(),
-            recalculating_tx: postage::watch::channel_with(false).0,
-            hunk_staging_operation_count: 0,
-            hunk_staging_operation_count_as_of_write: 0,
-            head_text: Default::default(),
-            index_text: Default::default(),
-            head_changed: Default::default(),
-            index_changed: Default::default(),
-            language_changed: Default::default(),
-            conflict_updated_futures: Default::default(),
-            conflict_set: Default::default(),
-            reparse_conflict_markers_task: Default::default(),
-        }
-    }
+    async fn handle_load_commit_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::LoadCommitDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadCommitDiffResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-    fn buffer_language_changed(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
-        self.language = buffer.read(cx).language().cloned();
-        self.language_changed = true;
-        let _ = self.recalculate_diffs(buffer.read(cx).text_snapshot(), cx);
+        let commit_diff = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.load_commit_diff(envelope.payload.commit)
+            })?
+            .await??;
+        Ok(commit_diff_to_proto(commit_diff))
     }
 
-    fn reparse_conflict_markers(
-        &mut self,
-        buffer: text::BufferSnapshot,
-        cx: &mut Context<Self>,
-    ) -> oneshot::Receiver<()> {
-        let (tx, rx) = oneshot::channel();
-
-        let Some(conflict_set) = self
-            .conflict_set
-            .as_ref()
-            .and_then(|conflict_set| conflict_set.upgrade())
-        else {
-            return rx;
-        };
+    async fn handle_diff_refs(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitDiffRefs>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadCommitDiffResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let old_snapshot = conflict_set.read_with(cx, |conflict_set, _| {
-            if conflict_set.has_conflict {
-                Some(conflict_set.snapshot())
-            } else {
-                None
-            }
-        });
+        let diff = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.diff_refs(envelope.payload.base, envelope.payload.head)
+            })?
+            .await??;
+        Ok(commit_diff_to_proto(diff))
+    }
 
-        if let Some(old_snapshot) = old_snapshot {
-            self.conflict_updated_futures.push(tx);
-            self.reparse_conflict_markers_task = Some(cx.spawn(async move |this, cx| {
-                let (snapshot, changed_range) = cx
-                    .background_spawn(async move {
-                        let new_snapshot = ConflictSet::parse(&buffer);
-                        let changed_range = old_snapshot.compare(&new_snapshot, &buffer);
-                        (new_snapshot, changed_range)
-                    })
-                    .await;
-                this.update(cx, |this, cx| {
-                    if let Some(conflict_set) = &this.conflict_set {
-                        conflict_set
-                            .update(cx, |conflict_set, cx| {
-                                conflict_set.set_snapshot(snapshot, changed_range, cx);
-                            })
-                            .ok();
-                    }
-                    let futures = std::mem::take(&mut this.conflict_updated_futures);
-                    for tx in futures {
-                        tx.send(()).ok();
-                    }
-                })
-            }))
-        }
+    async fn handle_load_staged_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLoadStagedDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadCommitDiffResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        rx
+        let diff = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.load_staged_diff()
+            })?
+            .await??;
+        Ok(commit_diff_to_proto(diff))
     }
 
-    fn unstaged_diff(&self) -> Option<Entity<BufferDiff>> {
-        self.unstaged_diff.as_ref().and_then(|set| set.upgrade())
-    }
+    async fn handle_merge_base(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitMergeBase>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitMergeBaseResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-    fn uncommitted_diff(&self) -> Option<Entity<BufferDiff>> {
-        self.uncommitted_diff.as_ref().and_then(|set| set.upgrade())
+        let sha = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.merge_base(envelope.payload.first, envelope.payload.second)
+            })?
+            .await??;
+        Ok(proto::GitMergeBaseResponse { sha })
     }
 
-    fn handle_base_texts_updated(
-        &mut self,
-        buffer: text::BufferSnapshot,
-        message: proto::UpdateDiffBases,
-        cx: &mut Context<Self>,
-    ) {
-        use proto::update_diff_bases::Mode;
+    async fn handle_load_merge_stages(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLoadMergeStages>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitMergeStagesResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = RepoPath::from_proto(&envelope.payload.path)?;
 
-        let Some(mode) = Mode::from_i32(message.mode) else {
-            return;
-        };
+        let stages = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.load_merge_stages(path)
+            })?
+            .await??;
+        Ok(proto::GitMergeStagesResponse {
+            base: stages.base,
+            ours: stages.ours,
+            theirs: stages.theirs,
+        })
+    }
 
-        let diff_bases_change = match mode {
-            Mode::HeadOnly => DiffBasesChange::SetHead(message.committed_text),
-            Mode::IndexOnly => DiffBasesChange::SetIndex(message.staged_text),
-            Mode::IndexMatchesHead => DiffBasesChange::SetBoth(message.committed_text),
-            Mode::IndexAndHead => DiffBasesChange::SetEach {
-                index: message.staged_text,
-                head: message.committed_text,
-            },
-        };
+    async fn handle_reset(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitReset>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        self.diff_bases_changed(buffer, Some(diff_bases_change), cx);
-    }
+        let mode = match envelope.payload.mode() {
+            git_reset::ResetMode::Soft => ResetMode::Soft,
+            git_reset::ResetMode::Mixed => ResetMode::Mixed,
+        };
 
-    pub fn wait_for_recalculation(&mut self) -> Option<impl Future<Output = ()> + use<>> {
-        if *self.recalculating_tx.borrow() {
-            let mut rx = self.recalculating_tx.subscribe();
-            Some(async move {
-                loop {
-                    let is_recalculating = rx.recv().await;
-                    if is_recalculating != Some(true) {
-                        break;
-                    }
-                }
-            })
-        } else {
-            None
-        }
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.reset(envelope.payload.commit, mode, cx)
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    fn diff_bases_changed(
-        &mut self,
-        buffer: text::BufferSnapshot,
-        diff_bases_change: Option<DiffBasesChange>,
-        cx: &mut Context<Self>,
-    ) {
-        match diff_bases_change {
-            Some(DiffBasesChange::SetIndex(index)) => {
-                self.index_text = index.map(|mut index| {
-                    text::LineEnding::normalize(&mut index);
-                    Arc::new(index)
-                });
-                self.index_changed = true;
-            }
-            Some(DiffBasesChange::SetHead(head)) => {
-                self.head_text = head.map(|mut head| {
-                    text::LineEnding::normalize(&mut head);
-                    Arc::new(head)
-                });
-                self.head_changed = true;
-            }
-            Some(DiffBasesChange::SetBoth(text)) => {
-                let text = text.map(|mut text| {
-                    text::LineEnding::normalize(&mut text);
-                    Arc::new(text)
-                });
-                self.head_text = text.clone();
-                self.index_text = text;
-                self.head_changed = true;
-                self.index_changed = true;
-            }
-            Some(DiffBasesChange::SetEach { index, head }) => {
-                self.index_text = index.map(|mut index| {
-                    text::LineEnding::normalize(&mut index);
-                    Arc::new(index)
-                });
-                self.index_changed = true;
-                self.head_text = head.map(|mut head| {
-                    text::LineEnding::normalize(&mut head);
-                    Arc::new(head)
-                });
-                self.head_changed = true;
-            }
-            None => {}
-        }
+    async fn handle_checkout_files(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCheckoutFiles>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let paths = envelope
+            .payload
+            .paths
+            .iter()
+            .map(|s| RepoPath::from_proto(s))
+            .collect::<Result<Vec<_>>>()?;
 
-        self.recalculate_diffs(buffer, cx)
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.checkout_files(&envelope.payload.commit, paths, cx)
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    fn recalculate_diffs(&mut self, buffer: text::BufferSnapshot, cx: &mut Context<Self>) {
-        *self.recalculating_tx.borrow_mut() = true;
+    async fn handle_open_commit_message_buffer(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::OpenCommitMessageBuffer>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::OpenBufferResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let buffer = repository
+            .update(&mut cx, |repository, cx| {
+                repository.open_commit_buffer(None, this.read(cx).buffer_store.clone(), cx)
+            })?
+            .await?;
 
-        let language = self.language.clone();
-        let language_registry = self.language_registry.clone();
-        let unstaged_diff = self.unstaged_diff();
-        let uncommitted_diff = self.uncommitted_diff();
-        let head = self.head_text.clone();
-        let index = self.index_text.clone();
-        let index_changed = self.index_changed;
-        let head_changed = self.head_changed;
-        let language_changed = self.language_changed;
-        let prev_hunk_staging_operation_count = self.hunk_staging_operation_count_as_of_write;
-        let index_matches_head = match (self.index_text.as_ref(), self.head_text.as_ref()) {
-            (Some(index), Some(head)) => Arc::ptr_eq(index, head),
-            (None, None) => true,
-            _ => false,
-        };
-        self.recalculate_diff_task = Some(cx.spawn(async move |this, cx| {
-            log::debug!(
-                "start recalculating diffs for buffer {}",
-                buffer.remote_id()
-            );
-
-            let mut new_unstaged_diff = None;
-            if let Some(unstaged_diff) = &unstaged_diff {
-                new_unstaged_diff = Some(
-                    BufferDiff::update_diff(
-                        unstaged_diff.clone(),
-                        buffer.clone(),
-                        index,
-                        index_changed,
-                        language_changed,
-                        language.clone(),
-                        language_registry.clone(),
+        let buffer_id = buffer.read_with(&cx, |buffer, _| buffer.remote_id())?;
+        this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                buffer_store
+                    .create_buffer_for_peer(
+                        &buffer,
+                        envelope.original_sender_id.unwrap_or(envelope.sender_id),
                         cx,
                     )
-                    .await?,
-                );
-            }
-
-            let mut new_uncommitted_diff = None;
-            if let Some(uncommitted_diff) = &uncommitted_diff {
-                new_uncommitted_diff = if index_matches_head {
-                    new_unstaged_diff.clone()
-                } else {
-                    Some(
-                        BufferDiff::update_diff(
-                            uncommitted_diff.clone(),
-                            buffer.clone(),
-                            head,
-                            head_changed,
-                            language_changed,
-                            language.clone(),
-                            language_registry.clone(),
-                            cx,
-                        )
-                        .await?,
-                    )
-                }
-            }
+                    .detach_and_log_err(cx);
+            })
+        })?;
 
-            let cancel = this.update(cx, |this, _| {
-                // This checks whether all pending stage/unstage operations
-                // have quiesced (i.e. both the corresponding write and the
-                // read of that write have completed). If not, then we cancel
-                // this recalculation attempt to avoid invalidating pending
-                // state too quickly; another recalculation will come along
-                // later and clear the pending state once the state of the index has settled.
-                if this.hunk_staging_operation_count > prev_hunk_staging_operation_count {
-                    *this.recalculating_tx.borrow_mut() = false;
-                    true
-                } else {
-                    false
-                }
-            })?;
-            if cancel {
-                log::debug!(
-                    concat!(
-                        "aborting recalculating diffs for buffer {}",
-                        "due to subsequent hunk operations",
-                    ),
-                    buffer.remote_id()
-                );
-                return Ok(());
-            }
+        Ok(proto::OpenBufferResponse {
+            buffer_id: buffer_id.to_proto(),
+        })
+    }
 
-            let unstaged_changed_range = if let Some((unstaged_diff, new_unstaged_diff)) =
-                unstaged_diff.as_ref().zip(new_unstaged_diff.clone())
-            {
-                unstaged_diff.update(cx, |diff, cx| {
-                    if language_changed {
-                        diff.language_changed(cx);
-                    }
-                    diff.set_snapshot(new_unstaged_diff, &buffer, cx)
-                })?
-            } else {
-                None
-            };
+    async fn handle_askpass(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::AskPassRequest>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::AskPassResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-            if let Some((uncommitted_diff, new_uncommitted_diff)) =
-                uncommitted_diff.as_ref().zip(new_uncommitted_diff.clone())
-            {
-                uncommitted_diff.update(cx, |diff, cx| {
-                    if language_changed {
-                        diff.language_changed(cx);
-                    }
-                    diff.set_snapshot_with_secondary(
-                        new_uncommitted_diff,
-                        &buffer,
-                        unstaged_changed_range,
-                        true,
-                        cx,
-                    );
-                })?;
-            }
+        let delegates = cx.update(|cx| repository.read(cx).askpass_delegates.clone())?;
+        let Some(mut askpass) = delegates.lock().remove(&envelope.payload.askpass_id) else {
+            debug_panic!("no askpass found");
+            anyhow::bail!("no askpass found");
+        };
 
-            log::debug!(
-                "finished recalculating diffs for buffer {}",
-                buffer.remote_id()
-            );
+        let response = askpass
+            .ask_password(envelope.payload.prompt)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("askpass cancelled"))?;
 
-            if let Some(this) = this.upgrade() {
-                this.update(cx, |this, _| {
-                    this.index_changed = false;
-                    this.head_changed = false;
-                    this.language_changed = false;
-                    *this.recalculating_tx.borrow_mut() = false;
-                })?;
-            }
+        delegates
+            .lock()
+            .insert(envelope.payload.askpass_id, askpass);
 
-            Ok(())
-        }));
+        // In fact, we don't quite know what we're doing here, as we're sending askpass password unencrypted, but..
+        Ok(proto::AskPassResponse {
+            response: response.decrypt(IKnowWhatIAmDoingAndIHaveReadTheDocs)?,
+        })
     }
-}
 
-fn make_remote_delegate(
-    this: Entity<GitStore>,
-    project_id: u64,
-    repository_id: RepositoryId,
-    askpass_id: u64,
-    cx: &mut AsyncApp,
-) -> AskPassDelegate {
-    AskPassDelegate::new(cx, move |prompt, tx, cx| {
-        this.update(cx, |this, cx| {
-            let Some((client, _)) = this.downstream_client() else {
-                return;
-            };
-            let response = client.request(proto::AskPassRequest {
-                project_id,
-                repository_id: repository_id.to_proto(),
-                askpass_id,
-                prompt,
-            });
-            cx.spawn(async move |_, _| {
-                let mut response = response.await?.response;
-                tx.send(EncryptedPassword::try_from(response.as_ref())?)
-                    .ok();
-                response.zeroize();
-                anyhow::Ok(())
-            })
-            .detach_and_log_err(cx);
-        })
-        .log_err();
-    })
-}
+    async fn handle_check_for_pushed_commits(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::CheckForPushedCommits>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::CheckForPushedCommitsResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-impl RepositoryId {
-    pub fn to_proto(self) -> u64 {
-        self.0
+        let branches = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.check_for_pushed_commits()
+            })?
+            .await??;
+        Ok(proto::CheckForPushedCommitsResponse {
+            pushed_to: branches
+                .into_iter()
+                .map(|commit| commit.to_string())
+                .collect(),
+        })
     }
 
-    pub fn from_proto(id: u64) -> Self {
-        RepositoryId(id)
-    }
-}
+    async fn handle_git_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitDiffResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let diff_type = match envelope.payload.diff_type() {
+            proto::git_diff::DiffType::HeadToIndex => DiffType::HeadToIndex,
+            proto::git_diff::DiffType::HeadToWorktree => DiffType::HeadToWorktree,
+        };
 
-impl RepositorySnapshot {
-    fn empty(id: RepositoryId, work_directory_abs_path: Arc<Path>, path_style: PathStyle) -> Self {
-        Self {
-            id,
-            statuses_by_path: Default::default(),
-            work_directory_abs_path,
-            branch: None,
-            head_commit: None,
-            scan_id: 0,
-            merge: Default::default(),
-            remote_origin_url: None,
-            remote_upstream_url: None,
-            stash_entries: Default::default(),
-            path_style,
+        let mut diff = repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.diff(diff_type, cx)
+            })?
+            .await??;
+        const ONE_MB: usize = 1_000_000;
+        if diff.len() > ONE_MB {
+            diff = diff.chars().take(ONE_MB).collect()
         }
+
+        Ok(proto::GitDiffResponse { diff })
     }
 
-    fn initial_update(&self, project_id: u64) -> proto::UpdateRepository {
-        proto::UpdateRepository {
-            branch_summary: self.branch.as_ref().map(branch_to_proto),
-            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
-            updated_statuses: self
-                .statuses_by_path
-                .iter()
-                .map(|entry| entry.to_proto())
-                .collect(),
-            removed_statuses: Default::default(),
-            current_merge_conflicts: self
-                .merge
-                .conflicted_paths
-                .iter()
-                .map(|repo_path| repo_path.to_proto())
-                .collect(),
-            merge_message: self.merge.message.as_ref().map(|msg| msg.to_string()),
-            project_id,
-            id: self.id.to_proto(),
-            abs_path: self.work_directory_abs_path.to_string_lossy().into_owned(),
-            entry_ids: vec![self.id.to_proto()],
-            scan_id: self.scan_id,
-            is_last_update: true,
-            stash_entries: self
-                .stash_entries
-                .entries
-                .iter()
-                .map(stash_to_proto)
-                .collect(),
-        }
+    async fn handle_open_unstaged_diff(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::OpenUnstagedDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::OpenUnstagedDiffResponse> {
+        let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        let diff = this
+            .update(&mut cx, |this, cx| {
+                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
+                Some(this.open_unstaged_diff(buffer, cx))
+            })?
+            .context("missing buffer")?
+            .await?;
+        this.update(&mut cx, |this, _| {
+            let shared_diffs = this
+                .shared_diffs
+                .entry(request.original_sender_id.unwrap_or(request.sender_id))
+                .or_default();
+            shared_diffs.entry(buffer_id).or_default().unstaged = Some(diff.clone());
+        })?;
+        let staged_text = diff.read_with(&cx, |diff, _| diff.base_text_string())?;
+        Ok(proto::OpenUnstagedDiffResponse { staged_text })
     }
 
-    fn build_update(&self, old: &Self, project_id: u64) -> proto::UpdateRepository {
-        let mut updated_statuses: Vec<proto::StatusEntry> = Vec::new();
-        let mut removed_statuses: Vec<String> = Vec::new();
+    async fn handle_open_uncommitted_diff(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::OpenUncommittedDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::OpenUncommittedDiffResponse> {
+        let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        let diff = this
+            .update(&mut cx, |this, cx| {
+                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
+                Some(this.open_uncommitted_diff(buffer, cx))
+            })?
+            .context("missing buffer")?
+            .await?;
+        this.update(&mut cx, |this, _| {
+            let shared_diffs = this
+                .shared_diffs
+                .entry(request.original_sender_id.unwrap_or(request.sender_id))
+                .or_default();
+            shared_diffs.entry(buffer_id).or_default().uncommitted = Some(diff.clone());
+        })?;
+        diff.read_with(&cx, |diff, cx| {
+            use proto::open_uncommitted_diff_response::Mode;
 
-        let mut new_statuses = self.statuses_by_path.iter().peekable();
-        let mut old_statuses = old.statuses_by_path.iter().peekable();
+            let unstaged_diff = diff.secondary_diff();
+            let index_snapshot = unstaged_diff.and_then(|diff| {
+                let diff = diff.read(cx);
+                diff.base_text_exists().then(|| diff.base_text())
+            });
 
-        let mut current_new_entry = new_statuses.next();
-        let mut current_old_entry = old_statuses.next();
-        loop {
-            match (current_new_entry, current_old_entry) {
-                (Some(new_entry), Some(old_entry)) => {
-                    match new_entry.repo_path.cmp(&old_entry.repo_path) {
-                        Ordering::Less => {
-                            updated_statuses.push(new_entry.to_proto());
-                            current_new_entry = new_statuses.next();
-                        }
-                        Ordering::Equal => {
-                            if new_entry.status != old_entry.status {
-                                updated_statuses.push(new_entry.to_proto());
-                            }
-                            current_old_entry = old_statuses.next();
-                            current_new_entry = new_statuses.next();
-                        }
-                        Ordering::Greater => {
-                            removed_statuses.push(old_entry.repo_path.to_proto());
-                            current_old_entry = old_statuses.next();
-                        }
+            let mode;
+            let staged_text;
+            let committed_text;
+            if diff.base_text_exists() {
+                let committed_snapshot = diff.base_text();
+                committed_text = Some(committed_snapshot.text());
+                if let Some(index_text) = index_snapshot {
+                    if index_text.remote_id() == committed_snapshot.remote_id() {
+                        mode = Mode::IndexMatchesHead;
+                        staged_text = None;
+                    } else {
+                        mode = Mode::IndexAndHead;
+                        staged_text = Some(index_text.text());
                     }
+                } else {
+                    mode = Mode::IndexAndHead;
+                    staged_text = None;
                 }
-                (None, Some(old_entry)) => {
-                    removed_statuses.push(old_entry.repo_path.to_proto());
-                    current_old_entry = old_statuses.next();
-                }
-                (Some(new_entry), None) => {
-                    updated_statuses.push(new_entry.to_proto());
-                    current_new_entry = new_statuses.next();
-                }
-                (None, None) => break,
+            } else {
+                mode = Mode::IndexAndHead;
+                committed_text = None;
+                staged_text = index_snapshot.as_ref().map(|buffer| buffer.text());
             }
-        }
 
-        proto::UpdateRepository {
-            branch_summary: self.branch.as_ref().map(branch_to_proto),
-            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
-            updated_statuses,
-            removed_statuses,
-            current_merge_conflicts: self
-                .merge
-                .conflicted_paths
-                .iter()
-                .map(|path| path.to_proto())
-                .collect(),
-            merge_message: self.merge.message.as_ref().map(|msg| msg.to_string()),
-            project_id,
-            id: self.id.to_proto(),
-            abs_path: self.work_directory_abs_path.to_string_lossy().into_owned(),
-            entry_ids: vec![],
-            scan_id: self.scan_id,
-            is_last_update: true,
-            stash_entries: self
-                .stash_entries
-                .entries
-                .iter()
-                .map(stash_to_proto)
-                .collect(),
-        }
+            proto::OpenUncommittedDiffResponse {
+                committed_text,
+                staged_text,
+                mode: mode.into(),
+            }
+        })
     }
 
-    pub fn status(&self) -> impl Iterator<Item = StatusEntry> + '_ {
-        self.statuses_by_path.iter().cloned()
+    async fn handle_update_diff_bases(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::UpdateDiffBases>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        this.update(&mut cx, |this, cx| {
+            if let Some(diff_state) = this.diffs.get_mut(&buffer_id)
+                && let Some(buffer) = this.buffer_store.read(cx).get(buffer_id)
+            {
+                let buffer = buffer.read(cx).text_snapshot();
+                diff_state.update(cx, |diff_state, cx| {
+                    diff_state.handle_base_texts_updated(buffer, request.payload, cx);
+                })
+            }
+        })
     }
 
-    pub fn status_summary(&self) -> GitSummary {
-        self.statuses_by_path.summary().item_summary
+    async fn handle_blame_buffer(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::BlameBuffer>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::BlameBufferResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let version = deserialize_version(&envelope.payload.version);
+        let buffer = this.read_with(&cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(version.clone())
+            })?
+            .await?;
+        let blame = this
+            .update(&mut cx, |this, cx| {
+                this.blame_buffer(&buffer, Some(version), cx)
+            })?
+            .await?;
+        Ok(serialize_blame_buffer_response(blame))
     }
 
-    pub fn status_for_path(&self, path: &RepoPath) -> Option<StatusEntry> {
-        self.statuses_by_path
-            .get(&PathKey(path.0.clone()), ())
-            .cloned()
+    async fn handle_get_permalink_to_line(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetPermalinkToLine>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetPermalinkToLineResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        // let version = deserialize_version(&envelope.payload.version);
+        let selection = {
+            let proto_selection = envelope
+                .payload
+                .selection
+                .context("no selection to get permalink for defined")?;
+            proto_selection.start as u32..proto_selection.end as u32
+        };
+        let buffer = this.read_with(&cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let permalink = this
+            .update(&mut cx, |this, cx| {
+                this.get_permalink_to_line(&buffer, selection, cx)
+            })?
+            .await?;
+        Ok(proto::GetPermalinkToLineResponse {
+            permalink: permalink.to_string(),
+        })
     }
 
-    pub fn abs_path_to_repo_path(&self, abs_path: &Path) -> Option<RepoPath> {
-        Self::abs_path_to_repo_path_inner(&self.work_directory_abs_path, abs_path, self.path_style)
+    fn repository_for_request(
+        this: &Entity<Self>,
+        id: RepositoryId,
+        cx: &mut AsyncApp,
+    ) -> Result<Entity<Repository>> {
+        this.read_with(cx, |this, _| {
+            this.repositories
+                .get(&id)
+                .context("missing repository handle")
+                .cloned()
+        })?
     }
 
-    #[inline]
-    fn abs_path_to_repo_path_inner(
-        work_directory_abs_path: &Path,
-        abs_path: &Path,
-        path_style: PathStyle,
-    ) -> Option<RepoPath> {
-        abs_path
-            .strip_prefix(&work_directory_abs_path)
-            .ok()
-            .and_then(|path| RepoPath::from_std_path(path, path_style).ok())
+    pub fn repo_snapshots(&self, cx: &App) -> HashMap<RepositoryId, RepositorySnapshot> {
+        self.repositories
+            .iter()
+            .map(|(id, repo)| (*id, repo.read(cx).snapshot.clone()))
+            .collect()
     }
 
-    pub fn had_conflict_on_last_merge_head_change(&self, repo_path: &RepoPath) -> bool {
+    fn process_updated_entries(
+        &self,
+        worktree: &Entity<Worktree>,
+        updated_entries: &[(Arc<RelPath>, ProjectEntryId, PathChange)],
+        cx: &mut App,
+    ) -> Task<HashMap<Entity<Repository>, Vec<RepoPath>>> {
+        let path_style = worktree.read(cx).path_style();
+        let mut repo_paths = self
+            .repositories
+            .values()
+            .map(|repo| (repo.read(cx).work_directory_abs_path.clone(), repo.clone()))
+            .collect::<Vec<_>>();
+        let mut entries: Vec<_> = updated_entries
+            .iter()
+            .map(|(path, _, _)| path.clone())
+            .collect();
+        entries.sort();
+        let worktree = worktree.read(cx);
+
+        let entries = entries
+            .into_iter()
+            .map(|path| worktree.absolutize(&path))
+            .collect::<Arc<[_]>>();
+
+        let executor = cx.background_executor().clone();
+        cx.background_executor().spawn(async move {
+            repo_paths.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+            let mut paths_by_git_repo = HashMap::<_, Vec<_>>::default();
+            let mut tasks = FuturesOrdered::new();
+            for (repo_path, repo) in repo_paths.into_iter().rev() {
+                let entries = entries.clone();
+                let task = executor.spawn(async move {
+                    // Find all repository paths that belong to this repo
+                    let mut ix = entries.partition_point(|path| path < &*repo_path);
+                    if ix == entries.len() {
+                        return None;
+                    };
+
+                    let mut paths = Vec::new();
+                    // All paths prefixed by a given repo will constitute a continuous range.
+                    while let Some(path) = entries.get(ix)
+                        && let Some(repo_path) = RepositorySnapshot::abs_path_to_repo_path_inner(
+                            &repo_path, path, path_style,
+                        )
+                    {
+                        paths.push((repo_path, ix));
+                        ix += 1;
+                    }
+                    if paths.is_empty() {
+                        None
+                    } else {
+                        Some((repo, paths))
+                    }
+                });
+                tasks.push_back(task);
+            }
+
+            // Now, let's filter out the "duplicate" entries that were processed by multiple distinct repos.
+            let mut path_was_used = vec![false; entries.len()];
+            let tasks = tasks.collect::<Vec<_>>().await;
+            // Process tasks from the back: iterating backwards allows us to see more-specific paths first.
+            // We always want to assign a path to it's innermost repository.
+            for t in tasks {
+                let Some((repo, paths)) = t else {
+                    continue;
+                };
+                let entry = paths_by_git_repo.entry(repo).or_default();
+                for (repo_path, ix) in paths {
+                    if path_was_used[ix] {
+                        continue;
+                    }
+                    path_was_used[ix] = true;
+                    entry.push(repo_path);
+                }
+            }
+
+            paths_by_git_repo
+        })
+    }
+}
+
+impl BufferGitState {
+    fn new(_git_store: WeakEntity<GitStore>) -> Self {
+        Self {
+            unstaged_diff: Default::default(),
+            uncommitted_diff: Default::default(),
+            recalculate_diff_task: Default::default(),
+            language: Default::default(),
+            language_registry: Default::default(),
+            recalculating_tx: postage::watch::channel_with(false).0,
+            hunk_staging_operation_count: 0,
+            hunk_staging_operation_count_as_of_write: 0,
+            head_text: Default::default(),
+            index_text: Default::default(),
+            head_changed: Default::default(),
+            index_changed: Default::default(),
+            language_changed: Default::default(),
+            conflict_updated_futures: Default::default(),
+            conflict_set: Default::default(),
+            reparse_conflict_markers_task: Default::default(),
+        }
+    }
+
+    fn buffer_language_changed(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        self.language = buffer.read(cx).language().cloned();
+        self.language_changed = true;
+        let _ = self.recalculate_diffs(buffer.read(cx).text_snapshot(), cx);
+    }
+
+    fn reparse_conflict_markers(
+        &mut self,
+        buffer: text::BufferSnapshot,
+        cx: &mut Context<Self>,
+    ) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+
+        let Some(conflict_set) = self
+            .conflict_set
+            .as_ref()
+            .and_then(|conflict_set| conflict_set.upgrade())
+        else {
+            return rx;
+        };
+
+        let old_snapshot = conflict_set.read_with(cx, |conflict_set, _| {
+            if conflict_set.has_conflict {
+                Some(conflict_set.snapshot())
+            } else {
+                None
+            }
+        });
+
+        if let Some(old_snapshot) = old_snapshot {
+            self.conflict_updated_futures.push(tx);
+            self.reparse_conflict_markers_task = Some(cx.spawn(async move |this, cx| {
+                let (snapshot, changed_range) = cx
+                    .background_spawn(async move {
+                        let new_snapshot = ConflictSet::parse(&buffer);
+                        let changed_range = old_snapshot.compare(&new_snapshot, &buffer);
+                        (new_snapshot, changed_range)
+                    })
+                    .await;
+                this.update(cx, |this, cx| {
+                    if let Some(conflict_set) = &this.conflict_set {
+                        conflict_set
+                            .update(cx, |conflict_set, cx| {
+                                conflict_set.set_snapshot(snapshot, changed_range, cx);
+                            })
+                            .ok();
+                    }
+                    let futures = std::mem::take(&mut this.conflict_updated_futures);
+                    for tx in futures {
+                        tx.send(()).ok();
+                    }
+                })
+            }))
+        }
+
+        rx
+    }
+
+    fn unstaged_diff(&self) -> Option<Entity<BufferDiff>> {
+        self.unstaged_diff.as_ref().and_then(|set| set.upgrade())
+    }
+
+    fn uncommitted_diff(&self) -> Option<Entity<BufferDiff>> {
+        self.uncommitted_diff.as_ref().and_then(|set| set.upgrade())
+    }
+
+    fn handle_base_texts_updated(
+        &mut self,
+        buffer: text::BufferSnapshot,
+        message: proto::UpdateDiffBases,
+        cx: &mut Context<Self>,
+    ) {
+        use proto::update_diff_bases::Mode;
+
+        let Some(mode) = Mode::from_i32(message.mode) else {
+            return;
+        };
+
+        let diff_bases_change = match mode {
+            Mode::HeadOnly => DiffBasesChange::SetHead(message.committed_text),
+            Mode::IndexOnly => DiffBasesChange::SetIndex(message.staged_text),
+            Mode::IndexMatchesHead => DiffBasesChange::SetBoth(message.committed_text),
+            Mode::IndexAndHead => DiffBasesChange::SetEach {
+                index: message.staged_text,
+                head: message.committed_text,
+            },
+        };
+
+        self.diff_bases_changed(buffer, Some(diff_bases_change), cx);
+    }
+
+    pub fn wait_for_recalculation(&mut self) -> Option<impl Future<Output = ()> + use<>> {
+        if *self.recalculating_tx.borrow() {
+            let mut rx = self.recalculating_tx.subscribe();
+            Some(async move {
+                loop {
+                    let is_recalculating = rx.recv().await;
+                    if is_recalculating != Some(true) {
+                        break;
+                    }
+                }
+            })
+        } else {
+            None
+        }
+    }
+
+    fn diff_bases_changed(
+        &mut self,
+        buffer: text::BufferSnapshot,
+        diff_bases_change: Option<DiffBasesChange>,
+        cx: &mut Context<Self>,
+    ) {
+        match diff_bases_change {
+            Some(DiffBasesChange::SetIndex(index)) => {
+                self.index_text = index.map(|mut index| {
+                    text::LineEnding::normalize(&mut index);
+                    Arc::new(index)
+                });
+                self.index_changed = true;
+            }
+            Some(DiffBasesChange::SetHead(head)) => {
+                self.head_text = head.map(|mut head| {
+                    text::LineEnding::normalize(&mut head);
+                    Arc::new(head)
+                });
+                self.head_changed = true;
+            }
+            Some(DiffBasesChange::SetBoth(text)) => {
+                let text = text.map(|mut text| {
+                    text::LineEnding::normalize(&mut text);
+                    Arc::new(text)
+                });
+                self.head_text = text.clone();
+                self.index_text = text;
+                self.head_changed = true;
+                self.index_changed = true;
+            }
+            Some(DiffBasesChange::SetEach { index, head }) => {
+                self.index_text = index.map(|mut index| {
+                    text::LineEnding::normalize(&mut index);
+                    Arc::new(index)
+                });
+                self.index_changed = true;
+                self.head_text = head.map(|mut head| {
+                    text::LineEnding::normalize(&mut head);
+                    Arc::new(head)
+                });
+                self.head_changed = true;
+            }
+            None => {}
+        }
+
+        self.recalculate_diffs(buffer, cx)
+    }
+
+    fn recalculate_diffs(&mut self, buffer: text::BufferSnapshot, cx: &mut Context<Self>) {
+        *self.recalculating_tx.borrow_mut() = true;
+
+        let language = self.language.clone();
+        let language_registry = self.language_registry.clone();
+        let unstaged_diff = self.unstaged_diff();
+        let uncommitted_diff = self.uncommitted_diff();
+        let head = self.head_text.clone();
+        let index = self.index_text.clone();
+        let index_changed = self.index_changed;
+        let head_changed = self.head_changed;
+        let language_changed = self.language_changed;
+        let prev_hunk_staging_operation_count = self.hunk_staging_operation_count_as_of_write;
+        let index_matches_head = match (self.index_text.as_ref(), self.head_text.as_ref()) {
+            (Some(index), Some(head)) => Arc::ptr_eq(index, head),
+            (None, None) => true,
+            _ => false,
+        };
+        self.recalculate_diff_task = Some(cx.spawn(async move |this, cx| {
+            log::debug!(
+                "start recalculating diffs for buffer {}",
+                buffer.remote_id()
+            );
+
+            let mut new_unstaged_diff = None;
+            if let Some(unstaged_diff) = &unstaged_diff {
+                new_unstaged_diff = Some(
+                    BufferDiff::update_diff(
+                        unstaged_diff.clone(),
+                        buffer.clone(),
+                        index,
+                        index_changed,
+                        language_changed,
+                        language.clone(),
+                        language_registry.clone(),
+                        cx,
+                    )
+                    .await?,
+                );
+            }
+
+            let mut new_uncommitted_diff = None;
+            if let Some(uncommitted_diff) = &uncommitted_diff {
+                new_uncommitted_diff = if index_matches_head {
+                    new_unstaged_diff.clone()
+                } else {
+                    Some(
+                        BufferDiff::update_diff(
+                            uncommitted_diff.clone(),
+                            buffer.clone(),
+                            head,
+                            head_changed,
+                            language_changed,
+                            language.clone(),
+                            language_registry.clone(),
+                            cx,
+                        )
+                        .await?,
+                    )
+                }
+            }
+
+            let cancel = this.update(cx, |this, _| {
+                // This checks whether all pending stage/unstage operations
+                // have quiesced (i.e. both the corresponding write and the
+                // read of that write have completed). If not, then we cancel
+                // this recalculation attempt to avoid invalidating pending
+                // state too quickly; another recalculation will come along
+                // later and clear the pending state once the state of the index has settled.
+                if this.hunk_staging_operation_count > prev_hunk_staging_operation_count {
+                    *this.recalculating_tx.borrow_mut() = false;
+                    true
+                } else {
+                    false
+                }
+            })?;
+            if cancel {
+                log::debug!(
+                    concat!(
+                        "aborting recalculating diffs for buffer {}",
+                        "due to subsequent hunk operations",
+                    ),
+                    buffer.remote_id()
+                );
+                return Ok(());
+            }
+
+            let unstaged_changed_range = if let Some((unstaged_diff, new_unstaged_diff)) =
+                unstaged_diff.as_ref().zip(new_unstaged_diff.clone())
+            {
+                unstaged_diff.update(cx, |diff, cx| {
+                    if language_changed {
+                        diff.language_changed(cx);
+                    }
+                    diff.set_snapshot(new_unstaged_diff, &buffer, cx)
+                })?
+            } else {
+                None
+            };
+
+            if let Some((uncommitted_diff, new_uncommitted_diff)) =
+                uncommitted_diff.as_ref().zip(new_uncommitted_diff.clone())
+            {
+                uncommitted_diff.update(cx, |diff, cx| {
+                    if language_changed {
+                        diff.language_changed(cx);
+                    }
+                    diff.set_snapshot_with_secondary(
+                        new_uncommitted_diff,
+                        &buffer,
+                        unstaged_changed_range,
+                        true,
+                        cx,
+                    );
+                })?;
+            }
+
+            log::debug!(
+                "finished recalculating diffs for buffer {}",
+                buffer.remote_id()
+            );
+
+            if let Some(this) = this.upgrade() {
+                this.update(cx, |this, _| {
+                    this.index_changed = false;
+                    this.head_changed = false;
+                    this.language_changed = false;
+                    *this.recalculating_tx.borrow_mut() = false;
+                })?;
+            }
+
+            Ok(())
+        }));
+    }
+}
+
+fn make_remote_delegate(
+    this: Entity<GitStore>,
+    project_id: u64,
+    repository_id: RepositoryId,
+    askpass_id: u64,
+    cx: &mut AsyncApp,
+) -> AskPassDelegate {
+    AskPassDelegate::new(cx, move |prompt, tx, cx| {
+        this.update(cx, |this, cx| {
+            let Some((client, _)) = this.downstream_client() else {
+                return;
+            };
+            let response = client.request(proto::AskPassRequest {
+                project_id,
+                repository_id: repository_id.to_proto(),
+                askpass_id,
+                prompt,
+            });
+            cx.spawn(async move |_, _| {
+                let mut response = response.await?.response;
+                tx.send(EncryptedPassword::try_from(response.as_ref())?)
+                    .ok();
+                response.zeroize();
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+        })
+        .log_err();
+    })
+}
+
+impl RepositoryId {
+    pub fn to_proto(self) -> u64 {
+        self.0
+    }
+
+    pub fn from_proto(id: u64) -> Self {
+        RepositoryId(id)
+    }
+}
+
+impl RepositorySnapshot {
+    fn empty(id: RepositoryId, work_directory_abs_path: Arc<Path>, path_style: PathStyle) -> Self {
+        Self {
+            id,
+            statuses_by_path: Default::default(),
+            lfs_paths: Default::default(),
+            submodules: Vec::new(),
+            worktrees: Vec::new(),
+            work_directory_abs_path,
+            branch: None,
+            head_commit: None,
+            scan_id: 0,
+            merge: Default::default(),
+            remote_origin_url: None,
+            remote_upstream_url: None,
+            stash_entries: Default::default(),
+            path_style,
+            metadata_mtimes: None,
+        }
+    }
+
+    fn initial_update(&self, project_id: u64) -> proto::UpdateRepository {
+        proto::UpdateRepository {
+            branch_summary: self.branch.as_ref().map(branch_to_proto),
+            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
+            updated_statuses: self
+                .statuses_by_path
+                .iter()
+                .map(|entry| entry.to_proto())
+                .collect(),
+            removed_statuses: Default::default(),
+            current_merge_conflicts: self
+                .merge
+                .conflicted_paths
+                .iter()
+                .map(|repo_path| repo_path.to_proto())
+                .collect(),
+            merge_message: self.merge.message.as_ref().map(|msg| msg.to_string()),
+            rebasing: self.merge.rebasing,
+            lfs_paths: self.lfs_paths.iter().map(|path| path.to_proto()).collect(),
+            submodules: self.submodules.iter().map(submodule_to_proto).collect(),
+            worktrees: self.worktrees.iter().map(worktree_to_proto).collect(),
+            project_id,
+            id: self.id.to_proto(),
+            abs_path: self.work_directory_abs_path.to_string_lossy().into_owned(),
+            entry_ids: vec![self.id.to_proto()],
+            scan_id: self.scan_id,
+            is_last_update: true,
+            stash_entries: self
+                .stash_entries
+                .entries
+                .iter()
+                .map(stash_to_proto)
+                .collect(),
+        }
+    }
+
+    fn build_update(&self, old: &Self, project_id: u64) -> proto::UpdateRepository {
+        let mut updated_statuses: Vec<proto::StatusEntry> = Vec::new();
+        let mut removed_statuses: Vec<String> = Vec::new();
+
+        let mut new_statuses = self.statuses_by_path.iter().peekable();
+        let mut old_statuses = old.statuses_by_path.iter().peekable();
+
+        let mut current_new_entry = new_statuses.next();
+        let mut current_old_entry = old_statuses.next();
+        loop {
+            match (current_new_entry, current_old_entry) {
+                (Some(new_entry), Some(old_entry)) => {
+                    match new_entry.repo_path.cmp(&old_entry.repo_path) {
+                        Ordering::Less => {
+                            updated_statuses.push(new_entry.to_proto());
+                            current_new_entry = new_statuses.next();
+                        }
+                        Ordering::Equal => {
+                            if new_entry.status != old_entry.status {
+                                updated_statuses.push(new_entry.to_proto());
+                            }
+                            current_old_entry = old_statuses.next();
+                            current_new_entry = new_statuses.next();
+                        }
+                        Ordering::Greater => {
+                            removed_statuses.push(old_entry.repo_path.to_proto());
+                            current_old_entry = old_statuses.next();
+                        }
+                    }
+                }
+                (None, Some(old_entry)) => {
+                    removed_statuses.push(old_entry.repo_path.to_proto());
+                    current_old_entry = old_statuses.next();
+                }
+                (Some(new_entry), None) => {
+                    updated_statuses.push(new_entry.to_proto());
+                    current_new_entry = new_statuses.next();
+                }
+                (None, None) => break,
+            }
+        }
+
+        proto::UpdateRepository {
+            branch_summary: self.branch.as_ref().map(branch_to_proto),
+            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
+            updated_statuses,
+            removed_statuses,
+            current_merge_conflicts: self
+                .merge
+                .conflicted_paths
+                .iter()
+                .map(|path| path.to_proto())
+                .collect(),
+            merge_message: self.merge.message.as_ref().map(|msg| msg.to_string()),
+            rebasing: self.merge.rebasing,
+            lfs_paths: self.lfs_paths.iter().map(|path| path.to_proto()).collect(),
+            submodules: self.submodules.iter().map(submodule_to_proto).collect(),
+            worktrees: self.worktrees.iter().map(worktree_to_proto).collect(),
+            project_id,
+            id: self.id.to_proto(),
+            abs_path: self.work_directory_abs_path.to_string_lossy().into_owned(),
+            entry_ids: vec![],
+            scan_id: self.scan_id,
+            is_last_update: true,
+            stash_entries: self
+                .stash_entries
+                .entries
+                .iter()
+                .map(stash_to_proto)
+                .collect(),
+        }
+    }
+
+    pub fn status(&self) -> impl Iterator<Item = StatusEntry> + '_ {
+        self.statuses_by_path.iter().cloned()
+    }
+
+    pub fn status_summary(&self) -> GitSummary {
+        self.statuses_by_path.summary().item_summary
+    }
+
+    pub fn status_for_path(&self, path: &RepoPath) -> Option<StatusEntry> {
+        self.statuses_by_path
+            .get(&PathKey(path.0.clone()), ())
+            .cloned()
+    }
+
+    pub fn submodule(&self, path: &RepoPath) -> Option<&Submodule> {
+        self.submodules
+            .binary_search_by(|submodule| submodule.path.cmp(path))
+            .ok()
+            .map(|ix| &self.submodules[ix])
+    }
+
+    /// The worktree of the repository this snapshot is of.
+    pub fn current_worktree(&self) -> Option<&GitWorktree> {
+        self.worktrees
+            .iter()
+            .find(|worktree| worktree.path.as_path() == self.work_directory_abs_path.as_ref())
+    }
+
+    pub fn abs_path_to_repo_path(&self, abs_path: &Path) -> Option<RepoPath> {
+        Self::abs_path_to_repo_path_inner(&self.work_directory_abs_path, abs_path, self.path_style)
+    }
+
+    #[inline]
+    fn abs_path_to_repo_path_inner(
+        work_directory_abs_path: &Path,
+        abs_path: &Path,
+        path_style: PathStyle,
+    ) -> Option<RepoPath> {
+        abs_path
+            .strip_prefix(&work_directory_abs_path)
+            .ok()
+            .and_then(|path| RepoPath::from_std_path(path, path_style).ok())
+    }
+
+    pub fn had_conflict_on_last_merge_head_change(&self, repo_path: &RepoPath) -> bool {
         self.merge.conflicted_paths.contains(repo_path)
     }
 
@@ -2973,6 +3591,7 @@ impl MergeDetails {
     ) -> Result<(MergeDetails, bool)> {
         log::debug!("load merge details");
         let message = backend.merge_message().await;
+        let rebasing = backend.rebase_in_progress().await;
         let heads = backend
             .revparse_batch(vec![
                 "MERGE_HEAD".into(),
@@ -3009,6 +3628,7 @@ impl MergeDetails {
                 return Ok((
                     MergeDetails {
                         message: message.map(SharedString::from),
+                        rebasing,
                         ..prev_snapshot.merge.clone()
                     },
                     false,
@@ -3023,6 +3643,7 @@ impl MergeDetails {
             conflicted_paths,
             message: message.map(SharedString::from),
             heads,
+            rebasing,
         };
         Ok((details, merge_heads_changed))
     }
@@ -3065,6 +3686,7 @@ impl Repository {
             ),
             job_id: 0,
             active_jobs: Default::default(),
+            lfs_transfer: None,
         }
     }
 
@@ -3089,6 +3711,7 @@ impl Repository {
             latest_askpass_id: 0,
             active_jobs: Default::default(),
             job_id: 0,
+            lfs_transfer: None,
         }
     }
 
@@ -3248,337 +3871,799 @@ impl Repository {
                                     .log_err();
                             }
 
-                            diff_state.diff_bases_changed(buffer_snapshot, diff_bases_change, cx);
-                        });
+                            diff_state.diff_bases_changed(buffer_snapshot, diff_bases_change, cx);
+                        });
+                    }
+                })
+            },
+        );
+    }
+
+    pub fn send_job<F, Fut, R>(
+        &mut self,
+        status: Option<SharedString>,
+        job: F,
+    ) -> oneshot::Receiver<R>
+    where
+        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        self.send_keyed_job(None, status, job)
+    }
+
+    fn send_keyed_job<F, Fut, R>(
+        &mut self,
+        key: Option<GitJobKey>,
+        status: Option<SharedString>,
+        job: F,
+    ) -> oneshot::Receiver<R>
+    where
+        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        let (result_tx, result_rx) = futures::channel::oneshot::channel();
+        let job_id = post_inc(&mut self.job_id);
+        let this = self.this.clone();
+        self.job_sender
+            .unbounded_send(GitJob {
+                key,
+                job: Box::new(move |state, cx: &mut AsyncApp| {
+                    let job = job(state, cx.clone());
+                    cx.spawn(async move |cx| {
+                        if let Some(s) = status.clone() {
+                            this.update(cx, |this, cx| {
+                                this.active_jobs.insert(
+                                    job_id,
+                                    JobInfo {
+                                        start: Instant::now(),
+                                        message: s.clone(),
+                                    },
+                                );
+
+                                cx.notify();
+                            })
+                            .ok();
+                        }
+                        let result = job.await;
+
+                        this.update(cx, |this, cx| {
+                            this.active_jobs.remove(&job_id);
+                            cx.notify();
+                        })
+                        .ok();
+
+                        result_tx.send(result).ok();
+                    })
+                }),
+            })
+            .ok();
+        result_rx
+    }
+
+    pub fn set_as_active_repository(&self, cx: &mut Context<Self>) {
+        let Some(git_store) = self.git_store.upgrade() else {
+            return;
+        };
+        let entity = cx.entity();
+        git_store.update(cx, |git_store, cx| {
+            let Some((&id, _)) = git_store
+                .repositories
+                .iter()
+                .find(|(_, handle)| *handle == &entity)
+            else {
+                return;
+            };
+            git_store.active_repo_id = Some(id);
+            cx.emit(GitStoreEvent::ActiveRepositoryChanged(Some(id)));
+        });
+    }
+
+    pub fn cached_status(&self) -> impl '_ + Iterator<Item = StatusEntry> {
+        self.snapshot.status()
+    }
+
+    pub fn cached_stash(&self) -> GitStash {
+        self.snapshot.stash_entries.clone()
+    }
+
+    pub fn repo_path_to_project_path(&self, path: &RepoPath, cx: &App) -> Option<ProjectPath> {
+        let git_store = self.git_store.upgrade()?;
+        let worktree_store = git_store.read(cx).worktree_store.read(cx);
+        let abs_path = self
+            .snapshot
+            .work_directory_abs_path
+            .join(path.as_std_path());
+        let abs_path = SanitizedPath::new(&abs_path);
+        let (worktree, relative_path) = worktree_store.find_worktree(abs_path, cx)?;
+        Some(ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: relative_path,
+        })
+    }
+
+    pub fn project_path_to_repo_path(&self, path: &ProjectPath, cx: &App) -> Option<RepoPath> {
+        let git_store = self.git_store.upgrade()?;
+        let worktree_store = git_store.read(cx).worktree_store.read(cx);
+        let abs_path = worktree_store.absolutize(path, cx)?;
+        self.snapshot.abs_path_to_repo_path(&abs_path)
+    }
+
+    pub fn contains_sub_repo(&self, other: &Entity<Self>, cx: &App) -> bool {
+        other
+            .read(cx)
+            .snapshot
+            .work_directory_abs_path
+            .starts_with(&self.snapshot.work_directory_abs_path)
+    }
+
+    pub fn open_commit_buffer(
+        &mut self,
+        languages: Option<Arc<LanguageRegistry>>,
+        buffer_store: Entity<BufferStore>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        let id = self.id;
+        if let Some(buffer) = self.commit_message_buffer.clone() {
+            return Task::ready(Ok(buffer));
+        }
+        let this = cx.weak_entity();
+
+        let rx = self.send_job(None, move |state, mut cx| async move {
+            let Some(this) = this.upgrade() else {
+                bail!("git store was dropped");
+            };
+            match state {
+                RepositoryState::Local { .. } => {
+                    this.update(&mut cx, |_, cx| {
+                        Self::open_local_commit_buffer(languages, buffer_store, cx)
+                    })?
+                    .await
+                }
+                RepositoryState::Remote { project_id, client } => {
+                    let request = client.request(proto::OpenCommitMessageBuffer {
+                        project_id: project_id.0,
+                        repository_id: id.to_proto(),
+                    });
+                    let response = request.await.context("requesting to open commit buffer")?;
+                    let buffer_id = BufferId::new(response.buffer_id)?;
+                    let buffer = buffer_store
+                        .update(&mut cx, |buffer_store, cx| {
+                            buffer_store.wait_for_remote_buffer(buffer_id, cx)
+                        })?
+                        .await?;
+                    if let Some(language_registry) = languages {
+                        let git_commit_language =
+                            language_registry.language_for_name("Git Commit").await?;
+                        buffer.update(&mut cx, |buffer, cx| {
+                            buffer.set_language(Some(git_commit_language), cx);
+                        })?;
+                    }
+                    this.update(&mut cx, |this, _| {
+                        this.commit_message_buffer = Some(buffer.clone());
+                    })?;
+                    Ok(buffer)
+                }
+            }
+        });
+
+        cx.spawn(|_, _: &mut AsyncApp| async move { rx.await? })
+    }
+
+    fn open_local_commit_buffer(
+        language_registry: Option<Arc<LanguageRegistry>>,
+        buffer_store: Entity<BufferStore>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        cx.spawn(async move |repository, cx| {
+            let buffer = buffer_store
+                .update(cx, |buffer_store, cx| buffer_store.create_buffer(false, cx))?
+                .await?;
+
+            if let Some(language_registry) = language_registry {
+                let git_commit_language = language_registry.language_for_name("Git Commit").await?;
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_language(Some(git_commit_language), cx);
+                })?;
+            }
+
+            repository.update(cx, |repository, _| {
+                repository.commit_message_buffer = Some(buffer.clone());
+            })?;
+            Ok(buffer)
+        })
+    }
+
+    pub fn checkout_files(
+        &mut self,
+        commit: &str,
+        paths: Vec<RepoPath>,
+        _cx: &mut App,
+    ) -> oneshot::Receiver<Result<()>> {
+        let commit = commit.to_string();
+        let id = self.id;
+
+        self.send_job(
+            Some(format!("git checkout {}", commit).into()),
+            move |git_repo, _| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => {
+                        backend
+                            .checkout_files(commit, paths, environment.clone())
+                            .await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCheckoutFiles {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                                paths: paths.into_iter().map(|p| p.to_proto()).collect(),
+                            })
+                            .await?;
+
+                        Ok(())
                     }
-                })
+                }
             },
-        );
+        )
     }
 
-    pub fn send_job<F, Fut, R>(
+    pub fn reset(
         &mut self,
-        status: Option<SharedString>,
-        job: F,
-    ) -> oneshot::Receiver<R>
-    where
-        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
-        Fut: Future<Output = R> + 'static,
-        R: Send + 'static,
-    {
-        self.send_keyed_job(None, status, job)
-    }
+        commit: String,
+        reset_mode: ResetMode,
+        _cx: &mut App,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
 
-    fn send_keyed_job<F, Fut, R>(
-        &mut self,
-        key: Option<GitJobKey>,
-        status: Option<SharedString>,
-        job: F,
-    ) -> oneshot::Receiver<R>
-    where
-        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
-        Fut: Future<Output = R> + 'static,
-        R: Send + 'static,
-    {
-        let (result_tx, result_rx) = futures::channel::oneshot::channel();
-        let job_id = post_inc(&mut self.job_id);
-        let this = self.this.clone();
-        self.job_sender
-            .unbounded_send(GitJob {
-                key,
-                job: Box::new(move |state, cx: &mut AsyncApp| {
-                    let job = job(state, cx.clone());
-                    cx.spawn(async move |cx| {
-                        if let Some(s) = status.clone() {
-                            this.update(cx, |this, cx| {
-                                this.active_jobs.insert(
-                                    job_id,
-                                    JobInfo {
-                                        start: Instant::now(),
-                                        message: s.clone(),
-                                    },
-                                );
+        self.send_job(None, move |git_repo, _| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.reset(commit, reset_mode, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    client
+                        .request(proto::GitReset {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commit,
+                            mode: match reset_mode {
+                                ResetMode::Soft => git_reset::ResetMode::Soft.into(),
+                                ResetMode::Mixed => git_reset::ResetMode::Mixed.into(),
+                            },
+                        })
+                        .await?;
 
-                                cx.notify();
-                            })
-                            .ok();
-                        }
-                        let result = job.await;
+                    Ok(())
+                }
+            }
+        })
+    }
 
-                        this.update(cx, |this, cx| {
-                            this.active_jobs.remove(&job_id);
-                            cx.notify();
+    pub fn show(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDetails>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.show(commit).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let resp = client
+                        .request(proto::GitShow {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commit,
                         })
-                        .ok();
+                        .await?;
 
-                        result_tx.send(result).ok();
+                    Ok(CommitDetails {
+                        sha: resp.sha.into(),
+                        message: resp.message.into(),
+                        commit_timestamp: resp.commit_timestamp,
+                        author_email: resp.author_email.into(),
+                        author_name: resp.author_name.into(),
                     })
-                }),
-            })
-            .ok();
-        result_rx
+                }
+            }
+        })
     }
 
-    pub fn set_as_active_repository(&self, cx: &mut Context<Self>) {
-        let Some(git_store) = self.git_store.upgrade() else {
-            return;
-        };
-        let entity = cx.entity();
-        git_store.update(cx, |git_store, cx| {
-            let Some((&id, _)) = git_store
-                .repositories
-                .iter()
-                .find(|(_, handle)| *handle == &entity)
-            else {
-                return;
-            };
-            git_store.active_repo_id = Some(id);
-            cx.emit(GitStoreEvent::ActiveRepositoryChanged(Some(id)));
-        });
+    pub fn log(&mut self, options: LogOptions) -> oneshot::Receiver<Result<Vec<CommitSummary>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.log(options).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(log_options_to_proto(project_id, id, options))
+                        .await?;
+                    Ok(response
+                        .commits
+                        .iter()
+                        .map(proto_to_commit_summary)
+                        .collect())
+                }
+            }
+        })
     }
 
-    pub fn cached_status(&self) -> impl '_ + Iterator<Item = StatusEntry> {
-        self.snapshot.status()
+    pub fn rebase_interactive(
+        &mut self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git rebase --interactive {base}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.rebase_interactive(base, todo, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRebase {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                base,
+                                todo: todo.iter().map(rebase_todo_entry_to_proto).collect(),
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn rebase_command(&mut self, command: RebaseCommand) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git rebase {}", command.as_arg()).into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.rebase_command(command, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRebaseCommand {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                command: match command {
+                                    RebaseCommand::Continue => {
+                                        git_rebase_command::Command::Continue.into()
+                                    }
+                                    RebaseCommand::Skip => git_rebase_command::Command::Skip.into(),
+                                    RebaseCommand::Abort => {
+                                        git_rebase_command::Command::Abort.into()
+                                    }
+                                },
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn cherry_pick(&mut self, commit: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git cherry-pick {commit}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.cherry_pick(commit, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCherryPick {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn revert(&mut self, commit: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git revert {commit}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.revert(commit, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRevert {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn tags(&mut self) -> oneshot::Receiver<Result<Vec<Tag>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.tags().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitGetTags {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
+                    Ok(response.tags.iter().map(proto_to_tag).collect())
+                }
+            }
+        })
     }
 
-    pub fn cached_stash(&self) -> GitStash {
-        self.snapshot.stash_entries.clone()
+    pub fn create_tag(
+        &mut self,
+        name: String,
+        target: Option<String>,
+        message: Option<String>,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git tag {name}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.create_tag(name, target, message, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCreateTag {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                name,
+                                target,
+                                message,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn repo_path_to_project_path(&self, path: &RepoPath, cx: &App) -> Option<ProjectPath> {
-        let git_store = self.git_store.upgrade()?;
-        let worktree_store = git_store.read(cx).worktree_store.read(cx);
-        let abs_path = self
-            .snapshot
-            .work_directory_abs_path
-            .join(path.as_std_path());
-        let abs_path = SanitizedPath::new(&abs_path);
-        let (worktree, relative_path) = worktree_store.find_worktree(abs_path, cx)?;
-        Some(ProjectPath {
-            worktree_id: worktree.read(cx).id(),
-            path: relative_path,
-        })
+    pub fn delete_tag(&mut self, name: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git tag --delete {name}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local { backend, .. } => backend.delete_tag(name).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitDeleteTag {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                name,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn project_path_to_repo_path(&self, path: &ProjectPath, cx: &App) -> Option<RepoPath> {
-        let git_store = self.git_store.upgrade()?;
-        let worktree_store = git_store.read(cx).worktree_store.read(cx);
-        let abs_path = worktree_store.absolutize(path, cx)?;
-        self.snapshot.abs_path_to_repo_path(&abs_path)
+    pub fn bisect_start(&mut self, bad: String, good: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git bisect start {bad} {good}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.bisect_start(bad, good, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitBisectStart {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                bad,
+                                good,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn contains_sub_repo(&self, other: &Entity<Self>, cx: &App) -> bool {
-        other
-            .read(cx)
-            .snapshot
-            .work_directory_abs_path
-            .starts_with(&self.snapshot.work_directory_abs_path)
+    pub fn bisect_mark(&mut self, mark: BisectMark) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git bisect {}", mark.as_arg()).into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.bisect_mark(mark, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitBisectMark {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                mark: match mark {
+                                    BisectMark::Good => git_bisect_mark::Mark::Good.into(),
+                                    BisectMark::Bad => git_bisect_mark::Mark::Bad.into(),
+                                    BisectMark::Skip => git_bisect_mark::Mark::Skip.into(),
+                                },
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn open_commit_buffer(
-        &mut self,
-        languages: Option<Arc<LanguageRegistry>>,
-        buffer_store: Entity<BufferStore>,
-        cx: &mut Context<Self>,
-    ) -> Task<Result<Entity<Buffer>>> {
+    pub fn bisect_reset(&mut self) -> oneshot::Receiver<Result<()>> {
         let id = self.id;
-        if let Some(buffer) = self.commit_message_buffer.clone() {
-            return Task::ready(Ok(buffer));
-        }
-        let this = cx.weak_entity();
-
-        let rx = self.send_job(None, move |state, mut cx| async move {
-            let Some(this) = this.upgrade() else {
-                bail!("git store was dropped");
-            };
-            match state {
-                RepositoryState::Local { .. } => {
-                    this.update(&mut cx, |_, cx| {
-                        Self::open_local_commit_buffer(languages, buffer_store, cx)
-                    })?
-                    .await
-                }
-                RepositoryState::Remote { project_id, client } => {
-                    let request = client.request(proto::OpenCommitMessageBuffer {
-                        project_id: project_id.0,
-                        repository_id: id.to_proto(),
-                    });
-                    let response = request.await.context("requesting to open commit buffer")?;
-                    let buffer_id = BufferId::new(response.buffer_id)?;
-                    let buffer = buffer_store
-                        .update(&mut cx, |buffer_store, cx| {
-                            buffer_store.wait_for_remote_buffer(buffer_id, cx)
-                        })?
-                        .await?;
-                    if let Some(language_registry) = languages {
-                        let git_commit_language =
-                            language_registry.language_for_name("Git Commit").await?;
-                        buffer.update(&mut cx, |buffer, cx| {
-                            buffer.set_language(Some(git_commit_language), cx);
-                        })?;
+        self.send_job(
+            Some("git bisect reset".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.bisect_reset(environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitBisectReset {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                            })
+                            .await?;
+                        Ok(())
                     }
-                    this.update(&mut cx, |this, _| {
-                        this.commit_message_buffer = Some(buffer.clone());
-                    })?;
-                    Ok(buffer)
                 }
-            }
-        });
-
-        cx.spawn(|_, _: &mut AsyncApp| async move { rx.await? })
+            },
+        )
     }
 
-    fn open_local_commit_buffer(
-        language_registry: Option<Arc<LanguageRegistry>>,
-        buffer_store: Entity<BufferStore>,
-        cx: &mut Context<Self>,
-    ) -> Task<Result<Entity<Buffer>>> {
-        cx.spawn(async move |repository, cx| {
-            let buffer = buffer_store
-                .update(cx, |buffer_store, cx| buffer_store.create_buffer(false, cx))?
-                .await?;
-
-            if let Some(language_registry) = language_registry {
-                let git_commit_language = language_registry.language_for_name("Git Commit").await?;
-                buffer.update(cx, |buffer, cx| {
-                    buffer.set_language(Some(git_commit_language), cx);
-                })?;
+    pub fn bisect_state(&mut self) -> oneshot::Receiver<Result<Option<BisectState>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.bisect_state().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitGetBisectState {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
+                    Ok(response.state.as_ref().map(proto_to_bisect_state))
+                }
             }
-
-            repository.update(cx, |repository, _| {
-                repository.commit_message_buffer = Some(buffer.clone());
-            })?;
-            Ok(buffer)
         })
     }
 
-    pub fn checkout_files(
+    /// Runs `git submodule init`, `update` or `sync` on the given submodules,
+    /// or on all of them if no paths are given.
+    pub fn submodule_command(
         &mut self,
-        commit: &str,
+        command: SubmoduleCommand,
         paths: Vec<RepoPath>,
-        _cx: &mut App,
     ) -> oneshot::Receiver<Result<()>> {
-        let commit = commit.to_string();
         let id = self.id;
-
         self.send_job(
-            Some(format!("git checkout {}", commit).into()),
-            move |git_repo, _| async move {
+            Some(format!("git submodule {}", command.as_args().join(" ")).into()),
+            move |git_repo, _cx| async move {
                 match git_repo {
                     RepositoryState::Local {
                         backend,
                         environment,
                         ..
-                    } => {
-                        backend
-                            .checkout_files(commit, paths, environment.clone())
-                            .await
-                    }
+                    } => backend.submodule_command(command, paths, environment).await,
                     RepositoryState::Remote { project_id, client } => {
                         client
-                            .request(proto::GitCheckoutFiles {
+                            .request(proto::GitSubmoduleCommand {
                                 project_id: project_id.0,
                                 repository_id: id.to_proto(),
-                                commit,
-                                paths: paths.into_iter().map(|p| p.to_proto()).collect(),
+                                command: match command {
+                                    SubmoduleCommand::Init => {
+                                        git_submodule_command::Command::Init.into()
+                                    }
+                                    SubmoduleCommand::Update => {
+                                        git_submodule_command::Command::Update.into()
+                                    }
+                                    SubmoduleCommand::Sync => {
+                                        git_submodule_command::Command::Sync.into()
+                                    }
+                                },
+                                paths: paths.iter().map(|path| path.to_proto()).collect(),
                             })
                             .await?;
-
                         Ok(())
                     }
                 }
-            },
-        )
+            },
+        )
+    }
+
+    pub fn submodule_range(&mut self, path: RepoPath) -> oneshot::Receiver<Result<SubmoduleRange>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.submodule_range(path).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitGetSubmoduleRange {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            path: path.to_proto(),
+                        })
+                        .await?;
+                    Ok(SubmoduleRange {
+                        old: response.old.map(SharedString::from),
+                        new: response.new.map(SharedString::from),
+                        added: response.added.iter().map(proto_to_commit_summary).collect(),
+                        removed: response
+                            .removed
+                            .iter()
+                            .map(proto_to_commit_summary)
+                            .collect(),
+                    })
+                }
+            }
+        })
+    }
+
+    pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.load_commit(commit, cx).await,
+                RepositoryState::Remote {
+                    client, project_id, ..
+                } => {
+                    let response = client
+                        .request(proto::LoadCommitDiff {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commit,
+                        })
+                        .await?;
+                    proto_to_commit_diff(response)
+                }
+            }
+        })
+    }
+
+    /// Loads the files that differ between two revisions.
+    pub fn diff_refs(
+        &mut self,
+        base: String,
+        head: String,
+    ) -> oneshot::Receiver<Result<CommitDiff>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.diff_refs(base, head, cx).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitDiffRefs {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            base,
+                            head,
+                        })
+                        .await?;
+                    proto_to_commit_diff(response)
+                }
+            }
+        })
     }
 
-    pub fn reset(
-        &mut self,
-        commit: String,
-        reset_mode: ResetMode,
-        _cx: &mut App,
-    ) -> oneshot::Receiver<Result<()>> {
+    /// Loads the files with staged changes, as they are in HEAD and in the
+    /// index.
+    pub fn load_staged_diff(&mut self) -> oneshot::Receiver<Result<CommitDiff>> {
         let id = self.id;
-
-        self.send_job(None, move |git_repo, _| async move {
+        self.send_job(None, move |git_repo, cx| async move {
             match git_repo {
-                RepositoryState::Local {
-                    backend,
-                    environment,
-                    ..
-                } => backend.reset(commit, reset_mode, environment).await,
+                RepositoryState::Local { backend, .. } => backend.load_staged_diff(cx).await,
                 RepositoryState::Remote { project_id, client } => {
-                    client
-                        .request(proto::GitReset {
+                    let response = client
+                        .request(proto::GitLoadStagedDiff {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            commit,
-                            mode: match reset_mode {
-                                ResetMode::Soft => git_reset::ResetMode::Soft.into(),
-                                ResetMode::Mixed => git_reset::ResetMode::Mixed.into(),
-                            },
                         })
                         .await?;
-
-                    Ok(())
+                    proto_to_commit_diff(response)
                 }
             }
         })
     }
 
-    pub fn show(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDetails>> {
+    /// Loads the base, ours and theirs versions of a conflicted file.
+    pub fn load_merge_stages(&mut self, path: RepoPath) -> oneshot::Receiver<Result<MergeStages>> {
         let id = self.id;
         self.send_job(None, move |git_repo, _cx| async move {
             match git_repo {
-                RepositoryState::Local { backend, .. } => backend.show(commit).await,
+                RepositoryState::Local { backend, .. } => backend.load_merge_stages(path).await,
                 RepositoryState::Remote { project_id, client } => {
-                    let resp = client
-                        .request(proto::GitShow {
+                    let response = client
+                        .request(proto::GitLoadMergeStages {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            commit,
+                            path: path.to_proto(),
                         })
                         .await?;
-
-                    Ok(CommitDetails {
-                        sha: resp.sha.into(),
-                        message: resp.message.into(),
-                        commit_timestamp: resp.commit_timestamp,
-                        author_email: resp.author_email.into(),
-                        author_name: resp.author_name.into(),
+                    Ok(MergeStages {
+                        base: response.base,
+                        ours: response.ours,
+                        theirs: response.theirs,
                     })
                 }
             }
         })
     }
 
-    pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
+    /// Returns the best common ancestor of two revisions, if they have one.
+    pub fn merge_base(
+        &mut self,
+        first: String,
+        second: String,
+    ) -> oneshot::Receiver<Result<Option<String>>> {
         let id = self.id;
-        self.send_job(None, move |git_repo, cx| async move {
+        self.send_job(None, move |git_repo, _cx| async move {
             match git_repo {
-                RepositoryState::Local { backend, .. } => backend.load_commit(commit, cx).await,
-                RepositoryState::Remote {
-                    client, project_id, ..
-                } => {
+                RepositoryState::Local { backend, .. } => backend.merge_base(first, second).await,
+                RepositoryState::Remote { project_id, client } => {
                     let response = client
-                        .request(proto::LoadCommitDiff {
+                        .request(proto::GitMergeBase {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            commit,
+                            first,
+                            second,
                         })
                         .await?;
-                    Ok(CommitDiff {
-                        files: response
-                            .files
-                            .into_iter()
-                            .map(|file| {
-                                Ok(CommitFile {
-                                    path: RepoPath::from_proto(&file.path)?,
-                                    old_text: file.old_text,
-                                    new_text: file.new_text,
-                                })
-                            })
-                            .collect::<Result<Vec<_>>>()?,
-                    })
+                    Ok(response.sha)
                 }
             }
         })
@@ -3962,13 +5047,20 @@ impl Repository {
         let askpass_id = util::post_inc(&mut self.latest_askpass_id);
         let id = self.id;
 
+        let this = self.this.clone();
+
         self.send_job(Some("git fetch".into()), move |git_repo, cx| async move {
             match git_repo {
                 RepositoryState::Local {
                     backend,
                     environment,
                     ..
-                } => backend.fetch(fetch_options, askpass, environment, cx).await,
+                } => {
+                    track_lfs_transfers(this, &backend, environment, cx, async |environment, cx| {
+                        backend.fetch(fetch_options, askpass, environment, cx).await
+                    })
+                    .await
+                }
                 RepositoryState::Remote { project_id, client } => {
                     askpass_delegates.lock().insert(askpass_id, askpass);
                     let _defer = util::defer(|| {
@@ -4109,6 +5201,8 @@ impl Repository {
         let askpass_id = util::post_inc(&mut self.latest_askpass_id);
         let id = self.id;
 
+        let this = self.this.clone();
+
         self.send_job(
             Some(format!("git pull {} {}", remote, branch).into()),
             move |git_repo, cx| async move {
@@ -4118,15 +5212,24 @@ impl Repository {
                         environment,
                         ..
                     } => {
-                        backend
-                            .pull(
-                                branch.to_string(),
-                                remote.to_string(),
-                                askpass,
-                                environment.clone(),
-                                cx,
-                            )
-                            .await
+                        track_lfs_transfers(
+                            this,
+                            &backend,
+                            environment,
+                            cx,
+                            async |environment, cx| {
+                                backend
+                                    .pull(
+                                        branch.to_string(),
+                                        remote.to_string(),
+                                        askpass,
+                                        environment,
+                                        cx,
+                                    )
+                                    .await
+                            },
+                        )
+                        .await
                     }
                     RepositoryState::Remote { project_id, client } => {
                         askpass_delegates.lock().insert(askpass_id, askpass);
@@ -4155,6 +5258,17 @@ impl Repository {
         )
     }
 
+    /// Replaces the staged version of a file, removing it from the index if
+    /// `content` is `None`.
+    pub fn set_index_text(
+        &mut self,
+        path: RepoPath,
+        content: Option<String>,
+        cx: &mut Context<Self>,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        self.spawn_set_index_text_job(path, content, None, cx)
+    }
+
     fn spawn_set_index_text_job(
         &mut self,
         path: RepoPath,
@@ -4255,95 +5369,292 @@ impl Repository {
         })
     }
 
-    pub fn branches(&mut self) -> oneshot::Receiver<Result<Vec<Branch>>> {
+    pub fn branches(&mut self) -> oneshot::Receiver<Result<Vec<Branch>>> {
+        let id = self.id;
+        self.send_job(None, move |repo, _| async move {
+            match repo {
+                RepositoryState::Local { backend, .. } => backend.branches().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitGetBranches {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
+
+                    let branches = response
+                        .branches
+                        .into_iter()
+                        .map(|branch| proto_to_branch(&branch))
+                        .collect();
+
+                    Ok(branches)
+                }
+            }
+        })
+    }
+
+    pub fn default_branch(&mut self) -> oneshot::Receiver<Result<Option<SharedString>>> {
+        let id = self.id;
+        self.send_job(None, move |repo, _| async move {
+            match repo {
+                RepositoryState::Local { backend, .. } => backend.default_branch().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GetDefaultBranch {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
+
+                    anyhow::Ok(response.branch.map(SharedString::from))
+                }
+            }
+        })
+    }
+
+    pub fn diff(&mut self, diff_type: DiffType, _cx: &App) -> oneshot::Receiver<Result<String>> {
+        let id = self.id;
+        self.send_job(None, move |repo, _cx| async move {
+            match repo {
+                RepositoryState::Local { backend, .. } => backend.diff(diff_type).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitDiff {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            diff_type: match diff_type {
+                                DiffType::HeadToIndex => {
+                                    proto::git_diff::DiffType::HeadToIndex.into()
+                                }
+                                DiffType::HeadToWorktree => {
+                                    proto::git_diff::DiffType::HeadToWorktree.into()
+                                }
+                            },
+                        })
+                        .await?;
+
+                    Ok(response.diff)
+                }
+            }
+        })
+    }
+
+    pub fn create_branch(&mut self, branch_name: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git switch -c {branch_name}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => {
+                        backend.create_branch(branch_name).await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCreateBranch {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                branch_name,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn change_branch(&mut self, branch_name: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git switch {branch_name}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => {
+                        backend.change_branch(branch_name).await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitChangeBranch {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                branch_name,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn rename_branch(
+        &mut self,
+        branch: String,
+        new_name: String,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git branch -m {branch} {new_name}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => {
+                        backend.rename_branch(branch, new_name).await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRenameBranch {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                branch,
+                                new_name,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn delete_branch(&mut self, branch: String, force: bool) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        let flag = if force { "-D" } else { "-d" };
+        self.send_job(
+            Some(format!("git branch {flag} {branch}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => {
+                        backend.delete_branch(branch, force).await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitDeleteBranch {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                branch,
+                                force,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn lfs_locks(&mut self) -> oneshot::Receiver<Result<Vec<LfsLock>>> {
         let id = self.id;
-        self.send_job(None, move |repo, _| async move {
+        self.send_job(None, move |repo, _cx| async move {
             match repo {
-                RepositoryState::Local { backend, .. } => backend.branches().await,
+                RepositoryState::Local { backend, .. } => backend.lfs_locks().await,
                 RepositoryState::Remote { project_id, client } => {
                     let response = client
-                        .request(proto::GitGetBranches {
+                        .request(proto::GitLfsLocks {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
                         })
                         .await?;
-
-                    let branches = response
-                        .branches
-                        .into_iter()
-                        .map(|branch| proto_to_branch(&branch))
-                        .collect();
-
-                    Ok(branches)
+                    response.locks.iter().map(proto_to_lfs_lock).collect()
                 }
             }
         })
     }
 
-    pub fn default_branch(&mut self) -> oneshot::Receiver<Result<Option<SharedString>>> {
+    pub fn lfs_lock(&mut self, path: RepoPath) -> oneshot::Receiver<Result<()>> {
         let id = self.id;
-        self.send_job(None, move |repo, _| async move {
-            match repo {
-                RepositoryState::Local { backend, .. } => backend.default_branch().await,
-                RepositoryState::Remote { project_id, client } => {
-                    let response = client
-                        .request(proto::GetDefaultBranch {
-                            project_id: project_id.0,
-                            repository_id: id.to_proto(),
-                        })
-                        .await?;
+        self.send_job(
+            Some(format!("git lfs lock {}", path.as_unix_str()).into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => backend.lfs_lock(path).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitLfsLockPath {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                path: path.to_proto(),
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
 
-                    anyhow::Ok(response.branch.map(SharedString::from))
+    pub fn lfs_unlock(&mut self, path: RepoPath, force: bool) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git lfs unlock {}", path.as_unix_str()).into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => backend.lfs_unlock(path, force).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitLfsUnlockPath {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                path: path.to_proto(),
+                                force,
+                            })
+                            .await?;
+                        Ok(())
+                    }
                 }
-            }
-        })
+            },
+        )
     }
 
-    pub fn diff(&mut self, diff_type: DiffType, _cx: &App) -> oneshot::Receiver<Result<String>> {
+    pub fn lfs_metadata(&mut self, path: RepoPath) -> oneshot::Receiver<Result<LfsDiffMetadata>> {
         let id = self.id;
         self.send_job(None, move |repo, _cx| async move {
             match repo {
-                RepositoryState::Local { backend, .. } => backend.diff(diff_type).await,
+                RepositoryState::Local { backend, .. } => backend.lfs_metadata(path).await,
                 RepositoryState::Remote { project_id, client } => {
                     let response = client
-                        .request(proto::GitDiff {
+                        .request(proto::GitLfsMetadata {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            diff_type: match diff_type {
-                                DiffType::HeadToIndex => {
-                                    proto::git_diff::DiffType::HeadToIndex.into()
-                                }
-                                DiffType::HeadToWorktree => {
-                                    proto::git_diff::DiffType::HeadToWorktree.into()
-                                }
-                            },
+                            path: path.to_proto(),
                         })
                         .await?;
-
-                    Ok(response.diff)
+                    Ok(LfsDiffMetadata {
+                        head: response.head.as_ref().map(proto_to_lfs_file_metadata),
+                        worktree: response.worktree.as_ref().map(proto_to_lfs_file_metadata),
+                    })
                 }
             }
         })
     }
 
-    pub fn create_branch(&mut self, branch_name: String) -> oneshot::Receiver<Result<()>> {
+    /// The progress of the Git LFS transfer in the running fetch or pull.
+    pub fn lfs_transfer(&self) -> Option<&LfsTransferProgress> {
+        self.lfs_transfer.as_ref()
+    }
+
+    pub fn merge_squash(&mut self, branch: String) -> oneshot::Receiver<Result<()>> {
         let id = self.id;
         self.send_job(
-            Some(format!("git switch -c {branch_name}").into()),
+            Some(format!("git merge --squash {branch}").into()),
             move |repo, _cx| async move {
                 match repo {
-                    RepositoryState::Local { backend, .. } => {
-                        backend.create_branch(branch_name).await
-                    }
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.merge_squash(branch, environment).await,
                     RepositoryState::Remote { project_id, client } => {
                         client
-                            .request(proto::GitCreateBranch {
+                            .request(proto::GitMergeSquash {
                                 project_id: project_id.0,
                                 repository_id: id.to_proto(),
-                                branch_name,
+                                branch,
                             })
                             .await?;
-
                         Ok(())
                     }
                 }
@@ -4351,24 +5662,30 @@ impl Repository {
         )
     }
 
-    pub fn change_branch(&mut self, branch_name: String) -> oneshot::Receiver<Result<()>> {
+    pub fn create_worktree(
+        &mut self,
+        branch: String,
+        path: PathBuf,
+        base: Option<String>,
+    ) -> oneshot::Receiver<Result<()>> {
         let id = self.id;
         self.send_job(
-            Some(format!("git switch {branch_name}").into()),
+            Some(format!("git worktree add -b {branch} {}", path.display()).into()),
             move |repo, _cx| async move {
                 match repo {
                     RepositoryState::Local { backend, .. } => {
-                        backend.change_branch(branch_name).await
+                        backend.create_worktree(branch, path, base).await
                     }
                     RepositoryState::Remote { project_id, client } => {
                         client
-                            .request(proto::GitChangeBranch {
+                            .request(proto::GitCreateWorktree {
                                 project_id: project_id.0,
                                 repository_id: id.to_proto(),
-                                branch_name,
+                                branch,
+                                path: path.to_string_lossy().into_owned(),
+                                base,
                             })
                             .await?;
-
                         Ok(())
                     }
                 }
@@ -4376,29 +5693,47 @@ impl Repository {
         )
     }
 
-    pub fn rename_branch(
-        &mut self,
-        branch: String,
-        new_name: String,
-    ) -> oneshot::Receiver<Result<()>> {
+    pub fn remove_worktree(&mut self, path: PathBuf, force: bool) -> oneshot::Receiver<Result<()>> {
         let id = self.id;
         self.send_job(
-            Some(format!("git branch -m {branch} {new_name}").into()),
+            Some(format!("git worktree remove {}", path.display()).into()),
             move |repo, _cx| async move {
                 match repo {
                     RepositoryState::Local { backend, .. } => {
-                        backend.rename_branch(branch, new_name).await
+                        backend.remove_worktree(path, force).await
                     }
                     RepositoryState::Remote { project_id, client } => {
                         client
-                            .request(proto::GitRenameBranch {
+                            .request(proto::GitRemoveWorktree {
                                 project_id: project_id.0,
                                 repository_id: id.to_proto(),
-                                branch,
-                                new_name,
+                                path: path.to_string_lossy().into_owned(),
+                                force,
                             })
                             .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
 
+    /// Cleans up the worktrees whose directories were deleted without
+    /// `git worktree remove`.
+    pub fn prune_worktrees(&mut self) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git worktree prune".into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local { backend, .. } => backend.prune_worktrees().await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitPruneWorktrees {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                            })
+                            .await?;
                         Ok(())
                     }
                 }
@@ -4470,6 +5805,19 @@ impl Repository {
 
         self.snapshot.merge.conflicted_paths = conflicted_paths;
         self.snapshot.merge.message = update.merge_message.map(SharedString::from);
+        self.snapshot.merge.rebasing = update.rebasing;
+        self.snapshot.lfs_paths = TreeSet::from_ordered_entries(
+            update
+                .lfs_paths
+                .iter()
+                .filter_map(|path| RepoPath::from_proto(path).log_err()),
+        );
+        self.snapshot.submodules = update
+            .submodules
+            .iter()
+            .filter_map(|submodule| proto_to_submodule(submodule).log_err())
+            .collect();
+        self.snapshot.worktrees = update.worktrees.iter().map(proto_to_worktree).collect();
         self.snapshot.stash_entries = GitStash {
             entries: update
                 .stash_entries
@@ -4553,7 +5901,7 @@ impl Repository {
                 let Some(this) = this.upgrade() else {
                     return Ok(());
                 };
-                let RepositoryState::Local { backend, .. } = state else {
+                let RepositoryState::Local { backend, fs, .. } = state else {
                     bail!("not a local repository")
                 };
                 let (snapshot, events) = this
@@ -4564,6 +5912,7 @@ impl Repository {
                             this.work_directory_abs_path.clone(),
                             this.snapshot.clone(),
                             backend.clone(),
+                            fs.clone(),
                         )
                     })?
                     .await?;
@@ -4608,11 +5957,14 @@ impl Repository {
                 });
             let search_paths = environment.get("PATH").map(|val| val.to_owned());
             let backend = cx
-                .background_spawn(async move {
-                    let system_git_binary_path = search_paths.and_then(|search_paths| which::which_in("git", Some(search_paths), &work_directory_abs_path).ok())
-                        .or_else(|| which::which("git").ok());
-                    fs.open_repo(&dot_git_abs_path, system_git_binary_path.as_deref())
-                        .with_context(|| format!("opening repository at {dot_git_abs_path:?}"))
+                .background_spawn({
+                    let fs = fs.clone();
+                    async move {
+                        let system_git_binary_path = search_paths.and_then(|search_paths| which::which_in("git", Some(search_paths), &work_directory_abs_path).ok())
+                            .or_else(|| which::which("git").ok());
+                        fs.open_repo(&dot_git_abs_path, system_git_binary_path.as_deref())
+                            .with_context(|| format!("opening repository at {dot_git_abs_path:?}"))
+                    }
                 })
                 .await?;
 
@@ -4628,6 +5980,7 @@ impl Repository {
             let state = RepositoryState::Local {
                 backend,
                 environment: Arc::new(environment),
+                fs,
             };
             let mut jobs = VecDeque::new();
             loop {
@@ -4794,6 +6147,7 @@ impl Repository {
                 }
                 let statuses = backend.status(&paths).await?;
                 let stash_entries = backend.stash_entries().await?;
+                let changed_lfs_paths = statuses.lfs_paths.clone();
 
                 let changed_path_statuses = cx
                     .background_spawn(async move {
@@ -4825,9 +6179,19 @@ impl Repository {
                     .await;
 
                 this.update(&mut cx, |this, cx| {
+                    let mut lfs_paths = this.snapshot.lfs_paths.clone();
+                    for lfs_path in this.snapshot.lfs_paths.iter() {
+                        if paths.iter().any(|path| lfs_path.starts_with(path)) {
+                            lfs_paths.remove(lfs_path);
+                        }
+                    }
+                    lfs_paths.extend(changed_lfs_paths.iter().cloned());
+
                     let needs_update = !changed_path_statuses.is_empty()
-                        || this.snapshot.stash_entries != stash_entries;
+                        || this.snapshot.stash_entries != stash_entries
+                        || this.snapshot.lfs_paths != lfs_paths;
                     this.snapshot.stash_entries = stash_entries;
+                    this.snapshot.lfs_paths = lfs_paths;
                     if !changed_path_statuses.is_empty() {
                         this.snapshot
                             .statuses_by_path
@@ -4865,6 +6229,62 @@ impl Repository {
     }
 }
 
+/// Runs a fetch or pull with Git LFS writing its progress to a file, and
+/// reports the latest progress in the file as the repository's `lfs_transfer`.
+async fn track_lfs_transfers<R>(
+    this: WeakEntity<Repository>,
+    backend: &Arc<dyn GitRepository>,
+    environment: Arc<HashMap<String, String>>,
+    mut cx: AsyncApp,
+    run: impl AsyncFnOnce(Arc<HashMap<String, String>>, AsyncApp) -> R,
+) -> R {
+    let progress_path = backend.path().join(LFS_PROGRESS_FILE);
+    let mut environment = (*environment).clone();
+    environment.insert(
+        "GIT_LFS_PROGRESS".into(),
+        progress_path.to_string_lossy().into_owned(),
+    );
+
+    let poll_progress = cx.spawn({
+        let this = this.clone();
+        let progress_path = progress_path.clone();
+        async move |cx| {
+            let mut last_progress = None;
+            loop {
+                cx.background_executor()
+                    .timer(LFS_PROGRESS_POLL_INTERVAL)
+                    .await;
+                let progress = smol::fs::read_to_string(&progress_path)
+                    .await
+                    .ok()
+                    .and_then(|contents| LfsTransferProgress::last_in(&contents));
+                if progress != last_progress {
+                    this.update(cx, |this, cx| {
+                        this.lfs_transfer = progress.clone();
+                        cx.emit(JobsUpdated);
+                        cx.notify();
+                    })
+                    .ok();
+                    last_progress = progress;
+                }
+            }
+        }
+    });
+
+    let result = run(Arc::new(environment), cx.clone()).await;
+
+    drop(poll_progress);
+    smol::fs::remove_file(&progress_path).await.ok();
+    this.update(&mut cx, |this, cx| {
+        if this.lfs_transfer.take().is_some() {
+            cx.emit(JobsUpdated);
+            cx.notify();
+        }
+    })
+    .ok();
+    result
+}
+
 fn get_permalink_in_rust_registry_src(
     provider_registry: Arc<GitHostingProviderRegistry>,
     path: PathBuf,
@@ -5026,12 +6446,7 @@ fn branch_to_proto(branch: &git::repository::Branch) -> proto::Branch {
         most_recent_commit: branch
             .most_recent_commit
             .as_ref()
-            .map(|commit| proto::CommitSummary {
-                sha: commit.sha.to_string(),
-                subject: commit.subject.to_string(),
-                commit_timestamp: commit.commit_timestamp,
-                author_name: commit.author_name.to_string(),
-            }),
+            .map(commit_summary_to_proto),
     }
 }
 
@@ -5067,6 +6482,282 @@ fn proto_to_branch(proto: &proto::Branch) -> git::repository::Branch {
     }
 }
 
+fn commit_diff_to_proto(diff: CommitDiff) -> proto::LoadCommitDiffResponse {
+    proto::LoadCommitDiffResponse {
+        files: diff
+            .files
+            .into_iter()
+            .map(|file| proto::CommitFile {
+                path: file.path.to_proto(),
+                old_text: file.old_text,
+                new_text: file.new_text,
+            })
+            .collect(),
+    }
+}
+
+fn proto_to_commit_diff(response: proto::LoadCommitDiffResponse) -> Result<CommitDiff> {
+    Ok(CommitDiff {
+        files: response
+            .files
+            .into_iter()
+            .map(|file| {
+                Ok(CommitFile {
+                    path: RepoPath::from_proto(&file.path)?,
+                    old_text: file.old_text,
+                    new_text: file.new_text,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+    })
+}
+
+fn commit_summary_to_proto(commit: &CommitSummary) -> proto::CommitSummary {
+    proto::CommitSummary {
+        sha: commit.sha.to_string(),
+        subject: commit.subject.to_string(),
+        commit_timestamp: commit.commit_timestamp,
+        author_name: commit.author_name.to_string(),
+        has_parent: commit.has_parent,
+    }
+}
+
+fn proto_to_commit_summary(commit: &proto::CommitSummary) -> CommitSummary {
+    CommitSummary {
+        sha: commit.sha.to_string().into(),
+        subject: commit.subject.to_string().into(),
+        commit_timestamp: commit.commit_timestamp,
+        author_name: commit.author_name.to_string().into(),
+        has_parent: commit.has_parent,
+    }
+}
+
+fn log_options_to_proto(
+    project_id: ProjectId,
+    repository_id: RepositoryId,
+    options: LogOptions,
+) -> proto::GitLog {
+    proto::GitLog {
+        project_id: project_id.0,
+        repository_id: repository_id.to_proto(),
+        branch: options.branch,
+        author: options.author,
+        path: options.path.map(|path| path.to_proto()),
+        start_line: options.line_range.as_ref().map(|range| *range.start()),
+        end_line: options.line_range.as_ref().map(|range| *range.end()),
+        skip: options.skip as u64,
+        limit: options.limit as u64,
+    }
+}
+
+fn log_options_from_proto(request: proto::GitLog) -> Result<LogOptions> {
+    Ok(LogOptions {
+        branch: request.branch,
+        author: request.author,
+        path: request
+            .path
+            .as_deref()
+            .map(RepoPath::from_proto)
+            .transpose()?,
+        line_range: request
+            .start_line
+            .zip(request.end_line)
+            .map(|(start, end)| start..=end),
+        skip: request.skip as usize,
+        limit: request.limit as usize,
+    })
+}
+
+fn rebase_todo_entry_to_proto(entry: &RebaseTodoEntry) -> proto::RebaseTodoEntry {
+    let action = match entry.action {
+        RebaseAction::Pick => rebase_todo_entry::RebaseAction::Pick,
+        RebaseAction::Reword => rebase_todo_entry::RebaseAction::Reword,
+        RebaseAction::Squash => rebase_todo_entry::RebaseAction::Squash,
+        RebaseAction::Fixup => rebase_todo_entry::RebaseAction::Fixup,
+        RebaseAction::Drop => rebase_todo_entry::RebaseAction::Drop,
+    };
+    proto::RebaseTodoEntry {
+        action: action.into(),
+        sha: entry.sha.to_string(),
+        message: entry.message.clone(),
+    }
+}
+
+fn proto_to_rebase_todo_entry(entry: &proto::RebaseTodoEntry) -> RebaseTodoEntry {
+    let action = match entry.action() {
+        rebase_todo_entry::RebaseAction::Pick => RebaseAction::Pick,
+        rebase_todo_entry::RebaseAction::Reword => RebaseAction::Reword,
+        rebase_todo_entry::RebaseAction::Squash => RebaseAction::Squash,
+        rebase_todo_entry::RebaseAction::Fixup => RebaseAction::Fixup,
+        rebase_todo_entry::RebaseAction::Drop => RebaseAction::Drop,
+    };
+    RebaseTodoEntry {
+        action,
+        sha: entry.sha.clone().into(),
+        message: entry.message.clone(),
+    }
+}
+
+fn tag_to_proto(tag: &Tag) -> proto::GitTag {
+    proto::GitTag {
+        name: tag.name.to_string(),
+        sha: tag.sha.to_string(),
+        subject: tag.subject.to_string(),
+        message: tag.message.as_ref().map(|message| message.to_string()),
+        timestamp: tag.timestamp,
+    }
+}
+
+fn proto_to_tag(tag: &proto::GitTag) -> Tag {
+    Tag {
+        name: tag.name.clone().into(),
+        sha: tag.sha.clone().into(),
+        subject: tag.subject.clone().into(),
+        message: tag.message.clone().map(SharedString::from),
+        timestamp: tag.timestamp,
+    }
+}
+
+fn bisect_state_to_proto(state: &BisectState) -> proto::GitBisectState {
+    proto::GitBisectState {
+        bad: state.bad.as_ref().map(|sha| sha.to_string()),
+        good: state.good.iter().map(|sha| sha.to_string()).collect(),
+        skipped: state.skipped.iter().map(|sha| sha.to_string()).collect(),
+        remaining: state
+            .remaining
+            .iter()
+            .map(commit_summary_to_proto)
+            .collect(),
+    }
+}
+
+fn proto_to_bisect_state(state: &proto::GitBisectState) -> BisectState {
+    BisectState {
+        bad: state.bad.clone().map(SharedString::from),
+        good: state.good.iter().cloned().map(SharedString::from).collect(),
+        skipped: state
+            .skipped
+            .iter()
+            .cloned()
+            .map(SharedString::from)
+            .collect(),
+        remaining: state
+            .remaining
+            .iter()
+            .map(proto_to_commit_summary)
+            .collect(),
+    }
+}
+
+fn submodule_to_proto(submodule: &Submodule) -> proto::GitSubmodule {
+    proto::GitSubmodule {
+        path: submodule.path.to_proto(),
+        sha: submodule.sha.to_string(),
+        status: match submodule.status {
+            SubmoduleStatus::Uninitialized => git_submodule::Status::Uninitialized,
+            SubmoduleStatus::UpToDate => git_submodule::Status::UpToDate,
+            SubmoduleStatus::OutOfDate => git_submodule::Status::OutOfDate,
+            SubmoduleStatus::Conflicted => git_submodule::Status::Conflicted,
+        }
+        .into(),
+        dirty: submodule.dirty,
+    }
+}
+
+fn worktree_to_proto(worktree: &GitWorktree) -> proto::GitWorktree {
+    proto::GitWorktree {
+        path: worktree.path.to_string_lossy().into_owned(),
+        sha: worktree.sha.as_ref().map(|sha| sha.to_string()),
+        branch: worktree.branch.as_ref().map(|branch| branch.to_string()),
+        is_main: worktree.is_main,
+        locked: worktree.locked,
+        prunable: worktree.prunable,
+    }
+}
+
+fn proto_to_worktree(worktree: &proto::GitWorktree) -> GitWorktree {
+    GitWorktree {
+        path: PathBuf::from(&worktree.path),
+        sha: worktree.sha.clone().map(SharedString::from),
+        branch: worktree.branch.clone().map(SharedString::from),
+        is_main: worktree.is_main,
+        locked: worktree.locked,
+        prunable: worktree.prunable,
+    }
+}
+
+fn proto_to_submodule(submodule: &proto::GitSubmodule) -> Result<Submodule> {
+    Ok(Submodule {
+        path: RepoPath::from_proto(&submodule.path)?,
+        sha: submodule.sha.clone().into(),
+        status: match submodule.status() {
+            git_submodule::Status::Uninitialized => SubmoduleStatus::Uninitialized,
+            git_submodule::Status::UpToDate => SubmoduleStatus::UpToDate,
+            git_submodule::Status::OutOfDate => SubmoduleStatus::OutOfDate,
+            git_submodule::Status::Conflicted => SubmoduleStatus::Conflicted,
+        },
+        dirty: submodule.dirty,
+    })
+}
+
+fn lfs_lock_to_proto(lock: &LfsLock) -> proto::GitLfsLock {
+    proto::GitLfsLock {
+        id: lock.id.to_string(),
+        path: lock.path.to_proto(),
+        owner: lock.owner.to_string(),
+        ours: lock.ours,
+    }
+}
+
+fn proto_to_lfs_lock(lock: &proto::GitLfsLock) -> Result<LfsLock> {
+    Ok(LfsLock {
+        id: lock.id.clone().into(),
+        path: RepoPath::from_proto(&lock.path)?,
+        owner: lock.owner.clone().into(),
+        ours: lock.ours,
+    })
+}
+
+fn lfs_file_metadata_to_proto(metadata: &LfsFileMetadata) -> proto::GitLfsFileMetadata {
+    proto::GitLfsFileMetadata {
+        oid: metadata.oid.clone(),
+        size: metadata.size,
+        audio: metadata
+            .audio
+            .as_ref()
+            .map(|audio| git_lfs_file_metadata::AudioInfo {
+                format: match audio.format {
+                    AudioFormat::Wav => git_lfs_file_metadata::Format::Wav,
+                    AudioFormat::Aiff => git_lfs_file_metadata::Format::Aiff,
+                    AudioFormat::Flac => git_lfs_file_metadata::Format::Flac,
+                }
+                .into(),
+                channels: audio.channels as u32,
+                sample_rate: audio.sample_rate,
+                bits_per_sample: audio.bits_per_sample as u32,
+                duration_nanos: audio.duration.as_nanos() as u64,
+            }),
+    }
+}
+
+fn proto_to_lfs_file_metadata(metadata: &proto::GitLfsFileMetadata) -> LfsFileMetadata {
+    LfsFileMetadata {
+        oid: metadata.oid.clone(),
+        size: metadata.size,
+        audio: metadata.audio.as_ref().map(|audio| AudioInfo {
+            format: match audio.format() {
+                git_lfs_file_metadata::Format::Wav => AudioFormat::Wav,
+                git_lfs_file_metadata::Format::Aiff => AudioFormat::Aiff,
+                git_lfs_file_metadata::Format::Flac => AudioFormat::Flac,
+            },
+            channels: audio.channels as u16,
+            sample_rate: audio.sample_rate,
+            bits_per_sample: audio.bits_per_sample as u16,
+            duration: Duration::from_nanos(audio.duration_nanos),
+        }),
+    }
+}
+
 fn commit_details_to_proto(commit: &CommitDetails) -> proto::GitCommitDetails {
     proto::GitCommitDetails {
         sha: commit.sha.to_string(),
@@ -5092,6 +6783,7 @@ async fn compute_snapshot(
     work_directory_abs_path: Arc<Path>,
     prev_snapshot: RepositorySnapshot,
     backend: Arc<dyn GitRepository>,
+    fs: Arc<dyn Fs>,
 ) -> Result<(RepositorySnapshot, Vec<RepositoryEvent>)> {
     let mut events = Vec::new();
     let branches = backend.branches().await?;
@@ -5108,13 +6800,90 @@ async fn compute_snapshot(
             }),
         (),
     );
+    let lfs_paths = TreeSet::from_ordered_entries(statuses.lfs_paths.iter().cloned());
+    let head_sha = backend.head_sha().await;
+    let common_dir_abs_path = backend.main_repository_path();
+    let metadata_mtimes =
+        RepositoryMetadataMtimes::load(fs.as_ref(), &work_directory_abs_path, &common_dir_abs_path)
+            .await;
+
+    // Listing the submodules and worktrees runs git, so the previous lists
+    // are kept until the files they're read from or the checked out commit
+    // change. Submodules also change when their own commit changes, which
+    // shows in their status.
+    let prev_metadata_mtimes = prev_snapshot.metadata_mtimes.as_ref();
+    let head_changed = branch != prev_snapshot.branch
+        || head_sha.as_deref()
+            != prev_snapshot
+                .head_commit
+                .as_ref()
+                .map(|commit| commit.sha.as_ref());
+    let submodules = if metadata_mtimes.gitmodules.is_none() {
+        Vec::new()
+    } else if !head_changed
+        && prev_metadata_mtimes.is_some_and(|prev| prev.gitmodules == metadata_mtimes.gitmodules)
+        && prev_snapshot.submodules.iter().all(|submodule| {
+            let key = PathKey(submodule.path.0.clone());
+            prev_snapshot.statuses_by_path.get(&key, ()) == statuses_by_path.get(&key, ())
+        })
+    {
+        prev_snapshot.submodules.clone()
+    } else {
+        backend.submodules().await.log_err().unwrap_or_default()
+    };
+    let mut worktrees_changed = head_changed
+        || prev_snapshot.worktrees.is_empty()
+        || prev_metadata_mtimes
+            .is_none_or(|prev| prev.linked_worktrees != metadata_mtimes.linked_worktrees);
+    for worktree in prev_snapshot
+        .worktrees
+        .iter()
+        .filter(|worktree| !worktree.is_main)
+    {
+        if worktrees_changed {
+            break;
+        }
+        worktrees_changed = worktree.prunable == fs.is_dir(&worktree.path).await;
+    }
+    let worktrees = if worktrees_changed {
+        backend
+            .worktrees()
+            .await
+            .log_err()
+            .filter(|worktrees| !worktrees.is_empty())
+            .unwrap_or_else(|| prev_snapshot.worktrees.clone())
+    } else {
+        prev_snapshot.worktrees.clone()
+    };
+    // The main worktree is always listed, even if git couldn't list them.
+    let worktrees = if worktrees.is_empty() {
+        vec![GitWorktree {
+            path: common_dir_abs_path
+                .parent()
+                .unwrap_or(&work_directory_abs_path)
+                .to_path_buf(),
+            sha: head_sha.clone().map(Into::into),
+            branch: branch
+                .as_ref()
+                .map(|branch| branch.name().to_string().into()),
+            is_main: true,
+            locked: false,
+            prunable: false,
+        }]
+    } else {
+        worktrees
+    };
     let (merge_details, merge_heads_changed) =
         MergeDetails::load(&backend, &statuses_by_path, &prev_snapshot).await?;
     log::debug!("new merge details (changed={merge_heads_changed:?}): {merge_details:?}");
 
     if merge_heads_changed
+        || merge_details.rebasing != prev_snapshot.merge.rebasing
         || branch != prev_snapshot.branch
         || statuses_by_path != prev_snapshot.statuses_by_path
+        || lfs_paths != prev_snapshot.lfs_paths
+        || submodules != prev_snapshot.submodules
+        || worktrees != prev_snapshot.worktrees
     {
         events.push(RepositoryEvent::Updated {
             full_scan: true,
@@ -5129,7 +6898,7 @@ async fn compute_snapshot(
     }
 
     // Useful when branch is None in detached head state
-    let head_commit = match backend.head_sha().await {
+    let head_commit = match head_sha {
         Some(head_sha) => backend.show(head_sha).await.log_err(),
         None => None,
     };
@@ -5141,6 +6910,9 @@ async fn compute_snapshot(
     let snapshot = RepositorySnapshot {
         id,
         statuses_by_path,
+        lfs_paths,
+        submodules,
+        worktrees,
         work_directory_abs_path,
         path_style: prev_snapshot.path_style,
         scan_id: prev_snapshot.scan_id + 1,
@@ -5150,6 +6922,7 @@ async fn compute_snapshot(
         remote_origin_url,
         remote_upstream_url,
         stash_entries,
+        metadata_mtimes: Some(metadata_mtimes),
     };
 
     Ok((snapshot, events))
@@ -9503,6 +9503,64 @@ async fn test_git_worktrees_and_submodules(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_git_worktrees_listed_after_change(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/project"), json!({".git": {}, "a.txt": "A"}))
+        .await;
+    let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+    let scan_complete = project.update(cx, |project, cx| project.git_scans_complete(cx));
+    scan_complete.await;
+    let repository = project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+    let worktrees = |cx: &mut gpui::TestAppContext| {
+        repository.read_with(cx, |repository, _| {
+            repository
+                .worktrees
+                .iter()
+                .map(|worktree| (worktree.path.clone(), worktree.locked))
+                .collect::<Vec<_>>()
+        })
+    };
+    // The main worktree is always listed.
+    assert_eq!(worktrees(cx), [(PathBuf::from(path!("/project")), false)]);
+
+    repository
+        .update(cx, |repository, _| {
+            repository.create_worktree("feature".into(), PathBuf::from(path!("/feature")), None)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        worktrees(cx),
+        [
+            (PathBuf::from(path!("/project")), false),
+            (PathBuf::from(path!("/feature")), false),
+        ]
+    );
+
+    // Rescans that find the worktrees' files unchanged don't list them again.
+    fs.with_git_state(Path::new(path!("/project/.git")), true, |state| {
+        state.worktrees[0].locked = true;
+    })
+    .unwrap();
+    cx.run_until_parked();
+    assert_eq!(worktrees(cx)[1], (PathBuf::from(path!("/feature")), false));
+
+    repository
+        .update(cx, |repository, _| {
+            repository.remove_worktree(PathBuf::from(path!("/feature")), true)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(worktrees(cx), [(PathBuf::from(path!("/project")), false)]);
+}
+
 #[gpui::test]
 async fn test_repository_deduplication(cx: &mut gpui::TestAppContext) {
     init_test(cx);
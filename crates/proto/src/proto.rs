@@ -289,6 +289,41 @@ messages!(
     (GitCheckoutFiles, Background),
     (GitShow, Background),
     (GitCommitDetails, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitRebase, Background),
+    (GitRebaseCommand, Background),
+    (GitCherryPick, Background),
+    (GitRevert, Background),
+    (GitGetTags, Background),
+    (GitTagsResponse, Background),
+    (GitCreateTag, Background),
+    (GitDeleteTag, Background),
+    (GitBisectStart, Background),
+    (GitBisectMark, Background),
+    (GitBisectReset, Background),
+    (GitGetBisectState, Background),
+    (GitBisectStateResponse, Background),
+    (GitSubmoduleCommand, Background),
+    (GitGetSubmoduleRange, Background),
+    (GitSubmoduleRangeResponse, Background),
+    (GitDiffRefs, Background),
+    (GitLoadStagedDiff, Background),
+    (GitMergeBase, Background),
+    (GitMergeBaseResponse, Background),
+    (GitLoadMergeStages, Background),
+    (GitMergeStagesResponse, Background),
+    (GitCreateWorktree, Background),
+    (GitRemoveWorktree, Background),
+    (GitPruneWorktrees, Background),
+    (GitDeleteBranch, Background),
+    (GitMergeSquash, Background),
+    (GitLfsLocks, Background),
+    (GitLfsLocksResponse, Background),
+    (GitLfsLockPath, Background),
+    (GitLfsUnlockPath, Background),
+    (GitLfsMetadata, Background),
+    (GitLfsMetadataResponse, Background),
     (SetIndexText, Background),
     (Push, Background),
     (Fetch, Background),
@@ -486,6 +521,33 @@ request_messages!(
     (GitCreateBranch, Ack),
     (GitChangeBranch, Ack),
     (GitRenameBranch, Ack),
+    (GitLog, GitLogResponse),
+    (GitRebase, Ack),
+    (GitRebaseCommand, Ack),
+    (GitCherryPick, Ack),
+    (GitRevert, Ack),
+    (GitGetTags, GitTagsResponse),
+    (GitCreateTag, Ack),
+    (GitDeleteTag, Ack),
+    (GitBisectStart, Ack),
+    (GitBisectMark, Ack),
+    (GitBisectReset, Ack),
+    (GitGetBisectState, GitBisectStateResponse),
+    (GitSubmoduleCommand, Ack),
+    (GitGetSubmoduleRange, GitSubmoduleRangeResponse),
+    (GitDiffRefs, LoadCommitDiffResponse),
+    (GitLoadStagedDiff, LoadCommitDiffResponse),
+    (GitMergeBase, GitMergeBaseResponse),
+    (GitLoadMergeStages, GitMergeStagesResponse),
+    (GitCreateWorktree, Ack),
+    (GitRemoveWorktree, Ack),
+    (GitPruneWorktrees, Ack),
+    (GitDeleteBranch, Ack),
+    (GitMergeSquash, Ack),
+    (GitLfsLocks, GitLfsLocksResponse),
+    (GitLfsLockPath, Ack),
+    (GitLfsUnlockPath, Ack),
+    (GitLfsMetadata, GitLfsMetadataResponse),
     (CheckForPushedCommits, CheckForPushedCommitsResponse),
     (GitDiff, GitDiffResponse),
     (GitInit, Ack),
@@ -643,6 +705,33 @@ entity_messages!(
     GitChangeBranch,
     GitRenameBranch,
     GitCreateBranch,
+    GitLog,
+    GitRebase,
+    GitRebaseCommand,
+    GitCherryPick,
+    GitRevert,
+    GitGetTags,
+    GitCreateTag,
+    GitDeleteTag,
+    GitBisectStart,
+    GitBisectMark,
+    GitBisectReset,
+    GitGetBisectState,
+    GitSubmoduleCommand,
+    GitGetSubmoduleRange,
+    GitDiffRefs,
+    GitLoadStagedDiff,
+    GitMergeBase,
+    GitLoadMergeStages,
+    GitCreateWorktree,
+    GitRemoveWorktree,
+    GitPruneWorktrees,
+    GitDeleteBranch,
+    GitMergeSquash,
+    GitLfsLocks,
+    GitLfsLockPath,
+    GitLfsUnlockPath,
+    GitLfsMetadata,
     CheckForPushedCommits,
     GitDiff,
     GitInit,
@@ -2,6 +2,7 @@ pub mod disconnected_overlay;
 mod remote_connections;
 mod remote_servers;
 mod ssh_config;
+mod worktree_picker;
 
 #[cfg(target_os = "windows")]
 mod wsl_picker;
@@ -134,6 +135,7 @@ pub fn init(cx: &mut App) {
     });
 
     cx.observe_new(DisconnectedOverlay::register).detach();
+    cx.observe_new(worktree_picker::register).detach();
 }
 
 pub struct RecentProjects {
@@ -0,0 +1,329 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::worktree::GitWorktree;
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task,
+    WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use workspace::{ModalView, OpenOptions, Workspace, notifications::DetachAndPromptErr as _};
+use zed_actions::git::OpenWorktree;
+
+use crate::open_remote_project;
+
+pub(crate) fn register(
+    workspace: &mut Workspace,
+    _: Option<&mut Window>,
+    _: &mut Context<Workspace>,
+) {
+    workspace.register_action(|workspace, action: &OpenWorktree, window, cx| {
+        if let Some(path) = action.path.clone() {
+            open_worktree(workspace, path, window, cx).detach_and_prompt_err(
+                "Failed to open worktree",
+                window,
+                cx,
+                |_, _, _| None,
+            );
+            return;
+        }
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        let workspace_handle = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, |window, cx| {
+            WorktreePicker::new(repository, workspace_handle, window, cx)
+        });
+    });
+}
+
+/// Opens the worktree at `path` in a new window, connected to the same
+/// remote server as the current project if there is one.
+fn open_worktree(
+    workspace: &Workspace,
+    path: PathBuf,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let project = workspace.project().read(cx);
+    let app_state = workspace.app_state().clone();
+    if let Some(connection_options) = project.remote_connection_options(cx) {
+        cx.spawn_in(window, async move |_, cx| {
+            open_remote_project(
+                connection_options,
+                vec![path],
+                app_state,
+                OpenOptions::default(),
+                cx,
+            )
+            .await
+        })
+    } else if project.is_local() {
+        let open = workspace::open_paths(
+            &[path],
+            app_state,
+            OpenOptions {
+                open_new_workspace: Some(true),
+                ..Default::default()
+            },
+            cx,
+        );
+        cx.spawn(async move |_, _| {
+            open.await?;
+            Ok(())
+        })
+    } else {
+        Task::ready(Err(anyhow!(
+            "Worktrees can only be opened by the host of a shared project"
+        )))
+    }
+}
+
+/// Lists the other worktrees of the active repository, to open one of them in
+/// a new window. A query that doesn't match an existing worktree can be used
+/// to create one with a new branch of that name.
+pub struct WorktreePicker {
+    picker: Entity<Picker<WorktreePickerDelegate>>,
+    _subscription: Subscription,
+}
+
+impl WorktreePicker {
+    fn new(
+        repository: Entity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot = repository.read(cx);
+        let current_path = snapshot
+            .current_worktree()
+            .map(|worktree| worktree.path.clone());
+        let worktrees = snapshot
+            .worktrees
+            .iter()
+            .filter(|worktree| !worktree.prunable && current_path.as_ref() != Some(&worktree.path))
+            .cloned()
+            .collect();
+        let main_worktree_path = snapshot
+            .worktrees
+            .iter()
+            .find(|worktree| worktree.is_main)
+            .map(|worktree| worktree.path.clone());
+
+        let delegate = WorktreePickerDelegate {
+            repository,
+            workspace,
+            worktrees,
+            main_worktree_path,
+            matches: Vec::new(),
+            new_branch: None,
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for WorktreePicker {}
+
+impl EventEmitter<DismissEvent> for WorktreePicker {}
+
+impl Focusable for WorktreePicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for WorktreePicker {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("WorktreePicker")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+pub struct WorktreePickerDelegate {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    worktrees: Vec<GitWorktree>,
+    main_worktree_path: Option<PathBuf>,
+    matches: Vec<StringMatch>,
+    /// The branch to create a worktree for, when the query doesn't name an
+    /// existing worktree.
+    new_branch: Option<String>,
+    selected_index: usize,
+}
+
+impl WorktreePickerDelegate {
+    /// Where a worktree for `branch` is created: next to the main worktree,
+    /// named after both.
+    fn new_worktree_path(&self, branch: &str) -> Option<PathBuf> {
+        let main_path = self.main_worktree_path.as_ref()?;
+        let main_name = main_path.file_name()?.to_string_lossy();
+        let name = format!("{main_name}-{}", branch.replace('/', "-"));
+        Some(main_path.parent()?.join(name))
+    }
+}
+
+impl EventEmitter<DismissEvent> for WorktreePickerDelegate {}
+
+impl PickerDelegate for WorktreePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Open a worktree, or name a branch to create one…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + usize::from(self.new_branch.is_some())
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let query = query.trim();
+        let candidates = self
+            .worktrees
+            .iter()
+            .enumerate()
+            .map(|(ix, worktree)| StringMatchCandidate::new(ix, &match_text(worktree)))
+            .collect::<Vec<_>>();
+        self.matches = smol::block_on(fuzzy::match_strings(
+            &candidates,
+            query,
+            query.chars().any(|c| c.is_uppercase()),
+            true,
+            100,
+            &Default::default(),
+            cx.background_executor().clone(),
+        ));
+        if query.is_empty() {
+            self.matches.sort_unstable_by_key(|m| m.candidate_id);
+        }
+        let exists = self.worktrees.iter().any(|worktree| {
+            worktree.name().as_ref() == query || worktree.branch.as_deref() == Some(query)
+        });
+        self.new_branch = (!query.is_empty() && !query.contains(char::is_whitespace) && !exists)
+            .then(|| query.to_string());
+        self.selected_index = 0;
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(worktree) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|m| self.worktrees.get(m.candidate_id))
+        {
+            let path = worktree.path.clone();
+            if let Ok(task) = self.workspace.update(cx, |workspace, cx| {
+                open_worktree(workspace, path, window, cx)
+            }) {
+                task.detach_and_prompt_err("Failed to open worktree", window, cx, |_, _, _| None);
+            }
+            cx.emit(DismissEvent);
+            return;
+        }
+
+        let Some(branch) = self.new_branch.clone() else {
+            return;
+        };
+        let Some(path) = self.new_worktree_path(&branch) else {
+            return;
+        };
+        let create = self.repository.update(cx, |repository, _| {
+            repository.create_worktree(branch, path.clone(), None)
+        });
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            create.await??;
+            workspace
+                .update_in(cx, |workspace, window, cx| {
+                    open_worktree(workspace, path, window, cx)
+                })?
+                .await
+        })
+        .detach_and_prompt_err("Failed to create worktree", window, cx, |_, _, _| None);
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("The repository has no other worktrees".into())
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected);
+
+        let Some(hit) = self.matches.get(ix) else {
+            let branch = self.new_branch.as_ref()?;
+            return Some(
+                item.start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                    .child(Label::new(format!(
+                        "Create a worktree for the new branch {branch}"
+                    ))),
+            );
+        };
+        let worktree = self.worktrees.get(hit.candidate_id)?;
+        Some(
+            item.start_slot(Icon::new(IconName::GitBranchAlt).color(Color::Muted))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_between()
+                        .child(HighlightedLabel::new(
+                            match_text(worktree),
+                            hit.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(worktree.path.to_string_lossy().into_owned())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        ),
+                ),
+        )
+    }
+}
+
+fn match_text(worktree: &GitWorktree) -> String {
+    match &worktree.branch {
+        Some(branch) => format!("{} ({branch})", worktree.name()),
+        None => worktree.name().to_string(),
+    }
+}
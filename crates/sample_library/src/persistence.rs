@@ -0,0 +1,166 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use fs::MTime;
+
+use crate::SampleMetadata;
+
+/// The metadata of a sample as of the modification time it was read at.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedSample {
+    pub(crate) abs_path: PathBuf,
+    pub(crate) mtime: MTime,
+    pub(crate) metadata: SampleMetadata,
+}
+
+impl Column for SerializedSample {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (abs_path, next_index): (PathBuf, i32) = Column::column(statement, start_index)?;
+        let (mtime_seconds, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (mtime_nanos, next_index): (u32, i32) = Column::column(statement, next_index)?;
+        let (duration_ms, next_index): (Option<u64>, i32) = Column::column(statement, next_index)?;
+        let (bpm, next_index): (Option<f32>, i32) = Column::column(statement, next_index)?;
+        let (key, next_index): (Option<String>, i32) = Column::column(statement, next_index)?;
+        let (tags, next_index): (String, i32) = Column::column(statement, next_index)?;
+
+        let sample = Self {
+            abs_path,
+            mtime: MTime::from_seconds_and_nanos(mtime_seconds, mtime_nanos),
+            metadata: SampleMetadata {
+                duration: duration_ms.map(Duration::from_millis),
+                bpm,
+                key,
+                tags: tags.split_whitespace().map(str::to_string).collect(),
+            },
+        };
+        Ok((sample, next_index))
+    }
+}
+
+pub struct SampleLibraryDb(ThreadSafeConnection);
+
+impl Domain for SampleLibraryDb {
+    const NAME: &str = stringify!(SampleLibraryDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE samples(
+            abs_path BLOB PRIMARY KEY,
+            mtime_seconds INTEGER NOT NULL,
+            mtime_nanos INTEGER NOT NULL,
+            duration_ms INTEGER,
+            bpm REAL,
+            musical_key TEXT,
+            tags TEXT NOT NULL
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(SAMPLE_LIBRARY_DB, SampleLibraryDb, []);
+
+impl SampleLibraryDb {
+    /// Stores the metadata of many samples in a single transaction, as
+    /// sample libraries easily contain tens of thousands of files.
+    pub(crate) async fn save_samples(&self, samples: Vec<SerializedSample>) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        self.write(move |connection| {
+            connection.with_savepoint("save_samples", || {
+                let mut insert = connection.exec_bound::<(
+                    PathBuf,
+                    u64,
+                    u32,
+                    Option<u64>,
+                    Option<f32>,
+                    Option<String>,
+                    String,
+                )>(sql!(
+                    INSERT OR REPLACE INTO samples(
+                        abs_path, mtime_seconds, mtime_nanos, duration_ms, bpm, musical_key, tags
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?)
+                ))?;
+                for sample in samples {
+                    let Some((mtime_seconds, mtime_nanos)) =
+                        sample.mtime.to_seconds_and_nanos_for_persistence()
+                    else {
+                        continue;
+                    };
+                    insert((
+                        sample.abs_path,
+                        mtime_seconds,
+                        mtime_nanos,
+                        sample
+                            .metadata
+                            .duration
+                            .map(|duration| duration.as_millis() as u64),
+                        sample.metadata.bpm,
+                        sample.metadata.key,
+                        sample.metadata.tags.join(" "),
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub(crate) fn samples() -> Result<Vec<SerializedSample>> {
+            SELECT abs_path, mtime_seconds, mtime_nanos, duration_ms, bpm, musical_key, tags
+            FROM samples
+            ORDER BY abs_path
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_sample(abs_path: PathBuf) -> Result<()> {
+            DELETE FROM samples WHERE abs_path = (?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_samples() {
+        let db = SampleLibraryDb::open_test_db("test_save_samples").await;
+        let kick = SerializedSample {
+            abs_path: PathBuf::from("/samples/kick.wav"),
+            mtime: MTime::from_seconds_and_nanos(100, 5),
+            metadata: SampleMetadata {
+                duration: Some(Duration::from_millis(350)),
+                bpm: None,
+                key: None,
+                tags: vec!["kick".into(), "punchy".into()],
+            },
+        };
+        let pad = SerializedSample {
+            abs_path: PathBuf::from("/samples/pad.wav"),
+            mtime: MTime::from_seconds_and_nanos(200, 0),
+            metadata: SampleMetadata {
+                duration: None,
+                bpm: Some(120.0),
+                key: Some("Am".into()),
+                tags: vec!["pad".into()],
+            },
+        };
+        db.save_samples(vec![kick.clone(), pad.clone()])
+            .await
+            .unwrap();
+        assert_eq!(db.samples().unwrap(), [kick.clone(), pad]);
+
+        db.delete_sample(PathBuf::from("/samples/pad.wav"))
+            .await
+            .unwrap();
+        assert_eq!(db.samples().unwrap(), [kick]);
+    }
+}
@@ -0,0 +1,645 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    io::Read as _,
+    path::{Path, PathBuf},
+    sync::{Arc, atomic::AtomicBool},
+    time::Duration,
+};
+
+use audio::AudioClip;
+use audio_metadata::METADATA_HEADER_LEN;
+use collections::HashMap;
+use fs::{Fs, MTime};
+use futures::{FutureExt as _, StreamExt as _, future::Shared};
+use fuzzy::StringMatchCandidate;
+use gpui::{
+    App, AppContext as _, Context, Entity, EventEmitter, Global, SharedString, Subscription, Task,
+    WeakEntity,
+};
+use project::{PathChange, Project, ProjectPath, WorktreeId};
+use util::{ResultExt as _, paths::PathStyle, rel_path::RelPath};
+
+use crate::{
+    SampleMetadata, bpm_matches,
+    persistence::{SAMPLE_LIBRARY_DB, SerializedSample},
+};
+
+/// The sample indices of open projects.
+#[derive(Default)]
+struct SampleIndices(HashMap<WeakEntity<Project>, Entity<SampleIndex>>);
+
+impl Global for SampleIndices {}
+
+/// An audio file in one of the project's worktrees.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub project_path: ProjectPath,
+    pub abs_path: Arc<Path>,
+    /// The path including the name of the worktree, as shown to the user.
+    pub display_path: SharedString,
+    pub metadata: SampleMetadata,
+}
+
+impl Sample {
+    /// Summarizes the metadata, for example "0:02.50 · 120 BPM · Am".
+    pub fn details(&self) -> String {
+        let metadata = &self.metadata;
+        let mut details = Vec::new();
+        if let Some(duration) = metadata.duration {
+            let seconds = duration.as_secs_f32();
+            details.push(format!(
+                "{}:{:05.2}",
+                (seconds / 60.0) as u32,
+                seconds % 60.0
+            ));
+        }
+        if let Some(bpm) = metadata.bpm {
+            details.push(format!("{bpm} BPM"));
+        }
+        if let Some(key) = &metadata.key {
+            details.push(key.clone());
+        }
+        details.join(" · ")
+    }
+
+    fn search_text(&self) -> String {
+        let mut text = self.display_path.to_string();
+        for tag in &self.metadata.tags {
+            text.push(' ');
+            text.push_str(tag);
+        }
+        text
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SampleQuery {
+    /// Words matched fuzzily against the path and tags of each sample.
+    pub terms: Vec<String>,
+    /// Only samples at this tempo, or at half or double of it, match.
+    pub bpm: Option<f32>,
+    /// Only samples in this key match.
+    pub key: Option<String>,
+    pub max_duration: Option<Duration>,
+}
+
+impl SampleQuery {
+    /// Parses a query such as "dark pad 90bpm F#m", where the tempo and key
+    /// are matched against the metadata of each sample.
+    pub fn parse(text: &str) -> Self {
+        let mut metadata = SampleMetadata::default();
+        let terms = metadata.take_tempo_and_key(text);
+        Self {
+            terms,
+            bpm: metadata.bpm,
+            key: metadata.key,
+            max_duration: None,
+        }
+    }
+
+    fn matches_metadata(&self, metadata: &SampleMetadata) -> bool {
+        self.bpm
+            .is_none_or(|target_bpm| metadata.bpm.is_some_and(|bpm| bpm_matches(bpm, target_bpm)))
+            && self
+                .key
+                .as_ref()
+                .is_none_or(|key| metadata.key.as_ref() == Some(key))
+            && self.max_duration.is_none_or(|max_duration| {
+                metadata
+                    .duration
+                    .is_some_and(|duration| duration <= max_duration)
+            })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SampleMatch {
+    pub sample: Sample,
+    /// The number of query terms that matched.
+    pub matched_terms: usize,
+    pub score: f64,
+    /// Byte offsets of the matched characters in the display path.
+    pub positions: Vec<usize>,
+}
+
+pub enum SampleIndexEvent {
+    Updated,
+}
+
+/// Keeps track of the audio files in a project along with their length, tempo
+/// and key. Metadata is cached by modification time, so files are only read
+/// again after they change.
+pub struct SampleIndex {
+    project: WeakEntity<Project>,
+    fs: Arc<dyn Fs>,
+    read_file_contents: bool,
+    cache: Shared<Task<Arc<HashMap<PathBuf, SerializedSample>>>>,
+    samples: BTreeMap<Arc<Path>, Sample>,
+    pending_scans: usize,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<SampleIndexEvent> for SampleIndex {}
+
+impl SampleIndex {
+    /// Returns the index of the project, creating it the first time.
+    pub fn for_project(project: &Entity<Project>, cx: &mut App) -> Entity<Self> {
+        let weak_project = project.downgrade();
+        if let Some(index) = cx
+            .try_global::<SampleIndices>()
+            .and_then(|indices| indices.0.get(&weak_project))
+        {
+            return index.clone();
+        }
+
+        let index = cx.new(|cx| Self::new(project.clone(), cx));
+        cx.default_global::<SampleIndices>()
+            .0
+            .insert(weak_project.clone(), index.clone());
+        cx.observe_release(project, move |_, cx| {
+            cx.default_global::<SampleIndices>().0.remove(&weak_project);
+        })
+        .detach();
+        index
+    }
+
+    fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let cache = cx
+            .background_spawn(async move {
+                let samples = SAMPLE_LIBRARY_DB.samples().log_err().unwrap_or_default();
+                Arc::new(
+                    samples
+                        .into_iter()
+                        .map(|sample| (sample.abs_path.clone(), sample))
+                        .collect::<HashMap<_, _>>(),
+                )
+            })
+            .shared();
+        let subscription = cx.subscribe(&project, |this, _, event, cx| match event {
+            project::Event::WorktreeAdded(worktree_id) => {
+                this.index_worktree(*worktree_id, None, cx);
+            }
+            project::Event::WorktreeRemoved(worktree_id) => {
+                this.samples
+                    .retain(|_, sample| sample.project_path.worktree_id != *worktree_id);
+                cx.emit(SampleIndexEvent::Updated);
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let mut changed_paths = Vec::new();
+                for (path, _, change) in changes.iter() {
+                    if !is_sample_path(path) {
+                        continue;
+                    }
+                    if *change == PathChange::Removed {
+                        this.remove_sample(*worktree_id, path, cx);
+                    } else {
+                        changed_paths.push(path.clone());
+                    }
+                }
+                if !changed_paths.is_empty() {
+                    this.index_worktree(*worktree_id, Some(changed_paths), cx);
+                }
+            }
+            _ => {}
+        });
+
+        let mut this = Self {
+            fs: project.read(cx).fs().clone(),
+            read_file_contents: project.read(cx).is_local(),
+            project: project.downgrade(),
+            cache,
+            samples: BTreeMap::default(),
+            pending_scans: 0,
+            _subscription: subscription,
+        };
+        let worktree_ids = project
+            .read(cx)
+            .worktrees(cx)
+            .map(|worktree| worktree.read(cx).id())
+            .collect::<Vec<_>>();
+        for worktree_id in worktree_ids {
+            this.index_worktree(worktree_id, None, cx);
+        }
+        this
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &Sample> {
+        self.samples.values()
+    }
+
+    /// Whether files are still being read, in which case searches may miss
+    /// some samples.
+    pub fn is_indexing(&self) -> bool {
+        self.pending_scans > 0
+    }
+
+    /// Returns the samples matching the query, best matches first. Samples
+    /// matching more of the query's terms rank higher.
+    pub fn search(
+        &self,
+        query: SampleQuery,
+        max_results: usize,
+        cx: &App,
+    ) -> Task<Vec<SampleMatch>> {
+        let samples = self
+            .samples
+            .values()
+            .filter(|sample| query.matches_metadata(&sample.metadata))
+            .cloned()
+            .collect::<Vec<_>>();
+        let executor = cx.background_executor().clone();
+        cx.background_spawn(async move {
+            if query.terms.is_empty() {
+                return samples
+                    .into_iter()
+                    .take(max_results)
+                    .map(|sample| SampleMatch {
+                        sample,
+                        matched_terms: 0,
+                        score: 0.,
+                        positions: Vec::new(),
+                    })
+                    .collect();
+            }
+
+            let candidates = samples
+                .iter()
+                .enumerate()
+                .map(|(id, sample)| StringMatchCandidate::new(id, &sample.search_text()))
+                .collect::<Vec<_>>();
+            let cancel_flag = AtomicBool::new(false);
+            let mut matches = HashMap::<usize, SampleMatch>::default();
+            for term in &query.terms {
+                let term_matches = fuzzy::match_strings(
+                    &candidates,
+                    term,
+                    false,
+                    true,
+                    candidates.len(),
+                    &cancel_flag,
+                    executor.clone(),
+                )
+                .await;
+                for term_match in term_matches {
+                    let sample = &samples[term_match.candidate_id];
+                    let display_path_len = sample.display_path.len();
+                    let sample_match =
+                        matches
+                            .entry(term_match.candidate_id)
+                            .or_insert_with(|| SampleMatch {
+                                sample: sample.clone(),
+                                matched_terms: 0,
+                                score: 0.,
+                                positions: Vec::new(),
+                            });
+                    sample_match.matched_terms += 1;
+                    sample_match.score += term_match.score;
+                    sample_match.positions.extend(
+                        term_match
+                            .positions
+                            .into_iter()
+                            .filter(|position| *position < display_path_len),
+                    );
+                }
+            }
+
+            let mut matches = matches.into_values().collect::<Vec<_>>();
+            matches.sort_by(|a, b| {
+                b.matched_terms
+                    .cmp(&a.matched_terms)
+                    .then(b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal))
+                    .then_with(|| a.sample.display_path.cmp(&b.sample.display_path))
+            });
+            matches.truncate(max_results);
+            for sample_match in &mut matches {
+                sample_match.positions.sort_unstable();
+                sample_match.positions.dedup();
+            }
+            matches
+        })
+    }
+
+    /// Reads the samples in the worktree, or only the given paths in it.
+    fn index_worktree(
+        &mut self,
+        worktree_id: WorktreeId,
+        paths: Option<Vec<Arc<RelPath>>>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let Some(worktree) = project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let snapshot = worktree.read(cx).snapshot();
+        let path_style = project.read(cx).path_style(cx);
+        let fs = self.fs.clone();
+        let read_file_contents = self.read_file_contents;
+        let cache = self.cache.clone();
+
+        self.pending_scans += 1;
+        cx.spawn(async move |this, cx| {
+            let cache = cache.await;
+            let files = match &paths {
+                Some(paths) => paths
+                    .iter()
+                    .filter_map(|path| snapshot.entry_for_path(path))
+                    .filter(|entry| entry.is_file())
+                    .map(|entry| (entry.path.clone(), entry.mtime, entry.size))
+                    .collect::<Vec<_>>(),
+                None => {
+                    let mut files = snapshot
+                        .files(true, 0)
+                        .filter(|entry| is_sample_path(&entry.path))
+                        .map(|entry| (entry.path.clone(), entry.mtime, entry.size))
+                        .collect::<Vec<_>>();
+                    // Sample folders are often gitignored for their size, and
+                    // the worktree doesn't load ignored directories.
+                    let unloaded_dirs = snapshot
+                        .entries(true, 0)
+                        .filter(|entry| entry.kind.is_unloaded())
+                        .map(|entry| snapshot.absolutize(&entry.path))
+                        .collect::<Vec<_>>();
+                    for dir in unloaded_dirs {
+                        files.extend(
+                            walk_samples(fs.as_ref(), snapshot.abs_path(), dir, path_style).await,
+                        );
+                    }
+                    files
+                }
+            };
+
+            let mut samples = Vec::with_capacity(files.len());
+            let mut reads = Vec::new();
+            for (path, mtime, size) in files {
+                let abs_path = snapshot.absolutize(&path);
+                let sample = Sample {
+                    display_path: snapshot
+                        .root_name()
+                        .join(&path)
+                        .display(path_style)
+                        .to_string()
+                        .into(),
+                    project_path: ProjectPath { worktree_id, path },
+                    abs_path: abs_path.as_path().into(),
+                    metadata: SampleMetadata::default(),
+                };
+                match cache.get(&abs_path) {
+                    Some(cached) if Some(cached.mtime) == mtime => {
+                        samples.push(Sample {
+                            metadata: cached.metadata.clone(),
+                            ..sample
+                        });
+                    }
+                    _ => reads.push((sample, mtime, size)),
+                }
+            }
+
+            let read_samples = cx
+                .background_spawn(async move {
+                    let mut read_samples = Vec::with_capacity(reads.len());
+                    for (sample, mtime, file_len) in reads {
+                        let metadata = if read_file_contents {
+                            read_metadata(fs.as_ref(), &sample.abs_path, file_len).await
+                        } else {
+                            SampleMetadata::from_path(&sample.abs_path)
+                        };
+                        read_samples.push((Sample { metadata, ..sample }, mtime));
+                    }
+                    read_samples
+                })
+                .await;
+            let serialized = read_samples
+                .iter()
+                .filter_map(|(sample, mtime)| {
+                    Some(SerializedSample {
+                        abs_path: sample.abs_path.to_path_buf(),
+                        mtime: (*mtime)?,
+                        metadata: sample.metadata.clone(),
+                    })
+                })
+                .collect();
+            SAMPLE_LIBRARY_DB.save_samples(serialized).await.log_err();
+            samples.extend(read_samples.into_iter().map(|(sample, _)| sample));
+
+            this.update(cx, |this, cx| {
+                if paths.is_none() {
+                    this.samples
+                        .retain(|_, sample| sample.project_path.worktree_id != worktree_id);
+                }
+                for sample in samples {
+                    this.samples.insert(sample.abs_path.clone(), sample);
+                }
+                this.pending_scans -= 1;
+                cx.emit(SampleIndexEvent::Updated);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn remove_sample(&mut self, worktree_id: WorktreeId, path: &RelPath, cx: &mut Context<Self>) {
+        let Some(abs_path) = self
+            .samples
+            .iter()
+            .find(|(_, sample)| {
+                sample.project_path.worktree_id == worktree_id
+                    && sample.project_path.path.as_ref() == path
+            })
+            .map(|(abs_path, _)| abs_path.clone())
+        else {
+            return;
+        };
+        self.samples.remove(&abs_path);
+        cx.background_spawn(SAMPLE_LIBRARY_DB.delete_sample(abs_path.to_path_buf()))
+            .detach_and_log_err(cx);
+        cx.emit(SampleIndexEvent::Updated);
+    }
+}
+
+fn is_sample_path(path: &RelPath) -> bool {
+    path.extension()
+        .is_some_and(AudioClip::is_supported_extension)
+}
+
+/// Lists the samples in a directory that the worktree hasn't loaded, with
+/// their paths relative to the worktree root.
+async fn walk_samples(
+    fs: &dyn Fs,
+    root: &Path,
+    dir: PathBuf,
+    path_style: PathStyle,
+) -> Vec<(Arc<RelPath>, Option<MTime>, u64)> {
+    let mut samples = Vec::new();
+    let mut dirs = vec![dir];
+    while let Some(dir) = dirs.pop() {
+        let mut children = match fs.read_dir(&dir).await {
+            Ok(children) => children,
+            Err(error) => {
+                log::debug!("failed to read directory {dir:?}: {error:#}");
+                continue;
+            }
+        };
+        while let Some(child) = children.next().await {
+            let Some(child) = child.log_err() else {
+                continue;
+            };
+            let Ok(Some(metadata)) = fs.metadata(&child).await else {
+                continue;
+            };
+            if metadata.is_dir {
+                if !metadata.is_symlink {
+                    dirs.push(child);
+                }
+                continue;
+            }
+            let Some(path) = child
+                .strip_prefix(root)
+                .ok()
+                .and_then(|path| RelPath::new(path, path_style).ok())
+            else {
+                continue;
+            };
+            if is_sample_path(&path) {
+                samples.push((path.into_arc(), Some(metadata.mtime), metadata.len));
+            }
+        }
+    }
+    samples
+}
+
+async fn read_metadata(fs: &dyn Fs, abs_path: &Path, file_len: u64) -> SampleMetadata {
+    let header = async {
+        let mut header = Vec::new();
+        fs.open_sync(abs_path)
+            .await?
+            .take(METADATA_HEADER_LEN)
+            .read_to_end(&mut header)?;
+        anyhow::Ok(header)
+    };
+    match header.await {
+        Ok(header) => SampleMetadata::read(abs_path, &header, file_len),
+        Err(error) => {
+            log::debug!("failed to read sample {abs_path:?}: {error:#}");
+            SampleMetadata::from_path(abs_path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_search_samples(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/samples"),
+            json!({
+                "Drums": {
+                    "Kick Punchy.wav": "",
+                    "Snare Tight.wav": "",
+                },
+                "Loops": {
+                    "Pad Warm 120bpm Am.wav": "",
+                    "Pad Dark 90bpm Am.wav": "",
+                },
+                "notes.txt": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/samples").as_ref()], cx).await;
+        let index = cx.update(|cx| SampleIndex::for_project(&project, cx));
+        cx.run_until_parked();
+
+        assert_eq!(
+            search(&index, "", cx).await,
+            [
+                "samples/Drums/Kick Punchy.wav",
+                "samples/Drums/Snare Tight.wav",
+                "samples/Loops/Pad Dark 90bpm Am.wav",
+                "samples/Loops/Pad Warm 120bpm Am.wav",
+            ]
+        );
+        assert_eq!(
+            search(&index, "kick", cx).await,
+            ["samples/Drums/Kick Punchy.wav"]
+        );
+        assert_eq!(
+            search(&index, "pad 60 bpm", cx).await,
+            ["samples/Loops/Pad Warm 120bpm Am.wav"]
+        );
+        assert_eq!(
+            search(&index, "warm pad in Am", cx).await[0],
+            "samples/Loops/Pad Warm 120bpm Am.wav"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_ignored_samples(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/samples"),
+            json!({
+                ".git": {},
+                ".gitignore": "Vendor/\n",
+                "Drums": {
+                    "Kick Punchy.wav": "",
+                },
+                "Vendor": {
+                    "Bass": {
+                        "Sub 808.wav": "",
+                    },
+                    "readme.txt": "",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/samples").as_ref()], cx).await;
+        let index = cx.update(|cx| SampleIndex::for_project(&project, cx));
+        cx.run_until_parked();
+
+        // Gitignored folders are indexed too, although the worktree doesn't
+        // load them.
+        assert_eq!(
+            search(&index, "", cx).await,
+            [
+                "samples/Drums/Kick Punchy.wav",
+                "samples/Vendor/Bass/Sub 808.wav",
+            ]
+        );
+    }
+
+    async fn search(
+        index: &Entity<SampleIndex>,
+        query: &str,
+        cx: &mut TestAppContext,
+    ) -> Vec<String> {
+        index
+            .update(cx, |index, cx| {
+                index.search(SampleQuery::parse(query), 10, cx)
+            })
+            .await
+            .into_iter()
+            .map(|sample_match| sample_match.sample.display_path.to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,8 @@
+//! Indexes the audio files in a project's worktrees, so they can be found by
+//! name, tag, tempo and key.
+
+mod persistence;
+mod sample_index;
+
+pub use audio_metadata::{SampleMetadata, bpm_matches, parse_key};
+pub use sample_index::{Sample, SampleIndex, SampleIndexEvent, SampleMatch, SampleQuery};
@@ -0,0 +1,266 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use audio::{Audio, AudioClip, ClipPlayback};
+use fs::Fs;
+use gpui::{
+    App, ClipboardItem, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    ParentElement, Render, Styled, Subscription, Task, WeakEntity, Window, actions,
+};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use sample_library::{SampleIndex, SampleIndexEvent, SampleMatch, SampleQuery};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(
+    sample_library,
+    [
+        /// Toggles the sample picker, which searches the audio files in the
+        /// project by name, tag, tempo and key.
+        Toggle
+    ]
+);
+
+/// How long a sample has to stay highlighted before it is auditioned, so
+/// scrolling through the list doesn't decode every file on the way.
+const AUDITION_DELAY: Duration = Duration::from_millis(150);
+const MAX_MATCHES: usize = 100;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(SamplePicker::register).detach();
+}
+
+pub struct SamplePicker {
+    picker: Entity<Picker<SamplePickerDelegate>>,
+    _index_subscription: Subscription,
+}
+
+impl SamplePicker {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            let project = workspace.project().clone();
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                SamplePicker::new(project, window, cx)
+            });
+        });
+    }
+
+    fn new(project: Entity<Project>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let index = SampleIndex::for_project(&project, cx);
+        let delegate = SamplePickerDelegate {
+            sample_picker: cx.entity().downgrade(),
+            fs: project.read(cx).fs().clone(),
+            can_audition: project.read(cx).is_local(),
+            project,
+            index: index.clone(),
+            matches: Vec::new(),
+            selected_index: 0,
+            audition: None,
+            _audition_task: Task::ready(()),
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let index_subscription = cx.subscribe_in(
+            &index,
+            window,
+            |this, _, event: &SampleIndexEvent, window, cx| match event {
+                SampleIndexEvent::Updated => {
+                    this.picker
+                        .update(cx, |picker, cx| picker.refresh(window, cx));
+                }
+            },
+        );
+        Self {
+            picker,
+            _index_subscription: index_subscription,
+        }
+    }
+}
+
+impl Render for SamplePicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("SamplePicker")
+            .w(rems(40.))
+            .child(self.picker.clone())
+    }
+}
+
+impl Focusable for SamplePicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SamplePicker {}
+impl ModalView for SamplePicker {}
+
+pub struct SamplePickerDelegate {
+    sample_picker: WeakEntity<SamplePicker>,
+    project: Entity<Project>,
+    fs: Arc<dyn Fs>,
+    /// Samples of remote projects can't be read locally.
+    can_audition: bool,
+    index: Entity<SampleIndex>,
+    matches: Vec<SampleMatch>,
+    selected_index: usize,
+    audition: Option<ClipPlayback>,
+    _audition_task: Task<()>,
+}
+
+impl SamplePickerDelegate {
+    fn audition(&mut self, abs_path: Arc<Path>, cx: &mut Context<Picker<Self>>) {
+        self.audition.take();
+        if !self.can_audition {
+            return;
+        }
+        let fs = self.fs.clone();
+        self._audition_task = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(AUDITION_DELAY).await;
+            let clip = cx
+                .background_spawn(async move {
+                    let bytes = fs.load_bytes(&abs_path).await?;
+                    AudioClip::decode(bytes)
+                        .with_context(|| format!("failed to decode {abs_path:?}"))
+                })
+                .await;
+            let Some(clip) = clip.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.delegate.audition = Audio::play_clip(clip, cx).log_err();
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+impl PickerDelegate for SamplePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search samples by name, tag, tempo or key…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, cx: &mut App) -> Option<SharedString> {
+        Some(if self.index.read(cx).is_indexing() {
+            "Indexing samples…".into()
+        } else {
+            "No samples found".into()
+        })
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+        if let Some(sample_match) = self.matches.get(ix) {
+            self.audition(sample_match.sample.abs_path.clone(), cx);
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let search = self
+            .index
+            .read(cx)
+            .search(SampleQuery::parse(&query), MAX_MATCHES, cx);
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = search.await;
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    /// Reveals the sample in the project panel, or copies its absolute path
+    /// when confirming with the secondary modifier.
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(sample_match) = self.matches.get(self.selected_index) {
+            let sample = &sample_match.sample;
+            if secondary {
+                cx.write_to_clipboard(ClipboardItem::new_string(
+                    sample.abs_path.to_string_lossy().into_owned(),
+                ));
+            } else if let Some(entry_id) = self
+                .project
+                .read(cx)
+                .entry_for_path(&sample.project_path, cx)
+                .map(|entry| entry.id)
+            {
+                self.project.update(cx, |_, cx| {
+                    cx.emit(project::Event::RevealInProjectPanel(entry_id))
+                });
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.audition.take();
+        self.sample_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let sample_match = self.matches.get(ix)?;
+        let sample = &sample_match.sample;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(if selected && self.audition.is_some() {
+                        IconName::AudioOn
+                    } else {
+                        IconName::FileGeneric
+                    })
+                    .color(Color::Muted),
+                )
+                .child(HighlightedLabel::new(
+                    sample.display_path.clone(),
+                    sample_match.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(sample.details())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
@@ -4,8 +4,10 @@ mod extension;
 mod language;
 mod language_model;
 mod project;
+mod speech;
 mod terminal;
 mod theme;
+mod web_search;
 mod workspace;
 
 pub use agent::*;
@@ -14,8 +16,10 @@ pub use extension::*;
 pub use language::*;
 pub use language_model::*;
 pub use project::*;
+pub use speech::*;
 pub use terminal::*;
 pub use theme::*;
+pub use web_search::*;
 pub use workspace::*;
 
 use collections::{HashMap, IndexMap};
@@ -138,6 +142,9 @@ pub struct SettingsContent {
 
     /// Configuration for session-related features
     pub session: Option<SessionSettingsContent>,
+
+    /// Configuration of speech input and output in the agent panel.
+    pub speech: Option<SpeechSettingsContent>,
     /// Control what info is collected by Zed.
     pub telemetry: Option<TelemetrySettingsContent>,
 
@@ -151,6 +158,9 @@ pub struct SettingsContent {
     /// Default: false
     pub vim_mode: Option<bool>,
 
+    /// Configuration of the providers used by the agent's web search tool.
+    pub web_search: Option<WebSearchSettingsContent>,
+
     // Settings related to calls in Zed
     pub calls: Option<CallSettingsContent>,
 
@@ -109,6 +109,18 @@ pub struct AgentSettingsContent {
     ///
     /// Default: 4
     pub message_editor_min_lines: Option<usize>,
+    /// Prompts that the agent runs on a schedule while a window with their
+    /// project is open. Each run starts a new thread, which shows up in the
+    /// thread history.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub scheduled_runs: Vec<ScheduledAgentRun>,
+    /// The image generation endpoint used by the `generate_image` tool, which
+    /// is unavailable when this is not set.
+    ///
+    /// Default: null
+    pub image_generation: Option<ImageGenerationSettingsContent>,
 }
 
 impl AgentSettingsContent {
@@ -178,6 +190,58 @@ pub struct AgentProfileContent {
     pub enable_all_context_servers: Option<bool>,
     #[serde(default)]
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
+    /// The voice used to read agent responses aloud with this profile.
+    /// Defaults to the voice of the configured `speech.text_to_speech` provider.
+    pub voice: Option<String>,
+    /// Commands that run before or after the agent's tool calls with this profile.
+    #[serde(default)]
+    pub hooks: Vec<AgentHookContent>,
+}
+
+/// A command or task that runs whenever the agent calls one of the matching tools.
+///
+/// The command runs through the system shell in the first worktree of the project,
+/// with a JSON description of the call on stdin, and `JAMU_HOOK_EVENT` and
+/// `JAMU_TOOL_NAME` set in its environment.
+#[skip_serializing_none]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHookContent {
+    /// When the hook runs.
+    pub event: AgentHookEvent,
+    /// The tools the hook runs for. Built-in tools are matched by name, tools
+    /// from context servers by `<server id>/<tool name>` or `<server id>/*`,
+    /// and `*` matches every tool.
+    ///
+    /// Default: every tool
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// The shell command to run.
+    pub command: Option<String>,
+    /// The label of a task from `tasks.json` to run instead of `command`.
+    /// The task's command, arguments, environment and working directory are
+    /// used, with its variables resolved for the first worktree of the project.
+    pub task: Option<String>,
+    /// Environment variables to set for the command.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// The directory to run the command in, relative to the first worktree of the project.
+    /// Tasks run in their own `cwd` instead.
+    pub cwd: Option<String>,
+    /// How many seconds the command may run before it's stopped and treated as failed.
+    ///
+    /// Default: 30
+    pub timeout: Option<u64>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentHookEvent {
+    /// Runs before the tool. If the command exits with a non-zero status, the
+    /// call is blocked and the command's output is given to the agent as the reason.
+    PreToolUse,
+    /// Runs after the tool has finished, with its output. If the command exits
+    /// with a non-zero status, the call is reported to the agent as failed.
+    PostToolUse,
 }
 
 #[skip_serializing_none]
@@ -186,6 +250,55 @@ pub struct ContextServerPresetContent {
     pub tools: IndexMap<Arc<str>, bool>,
 }
 
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct ScheduledAgentRun {
+    /// The name of the run, shown in notifications.
+    pub name: String,
+    /// When to run, as a cron expression with five fields (minute, hour, day
+    /// of month, month and day of week) in local time, or one of `@hourly`,
+    /// `@daily` and `@weekly`.
+    ///
+    /// For example, "0 9 * * 1-5" runs at 9:00 on weekdays.
+    pub schedule: String,
+    /// The prompt to send to the agent.
+    pub prompt: String,
+    /// The path of the project to run in. The run only happens while a window
+    /// with this directory open as a worktree is open.
+    pub project: PathBuf,
+    /// The profile to run with. Defaults to `agent.default_profile`.
+    pub profile: Option<Arc<str>>,
+    /// The model to run with. Defaults to `agent.default_model`.
+    pub model: Option<LanguageModelSelection>,
+    /// Whether the run is enabled.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+}
+
+/// An OpenAI-compatible `/images/generations` endpoint.
+///
+/// The API key is read from `api_key_env_var` when set, and otherwise from the
+/// credentials stored for `api_url`, which means an `openai_compatible` language
+/// model provider with the same URL shares its key. Endpoints that don't need a
+/// key, such as a local server, work without one.
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct ImageGenerationSettingsContent {
+    pub api_url: String,
+    /// Default: "gpt-image-1"
+    #[serde(default = "default_image_generation_model")]
+    pub model: String,
+    /// The size of the generated images, such as "1024x1024", when the tool
+    /// call doesn't ask for one. Uses the endpoint's default when unset.
+    pub size: Option<String>,
+    pub api_key_env_var: Option<String>,
+}
+
+fn default_image_generation_model() -> String {
+    "gpt-image-1".to_string()
+}
+
 #[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 #[serde(rename_all = "snake_case")]
 pub enum DefaultAgentView {
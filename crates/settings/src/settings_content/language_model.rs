@@ -238,6 +238,9 @@ pub struct OpenAiCompatibleAvailableModel {
 pub struct OpenAiCompatibleModelCapabilities {
     pub tools: bool,
     pub images: bool,
+    /// Whether the model accepts `input_audio` content parts.
+    #[serde(default)]
+    pub audio: bool,
     pub parallel_tool_calls: bool,
     pub prompt_cache_key: bool,
 }
@@ -247,6 +250,7 @@ impl Default for OpenAiCompatibleModelCapabilities {
         Self {
             tools: true,
             images: false,
+            audio: false,
             parallel_tool_calls: false,
             prompt_cache_key: false,
         }
@@ -0,0 +1,106 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use settings_macros::MergeFrom;
+use std::path::PathBuf;
+
+/// Configuration of speech input and output in the agent panel.
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug)]
+pub struct SpeechSettingsContent {
+    /// The speech-to-text backend used for dictating into the agent message editor.
+    /// Dictation is unavailable when this is not set.
+    ///
+    /// Default: null
+    pub transcription: Option<TranscriptionProviderContent>,
+    /// How often, in milliseconds, the audio recorded so far is transcribed while
+    /// dictating, so that partial transcripts appear in the message editor.
+    ///
+    /// Default: 1500
+    pub partial_transcript_interval_ms: Option<u64>,
+    /// The text-to-speech backend used to read agent responses aloud.
+    /// Reading aloud is unavailable when this is not set.
+    ///
+    /// Default: null
+    pub text_to_speech: Option<TextToSpeechProviderContent>,
+    /// Whether to read the final message of each agent turn aloud, leaving out
+    /// code blocks and tool calls.
+    ///
+    /// Default: false
+    pub read_agent_responses_aloud: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum TranscriptionProviderContent {
+    /// An OpenAI-compatible `/audio/transcriptions` endpoint.
+    ///
+    /// The API key is read from `api_key_env_var` when set, and otherwise from the
+    /// credentials stored for `api_url`, which means an `openai_compatible` language
+    /// model provider with the same URL shares its key.
+    OpenAiCompatible {
+        api_url: String,
+        /// Default: "whisper-1"
+        #[serde(default = "default_transcription_model")]
+        model: String,
+        /// The language spoken, as an ISO-639-1 code. Detected automatically when unset.
+        language: Option<String>,
+        api_key_env_var: Option<String>,
+    },
+    /// A local command-line speech-to-text engine, such as `whisper-cli` from whisper.cpp.
+    ///
+    /// The recorded audio is written to a 16kHz mono WAV file. Any argument equal to
+    /// `{file}` is replaced with the path of that file, and the transcript is read
+    /// from the command's standard output.
+    Local {
+        command: PathBuf,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+fn default_transcription_model() -> String {
+    "whisper-1".to_string()
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum TextToSpeechProviderContent {
+    /// An OpenAI-compatible `/audio/speech` endpoint.
+    ///
+    /// The API key is looked up the same way as for transcription.
+    OpenAiCompatible {
+        api_url: String,
+        /// Default: "tts-1"
+        #[serde(default = "default_text_to_speech_model")]
+        model: String,
+        /// The voice used when the agent profile does not set one.
+        ///
+        /// Default: "alloy"
+        #[serde(default = "default_text_to_speech_voice")]
+        voice: String,
+        api_key_env_var: Option<String>,
+    },
+    /// A local command-line text-to-speech engine, such as `piper` or `say`.
+    ///
+    /// The text is written to the command's standard input. Any argument equal to
+    /// `{file}` is replaced with the path of the WAV file the command must write,
+    /// and any argument equal to `{voice}` with the voice of the agent profile.
+    Local {
+        command: PathBuf,
+        #[serde(default)]
+        args: Vec<String>,
+        /// The voice used when the agent profile does not set one.
+        voice: Option<String>,
+    },
+}
+
+fn default_text_to_speech_model() -> String {
+    "tts-1".to_string()
+}
+
+fn default_text_to_speech_voice() -> String {
+    "alloy".to_string()
+}
@@ -0,0 +1,89 @@
+use collections::HashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use settings_macros::MergeFrom;
+
+/// Configuration of the providers used by the agent's web search tool.
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug)]
+pub struct WebSearchSettingsContent {
+    /// The id of the provider used for web searches. When unset or not
+    /// available, the first of `providers` is used, and otherwise Zed's
+    /// provider when signed in with a Zed-hosted model.
+    ///
+    /// Default: null
+    pub active_provider: Option<String>,
+    /// Web search providers available in addition to Zed's.
+    ///
+    /// Default: []
+    pub providers: Option<Vec<WebSearchProviderContent>>,
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum WebSearchProviderContent {
+    /// A SearxNG instance with the JSON output format enabled. Its id is `searxng`.
+    Searxng {
+        /// The URL of the instance, such as `http://localhost:8888`.
+        url: String,
+        /// Default: 10
+        #[serde(default = "default_max_results")]
+        max_results: usize,
+    },
+    /// The Brave Search API. Its id is `brave`.
+    ///
+    /// The API key is read from `api_key_env_var` when set, and otherwise from the
+    /// credentials stored for `api_url`.
+    Brave {
+        /// Default: "https://api.search.brave.com/res/v1"
+        #[serde(default = "default_brave_api_url")]
+        api_url: String,
+        api_key_env_var: Option<String>,
+        /// At most 20.
+        ///
+        /// Default: 10
+        #[serde(default = "default_max_results")]
+        max_results: usize,
+    },
+    /// Any HTTP endpoint answering a GET request with JSON, whose results are
+    /// picked out of the response with `response`.
+    Json {
+        /// Identifies this provider in `active_provider`.
+        id: String,
+        /// The URL to request. `{query}` is replaced with the URL-encoded query.
+        url: String,
+        /// Headers sent with each request.
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        /// When set, the value of this environment variable is sent as a bearer token.
+        api_key_env_var: Option<String>,
+        response: WebSearchResponseMappingContent,
+        /// Default: 10
+        #[serde(default = "default_max_results")]
+        max_results: usize,
+    },
+}
+
+/// Where the results are found in the JSON response of a `json` provider,
+/// as JSON pointers such as `/data/items`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct WebSearchResponseMappingContent {
+    /// The array of results, relative to the response.
+    pub results: String,
+    /// The title of a result, relative to the result.
+    pub title: String,
+    /// The URL of a result, relative to the result.
+    pub url: String,
+    /// The snippet of a result, relative to the result.
+    pub text: String,
+}
+
+fn default_max_results() -> usize {
+    10
+}
+
+fn default_brave_api_url() -> String {
+    "https://api.search.brave.com/res/v1".to_string()
+}
@@ -0,0 +1,471 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use futures::channel::oneshot;
+use gpui::{Context, EventEmitter, SharedString, Task};
+use parking_lot::Mutex;
+use util::ResultExt as _;
+
+use crate::TranscriptionProvider;
+
+/// Recordings shorter than this are not worth sending to the transcription backend.
+const MIN_TRANSCRIBED_DURATION: Duration = Duration::from_millis(300);
+
+/// Only the audio since the last completed segment is uploaded, and segments
+/// are completed once they reach this length.
+const MAX_SEGMENT_DURATION: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+pub enum DictationEvent {
+    /// The transcript of everything recorded so far. Each transcript replaces
+    /// the previous one; the final transcript is emitted once after [`Dictation::stop`].
+    Transcript {
+        text: String,
+        is_final: bool,
+    },
+    Error(SharedString),
+}
+
+/// Records the microphone through the same capture, echo cancelling and
+/// denoising pipeline as calls, and periodically transcribes the recording.
+///
+/// The recording is transcribed in segments of at most [`MAX_SEGMENT_DURATION`],
+/// so that long dictations don't upload everything said so far each time.
+pub struct Dictation {
+    provider: Arc<dyn TranscriptionProvider>,
+    samples: Arc<Mutex<Vec<f32>>>,
+    /// The transcript of the completed segments.
+    completed_transcript: String,
+    /// Where in `samples` the current segment starts.
+    segment_start: usize,
+    stop_recording: Arc<AtomicBool>,
+    stopped: bool,
+    _transcribe_task: Task<()>,
+}
+
+impl EventEmitter<DictationEvent> for Dictation {}
+
+impl Dictation {
+    pub fn new(
+        provider: Arc<dyn TranscriptionProvider>,
+        partial_transcript_interval: Duration,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let stop_recording = Arc::new(AtomicBool::new(false));
+        let (error_tx, error_rx) = oneshot::channel();
+
+        Self::start_recording(samples.clone(), stop_recording.clone(), error_tx, cx);
+        Self::with_recording(
+            provider,
+            samples,
+            stop_recording,
+            error_rx,
+            partial_transcript_interval,
+            cx,
+        )
+    }
+
+    /// Transcribes the samples recorded into `samples` until `stop_recording`
+    /// is set. If recording fails to start, its error is sent through `error_rx`.
+    fn with_recording(
+        provider: Arc<dyn TranscriptionProvider>,
+        samples: Arc<Mutex<Vec<f32>>>,
+        stop_recording: Arc<AtomicBool>,
+        error_rx: oneshot::Receiver<anyhow::Error>,
+        partial_transcript_interval: Duration,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let transcribe_task = cx.spawn({
+            let samples = samples.clone();
+            let provider = provider.clone();
+            async move |this, cx| {
+                if let Ok(error) = error_rx.await {
+                    this.update(cx, |this, cx| {
+                        this.stopped = true;
+                        cx.emit(DictationEvent::Error(error.to_string().into()));
+                    })
+                    .ok();
+                    return;
+                }
+
+                let mut transcribed_len = 0;
+                loop {
+                    cx.background_executor()
+                        .timer(partial_transcript_interval)
+                        .await;
+                    let Ok(segment_start) = this.read_with(cx, |this, _| this.segment_start) else {
+                        break;
+                    };
+                    let (wav, segment_end) = {
+                        let samples = samples.lock();
+                        if samples.len() <= transcribed_len {
+                            continue;
+                        }
+                        let segment_end = segment_end(&samples, segment_start);
+                        let end = segment_end.unwrap_or(samples.len());
+                        transcribed_len = end;
+                        (segment_as_wav(&samples[segment_start..end]), segment_end)
+                    };
+                    let Some(wav) = wav else {
+                        continue;
+                    };
+                    let Some(text) = provider.transcribe(wav).await.log_err() else {
+                        continue;
+                    };
+                    if this
+                        .update(cx, |this, cx| {
+                            let text = match segment_end {
+                                Some(segment_end) => {
+                                    this.completed_transcript = this.transcript_with(&text);
+                                    this.segment_start = segment_end;
+                                    this.completed_transcript.clone()
+                                }
+                                None => this.transcript_with(&text),
+                            };
+                            cx.emit(DictationEvent::Transcript {
+                                text,
+                                is_final: false,
+                            })
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            provider,
+            samples,
+            completed_transcript: String::new(),
+            segment_start: 0,
+            stop_recording,
+            stopped: false,
+            _transcribe_task: transcribe_task,
+        }
+    }
+
+    #[cfg(not(any(all(target_os = "windows", target_env = "gnu"), target_os = "freebsd")))]
+    fn start_recording(
+        samples: Arc<Mutex<Vec<f32>>>,
+        stop_recording: Arc<AtomicBool>,
+        error_tx: oneshot::Sender<anyhow::Error>,
+        cx: &mut Context<Self>,
+    ) {
+        let voip_parts = match audio::VoipParts::for_dictation(&cx.to_async()) {
+            Ok(voip_parts) => voip_parts,
+            Err(error) => {
+                error_tx.send(error).ok();
+                return;
+            }
+        };
+
+        // Audio needs to run real-time and should never be paused. That is
+        // why we are using a normal std::thread and not a background task
+        std::thread::Builder::new()
+            .name("MicrophoneToDictation".to_string())
+            .spawn(move || {
+                // microphone is non send on mac
+                let mut microphone = match audio::Audio::open_microphone(voip_parts) {
+                    Ok(microphone) => microphone,
+                    Err(error) => {
+                        error_tx.send(error).ok();
+                        return;
+                    }
+                };
+                drop(error_tx);
+
+                while !stop_recording.load(Ordering::Relaxed) {
+                    let buffer = microphone
+                        .by_ref()
+                        .take(audio::BUFFER_SIZE)
+                        .collect::<Vec<_>>();
+                    if buffer.is_empty() {
+                        break;
+                    }
+                    samples.lock().extend(buffer);
+                }
+            })
+            .expect("should be able to spawn threads");
+    }
+
+    #[cfg(any(all(target_os = "windows", target_env = "gnu"), target_os = "freebsd"))]
+    fn start_recording(
+        _samples: Arc<Mutex<Vec<f32>>>,
+        _stop_recording: Arc<AtomicBool>,
+        error_tx: oneshot::Sender<anyhow::Error>,
+        _cx: &mut Context<Self>,
+    ) {
+        error_tx
+            .send(anyhow::anyhow!(
+                "Dictation is not supported on this platform"
+            ))
+            .ok();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// The transcript of the completed segments followed by `segment_text`.
+    fn transcript_with(&self, segment_text: &str) -> String {
+        let segment_text = segment_text.trim();
+        if self.completed_transcript.is_empty() {
+            segment_text.to_string()
+        } else if segment_text.is_empty() {
+            self.completed_transcript.clone()
+        } else {
+            format!("{} {segment_text}", self.completed_transcript)
+        }
+    }
+
+    /// Stops recording and transcribes the rest of the recording.
+    pub fn stop(&mut self, cx: &mut Context<Self>) {
+        if self.stopped {
+            return;
+        }
+        self.stopped = true;
+        self.stop_recording.store(true, Ordering::Relaxed);
+
+        let wav = segment_as_wav(&self.samples.lock()[self.segment_start..]);
+        let provider = self.provider.clone();
+        self._transcribe_task = cx.spawn(async move |this, cx| {
+            let result: Result<String> = match wav {
+                Some(wav) => provider.transcribe(wav).await,
+                None => Ok(String::new()),
+            };
+            this.update(cx, |this, cx| match result {
+                Ok(text) => cx.emit(DictationEvent::Transcript {
+                    text: this.transcript_with(&text),
+                    is_final: true,
+                }),
+                Err(error) => cx.emit(DictationEvent::Error(error.to_string().into())),
+            })
+            .ok();
+        });
+    }
+}
+
+impl Drop for Dictation {
+    fn drop(&mut self) {
+        self.stop_recording.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Encodes a segment of the recording, unless it is too short.
+fn segment_as_wav(samples: &[f32]) -> Option<Arc<[u8]>> {
+    let min_len =
+        (audio::SAMPLE_RATE.get() as f32 * MIN_TRANSCRIBED_DURATION.as_secs_f32()) as usize;
+    if samples.len() < min_len {
+        return None;
+    }
+    Some(audio::encode_wav(samples, audio::SAMPLE_RATE.get()).into())
+}
+
+/// Where the segment starting at `segment_start` ends, once it has reached
+/// [`MAX_SEGMENT_DURATION`]. It ends at the quietest 10ms of its last second,
+/// so that it's unlikely to cut a word in half.
+fn segment_end(samples: &[f32], segment_start: usize) -> Option<usize> {
+    let sample_rate = audio::SAMPLE_RATE.get() as usize;
+    let max_len = MAX_SEGMENT_DURATION.as_secs() as usize * sample_rate;
+    if samples.len() - segment_start < max_len {
+        return None;
+    }
+    let search_start = segment_start + max_len - sample_rate;
+    let energy = |buffer: &[f32]| buffer.iter().map(|sample| sample * sample).sum::<f32>();
+    samples[search_start..segment_start + max_len]
+        .chunks(audio::BUFFER_SIZE)
+        .enumerate()
+        .min_by(|(_, a), (_, b)| energy(a).total_cmp(&energy(b)))
+        .map(|(ix, _)| search_start + ix * audio::BUFFER_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{FutureExt as _, future::BoxFuture};
+    use gpui::{AppContext as _, TestAppContext};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Default)]
+    struct FakeTranscription {
+        recording_lens: Mutex<Vec<usize>>,
+    }
+
+    impl TranscriptionProvider for FakeTranscription {
+        fn transcribe(&self, wav: Arc<[u8]>) -> BoxFuture<'static, Result<String>> {
+            let mut recording_lens = self.recording_lens.lock();
+            recording_lens.push(wav.len());
+            let text = format!("transcript {}", recording_lens.len());
+            async move { Ok(text) }.boxed()
+        }
+    }
+
+    fn one_second_of_samples() -> Vec<f32> {
+        vec![0.; audio::SAMPLE_RATE.get() as usize]
+    }
+
+    fn record_events(
+        dictation: &gpui::Entity<Dictation>,
+        cx: &mut TestAppContext,
+    ) -> Rc<RefCell<Vec<String>>> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(dictation, move |_, event, _| {
+                events.borrow_mut().push(match event {
+                    DictationEvent::Transcript { text, is_final } => {
+                        format!("{text} (final: {is_final})")
+                    }
+                    DictationEvent::Error(error) => format!("error: {error}"),
+                })
+            })
+            .detach();
+        });
+        events
+    }
+
+    #[gpui::test]
+    async fn test_dictation_transcripts(cx: &mut TestAppContext) {
+        let provider = Arc::new(FakeTranscription::default());
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let stop_recording = Arc::new(AtomicBool::new(false));
+        let (error_tx, error_rx) = oneshot::channel();
+        drop(error_tx);
+        let dictation = cx.new(|cx| {
+            Dictation::with_recording(
+                provider.clone(),
+                samples.clone(),
+                stop_recording.clone(),
+                error_rx,
+                Duration::from_secs(1),
+                cx,
+            )
+        });
+        let events = record_events(&dictation, cx);
+
+        // Nothing is transcribed until enough has been recorded.
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert!(events.borrow().is_empty());
+
+        samples.lock().extend(one_second_of_samples());
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert_eq!(*events.borrow(), ["transcript 1 (final: false)"]);
+
+        // The recording isn't transcribed again until it grows.
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert_eq!(events.borrow().len(), 1);
+
+        samples.lock().extend(one_second_of_samples());
+        dictation.update(cx, |dictation, cx| dictation.stop(cx));
+        cx.run_until_parked();
+        assert!(stop_recording.load(Ordering::Relaxed));
+        assert!(dictation.read_with(cx, |dictation, _| dictation.is_stopped()));
+        assert_eq!(
+            *events.borrow(),
+            ["transcript 1 (final: false)", "transcript 2 (final: true)"]
+        );
+        let recording_lens = provider.recording_lens.lock().clone();
+        assert_eq!(recording_lens.len(), 2);
+        assert!(recording_lens[1] > recording_lens[0]);
+    }
+
+    #[gpui::test]
+    async fn test_dictation_segments(cx: &mut TestAppContext) {
+        let provider = Arc::new(FakeTranscription::default());
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (error_tx, error_rx) = oneshot::channel();
+        drop(error_tx);
+        let dictation = cx.new(|cx| {
+            Dictation::with_recording(
+                provider.clone(),
+                samples.clone(),
+                Arc::new(AtomicBool::new(false)),
+                error_rx,
+                Duration::from_secs(1),
+                cx,
+            )
+        });
+        let events = record_events(&dictation, cx);
+
+        // Once the recording is longer than a segment, the segment is
+        // completed and only what follows it is uploaded afterwards.
+        for _ in 0..MAX_SEGMENT_DURATION.as_secs() + 1 {
+            samples.lock().extend(one_second_of_samples());
+        }
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        samples.lock().extend(one_second_of_samples());
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        dictation.update(cx, |dictation, cx| dictation.stop(cx));
+        cx.run_until_parked();
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                "transcript 1 (final: false)",
+                "transcript 1 transcript 2 (final: false)",
+                "transcript 1 transcript 3 (final: true)",
+            ]
+        );
+        let recording_lens = provider.recording_lens.lock().clone();
+        assert!(recording_lens[1] < recording_lens[0]);
+        assert_eq!(recording_lens[2], recording_lens[1]);
+    }
+
+    #[gpui::test]
+    async fn test_dictation_too_short(cx: &mut TestAppContext) {
+        let provider = Arc::new(FakeTranscription::default());
+        let (error_tx, error_rx) = oneshot::channel();
+        drop(error_tx);
+        let dictation = cx.new(|cx| {
+            Dictation::with_recording(
+                provider.clone(),
+                Arc::new(Mutex::new(vec![0.; 10])),
+                Arc::new(AtomicBool::new(false)),
+                error_rx,
+                Duration::from_secs(1),
+                cx,
+            )
+        });
+        let events = record_events(&dictation, cx);
+
+        dictation.update(cx, |dictation, cx| dictation.stop(cx));
+        cx.run_until_parked();
+        assert_eq!(*events.borrow(), [" (final: true)"]);
+        assert!(provider.recording_lens.lock().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_dictation_recording_error(cx: &mut TestAppContext) {
+        let (error_tx, error_rx) = oneshot::channel();
+        let dictation = cx.new(|cx| {
+            Dictation::with_recording(
+                Arc::new(FakeTranscription::default()),
+                Arc::new(Mutex::new(Vec::new())),
+                Arc::new(AtomicBool::new(false)),
+                error_rx,
+                Duration::from_secs(1),
+                cx,
+            )
+        });
+        let events = record_events(&dictation, cx);
+
+        error_tx.send(anyhow::anyhow!("no microphone")).ok();
+        cx.run_until_parked();
+        assert_eq!(*events.borrow(), ["error: no microphone"]);
+        assert!(dictation.read_with(cx, |dictation, _| dictation.is_stopped()));
+    }
+}
@@ -0,0 +1,85 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use audio::{Audio, SpeechPlayback};
+use gpui::{Context, EventEmitter, SharedString, Task};
+
+use crate::{TextToSpeechProvider, split_into_utterances};
+
+#[derive(Clone, Debug)]
+pub enum ReadAloudEvent {
+    Finished,
+    Error(SharedString),
+}
+
+/// Reads text aloud, synthesizing the next utterance while the previous one plays.
+pub struct ReadAloud {
+    playback: Option<Arc<SpeechPlayback>>,
+    _task: Task<()>,
+}
+
+impl EventEmitter<ReadAloudEvent> for ReadAloud {}
+
+impl ReadAloud {
+    pub fn new(
+        provider: Arc<dyn TextToSpeechProvider>,
+        text: String,
+        voice: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let playback = match Audio::play_speech(cx) {
+            Ok(playback) => Arc::new(playback),
+            Err(error) => {
+                return Self {
+                    playback: None,
+                    _task: cx.spawn(async move |this, cx| {
+                        this.update(cx, |_, cx| {
+                            cx.emit(ReadAloudEvent::Error(error.to_string().into()))
+                        })
+                        .ok();
+                    }),
+                };
+            }
+        };
+
+        let task = cx.spawn({
+            let playback = playback.clone();
+            async move |this, cx| {
+                let result: Result<()> = async {
+                    for utterance in split_into_utterances(&text) {
+                        let wav = provider.synthesize(utterance, voice.clone()).await?;
+                        let playback = playback.clone();
+                        cx.background_spawn(async move { playback.append_wav(wav) })
+                            .await?;
+                    }
+                    while !playback.is_idle() {
+                        cx.background_executor()
+                            .timer(Duration::from_millis(100))
+                            .await;
+                    }
+                    Ok(())
+                }
+                .await;
+
+                this.update(cx, |_, cx| match result {
+                    Ok(()) => cx.emit(ReadAloudEvent::Finished),
+                    Err(error) => cx.emit(ReadAloudEvent::Error(error.to_string().into())),
+                })
+                .ok();
+            }
+        });
+
+        Self {
+            playback: Some(playback),
+            _task: task,
+        }
+    }
+
+    pub fn stop(&mut self, cx: &mut Context<Self>) {
+        if let Some(playback) = self.playback.take() {
+            playback.stop();
+        }
+        self._task = Task::ready(());
+        cx.emit(ReadAloudEvent::Finished);
+    }
+}
@@ -0,0 +1,21 @@
+mod dictation;
+mod open_ai_compatible;
+mod read_aloud;
+mod speech_settings;
+mod text_to_speech;
+mod transcription;
+
+use gpui::App;
+use settings::Settings as _;
+
+pub use dictation::{Dictation, DictationEvent};
+pub use read_aloud::{ReadAloud, ReadAloudEvent};
+pub use speech_settings::SpeechSettings;
+pub use text_to_speech::{
+    TextToSpeechProvider, speakable_text, split_into_utterances, text_to_speech_provider,
+};
+pub use transcription::{TranscriptionProvider, transcription_provider};
+
+pub fn init(cx: &mut App) {
+    SpeechSettings::register(cx);
+}
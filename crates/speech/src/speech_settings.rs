@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use settings::{Settings, TextToSpeechProviderContent, TranscriptionProviderContent};
+
+#[derive(Clone, Debug)]
+pub struct SpeechSettings {
+    /// The speech-to-text backend, if dictation is configured.
+    pub transcription: Option<TranscriptionProviderContent>,
+    /// How often the audio recorded so far is transcribed while dictating.
+    pub partial_transcript_interval: Duration,
+    /// The text-to-speech backend, if reading aloud is configured.
+    pub text_to_speech: Option<TextToSpeechProviderContent>,
+    /// Whether the final message of each agent turn is read aloud.
+    pub read_agent_responses_aloud: bool,
+}
+
+impl Settings for SpeechSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let speech = content.speech.as_ref().unwrap();
+        Self {
+            transcription: speech.transcription.clone(),
+            partial_transcript_interval: Duration::from_millis(
+                speech.partial_transcript_interval_ms.unwrap(),
+            ),
+            text_to_speech: speech.text_to_speech.clone(),
+            read_agent_responses_aloud: speech.read_agent_responses_aloud.unwrap(),
+        }
+    }
+}
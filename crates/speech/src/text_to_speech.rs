@@ -0,0 +1,324 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result, anyhow};
+use credentials_provider::load_api_key;
+use futures::{AsyncReadExt as _, AsyncWriteExt as _, FutureExt as _, future::BoxFuture};
+use gpui::{App, SharedString, Task};
+use http_client::{AsyncBody, HttpClient, Method};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::Serialize;
+use settings::{Settings as _, TextToSpeechProviderContent};
+
+use crate::SpeechSettings;
+
+/// Utterances are synthesized one at a time, so that playback can start
+/// before the whole response has been synthesized.
+const MAX_UTTERANCE_LEN: usize = 400;
+
+/// A text-to-speech backend.
+pub trait TextToSpeechProvider: Send + Sync {
+    /// Synthesizes `text` into a WAV file, using the provider's default voice
+    /// when `voice` is `None`.
+    fn synthesize(
+        &self,
+        text: String,
+        voice: Option<SharedString>,
+    ) -> BoxFuture<'static, Result<Vec<u8>>>;
+}
+
+/// Builds the text-to-speech provider configured in [`SpeechSettings`].
+pub fn text_to_speech_provider(
+    http_client: Arc<dyn HttpClient>,
+    cx: &App,
+) -> Task<Result<Arc<dyn TextToSpeechProvider>>> {
+    let Some(content) = SpeechSettings::get_global(cx).text_to_speech.clone() else {
+        return Task::ready(Err(anyhow!(
+            "Reading aloud is not configured. Set `speech.text_to_speech` in your settings."
+        )));
+    };
+
+    match content {
+        TextToSpeechProviderContent::OpenAiCompatible {
+            api_url,
+            model,
+            voice,
+            api_key_env_var,
+        } => cx.spawn(async move |cx| {
+            let api_key = load_api_key(&api_url, api_key_env_var.as_deref(), cx).await?;
+            Ok(Arc::new(OpenAiCompatibleTextToSpeech {
+                http_client,
+                api_url,
+                model,
+                voice: voice.into(),
+                api_key,
+            }) as Arc<dyn TextToSpeechProvider>)
+        }),
+        TextToSpeechProviderContent::Local {
+            command,
+            args,
+            voice,
+        } => Task::ready(Ok(Arc::new(LocalTextToSpeech {
+            command,
+            args,
+            voice: voice.map(Into::into),
+        }))),
+    }
+}
+
+struct OpenAiCompatibleTextToSpeech {
+    http_client: Arc<dyn HttpClient>,
+    api_url: String,
+    model: String,
+    voice: SharedString,
+    api_key: Option<Arc<str>>,
+}
+
+#[derive(Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: &'static str,
+}
+
+impl TextToSpeechProvider for OpenAiCompatibleTextToSpeech {
+    fn synthesize(
+        &self,
+        text: String,
+        voice: Option<SharedString>,
+    ) -> BoxFuture<'static, Result<Vec<u8>>> {
+        let http_client = self.http_client.clone();
+        let uri = format!("{}/audio/speech", self.api_url.trim_end_matches('/'));
+        let body = serde_json::to_string(&SpeechRequest {
+            model: &self.model,
+            input: &text,
+            voice: voice.as_deref().unwrap_or(&self.voice),
+            response_format: "wav",
+        });
+        let api_key = self.api_key.clone();
+
+        async move {
+            let mut request = http_client::Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("Content-Type", "application/json");
+            if let Some(api_key) = api_key {
+                request = request.header("Authorization", format!("Bearer {}", api_key.trim()));
+            }
+            let request = request.body(AsyncBody::from(body?))?;
+            let mut response = http_client
+                .send(request)
+                .await
+                .context("failed to send speech request")?;
+
+            let mut body = Vec::new();
+            response.body_mut().read_to_end(&mut body).await?;
+            anyhow::ensure!(
+                response.status().is_success(),
+                "speech request failed.\nStatus: {:?}\nBody: {}",
+                response.status(),
+                String::from_utf8_lossy(&body),
+            );
+            Ok(body)
+        }
+        .boxed()
+    }
+}
+
+struct LocalTextToSpeech {
+    command: PathBuf,
+    args: Vec<String>,
+    voice: Option<SharedString>,
+}
+
+impl TextToSpeechProvider for LocalTextToSpeech {
+    fn synthesize(
+        &self,
+        text: String,
+        voice: Option<SharedString>,
+    ) -> BoxFuture<'static, Result<Vec<u8>>> {
+        let command = self.command.clone();
+        let args = self.args.clone();
+        let voice = voice.or_else(|| self.voice.clone());
+
+        async move {
+            let file = tempfile::Builder::new()
+                .prefix("jamu-speech")
+                .suffix(".wav")
+                .tempfile()?;
+
+            let file_path = file.path().to_string_lossy();
+            let mut child = util::command::new_smol_command(&command)
+                .args(args.iter().map(|arg| match arg.as_str() {
+                    "{file}" => file_path.as_ref(),
+                    "{voice}" => voice.as_deref().unwrap_or_default(),
+                    arg => arg,
+                }))
+                .stdin(smol::process::Stdio::piped())
+                .stdout(smol::process::Stdio::null())
+                .stderr(smol::process::Stdio::piped())
+                .spawn()
+                .with_context(|| format!("failed to run {}", command.display()))?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(text.as_bytes()).await?;
+                stdin.close().await?;
+            }
+            let output = child.output().await?;
+            anyhow::ensure!(
+                output.status.success(),
+                "{} exited with {}: {}",
+                command.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Ok(smol::fs::read(file.path()).await?)
+        }
+        .boxed()
+    }
+}
+
+/// Extracts the prose of an agent message, leaving out code blocks, HTML and
+/// markdown syntax that should not be read aloud.
+pub fn speakable_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut in_code_block = false;
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(chunk) | Event::Code(chunk) if !in_code_block => text.push_str(&chunk),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak
+            | Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item) => {
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+/// Splits text into utterances of at most [`MAX_UTTERANCE_LEN`] bytes, breaking
+/// at the end of lines or sentences where possible. Longer sentences are broken
+/// between words, and longer words wherever the limit falls.
+pub fn split_into_utterances(text: &str) -> Vec<String> {
+    let mut utterances = Vec::new();
+    let mut current = String::new();
+    let sentences = split_sentences(text)
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty());
+    for sentence in sentences {
+        if sentence.len() <= MAX_UTTERANCE_LEN {
+            push_to_utterance(&mut utterances, &mut current, sentence);
+            continue;
+        }
+        for word in sentence.split_whitespace() {
+            let mut word = word;
+            while word.len() > MAX_UTTERANCE_LEN {
+                let mut end = MAX_UTTERANCE_LEN;
+                while !word.is_char_boundary(end) {
+                    end -= 1;
+                }
+                let (head, tail) = word.split_at(end);
+                push_to_utterance(&mut utterances, &mut current, head);
+                word = tail;
+            }
+            push_to_utterance(&mut utterances, &mut current, word);
+        }
+    }
+    if !current.is_empty() {
+        utterances.push(current);
+    }
+    utterances
+}
+
+/// Splits text after line breaks and after sentence punctuation followed by
+/// whitespace, so that numbers such as "3.5" stay together.
+fn split_sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while let Some((ix, c)) = chars.next() {
+            let ends_sentence = c == '\n'
+                || (matches!(c, '.' | '!' | '?')
+                    && chars.peek().is_none_or(|(_, next)| next.is_whitespace()));
+            if ends_sentence {
+                let sentence = &text[start..ix + c.len_utf8()];
+                start = ix + c.len_utf8();
+                return Some(sentence);
+            }
+        }
+        let rest = &text[start..];
+        start = text.len();
+        (!rest.is_empty()).then_some(rest)
+    })
+}
+
+/// Appends `text` to the current utterance, starting a new one if it would
+/// otherwise grow past [`MAX_UTTERANCE_LEN`].
+fn push_to_utterance(utterances: &mut Vec<String>, current: &mut String, text: &str) {
+    if !current.is_empty() && current.len() + text.len() + 1 > MAX_UTTERANCE_LEN {
+        utterances.push(std::mem::take(current));
+    }
+    if !current.is_empty() {
+        current.push(' ');
+    }
+    current.push_str(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speakable_text() {
+        let markdown = "# Mix notes\n\nThe kick is *too loud*, lower it by `3dB`.\n\n```python\nset_volume(-3)\n```\n\n- Then bounce the stems.\n";
+        assert_eq!(
+            speakable_text(markdown),
+            "Mix notes\nThe kick is too loud, lower it by 3dB.\nThen bounce the stems."
+        );
+    }
+
+    #[test]
+    fn test_split_into_utterances() {
+        assert_eq!(
+            split_into_utterances("One. Two!\nThree?"),
+            vec!["One. Two! Three?".to_string()]
+        );
+
+        // Decimal points and version numbers don't end sentences.
+        let sentence = format!("Lower it by 3.5dB in v1.2. {}", "a".repeat(390));
+        assert_eq!(
+            split_into_utterances(&sentence),
+            vec!["Lower it by 3.5dB in v1.2.".to_string(), "a".repeat(390)]
+        );
+
+        let sentence = format!("{}.", "a".repeat(300));
+        let text = format!("{sentence} {sentence}");
+        assert_eq!(
+            split_into_utterances(&text),
+            vec![sentence.clone(), sentence]
+        );
+
+        // Sentences longer than the limit are split between words.
+        let sentence = format!("{}end.", "word ".repeat(100));
+        let utterances = split_into_utterances(&sentence);
+        assert_eq!(utterances.len(), 2);
+        assert!(utterances.iter().all(|utterance| {
+            utterance.len() <= MAX_UTTERANCE_LEN
+                && utterance
+                    .split(' ')
+                    .all(|word| word == "word" || word == "end.")
+        }));
+        assert_eq!(utterances.join(" "), sentence);
+
+        // Words longer than the limit are split wherever it falls.
+        let word = "é".repeat(300);
+        assert_eq!(
+            split_into_utterances(&word),
+            vec!["é".repeat(200), "é".repeat(100)]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result, anyhow};
+use credentials_provider::load_api_key;
+use futures::{AsyncReadExt as _, FutureExt as _, future::BoxFuture};
+use gpui::{App, Task};
+use http_client::{AsyncBody, HttpClient, Method};
+use serde::Deserialize;
+use settings::{Settings as _, TranscriptionProviderContent};
+
+use crate::{SpeechSettings, open_ai_compatible};
+
+/// A speech-to-text backend.
+pub trait TranscriptionProvider: Send + Sync {
+    /// Transcribes a complete recording, encoded as a WAV file.
+    fn transcribe(&self, wav: Arc<[u8]>) -> BoxFuture<'static, Result<String>>;
+}
+
+/// Builds the transcription provider configured in [`SpeechSettings`].
+pub fn transcription_provider(
+    http_client: Arc<dyn HttpClient>,
+    cx: &App,
+) -> Task<Result<Arc<dyn TranscriptionProvider>>> {
+    let Some(content) = SpeechSettings::get_global(cx).transcription.clone() else {
+        return Task::ready(Err(anyhow!(
+            "Dictation is not configured. Set `speech.transcription` in your settings."
+        )));
+    };
+
+    match content {
+        TranscriptionProviderContent::OpenAiCompatible {
+            api_url,
+            model,
+            language,
+            api_key_env_var,
+        } => cx.spawn(async move |cx| {
+            let api_key = load_api_key(&api_url, api_key_env_var.as_deref(), cx).await?;
+            Ok(Arc::new(OpenAiCompatibleTranscription {
+                http_client,
+                api_url,
+                model,
+                language,
+                api_key,
+            }) as Arc<dyn TranscriptionProvider>)
+        }),
+        TranscriptionProviderContent::Local { command, args } => {
+            Task::ready(Ok(Arc::new(LocalTranscription { command, args })))
+        }
+    }
+}
+
+struct OpenAiCompatibleTranscription {
+    http_client: Arc<dyn HttpClient>,
+    api_url: String,
+    model: String,
+    language: Option<String>,
+    api_key: Option<Arc<str>>,
+}
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+impl TranscriptionProvider for OpenAiCompatibleTranscription {
+    fn transcribe(&self, wav: Arc<[u8]>) -> BoxFuture<'static, Result<String>> {
+        let http_client = self.http_client.clone();
+        let uri = format!(
+            "{}/audio/transcriptions",
+            self.api_url.trim_end_matches('/')
+        );
+        let mut form = open_ai_compatible::MultipartForm::new()
+            .text("model", &self.model)
+            .text("response_format", "json");
+        if let Some(language) = &self.language {
+            form = form.text("language", language);
+        }
+        let form = form.file("file", "dictation.wav", "audio/wav", &wav);
+        let api_key = self.api_key.clone();
+
+        async move {
+            let mut request = http_client::Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("Content-Type", form.content_type());
+            if let Some(api_key) = api_key {
+                request = request.header("Authorization", format!("Bearer {}", api_key.trim()));
+            }
+            let request = request.body(AsyncBody::from(form.finish()))?;
+            let mut response = http_client
+                .send(request)
+                .await
+                .context("failed to send transcription request")?;
+
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await?;
+            anyhow::ensure!(
+                response.status().is_success(),
+                "transcription request failed.\nStatus: {:?}\nBody: {body}",
+                response.status(),
+            );
+            let response: TranscriptionResponse = serde_json::from_str(&body)?;
+            Ok(response.text.trim().to_string())
+        }
+        .boxed()
+    }
+}
+
+struct LocalTranscription {
+    command: PathBuf,
+    args: Vec<String>,
+}
+
+impl TranscriptionProvider for LocalTranscription {
+    fn transcribe(&self, wav: Arc<[u8]>) -> BoxFuture<'static, Result<String>> {
+        let command = self.command.clone();
+        let args = self.args.clone();
+
+        async move {
+            let file = tempfile::Builder::new()
+                .prefix("jamu-dictation")
+                .suffix(".wav")
+                .tempfile()?;
+            smol::fs::write(file.path(), &wav).await?;
+
+            let file_path = file.path().to_string_lossy();
+            let output = util::command::new_smol_command(&command)
+                .args(args.iter().map(|arg| {
+                    if arg == "{file}" {
+                        file_path.as_ref()
+                    } else {
+                        arg.as_str()
+                    }
+                }))
+                .output()
+                .await
+                .with_context(|| format!("failed to run {}", command.display()))?;
+            anyhow::ensure!(
+                output.status.success(),
+                "{} exited with {}: {}",
+                command.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        .boxed()
+    }
+}
@@ -1269,6 +1269,13 @@ impl Terminal {
         terminal.total_lines()
     }
 
+    /// Whether the scrollback is full, so that new output pushes the oldest
+    /// lines out of the history.
+    pub fn history_is_full(&self) -> bool {
+        let term = self.term.lock_unfair();
+        term.history_size() >= self.term_config.scrolling_history
+    }
+
     pub fn viewport_lines(&self) -> usize {
         let term = self.term.clone();
         let terminal = term.lock_unfair();
@@ -10,6 +10,10 @@ pub fn init(cx: &mut App) {
     cx.set_global(GlobalWebSearchRegistry(registry));
 }
 
+/// The id of the provider backed by Zed's cloud, which can only be used by
+/// Zed-hosted models.
+pub const ZED_WEB_SEARCH_PROVIDER_ID: &str = "zed.dev";
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
 pub struct WebSearchProviderId(pub SharedString);
 
@@ -37,10 +41,19 @@ impl WebSearchRegistry {
         cx.global::<GlobalWebSearchRegistry>().0.read(cx)
     }
 
+    pub fn try_read_global(cx: &App) -> Option<&Self> {
+        cx.try_global::<GlobalWebSearchRegistry>()
+            .map(|registry| registry.0.read(cx))
+    }
+
     pub fn providers(&self) -> impl Iterator<Item = &Arc<dyn WebSearchProvider>> {
         self.providers.values()
     }
 
+    pub fn provider(&self, id: &WebSearchProviderId) -> Option<Arc<dyn WebSearchProvider>> {
+        self.providers.get(id).cloned()
+    }
+
     pub fn active_provider(&self) -> Option<Arc<dyn WebSearchProvider>> {
         self.active_provider.clone()
     }
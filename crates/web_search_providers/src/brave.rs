@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use cloud_llm_client::{WebSearchResponse, WebSearchResult};
+use credentials_provider::load_api_key;
+use gpui::{App, Task};
+use http_client::{AsyncBody, HttpClient, Method};
+use serde::Deserialize;
+use web_search::{WebSearchProvider, WebSearchProviderId};
+
+use crate::send_search_request;
+
+pub const BRAVE_WEB_SEARCH_PROVIDER_ID: &str = "brave";
+
+/// The most results the Brave Search API returns for a single request.
+const MAX_COUNT: usize = 20;
+
+pub struct BraveWebSearchProvider {
+    http_client: Arc<dyn HttpClient>,
+    api_url: String,
+    api_key_env_var: Option<String>,
+    max_results: usize,
+}
+
+impl BraveWebSearchProvider {
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        api_url: String,
+        api_key_env_var: Option<String>,
+        max_results: usize,
+    ) -> Self {
+        Self {
+            http_client,
+            api_url,
+            api_key_env_var,
+            max_results,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    /// Missing when nothing was found.
+    #[serde(default)]
+    web: Option<BraveWebResults>,
+}
+
+#[derive(Deserialize)]
+struct BraveWebResults {
+    results: Vec<BraveResult>,
+}
+
+#[derive(Deserialize)]
+struct BraveResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+impl WebSearchProvider for BraveWebSearchProvider {
+    fn id(&self) -> WebSearchProviderId {
+        WebSearchProviderId(BRAVE_WEB_SEARCH_PROVIDER_ID.into())
+    }
+
+    fn search(&self, query: String, cx: &mut App) -> Task<Result<WebSearchResponse>> {
+        let http_client = self.http_client.clone();
+        let api_url = self.api_url.clone();
+        let api_key_env_var = self.api_key_env_var.clone();
+        let count = self.max_results.min(MAX_COUNT);
+        cx.spawn(async move |cx| {
+            let api_key = load_api_key(&api_url, api_key_env_var.as_deref(), cx)
+                .await?
+                .with_context(|| format!("no Brave Search API key is stored for {api_url}"))?;
+            let uri = format!(
+                "{}/web/search?q={}&count={count}",
+                api_url.trim_end_matches('/'),
+                urlencoding::encode(&query)
+            );
+            let request = http_client::Request::builder()
+                .method(Method::GET)
+                .uri(uri)
+                .header("Accept", "application/json")
+                .header("X-Subscription-Token", api_key.trim())
+                .body(AsyncBody::empty())?;
+            let body = send_search_request(&http_client, request).await?;
+            let response: BraveResponse =
+                serde_json::from_slice(&body).context("invalid Brave Search response")?;
+            Ok(WebSearchResponse {
+                results: response
+                    .web
+                    .map(|web| web.results)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .take(count)
+                    .map(|result| WebSearchResult {
+                        title: result.title,
+                        url: result.url,
+                        text: result.description,
+                    })
+                    .collect(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_brave_search(cx: &mut TestAppContext) {
+        let expected_key = std::env::var("PATH").unwrap();
+        let http_client = FakeHttpClient::create(move |request| {
+            let expected_key = expected_key.clone();
+            async move {
+                assert_eq!(
+                    request.uri().to_string(),
+                    "http://localhost:8080/res/v1/web/search?q=zed%20editor&count=20"
+                );
+                assert_eq!(
+                    request.headers()["X-Subscription-Token"].to_str().unwrap(),
+                    expected_key.trim()
+                );
+                Ok(Response::builder()
+                    .status(200)
+                    .body(
+                        json!({
+                            "type": "search",
+                            "web": {
+                                "results": [{
+                                    "title": "Zed",
+                                    "url": "https://zed.dev",
+                                    "description": "A code editor"
+                                }]
+                            }
+                        })
+                        .to_string()
+                        .into(),
+                    )
+                    .unwrap())
+            }
+        });
+        // Requests are capped at what the API allows.
+        let provider = BraveWebSearchProvider::new(
+            http_client,
+            "http://localhost:8080/res/v1".into(),
+            Some("PATH".into()),
+            50,
+        );
+
+        let response = cx
+            .update(|cx| provider.search("zed editor".into(), cx))
+            .await
+            .unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].title, "Zed");
+        assert_eq!(response.results[0].url, "https://zed.dev");
+        assert_eq!(response.results[0].text, "A code editor");
+    }
+
+    #[gpui::test]
+    async fn test_brave_search_without_results(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|_| async move {
+            Ok(Response::builder()
+                .status(200)
+                .body(json!({ "type": "search" }).to_string().into())
+                .unwrap())
+        });
+        let provider = BraveWebSearchProvider::new(
+            http_client,
+            "http://localhost:8080/res/v1".into(),
+            Some("PATH".into()),
+            10,
+        );
+
+        let response = cx
+            .update(|cx| provider.search("nothing".into(), cx))
+            .await
+            .unwrap();
+        assert!(response.results.is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use futures::AsyncReadExt as _;
 use gpui::{App, AppContext, Context, Entity, Subscription, Task};
 use http_client::{HttpClient, Method};
 use language_model::{LlmApiToken, RefreshLlmTokenListener};
-use web_search::{WebSearchProvider, WebSearchProviderId};
+use web_search::{WebSearchProvider, WebSearchProviderId, ZED_WEB_SEARCH_PROVIDER_ID};
 
 pub struct CloudWebSearchProvider {
     state: Entity<State>,
@@ -50,8 +50,6 @@ impl State {
     }
 }
 
-pub const ZED_WEB_SEARCH_PROVIDER_ID: &str = "zed.dev";
-
 impl WebSearchProvider for CloudWebSearchProvider {
     fn id(&self) -> WebSearchProviderId {
         WebSearchProviderId(ZED_WEB_SEARCH_PROVIDER_ID.into())
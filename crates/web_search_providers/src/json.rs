@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use cloud_llm_client::{WebSearchResponse, WebSearchResult};
+use collections::HashMap;
+use gpui::{App, Task};
+use http_client::{AsyncBody, HttpClient, Method};
+use settings::WebSearchResponseMappingContent;
+use web_search::{WebSearchProvider, WebSearchProviderId};
+
+use crate::send_search_request;
+
+/// Searches any HTTP endpoint that answers with JSON, picking the results out
+/// of the response with JSON pointers.
+pub struct JsonWebSearchProvider {
+    id: WebSearchProviderId,
+    http_client: Arc<dyn HttpClient>,
+    url: String,
+    headers: HashMap<String, String>,
+    api_key_env_var: Option<String>,
+    mapping: WebSearchResponseMappingContent,
+    max_results: usize,
+}
+
+impl JsonWebSearchProvider {
+    pub fn new(
+        id: WebSearchProviderId,
+        http_client: Arc<dyn HttpClient>,
+        url: String,
+        headers: HashMap<String, String>,
+        api_key_env_var: Option<String>,
+        mapping: WebSearchResponseMappingContent,
+        max_results: usize,
+    ) -> Self {
+        Self {
+            id,
+            http_client,
+            url,
+            headers,
+            api_key_env_var,
+            mapping,
+            max_results,
+        }
+    }
+}
+
+impl WebSearchProvider for JsonWebSearchProvider {
+    fn id(&self) -> WebSearchProviderId {
+        self.id.clone()
+    }
+
+    fn search(&self, query: String, cx: &mut App) -> Task<Result<WebSearchResponse>> {
+        let http_client = self.http_client.clone();
+        let uri = self.url.replace("{query}", &urlencoding::encode(&query));
+        let headers = self.headers.clone();
+        let api_key_env_var = self.api_key_env_var.clone();
+        let mapping = self.mapping.clone();
+        let max_results = self.max_results;
+        cx.background_spawn(async move {
+            let mut request = http_client::Request::builder()
+                .method(Method::GET)
+                .uri(uri)
+                .header("Accept", "application/json");
+            for (name, value) in &headers {
+                request = request.header(name, value);
+            }
+            if let Some(env_var) = api_key_env_var {
+                let api_key = std::env::var(&env_var)
+                    .with_context(|| format!("environment variable {env_var} is not set"))?;
+                request = request.header("Authorization", format!("Bearer {}", api_key.trim()));
+            }
+            let request = request.body(AsyncBody::empty())?;
+            let body = send_search_request(&http_client, request).await?;
+            let response: serde_json::Value =
+                serde_json::from_slice(&body).context("invalid web search response")?;
+            map_response(&response, &mapping, max_results)
+        })
+    }
+}
+
+fn map_response(
+    response: &serde_json::Value,
+    mapping: &WebSearchResponseMappingContent,
+    max_results: usize,
+) -> Result<WebSearchResponse> {
+    let results = response
+        .pointer(&mapping.results)
+        .and_then(|results| results.as_array())
+        .with_context(|| format!("no array of results at {} in the response", mapping.results))?;
+    let string_at = |result: &serde_json::Value, pointer: &str| {
+        result
+            .pointer(pointer)
+            .and_then(|value| value.as_str())
+            .map(ToString::to_string)
+    };
+    Ok(WebSearchResponse {
+        results: results
+            .iter()
+            // Results without a title or URL can't be linked to, so skip them.
+            .filter_map(|result| {
+                Some(WebSearchResult {
+                    title: string_at(result, &mapping.title)?,
+                    url: string_at(result, &mapping.url)?,
+                    text: string_at(result, &mapping.text).unwrap_or_default(),
+                })
+            })
+            .take(max_results)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_json_search(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(
+                request.uri().to_string(),
+                "http://localhost:9000/api?q=a%26b&limit=50"
+            );
+            assert_eq!(request.headers()["X-Client"].to_str().unwrap(), "jamu");
+            Ok(Response::builder()
+                .status(200)
+                .body(
+                    json!({
+                        "data": {
+                            "items": [
+                                { "name": "First", "link": "https://one.example", "summary": { "text": "One" } },
+                                { "name": "No link" },
+                                { "name": "Second", "link": "https://two.example" },
+                                { "name": "Third", "link": "https://three.example" },
+                            ]
+                        }
+                    })
+                    .to_string()
+                    .into(),
+                )
+                .unwrap())
+        });
+        let provider = JsonWebSearchProvider::new(
+            WebSearchProviderId("my-search".into()),
+            http_client,
+            "http://localhost:9000/api?q={query}&limit=50".into(),
+            HashMap::from_iter([("X-Client".to_string(), "jamu".to_string())]),
+            None,
+            WebSearchResponseMappingContent {
+                results: "/data/items".into(),
+                title: "/name".into(),
+                url: "/link".into(),
+                text: "/summary/text".into(),
+            },
+            2,
+        );
+
+        let response = cx
+            .update(|cx| provider.search("a&b".into(), cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            response
+                .results
+                .iter()
+                .map(|result| (
+                    result.title.as_str(),
+                    result.url.as_str(),
+                    result.text.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("First", "https://one.example", "One"),
+                ("Second", "https://two.example", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_results() {
+        let mapping = WebSearchResponseMappingContent {
+            results: "/results".into(),
+            title: "/title".into(),
+            url: "/url".into(),
+            text: "/text".into(),
+        };
+        let error = map_response(&json!({ "error": "rate limited" }), &mapping, 10).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no array of results at /results in the response"
+        );
+    }
+}
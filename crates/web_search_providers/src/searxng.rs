@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use cloud_llm_client::{WebSearchResponse, WebSearchResult};
+use gpui::{App, Task};
+use http_client::{AsyncBody, HttpClient, Method};
+use serde::Deserialize;
+use web_search::{WebSearchProvider, WebSearchProviderId};
+
+use crate::send_search_request;
+
+pub const SEARXNG_WEB_SEARCH_PROVIDER_ID: &str = "searxng";
+
+/// Searches a SearxNG instance through its JSON output format, which has to be
+/// enabled in the instance's `search.formats` setting.
+pub struct SearxngWebSearchProvider {
+    http_client: Arc<dyn HttpClient>,
+    url: String,
+    max_results: usize,
+}
+
+impl SearxngWebSearchProvider {
+    pub fn new(http_client: Arc<dyn HttpClient>, url: String, max_results: usize) -> Self {
+        Self {
+            http_client,
+            url,
+            max_results,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearxngResponse {
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Deserialize)]
+struct SearxngResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl WebSearchProvider for SearxngWebSearchProvider {
+    fn id(&self) -> WebSearchProviderId {
+        WebSearchProviderId(SEARXNG_WEB_SEARCH_PROVIDER_ID.into())
+    }
+
+    fn search(&self, query: String, cx: &mut App) -> Task<Result<WebSearchResponse>> {
+        let http_client = self.http_client.clone();
+        let uri = format!(
+            "{}/search?q={}&format=json",
+            self.url.trim_end_matches('/'),
+            urlencoding::encode(&query)
+        );
+        let max_results = self.max_results;
+        cx.background_spawn(async move {
+            let request = http_client::Request::builder()
+                .method(Method::GET)
+                .uri(uri)
+                .header("Accept", "application/json")
+                .body(AsyncBody::empty())?;
+            let body = send_search_request(&http_client, request).await?;
+            let response: SearxngResponse =
+                serde_json::from_slice(&body).context("invalid SearxNG response")?;
+            Ok(WebSearchResponse {
+                results: response
+                    .results
+                    .into_iter()
+                    .take(max_results)
+                    .map(|result| WebSearchResult {
+                        title: result.title,
+                        url: result.url,
+                        text: result.content.unwrap_or_default(),
+                    })
+                    .collect(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_searxng_search(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(
+                request.uri().to_string(),
+                "http://localhost:8888/search?q=rust%20gpui&format=json"
+            );
+            let results = (0..5)
+                .map(|ix| {
+                    json!({
+                        "title": format!("Result {ix}"),
+                        "url": format!("https://example.com/{ix}"),
+                        "content": format!("Snippet {ix}"),
+                        "engine": "duckduckgo",
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(Response::builder()
+                .status(200)
+                .body(
+                    json!({ "query": "rust gpui", "results": results })
+                        .to_string()
+                        .into(),
+                )
+                .unwrap())
+        });
+        let provider =
+            SearxngWebSearchProvider::new(http_client, "http://localhost:8888/".into(), 3);
+
+        let response = cx
+            .update(|cx| provider.search("rust gpui".into(), cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            response
+                .results
+                .iter()
+                .map(|result| (
+                    result.title.as_str(),
+                    result.url.as_str(),
+                    result.text.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("Result 0", "https://example.com/0", "Snippet 0"),
+                ("Result 1", "https://example.com/1", "Snippet 1"),
+                ("Result 2", "https://example.com/2", "Snippet 2"),
+            ]
+        );
+    }
+}
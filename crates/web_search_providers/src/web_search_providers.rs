@@ -1,12 +1,23 @@
+mod brave;
 mod cloud;
+mod json;
+mod searxng;
+mod web_search_settings;
 
+use anyhow::{Context as _, Result};
 use client::Client;
+use futures::AsyncReadExt as _;
 use gpui::{App, Context, Entity};
+use http_client::{AsyncBody, HttpClient};
 use language_model::LanguageModelRegistry;
+use settings::{Settings as _, SettingsStore, WebSearchProviderContent};
 use std::sync::Arc;
-use web_search::{WebSearchProviderId, WebSearchRegistry};
+use web_search::{WebSearchProviderId, WebSearchRegistry, ZED_WEB_SEARCH_PROVIDER_ID};
+
+pub use web_search_settings::WebSearchSettings;
 
 pub fn init(client: Arc<Client>, cx: &mut App) {
+    WebSearchSettings::register(cx);
     let registry = WebSearchRegistry::global(cx);
     registry.update(cx, |registry, cx| {
         register_web_search_providers(registry, client, cx);
@@ -25,6 +36,24 @@ fn register_web_search_providers(
         cx,
     );
 
+    let http_client = client.http_client();
+    let mut configured_providers = Vec::new();
+    register_configured_web_search_providers(
+        registry,
+        &mut configured_providers,
+        http_client.clone(),
+        cx,
+    );
+    cx.observe_global::<SettingsStore>(move |this, cx| {
+        register_configured_web_search_providers(
+            this,
+            &mut configured_providers,
+            http_client.clone(),
+            cx,
+        );
+    })
+    .detach();
+
     cx.subscribe(
         &LanguageModelRegistry::global(cx),
         move |this, registry, event, cx| {
@@ -49,8 +78,108 @@ fn register_zed_web_search_provider(
     if using_zed_provider {
         registry.register_provider(cloud::CloudWebSearchProvider::new(client, cx), cx)
     } else {
-        registry.unregister_provider(WebSearchProviderId(
-            cloud::ZED_WEB_SEARCH_PROVIDER_ID.into(),
-        ));
+        registry.unregister_provider(WebSearchProviderId(ZED_WEB_SEARCH_PROVIDER_ID.into()));
     }
+    update_active_provider(registry, cx);
+}
+
+/// Replaces the providers from the `web_search` settings when they change.
+fn register_configured_web_search_providers(
+    registry: &mut WebSearchRegistry,
+    configured_providers: &mut Vec<WebSearchProviderContent>,
+    http_client: Arc<dyn HttpClient>,
+    cx: &mut Context<WebSearchRegistry>,
+) {
+    let providers = &WebSearchSettings::get_global(cx).providers;
+    if providers == configured_providers {
+        update_active_provider(registry, cx);
+        return;
+    }
+
+    for provider in configured_providers.drain(..) {
+        registry.unregister_provider(web_search_settings::provider_id(&provider));
+    }
+    configured_providers.clone_from(providers);
+    for provider in configured_providers.iter() {
+        let http_client = http_client.clone();
+        match provider.clone() {
+            WebSearchProviderContent::Searxng { url, max_results } => registry.register_provider(
+                searxng::SearxngWebSearchProvider::new(http_client, url, max_results),
+                cx,
+            ),
+            WebSearchProviderContent::Brave {
+                api_url,
+                api_key_env_var,
+                max_results,
+            } => registry.register_provider(
+                brave::BraveWebSearchProvider::new(
+                    http_client,
+                    api_url,
+                    api_key_env_var,
+                    max_results,
+                ),
+                cx,
+            ),
+            WebSearchProviderContent::Json {
+                id,
+                url,
+                headers,
+                api_key_env_var,
+                response,
+                max_results,
+            } => registry.register_provider(
+                json::JsonWebSearchProvider::new(
+                    WebSearchProviderId(id.into()),
+                    http_client,
+                    url,
+                    headers,
+                    api_key_env_var,
+                    response,
+                    max_results,
+                ),
+                cx,
+            ),
+        }
+    }
+    update_active_provider(registry, cx);
+}
+
+/// Activates the provider chosen in the settings, falling back to the first
+/// configured provider and then to Zed's.
+fn update_active_provider(registry: &mut WebSearchRegistry, cx: &App) {
+    let settings = WebSearchSettings::get_global(cx);
+    let provider = settings
+        .active_provider
+        .iter()
+        .cloned()
+        .chain(
+            settings
+                .providers
+                .iter()
+                .map(web_search_settings::provider_id),
+        )
+        .chain([WebSearchProviderId(ZED_WEB_SEARCH_PROVIDER_ID.into())])
+        .find_map(|id| registry.provider(&id));
+    if let Some(provider) = provider {
+        registry.set_active_provider(provider);
+    }
+}
+
+async fn send_search_request(
+    http_client: &Arc<dyn HttpClient>,
+    request: http_client::Request<AsyncBody>,
+) -> Result<Vec<u8>> {
+    let mut response = http_client
+        .send(request)
+        .await
+        .context("failed to send web search request")?;
+    let mut body = Vec::new();
+    response.body_mut().read_to_end(&mut body).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "error performing web search.\nStatus: {:?}\nBody: {}",
+        response.status(),
+        String::from_utf8_lossy(&body),
+    );
+    Ok(body)
 }
@@ -0,0 +1,37 @@
+use settings::{Settings, WebSearchProviderContent};
+use web_search::WebSearchProviderId;
+
+use crate::{brave, searxng};
+
+#[derive(Clone, Debug)]
+pub struct WebSearchSettings {
+    /// The id of the provider preferred for web searches.
+    pub active_provider: Option<WebSearchProviderId>,
+    /// The configured providers, in the order they were listed.
+    pub providers: Vec<WebSearchProviderContent>,
+}
+
+impl Settings for WebSearchSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let web_search = content.web_search.as_ref().unwrap();
+        Self {
+            active_provider: web_search
+                .active_provider
+                .clone()
+                .map(|id| WebSearchProviderId(id.into())),
+            providers: web_search.providers.clone().unwrap(),
+        }
+    }
+}
+
+pub fn provider_id(provider: &WebSearchProviderContent) -> WebSearchProviderId {
+    match provider {
+        WebSearchProviderContent::Searxng { .. } => {
+            WebSearchProviderId(searxng::SEARXNG_WEB_SEARCH_PROVIDER_ID.into())
+        }
+        WebSearchProviderContent::Brave { .. } => {
+            WebSearchProviderId(brave::BRAVE_WEB_SEARCH_PROVIDER_ID.into())
+        }
+        WebSearchProviderContent::Json { id, .. } => WebSearchProviderId(id.clone().into()),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::handle_open_request;
 use crate::restorable_workspace_locations;
+use agent_settings::AgentProfileId;
 use anyhow::{Context as _, Result, anyhow};
 use cli::{CliRequest, CliResponse, ipc::IpcSender};
 use cli::{IpcHandshake, ipc};
@@ -17,9 +18,10 @@ use gpui::{App, AsyncApp, Global, WindowHandle};
 use language::Point;
 use onboarding::FIRST_OPEN;
 use onboarding::show_onboarding_view;
+use project::Project;
 use recent_projects::{SshSettings, open_remote_project};
 use remote::{RemoteConnectionOptions, WslConnectionOptions};
-use settings::Settings;
+use settings::{LanguageModelProviderSetting, LanguageModelSelection, Settings};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
@@ -374,10 +376,111 @@ pub async fn handle_cli_connection(
                 let status = if open_workspace_result.is_err() { 1 } else { 0 };
                 responses.send(CliResponse::Exit { status }).log_err();
             }
+            CliRequest::RunAgent {
+                path,
+                prompt,
+                profile,
+                model,
+                env,
+            } => {
+                let result = run_agent(
+                    path,
+                    prompt,
+                    profile,
+                    model,
+                    env,
+                    &responses,
+                    app_state.clone(),
+                    cx,
+                )
+                .await;
+
+                let status = match result {
+                    Ok(()) => 0,
+                    Err(error) => {
+                        send_agent_event(
+                            &responses,
+                            &agent2::HeadlessEvent::Error {
+                                message: format!("{error:#}"),
+                            },
+                        );
+                        1
+                    }
+                };
+                responses.send(CliResponse::Exit { status }).log_err();
+            }
         }
     }
 }
 
+async fn run_agent(
+    path: String,
+    prompt: String,
+    profile: Option<String>,
+    model: Option<String>,
+    env: Option<HashMap<String, String>>,
+    responses: &IpcSender<CliResponse>,
+    app_state: Arc<AppState>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let model = model
+        .map(|model| {
+            let (provider, model) = model
+                .split_once('/')
+                .with_context(|| format!("expected `provider/model`, got `{model}`"))?;
+            anyhow::Ok(LanguageModelSelection {
+                provider: LanguageModelProviderSetting(provider.to_string()),
+                model: model.to_string(),
+            })
+        })
+        .transpose()?;
+
+    let project = cx.update(|cx| {
+        Project::local(
+            app_state.client.clone(),
+            app_state.node_runtime.clone(),
+            app_state.user_store.clone(),
+            app_state.languages.clone(),
+            app_state.fs.clone(),
+            env,
+            cx,
+        )
+    })?;
+    let path = PathBuf::from(path);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(&path, true, cx)
+        })?
+        .await?;
+    let scan_complete = worktree.read_with(cx, |worktree, _| {
+        worktree.as_local().map(|worktree| worktree.scan_complete())
+    })?;
+    if let Some(scan_complete) = scan_complete {
+        scan_complete.await;
+    }
+
+    let responses = responses.clone();
+    agent2::run_headless(
+        project,
+        &path,
+        agent2::HeadlessRunOptions {
+            prompt,
+            profile: profile.map(|profile| AgentProfileId(profile.into())),
+            model,
+        },
+        app_state.fs.clone(),
+        move |event| send_agent_event(&responses, &event),
+        cx,
+    )
+    .await
+}
+
+fn send_agent_event(responses: &IpcSender<CliResponse>, event: &agent2::HeadlessEvent) {
+    if let Some(message) = serde_json::to_string(event).log_err() {
+        responses.send(CliResponse::Stdout { message }).log_err();
+    }
+}
+
 async fn open_workspaces(
     paths: Vec<String>,
     diff_paths: Vec<[String; 2]>,
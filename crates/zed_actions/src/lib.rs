@@ -169,7 +169,11 @@ pub mod workspace {
 }
 
 pub mod git {
-    use gpui::actions;
+    use std::path::PathBuf;
+
+    use gpui::{Action, actions};
+    use schemars::JsonSchema;
+    use serde::Deserialize;
 
     actions!(
         git,
@@ -184,9 +188,21 @@ pub mod git {
             #[action(deprecated_aliases = ["branches::OpenRecent"])]
             Branch,
             /// Opens the git stash selector.
-            ViewStash
+            ViewStash,
+            /// Opens the git tag selector.
+            ViewTags
         ]
     );
+
+    /// Opens a worktree of the active repository in a new window.
+    #[derive(PartialEq, Clone, Deserialize, Default, JsonSchema, Action)]
+    #[action(namespace = git)]
+    #[serde(deny_unknown_fields)]
+    pub struct OpenWorktree {
+        /// The worktree to open. If not set, a picker is shown to choose one.
+        #[serde(default)]
+        pub path: Option<PathBuf>,
+    }
 }
 
 pub mod toast {